@@ -1,8 +1,16 @@
 import RESTAdapter from '@ember-data/adapter/rest';
 
+import { csrfHeaders } from '../utils/csrf';
+
 export default class ApplicationAdapter extends RESTAdapter {
   namespace = 'api/v1';
 
+  ajaxOptions(url, type, options) {
+    let hash = super.ajaxOptions(url, type, options);
+    hash.headers = { ...hash.headers, ...csrfHeaders(type) };
+    return hash;
+  }
+
   isInvalid() {
     // HTTP 422 errors are causing all sorts of issues within Ember Data,
     // so we disable their special case handling here, since we don't need/want it.
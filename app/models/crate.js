@@ -27,6 +27,18 @@ export default class Crate extends Model {
   @attr homepage;
   @attr documentation;
   @attr repository;
+  /**
+   * The time at which an owner proved that they have push access to the
+   * `repository`, or `undefined` if it has not been verified.
+   * @type {Date | undefined}
+   */
+  @attr('date') repository_verified_at;
+  /**
+   * Links to the funding pages of the crate, only included when the crate is
+   * loaded individually.
+   * @type {{ platform: string, url: string }[] | undefined}
+   */
+  @attr funding_links;
 
   @hasMany('version', { async: true, inverse: 'crate' }) versions;
   @hasMany('team', { async: true, inverse: null }) owner_team;
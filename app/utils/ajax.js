@@ -2,12 +2,15 @@ import { runInDebug } from '@ember/debug';
 
 import fetch from 'fetch';
 
+import { csrfHeaders } from './csrf';
+
 export default async function ajax(input, init) {
   let method = init?.method ?? 'GET';
 
   let cause;
   try {
-    let response = await fetch(input, init);
+    let headers = { ...csrfHeaders(method), ...init?.headers };
+    let response = await fetch(input, { ...init, headers });
     if (response.ok) {
       return await response.json();
     }
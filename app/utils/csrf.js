@@ -0,0 +1,21 @@
+const COOKIE_NAME = 'csrf_token';
+const HEADER_NAME = 'X-CSRF-Token';
+const SAFE_METHODS = new Set(['GET', 'HEAD', 'OPTIONS']);
+
+export function csrfToken() {
+  for (let cookie of document.cookie.split(';')) {
+    let [name, ...value] = cookie.trim().split('=');
+    if (name === COOKIE_NAME) {
+      return decodeURIComponent(value.join('='));
+    }
+  }
+}
+
+/**
+ * Returns the headers that cookie-authenticated requests with the given
+ * method need to pass the CSRF protection of the API.
+ */
+export function csrfHeaders(method = 'GET') {
+  let token = csrfToken();
+  return token && !SAFE_METHODS.has(method.toUpperCase()) ? { [HEADER_NAME]: token } : {};
+}
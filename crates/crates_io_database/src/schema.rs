@@ -17,6 +17,50 @@ pub mod sql_types {
     pub use diesel_full_text_search::Tsvector;
 }
 
+diesel::table! {
+    /// Data export archives requested by users
+    account_exports (id) {
+        /// Unique identifier of the `account_exports` row
+        id -> Int4,
+        /// ID of the user whose data is exported
+        user_id -> Int4,
+        /// Secret token used in the download URL of the export
+        token -> Text,
+        /// Date and time when the export was requested
+        created_at -> Timestamptz,
+        /// Date and time when the export archive was generated, or NULL if it is still pending
+        completed_at -> Nullable<Timestamptz>,
+        /// Date and time after which the export archive can no longer be downloaded
+        expires_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    /// Announcements that are shown in a banner on the crates.io website, managed by the crates.io team
+    announcements (id) {
+        /// Unique identifier of the `announcements` row
+        id -> Int4,
+        /// The announcement that is shown to users
+        message -> Text,
+        /// URL of a page with more information about the announcement
+        docs_url -> Nullable<Text>,
+        /// Severity of the announcement, see `AnnouncementSeverity` for the possible values
+        severity -> Int4,
+        /// Users that the announcement is shown to, see `AnnouncementAudience` for the possible values
+        audience -> Int4,
+        /// Date and time from which on the announcement is shown
+        starts_at -> Timestamptz,
+        /// Date and time when the announcement stops being shown, or `NULL` if it is shown until it is deleted
+        ends_at -> Nullable<Timestamptz>,
+        /// ID of the admin that created the announcement
+        created_by -> Nullable<Int4>,
+        /// Date and time when the announcement was created
+        created_at -> Timestamptz,
+        /// Date and time when the announcement was last changed
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     /// Representation of the `api_tokens` table.
     ///
@@ -76,6 +120,56 @@ diesel::table! {
         expired_at -> Nullable<Timestamp>,
         /// timestamp of when the user was informed about their token's impending expiration
         expiry_notification_at -> Nullable<Timestamp>,
+        /// ID of the organization that owns the token, which restricts the token to crates owned by the organization
+        organization_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    /// Number of API requests per crate and day, which are counted in memory by the API servers and added to this table periodically
+    api_usage_by_crate (crate_id, date, kind) {
+        /// ID of the crate that the requests were about
+        crate_id -> Int4,
+        /// The day on which the requests were sent
+        date -> Date,
+        /// The kind of the requests, either `download` for downloads through the API, or `metadata` for the other read requests of the crate
+        kind -> Text,
+        /// Number of requests on this day
+        count -> Int8,
+    }
+}
+
+diesel::table! {
+    /// Number of authenticated API requests per user and day, which are counted in memory by the API servers and added to this table periodically
+    api_usage_by_user (user_id, date, kind) {
+        /// ID of the user that sent the requests
+        user_id -> Int4,
+        /// The day on which the requests were sent
+        date -> Date,
+        /// How the requests were authenticated, either `cookie` or `token`
+        kind -> Text,
+        /// Number of requests on this day
+        count -> Int8,
+    }
+}
+
+diesel::table! {
+    /// Record of all mutating operations on crates and user accounts
+    audit_log (id) {
+        /// Unique identifier of the `audit_log` row
+        id -> Int8,
+        /// ID of the user that performed the operation
+        user_id -> Nullable<Int4>,
+        /// ID of the API token that was used to perform the operation, or NULL if a session cookie was used
+        api_token_id -> Nullable<Int4>,
+        /// ID of the crate that was affected by the operation, or NULL for account operations
+        crate_id -> Nullable<Int4>,
+        /// The kind of operation, see `AuditLogAction` for the possible values
+        action -> Int4,
+        /// Additional information about the operation, like the affected version or owner
+        details -> Jsonb,
+        /// Date and time when the operation was performed
+        created_at -> Timestamptz,
     }
 }
 
@@ -126,6 +220,26 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         priority -> Int2,
+        /// Information about the request or job that enqueued this job, e.g. its Sentry trace, which is attached to the error reports of this job.
+        enqueue_context -> Nullable<Jsonb>,
+    }
+}
+
+diesel::table! {
+    /// Warnings that are returned to cargo for every publish, managed by the crates.io team
+    broadcast_warnings (id) {
+        /// Unique identifier of the `broadcast_warnings` row
+        id -> Int4,
+        /// The warning that is shown to users
+        message -> Text,
+        /// URL of a page with more information about the warning
+        docs_url -> Nullable<Text>,
+        /// Date and time when the warning stops being returned, or `NULL` if it is returned until it is deleted
+        expires_at -> Nullable<Timestamptz>,
+        /// ID of the admin that created the warning
+        created_by -> Nullable<Int4>,
+        /// Date and time when the warning was created
+        created_at -> Timestamptz,
     }
 }
 
@@ -176,6 +290,76 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Pre-aggregated daily downloads of all crates in a category, maintained by the `UpdateAggregateDownloads` background job
+    category_downloads (category_id, date) {
+        /// Reference to the category
+        category_id -> Int4,
+        /// The day that the downloads are for
+        date -> Date,
+        /// Number of downloads of all crates in the category on this day
+        downloads -> Int8,
+    }
+}
+
+diesel::table! {
+    /// Categories that are suggested to the owners of crates without categories, maintained by the `SuggestCategories` background job
+    category_suggestions (crate_id, category_id) {
+        /// The crate that the category is suggested for
+        crate_id -> Int4,
+        /// The suggested category
+        category_id -> Int4,
+        /// How well the description and keywords of the crate match the category, higher is better
+        score -> Float4,
+        /// Time at which the suggestion was made
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Paths waiting to be invalidated on the CDNs, which are processed in batches by the `ProcessCdnInvalidationQueue` background job
+    cdn_invalidations (cdn, path) {
+        /// The CDN that the path has to be invalidated on (0 = CloudFront, 1 = Fastly)
+        cdn -> Int4,
+        /// The path to invalidate
+        path -> Text,
+        /// Date and time when the path was first queued for invalidation
+        queued_at -> Timestamptz,
+        /// Date and time when the path was last queued for invalidation. Paths that are queued again while they are being invalidated stay in the queue.
+        last_queued_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Feed of all crate and version mutations, used by trusted services like docs.rs and crater. The rows are inserted by triggers on the `crates` and `versions` tables.
+    crate_changes (seq) {
+        /// Sequence number of the change. Sequence numbers become visible in increasing order, so consumers can continue after the last change they have seen.
+        seq -> Int8,
+        /// The kind of change, see `CrateChangeKind` for the possible values
+        kind -> Int4,
+        /// ID of the affected crate. There is no foreign key, so that changes of deleted crates are kept.
+        crate_id -> Int4,
+        /// Name of the affected crate
+        crate_name -> Text,
+        /// Number of the affected version, or `NULL` for changes of the crate itself
+        version -> Nullable<Text>,
+        /// Date and time when the change was made
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Number of crates that depend on a crate, maintained by the `UpdateCrateDependentStats` background job. Crates without dependents have no row.
+    crate_dependent_stats (crate_id) {
+        /// Reference to the crate
+        crate_id -> Int4,
+        /// Number of distinct crates whose default version depends on the crate, excluding yanked crates
+        dependents -> Int4,
+        /// Sum of the downloads of the last 90 days of all dependent crates
+        weighted_dependents -> Int8,
+    }
+}
+
 diesel::table! {
     /// Number of downloads per crate. This was extracted from the `crates` table for performance reasons.
     crate_downloads (crate_id) {
@@ -186,6 +370,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Funding links of a crate, taken from the manifest or the `FUNDING.yml` file of the most recently published version
+    crate_funding_links (crate_id, position) {
+        /// ID of the crate
+        crate_id -> Int4,
+        /// Position of the link in the list of links of the crate, starting at 0
+        position -> Int2,
+        /// The funding platform of the link, see `FundingPlatform` for the possible values
+        platform -> Int4,
+        /// URL of the funding page on the platform
+        url -> Text,
+    }
+}
+
 diesel::table! {
     /// Representation of the `crate_owner_invitations` table.
     ///
@@ -227,6 +425,8 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         token_generated_at -> Nullable<Timestamp>,
+        /// The `crate_owners.role` that the invited user will have once the invitation is accepted
+        role -> Int4,
     }
 }
 
@@ -275,6 +475,146 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         email_notifications -> Bool,
+        /// `role = 0` allows publishing and managing owners and settings of the crate, `role = 1` only allows publishing new versions. Only applies to users.
+        role -> Int4,
+    }
+}
+
+diesel::table! {
+    /// Requests to take over the name of an unused crate, which are decided by the crates.io team after a waiting period
+    crate_reclaim_requests (id) {
+        /// Unique identifier of the `crate_reclaim_requests` row
+        id -> Int4,
+        /// ID of the crate whose name is requested
+        crate_id -> Int4,
+        /// ID of the user that wants to become the owner of the crate
+        requester_id -> Int4,
+        /// Explanation of why the crate is unused and what the requester plans to do with the name
+        reason -> Text,
+        /// Status of the request, see `ReclaimStatus` for the possible values
+        status -> Int4,
+        /// Explanation of the decision, which is sent to the requester
+        decision -> Nullable<Text>,
+        /// ID of the admin that approved or rejected the request
+        decided_by -> Nullable<Int4>,
+        /// Date and time when the request was submitted
+        created_at -> Timestamptz,
+        /// Date and time when the request was approved or rejected
+        decided_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    /// Redirects from the name of a renamed or superseded crate to its successor, which are requested by the owners and approved by the crates.io team
+    crate_redirects (id) {
+        /// Unique identifier of the `crate_redirects` row
+        id -> Int4,
+        /// Name of the renamed crate, which is blocked from being registered again once the redirect is approved
+        name -> Text,
+        /// ID of the crate that the requests are redirected to
+        target_crate_id -> Int4,
+        /// ID of the owner that requested the redirect
+        requested_by -> Nullable<Int4>,
+        /// Explanation of why the crate has been renamed or superseded
+        reason -> Text,
+        /// Status of the redirect, see `RedirectStatus` for the possible values
+        status -> Int4,
+        /// Explanation of the decision, which is sent to the requester
+        decision -> Nullable<Text>,
+        /// ID of the admin that approved or rejected the redirect
+        decided_by -> Nullable<Int4>,
+        /// Date and time when the redirect was requested
+        created_at -> Timestamptz,
+        /// Date and time when the redirect was approved or rejected
+        decided_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    /// Reports of malicious or abusive crates, submitted by users for triage by the crates.io team
+    crate_reports (id) {
+        /// Unique identifier of the `crate_reports` row
+        id -> Int4,
+        /// ID of the reported crate
+        crate_id -> Int4,
+        /// ID of the user that submitted the report
+        reporter_id -> Nullable<Int4>,
+        /// The kind of abuse, see `ReportCategory` for the possible values
+        category -> Int4,
+        /// Description of the problem, provided by the reporter
+        description -> Text,
+        /// Optional email address under which the reporter can be contacted about the report
+        contact -> Nullable<Varchar>,
+        /// Triage status of the report, see `ReportStatus` for the possible values
+        status -> Int4,
+        /// Explanation of how the report was resolved, which is sent to the reporter
+        resolution -> Nullable<Text>,
+        /// ID of the admin that resolved the report
+        resolved_by -> Nullable<Int4>,
+        /// Date and time when the report was submitted
+        created_at -> Timestamptz,
+        /// Date and time when the report was resolved or dismissed
+        resolved_at -> Nullable<Timestamptz>,
+        /// Severity score assigned by the automated malware scan, `NULL` for reports submitted by users
+        score -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    /// Quality signals of each crate, maintained by the `UpdateCrateScores` background job.
+    crate_scores (crate_id) {
+        /// Reference to the crate
+        crate_id -> Int4,
+        /// Whether the default version was built successfully on docs.rs, or the crate has a documentation URL
+        has_documentation -> Bool,
+        /// Whether the crate has a repository URL
+        has_repository -> Bool,
+        /// Whether the crate has a non-empty README
+        has_readme -> Bool,
+        /// Whether a version that is not yanked was published in the last 6 months
+        recent_release -> Bool,
+        /// Whether a version that is not yanked was published in the last 2 years
+        maintained -> Bool,
+        /// Share of the versions of the crate that are yanked, between 0 and 1
+        yanked_ratio -> Float8,
+        /// Composite score between 0 and 1, the average of the other signals with the yanked ratio inverted
+        score -> Float8,
+        /// Time when the signals were last calculated
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Settings of a crate that can be changed by its owners. Crates without a row use the default values of the columns
+    crate_settings (crate_id) {
+        /// ID of the crate
+        crate_id -> Int4,
+        /// Whether the crate is included in the weekly digest emails of its owners
+        digest_emails -> Bool,
+        /// Whether the files of new versions are compared to the previous release by the `ComputeVersionDiff` background job
+        version_diffs -> Bool,
+        /// Whether the crate is an internal or test crate, which is excluded from the default crate search
+        internal -> Bool,
+        /// Date and time when the settings were last changed
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Requests to transfer the ownership of a crate to a single new owner
+    crate_transfers (id) {
+        /// Unique identifier of the `crate_transfers` row
+        id -> Int4,
+        /// ID of the crate that is transferred
+        crate_id -> Int4,
+        /// ID of the crate owner that requested the transfer
+        requested_by -> Int4,
+        /// ID of the user that will become the only owner of the crate
+        recipient_id -> Int4,
+        /// Date and time when the transfer was requested
+        created_at -> Timestamptz,
+        /// Date and time when the recipient accepted the transfer, or NULL if it is still pending
+        accepted_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -358,6 +698,14 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         max_features -> Nullable<Int2>,
+        /// Time at which the crate was hidden from search and listings by the crates.io team, or `NULL` if the crate is visible
+        hidden_at -> Nullable<Timestamptz>,
+        /// Time at which the crate was frozen by the crates.io team, or `NULL` if new versions can be published
+        frozen_at -> Nullable<Timestamptz>,
+        /// Time at which the crate was quarantined pending review by the crates.io team, or `NULL` if the crate is not quarantined
+        quarantined_at -> Nullable<Timestamptz>,
+        /// Time at which an owner of the crate proved that they have push access to the `repository`, or `NULL` if the repository has not been verified
+        repository_verified_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -401,6 +749,26 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Database dump archives that are available for download
+    db_dumps (id) {
+        /// Unique identifier of the `db_dumps` row
+        id -> Int4,
+        /// Whether this is a full or an incremental dump, see `DbDumpKind` for the possible values
+        kind -> Int4,
+        /// Path of the archive in the storage bucket
+        path -> Varchar,
+        /// Size of the archive in bytes
+        size -> Int8,
+        /// Hex-encoded SHA256 checksum of the archive
+        checksum -> Varchar,
+        /// Time of the previous dump, for incremental dumps that only contain the rows that changed since then
+        since -> Nullable<Timestamptz>,
+        /// Time at which the dump was started
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     /// A mapping from crates to the versions that the frontend will display by default.
     default_versions (crate_id) {
@@ -408,6 +776,10 @@ diesel::table! {
         crate_id -> Int4,
         /// Reference to the version in the `versions` table.
         version_id -> Int4,
+        /// Reference to the highest version in the `versions` table that is neither yanked nor a pre-release, if there is one.
+        latest_stable_version_id -> Nullable<Int4>,
+        /// Reference to the highest pre-release version in the `versions` table that is not yanked, if there is one.
+        latest_prerelease_version_id -> Nullable<Int4>,
     }
 }
 
@@ -539,7 +911,60 @@ diesel::table! {
         /// Its SQL type is `Nullable<Timestamp>`.
         ///
         /// (Automatically generated by Diesel.)
-        token_generated_at -> Nullable<Timestamp>,
+        token_generated_at -> Nullable<Timestamp>,        /// Whether this is the primary email address of the user, which is used for notifications
+        is_primary -> Bool,
+    }
+}
+
+diesel::table! {
+    /// Per-user overrides of feature flags, which take precedence over the rollout of the feature
+    feature_overrides (feature, user_id) {
+        /// Name of the overridden feature
+        feature -> Varchar,
+        /// ID of the user for which the feature is overridden
+        user_id -> Int4,
+        /// Whether the feature is switched on or off for the user
+        enabled -> Bool,
+        /// Date and time when the override was added
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Crates that the crates.io team picked to be highlighted on the front page for a limited time
+    featured_crates (id) {
+        /// Unique identifier of the `featured_crates` row
+        id -> Int4,
+        /// ID of the highlighted crate
+        crate_id -> Int4,
+        /// Date and time when the crate starts being highlighted
+        starts_at -> Timestamptz,
+        /// Date and time when the crate stops being highlighted, or `NULL` if it is highlighted until the row is deleted
+        ends_at -> Nullable<Timestamptz>,
+        /// Internal explanation of why the crate was picked
+        note -> Nullable<Text>,
+        /// ID of the admin that picked the crate
+        created_by -> Nullable<Int4>,
+        /// Date and time when the crate was picked
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Feature flags that switch application features on or off at runtime, either for all users or gradually for a percentage of them
+    features (name) {
+        /// Unique name of the feature, e.g. `read_only_mode`
+        name -> Varchar,
+        /// Whether the feature is switched on for all users, regardless of the rollout percentage
+        enabled -> Bool,
+        /// ID of the admin that changed the feature most recently
+        updated_by -> Nullable<Int4>,
+        /// Date and time when the feature was changed most recently
+        updated_at -> Timestamptz,
+        /// Explanation of the behavior that the feature changes
+        description -> Nullable<Text>,
+        /// Percentage of users for which the feature is switched on, if it is not enabled for all users
+        rollout_percentage -> Int2,
     }
 }
 
@@ -560,6 +985,94 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         crate_id -> Int4,
+        /// Whether the user wants to receive emails when the crate publishes or yanks a version
+        notify_email -> Bool,
+        /// URL that is notified when the crate publishes or yanks a version
+        webhook_url -> Nullable<Varchar>,
+        /// Date and time up to which the user has been notified about new versions and yanks of the crate
+        notified_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Cached responses of the GitHub API, which are refreshed by the `RefreshGitHubCache` background job
+    github_cache (key) {
+        /// The serialized `GitHubRequest` that the response belongs to
+        key -> Text,
+        /// The response of the GitHub API, or `null` if the requested resource was not found
+        value -> Jsonb,
+        /// ID of the user whose GitHub token was used to fetch the response, and is used to refresh it
+        user_id -> Int4,
+        /// Date and time when the response was fetched from GitHub
+        fetched_at -> Timestamptz,
+        /// Date and time after which the response has to be fetched again. Expired responses are only used while GitHub is unavailable.
+        expires_at -> Timestamptz,
+        /// Date and time when the response was last used. Responses that have not been used for a while are no longer refreshed and eventually deleted.
+        last_used_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Permanent rules that exempt IP networks from the abuse throttling, or deny them access to the API
+    ip_access_rules (id) {
+        /// Unique identifier of the `ip_access_rules` row
+        id -> Int4,
+        /// The IP network in CIDR notation, e.g. `192.0.2.0/24`
+        network -> Varchar,
+        /// Whether the network is allowed or denied, see `IpAccessRuleKind` for the possible values
+        kind -> Int4,
+        /// Explanation of why the rule was added
+        reason -> Nullable<Text>,
+        /// ID of the admin that added the rule
+        created_by -> Nullable<Int4>,
+        /// Date and time when the rule was added
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Temporary bans of IP networks that were detected by the abuse throttling
+    ip_bans (id) {
+        /// Unique identifier of the `ip_bans` row
+        id -> Int4,
+        /// The IP network in CIDR notation, e.g. `192.0.2.0/24`
+        network -> Varchar,
+        /// Number of times the network has been banned, which determines the duration of the next ban
+        strikes -> Int4,
+        /// Explanation of why the network was banned most recently
+        reason -> Text,
+        /// Date and time when the current ban expires
+        banned_until -> Timestamptz,
+        /// Date and time when the network was banned for the first time
+        created_at -> Timestamptz,
+        /// Date and time when the network was banned most recently
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Alternative names of keywords, e.g. for keywords that were merged into another keyword
+    keyword_aliases (alias) {
+        /// The lowercase alternative name, which must not be used by a keyword
+        alias -> Text,
+        /// ID of the keyword that the alias redirects to
+        keyword_id -> Int4,
+        /// ID of the admin that created the alias
+        created_by -> Nullable<Int4>,
+        /// Date and time when the alias was created
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Pre-aggregated daily downloads of all crates with a keyword, maintained by the `UpdateAggregateDownloads` background job
+    keyword_downloads (keyword_id, date) {
+        /// Reference to the keyword
+        keyword_id -> Int4,
+        /// The day that the downloads are for
+        date -> Date,
+        /// Number of downloads of all crates with the keyword on this day
+        downloads -> Int8,
     }
 }
 
@@ -595,6 +1108,26 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Accounts of additional OAuth identity providers that can be used to log in to a crates.io account
+    linked_identities (id) {
+        /// Unique identifier of the linked identity
+        id -> Int4,
+        /// The crates.io user that this identity is linked to
+        user_id -> Int4,
+        /// Name of the identity provider (e.g. `gitlab` or `google`)
+        provider -> Text,
+        /// Unique, stable account ID of the user at the identity provider
+        account_id -> Text,
+        /// Username or display name of the account at the time it was linked
+        login -> Text,
+        /// Email address of the account at the time it was linked, if available
+        email -> Nullable<Text>,
+        /// Date and time when the identity was linked
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     /// Representation of the `metadata` table.
     ///
@@ -606,6 +1139,36 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         total_downloads -> Int8,
+        /// The oldest date whose downloads are included in the `recent_crate_downloads` table. Moved forward by the `UpdateRecentCrateDownloads` background job.
+        recent_downloads_start -> Date,
+    }
+}
+
+diesel::table! {
+    /// Members of organizations and their roles
+    organization_members (organization_id, user_id) {
+        /// ID of the organization
+        organization_id -> Int4,
+        /// ID of the member
+        user_id -> Int4,
+        /// Role of the member: 0 = admin, 1 = publisher, 2 = viewer
+        role -> Int4,
+        /// Date and time when the user joined the organization
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Organizations that can own crates independently of GitHub teams
+    organizations (id) {
+        /// Unique identifier of the organization
+        id -> Int4,
+        /// Unique name of the organization, used as `org:<login>` when adding it as a crate owner
+        login -> Text,
+        /// Human-readable display name of the organization
+        name -> Nullable<Text>,
+        /// Date and time when the organization was created
+        created_at -> Timestamptz,
     }
 }
 
@@ -619,6 +1182,26 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Results of publish requests with an `Idempotency-Key` header, so that retried requests return the original result. Rows are kept for 24 hours.
+    publish_idempotency_keys (user_id, key) {
+        /// Reference to the user that sent the publish request
+        user_id -> Int4,
+        /// The value of the `Idempotency-Key` header, which is unique per user
+        key -> Text,
+        /// Name of the published crate
+        crate_name -> Text,
+        /// Number of the published version
+        version -> Text,
+        /// SHA256 checksum of the published `.crate` file, to detect reuse of the key for a different request
+        checksum -> Text,
+        /// The JSON response of the original publish request
+        response -> Jsonb,
+        /// Date and time when the version was published
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     /// Representation of the `publish_limit_buckets` table.
     ///
@@ -651,6 +1234,28 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Rules that all crates have to follow to be published, managed by the crates.io team
+    publish_policy_rules (id) {
+        /// Unique identifier of the `publish_policy_rules` row
+        id -> Int4,
+        /// The kind of the rule: 0 = maximum number of dependencies, 1 = maximum number of features, 2 = banned license, 3 = required manifest field
+        kind -> Int4,
+        /// The parameter of the rule, e.g. the maximum number, the SPDX identifier of the banned license or the name of the required field
+        value -> Text,
+        /// Additional explanation that is shown to users when their crate violates the rule
+        message -> Nullable<Text>,
+        /// ID of the admin that created the rule
+        created_by -> Nullable<Int4>,
+        /// Date and time when the rule was created
+        created_at -> Timestamptz,
+        /// Date and time when the rule was last changed
+        updated_at -> Timestamptz,
+        /// Date and time from which on the rule is enforced. Until then, crates that violate the rule are published with a warning. `NULL` means that the rule is enforced immediately.
+        enforced_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     /// Representation of the `publish_rate_overrides` table.
     ///
@@ -700,38 +1305,94 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         rendered_at -> Timestamp,
+        /// Hex-encoded SHA256 checksum of the rendered readme, which is stored under this checksum so that identical readmes of different versions are only stored once. `NULL` for readmes that were stored per version before.
+        checksum -> Nullable<Varchar>,
     }
 }
 
 diesel::table! {
-    /// Representation of the `recent_crate_downloads` view.
-    ///
-    /// This data represents the downloads in the last 90 days.
-    /// This view does not contain realtime data.
-    /// It is refreshed by the `update-downloads` script.
+    /// Number of downloads per crate in the last 90 days. Incremented by the `update_downloads` job and decremented by the `UpdateRecentCrateDownloads` job once a day leaves the window.
     recent_crate_downloads (crate_id) {
-        /// The `crate_id` column of the `recent_crate_downloads` view.
-        ///
-        /// Its SQL type is `Integer`.
-        crate_id -> Integer,
-        /// The `downloads` column of the `recent_crate_downloads` table.
-        ///
-        /// Its SQL type is `BigInt`.
-        downloads -> BigInt,
+        /// Reference to the crate
+        crate_id -> Int4,
+        /// Number of counted downloads of the crate since `metadata.recent_downloads_start`
+        downloads -> Int8,
     }
 }
 
 diesel::table! {
-    /// Representation of the `reserved_crate_names` table.
-    ///
-    /// (Automatically generated by Diesel.)
+    /// Pre-aggregated daily statistics of the whole registry, maintained by the `UpdateRegistryStats` background job
+    registry_stats (date) {
+        /// The day that the statistics are for
+        date -> Date,
+        /// Number of crates that existed at the end of the day
+        total_crates -> Int8,
+        /// Number of versions that existed at the end of the day
+        total_versions -> Int8,
+        /// Number of versions that were published on this day
+        publishes -> Int8,
+        /// Number of downloads of all versions on this day
+        downloads -> Int8,
+    }
+}
+
+diesel::table! {
+    /// Crate names that can not be used for new crates, e.g. names of the standard library crates or names that are reserved on Windows
     reserved_crate_names (name) {
-        /// The `name` column of the `reserved_crate_names` table.
-        ///
-        /// Its SQL type is `Text`.
-        ///
-        /// (Automatically generated by Diesel.)
+        /// The reserved crate name, which is compared to other crate names after canonicalization
         name -> Text,
+        /// Why the name is reserved, for the crates.io team
+        reason -> Nullable<Text>,
+        /// ID of the admin that reserved the name, or `NULL` for names that were reserved by migrations
+        created_by -> Nullable<Int4>,
+        /// Date and time when the name was reserved
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Crate name prefixes that organizations requested to reserve, so that only their members can publish new crates with these prefixes once approved by an admin
+    reserved_prefixes (id) {
+        /// Unique identifier of the `reserved_prefixes` row
+        id -> Int4,
+        /// ID of the organization that reserved the prefix
+        organization_id -> Int4,
+        /// The lowercase crate name prefix, ending with `-` or `_`, e.g. `aws-sdk-`
+        prefix -> Text,
+        /// ID of the organization admin that requested the reservation
+        requested_by -> Nullable<Int4>,
+        /// ID of the crates.io admin that approved the reservation
+        approved_by -> Nullable<Int4>,
+        /// Date and time when the reservation was approved, or `NULL` if it is still pending
+        approved_at -> Nullable<Timestamptz>,
+        /// Date and time when the reservation was requested
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Emails that were sent with an idempotency key, to avoid sending the same notification more than once
+    sent_emails (idempotency_key) {
+        /// SHA-256 hash of the idempotency key, so that keys derived from secret tokens are not stored in plain text
+        idempotency_key -> Varchar,
+        /// ID of the message, as reported by the email provider
+        message_id -> Nullable<Varchar>,
+        /// Date and time when the email was sent
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Files in the storage that don't match the database, as found by the `ReconcileStorage` background job
+    storage_discrepancies (kind, path) {
+        /// The kind of discrepancy (0 = orphaned crate file, 1 = missing crate file, 2 = orphaned index file, 3 = missing index file)
+        kind -> Int4,
+        /// The path of the file in the storage
+        path -> Text,
+        /// Date and time when the discrepancy was first found. Orphaned files are only deleted after they have been found for the whole quarantine period.
+        first_seen_at -> Timestamptz,
+        /// Date and time when the discrepancy was last found
+        last_seen_at -> Timestamptz,
     }
 }
 
@@ -767,6 +1428,40 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Crates with the fastest growing number of downloads, maintained by the `UpdateTrendingCrates` background job
+    trending_crates (window_days, crate_id) {
+        /// Number of days that the downloads are compared over, e.g. `7` for the last week compared to the week before
+        window_days -> Int4,
+        /// Reference to the crate
+        crate_id -> Int4,
+        /// Position of the crate in the ranking of the window, starting at 1
+        rank -> Int4,
+        /// Number of downloads of the crate in the last `window_days` complete days
+        downloads -> Int8,
+        /// Number of downloads of the crate in the `window_days` days before that
+        previous_downloads -> Int8,
+        /// Relative growth of the downloads, e.g. `0.5` for 50% more downloads than in the previous window
+        growth -> Float8,
+    }
+}
+
+diesel::table! {
+    /// Contribution stats of the users that opted in to public stats, maintained by the `UpdateUserStats` background job
+    user_stats (user_id) {
+        /// ID of the user that the stats belong to
+        user_id -> Int4,
+        /// Total number of downloads of all crates that the user owns
+        total_downloads -> Int8,
+        /// JSON array of the number of versions that the user published per year
+        publishes_per_year -> Jsonb,
+        /// JSON array of the users that most frequently own crates together with the user, with the number of shared crates
+        co_owners -> Jsonb,
+        /// Date and time when the stats were last computed
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     /// Representation of the `users` table.
     ///
@@ -828,13 +1523,41 @@ diesel::table! {
         is_admin -> Bool,
         /// Whether or not the user wants to receive notifications when a package they own is published
         publish_notifications -> Bool,
+        /// Preferred language of the user for emails, e.g. `de`. English is used if this is not set.
+        locale -> Nullable<Varchar>,
+        /// Whether the user wants to receive a weekly email summarizing the activity of their crates
+        weekly_digest -> Bool,
+        /// Whether the user wants to receive messages from other users about their crates through the `contact_owners` endpoint
+        contact_requests -> Bool,
+        /// Whether the user wants their contribution stats to be shown on their public profile
+        public_stats -> Bool,
     }
 }
 
 diesel::table! {
-    /// Representation of the `version_downloads` table.
-    ///
-    /// (Automatically generated by Diesel.)
+    /// Summary of the changes in the `.crate` file of a version compared to the previous release, maintained by the `ComputeVersionDiff` background job
+    version_diffs (version_id) {
+        /// The version that was compared to the previous release
+        version_id -> Int4,
+        /// The previous release that the version was compared to
+        previous_version_id -> Int4,
+        /// Number of files that only exist in the new version
+        files_added -> Int4,
+        /// Number of files that only exist in the previous release
+        files_removed -> Int4,
+        /// Number of files that exist in both versions, but with different contents
+        files_modified -> Int4,
+        /// Difference of the total unpacked size of all files in bytes
+        size_delta -> Int8,
+        /// JSON array of the added, removed and modified files, with their path, status and size difference
+        files -> Jsonb,
+        /// Time at which the diff was computed
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    /// Number of downloads per version and day, partitioned by month. Months that have been archived are moved to the `version_downloads_archive` table.
     version_downloads (version_id, date) {
         /// The `version_id` column of the `version_downloads` table.
         ///
@@ -869,6 +1592,52 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Monthly partitions of the `version_downloads` table that have been exported to the downloads archive. Their rows no longer change.
+    version_downloads_archive (version_id, date) {
+        /// The `version_id` column of the `version_downloads_archive` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The `downloads` column of the `version_downloads_archive` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        downloads -> Int4,
+        /// The `counted` column of the `version_downloads_archive` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        counted -> Int4,
+        /// The `date` column of the `version_downloads_archive` table.
+        ///
+        /// Its SQL type is `Date`.
+        ///
+        /// (Automatically generated by Diesel.)
+        date -> Date,
+        /// The `processed` column of the `version_downloads_archive` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        processed -> Bool,
+    }
+}
+
+diesel::table! {
+    /// Listing of the files in the `.crate` file of a version, saved when the version is published
+    version_files (version_id) {
+        /// The version that the files belong to
+        version_id -> Int4,
+        /// JSON array of the files in the `.crate` file, with their path relative to the package root and their size in bytes
+        files -> Jsonb,
+    }
+}
+
 diesel::table! {
     /// Representation of the `version_owner_actions` table.
     ///
@@ -1025,6 +1794,18 @@ diesel::table! {
         categories -> Array<Nullable<Text>>,
         /// The list of `keywords` in the `Cargo.toml` file of this version.
         keywords -> Array<Nullable<Text>>,
+        /// Result of the most recent docs.rs build of this version, see `DocsRsStatus` for the possible values, or `NULL` if no build result has been received yet
+        docs_rs_status -> Nullable<Int4>,
+        /// Percentage of documented items reported by the most recent docs.rs build, or `NULL` if not available
+        docs_rs_doc_coverage -> Nullable<Float4>,
+        /// Time at which the most recent docs.rs build result was received
+        docs_rs_built_at -> Nullable<Timestamptz>,
+        /// Key that sorts in the same order as the semver precedence of `num` when compared bytewise, or NULL if `num` is not a valid semver version or the key has not been backfilled yet.
+        semver_ord -> Nullable<Text>,
+        /// How the version was published, see `PublishMethod` for the possible values, or `NULL` if unknown because the version was published before the method was recorded
+        publish_method -> Nullable<Int4>,
+        /// Time at which the version was soft-deleted by the crates.io team, or `NULL` if it has not been deleted. Deleted versions are removed from the index and the API, but their `.crate` files are kept in the quarantine, so that they can be restored.
+        deleted_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -1048,12 +1829,36 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(account_exports -> users (user_id));
+diesel::joinable!(api_tokens -> organizations (organization_id));
+diesel::joinable!(announcements -> users (created_by));
 diesel::joinable!(api_tokens -> users (user_id));
+diesel::joinable!(api_usage_by_crate -> crates (crate_id));
+diesel::joinable!(api_usage_by_user -> users (user_id));
+diesel::joinable!(audit_log -> api_tokens (api_token_id));
+diesel::joinable!(audit_log -> crates (crate_id));
+diesel::joinable!(audit_log -> users (user_id));
+diesel::joinable!(broadcast_warnings -> users (created_by));
+diesel::joinable!(category_downloads -> categories (category_id));
+diesel::joinable!(category_suggestions -> categories (category_id));
+diesel::joinable!(category_suggestions -> crates (crate_id));
+diesel::joinable!(crate_dependent_stats -> crates (crate_id));
 diesel::joinable!(crate_downloads -> crates (crate_id));
+diesel::joinable!(crate_funding_links -> crates (crate_id));
 diesel::joinable!(crate_owner_invitations -> crates (crate_id));
 diesel::joinable!(crate_owners -> crates (crate_id));
+diesel::joinable!(crate_owners -> organizations (owner_id));
 diesel::joinable!(crate_owners -> teams (owner_id));
 diesel::joinable!(crate_owners -> users (owner_id));
+diesel::joinable!(crate_reclaim_requests -> crates (crate_id));
+diesel::joinable!(crate_reclaim_requests -> users (requester_id));
+diesel::joinable!(crate_redirects -> crates (target_crate_id));
+diesel::joinable!(crate_redirects -> users (requested_by));
+diesel::joinable!(crate_reports -> crates (crate_id));
+diesel::joinable!(crate_reports -> users (reporter_id));
+diesel::joinable!(crate_scores -> crates (crate_id));
+diesel::joinable!(crate_settings -> crates (crate_id));
+diesel::joinable!(crate_transfers -> crates (crate_id));
 diesel::joinable!(crates_categories -> categories (category_id));
 diesel::joinable!(crates_categories -> crates (crate_id));
 diesel::joinable!(crates_keywords -> crates (crate_id));
@@ -1064,13 +1869,35 @@ diesel::joinable!(deleted_crates -> users (deleted_by));
 diesel::joinable!(dependencies -> crates (crate_id));
 diesel::joinable!(dependencies -> versions (version_id));
 diesel::joinable!(emails -> users (user_id));
+diesel::joinable!(feature_overrides -> features (feature));
+diesel::joinable!(feature_overrides -> users (user_id));
+diesel::joinable!(featured_crates -> crates (crate_id));
+diesel::joinable!(featured_crates -> users (created_by));
+diesel::joinable!(features -> users (updated_by));
 diesel::joinable!(follows -> crates (crate_id));
 diesel::joinable!(follows -> users (user_id));
+diesel::joinable!(github_cache -> users (user_id));
+diesel::joinable!(ip_access_rules -> users (created_by));
+diesel::joinable!(keyword_aliases -> keywords (keyword_id));
+diesel::joinable!(keyword_downloads -> keywords (keyword_id));
+diesel::joinable!(keyword_aliases -> users (created_by));
+diesel::joinable!(linked_identities -> users (user_id));
+diesel::joinable!(organization_members -> organizations (organization_id));
+diesel::joinable!(organization_members -> users (user_id));
+diesel::joinable!(publish_idempotency_keys -> users (user_id));
 diesel::joinable!(publish_limit_buckets -> users (user_id));
+diesel::joinable!(publish_policy_rules -> users (created_by));
 diesel::joinable!(publish_rate_overrides -> users (user_id));
 diesel::joinable!(readme_renderings -> versions (version_id));
 diesel::joinable!(recent_crate_downloads -> crates (crate_id));
+diesel::joinable!(reserved_crate_names -> users (created_by));
+diesel::joinable!(reserved_prefixes -> organizations (organization_id));
+diesel::joinable!(trending_crates -> crates (crate_id));
+diesel::joinable!(user_stats -> users (user_id));
+diesel::joinable!(version_diffs -> versions (version_id));
 diesel::joinable!(version_downloads -> versions (version_id));
+diesel::joinable!(version_downloads_archive -> versions (version_id));
+diesel::joinable!(version_files -> versions (version_id));
 diesel::joinable!(version_owner_actions -> api_tokens (api_token_id));
 diesel::joinable!(version_owner_actions -> users (user_id));
 diesel::joinable!(version_owner_actions -> versions (version_id));
@@ -1079,31 +1906,72 @@ diesel::joinable!(versions -> users (published_by));
 diesel::joinable!(versions_published_by -> versions (version_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    account_exports,
+    announcements,
     api_tokens,
+    api_usage_by_crate,
+    api_usage_by_user,
+    audit_log,
     background_jobs,
+    broadcast_warnings,
     categories,
+    category_downloads,
+    category_suggestions,
+    cdn_invalidations,
+    crate_changes,
+    crate_dependent_stats,
     crate_downloads,
+    crate_funding_links,
     crate_owner_invitations,
     crate_owners,
+    crate_reclaim_requests,
+    crate_redirects,
+    crate_reports,
+    crate_scores,
+    crate_settings,
+    crate_transfers,
     crates,
     crates_categories,
     crates_keywords,
+    db_dumps,
     default_versions,
     deleted_crates,
     dependencies,
     emails,
+    feature_overrides,
+    featured_crates,
+    features,
     follows,
+    github_cache,
+    ip_access_rules,
+    ip_bans,
+    keyword_aliases,
+    keyword_downloads,
     keywords,
+    linked_identities,
     metadata,
+    organization_members,
+    organizations,
     processed_log_files,
+    publish_idempotency_keys,
     publish_limit_buckets,
+    publish_policy_rules,
     publish_rate_overrides,
     readme_renderings,
     recent_crate_downloads,
+    registry_stats,
     reserved_crate_names,
+    reserved_prefixes,
+    sent_emails,
+    storage_discrepancies,
     teams,
+    trending_crates,
+    user_stats,
     users,
+    version_diffs,
     version_downloads,
+    version_downloads_archive,
+    version_files,
     version_owner_actions,
     versions,
     versions_published_by,
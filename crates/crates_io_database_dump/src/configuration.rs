@@ -14,13 +14,16 @@ pub enum ColumnVisibility {
 /// used to determine the order of the tables in the generated import script,
 /// and should list all tables the current tables refers to with foreign key
 /// constraints on public columns. The `filter` field is a valid SQL expression
-/// used in a `WHERE` clause to filter the rows of the table. The `columns`
-/// field maps column names to their respective visibilities.
+/// used in a `WHERE` clause to filter the rows of the table. The `incremental`
+/// field is a similar SQL expression, selecting the rows that have changed
+/// since the previous dump for incremental dumps. The `columns` field maps
+/// column names to their respective visibilities.
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct TableConfig {
     #[serde(default)]
     pub dependencies: Vec<String>,
     pub filter: Option<String>,
+    pub incremental: Option<String>,
     pub columns: BTreeMap<String, ColumnVisibility>,
     #[serde(default)]
     pub column_defaults: BTreeMap<String, String>,
@@ -1,5 +1,6 @@
 use crate::configuration::{ColumnVisibility, TableConfig, VisibilityConfig};
 use anyhow::Context;
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::{fs::File, path::Path};
 use tracing::debug;
@@ -11,6 +12,14 @@ pub fn gen_scripts(export_script: &Path, import_script: &Path) -> anyhow::Result
     config.gen_psql_scripts(export_sql, import_sql)
 }
 
+/// Generates the export script for an incremental dump, containing only the
+/// rows that have changed since `since`.
+pub fn gen_incremental_script(export_script: &Path, since: &DateTime<Utc>) -> anyhow::Result<()> {
+    let config = VisibilityConfig::get();
+    let export_sql = File::create(export_script).context("Failed to create export script file")?;
+    config.gen_incremental_psql_script(export_sql, since)
+}
+
 /// Subset of the configuration data to be passed on to the Handlbars template.
 #[derive(Debug, Serialize)]
 struct HandlebarsTableContext<'a> {
@@ -27,7 +36,14 @@ struct ColumnDefault<'a> {
 }
 
 impl TableConfig {
-    fn template_context<'a>(&'a self, name: &'a str) -> Option<HandlebarsTableContext<'a>> {
+    /// Returns the template context for this table. If `since` is set, the
+    /// context selects only the rows that have changed since then, and
+    /// `None` is returned for tables that don't support incremental dumps.
+    fn template_context<'a>(
+        &'a self,
+        name: &'a str,
+        since: Option<&str>,
+    ) -> Option<HandlebarsTableContext<'a>> {
         let columns = self
             .columns
             .iter()
@@ -35,10 +51,21 @@ impl TableConfig {
             .map(|(col, _)| format!("\"{col}\""))
             .collect::<Vec<String>>()
             .join(", ");
+        let incremental = match since {
+            Some(since) => Some(self.incremental.as_ref()?.replace("{since}", since)),
+            None => None,
+        };
+
         if columns.is_empty() {
             None
         } else {
-            let filter = self.filter.as_ref().map(|s| s.replace('\n', " "));
+            let filter = match (&self.filter, incremental) {
+                (Some(filter), Some(incremental)) => {
+                    Some(format!("({filter}) AND ({incremental})"))
+                }
+                (filter, incremental) => filter.clone().or(incremental),
+            };
+            let filter = filter.map(|s| s.replace('\n', " "));
             let column_defaults = self
                 .column_defaults
                 .iter()
@@ -64,11 +91,11 @@ struct TemplateContext<'a> {
 }
 
 impl VisibilityConfig {
-    fn template_context(&self) -> TemplateContext<'_> {
+    fn template_context(&self, since: Option<&str>) -> TemplateContext<'_> {
         let tables = self
             .topological_sort()
             .into_iter()
-            .filter_map(|table| self.0[table].template_context(table))
+            .filter_map(|table| self.0[table].template_context(table, since))
             .collect();
         TemplateContext { tables }
     }
@@ -85,7 +112,7 @@ impl VisibilityConfig {
         env.add_template("dump-import.sql", include_str!("dump-import.sql.j2"))
             .context("Failed to load dump-import.sql.j2 template")?;
 
-        let context = self.template_context();
+        let context = self.template_context(None);
 
         debug!("Rendering dump-export.sql file…");
         let export_sql = env
@@ -113,6 +140,38 @@ impl VisibilityConfig {
 
         Ok(())
     }
+
+    fn gen_incremental_psql_script<W>(
+        &self,
+        mut export_writer: W,
+        since: &DateTime<Utc>,
+    ) -> anyhow::Result<()>
+    where
+        W: std::io::Write,
+    {
+        use minijinja::Environment;
+
+        let mut env = Environment::new();
+        env.add_template("dump-export.sql", include_str!("dump-export.sql.j2"))
+            .context("Failed to load dump-export.sql.j2 template")?;
+
+        let since = since.format("'%Y-%m-%d %H:%M:%S%.6f'").to_string();
+        let context = self.template_context(Some(&since));
+
+        debug!("Rendering incremental dump-export.sql file…");
+        let export_sql = env
+            .get_template("dump-export.sql")
+            .unwrap()
+            .render(&context)
+            .context("Failed to render dump-export.sql file")?;
+
+        debug!("Writing incremental dump-export.sql file…");
+        export_writer
+            .write_all(export_sql.as_bytes())
+            .context("Failed to write dump-export.sql file")?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -12,7 +12,7 @@ mod configuration;
 mod gen_scripts;
 
 pub use configuration::VisibilityConfig;
-pub use gen_scripts::gen_scripts;
+pub use gen_scripts::{gen_incremental_script, gen_scripts};
 
 /// Manage the export directory.
 ///
@@ -23,6 +23,9 @@ pub struct DumpDirectory {
     /// The temporary directory that contains the export directory.
     tempdir: tempfile::TempDir,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// The timestamp of the previous dump, if this is an incremental dump
+    /// that only contains the rows that have changed since then.
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl DumpDirectory {
@@ -31,7 +34,19 @@ impl DumpDirectory {
         let tempdir = tempfile::tempdir()?;
         let timestamp = chrono::Utc::now();
 
-        Ok(Self { tempdir, timestamp })
+        Ok(Self {
+            tempdir,
+            timestamp,
+            since: None,
+        })
+    }
+
+    /// Creates the directory for an incremental dump, containing only the
+    /// rows that have changed since the previous dump at `since`.
+    pub fn create_incremental(since: chrono::DateTime<chrono::Utc>) -> anyhow::Result<Self> {
+        let mut directory = Self::create()?;
+        directory.since = Some(since);
+        Ok(directory)
     }
 
     pub fn path(&self) -> &Path {
@@ -58,7 +73,10 @@ impl DumpDirectory {
         let path = self.path().join("README.md");
         debug!(?path, "Writing README.md file…");
         let mut readme = File::create(path)?;
-        readme.write_all(include_bytes!("readme_for_tarball.md"))?;
+        match self.since {
+            Some(_) => readme.write_all(include_bytes!("readme_for_incremental.md"))?,
+            None => readme.write_all(include_bytes!("readme_for_tarball.md"))?,
+        }
         Ok(())
     }
 
@@ -66,10 +84,13 @@ impl DumpDirectory {
         #[derive(Serialize)]
         struct Metadata<'a> {
             timestamp: &'a chrono::DateTime<chrono::Utc>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            since: Option<&'a chrono::DateTime<chrono::Utc>>,
             crates_io_commit: String,
         }
         let metadata = Metadata {
             timestamp: &self.timestamp,
+            since: self.since.as_ref(),
             crates_io_commit: std::env::var("HEROKU_SLUG_COMMIT")
                 .unwrap_or_else(|_| "unknown".to_owned()),
         };
@@ -108,11 +129,17 @@ impl DumpDirectory {
     }
 
     pub fn dump_db(&self, database_url: &str) -> anyhow::Result<()> {
-        debug!("Generating export.sql and import.sql files…");
         let export_script = self.path().join("export.sql");
-        let import_script = self.path().join("import.sql");
-        gen_scripts(&export_script, &import_script)
-            .context("Failed to generate export/import scripts")?;
+        if let Some(since) = &self.since {
+            debug!("Generating incremental export.sql file…");
+            gen_incremental_script(&export_script, since)
+                .context("Failed to generate incremental export script")?;
+        } else {
+            debug!("Generating export.sql and import.sql files…");
+            let import_script = self.path().join("import.sql");
+            gen_scripts(&export_script, &import_script)
+                .context("Failed to generate export/import scripts")?;
+        }
 
         debug!("Filling data folder…");
         fs::create_dir(self.path().join("data")).context("Failed to create `data` directory")?;
@@ -321,4 +348,18 @@ mod tests {
             assert_snapshot!(content);
         });
     }
+
+    #[test]
+    fn test_incremental_sql_script() {
+        let db = TestDatabase::new();
+
+        let since = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z").unwrap();
+        let directory = DumpDirectory::create_incremental(since.to_utc()).unwrap();
+        directory.populate(db.url()).unwrap();
+
+        assert!(!directory.path().join("import.sql").exists());
+
+        let content = fs::read_to_string(directory.path().join("export.sql")).unwrap();
+        assert_snapshot!(content);
+    }
 }
@@ -0,0 +1,140 @@
+//! Support for a TOML config file with default values for the environment
+//! variables.
+//!
+//! The keys of the file are converted to the names of the environment
+//! variables by joining the keys of nested tables with `_` and converting them
+//! to uppercase, e.g. `[database] url = "…"` is read as `DATABASE_URL`. Arrays
+//! are read as comma-separated lists.
+//!
+//! Environment variables, including those from the `.env` file, always take
+//! precedence over the values in the config file.
+
+use anyhow::{anyhow, bail, Context};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use toml::{Table, Value};
+
+/// Name of the environment variable that contains the path of the optional
+/// config file.
+pub const CONFIG_FILE_VAR: &str = "CRATES_IO_CONFIG_FILE";
+
+/// The values of the config file, which is only read once per process.
+///
+/// The error is stored as a string, so that it can be reported every time
+/// a value is read.
+static VALUES: LazyLock<Result<HashMap<String, String>, String>> =
+    LazyLock::new(|| load().map_err(|error| format!("{error:#}")));
+
+pub(crate) fn var(key: &str) -> anyhow::Result<Option<String>> {
+    match &*VALUES {
+        Ok(values) => Ok(values.get(key).cloned()),
+        Err(error) => Err(anyhow!("{error}")),
+    }
+}
+
+fn load() -> anyhow::Result<HashMap<String, String>> {
+    let path = match dotenvy::var(CONFIG_FILE_VAR) {
+        Ok(path) => path,
+        Err(dotenvy::Error::EnvVar(std::env::VarError::NotPresent)) => {
+            return Ok(HashMap::new());
+        }
+        Err(error) => return Err(error.into()),
+    };
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config file {path}"))?;
+
+    parse(&content).with_context(|| format!("Failed to parse config file {path}"))
+}
+
+fn parse(content: &str) -> anyhow::Result<HashMap<String, String>> {
+    let table: Table = content.parse()?;
+
+    let mut values = HashMap::new();
+    flatten(&table, None, &mut values)?;
+    Ok(values)
+}
+
+fn flatten(
+    table: &Table,
+    prefix: Option<&str>,
+    values: &mut HashMap<String, String>,
+) -> anyhow::Result<()> {
+    for (key, value) in table {
+        let key = match prefix {
+            Some(prefix) => format!("{prefix}_{}", key.to_uppercase()),
+            None => key.to_uppercase(),
+        };
+
+        if let Value::Table(table) = value {
+            flatten(table, Some(&key), values)?;
+            continue;
+        }
+
+        let value = match value {
+            Value::Array(array) => array
+                .iter()
+                .map(|value| to_string(value, &key))
+                .collect::<anyhow::Result<Vec<_>>>()?
+                .join(","),
+            value => to_string(value, &key)?,
+        };
+
+        values.insert(key, value);
+    }
+
+    Ok(())
+}
+
+fn to_string(value: &Value, key: &str) -> anyhow::Result<String> {
+    Ok(match value {
+        Value::String(value) => value.clone(),
+        Value::Integer(value) => value.to_string(),
+        Value::Float(value) => value.to_string(),
+        Value::Boolean(value) => value.to_string(),
+        Value::Datetime(value) => value.to_string(),
+        Value::Array(_) | Value::Table(_) => bail!("Unsupported nested value for {key}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::*;
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+            domain_name = "crates.io"
+            port = 8888
+            request_rate_limiter_enabled = true
+            blocked_routes = ["/foo", "/bar"]
+
+            [database]
+            url = "postgres://localhost/crates_io"
+
+            [rate_limiter.publish_new]
+            burst = 10
+        "#;
+
+        let values = assert_ok!(parse(content));
+        assert_eq!(values.len(), 6);
+        assert_eq!(values["DOMAIN_NAME"], "crates.io");
+        assert_eq!(values["PORT"], "8888");
+        assert_eq!(values["REQUEST_RATE_LIMITER_ENABLED"], "true");
+        assert_eq!(values["BLOCKED_ROUTES"], "/foo,/bar");
+        assert_eq!(values["DATABASE_URL"], "postgres://localhost/crates_io");
+        assert_eq!(values["RATE_LIMITER_PUBLISH_NEW_BURST"], "10");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_err!(parse("port = "));
+
+        let error = assert_err!(parse("blocked_routes = [[\"/foo\"]]"));
+        assert_eq!(
+            error.to_string(),
+            "Unsupported nested value for BLOCKED_ROUTES"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+/// Collects the errors of multiple configuration values, so that all missing
+/// and invalid values can be reported at once, instead of one at a time.
+#[derive(Debug, Default)]
+pub struct Errors(Vec<anyhow::Error>);
+
+impl Errors {
+    /// Returns the value, or records the error and returns the default value
+    /// of `T` instead.
+    pub fn check<T: Default>(&mut self, result: anyhow::Result<T>) -> T {
+        result.unwrap_or_else(|error| {
+            self.0.push(error);
+            T::default()
+        })
+    }
+
+    /// Records an error, e.g. for a value that was parsed successfully but
+    /// is not allowed in combination with other values.
+    pub fn push(&mut self, error: anyhow::Error) {
+        self.0.push(error);
+    }
+
+    /// Fails with a list of all recorded errors, if there are any.
+    pub fn finish(self) -> anyhow::Result<()> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+
+        let list = self
+            .0
+            .iter()
+            .map(|error| format!("- {error:#}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Err(anyhow::anyhow!("Invalid configuration:\n{list}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use claims::*;
+
+    #[test]
+    fn test_errors() {
+        let mut errors = Errors::default();
+        assert_eq!(errors.check(Ok(42)), 42);
+        assert_ok!(errors.finish());
+
+        let mut errors = Errors::default();
+        assert_eq!(errors.check::<i32>(Err(anyhow!("missing FOO"))), 0);
+        assert_none!(errors.check::<Option<i32>>(Err(anyhow!("invalid BAR"))));
+        errors.push(anyhow!("BAZ is not allowed"));
+
+        let error = assert_err!(errors.finish());
+        assert_eq!(
+            error.to_string(),
+            "Invalid configuration:\n- missing FOO\n- invalid BAR\n- BAZ is not allowed"
+        );
+    }
+}
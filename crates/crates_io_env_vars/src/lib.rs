@@ -4,6 +4,12 @@ use anyhow::{anyhow, Context};
 use std::error::Error;
 use std::str::FromStr;
 
+mod config_file;
+mod errors;
+
+pub use self::config_file::CONFIG_FILE_VAR;
+pub use self::errors::Errors;
+
 /// Reads an environment variable for the current process.
 ///
 /// Compared to [std::env::var] there are a couple of differences:
@@ -11,13 +17,16 @@ use std::str::FromStr;
 /// - [var] uses [dotenvy] which loads the `.env` file from the current or
 ///   parent directories before returning the value.
 ///
+/// - [var] falls back to the config file referenced by [CONFIG_FILE_VAR], if
+///   the environment variable wasn't set.
+///
 /// - [var] returns `Ok(None)` (instead of `Err`) if an environment variable
 ///   wasn't set.
 #[track_caller]
 pub fn var(key: &str) -> anyhow::Result<Option<String>> {
     match dotenvy::var(key) {
         Ok(content) => Ok(Some(content)),
-        Err(dotenvy::Error::EnvVar(std::env::VarError::NotPresent)) => Ok(None),
+        Err(dotenvy::Error::EnvVar(std::env::VarError::NotPresent)) => config_file::var(key),
         Err(error) => Err(error.into()),
     }
 }
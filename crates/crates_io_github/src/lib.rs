@@ -9,10 +9,11 @@ use reqwest::{self, header};
 use serde::de::DeserializeOwned;
 
 use std::str;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use reqwest::Client;
-use serde::Deserialize;
+use crates_io_outbound::{OutboundClient, OutboundError, Policy};
+use serde::{Deserialize, Serialize};
 
 type Result<T> = std::result::Result<T, GitHubError>;
 
@@ -40,16 +41,32 @@ pub trait GitHubClient: Send + Sync {
         username: &str,
         auth: &AccessToken,
     ) -> Result<GitHubOrgMembership>;
+    async fn repository(
+        &self,
+        owner: &str,
+        repo: &str,
+        auth: &AccessToken,
+    ) -> Result<GitHubRepository>;
     async fn public_keys(&self, username: &str, password: &str) -> Result<Vec<GitHubPublicKey>>;
 }
 
+const GITHUB_API_HOST: &str = "api.github.com";
+
 #[derive(Debug)]
 pub struct RealGitHubClient {
-    client: Client,
+    client: OutboundClient,
 }
 
 impl RealGitHubClient {
-    pub fn new(client: Client) -> Self {
+    pub fn new(client: OutboundClient) -> Self {
+        // Most GitHub requests are made while handling a request to
+        // crates.io, so they must not take too long.
+        let policy = Policy {
+            timeout: Duration::from_secs(10),
+            ..Policy::default()
+        };
+
+        let client = client.with_policy(GITHUB_API_HOST, policy);
         Self { client }
     }
 
@@ -58,15 +75,18 @@ impl RealGitHubClient {
     where
         T: DeserializeOwned,
     {
-        let url = format!("https://api.github.com{url}");
+        let url = format!("https://{GITHUB_API_HOST}{url}");
         info!("GITHUB HTTP: {url}");
 
-        self.client
+        let request = self
+            .client
             .get(&url)
             .header(header::ACCEPT, "application/vnd.github.v3+json")
             .header(header::AUTHORIZATION, auth)
-            .header(header::USER_AGENT, "crates.io (https://crates.io)")
-            .send()
+            .header(header::USER_AGENT, "crates.io (https://crates.io)");
+
+        self.client
+            .send(request)
             .await?
             .error_for_status()?
             .json()
@@ -138,6 +158,16 @@ impl GitHubClient for RealGitHubClient {
         .await
     }
 
+    async fn repository(
+        &self,
+        owner: &str,
+        repo: &str,
+        auth: &AccessToken,
+    ) -> Result<GitHubRepository> {
+        let url = format!("/repos/{owner}/{repo}");
+        self.request(&url, auth).await
+    }
+
     /// Returns the list of public keys that can be used to verify GitHub secret alert signatures
     async fn public_keys(&self, username: &str, password: &str) -> Result<Vec<GitHubPublicKey>> {
         let url = "/meta/public_keys/secret_scanning";
@@ -173,6 +203,15 @@ impl From<reqwest::Error> for GitHubError {
     }
 }
 
+impl From<OutboundError> for GitHubError {
+    fn from(error: OutboundError) -> Self {
+        match error {
+            OutboundError::Request(error) => error.into(),
+            error => Self::Other(error.into()),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GithubUser {
     pub avatar_url: Option<String>,
@@ -182,30 +221,45 @@ pub struct GithubUser {
     pub name: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct GitHubOrganization {
     pub id: i32, // unique GH id (needed for membership queries)
     pub avatar_url: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct GitHubTeam {
     pub id: i32,              // unique GH id (needed for membership queries)
     pub name: Option<String>, // Pretty name
     pub organization: GitHubOrganization,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct GitHubTeamMembership {
     pub state: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct GitHubOrgMembership {
     pub state: String,
     pub role: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct GitHubRepository {
+    pub id: i64,
+    pub full_name: String,
+    /// The permissions of the authenticated user on the repository. Only
+    /// present if the request was authenticated.
+    pub permissions: Option<GitHubRepositoryPermissions>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubRepositoryPermissions {
+    pub admin: bool,
+    pub push: bool,
+}
+
 #[derive(Debug, Deserialize, Clone, Eq, Hash, PartialEq)]
 pub struct GitHubPublicKey {
     pub key_identifier: String,
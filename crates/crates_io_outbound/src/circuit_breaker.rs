@@ -0,0 +1,86 @@
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+/// Stops sending requests to a host for a while after repeated failures, so
+/// that requests don't pile up waiting for timeouts during an outage.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Mutex<CircuitState>,
+}
+
+#[derive(Debug, Default)]
+struct CircuitState {
+    /// The number of consecutive failed requests.
+    failures: u32,
+    /// No requests are sent until this time.
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Creates a circuit breaker that opens for `open_duration` after
+    /// `failure_threshold` consecutive failures.
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_duration,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns `true` if no requests should be sent right now.
+    pub fn is_open(&self) -> bool {
+        let state = self.state.lock();
+        state
+            .open_until
+            .is_some_and(|open_until| Instant::now() < open_until)
+    }
+
+    pub fn record_success(&self) {
+        *self.state.lock() = CircuitState::default();
+    }
+
+    /// Records a failed request, and returns `true` if the circuit has been
+    /// opened because of it. Once the circuit has been opened, a single
+    /// failure after the open duration opens it again.
+    pub fn record_failure(&self) -> bool {
+        let mut state = self.state.lock();
+        state.failures += 1;
+        if state.failures < self.failure_threshold {
+            return false;
+        }
+
+        state.open_until = Some(Instant::now() + self.open_duration);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        assert!(!breaker.is_open());
+
+        assert!(breaker.record_failure());
+        assert!(breaker.is_open());
+
+        breaker.record_success();
+        assert!(!breaker.is_open());
+
+        assert!(!breaker.record_failure());
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_circuit_breaker_closes_after_open_duration() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        assert!(breaker.record_failure());
+        assert!(!breaker.is_open());
+    }
+}
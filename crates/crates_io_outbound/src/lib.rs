@@ -0,0 +1,285 @@
+#![doc = include_str!("../README.md")]
+
+#[macro_use]
+extern crate tracing;
+
+mod circuit_breaker;
+mod metrics;
+mod policy;
+
+pub use crate::circuit_breaker::CircuitBreaker;
+pub use crate::metrics::gather_metrics;
+pub use crate::policy::Policy;
+
+use crate::metrics::{Outcome, METRICS};
+use parking_lot::Mutex;
+use reqwest::{Client, Method, Request, RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutboundError {
+    #[error("requests to {host} are paused after repeated failures")]
+    CircuitOpen { host: String },
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+}
+
+/// An HTTP client for requests to external services, which applies the
+/// [`Policy`] of the requested host.
+///
+/// Clones of the client share the circuit breakers of the hosts.
+#[derive(Debug, Clone)]
+pub struct OutboundClient {
+    client: Client,
+    default_policy: Policy,
+    policies: Arc<HashMap<String, Policy>>,
+    breakers: Arc<Mutex<HashMap<String, Arc<CircuitBreaker>>>>,
+}
+
+impl OutboundClient {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            default_policy: Policy::default(),
+            policies: Arc::default(),
+            breakers: Arc::default(),
+        }
+    }
+
+    /// Returns a client that uses the given policy for requests to `host`.
+    ///
+    /// The policy has to be set before the first request to the host, since
+    /// the circuit breaker of the host is created on first use.
+    pub fn with_policy(mut self, host: impl Into<String>, policy: Policy) -> Self {
+        Arc::make_mut(&mut self.policies).insert(host.into(), policy);
+        self
+    }
+
+    fn policy(&self, host: &str) -> &Policy {
+        self.policies.get(host).unwrap_or(&self.default_policy)
+    }
+
+    fn breaker(&self, host: &str) -> Arc<CircuitBreaker> {
+        let mut breakers = self.breakers.lock();
+        let breaker = breakers.entry(host.to_string()).or_insert_with(|| {
+            let policy = self.policy(host);
+            Arc::new(CircuitBreaker::new(
+                policy.failure_threshold,
+                policy.open_duration,
+            ))
+        });
+
+        breaker.clone()
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    /// Sends the request according to the policy of its host.
+    ///
+    /// Requests that fail without a response, or with a server error or rate
+    /// limit status, are retried as long as the policy allows it. The
+    /// response of the last attempt is returned, regardless of its status.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, OutboundError> {
+        let mut request = request.build()?;
+
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        let policy = self.policy(&host);
+        let breaker = self.breaker(&host);
+
+        if request.timeout().is_none() {
+            *request.timeout_mut() = Some(policy.timeout);
+        }
+
+        let retryable = policy.retry_non_idempotent || is_idempotent(request.method());
+
+        let mut attempt = 1;
+        loop {
+            if breaker.is_open() {
+                METRICS.record_attempt(&host, Outcome::CircuitOpen, None);
+                return Err(OutboundError::CircuitOpen { host });
+            }
+
+            // Requests with streaming bodies can't be cloned, and are
+            // therefore never retried.
+            let next_attempt = (retryable && attempt < policy.max_attempts)
+                .then(|| request.try_clone())
+                .flatten();
+
+            let result = self.execute(&host, &breaker, request).await;
+            let failed = match &result {
+                Ok(response) => is_retryable_status(response.status()),
+                Err(_) => true,
+            };
+
+            let Some(next_attempt) = next_attempt.filter(|_| failed) else {
+                return result.map_err(Into::into);
+            };
+
+            let backoff = policy.backoff(attempt);
+            warn!(%host, attempt, ?backoff, "Retrying failed outbound request");
+            METRICS.record_retry(&host);
+            tokio::time::sleep(backoff).await;
+
+            request = next_attempt;
+            attempt += 1;
+        }
+    }
+
+    async fn execute(
+        &self,
+        host: &str,
+        breaker: &CircuitBreaker,
+        request: Request,
+    ) -> reqwest::Result<Response> {
+        let start = Instant::now();
+        let result = self.client.execute(request).await;
+        let duration = start.elapsed();
+
+        let outcome = match &result {
+            Ok(response) if is_retryable_status(response.status()) => Outcome::ErrorStatus,
+            Ok(_) => Outcome::Success,
+            Err(_) => Outcome::TransportError,
+        };
+
+        METRICS.record_attempt(host, outcome, Some(duration));
+        self.record_outcome(host, breaker, outcome);
+
+        result
+    }
+
+    /// Runs a request to `host` that is sent by another client, like one of
+    /// the AWS SDK clients, behind the circuit breaker of the host.
+    ///
+    /// The request is not retried, since these clients usually implement
+    /// their own retries, but its outcome is recorded in the metrics.
+    pub async fn guard<T, E>(
+        &self,
+        host: &str,
+        request: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E>
+    where
+        E: From<OutboundError>,
+    {
+        let breaker = self.breaker(host);
+        if breaker.is_open() {
+            METRICS.record_attempt(host, Outcome::CircuitOpen, None);
+            let host = host.to_string();
+            return Err(OutboundError::CircuitOpen { host }.into());
+        }
+
+        let start = Instant::now();
+        let result = request.await;
+        let duration = start.elapsed();
+
+        let outcome = match &result {
+            Ok(_) => Outcome::Success,
+            Err(_) => Outcome::TransportError,
+        };
+
+        METRICS.record_attempt(host, outcome, Some(duration));
+        self.record_outcome(host, &breaker, outcome);
+
+        result
+    }
+
+    fn record_outcome(&self, host: &str, breaker: &CircuitBreaker, outcome: Outcome) {
+        match outcome {
+            Outcome::Success => breaker.record_success(),
+            Outcome::ErrorStatus | Outcome::TransportError => {
+                if breaker.record_failure() {
+                    warn!(%host, "Pausing outbound requests after repeated failures");
+                    METRICS.record_circuit_opened(host);
+                }
+            }
+            Outcome::CircuitOpen => {}
+        }
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn test_client() -> OutboundClient {
+        let policy = Policy {
+            failure_threshold: 1,
+            open_duration: Duration::from_secs(60),
+            ..Policy::default()
+        };
+
+        OutboundClient::new(Client::new()).with_policy("example.com", policy)
+    }
+
+    #[tokio::test]
+    async fn test_guard() {
+        let client = test_client();
+
+        let result = client
+            .guard("example.com", async { Ok::<_, anyhow::Error>(42) })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+
+        let result = client
+            .guard("example.com", async {
+                Err::<(), _>(anyhow::anyhow!("failed"))
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "failed");
+
+        let result = client
+            .guard("example.com", async { Ok::<_, anyhow::Error>(42) })
+            .await;
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "requests to example.com are paused after repeated failures"
+        );
+
+        // Other hosts are not affected by the open circuit.
+        let result = client
+            .guard("example.org", async { Ok::<_, anyhow::Error>(42) })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_open_circuit() {
+        let client = test_client();
+        client.breaker("example.com").record_failure();
+
+        let request = client.get("https://example.com/");
+        let error = client.send(request).await.unwrap_err();
+        assert!(matches!(error, OutboundError::CircuitOpen { .. }));
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(StatusCode::OK));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::FORBIDDEN));
+    }
+}
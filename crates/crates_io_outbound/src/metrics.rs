@@ -0,0 +1,114 @@
+use prometheus::proto::MetricFamily;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+const NAMESPACE: &str = "cratesio_outbound";
+
+/// The metrics of all outbound requests of this process.
+pub(crate) static METRICS: LazyLock<Metrics> =
+    LazyLock::new(|| Metrics::new().expect("could not initialize outbound metrics"));
+
+pub(crate) struct Metrics {
+    registry: Registry,
+    /// Number of outbound requests per host and outcome
+    requests_total: IntCounterVec,
+    /// Duration of outbound requests per host
+    request_duration_seconds: HistogramVec,
+    /// Number of retried outbound requests per host
+    retries_total: IntCounterVec,
+    /// Number of times the circuit breaker of a host was opened
+    circuit_opened_total: IntCounterVec,
+}
+
+/// The outcome of a single attempt, as recorded in the metrics.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Outcome {
+    Success,
+    /// The host responded with a server error or rate limit status.
+    ErrorStatus,
+    /// The request failed before a response was received, e.g. because of a
+    /// timeout.
+    TransportError,
+    /// The request was not sent, because the circuit breaker is open.
+    CircuitOpen,
+}
+
+impl Outcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::ErrorStatus => "error_status",
+            Outcome::TransportError => "transport_error",
+            Outcome::CircuitOpen => "circuit_open",
+        }
+    }
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+
+        let requests_total = IntCounterVec::new(
+            Opts::new("requests_total", "Number of outbound requests").namespace(NAMESPACE),
+            &["host", "outcome"],
+        )?;
+        registry.register(Box::new(requests_total.clone()))?;
+
+        let request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new("request_duration_seconds", "Duration of outbound requests")
+                .namespace(NAMESPACE),
+            &["host"],
+        )?;
+        registry.register(Box::new(request_duration_seconds.clone()))?;
+
+        let retries_total = IntCounterVec::new(
+            Opts::new("retries_total", "Number of retried outbound requests").namespace(NAMESPACE),
+            &["host"],
+        )?;
+        registry.register(Box::new(retries_total.clone()))?;
+
+        let circuit_opened_total = IntCounterVec::new(
+            Opts::new(
+                "circuit_opened_total",
+                "Number of times requests to a host were paused after repeated failures",
+            )
+            .namespace(NAMESPACE),
+            &["host"],
+        )?;
+        registry.register(Box::new(circuit_opened_total.clone()))?;
+
+        Ok(Self {
+            registry,
+            requests_total,
+            request_duration_seconds,
+            retries_total,
+            circuit_opened_total,
+        })
+    }
+
+    pub(crate) fn record_attempt(&self, host: &str, outcome: Outcome, duration: Option<Duration>) {
+        self.requests_total
+            .with_label_values(&[host, outcome.as_str()])
+            .inc();
+
+        if let Some(duration) = duration {
+            self.request_duration_seconds
+                .with_label_values(&[host])
+                .observe(duration.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn record_retry(&self, host: &str) {
+        self.retries_total.with_label_values(&[host]).inc();
+    }
+
+    pub(crate) fn record_circuit_opened(&self, host: &str) {
+        self.circuit_opened_total.with_label_values(&[host]).inc();
+    }
+}
+
+/// Returns the metrics of all outbound requests of this process.
+pub fn gather_metrics() -> Vec<MetricFamily> {
+    METRICS.registry.gather()
+}
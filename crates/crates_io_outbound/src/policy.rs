@@ -0,0 +1,77 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// How requests to a host are timed out, retried and cut off after repeated
+/// failures.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    /// The timeout of a single attempt, unless the request sets its own.
+    pub timeout: Duration,
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// The backoff before the first retry. It doubles with every retry.
+    pub initial_backoff: Duration,
+    /// The upper limit of the backoff between two attempts.
+    pub max_backoff: Duration,
+    /// Whether requests with non-idempotent methods like `POST` are retried
+    /// too. Only enable this for hosts where repeating such a request is
+    /// harmless.
+    pub retry_non_idempotent: bool,
+    /// The number of consecutive failures after which no requests are sent
+    /// to the host for `open_duration`.
+    pub failure_threshold: u32,
+    /// How long no requests are sent to the host after repeated failures.
+    pub open_duration: Duration,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            retry_non_idempotent: false,
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Policy {
+    /// Returns how long to wait before the given retry, starting at `1`.
+    ///
+    /// The backoff is randomized between half of and the full exponential
+    /// backoff, so that the retries of concurrent requests are spread out.
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = Policy::default();
+
+        let backoff = policy.backoff(1);
+        assert!(backoff >= Duration::from_millis(50));
+        assert!(backoff <= Duration::from_millis(100));
+
+        let backoff = policy.backoff(3);
+        assert!(backoff >= Duration::from_millis(200));
+        assert!(backoff <= Duration::from_millis(400));
+
+        let backoff = policy.backoff(100);
+        assert!(backoff >= Duration::from_secs(1));
+        assert!(backoff <= Duration::from_secs(2));
+    }
+}
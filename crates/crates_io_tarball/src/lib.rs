@@ -30,6 +30,9 @@ const DEFAULT_BUF_SIZE: usize = 128 * 1024;
 pub struct TarballInfo {
     pub manifest: Manifest,
     pub vcs_info: Option<CargoVcsInfo>,
+    /// The contents of the `FUNDING.yml` file in the package root or in its
+    /// `.github` directory, if there is one.
+    pub funding_yml: Option<String>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -72,6 +75,7 @@ pub async fn process_tarball<R: tokio::io::AsyncRead + Unpin>(
     let pkg_root = Path::new(&pkg_name);
 
     let mut vcs_info = None;
+    let mut funding_yml = None;
     let mut paths = Vec::new();
     let mut manifests = BTreeMap::new();
     let mut entries = archive.entries()?;
@@ -103,6 +107,17 @@ pub async fn process_tarball<R: tokio::io::AsyncRead + Unpin>(
 
         paths.push(in_pkg_path.to_path_buf());
 
+        // The funding links of the crate, which are shown on the crate page.
+        if in_pkg_path == Path::new("FUNDING.yml")
+            || in_pkg_path == Path::new(".github/FUNDING.yml")
+        {
+            let mut contents = String::new();
+            if entry.read_to_string(&mut contents).await.is_ok() {
+                funding_yml = Some(contents);
+            }
+            continue;
+        }
+
         // Let's go hunting for the VCS info and crate manifest. The only valid place for these is
         // in the package root in the tarball.
         if entry_path.parent() == Some(pkg_root) {
@@ -148,7 +163,11 @@ pub async fn process_tarball<R: tokio::io::AsyncRead + Unpin>(
 
     manifest.complete_from_abstract_filesystem(&PathsFileSystem(paths))?;
 
-    Ok(TarballInfo { manifest, vcs_info })
+    Ok(TarballInfo {
+        manifest,
+        vcs_info,
+        funding_yml,
+    })
 }
 
 struct PathsFileSystem(Vec<PathBuf>);
@@ -241,6 +260,19 @@ mod tests {
         assert_eq!(vcs_info.path_in_vcs, "path/in/vcs");
     }
 
+    #[tokio::test]
+    async fn process_tarball_test_funding_yml() {
+        let tarball = TarballBuilder::new()
+            .add_file("foo-0.0.1/Cargo.toml", MANIFEST)
+            .add_file("foo-0.0.1/.github/FUNDING.yml", b"github: octocat\n")
+            .add_file("foo-0.0.1/src/FUNDING.yml", b"github: someone-else\n")
+            .build();
+
+        let tarball_info = assert_ok!(process_tarball("foo-0.0.1", &*tarball, MAX_SIZE).await);
+        let funding_yml = assert_some!(tarball_info.funding_yml);
+        assert_eq!(funding_yml, "github: octocat\n");
+    }
+
     #[tokio::test]
     async fn process_tarball_test_manifest() {
         let manifest = br#"
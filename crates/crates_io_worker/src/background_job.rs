@@ -1,7 +1,8 @@
 use crate::errors::EnqueueError;
+use crate::job_context::EnqueueContext;
 use crate::schema::background_jobs;
 use diesel::dsl::{exists, not};
-use diesel::sql_types::{Int2, Jsonb, Text};
+use diesel::sql_types::{Int2, Jsonb, Nullable, Text};
 use diesel::{ExpressionMethods, IntoSql, OptionalExtension, QueryDsl};
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use futures_util::future::BoxFuture;
@@ -50,12 +51,13 @@ pub trait BackgroundJob: Serialize + DeserializeOwned + Send + Sync + 'static {
             Err(err) => return async move { Err(EnqueueError::SerializationError(err)) }.boxed(),
         };
         let priority = Self::PRIORITY;
+        let context = serde_json::to_value(EnqueueContext::current()).ok();
 
         if Self::DEDUPLICATED {
-            let future = enqueue_deduplicated(conn, Self::JOB_NAME, data, priority);
+            let future = enqueue_deduplicated(conn, Self::JOB_NAME, data, priority, context);
             future.boxed()
         } else {
-            let future = enqueue_simple(conn, Self::JOB_NAME, data, priority);
+            let future = enqueue_simple(conn, Self::JOB_NAME, data, priority, context);
             async move { Ok(Some(future.await?)) }.boxed()
         }
     }
@@ -66,6 +68,7 @@ fn enqueue_deduplicated(
     job_type: &'static str,
     data: Value,
     priority: i16,
+    context: Option<Value>,
 ) -> impl Future<Output = Result<Option<i64>, EnqueueError>> {
     let similar_jobs = background_jobs::table
         .select(background_jobs::id)
@@ -79,6 +82,7 @@ fn enqueue_deduplicated(
         job_type.into_sql::<Text>(),
         data.into_sql::<Jsonb>(),
         priority.into_sql::<Int2>(),
+        context.into_sql::<Nullable<Jsonb>>(),
     ))
     .filter(not(exists(similar_jobs)));
 
@@ -88,6 +92,7 @@ fn enqueue_deduplicated(
             background_jobs::job_type,
             background_jobs::data,
             background_jobs::priority,
+            background_jobs::enqueue_context,
         ))
         .returning(background_jobs::id)
         .get_result::<i64>(conn);
@@ -100,12 +105,14 @@ fn enqueue_simple(
     job_type: &'static str,
     data: Value,
     priority: i16,
+    context: Option<Value>,
 ) -> impl Future<Output = Result<i64, EnqueueError>> {
     let future = diesel::insert_into(background_jobs::table)
         .values((
             background_jobs::job_type.eq(job_type),
             background_jobs::data.eq(data),
             background_jobs::priority.eq(priority),
+            background_jobs::enqueue_context.eq(context),
         ))
         .returning(background_jobs::id)
         .get_result(conn);
@@ -0,0 +1,109 @@
+use crate::storage::BackgroundJob;
+use sentry_core::protocol::{Breadcrumb, Context, Map};
+use sentry_core::{Hub, Level, Scope};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Information about the request or job that enqueued a job.
+///
+/// It is saved together with the job, so that the Sentry events of the job
+/// can refer back to the trace in which it was enqueued.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct EnqueueContext {
+    /// ID of the Sentry trace in which the job was enqueued.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trace_id: Option<String>,
+    /// ID of the Sentry span in which the job was enqueued.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    span_id: Option<String>,
+}
+
+impl EnqueueContext {
+    /// Captures the trace of the current Sentry hub.
+    pub(crate) fn current() -> Self {
+        let trace = Hub::current()
+            .configure_scope(|scope| scope.get_span())
+            .map(|span| span.get_trace_context());
+
+        Self {
+            trace_id: trace.as_ref().map(|trace| trace.trace_id.to_string()),
+            span_id: trace.as_ref().map(|trace| trace.span_id.to_string()),
+        }
+    }
+
+    fn into_breadcrumb_data(self) -> Map<String, Value> {
+        let mut data = Map::new();
+        let fields = [("trace_id", self.trace_id), ("span_id", self.span_id)];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                data.insert(key.to_string(), value.into());
+            }
+        }
+        data
+    }
+}
+
+/// Adds the details of the job to the Sentry scope, so that they are
+/// attached to all events that are reported while the job is running.
+pub(crate) fn configure_job_scope(scope: &mut Scope, job: &BackgroundJob) {
+    scope.set_tag("job.type", &job.job_type);
+    scope.set_tag("job.id", job.id);
+    scope.set_tag("job.retries", job.retries);
+
+    let mut context = Map::new();
+    context.insert("id".into(), job.id.into());
+    context.insert("type".into(), job.job_type.clone().into());
+    context.insert("retries".into(), job.retries.into());
+    context.insert("priority".into(), job.priority.into());
+    scope.set_context("job", Context::Other(context));
+
+    let enqueue_context = job
+        .enqueue_context
+        .clone()
+        .and_then(|context| serde_json::from_value::<EnqueueContext>(context).ok())
+        .unwrap_or_default();
+
+    scope.add_breadcrumb(Breadcrumb {
+        timestamp: job.created_at,
+        category: Some("job".into()),
+        message: Some(format!("Enqueued job `{}`", job.job_type)),
+        data: enqueue_context.into_breadcrumb_data(),
+        level: Level::Info,
+        ..Default::default()
+    });
+
+    if job.retries > 0 {
+        scope.add_breadcrumb(Breadcrumb {
+            timestamp: job.last_retry,
+            category: Some("job".into()),
+            message: Some(format!("Job failed {} time(s) before", job.retries)),
+            level: Level::Warning,
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breadcrumb_data() {
+        let context = EnqueueContext {
+            trace_id: Some("4bf92f3577b34da6a3ce929d0e0e4736".into()),
+            span_id: None,
+        };
+
+        let json = serde_json::to_value(&context).unwrap();
+        let expected = serde_json::json!({ "trace_id": "4bf92f3577b34da6a3ce929d0e0e4736" });
+        assert_eq!(json, expected);
+        assert_eq!(
+            serde_json::from_value::<EnqueueContext>(json).unwrap(),
+            context
+        );
+
+        let data = context.into_breadcrumb_data();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data["trace_id"], "4bf92f3577b34da6a3ce929d0e0e4736");
+    }
+}
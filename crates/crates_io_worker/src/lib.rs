@@ -2,6 +2,7 @@
 
 mod background_job;
 mod errors;
+mod job_context;
 mod job_registry;
 mod runner;
 pub mod schema;
@@ -1,11 +1,11 @@
 use crate::background_job::DEFAULT_QUEUE;
 use crate::job_registry::JobRegistry;
-use crate::worker::Worker;
+use crate::worker::{PauseCheck, Worker};
 use crate::{storage, BackgroundJob};
 use anyhow::anyhow;
 use diesel_async::pooled_connection::deadpool::Pool;
 use diesel_async::AsyncPgConnection;
-use futures_util::future::join_all;
+use futures_util::future::{join_all, BoxFuture};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
@@ -20,6 +20,7 @@ pub struct Runner<Context> {
     queues: HashMap<String, Queue<Context>>,
     context: Context,
     shutdown_when_queue_empty: bool,
+    pause_check: Option<Arc<PauseCheck>>,
 }
 
 impl<Context: Clone + Send + Sync + 'static> Runner<Context> {
@@ -29,6 +30,7 @@ impl<Context: Clone + Send + Sync + 'static> Runner<Context> {
             queues: HashMap::new(),
             context,
             shutdown_when_queue_empty: false,
+            pause_check: None,
         }
     }
 
@@ -63,6 +65,17 @@ impl<Context: Clone + Send + Sync + 'static> Runner<Context> {
         self
     }
 
+    /// Pause all workers while `check` returns `true`, e.g. during
+    /// maintenance. The check runs before each job, and the workers don't
+    /// shut down while paused, even if `shutdown_when_queue_empty` is set.
+    pub fn pause_when<F>(mut self, check: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut AsyncPgConnection) -> BoxFuture<'a, bool> + Send + Sync + 'static,
+    {
+        self.pause_check = Some(Arc::new(check));
+        self
+    }
+
     /// Start the background workers.
     ///
     /// This returns a `RunningRunner` which can be used to wait for the workers to shutdown.
@@ -79,6 +92,7 @@ impl<Context: Clone + Send + Sync + 'static> Runner<Context> {
                     job_registry: Arc::new(queue.job_registry.clone()),
                     shutdown_when_queue_empty: self.shutdown_when_queue_empty,
                     poll_interval: queue.poll_interval,
+                    pause_check: self.pause_check.clone(),
                 };
 
                 let span = info_span!("worker", worker.name = %name);
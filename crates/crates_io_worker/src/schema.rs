@@ -7,5 +7,6 @@ diesel::table! {
         last_retry -> Timestamp,
         created_at -> Timestamp,
         priority -> Int2,
+        enqueue_context -> Nullable<Jsonb>,
     }
 }
@@ -5,12 +5,18 @@ use diesel::prelude::*;
 use diesel::sql_types::{Bool, Integer, Interval};
 use diesel::{delete, update};
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::time::SystemTime;
 
 #[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
 pub(super) struct BackgroundJob {
     pub(super) id: i64,
     pub(super) job_type: String,
     pub(super) data: serde_json::Value,
+    pub(super) retries: i32,
+    pub(super) last_retry: SystemTime,
+    pub(super) created_at: SystemTime,
+    pub(super) priority: i16,
+    pub(super) enqueue_context: Option<serde_json::Value>,
 }
 
 fn retriable() -> Box<dyn BoxableExpression<background_jobs::table, Pg, SqlType = Bool>> {
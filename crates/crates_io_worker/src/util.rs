@@ -4,6 +4,8 @@ use std::any::Any;
 use std::future::Future;
 use std::panic::PanicHookInfo;
 
+/// Runs the callback in a Sentry transaction on the current hub, which is the
+/// hub of the job when called by the worker.
 pub async fn with_sentry_transaction<F, R, E, Fut>(
     transaction_name: &str,
     callback: F,
@@ -12,8 +14,7 @@ where
     F: FnOnce() -> Fut,
     Fut: Future<Output = Result<R, E>>,
 {
-    let hub = Hub::new_from_top(Hub::current());
-    let _scope_guard = hub.push_scope();
+    let hub = Hub::current();
 
     let tx_ctx = sentry_core::TransactionContext::new(transaction_name, "swirl.perform");
     let tx = sentry_core::start_transaction(tx_ctx);
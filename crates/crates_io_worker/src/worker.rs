@@ -1,3 +1,4 @@
+use crate::job_context::configure_job_scope;
 use crate::job_registry::JobRegistry;
 use crate::storage;
 use crate::util::{try_to_extract_panic_info, with_sentry_transaction};
@@ -6,26 +7,41 @@ use diesel::prelude::*;
 use diesel_async::pooled_connection::deadpool::Pool;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, AsyncPgConnection};
+use futures_util::future::BoxFuture;
 use futures_util::FutureExt;
-use sentry_core::{Hub, SentryFutureExt};
+use sentry_core::{Hub, Level, SentryFutureExt};
 use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, error, info_span, warn};
 
+/// Returns `true` if the workers should not run any jobs at the moment.
+pub(crate) type PauseCheck =
+    dyn for<'a> Fn(&'a mut AsyncPgConnection) -> BoxFuture<'a, bool> + Send + Sync;
+
 pub struct Worker<Context> {
     pub(crate) connection_pool: Pool<AsyncPgConnection>,
     pub(crate) context: Context,
     pub(crate) job_registry: Arc<JobRegistry<Context>>,
     pub(crate) shutdown_when_queue_empty: bool,
     pub(crate) poll_interval: Duration,
+    pub(crate) pause_check: Option<Arc<PauseCheck>>,
 }
 
 impl<Context: Clone + Send + Sync + 'static> Worker<Context> {
     /// Run background jobs forever, or until the queue is empty if `shutdown_when_queue_empty` is set.
     pub async fn run(&self) {
         loop {
+            if self.is_paused().await {
+                debug!(
+                    "Background worker jobs are paused. Checking again in {:?}…",
+                    self.poll_interval
+                );
+                sleep(self.poll_interval).await;
+                continue;
+            }
+
             match self.run_next_job().await {
                 Ok(Some(_)) => {}
                 Ok(None) if self.shutdown_when_queue_empty => {
@@ -47,6 +63,21 @@ impl<Context: Clone + Send + Sync + 'static> Worker<Context> {
         }
     }
 
+    /// Returns `true` if the pause check of the runner returns `true`.
+    ///
+    /// Failing to get a database connection is not treated as a pause, since
+    /// [Worker::run_next_job] reports that error anyway.
+    async fn is_paused(&self) -> bool {
+        let Some(pause_check) = &self.pause_check else {
+            return false;
+        };
+
+        match self.connection_pool.get().await {
+            Ok(mut conn) => pause_check(&mut conn).await,
+            Err(_) => false,
+        }
+    }
+
     /// Run the next job in the queue, if there is one.
     ///
     /// Returns:
@@ -75,6 +106,11 @@ impl<Context: Clone + Send + Sync + 'static> Worker<Context> {
                 let job_id = job.id;
                 debug!("Running job…");
 
+                // Every job gets its own hub, so that the job details in its
+                // scope don't leak into the events of other jobs.
+                let hub = Arc::new(Hub::new_from_top(Hub::current()));
+                hub.configure_scope(|scope| configure_job_scope(scope, &job));
+
                 let future = with_sentry_transaction(&job.job_type, || async {
                     let run_task_fn = job_registry
                         .get(&job.job_type)
@@ -88,13 +124,14 @@ impl<Context: Clone + Send + Sync + 'static> Worker<Context> {
                         .and_then(std::convert::identity)
                 });
 
-                match future.bind_hub(Hub::current()).await {
+                match future.bind_hub(hub.clone()).await {
                     Ok(_) => {
                         debug!("Deleting successful job…");
                         storage::delete_successful_job(conn, job_id).await?
                     }
                     Err(error) => {
                         warn!("Failed to run job: {error}");
+                        report_failure(&hub, &job.job_type, &error);
                         storage::update_failed_job(conn, job_id).await;
                     }
                 }
@@ -106,3 +143,15 @@ impl<Context: Clone + Send + Sync + 'static> Worker<Context> {
         .await
     }
 }
+
+/// Reports the failure of a job to Sentry, including the job details from
+/// the scope of the job's hub.
+///
+/// The failures are grouped by job type in addition to the error message, so
+/// that the same error in different jobs shows up as separate issues.
+fn report_failure(hub: &Hub, job_type: &str, error: &anyhow::Error) {
+    hub.with_scope(
+        |scope| scope.set_fingerprint(Some(&["{{ default }}", job_type][..])),
+        || hub.capture_message(&format!("Failed to run job: {error:#}"), Level::Error),
+    );
+}
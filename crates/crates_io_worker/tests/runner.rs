@@ -6,11 +6,13 @@ use diesel::prelude::*;
 use diesel_async::pooled_connection::deadpool::Pool;
 use diesel_async::pooled_connection::AsyncDieselConnectionManager;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use futures_util::FutureExt;
 use insta::assert_compact_json_snapshot;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Barrier;
 
 async fn all_jobs(conn: &mut AsyncPgConnection) -> QueryResult<Vec<(String, Value)>> {
@@ -314,6 +316,48 @@ async fn jobs_can_be_deduplicated() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn paused_runners_do_not_run_jobs() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize)]
+    struct TestJob;
+
+    impl BackgroundJob for TestJob {
+        const JOB_NAME: &'static str = "test";
+        type Context = ();
+
+        async fn run(&self, _ctx: Self::Context) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    let test_database = TestDatabase::new();
+
+    let pool = pool(test_database.url())?;
+    let mut conn = pool.get().await?;
+
+    let paused = Arc::new(AtomicBool::new(true));
+    let pause_check = paused.clone();
+    let runner = runner(pool, ())
+        .register_job_type::<TestJob>()
+        .pause_when(move |_conn| {
+            let paused = pause_check.load(Ordering::SeqCst);
+            async move { paused }.boxed()
+        });
+
+    let job_id = assert_some!(TestJob.enqueue(&mut conn).await?);
+
+    let runner = runner.start();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(job_exists(job_id, &mut conn).await?);
+    assert!(!job_is_locked(job_id, &mut conn).await?);
+
+    paused.store(false, Ordering::SeqCst);
+    runner.wait_for_shutdown().await;
+    assert!(!job_exists(job_id, &mut conn).await?);
+
+    Ok(())
+}
+
 fn pool(database_url: &str) -> anyhow::Result<Pool<AsyncPgConnection>> {
     let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url);
     Ok(Pool::builder(manager).max_size(4).build()?)
@@ -0,0 +1,26 @@
+create table account_exports
+(
+    id           serial primary key,
+    user_id      integer     not null
+        constraint account_exports_users_id_fk
+            references users
+            on delete cascade,
+    token        text        not null default random_string(26),
+    created_at   timestamptz not null default now(),
+    completed_at timestamptz,
+    expires_at   timestamptz
+);
+
+comment on table account_exports is 'Data export archives requested by users';
+comment on column account_exports.id is 'Unique identifier of the `account_exports` row';
+comment on column account_exports.user_id is 'ID of the user whose data is exported';
+comment on column account_exports.token is 'Secret token used in the download URL of the export';
+comment on column account_exports.created_at is 'Date and time when the export was requested';
+comment on column account_exports.completed_at is 'Date and time when the export archive was generated, or NULL if it is still pending';
+comment on column account_exports.expires_at is 'Date and time after which the export archive can no longer be downloaded';
+
+create unique index account_exports_token_uindex
+    on account_exports (token);
+
+create index account_exports_user_id_index
+    on account_exports (user_id);
@@ -0,0 +1,10 @@
+drop index emails_user_id_email_uindex;
+drop index emails_user_id_is_primary_uindex;
+
+delete from emails where not is_primary;
+
+alter table emails
+    add constraint emails_user_id_key unique (user_id);
+
+alter table emails
+    drop column is_primary;
@@ -0,0 +1,16 @@
+alter table emails
+    add column is_primary boolean not null default false;
+
+comment on column emails.is_primary is 'Whether this is the primary email address of the user, which is used for notifications';
+
+update emails set is_primary = true;
+
+alter table emails
+    drop constraint emails_user_id_key;
+
+create unique index emails_user_id_is_primary_uindex
+    on emails (user_id)
+    where is_primary;
+
+create unique index emails_user_id_email_uindex
+    on emails (user_id, lower(email));
@@ -0,0 +1 @@
+drop table linked_identities;
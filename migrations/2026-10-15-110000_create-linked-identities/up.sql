@@ -0,0 +1,30 @@
+create table linked_identities
+(
+    id         serial primary key,
+    user_id    integer     not null
+        constraint linked_identities_users_id_fk
+            references users
+            on delete cascade,
+    provider   text        not null
+        constraint linked_identities_provider_check
+            check (provider in ('gitlab', 'google')),
+    account_id text        not null,
+    login      text        not null,
+    email      text,
+    created_at timestamptz not null default now()
+);
+
+comment on table linked_identities is 'Accounts of additional OAuth identity providers that can be used to log in to a crates.io account';
+comment on column linked_identities.id is 'Unique identifier of the linked identity';
+comment on column linked_identities.user_id is 'The crates.io user that this identity is linked to';
+comment on column linked_identities.provider is 'Name of the identity provider (e.g. `gitlab` or `google`)';
+comment on column linked_identities.account_id is 'Unique, stable account ID of the user at the identity provider';
+comment on column linked_identities.login is 'Username or display name of the account at the time it was linked';
+comment on column linked_identities.email is 'Email address of the account at the time it was linked, if available';
+comment on column linked_identities.created_at is 'Date and time when the identity was linked';
+
+create unique index linked_identities_provider_account_id_uindex
+    on linked_identities (provider, account_id);
+
+create unique index linked_identities_user_id_provider_uindex
+    on linked_identities (user_id, provider);
@@ -0,0 +1,7 @@
+delete from crate_owners where owner_kind = 2;
+
+alter table api_tokens
+    drop column organization_id;
+
+drop table organization_members;
+drop table organizations;
@@ -0,0 +1,48 @@
+create table organizations
+(
+    id         serial primary key,
+    login      text        not null,
+    name       text,
+    created_at timestamptz not null default now()
+);
+
+comment on table organizations is 'Organizations that can own crates independently of GitHub teams';
+comment on column organizations.id is 'Unique identifier of the organization';
+comment on column organizations.login is 'Unique name of the organization, used as `org:<login>` when adding it as a crate owner';
+comment on column organizations.name is 'Human-readable display name of the organization';
+comment on column organizations.created_at is 'Date and time when the organization was created';
+
+create unique index organizations_login_uindex
+    on organizations (lower(login));
+
+create table organization_members
+(
+    organization_id integer     not null
+        constraint organization_members_organizations_id_fk
+            references organizations
+            on delete cascade,
+    user_id         integer     not null
+        constraint organization_members_users_id_fk
+            references users
+            on delete cascade,
+    role            integer     not null,
+    created_at      timestamptz not null default now(),
+    primary key (organization_id, user_id)
+);
+
+comment on table organization_members is 'Members of organizations and their roles';
+comment on column organization_members.organization_id is 'ID of the organization';
+comment on column organization_members.user_id is 'ID of the member';
+comment on column organization_members.role is 'Role of the member: 0 = admin, 1 = publisher, 2 = viewer';
+comment on column organization_members.created_at is 'Date and time when the user joined the organization';
+
+create index organization_members_user_id_index
+    on organization_members (user_id);
+
+alter table api_tokens
+    add column organization_id integer
+        constraint api_tokens_organizations_id_fk
+            references organizations
+            on delete cascade;
+
+comment on column api_tokens.organization_id is 'ID of the organization that owns the token, which restricts the token to crates owned by the organization';
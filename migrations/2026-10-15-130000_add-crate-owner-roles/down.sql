@@ -0,0 +1,5 @@
+alter table crate_owner_invitations
+    drop column role;
+
+alter table crate_owners
+    drop column role;
@@ -0,0 +1,9 @@
+alter table crate_owners
+    add column role integer not null default 0;
+
+comment on column crate_owners.role is '`role = 0` allows publishing and managing owners and settings of the crate, `role = 1` only allows publishing new versions. Only applies to users.';
+
+alter table crate_owner_invitations
+    add column role integer not null default 0;
+
+comment on column crate_owner_invitations.role is 'The `crate_owners.role` that the invited user will have once the invitation is accepted';
@@ -0,0 +1,33 @@
+create table crate_transfers
+(
+    id           serial primary key,
+    crate_id     integer     not null
+        constraint crate_transfers_crates_id_fk
+            references crates
+            on delete cascade,
+    requested_by integer     not null
+        constraint crate_transfers_requested_by_fk
+            references users
+            on delete cascade,
+    recipient_id integer     not null
+        constraint crate_transfers_recipient_id_fk
+            references users
+            on delete cascade,
+    created_at   timestamptz not null default now(),
+    accepted_at  timestamptz
+);
+
+comment on table crate_transfers is 'Requests to transfer the ownership of a crate to a single new owner';
+comment on column crate_transfers.id is 'Unique identifier of the `crate_transfers` row';
+comment on column crate_transfers.crate_id is 'ID of the crate that is transferred';
+comment on column crate_transfers.requested_by is 'ID of the crate owner that requested the transfer';
+comment on column crate_transfers.recipient_id is 'ID of the user that will become the only owner of the crate';
+comment on column crate_transfers.created_at is 'Date and time when the transfer was requested';
+comment on column crate_transfers.accepted_at is 'Date and time when the recipient accepted the transfer, or NULL if it is still pending';
+
+create unique index crate_transfers_pending_crate_id_uindex
+    on crate_transfers (crate_id)
+    where accepted_at is null;
+
+create index crate_transfers_recipient_id_index
+    on crate_transfers (recipient_id);
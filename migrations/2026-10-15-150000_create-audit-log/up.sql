@@ -0,0 +1,34 @@
+create table audit_log
+(
+    id           bigserial primary key,
+    user_id      integer
+        constraint audit_log_users_id_fk
+            references users
+            on delete set null,
+    api_token_id integer
+        constraint audit_log_api_tokens_id_fk
+            references api_tokens
+            on delete set null,
+    crate_id     integer
+        constraint audit_log_crates_id_fk
+            references crates
+            on delete set null,
+    action       integer     not null,
+    details      jsonb       not null default '{}'::jsonb,
+    created_at   timestamptz not null default now()
+);
+
+comment on table audit_log is 'Record of all mutating operations on crates and user accounts';
+comment on column audit_log.id is 'Unique identifier of the `audit_log` row';
+comment on column audit_log.user_id is 'ID of the user that performed the operation';
+comment on column audit_log.api_token_id is 'ID of the API token that was used to perform the operation, or NULL if a session cookie was used';
+comment on column audit_log.crate_id is 'ID of the crate that was affected by the operation, or NULL for account operations';
+comment on column audit_log.action is 'The kind of operation, see `AuditLogAction` for the possible values';
+comment on column audit_log.details is 'Additional information about the operation, like the affected version or owner';
+comment on column audit_log.created_at is 'Date and time when the operation was performed';
+
+create index audit_log_crate_id_index
+    on audit_log (crate_id, id desc);
+
+create index audit_log_user_id_index
+    on audit_log (user_id, id desc);
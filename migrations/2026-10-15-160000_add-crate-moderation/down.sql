@@ -0,0 +1,3 @@
+alter table crates
+    drop column frozen_at,
+    drop column hidden_at;
@@ -0,0 +1,6 @@
+alter table crates
+    add column hidden_at timestamptz,
+    add column frozen_at timestamptz;
+
+comment on column crates.hidden_at is 'Time at which the crate was hidden from search and listings by the crates.io team, or `NULL` if the crate is visible';
+comment on column crates.frozen_at is 'Time at which the crate was frozen by the crates.io team, or `NULL` if new versions can be published';
@@ -0,0 +1,42 @@
+create table crate_reports
+(
+    id          serial primary key,
+    crate_id    integer     not null
+        constraint crate_reports_crates_id_fk
+            references crates
+            on delete cascade,
+    reporter_id integer
+        constraint crate_reports_users_id_fk
+            references users
+            on delete set null,
+    category    integer     not null,
+    description text        not null,
+    contact     varchar,
+    status      integer     not null default 0,
+    resolution  text,
+    resolved_by integer
+        constraint crate_reports_resolved_by_fk
+            references users
+            on delete set null,
+    created_at  timestamptz not null default now(),
+    resolved_at timestamptz
+);
+
+comment on table crate_reports is 'Reports of malicious or abusive crates, submitted by users for triage by the crates.io team';
+comment on column crate_reports.id is 'Unique identifier of the `crate_reports` row';
+comment on column crate_reports.crate_id is 'ID of the reported crate';
+comment on column crate_reports.reporter_id is 'ID of the user that submitted the report';
+comment on column crate_reports.category is 'The kind of abuse, see `ReportCategory` for the possible values';
+comment on column crate_reports.description is 'Description of the problem, provided by the reporter';
+comment on column crate_reports.contact is 'Optional email address under which the reporter can be contacted about the report';
+comment on column crate_reports.status is 'Triage status of the report, see `ReportStatus` for the possible values';
+comment on column crate_reports.resolution is 'Explanation of how the report was resolved, which is sent to the reporter';
+comment on column crate_reports.resolved_by is 'ID of the admin that resolved the report';
+comment on column crate_reports.created_at is 'Date and time when the report was submitted';
+comment on column crate_reports.resolved_at is 'Date and time when the report was resolved or dismissed';
+
+create index crate_reports_status_index
+    on crate_reports (status, id);
+
+create index crate_reports_crate_id_index
+    on crate_reports (crate_id);
@@ -0,0 +1,2 @@
+alter table crate_reports
+    drop column score;
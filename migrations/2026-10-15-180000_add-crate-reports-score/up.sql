@@ -0,0 +1,4 @@
+alter table crate_reports
+    add column score integer;
+
+comment on column crate_reports.score is 'Severity score assigned by the automated malware scan, `NULL` for reports submitted by users';
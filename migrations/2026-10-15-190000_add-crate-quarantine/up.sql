@@ -0,0 +1,4 @@
+alter table crates
+    add column quarantined_at timestamptz;
+
+comment on column crates.quarantined_at is 'Time at which the crate was quarantined pending review by the crates.io team, or `NULL` if the crate is not quarantined';
@@ -0,0 +1 @@
+drop table db_dumps;
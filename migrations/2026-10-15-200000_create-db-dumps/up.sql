@@ -0,0 +1,22 @@
+create table db_dumps
+(
+    id         serial primary key,
+    kind       integer     not null,
+    path       varchar     not null,
+    size       bigint      not null,
+    checksum   varchar     not null,
+    since      timestamptz,
+    created_at timestamptz not null
+);
+
+comment on table db_dumps is 'Database dump archives that are available for download';
+comment on column db_dumps.id is 'Unique identifier of the `db_dumps` row';
+comment on column db_dumps.kind is 'Whether this is a full or an incremental dump, see `DbDumpKind` for the possible values';
+comment on column db_dumps.path is 'Path of the archive in the storage bucket';
+comment on column db_dumps.size is 'Size of the archive in bytes';
+comment on column db_dumps.checksum is 'Hex-encoded SHA256 checksum of the archive';
+comment on column db_dumps.since is 'Time of the previous dump, for incremental dumps that only contain the rows that changed since then';
+comment on column db_dumps.created_at is 'Time at which the dump was started';
+
+create index db_dumps_created_at_index
+    on db_dumps (created_at);
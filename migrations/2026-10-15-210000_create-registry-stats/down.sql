@@ -0,0 +1 @@
+drop table registry_stats;
@@ -0,0 +1,15 @@
+create table registry_stats
+(
+    date           date   primary key,
+    total_crates   bigint not null,
+    total_versions bigint not null,
+    publishes      bigint not null,
+    downloads      bigint not null
+);
+
+comment on table registry_stats is 'Pre-aggregated daily statistics of the whole registry, maintained by the `UpdateRegistryStats` background job';
+comment on column registry_stats.date is 'The day that the statistics are for';
+comment on column registry_stats.total_crates is 'Number of crates that existed at the end of the day';
+comment on column registry_stats.total_versions is 'Number of versions that existed at the end of the day';
+comment on column registry_stats.publishes is 'Number of versions that were published on this day';
+comment on column registry_stats.downloads is 'Number of downloads of all versions on this day';
@@ -0,0 +1,4 @@
+alter table versions
+    drop column docs_rs_status,
+    drop column docs_rs_doc_coverage,
+    drop column docs_rs_built_at;
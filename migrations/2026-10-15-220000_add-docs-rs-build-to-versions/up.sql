@@ -0,0 +1,8 @@
+alter table versions
+    add column docs_rs_status integer,
+    add column docs_rs_doc_coverage real,
+    add column docs_rs_built_at timestamptz;
+
+comment on column versions.docs_rs_status is 'Result of the most recent docs.rs build of this version, see `DocsRsStatus` for the possible values, or `NULL` if no build result has been received yet';
+comment on column versions.docs_rs_doc_coverage is 'Percentage of documented items reported by the most recent docs.rs build, or `NULL` if not available';
+comment on column versions.docs_rs_built_at is 'Time at which the most recent docs.rs build result was received';
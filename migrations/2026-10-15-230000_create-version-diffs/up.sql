@@ -0,0 +1,24 @@
+create table version_diffs
+(
+    version_id          integer     primary key references versions (id) on delete cascade,
+    previous_version_id integer     not null references versions (id) on delete cascade,
+    files_added         integer     not null,
+    files_removed       integer     not null,
+    files_modified      integer     not null,
+    size_delta          bigint      not null,
+    files               jsonb       not null,
+    created_at          timestamptz not null default now()
+);
+
+comment on table version_diffs is 'Summary of the changes in the `.crate` file of a version compared to the previous release, maintained by the `ComputeVersionDiff` background job';
+comment on column version_diffs.version_id is 'The version that was compared to the previous release';
+comment on column version_diffs.previous_version_id is 'The previous release that the version was compared to';
+comment on column version_diffs.files_added is 'Number of files that only exist in the new version';
+comment on column version_diffs.files_removed is 'Number of files that only exist in the previous release';
+comment on column version_diffs.files_modified is 'Number of files that exist in both versions, but with different contents';
+comment on column version_diffs.size_delta is 'Difference of the total unpacked size of all files in bytes';
+comment on column version_diffs.files is 'JSON array of the added, removed and modified files, with their path, status and size difference';
+comment on column version_diffs.created_at is 'Time at which the diff was computed';
+
+create index version_diffs_previous_version_id_index
+    on version_diffs (previous_version_id);
@@ -0,0 +1,9 @@
+create table version_files
+(
+    version_id integer primary key references versions (id) on delete cascade,
+    files      jsonb   not null
+);
+
+comment on table version_files is 'Listing of the files in the `.crate` file of a version, saved when the version is published';
+comment on column version_files.version_id is 'The version that the files belong to';
+comment on column version_files.files is 'JSON array of the files in the `.crate` file, with their path relative to the package root and their size in bytes';
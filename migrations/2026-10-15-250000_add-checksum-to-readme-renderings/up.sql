@@ -0,0 +1,4 @@
+alter table readme_renderings
+    add column checksum varchar;
+
+comment on column readme_renderings.checksum is 'Hex-encoded SHA256 checksum of the rendered readme, which is stored under this checksum so that identical readmes of different versions are only stored once. `NULL` for readmes that were stored per version before.';
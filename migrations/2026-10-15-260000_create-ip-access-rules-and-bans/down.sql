@@ -0,0 +1,2 @@
+drop table ip_bans;
+drop table ip_access_rules;
@@ -0,0 +1,45 @@
+create table ip_access_rules
+(
+    id         serial primary key,
+    network    varchar     not null,
+    kind       integer     not null,
+    reason     text,
+    created_by integer
+        constraint ip_access_rules_created_by_fk
+            references users
+            on delete set null,
+    created_at timestamptz not null default now(),
+    constraint ip_access_rules_network_unique unique (network)
+);
+
+comment on table ip_access_rules is 'Permanent rules that exempt IP networks from the abuse throttling, or deny them access to the API';
+comment on column ip_access_rules.id is 'Unique identifier of the `ip_access_rules` row';
+comment on column ip_access_rules.network is 'The IP network in CIDR notation, e.g. `192.0.2.0/24`';
+comment on column ip_access_rules.kind is 'Whether the network is allowed or denied, see `IpAccessRuleKind` for the possible values';
+comment on column ip_access_rules.reason is 'Explanation of why the rule was added';
+comment on column ip_access_rules.created_by is 'ID of the admin that added the rule';
+comment on column ip_access_rules.created_at is 'Date and time when the rule was added';
+
+create table ip_bans
+(
+    id           serial primary key,
+    network      varchar     not null,
+    strikes      integer     not null default 1,
+    reason       text        not null,
+    banned_until timestamptz not null,
+    created_at   timestamptz not null default now(),
+    updated_at   timestamptz not null default now(),
+    constraint ip_bans_network_unique unique (network)
+);
+
+comment on table ip_bans is 'Temporary bans of IP networks that were detected by the abuse throttling';
+comment on column ip_bans.id is 'Unique identifier of the `ip_bans` row';
+comment on column ip_bans.network is 'The IP network in CIDR notation, e.g. `192.0.2.0/24`';
+comment on column ip_bans.strikes is 'Number of times the network has been banned, which determines the duration of the next ban';
+comment on column ip_bans.reason is 'Explanation of why the network was banned most recently';
+comment on column ip_bans.banned_until is 'Date and time when the current ban expires';
+comment on column ip_bans.created_at is 'Date and time when the network was banned for the first time';
+comment on column ip_bans.updated_at is 'Date and time when the network was banned most recently';
+
+create index ip_bans_banned_until_index
+    on ip_bans (banned_until);
@@ -0,0 +1,16 @@
+create table feature_flags
+(
+    name       varchar     not null primary key,
+    enabled    boolean     not null default false,
+    updated_by integer
+        constraint feature_flags_updated_by_fk
+            references users
+            on delete set null,
+    updated_at timestamptz not null default now()
+);
+
+comment on table feature_flags is 'Flags that switch application features on or off at runtime, e.g. the read-only maintenance mode';
+comment on column feature_flags.name is 'Unique name of the feature flag, e.g. `read_only_mode`';
+comment on column feature_flags.enabled is 'Whether the feature is currently switched on';
+comment on column feature_flags.updated_by is 'ID of the admin that changed the flag most recently';
+comment on column feature_flags.updated_at is 'Date and time when the flag was changed most recently';
@@ -0,0 +1,15 @@
+drop table feature_overrides;
+
+alter table features
+    drop column rollout_percentage,
+    drop column description;
+
+alter table features rename constraint features_updated_by_fk to feature_flags_updated_by_fk;
+alter table features rename constraint features_pkey to feature_flags_pkey;
+alter table features rename to feature_flags;
+
+comment on table feature_flags is 'Flags that switch application features on or off at runtime, e.g. the read-only maintenance mode';
+comment on column feature_flags.name is 'Unique name of the feature flag, e.g. `read_only_mode`';
+comment on column feature_flags.enabled is 'Whether the feature is currently switched on';
+comment on column feature_flags.updated_by is 'ID of the admin that changed the flag most recently';
+comment on column feature_flags.updated_at is 'Date and time when the flag was changed most recently';
@@ -0,0 +1,39 @@
+alter table feature_flags rename to features;
+alter table features rename constraint feature_flags_pkey to features_pkey;
+alter table features rename constraint feature_flags_updated_by_fk to features_updated_by_fk;
+
+alter table features
+    add column description        text,
+    add column rollout_percentage smallint not null default 0
+        constraint features_rollout_percentage_check
+            check (rollout_percentage between 0 and 100);
+
+comment on table features is 'Feature flags that switch application features on or off at runtime, either for all users or gradually for a percentage of them';
+comment on column features.name is 'Unique name of the feature, e.g. `read_only_mode`';
+comment on column features.enabled is 'Whether the feature is switched on for all users, regardless of the rollout percentage';
+comment on column features.updated_by is 'ID of the admin that changed the feature most recently';
+comment on column features.updated_at is 'Date and time when the feature was changed most recently';
+comment on column features.description is 'Explanation of the behavior that the feature changes';
+comment on column features.rollout_percentage is 'Percentage of users for which the feature is switched on, if it is not enabled for all users';
+
+create table feature_overrides
+(
+    feature    varchar     not null
+        constraint feature_overrides_feature_fk
+            references features
+            on update cascade
+            on delete cascade,
+    user_id    integer     not null
+        constraint feature_overrides_user_id_fk
+            references users
+            on delete cascade,
+    enabled    boolean     not null,
+    created_at timestamptz not null default now(),
+    primary key (feature, user_id)
+);
+
+comment on table feature_overrides is 'Per-user overrides of feature flags, which take precedence over the rollout of the feature';
+comment on column feature_overrides.feature is 'Name of the overridden feature';
+comment on column feature_overrides.user_id is 'ID of the user for which the feature is overridden';
+comment on column feature_overrides.enabled is 'Whether the feature is switched on or off for the user';
+comment on column feature_overrides.created_at is 'Date and time when the override was added';
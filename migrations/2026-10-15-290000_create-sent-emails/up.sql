@@ -0,0 +1,11 @@
+create table sent_emails
+(
+    idempotency_key varchar     not null primary key,
+    message_id      varchar,
+    created_at      timestamptz not null default now()
+);
+
+comment on table sent_emails is 'Emails that were sent with an idempotency key, to avoid sending the same notification more than once';
+comment on column sent_emails.idempotency_key is 'SHA-256 hash of the idempotency key, so that keys derived from secret tokens are not stored in plain text';
+comment on column sent_emails.message_id is 'ID of the message, as reported by the email provider';
+comment on column sent_emails.created_at is 'Date and time when the email was sent';
@@ -0,0 +1,4 @@
+alter table users
+    add column locale varchar;
+
+comment on column users.locale is 'Preferred language of the user for emails, e.g. `de`. English is used if this is not set.';
@@ -0,0 +1,4 @@
+alter table users
+    add column weekly_digest boolean not null default false;
+
+comment on column users.weekly_digest is 'Whether the user wants to receive a weekly email summarizing the activity of their crates';
@@ -0,0 +1,4 @@
+alter table follows
+    drop column notify_email,
+    drop column webhook_url,
+    drop column notified_at;
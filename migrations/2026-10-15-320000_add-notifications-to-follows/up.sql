@@ -0,0 +1,8 @@
+alter table follows
+    add column notify_email boolean     not null default false,
+    add column webhook_url  varchar,
+    add column notified_at  timestamptz not null default now();
+
+comment on column follows.notify_email is 'Whether the user wants to receive emails when the crate publishes or yanks a version';
+comment on column follows.webhook_url is 'URL that is notified when the crate publishes or yanks a version';
+comment on column follows.notified_at is 'Date and time up to which the user has been notified about new versions and yanks of the crate';
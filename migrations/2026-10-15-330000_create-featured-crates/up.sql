@@ -0,0 +1,29 @@
+create table featured_crates
+(
+    id         serial primary key,
+    crate_id   integer     not null
+        constraint featured_crates_crate_id_fk
+            references crates
+            on delete cascade,
+    starts_at  timestamptz not null default now(),
+    ends_at    timestamptz,
+    note       text,
+    created_by integer
+        constraint featured_crates_created_by_fk
+            references users
+            on delete set null,
+    created_at timestamptz not null default now(),
+    constraint featured_crates_dates_check check (ends_at is null or ends_at > starts_at)
+);
+
+comment on table featured_crates is 'Crates that the crates.io team picked to be highlighted on the front page for a limited time';
+comment on column featured_crates.id is 'Unique identifier of the `featured_crates` row';
+comment on column featured_crates.crate_id is 'ID of the highlighted crate';
+comment on column featured_crates.starts_at is 'Date and time when the crate starts being highlighted';
+comment on column featured_crates.ends_at is 'Date and time when the crate stops being highlighted, or `NULL` if it is highlighted until the row is deleted';
+comment on column featured_crates.note is 'Internal explanation of why the crate was picked';
+comment on column featured_crates.created_by is 'ID of the admin that picked the crate';
+comment on column featured_crates.created_at is 'Date and time when the crate was picked';
+
+create index featured_crates_starts_at_ends_at_index
+    on featured_crates (starts_at, ends_at);
@@ -0,0 +1 @@
+drop table keyword_aliases;
@@ -0,0 +1,22 @@
+create table keyword_aliases
+(
+    alias      text        primary key,
+    keyword_id integer     not null
+        constraint keyword_aliases_keyword_id_fk
+            references keywords
+            on delete cascade,
+    created_by integer
+        constraint keyword_aliases_created_by_fk
+            references users
+            on delete set null,
+    created_at timestamptz not null default now()
+);
+
+comment on table keyword_aliases is 'Alternative names of keywords, e.g. for keywords that were merged into another keyword';
+comment on column keyword_aliases.alias is 'The lowercase alternative name, which must not be used by a keyword';
+comment on column keyword_aliases.keyword_id is 'ID of the keyword that the alias redirects to';
+comment on column keyword_aliases.created_by is 'ID of the admin that created the alias';
+comment on column keyword_aliases.created_at is 'Date and time when the alias was created';
+
+create index keyword_aliases_keyword_id_index
+    on keyword_aliases (keyword_id);
@@ -0,0 +1,17 @@
+create table category_suggestions
+(
+    crate_id    integer     not null references crates (id) on delete cascade,
+    category_id integer     not null references categories (id) on delete cascade,
+    score       real        not null,
+    created_at  timestamptz not null default now(),
+    primary key (crate_id, category_id)
+);
+
+comment on table category_suggestions is 'Categories that are suggested to the owners of crates without categories, maintained by the `SuggestCategories` background job';
+comment on column category_suggestions.crate_id is 'The crate that the category is suggested for';
+comment on column category_suggestions.category_id is 'The suggested category';
+comment on column category_suggestions.score is 'How well the description and keywords of the crate match the category, higher is better';
+comment on column category_suggestions.created_at is 'Time at which the suggestion was made';
+
+create index category_suggestions_category_id_index
+    on category_suggestions (category_id);
@@ -0,0 +1 @@
+drop table reserved_prefixes;
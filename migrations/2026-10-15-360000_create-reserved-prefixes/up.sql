@@ -0,0 +1,34 @@
+create table reserved_prefixes
+(
+    id              serial primary key,
+    organization_id integer     not null
+        constraint reserved_prefixes_organization_id_fk
+            references organizations
+            on delete cascade,
+    prefix          text        not null,
+    requested_by    integer
+        constraint reserved_prefixes_requested_by_fk
+            references users
+            on delete set null,
+    approved_by     integer
+        constraint reserved_prefixes_approved_by_fk
+            references users
+            on delete set null,
+    approved_at     timestamptz,
+    created_at      timestamptz not null default now()
+);
+
+comment on table reserved_prefixes is 'Crate name prefixes that organizations requested to reserve, so that only their members can publish new crates with these prefixes once approved by an admin';
+comment on column reserved_prefixes.id is 'Unique identifier of the `reserved_prefixes` row';
+comment on column reserved_prefixes.organization_id is 'ID of the organization that reserved the prefix';
+comment on column reserved_prefixes.prefix is 'The lowercase crate name prefix, ending with `-` or `_`, e.g. `aws-sdk-`';
+comment on column reserved_prefixes.requested_by is 'ID of the organization admin that requested the reservation';
+comment on column reserved_prefixes.approved_by is 'ID of the crates.io admin that approved the reservation';
+comment on column reserved_prefixes.approved_at is 'Date and time when the reservation was approved, or `NULL` if it is still pending';
+comment on column reserved_prefixes.created_at is 'Date and time when the reservation was requested';
+
+create unique index reserved_prefixes_prefix_index
+    on reserved_prefixes (canon_crate_name(prefix));
+
+create index reserved_prefixes_organization_id_index
+    on reserved_prefixes (organization_id);
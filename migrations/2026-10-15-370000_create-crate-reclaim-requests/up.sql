@@ -0,0 +1,39 @@
+create table crate_reclaim_requests
+(
+    id           serial primary key,
+    crate_id     integer     not null
+        constraint crate_reclaim_requests_crates_id_fk
+            references crates
+            on delete cascade,
+    requester_id integer     not null
+        constraint crate_reclaim_requests_users_id_fk
+            references users
+            on delete cascade,
+    reason       text        not null,
+    status       integer     not null default 0,
+    decision     text,
+    decided_by   integer
+        constraint crate_reclaim_requests_decided_by_fk
+            references users
+            on delete set null,
+    created_at   timestamptz not null default now(),
+    decided_at   timestamptz
+);
+
+comment on table crate_reclaim_requests is 'Requests to take over the name of an unused crate, which are decided by the crates.io team after a waiting period';
+comment on column crate_reclaim_requests.id is 'Unique identifier of the `crate_reclaim_requests` row';
+comment on column crate_reclaim_requests.crate_id is 'ID of the crate whose name is requested';
+comment on column crate_reclaim_requests.requester_id is 'ID of the user that wants to become the owner of the crate';
+comment on column crate_reclaim_requests.reason is 'Explanation of why the crate is unused and what the requester plans to do with the name';
+comment on column crate_reclaim_requests.status is 'Status of the request, see `ReclaimStatus` for the possible values';
+comment on column crate_reclaim_requests.decision is 'Explanation of the decision, which is sent to the requester';
+comment on column crate_reclaim_requests.decided_by is 'ID of the admin that approved or rejected the request';
+comment on column crate_reclaim_requests.created_at is 'Date and time when the request was submitted';
+comment on column crate_reclaim_requests.decided_at is 'Date and time when the request was approved or rejected';
+
+create unique index crate_reclaim_requests_pending_index
+    on crate_reclaim_requests (crate_id, requester_id)
+    where status = 0;
+
+create index crate_reclaim_requests_status_index
+    on crate_reclaim_requests (status, id);
@@ -0,0 +1,7 @@
+comment on table reserved_crate_names is null;
+comment on column reserved_crate_names.name is null;
+
+alter table reserved_crate_names
+    drop column reason,
+    drop column created_by,
+    drop column created_at;
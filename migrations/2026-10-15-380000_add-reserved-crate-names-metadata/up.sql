@@ -0,0 +1,13 @@
+alter table reserved_crate_names
+    add column reason     text,
+    add column created_by integer
+        constraint reserved_crate_names_created_by_fk
+            references users
+            on delete set null,
+    add column created_at timestamptz not null default now();
+
+comment on table reserved_crate_names is 'Crate names that can not be used for new crates, e.g. names of the standard library crates or names that are reserved on Windows';
+comment on column reserved_crate_names.name is 'The reserved crate name, which is compared to other crate names after canonicalization';
+comment on column reserved_crate_names.reason is 'Why the name is reserved, for the crates.io team';
+comment on column reserved_crate_names.created_by is 'ID of the admin that reserved the name, or `NULL` for names that were reserved by migrations';
+comment on column reserved_crate_names.created_at is 'Date and time when the name was reserved';
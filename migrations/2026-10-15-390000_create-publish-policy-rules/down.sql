@@ -0,0 +1 @@
+drop table publish_policy_rules;
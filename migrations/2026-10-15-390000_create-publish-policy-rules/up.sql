@@ -0,0 +1,25 @@
+create table publish_policy_rules
+(
+    id         serial primary key,
+    kind       integer     not null,
+    value      text        not null,
+    message    text,
+    created_by integer
+        constraint publish_policy_rules_created_by_fk
+            references users
+            on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now()
+);
+
+comment on table publish_policy_rules is 'Rules that all crates have to follow to be published, managed by the crates.io team';
+comment on column publish_policy_rules.id is 'Unique identifier of the `publish_policy_rules` row';
+comment on column publish_policy_rules.kind is 'The kind of the rule: 0 = maximum number of dependencies, 1 = maximum number of features, 2 = banned license, 3 = required manifest field';
+comment on column publish_policy_rules.value is 'The parameter of the rule, e.g. the maximum number, the SPDX identifier of the banned license or the name of the required field';
+comment on column publish_policy_rules.message is 'Additional explanation that is shown to users when their crate violates the rule';
+comment on column publish_policy_rules.created_by is 'ID of the admin that created the rule';
+comment on column publish_policy_rules.created_at is 'Date and time when the rule was created';
+comment on column publish_policy_rules.updated_at is 'Date and time when the rule was last changed';
+
+create unique index publish_policy_rules_kind_value_index
+    on publish_policy_rules (kind, value);
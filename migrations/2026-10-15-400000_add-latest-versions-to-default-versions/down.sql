@@ -0,0 +1,3 @@
+alter table default_versions
+    drop column latest_stable_version_id,
+    drop column latest_prerelease_version_id;
@@ -0,0 +1,12 @@
+alter table default_versions
+    add column latest_stable_version_id     integer
+        constraint default_versions_latest_stable_version_id_fk
+            references versions
+            on delete set null,
+    add column latest_prerelease_version_id integer
+        constraint default_versions_latest_prerelease_version_id_fk
+            references versions
+            on delete set null;
+
+comment on column default_versions.latest_stable_version_id is 'Reference to the highest version in the `versions` table that is neither yanked nor a pre-release, if there is one.';
+comment on column default_versions.latest_prerelease_version_id is 'Reference to the highest pre-release version in the `versions` table that is not yanked, if there is one.';
@@ -0,0 +1,4 @@
+drop index versions_crate_id_semver_ord_index;
+
+alter table versions
+    drop column semver_ord;
@@ -0,0 +1,7 @@
+alter table versions
+    add column semver_ord text collate "C";
+
+comment on column versions.semver_ord is 'Key that sorts in the same order as the semver precedence of `num` when compared bytewise, or NULL if `num` is not a valid semver version or the key has not been backfilled yet.';
+
+create index versions_crate_id_semver_ord_index
+    on versions (crate_id, semver_ord desc nulls last, id desc);
@@ -0,0 +1,4 @@
+alter table background_jobs
+    add column enqueue_context jsonb;
+
+comment on column background_jobs.enqueue_context is 'Information about the request or job that enqueued this job, e.g. its Sentry trace, which is attached to the error reports of this job.';
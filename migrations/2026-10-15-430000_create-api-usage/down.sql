@@ -0,0 +1,2 @@
+drop table api_usage_by_crate;
+drop table api_usage_by_user;
@@ -0,0 +1,35 @@
+create table api_usage_by_user
+(
+    user_id integer not null
+        constraint api_usage_by_user_users_id_fk
+            references users
+            on delete cascade,
+    date    date    not null,
+    kind    text    not null,
+    count   bigint  not null default 0,
+    primary key (user_id, date, kind)
+);
+
+comment on table api_usage_by_user is 'Number of authenticated API requests per user and day, which are counted in memory by the API servers and added to this table periodically';
+comment on column api_usage_by_user.user_id is 'ID of the user that sent the requests';
+comment on column api_usage_by_user.date is 'The day on which the requests were sent';
+comment on column api_usage_by_user.kind is 'How the requests were authenticated, either `cookie` or `token`';
+comment on column api_usage_by_user.count is 'Number of requests on this day';
+
+create table api_usage_by_crate
+(
+    crate_id integer not null
+        constraint api_usage_by_crate_crates_id_fk
+            references crates
+            on delete cascade,
+    date     date    not null,
+    kind     text    not null,
+    count    bigint  not null default 0,
+    primary key (crate_id, date, kind)
+);
+
+comment on table api_usage_by_crate is 'Number of API requests per crate and day, which are counted in memory by the API servers and added to this table periodically';
+comment on column api_usage_by_crate.crate_id is 'ID of the crate that the requests were about';
+comment on column api_usage_by_crate.date is 'The day on which the requests were sent';
+comment on column api_usage_by_crate.kind is 'The kind of the requests, either `download` for downloads through the API, or `metadata` for the other read requests of the crate';
+comment on column api_usage_by_crate.count is 'Number of requests on this day';
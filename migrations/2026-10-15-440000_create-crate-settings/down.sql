@@ -0,0 +1 @@
+drop table crate_settings;
@@ -0,0 +1,18 @@
+create table crate_settings
+(
+    crate_id      integer     not null primary key
+        constraint crate_settings_crates_id_fk
+            references crates
+            on delete cascade,
+    digest_emails boolean     not null default true,
+    version_diffs boolean     not null default true,
+    internal      boolean     not null default false,
+    updated_at    timestamptz not null default now()
+);
+
+comment on table crate_settings is 'Settings of a crate that can be changed by its owners. Crates without a row use the default values of the columns';
+comment on column crate_settings.crate_id is 'ID of the crate';
+comment on column crate_settings.digest_emails is 'Whether the crate is included in the weekly digest emails of its owners';
+comment on column crate_settings.version_diffs is 'Whether the files of new versions are compared to the previous release by the `ComputeVersionDiff` background job';
+comment on column crate_settings.internal is 'Whether the crate is an internal or test crate, which is excluded from the default crate search';
+comment on column crate_settings.updated_at is 'Date and time when the settings were last changed';
@@ -0,0 +1,5 @@
+drop trigger trigger_crates_reset_repository_verified_at on crates;
+drop function reset_repository_verified_at();
+
+alter table crates
+    drop column repository_verified_at;
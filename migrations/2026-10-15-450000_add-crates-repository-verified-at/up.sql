@@ -0,0 +1,21 @@
+alter table crates
+    add column repository_verified_at timestamptz;
+
+comment on column crates.repository_verified_at is 'Time at which an owner of the crate proved that they have push access to the `repository`, or `NULL` if the repository has not been verified';
+
+-- a verification is only valid for the repository that was verified
+create function reset_repository_verified_at() returns trigger as
+$$
+begin
+    if new.repository is distinct from old.repository then
+        new.repository_verified_at := null;
+    end if;
+    return new;
+end
+$$ language plpgsql;
+
+create trigger trigger_crates_reset_repository_verified_at
+    before update of repository
+    on crates
+    for each row
+execute procedure reset_repository_verified_at();
@@ -0,0 +1,17 @@
+create table crate_funding_links
+(
+    crate_id integer  not null
+        constraint crate_funding_links_crates_id_fk
+            references crates
+            on delete cascade,
+    position smallint not null,
+    platform integer  not null,
+    url      text     not null,
+    primary key (crate_id, position)
+);
+
+comment on table crate_funding_links is 'Funding links of a crate, taken from the manifest or the `FUNDING.yml` file of the most recently published version';
+comment on column crate_funding_links.crate_id is 'ID of the crate';
+comment on column crate_funding_links.position is 'Position of the link in the list of links of the crate, starting at 0';
+comment on column crate_funding_links.platform is 'The funding platform of the link, see `FundingPlatform` for the possible values';
+comment on column crate_funding_links.url is 'URL of the funding page on the platform';
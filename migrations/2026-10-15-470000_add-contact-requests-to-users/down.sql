@@ -0,0 +1 @@
+alter table users drop column contact_requests;
@@ -0,0 +1,4 @@
+alter table users
+    add column contact_requests boolean not null default false;
+
+comment on column users.contact_requests is 'Whether the user wants to receive messages from other users about their crates through the `contact_owners` endpoint';
@@ -0,0 +1,31 @@
+drop function archive_version_downloads_partition(date);
+drop function create_version_downloads_partition(date);
+
+drop materialized view recent_crate_downloads;
+
+-- Move all rows back into the default partition, which becomes the regular
+-- `version_downloads` table again.
+alter table version_downloads detach partition partitions.version_downloads_default;
+
+insert into partitions.version_downloads_default (version_id, downloads, counted, date, processed)
+    select version_id, downloads, counted, date, processed from version_downloads
+    union all
+    select version_id, downloads, counted, date, processed from version_downloads_archive;
+
+drop table version_downloads_archive;
+drop table version_downloads;
+
+alter table partitions.version_downloads_default rename to version_downloads;
+alter table partitions.version_downloads set schema public;
+
+drop schema partitions;
+
+create materialized view recent_crate_downloads (crate_id, downloads) as
+    select crate_id, sum(version_downloads.downloads) from version_downloads
+        inner join versions
+            on version_downloads.version_id = versions.id
+        where version_downloads.date > date(current_timestamp - interval '90 days')
+        group by crate_id;
+
+create unique index recent_crate_downloads_crate_id on recent_crate_downloads (crate_id);
+create index index_recent_crate_downloads_by_downloads on recent_crate_downloads using btree (downloads);
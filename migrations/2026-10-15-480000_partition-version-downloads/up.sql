@@ -0,0 +1,117 @@
+-- The partitions of the `version_downloads` tables live in a separate schema,
+-- so that they don't show up next to the regular tables, e.g. in the
+-- database dumps.
+create schema partitions;
+
+comment on schema partitions is 'Partitions of the partitioned tables in the `public` schema';
+
+-- The materialized view depends on the table that is turned into the default
+-- partition below, so it has to be recreated on top of the partitioned table.
+drop materialized view recent_crate_downloads;
+
+-- The existing table becomes the default partition of the partitioned table.
+-- Its rows are moved into monthly partitions by the
+-- `PartitionVersionDownloads` background job.
+alter table version_downloads set schema partitions;
+alter table partitions.version_downloads rename to version_downloads_default;
+
+create table version_downloads (
+    version_id integer not null
+        constraint fk_version_downloads_version_id references versions (id) on delete cascade,
+    downloads integer not null default 1,
+    counted integer not null default 0,
+    date date not null default current_date,
+    processed boolean not null default false,
+    constraint version_downloads_pkey primary key (version_id, date)
+) partition by range (date);
+
+comment on table version_downloads is 'Number of downloads per version and day, partitioned by month. Months that have been archived are moved to the `version_downloads_archive` table.';
+
+-- These match the existing indexes of the default partition, so they are
+-- attached instead of being rebuilt.
+create index index_version_downloads_date on version_downloads using brin (date) with (pages_per_range = 1);
+create index index_version_downloads_not_processed on version_downloads (processed) where not processed;
+
+alter table version_downloads attach partition partitions.version_downloads_default default;
+
+create table version_downloads_archive (
+    version_id integer not null
+        constraint fk_version_downloads_archive_version_id references versions (id) on delete cascade,
+    downloads integer not null default 1,
+    counted integer not null default 0,
+    date date not null default current_date,
+    processed boolean not null default false,
+    constraint version_downloads_archive_pkey primary key (version_id, date)
+) partition by range (date);
+
+comment on table version_downloads_archive is 'Monthly partitions of the `version_downloads` table that have been exported to the downloads archive. Their rows no longer change.';
+
+create materialized view recent_crate_downloads (crate_id, downloads) as
+    select crate_id, sum(version_downloads.downloads) from version_downloads
+        inner join versions
+            on version_downloads.version_id = versions.id
+        where version_downloads.date > date(current_timestamp - interval '90 days')
+        group by crate_id;
+
+create unique index recent_crate_downloads_crate_id on recent_crate_downloads (crate_id);
+create index index_recent_crate_downloads_by_downloads on recent_crate_downloads using btree (downloads);
+
+-- Creates the partition of the `version_downloads` table for the month of the
+-- given date, unless it exists already. Rows of the month that ended up in the
+-- default partition are moved to the new partition.
+create function create_version_downloads_partition(month date) returns void as $$
+declare
+    start_date date := date_trunc('month', month);
+    end_date date := date_trunc('month', month) + interval '1 month';
+    partition_name text := 'version_downloads_' || to_char(month, 'YYYY_MM');
+begin
+    if to_regclass(format('partitions.%I', partition_name)) is not null then
+        return;
+    end if;
+
+    -- Prevent new rows for the month from ending up in the default partition
+    -- until the new partition is attached.
+    lock table partitions.version_downloads_default in access exclusive mode;
+
+    execute format(
+        'create table partitions.%I (like version_downloads including defaults)',
+        partition_name
+    );
+
+    execute format(
+        'with moved as (
+            delete from partitions.version_downloads_default
+            where date >= %L and date < %L
+            returning version_id, downloads, counted, date, processed
+        )
+        insert into partitions.%I (version_id, downloads, counted, date, processed)
+        select version_id, downloads, counted, date, processed from moved',
+        start_date, end_date, partition_name
+    );
+
+    execute format(
+        'alter table version_downloads attach partition partitions.%I for values from (%L) to (%L)',
+        partition_name, start_date, end_date
+    );
+end;
+$$ language plpgsql;
+
+-- Moves the partition of the `version_downloads` table for the month of the
+-- given date to the `version_downloads_archive` table.
+create function archive_version_downloads_partition(month date) returns void as $$
+declare
+    start_date date := date_trunc('month', month);
+    end_date date := date_trunc('month', month) + interval '1 month';
+    partition_name text := 'version_downloads_' || to_char(month, 'YYYY_MM');
+begin
+    execute format(
+        'alter table version_downloads detach partition partitions.%I',
+        partition_name
+    );
+
+    execute format(
+        'alter table version_downloads_archive attach partition partitions.%I for values from (%L) to (%L)',
+        partition_name, start_date, end_date
+    );
+end;
+$$ language plpgsql;
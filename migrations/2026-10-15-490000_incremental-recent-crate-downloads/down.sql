@@ -0,0 +1,19 @@
+drop function expire_recent_crate_downloads(date);
+
+drop table recent_crate_downloads;
+
+alter table metadata drop column recent_downloads_start;
+
+create materialized view recent_crate_downloads (crate_id, downloads) as
+    select crate_id, sum(version_downloads.downloads) from version_downloads
+        inner join versions
+            on version_downloads.version_id = versions.id
+        where version_downloads.date > date(current_timestamp - interval '90 days')
+        group by crate_id;
+
+create unique index recent_crate_downloads_crate_id on recent_crate_downloads (crate_id);
+create index index_recent_crate_downloads_by_downloads on recent_crate_downloads using btree (downloads);
+
+create function refresh_recent_crate_downloads() returns void as $$
+    refresh materialized view concurrently recent_crate_downloads;
+$$ language sql;
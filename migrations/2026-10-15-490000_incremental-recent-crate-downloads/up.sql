@@ -0,0 +1,64 @@
+drop materialized view recent_crate_downloads;
+drop function refresh_recent_crate_downloads();
+
+-- The first day of the 90 day window is stored next to the total download
+-- count, so that the `update_downloads` job only adds downloads that are
+-- still inside of the window.
+alter table metadata
+    add column recent_downloads_start date not null default current_date - 89;
+
+comment on column metadata.recent_downloads_start is 'The oldest date whose downloads are included in the `recent_crate_downloads` table. Moved forward by the `UpdateRecentCrateDownloads` background job.';
+
+create table recent_crate_downloads (
+    crate_id integer not null primary key
+        references crates (id) on delete cascade,
+    downloads bigint not null default 0
+);
+
+comment on table recent_crate_downloads is 'Number of downloads per crate in the last 90 days. Incremented by the `update_downloads` job and decremented by the `UpdateRecentCrateDownloads` job once a day leaves the window.';
+comment on column recent_crate_downloads.crate_id is 'Reference to the crate';
+comment on column recent_crate_downloads.downloads is 'Number of counted downloads of the crate since `metadata.recent_downloads_start`';
+
+create index index_recent_crate_downloads_by_downloads on recent_crate_downloads using btree (downloads);
+
+insert into recent_crate_downloads (crate_id, downloads)
+    select versions.crate_id, sum(version_downloads.counted)
+    from version_downloads
+    inner join versions on versions.id = version_downloads.version_id
+    where version_downloads.date >= (select recent_downloads_start from metadata)
+    group by versions.crate_id
+    having sum(version_downloads.counted) > 0;
+
+-- Removes the downloads of the given day from the `recent_crate_downloads`
+-- table, unless they have been removed already. Days have to be removed in
+-- order, starting with `metadata.recent_downloads_start`.
+create function expire_recent_crate_downloads(day date) returns void as $$
+begin
+    -- Locking the `metadata` row also prevents the `update_downloads` job
+    -- from adding downloads at the same time.
+    update metadata
+    set recent_downloads_start = day + 1
+    where recent_downloads_start = day;
+
+    if not found then
+        return;
+    end if;
+
+    with expired_downloads as (
+        select versions.crate_id, sum(expired.counted) as downloads
+        from (
+            select version_id, counted from version_downloads where date = day
+            union all
+            select version_id, counted from version_downloads_archive where date = day
+        ) expired
+        inner join versions on versions.id = expired.version_id
+        group by versions.crate_id
+    )
+    update recent_crate_downloads
+    set downloads = recent_crate_downloads.downloads - expired_downloads.downloads
+    from expired_downloads
+    where recent_crate_downloads.crate_id = expired_downloads.crate_id;
+
+    delete from recent_crate_downloads where downloads <= 0;
+end;
+$$ language plpgsql;
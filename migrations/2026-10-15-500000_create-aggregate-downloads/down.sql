@@ -0,0 +1,2 @@
+drop table category_downloads;
+drop table keyword_downloads;
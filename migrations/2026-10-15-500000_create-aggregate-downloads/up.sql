@@ -0,0 +1,25 @@
+create table keyword_downloads
+(
+    keyword_id integer not null references keywords (id) on delete cascade,
+    date       date    not null,
+    downloads  bigint  not null,
+    primary key (keyword_id, date)
+);
+
+comment on table keyword_downloads is 'Pre-aggregated daily downloads of all crates with a keyword, maintained by the `UpdateAggregateDownloads` background job';
+comment on column keyword_downloads.keyword_id is 'Reference to the keyword';
+comment on column keyword_downloads.date is 'The day that the downloads are for';
+comment on column keyword_downloads.downloads is 'Number of downloads of all crates with the keyword on this day';
+
+create table category_downloads
+(
+    category_id integer not null references categories (id) on delete cascade,
+    date        date    not null,
+    downloads   bigint  not null,
+    primary key (category_id, date)
+);
+
+comment on table category_downloads is 'Pre-aggregated daily downloads of all crates in a category, maintained by the `UpdateAggregateDownloads` background job';
+comment on column category_downloads.category_id is 'Reference to the category';
+comment on column category_downloads.date is 'The day that the downloads are for';
+comment on column category_downloads.downloads is 'Number of downloads of all crates in the category on this day';
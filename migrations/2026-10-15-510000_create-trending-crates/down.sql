@@ -0,0 +1 @@
+drop table trending_crates;
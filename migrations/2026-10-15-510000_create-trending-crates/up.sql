@@ -0,0 +1,20 @@
+create table trending_crates
+(
+    window_days        integer          not null,
+    crate_id           integer          not null references crates (id) on delete cascade,
+    rank               integer          not null,
+    downloads          bigint           not null,
+    previous_downloads bigint           not null,
+    growth             double precision not null,
+    primary key (window_days, crate_id)
+);
+
+create index trending_crates_window_days_rank on trending_crates (window_days, rank);
+
+comment on table trending_crates is 'Crates with the fastest growing number of downloads, maintained by the `UpdateTrendingCrates` background job';
+comment on column trending_crates.window_days is 'Number of days that the downloads are compared over, e.g. `7` for the last week compared to the week before';
+comment on column trending_crates.crate_id is 'Reference to the crate';
+comment on column trending_crates.rank is 'Position of the crate in the ranking of the window, starting at 1';
+comment on column trending_crates.downloads is 'Number of downloads of the crate in the last `window_days` complete days';
+comment on column trending_crates.previous_downloads is 'Number of downloads of the crate in the `window_days` days before that';
+comment on column trending_crates.growth is 'Relative growth of the downloads, e.g. `0.5` for 50% more downloads than in the previous window';
@@ -0,0 +1,14 @@
+create table crate_dependent_stats
+(
+    crate_id            integer not null primary key references crates (id) on delete cascade,
+    dependents          integer not null,
+    weighted_dependents bigint  not null
+);
+
+create index crate_dependent_stats_dependents on crate_dependent_stats (dependents desc);
+create index crate_dependent_stats_weighted_dependents on crate_dependent_stats (weighted_dependents desc);
+
+comment on table crate_dependent_stats is 'Number of crates that depend on a crate, maintained by the `UpdateCrateDependentStats` background job. Crates without dependents have no row.';
+comment on column crate_dependent_stats.crate_id is 'Reference to the crate';
+comment on column crate_dependent_stats.dependents is 'Number of distinct crates whose default version depends on the crate, excluding yanked crates';
+comment on column crate_dependent_stats.weighted_dependents is 'Sum of the downloads of the last 90 days of all dependent crates';
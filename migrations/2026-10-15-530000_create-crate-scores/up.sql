@@ -0,0 +1,23 @@
+create table crate_scores
+(
+    crate_id          integer          not null primary key references crates (id) on delete cascade,
+    has_documentation boolean          not null,
+    has_repository    boolean          not null,
+    has_readme        boolean          not null,
+    recent_release    boolean          not null,
+    maintained        boolean          not null,
+    yanked_ratio      double precision not null,
+    score             double precision not null,
+    updated_at        timestamptz      not null default now()
+);
+
+comment on table crate_scores is 'Quality signals of each crate, maintained by the `UpdateCrateScores` background job.';
+comment on column crate_scores.crate_id is 'Reference to the crate';
+comment on column crate_scores.has_documentation is 'Whether the default version was built successfully on docs.rs, or the crate has a documentation URL';
+comment on column crate_scores.has_repository is 'Whether the crate has a repository URL';
+comment on column crate_scores.has_readme is 'Whether the crate has a non-empty README';
+comment on column crate_scores.recent_release is 'Whether a version that is not yanked was published in the last 6 months';
+comment on column crate_scores.maintained is 'Whether a version that is not yanked was published in the last 2 years';
+comment on column crate_scores.yanked_ratio is 'Share of the versions of the crate that are yanked, between 0 and 1';
+comment on column crate_scores.score is 'Composite score between 0 and 1, the average of the other signals with the yanked ratio inverted';
+comment on column crate_scores.updated_at is 'Time when the signals were last calculated';
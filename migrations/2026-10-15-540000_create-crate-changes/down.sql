@@ -0,0 +1,6 @@
+drop trigger trigger_versions_record_crate_changes on versions;
+drop function record_crate_changes_of_versions();
+drop trigger trigger_crates_record_crate_changes on crates;
+drop function record_crate_changes_of_crates();
+drop function record_crate_change(integer, integer, text, text);
+drop table crate_changes;
@@ -0,0 +1,109 @@
+create table crate_changes
+(
+    seq        bigserial   not null primary key,
+    kind       integer     not null,
+    crate_id   integer     not null,
+    crate_name text        not null,
+    version    text,
+    created_at timestamptz not null default now()
+);
+
+comment on table crate_changes is 'Feed of all crate and version mutations, used by trusted services like docs.rs and crater. The rows are inserted by triggers on the `crates` and `versions` tables.';
+comment on column crate_changes.seq is 'Sequence number of the change. Sequence numbers become visible in increasing order, so consumers can continue after the last change they have seen.';
+comment on column crate_changes.kind is 'The kind of change, see `CrateChangeKind` for the possible values';
+comment on column crate_changes.crate_id is 'ID of the affected crate. There is no foreign key, so that changes of deleted crates are kept.';
+comment on column crate_changes.crate_name is 'Name of the affected crate';
+comment on column crate_changes.version is 'Number of the affected version, or `NULL` for changes of the crate itself';
+comment on column crate_changes.created_at is 'Date and time when the change was made';
+
+-- Sequence numbers are assigned when the row is inserted, but the rows only
+-- become visible when the transaction commits. To prevent consumers from
+-- skipping changes of transactions that commit late, the table is locked
+-- until the end of the transaction, so that all changes are committed in
+-- the order of their sequence numbers. Reading the feed is not blocked by
+-- the lock.
+create function record_crate_change(kind integer, crate_id integer, crate_name text, version text) returns void as
+$$
+begin
+    lock table crate_changes in exclusive mode;
+
+    insert into crate_changes (kind, crate_id, crate_name, version)
+    values (kind, crate_id, crate_name, version);
+end
+$$ language plpgsql;
+
+-- `CrateChangeKind::CrateCreated` (0) and `CrateChangeKind::CrateDeleted` (1)
+create function record_crate_changes_of_crates() returns trigger as
+$$
+begin
+    if tg_op = 'INSERT' then
+        perform record_crate_change(0, new.id, new.name, null);
+        return new;
+    else
+        perform record_crate_change(1, old.id, old.name, null);
+        return old;
+    end if;
+end
+$$ language plpgsql;
+
+create trigger trigger_crates_record_crate_changes
+    after insert or delete
+    on crates
+    for each row
+execute procedure record_crate_changes_of_crates();
+
+-- `CrateChangeKind::VersionPublished` (2), `CrateChangeKind::VersionYanked`
+-- (3), `CrateChangeKind::VersionUnyanked` (4) and
+-- `CrateChangeKind::VersionDeleted` (5)
+create function record_crate_changes_of_versions() returns trigger as
+$$
+declare
+    crate_name text;
+begin
+    if tg_op = 'INSERT' then
+        select crates.name into crate_name from crates where crates.id = new.crate_id;
+        perform record_crate_change(2, new.crate_id, crate_name, new.num);
+        return new;
+    elsif tg_op = 'UPDATE' then
+        if new.yanked is distinct from old.yanked then
+            select crates.name into crate_name from crates where crates.id = new.crate_id;
+            perform record_crate_change(case when new.yanked then 3 else 4 end, new.crate_id, crate_name, new.num);
+        end if;
+        return new;
+    else
+        -- The versions of deleted crates are covered by the deletion of
+        -- the crate itself.
+        select crates.name into crate_name from crates where crates.id = old.crate_id;
+        if found then
+            perform record_crate_change(5, old.crate_id, crate_name, old.num);
+        end if;
+        return old;
+    end if;
+end
+$$ language plpgsql;
+
+create trigger trigger_versions_record_crate_changes
+    after insert or update of yanked or delete
+    on versions
+    for each row
+execute procedure record_crate_changes_of_versions();
+
+-- Start the feed with all existing crates and versions, and the yanks of
+-- the currently yanked versions, so that consumers can enumerate the whole
+-- registry by starting at the beginning.
+insert into crate_changes (kind, crate_id, crate_name, version, created_at)
+select kind, crate_id, crate_name, version, created_at
+from (
+    select 0 as kind, id as crate_id, name as crate_name, null as version, created_at, 0 as version_id
+    from crates
+    union all
+    select 2, crates.id, crates.name, versions.num, versions.created_at, versions.id
+    from versions
+    inner join crates on crates.id = versions.crate_id
+    union all
+    select 3, crates.id, crates.name, versions.num, versions.updated_at, versions.id
+    from versions
+    inner join crates on crates.id = versions.crate_id
+    where versions.yanked
+) as existing
+order by created_at, version_id, kind;
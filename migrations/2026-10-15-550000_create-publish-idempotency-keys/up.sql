@@ -0,0 +1,27 @@
+create table publish_idempotency_keys
+(
+    user_id    integer     not null
+        constraint publish_idempotency_keys_users_id_fk
+            references users
+            on delete cascade,
+    key        text        not null,
+    crate_name text        not null,
+    version    text        not null,
+    checksum   text        not null,
+    response   jsonb       not null,
+    created_at timestamptz not null default now(),
+    constraint publish_idempotency_keys_pk
+        primary key (user_id, key)
+);
+
+create index publish_idempotency_keys_created_at_index
+    on publish_idempotency_keys (created_at);
+
+comment on table publish_idempotency_keys is 'Results of publish requests with an `Idempotency-Key` header, so that retried requests return the original result. Rows are kept for 24 hours.';
+comment on column publish_idempotency_keys.user_id is 'Reference to the user that sent the publish request';
+comment on column publish_idempotency_keys.key is 'The value of the `Idempotency-Key` header, which is unique per user';
+comment on column publish_idempotency_keys.crate_name is 'Name of the published crate';
+comment on column publish_idempotency_keys.version is 'Number of the published version';
+comment on column publish_idempotency_keys.checksum is 'SHA256 checksum of the published `.crate` file, to detect reuse of the key for a different request';
+comment on column publish_idempotency_keys.response is 'The JSON response of the original publish request';
+comment on column publish_idempotency_keys.created_at is 'Date and time when the version was published';
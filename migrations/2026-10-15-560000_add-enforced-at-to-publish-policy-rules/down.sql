@@ -0,0 +1 @@
+alter table publish_policy_rules drop column enforced_at;
@@ -0,0 +1,4 @@
+alter table publish_policy_rules
+    add column enforced_at timestamptz;
+
+comment on column publish_policy_rules.enforced_at is 'Date and time from which on the rule is enforced. Until then, crates that violate the rule are published with a warning. `NULL` means that the rule is enforced immediately.';
@@ -0,0 +1 @@
+drop table broadcast_warnings;
@@ -0,0 +1,20 @@
+create table broadcast_warnings
+(
+    id         serial primary key,
+    message    text        not null,
+    docs_url   text,
+    expires_at timestamptz,
+    created_by integer
+        constraint broadcast_warnings_created_by_fk
+            references users
+            on delete set null,
+    created_at timestamptz not null default now()
+);
+
+comment on table broadcast_warnings is 'Warnings that are returned to cargo for every publish, managed by the crates.io team';
+comment on column broadcast_warnings.id is 'Unique identifier of the `broadcast_warnings` row';
+comment on column broadcast_warnings.message is 'The warning that is shown to users';
+comment on column broadcast_warnings.docs_url is 'URL of a page with more information about the warning';
+comment on column broadcast_warnings.expires_at is 'Date and time when the warning stops being returned, or `NULL` if it is returned until it is deleted';
+comment on column broadcast_warnings.created_by is 'ID of the admin that created the warning';
+comment on column broadcast_warnings.created_at is 'Date and time when the warning was created';
@@ -0,0 +1,41 @@
+create table crate_redirects
+(
+    id              serial primary key,
+    name            text        not null,
+    target_crate_id integer     not null
+        constraint crate_redirects_target_crate_id_fk
+            references crates
+            on delete cascade,
+    requested_by    integer
+        constraint crate_redirects_requested_by_fk
+            references users
+            on delete set null,
+    reason          text        not null,
+    status          integer     not null default 0,
+    decision        text,
+    decided_by      integer
+        constraint crate_redirects_decided_by_fk
+            references users
+            on delete set null,
+    created_at      timestamptz not null default now(),
+    decided_at      timestamptz
+);
+
+comment on table crate_redirects is 'Redirects from the name of a renamed or superseded crate to its successor, which are requested by the owners and approved by the crates.io team';
+comment on column crate_redirects.id is 'Unique identifier of the `crate_redirects` row';
+comment on column crate_redirects.name is 'Name of the renamed crate, which is blocked from being registered again once the redirect is approved';
+comment on column crate_redirects.target_crate_id is 'ID of the crate that the requests are redirected to';
+comment on column crate_redirects.requested_by is 'ID of the owner that requested the redirect';
+comment on column crate_redirects.reason is 'Explanation of why the crate has been renamed or superseded';
+comment on column crate_redirects.status is 'Status of the redirect, see `RedirectStatus` for the possible values';
+comment on column crate_redirects.decision is 'Explanation of the decision, which is sent to the requester';
+comment on column crate_redirects.decided_by is 'ID of the admin that approved or rejected the redirect';
+comment on column crate_redirects.created_at is 'Date and time when the redirect was requested';
+comment on column crate_redirects.decided_at is 'Date and time when the redirect was approved or rejected';
+
+create unique index crate_redirects_name_index
+    on crate_redirects (canon_crate_name(name))
+    where status <> 2;
+
+create index crate_redirects_status_index
+    on crate_redirects (status, id);
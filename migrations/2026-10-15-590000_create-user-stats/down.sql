@@ -0,0 +1,4 @@
+drop table user_stats;
+
+alter table users
+    drop column public_stats;
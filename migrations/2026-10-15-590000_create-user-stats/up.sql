@@ -0,0 +1,25 @@
+alter table users
+    add column public_stats boolean not null default false;
+
+comment on column users.public_stats is 'Whether the user wants their contribution stats to be shown on their public profile';
+
+create table user_stats
+(
+    user_id            integer     not null
+        constraint user_stats_pk
+            primary key
+        constraint user_stats_users_id_fk
+            references users
+            on delete cascade,
+    total_downloads    bigint      not null,
+    publishes_per_year jsonb       not null,
+    co_owners          jsonb       not null,
+    updated_at         timestamptz not null default now()
+);
+
+comment on table user_stats is 'Contribution stats of the users that opted in to public stats, maintained by the `UpdateUserStats` background job';
+comment on column user_stats.user_id is 'ID of the user that the stats belong to';
+comment on column user_stats.total_downloads is 'Total number of downloads of all crates that the user owns';
+comment on column user_stats.publishes_per_year is 'JSON array of the number of versions that the user published per year';
+comment on column user_stats.co_owners is 'JSON array of the users that most frequently own crates together with the user, with the number of shared crates';
+comment on column user_stats.updated_at is 'Date and time when the stats were last computed';
@@ -0,0 +1,4 @@
+alter table versions
+    add column publish_method integer;
+
+comment on column versions.publish_method is 'How the version was published, see `PublishMethod` for the possible values, or `NULL` if unknown because the version was published before the method was recorded';
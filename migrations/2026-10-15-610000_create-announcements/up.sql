@@ -0,0 +1,31 @@
+create table announcements
+(
+    id         serial primary key,
+    message    text        not null,
+    docs_url   text,
+    severity   integer     not null default 0,
+    audience   integer     not null default 0,
+    starts_at  timestamptz not null default now(),
+    ends_at    timestamptz,
+    created_by integer
+        constraint announcements_created_by_fk
+            references users
+            on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now()
+);
+
+comment on table announcements is 'Announcements that are shown in a banner on the crates.io website, managed by the crates.io team';
+comment on column announcements.id is 'Unique identifier of the `announcements` row';
+comment on column announcements.message is 'The announcement that is shown to users';
+comment on column announcements.docs_url is 'URL of a page with more information about the announcement';
+comment on column announcements.severity is 'Severity of the announcement, see `AnnouncementSeverity` for the possible values';
+comment on column announcements.audience is 'Users that the announcement is shown to, see `AnnouncementAudience` for the possible values';
+comment on column announcements.starts_at is 'Date and time from which on the announcement is shown';
+comment on column announcements.ends_at is 'Date and time when the announcement stops being shown, or `NULL` if it is shown until it is deleted';
+comment on column announcements.created_by is 'ID of the admin that created the announcement';
+comment on column announcements.created_at is 'Date and time when the announcement was created';
+comment on column announcements.updated_at is 'Date and time when the announcement was last changed';
+
+create index announcements_ends_at_index
+    on announcements (ends_at);
@@ -0,0 +1 @@
+drop table github_cache;
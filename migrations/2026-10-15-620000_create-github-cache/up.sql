@@ -0,0 +1,25 @@
+create table github_cache
+(
+    key          text        not null
+        constraint github_cache_pk
+            primary key,
+    value        jsonb       not null,
+    user_id      integer     not null
+        constraint github_cache_user_id_fk
+            references users
+            on delete cascade,
+    fetched_at   timestamptz not null default now(),
+    expires_at   timestamptz not null,
+    last_used_at timestamptz not null default now()
+);
+
+comment on table github_cache is 'Cached responses of the GitHub API, which are refreshed by the `RefreshGitHubCache` background job';
+comment on column github_cache.key is 'The serialized `GitHubRequest` that the response belongs to';
+comment on column github_cache.value is 'The response of the GitHub API, or `null` if the requested resource was not found';
+comment on column github_cache.user_id is 'ID of the user whose GitHub token was used to fetch the response, and is used to refresh it';
+comment on column github_cache.fetched_at is 'Date and time when the response was fetched from GitHub';
+comment on column github_cache.expires_at is 'Date and time after which the response has to be fetched again. Expired responses are only used while GitHub is unavailable.';
+comment on column github_cache.last_used_at is 'Date and time when the response was last used. Responses that have not been used for a while are no longer refreshed and eventually deleted.';
+
+create index github_cache_expires_at_index
+    on github_cache (expires_at);
@@ -0,0 +1 @@
+drop table cdn_invalidations;
@@ -0,0 +1,18 @@
+create table cdn_invalidations
+(
+    cdn            integer     not null,
+    path           text        not null,
+    queued_at      timestamptz not null default now(),
+    last_queued_at timestamptz not null default now(),
+    constraint cdn_invalidations_pk
+        primary key (cdn, path)
+);
+
+comment on table cdn_invalidations is 'Paths waiting to be invalidated on the CDNs, which are processed in batches by the `ProcessCdnInvalidationQueue` background job';
+comment on column cdn_invalidations.cdn is 'The CDN that the path has to be invalidated on (0 = CloudFront, 1 = Fastly)';
+comment on column cdn_invalidations.path is 'The path to invalidate';
+comment on column cdn_invalidations.queued_at is 'Date and time when the path was first queued for invalidation';
+comment on column cdn_invalidations.last_queued_at is 'Date and time when the path was last queued for invalidation. Paths that are queued again while they are being invalidated stay in the queue.';
+
+create index cdn_invalidations_queued_at_index
+    on cdn_invalidations (queued_at);
@@ -0,0 +1,15 @@
+create table storage_discrepancies
+(
+    kind          integer     not null,
+    path          text        not null,
+    first_seen_at timestamptz not null default now(),
+    last_seen_at  timestamptz not null default now(),
+    constraint storage_discrepancies_pk
+        primary key (kind, path)
+);
+
+comment on table storage_discrepancies is 'Files in the storage that don''t match the database, as found by the `ReconcileStorage` background job';
+comment on column storage_discrepancies.kind is 'The kind of discrepancy (0 = orphaned crate file, 1 = missing crate file, 2 = orphaned index file, 3 = missing index file)';
+comment on column storage_discrepancies.path is 'The path of the file in the storage';
+comment on column storage_discrepancies.first_seen_at is 'Date and time when the discrepancy was first found. Orphaned files are only deleted after they have been found for the whole quarantine period.';
+comment on column storage_discrepancies.last_seen_at is 'Date and time when the discrepancy was last found';
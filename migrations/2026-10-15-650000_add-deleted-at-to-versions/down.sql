@@ -0,0 +1,37 @@
+drop trigger trigger_versions_record_crate_changes on versions;
+
+create or replace function record_crate_changes_of_versions() returns trigger as
+$$
+declare
+    crate_name text;
+begin
+    if tg_op = 'INSERT' then
+        select crates.name into crate_name from crates where crates.id = new.crate_id;
+        perform record_crate_change(2, new.crate_id, crate_name, new.num);
+        return new;
+    elsif tg_op = 'UPDATE' then
+        if new.yanked is distinct from old.yanked then
+            select crates.name into crate_name from crates where crates.id = new.crate_id;
+            perform record_crate_change(case when new.yanked then 3 else 4 end, new.crate_id, crate_name, new.num);
+        end if;
+        return new;
+    else
+        -- The versions of deleted crates are covered by the deletion of
+        -- the crate itself.
+        select crates.name into crate_name from crates where crates.id = old.crate_id;
+        if found then
+            perform record_crate_change(5, old.crate_id, crate_name, old.num);
+        end if;
+        return old;
+    end if;
+end
+$$ language plpgsql;
+
+create trigger trigger_versions_record_crate_changes
+    after insert or update of yanked or delete
+    on versions
+    for each row
+execute procedure record_crate_changes_of_versions();
+
+alter table versions
+    drop column deleted_at;
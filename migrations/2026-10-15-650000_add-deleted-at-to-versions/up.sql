@@ -0,0 +1,47 @@
+alter table versions
+    add column deleted_at timestamptz;
+
+comment on column versions.deleted_at is 'Time at which the version was soft-deleted by the crates.io team, or `NULL` if it has not been deleted. Deleted versions are removed from the index and the API, but their `.crate` files are kept in the quarantine, so that they can be restored.';
+
+-- `CrateChangeKind::VersionPublished` (2), `CrateChangeKind::VersionYanked`
+-- (3), `CrateChangeKind::VersionUnyanked` (4),
+-- `CrateChangeKind::VersionDeleted` (5),
+-- `CrateChangeKind::VersionSoftDeleted` (6) and
+-- `CrateChangeKind::VersionRestored` (7)
+create or replace function record_crate_changes_of_versions() returns trigger as
+$$
+declare
+    crate_name text;
+begin
+    if tg_op = 'INSERT' then
+        select crates.name into crate_name from crates where crates.id = new.crate_id;
+        perform record_crate_change(2, new.crate_id, crate_name, new.num);
+        return new;
+    elsif tg_op = 'UPDATE' then
+        select crates.name into crate_name from crates where crates.id = new.crate_id;
+        if new.yanked is distinct from old.yanked then
+            perform record_crate_change(case when new.yanked then 3 else 4 end, new.crate_id, crate_name, new.num);
+        end if;
+        if (new.deleted_at is null) is distinct from (old.deleted_at is null) then
+            perform record_crate_change(case when new.deleted_at is null then 7 else 6 end, new.crate_id, crate_name, new.num);
+        end if;
+        return new;
+    else
+        -- The versions of deleted crates are covered by the deletion of
+        -- the crate itself.
+        select crates.name into crate_name from crates where crates.id = old.crate_id;
+        if found then
+            perform record_crate_change(5, old.crate_id, crate_name, old.num);
+        end if;
+        return old;
+    end if;
+end
+$$ language plpgsql;
+
+drop trigger trigger_versions_record_crate_changes on versions;
+
+create trigger trigger_versions_record_crate_changes
+    after insert or update of yanked, deleted_at or delete
+    on versions
+    for each row
+execute procedure record_crate_changes_of_versions();
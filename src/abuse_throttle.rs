@@ -0,0 +1,375 @@
+//! Adaptive throttling of abusive clients.
+//!
+//! The [AbuseThrottle] counts the requests and the error responses of every
+//! subnet (`/24` for IPv4 and `/64` for IPv6 addresses) in fixed time windows.
+//! Once a subnet exceeds one of the thresholds, it is banned temporarily. The
+//! bans are stored in the `ip_bans` table, so that they apply to all server
+//! instances and can be reviewed in the admin console, and their duration
+//! escalates with every repeated offense.
+//!
+//! The `ip_access_rules` table contains permanent rules that either exempt
+//! networks from the throttling, or deny them access to the API. The rules
+//! and bans are cached in memory and reloaded from the database periodically.
+
+use crate::app::App;
+use crate::models::{IpAccessRule, IpAccessRuleKind, IpBan};
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::QueryResult;
+use diesel_async::AsyncPgConnection;
+use http::StatusCode;
+use ipnetwork::IpNetwork;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often the rules and bans are reloaded from the database.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Expired counters are removed once there are this many.
+const MAX_COUNTERS: usize = 100_000;
+
+const IPV4_SUBNET_PREFIX: u8 = 24;
+const IPV6_SUBNET_PREFIX: u8 = 64;
+
+#[derive(Debug, Clone)]
+pub struct AbuseThrottleConfig {
+    pub enabled: bool,
+    /// Length of the windows in which requests and errors are counted.
+    pub window: Duration,
+    /// Maximum number of requests of a subnet in each window.
+    pub max_requests: u64,
+    /// Maximum number of `4xx` responses of a subnet in each window.
+    pub max_errors: u64,
+    /// Duration of the first ban of a subnet.
+    pub ban_duration: Duration,
+    /// Maximum duration of repeated bans.
+    pub max_ban_duration: Duration,
+}
+
+impl Default for AbuseThrottleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: Duration::from_secs(60),
+            max_requests: 10_000,
+            max_errors: 1_000,
+            ban_duration: Duration::from_secs(5 * 60),
+            max_ban_duration: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The client is subject to the throttling.
+    Allowed,
+    /// The client is exempt from the throttling by an allow rule.
+    Exempt,
+    /// The client is denied access by a deny rule.
+    Denied,
+    /// The client is temporarily banned.
+    Banned { until: DateTime<Utc> },
+}
+
+pub struct AbuseThrottle {
+    config: AbuseThrottleConfig,
+    counters: Mutex<HashMap<IpNetwork, Counter>>,
+    rules: RwLock<Rules>,
+    refreshing: AtomicBool,
+}
+
+struct Counter {
+    window_start: Instant,
+    requests: u64,
+    errors: u64,
+}
+
+impl Counter {
+    fn new(window_start: Instant) -> Self {
+        Self {
+            window_start,
+            requests: 0,
+            errors: 0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Rules {
+    allow: Vec<IpNetwork>,
+    deny: Vec<IpNetwork>,
+    bans: HashMap<IpNetwork, DateTime<Utc>>,
+    refreshed_at: Option<Instant>,
+}
+
+impl AbuseThrottle {
+    pub fn new(config: AbuseThrottleConfig) -> Self {
+        Self {
+            config,
+            counters: Mutex::default(),
+            rules: RwLock::default(),
+            refreshing: AtomicBool::new(false),
+        }
+    }
+
+    pub fn config(&self) -> &AbuseThrottleConfig {
+        &self.config
+    }
+
+    pub fn verdict(&self, ip: IpAddr) -> Verdict {
+        let rules = self.rules.read();
+        if rules.allow.iter().any(|network| network.contains(ip)) {
+            return Verdict::Exempt;
+        }
+        if rules.deny.iter().any(|network| network.contains(ip)) {
+            return Verdict::Denied;
+        }
+
+        match rules.bans.get(&subnet(ip)) {
+            Some(until) if *until > Utc::now() => Verdict::Banned { until: *until },
+            _ => Verdict::Allowed,
+        }
+    }
+
+    /// Counts a response to the client, and returns its subnet and the reason
+    /// if the subnet has exceeded one of the thresholds.
+    ///
+    /// The subnet is banned locally right away, but the caller is responsible
+    /// for persisting the ban via [AbuseThrottle::ban].
+    pub fn record(&self, ip: IpAddr, status: StatusCode) -> Option<(IpNetwork, &'static str)> {
+        let subnet = subnet(ip);
+        let now = Instant::now();
+
+        let reason = {
+            let mut counters = self.counters.lock();
+            let window = self.config.window;
+            if counters.len() >= MAX_COUNTERS {
+                counters.retain(|_, counter| now.duration_since(counter.window_start) < window);
+            }
+
+            let counter = counters.entry(subnet).or_insert_with(|| Counter::new(now));
+            if now.duration_since(counter.window_start) >= window {
+                *counter = Counter::new(now);
+            }
+
+            counter.requests += 1;
+            if status.is_client_error() {
+                counter.errors += 1;
+            }
+
+            let reason = if counter.requests > self.config.max_requests {
+                "too many requests"
+            } else if counter.errors > self.config.max_errors {
+                "too many error responses"
+            } else {
+                return None;
+            };
+
+            counters.remove(&subnet);
+            reason
+        };
+
+        let duration = TimeDelta::from_std(self.config.ban_duration).unwrap_or(TimeDelta::MAX);
+        let until = Utc::now()
+            .checked_add_signed(duration)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        self.rules.write().bans.insert(subnet, until);
+
+        Some((subnet, reason))
+    }
+
+    /// Persists the ban of the subnet, escalating its duration for repeated
+    /// offenses.
+    pub async fn ban(
+        &self,
+        subnet: IpNetwork,
+        reason: &str,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<IpBan> {
+        let ban = IpBan::record_strike(
+            conn,
+            &subnet.to_string(),
+            reason,
+            self.config.ban_duration,
+            self.config.max_ban_duration,
+        )
+        .await?;
+
+        self.rules.write().bans.insert(subnet, ban.banned_until);
+        Ok(ban)
+    }
+
+    /// Removes the ban of the network from the local cache, so that it is
+    /// lifted on this instance before the next refresh.
+    pub fn lift(&self, network: &str) {
+        if let Ok(network) = IpNetwork::from_str(network) {
+            self.rules.write().bans.remove(&network);
+        }
+    }
+
+    /// Reloads the rules and bans from the database on the next request, e.g.
+    /// after the access rules have been changed.
+    pub fn invalidate(&self) {
+        self.rules.write().refreshed_at = None;
+    }
+
+    /// Returns `true` if the rules and bans should be reloaded from the
+    /// database, and no other refresh is in progress. The caller must call
+    /// [AbuseThrottle::refresh] afterwards.
+    pub fn start_refresh(&self) -> bool {
+        let refreshed_at = self.rules.read().refreshed_at;
+        if refreshed_at.is_some_and(|refreshed_at| refreshed_at.elapsed() < REFRESH_INTERVAL) {
+            return false;
+        }
+
+        !self.refreshing.swap(true, Ordering::AcqRel)
+    }
+
+    pub async fn refresh(&self, app: &App) -> anyhow::Result<()> {
+        let result = async {
+            let mut conn = app.db_read().await?;
+            Ok::<_, anyhow::Error>(self.load_rules(&mut conn).await?)
+        }
+        .await;
+
+        let result = {
+            let mut rules = self.rules.write();
+            let result = result.map(|new_rules| *rules = new_rules);
+
+            // Also wait for the next interval after a failed refresh, so that
+            // an unavailable database isn't queried for every request.
+            rules.refreshed_at = Some(Instant::now());
+            result
+        };
+
+        self.refreshing.store(false, Ordering::Release);
+        result
+    }
+
+    async fn load_rules(&self, conn: &mut AsyncPgConnection) -> QueryResult<Rules> {
+        let mut rules = Rules::default();
+
+        for rule in IpAccessRule::all(conn).await? {
+            let Ok(network) = IpNetwork::from_str(&rule.network) else {
+                warn!(network = %rule.network, "Ignoring invalid IP access rule");
+                continue;
+            };
+
+            match rule.kind {
+                IpAccessRuleKind::Allow => rules.allow.push(network),
+                IpAccessRuleKind::Deny => rules.deny.push(network),
+            }
+        }
+
+        for ban in IpBan::active(conn).await? {
+            if let Ok(network) = IpNetwork::from_str(&ban.network) {
+                rules.bans.insert(network, ban.banned_until);
+            }
+        }
+
+        Ok(rules)
+    }
+}
+
+/// Returns the subnet of the IP address that is tracked as a whole.
+fn subnet(ip: IpAddr) -> IpNetwork {
+    let prefix = match ip {
+        IpAddr::V4(_) => IPV4_SUBNET_PREFIX,
+        IpAddr::V6(_) => IPV6_SUBNET_PREFIX,
+    };
+
+    // The prefix is always valid for the address family.
+    let network = IpNetwork::new(ip, prefix).unwrap();
+    IpNetwork::new(network.network(), prefix).unwrap()
+}
+
+/// Parses an IP network in CIDR notation, and normalizes it to the first
+/// address of the network, e.g. `192.0.2.1/24` to `192.0.2.0/24`.
+pub fn parse_network(network: &str) -> Option<IpNetwork> {
+    let network = IpNetwork::from_str(network).ok()?;
+    IpNetwork::new(network.network(), network.prefix()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle() -> AbuseThrottle {
+        AbuseThrottle::new(AbuseThrottleConfig {
+            enabled: true,
+            max_requests: 3,
+            max_errors: 1,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_subnet() {
+        let ip = "192.0.2.42".parse().unwrap();
+        assert_eq!(subnet(ip).to_string(), "192.0.2.0/24");
+
+        let ip = "2001:db8::1:2:3:4".parse().unwrap();
+        assert_eq!(subnet(ip).to_string(), "2001:db8::/64");
+    }
+
+    #[test]
+    fn test_parse_network() {
+        let network = assert_some!(parse_network("192.0.2.42/24"));
+        assert_eq!(network.to_string(), "192.0.2.0/24");
+        let network = assert_some!(parse_network("192.0.2.42"));
+        assert_eq!(network.to_string(), "192.0.2.42/32");
+        assert_none!(parse_network("foo"));
+    }
+
+    #[test]
+    fn test_request_volume() {
+        let throttle = throttle();
+        let ip = "192.0.2.1".parse().unwrap();
+        let neighbor = "192.0.2.2".parse().unwrap();
+
+        assert_none!(throttle.record(ip, StatusCode::OK));
+        assert_none!(throttle.record(neighbor, StatusCode::OK));
+        assert_none!(throttle.record(ip, StatusCode::OK));
+        assert_eq!(throttle.verdict(ip), Verdict::Allowed);
+
+        let (subnet, reason) = assert_some!(throttle.record(neighbor, StatusCode::OK));
+        assert_eq!(subnet.to_string(), "192.0.2.0/24");
+        assert_eq!(reason, "too many requests");
+        assert!(matches!(throttle.verdict(ip), Verdict::Banned { .. }));
+
+        let other = "198.51.100.1".parse().unwrap();
+        assert_eq!(throttle.verdict(other), Verdict::Allowed);
+    }
+
+    #[test]
+    fn test_error_rate() {
+        let throttle = throttle();
+        let ip = "2001:db8::1".parse().unwrap();
+
+        assert_none!(throttle.record(ip, StatusCode::NOT_FOUND));
+        assert_none!(throttle.record(ip, StatusCode::INTERNAL_SERVER_ERROR));
+
+        let (_, reason) = assert_some!(throttle.record(ip, StatusCode::FORBIDDEN));
+        assert_eq!(reason, "too many error responses");
+        assert!(matches!(throttle.verdict(ip), Verdict::Banned { .. }));
+    }
+
+    #[test]
+    fn test_access_rules() {
+        let throttle = throttle();
+        {
+            let mut rules = throttle.rules.write();
+            rules.allow.push("192.0.2.0/28".parse().unwrap());
+            rules.deny.push("192.0.2.0/24".parse().unwrap());
+        }
+
+        let ip = "192.0.2.1".parse().unwrap();
+        assert_eq!(throttle.verdict(ip), Verdict::Exempt);
+
+        let ip = "192.0.2.100".parse().unwrap();
+        assert_eq!(throttle.verdict(ip), Verdict::Denied);
+    }
+}
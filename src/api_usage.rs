@@ -0,0 +1,204 @@
+//! Counting of API requests per user and per crate.
+//!
+//! The [ApiUsage] counts the requests of every authenticated user, and the
+//! downloads and metadata requests of every crate, in memory. The counts are
+//! added to the `api_usage_by_user` and `api_usage_by_crate` tables
+//! periodically by the server, so that they don't cause a database write for
+//! every request. Counts that can't be persisted are kept in memory and
+//! retried with the next batch.
+
+use chrono::{NaiveDate, Utc};
+use diesel::sql_types::{Array, BigInt, Date, Integer, Text};
+use diesel::QueryResult;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::mem;
+
+/// How the requests of a user were authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UserUsageKind {
+    Cookie,
+    Token,
+}
+
+impl UserUsageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cookie => "cookie",
+            Self::Token => "token",
+        }
+    }
+}
+
+/// The kind of the requests about a crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CrateUsageKind {
+    /// Downloads of a version through the API.
+    Download,
+    /// All other read requests of the crate, its versions and its owners.
+    Metadata,
+}
+
+impl CrateUsageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Download => "download",
+            Self::Metadata => "metadata",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ApiUsage {
+    counts: Mutex<Counts>,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    users: HashMap<(i32, NaiveDate, UserUsageKind), i64>,
+    /// The crates are counted by the name in the request path, which is only
+    /// resolved to a crate when the counts are persisted.
+    crates: HashMap<(String, NaiveDate, CrateUsageKind), i64>,
+}
+
+impl Counts {
+    fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.crates.is_empty()
+    }
+
+    fn merge(&mut self, other: Counts) {
+        for (key, count) in other.users {
+            *self.users.entry(key).or_default() += count;
+        }
+        for (key, count) in other.crates {
+            *self.crates.entry(key).or_default() += count;
+        }
+    }
+}
+
+impl ApiUsage {
+    /// Counts an authenticated request of the user.
+    pub fn record_user(&self, user_id: i32, kind: UserUsageKind) {
+        let key = (user_id, Utc::now().date_naive(), kind);
+        *self.counts.lock().users.entry(key).or_default() += 1;
+    }
+
+    /// Counts a successful request about the crate with the given name.
+    pub fn record_crate(&self, name: &str, kind: CrateUsageKind) {
+        let key = (name.to_string(), Utc::now().date_naive(), kind);
+        *self.counts.lock().crates.entry(key).or_default() += 1;
+    }
+
+    /// Adds the counts since the last call to the database.
+    ///
+    /// Counts of users and crates that don't exist (anymore) are dropped. If
+    /// the counts can't be saved, they are kept for the next call.
+    pub async fn persist(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        let counts = mem::take(&mut *self.counts.lock());
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        let result = save_counts(&counts, conn).await;
+        if result.is_err() {
+            self.counts.lock().merge(counts);
+        }
+
+        result
+    }
+}
+
+async fn save_counts(counts: &Counts, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+    let mut user_ids = Vec::with_capacity(counts.users.len());
+    let mut dates = Vec::with_capacity(counts.users.len());
+    let mut kinds = Vec::with_capacity(counts.users.len());
+    let mut values = Vec::with_capacity(counts.users.len());
+    for ((user_id, date, kind), count) in &counts.users {
+        user_ids.push(*user_id);
+        dates.push(*date);
+        kinds.push(kind.as_str());
+        values.push(*count);
+    }
+
+    diesel::sql_query(
+        r#"
+            INSERT INTO api_usage_by_user (user_id, date, kind, count)
+            SELECT usage.user_id, usage.date, usage.kind, usage.count
+            FROM unnest($1::int4[], $2::date[], $3::text[], $4::int8[])
+                AS usage (user_id, date, kind, count)
+            JOIN users ON users.id = usage.user_id
+            ON CONFLICT (user_id, date, kind)
+            DO UPDATE SET count = api_usage_by_user.count + EXCLUDED.count
+        "#,
+    )
+    .bind::<Array<Integer>, _>(user_ids)
+    .bind::<Array<Date>, _>(dates)
+    .bind::<Array<Text>, _>(kinds)
+    .bind::<Array<BigInt>, _>(values)
+    .execute(conn)
+    .await?;
+
+    let mut names = Vec::with_capacity(counts.crates.len());
+    let mut dates = Vec::with_capacity(counts.crates.len());
+    let mut kinds = Vec::with_capacity(counts.crates.len());
+    let mut values = Vec::with_capacity(counts.crates.len());
+    for ((name, date, kind), count) in &counts.crates {
+        names.push(name.as_str());
+        dates.push(*date);
+        kinds.push(kind.as_str());
+        values.push(*count);
+    }
+
+    // Different spellings of the same crate name are summed up, since a
+    // single `INSERT` can't update the same row twice.
+    diesel::sql_query(
+        r#"
+            INSERT INTO api_usage_by_crate (crate_id, date, kind, count)
+            SELECT crates.id, usage.date, usage.kind, sum(usage.count)::int8
+            FROM unnest($1::text[], $2::date[], $3::text[], $4::int8[])
+                AS usage (name, date, kind, count)
+            JOIN crates ON canon_crate_name(crates.name) = canon_crate_name(usage.name)
+            GROUP BY crates.id, usage.date, usage.kind
+            ON CONFLICT (crate_id, date, kind)
+            DO UPDATE SET count = api_usage_by_crate.count + EXCLUDED.count
+        "#,
+    )
+    .bind::<Array<Text>, _>(names)
+    .bind::<Array<Date>, _>(dates)
+    .bind::<Array<Text>, _>(kinds)
+    .bind::<Array<BigInt>, _>(values)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_merge() {
+        let usage = ApiUsage::default();
+        usage.record_user(1, UserUsageKind::Token);
+        usage.record_user(1, UserUsageKind::Token);
+        usage.record_user(1, UserUsageKind::Cookie);
+        usage.record_crate("serde", CrateUsageKind::Metadata);
+
+        let counts = mem::take(&mut *usage.counts.lock());
+        assert_eq!(counts.users.len(), 2);
+        assert_eq!(counts.crates.len(), 1);
+        assert!(usage.counts.lock().is_empty());
+
+        let today = Utc::now().date_naive();
+        usage.record_user(1, UserUsageKind::Token);
+        usage.counts.lock().merge(counts);
+
+        let counts = usage.counts.lock();
+        assert_eq!(counts.users[&(1, today, UserUsageKind::Token)], 3);
+        assert_eq!(counts.users[&(1, today, UserUsageKind::Cookie)], 1);
+        let key = ("serde".to_string(), today, CrateUsageKind::Metadata);
+        assert_eq!(counts.crates[&key], 1);
+    }
+}
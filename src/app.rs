@@ -1,22 +1,36 @@
 //! Application-wide components in a struct accessible from each request
 
+use crate::abuse_throttle::AbuseThrottle;
+use crate::api_usage::ApiUsage;
 use crate::config;
-use crate::db::{connection_url, make_manager_config, ConnectionConfig};
+use crate::db::{
+    connection_url, make_manager_config, update_statement_timeout, ConnectionConfig, QueryStats,
+};
+use crate::deadline::Deadline;
+use crate::dependency_graph::DependencyGraphCache;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::email::Emails;
+use crate::feature_flags::FeatureFlags;
 use crate::metrics::{InstanceMetrics, ServiceMetrics};
+use crate::oauth::OAuthProviders;
+use crate::rate_limiter::requests::RequestRateLimiter;
 use crate::rate_limiter::RateLimiter;
+use crate::reserved_names::ReservedNames;
 use crate::storage::Storage;
+use crate::tasks::BlockingPool;
 use axum::extract::{FromRef, FromRequestParts, State};
 use crates_io_github::GitHubClient;
 use deadpool_diesel::Runtime;
 use derive_more::Deref;
-use diesel_async::pooled_connection::deadpool::Pool as DeadpoolPool;
-use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use diesel_async::pooled_connection::deadpool::{Pool as DeadpoolPool, PoolError};
+use diesel_async::pooled_connection::{AsyncDieselConnectionManager, PoolError as BackendError};
 use diesel_async::AsyncPgConnection;
-use oauth2::basic::BasicClient;
-use oauth2::{EndpointNotSet, EndpointSet};
+
+/// The `statement_timeout` of a connection is only shortened for a request if
+/// its deadline is at least this much closer than the configured timeout.
+const STATEMENT_TIMEOUT_SLACK: Duration = Duration::from_secs(1);
 
 type DeadpoolResult = Result<
     diesel_async::pooled_connection::deadpool::Object<AsyncPgConnection>,
@@ -32,12 +46,14 @@ pub struct App {
     /// Database connection pool connected to the read-only replica database
     pub replica_database: Option<DeadpoolPool<AsyncPgConnection>>,
 
+    /// Number and duration of the queries on both database pools
+    pub query_stats: QueryStats,
+
     /// GitHub API client
-    pub github: Box<dyn GitHubClient>,
+    pub github: Arc<dyn GitHubClient>,
 
-    /// The GitHub OAuth2 configuration
-    pub github_oauth:
-        BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>,
+    /// The OAuth2 identity providers that can be used to log in
+    pub oauth: OAuthProviders,
 
     /// The server configuration
     pub config: Arc<config::Server>,
@@ -48,6 +64,10 @@ pub struct App {
     /// Storage backend for crate files and other large objects.
     pub storage: Arc<Storage>,
 
+    /// Storage backend of the standby region that crate files are
+    /// replicated to, if configured.
+    pub standby_storage: Option<Arc<Storage>>,
+
     /// Metrics related to the service as a whole
     pub service_metrics: ServiceMetrics,
 
@@ -56,6 +76,27 @@ pub struct App {
 
     /// Rate limit select actions.
     pub rate_limiter: RateLimiter,
+
+    /// Rate limit API requests per client and endpoint group.
+    pub request_rate_limiter: RequestRateLimiter,
+
+    /// Temporarily ban IP networks that send abusive traffic.
+    pub abuse_throttle: AbuseThrottle,
+
+    /// API requests per user and per crate that have not been persisted yet.
+    pub api_usage: ApiUsage,
+
+    /// Cached feature flags for the gradual rollout of new behavior.
+    pub feature_flags: FeatureFlags,
+
+    /// Cached crate names that can not be used for new crates.
+    pub reserved_names: ReservedNames,
+
+    /// Recently resolved dependency graphs
+    pub dependency_graph_cache: DependencyGraphCache,
+
+    /// Bounded pool for the blocking work of request handlers
+    pub blocking_pool: BlockingPool,
 }
 
 impl App {
@@ -63,24 +104,17 @@ impl App {
     ///
     /// Configures and sets up:
     ///
-    /// - GitHub OAuth
+    /// - OAuth identity providers
     /// - Database connection pools
     /// - A `git2::Repository` instance from the index repo checkout (that server.rs ensures exists)
     pub fn new(config: config::Server, emails: Emails, github: Box<dyn GitHubClient>) -> App {
-        use oauth2::{AuthUrl, TokenUrl};
-
         let instance_metrics =
             InstanceMetrics::new().expect("could not initialize instance metrics");
 
-        let auth_url = "https://github.com/login/oauth/authorize";
-        let auth_url = AuthUrl::new(auth_url.into()).unwrap();
-        let token_url = "https://github.com/login/oauth/access_token";
-        let token_url = TokenUrl::new(token_url.into()).unwrap();
+        let github: Arc<dyn GitHubClient> = Arc::from(github);
+        let oauth = OAuthProviders::new(&config, github.clone());
 
-        let github_oauth = BasicClient::new(config.gh_client_id.clone())
-            .set_client_secret(config.gh_client_secret.clone())
-            .set_auth_uri(auth_url)
-            .set_token_uri(token_url);
+        let query_stats = QueryStats::default();
 
         let primary_database = {
             use secrecy::ExposeSecret;
@@ -88,6 +122,7 @@ impl App {
             let primary_db_connection_config = ConnectionConfig {
                 statement_timeout: config.db.statement_timeout,
                 read_only: config.db.primary.read_only_mode,
+                query_stats: query_stats.clone(),
             };
 
             let url = connection_url(&config.db, config.db.primary.url.expose_secret());
@@ -109,6 +144,7 @@ impl App {
             let replica_db_connection_config = ConnectionConfig {
                 statement_timeout: config.db.statement_timeout,
                 read_only: pool_config.read_only_mode,
+                query_stats: query_stats.clone(),
             };
 
             let url = connection_url(&config.db, pool_config.url.expose_secret());
@@ -128,20 +164,50 @@ impl App {
             None
         };
 
+        let blocking_pool = BlockingPool::new(
+            config.blocking_pool_size,
+            config.blocking_pool_queue_size,
+            &instance_metrics,
+        );
+
+        let request_rate_limiter = RequestRateLimiter::new(&config.request_rate_limiter)
+            .expect("could not initialize request rate limiter");
+
         App {
             primary_database,
             replica_database,
+            query_stats,
             github,
-            github_oauth,
+            oauth,
             emails,
             storage: Arc::new(Storage::from_config(&config.storage)),
+            standby_storage: config
+                .standby_storage
+                .as_ref()
+                .map(|config| Arc::new(Storage::from_config(config))),
             service_metrics: ServiceMetrics::new().expect("could not initialize service metrics"),
             instance_metrics,
             rate_limiter: RateLimiter::new(config.rate_limiter.clone()),
+            request_rate_limiter,
+            abuse_throttle: AbuseThrottle::new(config.abuse_throttle.clone()),
+            api_usage: ApiUsage::default(),
+            feature_flags: FeatureFlags::default(),
+            reserved_names: ReservedNames::default(),
+            dependency_graph_cache: DependencyGraphCache::default(),
+            blocking_pool,
             config: Arc::new(config),
         }
     }
 
+    /// Returns the storage that download redirects point to, which is the
+    /// storage of the standby region during a failover.
+    pub fn download_storage(&self) -> &Storage {
+        match &self.standby_storage {
+            Some(standby) if self.config.downloads_failover => standby,
+            _ => &self.storage,
+        }
+    }
+
     /// A unique key to generate signed cookies
     pub fn session_key(&self) -> &cookie::Key {
         &self.config.session_key
@@ -150,7 +216,8 @@ impl App {
     /// Obtain a read/write database connection from the async primary pool
     #[instrument(skip_all)]
     pub async fn db_write(&self) -> DeadpoolResult {
-        self.primary_database.get().await
+        self.get_connection("async_primary", &self.primary_database)
+            .await
     }
 
     /// Obtain a readonly database connection from the replica pool
@@ -160,10 +227,12 @@ impl App {
     pub async fn db_read(&self) -> DeadpoolResult {
         let Some(read_only_pool) = self.replica_database.as_ref() else {
             // Replica is disabled, but primary might be available
-            return self.primary_database.get().await;
+            return self
+                .get_connection("async_primary", &self.primary_database)
+                .await;
         };
 
-        match read_only_pool.get().await {
+        match self.get_connection("async_follower", read_only_pool).await {
             // Replica is available
             Ok(connection) => Ok(connection),
 
@@ -176,7 +245,8 @@ impl App {
                     .map(|metric| metric.inc());
 
                 warn!("Replica is unavailable, falling back to primary ({error})");
-                self.primary_database.get().await
+                self.get_connection("async_primary", &self.primary_database)
+                    .await
             }
         }
     }
@@ -187,10 +257,15 @@ impl App {
     #[instrument(skip_all)]
     pub async fn db_read_prefer_primary(&self) -> DeadpoolResult {
         let Some(read_only_pool) = self.replica_database.as_ref() else {
-            return self.primary_database.get().await;
+            return self
+                .get_connection("async_primary", &self.primary_database)
+                .await;
         };
 
-        match self.primary_database.get().await {
+        match self
+            .get_connection("async_primary", &self.primary_database)
+            .await
+        {
             // Primary is available
             Ok(connection) => Ok(connection),
 
@@ -203,10 +278,54 @@ impl App {
                     .map(|metric| metric.inc());
 
                 warn!("Primary is unavailable, falling back to replica ({error})");
-                read_only_pool.get().await
+                self.get_connection("async_follower", read_only_pool).await
             }
         }
     }
+
+    /// Obtain a connection from the given pool and record how long it took
+    ///
+    /// The `statement_timeout` of the connection is limited to the time that
+    /// is left until the deadline of the current request, so that the
+    /// database stops running the queries of requests that have timed out.
+    async fn get_connection(
+        &self,
+        name: &str,
+        pool: &DeadpoolPool<AsyncPgConnection>,
+    ) -> DeadpoolResult {
+        let start = Instant::now();
+        let result = pool.get().await;
+
+        let _ = self
+            .instance_metrics
+            .database_time_to_obtain_connection
+            .get_metric_with_label_values(&[name])
+            .map(|metric| metric.observe(start.elapsed().as_secs_f64()));
+
+        // This only runs a query if the request needs a shorter timeout, or
+        // if the connection still has the shorter timeout of a previous
+        // request.
+        let mut conn = result?;
+        update_statement_timeout(&mut conn, self.statement_timeout())
+            .await
+            .map_err(|error| PoolError::Backend(BackendError::QueryError(error)))?;
+
+        Ok(conn)
+    }
+
+    /// The `statement_timeout` for the queries of the current request
+    ///
+    /// The configured timeout is only shortened if the deadline of the
+    /// request is more than [`STATEMENT_TIMEOUT_SLACK`] closer, so that most
+    /// requests can keep the timeout of the connection.
+    fn statement_timeout(&self) -> Duration {
+        let statement_timeout = self.config.db.statement_timeout;
+        let remaining = Deadline::current().map(|deadline| deadline.remaining());
+        match remaining {
+            Some(remaining) if remaining + STATEMENT_TIMEOUT_SLACK < statement_timeout => remaining,
+            _ => statement_timeout,
+        }
+    }
 }
 
 #[derive(Clone, FromRequestParts, Deref)]
@@ -1,15 +1,20 @@
+use crate::api_usage::UserUsageKind;
 use crate::controllers;
 use crate::controllers::util::RequestPartsExt;
+use crate::middleware::app::RequestApp;
+use crate::middleware::csrf;
 use crate::middleware::log_request::RequestLogExt;
 use crate::models::token::{CrateScope, EndpointScope};
-use crate::models::{ApiToken, User};
+use crate::models::{ApiToken, Crate, CrateOwner, OrganizationRole, OwnerKind, User};
+use crate::schema::{crate_owners, crates, organization_members};
 use crate::util::errors::{
     account_locked, forbidden, internal, AppResult, InsecurelyGeneratedTokenRevoked,
 };
 use crate::util::token::HashedToken;
 use chrono::Utc;
 use crates_io_session::SessionExtension;
-use diesel_async::AsyncPgConnection;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::header;
 use http::request::Parts;
 
@@ -93,8 +98,28 @@ impl AuthCheck {
                     "this token does not have the required permissions to perform this action",
                 ));
             }
+
+            if let Some(organization_id) = token.organization_id {
+                if !self
+                    .organization_scope_matches(conn, organization_id, token.user_id)
+                    .await?
+                {
+                    let error_message = "Organization scope mismatch";
+                    parts.request_log().add("cause", error_message);
+
+                    return Err(forbidden(
+                        "this token can only be used for crates owned by its organization",
+                    ));
+                }
+            }
         }
 
+        let kind = match auth {
+            Authentication::Cookie(_) => UserUsageKind::Cookie,
+            Authentication::Token(_) => UserUsageKind::Token,
+        };
+        parts.app().api_usage.record_user(auth.user_id(), kind);
+
         Ok(auth)
     }
 
@@ -111,6 +136,47 @@ impl AuthCheck {
         }
     }
 
+    /// Organization tokens can only be used for crates that are owned by the
+    /// organization, and only while the token creator is still allowed to
+    /// publish on behalf of the organization.
+    async fn organization_scope_matches(
+        &self,
+        conn: &mut AsyncPgConnection,
+        organization_id: i32,
+        user_id: i32,
+    ) -> QueryResult<bool> {
+        let Some(crate_name) = &self.crate_name else {
+            return Ok(false);
+        };
+
+        let role: Option<OrganizationRole> = organization_members::table
+            .find((organization_id, user_id))
+            .select(organization_members::role)
+            .first(conn)
+            .await
+            .optional()?;
+
+        if !matches!(
+            role,
+            Some(OrganizationRole::Admin | OrganizationRole::Publisher)
+        ) {
+            return Ok(false);
+        }
+
+        let owned_crates = CrateOwner::by_owner_kind(OwnerKind::Organization)
+            .filter(crate_owners::owner_id.eq(organization_id))
+            .select(crate_owners::crate_id);
+
+        let crate_id: Option<i32> = Crate::by_name(crate_name)
+            .filter(crates::id.eq_any(owned_crates))
+            .select(crates::id)
+            .first(conn)
+            .await
+            .optional()?;
+
+        Ok(crate_id.is_some())
+    }
+
     fn crate_scope_matches(&self, token_scopes: Option<&Vec<CrateScope>>) -> bool {
         match (&token_scopes, &self.crate_name) {
             // The token is a legacy token.
@@ -195,6 +261,8 @@ async fn authenticate_via_cookie(
 
     parts.request_log().add("uid", id);
 
+    csrf::verify(parts, session)?;
+
     Ok(Some(CookieAuthentication { user }))
 }
 
@@ -260,7 +328,7 @@ async fn authenticate(parts: &Parts, conn: &mut AsyncPgConnection) -> AppResult<
     return Err(forbidden("this action requires authentication"));
 }
 
-fn ensure_not_locked(user: &User) -> AppResult<()> {
+pub fn ensure_not_locked(user: &User) -> AppResult<()> {
     if let Some(reason) = &user.account_lock_reason {
         let still_locked = user
             .account_lock_until
@@ -7,6 +7,9 @@
 //! the worker thread), we will rebuild the runner and try again up to 5 times.
 //! After the 5th occurrence, we will panic.
 //!
+//! While the read-only mode is enabled through the admin API, the runner
+//! pauses and doesn't run any jobs.
+//!
 //! Usage:
 //!      cargo run --bin background-worker
 
@@ -17,16 +20,20 @@ use anyhow::Context;
 use crates_io::cloudfront::CloudFront;
 use crates_io::db::make_manager_config;
 use crates_io::fastly::Fastly;
+use crates_io::models::FeatureFlag;
 use crates_io::storage::Storage;
 use crates_io::worker::{Environment, RunnerExt};
 use crates_io::{config, Emails};
 use crates_io::{db, ssh};
 use crates_io_env_vars::var;
+use crates_io_github::RealGitHubClient;
 use crates_io_index::RepositoryConfig;
+use crates_io_outbound::OutboundClient;
 use crates_io_team_repo::TeamRepoImpl;
 use crates_io_worker::Runner;
 use diesel_async::pooled_connection::deadpool::Pool;
 use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use futures_util::FutureExt;
 use object_store::prefix::PrefixStore;
 use reqwest::Client;
 use secrecy::ExposeSecret;
@@ -69,8 +76,11 @@ fn main() -> anyhow::Result<()> {
 
     let repository_config = RepositoryConfig::from_environment()?;
 
-    let cloudfront = CloudFront::from_environment();
     let storage = Arc::new(Storage::from_config(&config.storage));
+    let standby_storage = config
+        .standby_storage
+        .as_ref()
+        .map(|config| Arc::new(Storage::from_config(config)));
 
     let downloads_archive_store = PrefixStore::new(storage.as_inner(), "archive/version-downloads");
     let downloads_archive_store = Box::new(downloads_archive_store);
@@ -80,9 +90,13 @@ fn main() -> anyhow::Result<()> {
         .build()
         .expect("Couldn't build client");
 
-    let emails = Emails::from_environment(&config);
-    let fastly = Fastly::from_environment(client.clone());
+    let outbound = OutboundClient::new(client.clone());
+
+    let emails = Emails::from_config(&config, outbound.clone());
+    let cloudfront = CloudFront::from_environment(outbound.clone())?;
+    let fastly = Fastly::from_environment(outbound.clone())?;
     let team_repo = TeamRepoImpl::default();
+    let github = Arc::new(RealGitHubClient::new(outbound));
 
     let manager_config = make_manager_config(config.db.enforce_tls);
     let manager = AsyncDieselConnectionManager::new_with_config(db_url, manager_config);
@@ -94,10 +108,13 @@ fn main() -> anyhow::Result<()> {
         .maybe_cloudfront(cloudfront)
         .maybe_fastly(fastly)
         .storage(storage)
+        .maybe_standby_storage(standby_storage)
         .downloads_archive_store(downloads_archive_store)
         .deadpool(deadpool.clone())
         .emails(emails)
         .team_repo(Box::new(team_repo))
+        .github(github)
+        .http_client(client)
         .build();
 
     let environment = Arc::new(environment);
@@ -115,7 +132,19 @@ fn main() -> anyhow::Result<()> {
         .configure_default_queue(|queue| queue.num_workers(5))
         .configure_queue("downloads", |queue| queue.num_workers(1))
         .configure_queue("repository", |queue| queue.num_workers(1))
-        .register_crates_io_job_types();
+        .register_crates_io_job_types()
+        .pause_when(|conn| {
+            async move {
+                let name = FeatureFlag::READ_ONLY_MODE;
+                FeatureFlag::is_enabled(conn, name)
+                    .await
+                    .unwrap_or_else(|error| {
+                        warn!("Failed to load the read-only mode flag: {error}");
+                        false
+                    })
+            }
+            .boxed()
+        });
 
     runtime.block_on(async {
         let handle = runner.start();
@@ -1,7 +1,7 @@
 use crate::dialoguer;
 use anyhow::Context;
 use crates_io::models::update_default_version;
-use crates_io::schema::crates;
+use crates_io::schema::{crates, readme_renderings};
 use crates_io::storage::Storage;
 use crates_io::worker::jobs;
 use crates_io::{db, schema::versions};
@@ -34,7 +34,7 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
         .await
         .context("Failed to establish database connection")?;
 
-    let store = Storage::from_environment();
+    let store = Storage::from_environment()?;
 
     let crate_id: i32 = crates::table
         .select(crates::id)
@@ -59,6 +59,17 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
         }
     }
 
+    let readme_checksums: Vec<String> = readme_renderings::table
+        .inner_join(versions::table)
+        .filter(versions::crate_id.eq(crate_id))
+        .filter(versions::num.eq_any(&opts.versions))
+        .filter(readme_renderings::checksum.is_not_null())
+        .select(readme_renderings::checksum.assume_not_null())
+        .distinct()
+        .load(&mut conn)
+        .await
+        .context("Failed to look up readme checksums from the database")?;
+
     let opts = conn.transaction(|conn| async move {
         let crate_name = &opts.crate_name;
 
@@ -121,5 +132,36 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
         }
     }
 
+    // Readmes are shared between versions with identical readmes, so they
+    // can only be deleted if none of the remaining versions uses them.
+    for checksum in &readme_checksums {
+        let still_used = diesel::select(diesel::dsl::exists(
+            readme_renderings::table
+                .inner_join(versions::table)
+                .filter(versions::crate_id.eq(crate_id))
+                .filter(readme_renderings::checksum.eq(checksum)),
+        ))
+        .get_result::<bool>(&mut conn)
+        .await;
+
+        match still_used {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(error) => {
+                warn!(%crate_name, %checksum, ?error, "Failed to check if readme file is still used");
+                continue;
+            }
+        }
+
+        debug!(%crate_name, %checksum, "Deleting readme file from S3");
+        match store.delete_readme_by_checksum(crate_name, checksum).await {
+            Err(object_store::Error::NotFound { .. }) => {}
+            Err(error) => {
+                warn!(%crate_name, %checksum, ?error, "Failed to delete readme file from S3")
+            }
+            Ok(_) => {}
+        }
+    }
+
     Ok(())
 }
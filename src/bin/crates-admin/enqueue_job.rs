@@ -17,13 +17,53 @@ use diesel_async::RunQueryDsl;
 pub enum Command {
     ArchiveVersionDownloads {
         #[arg(long)]
-        /// The date before which to archive version downloads (default: 90 days ago)
+        /// Archive the monthly partitions that end before this date (default: 90 days ago)
         before: Option<NaiveDate>,
     },
     IndexVersionDownloadsArchive,
+    PartitionVersionDownloads,
+    BackfillPublishMethods {
+        #[arg(long, default_value_t = 0)]
+        /// The version id after which to start the backfill
+        after: i32,
+    },
+    BackfillRustVersions {
+        #[arg(long, default_value_t = 0)]
+        /// The version id after which to start the backfill
+        after: i32,
+    },
+    BackfillSemverOrd {
+        #[arg(long, default_value_t = 0)]
+        /// The version id after which to start the backfill
+        after: i32,
+    },
     UpdateDownloads,
+    UpdateRecentCrateDownloads,
+    CheckRecentCrateDownloads,
+    UpdateRegistryStats {
+        #[arg(long)]
+        /// The date to calculate the statistics for (default: yesterday)
+        date: Option<NaiveDate>,
+    },
+    UpdateTrendingCrates,
+    UpdateUserStats,
+    UpdateCrateDependentStats,
+    UpdateCrateScores,
+    UpdateAggregateDownloads {
+        #[arg(long)]
+        /// The date to aggregate the downloads for (default: yesterday)
+        date: Option<NaiveDate>,
+    },
     CleanProcessedLogFiles,
+    DeleteExpiredIdempotencyKeys,
     DumpDb,
+    ExportAnalytics {
+        #[arg(long)]
+        /// The date of the download counts to export (default: yesterday)
+        date: Option<NaiveDate>,
+    },
+    ExportMetadataSnapshot,
+    GenerateSitemaps,
     DailyDbMaintenance,
     SquashIndex,
     NormalizeIndex {
@@ -34,13 +74,25 @@ pub enum Command {
         #[arg()]
         name: String,
     },
+    ProcessCdnInvalidationQueue,
     ProcessCdnLogQueue(jobs::ProcessCdnLogQueue),
+    PurgeCrateSurrogateKey {
+        name: String,
+    },
+    ReconcileStagedCrateFiles,
+    ReconcileStorage,
+    RefreshGitHubCache,
     SyncAdmins {
         /// Force a sync even if one is already in progress
         #[arg(long)]
         force: bool,
     },
+    SendFollowNotifications,
     SendTokenExpiryNotifications,
+    SendWeeklyDigests,
+    SuggestCategories {
+        name: String,
+    },
     SyncCratesFeed,
     SyncToGitIndex {
         name: String,
@@ -49,6 +101,7 @@ pub enum Command {
         name: String,
     },
     SyncUpdatesFeed,
+    VerifyReplication,
 }
 
 pub async fn run(command: Command) -> Result<()> {
@@ -68,6 +121,24 @@ pub async fn run(command: Command) -> Result<()> {
                 .enqueue(&mut conn)
                 .await?;
         }
+        Command::PartitionVersionDownloads => {
+            jobs::PartitionVersionDownloads.enqueue(&mut conn).await?;
+        }
+        Command::BackfillPublishMethods { after } => {
+            jobs::BackfillPublishMethods::after(after)
+                .enqueue(&mut conn)
+                .await?;
+        }
+        Command::BackfillRustVersions { after } => {
+            jobs::BackfillRustVersions::after(after)
+                .enqueue(&mut conn)
+                .await?;
+        }
+        Command::BackfillSemverOrd { after } => {
+            jobs::BackfillSemverOrd::after(after)
+                .enqueue(&mut conn)
+                .await?;
+        }
         Command::UpdateDownloads => {
             let count: i64 = background_jobs::table
                 .filter(background_jobs::job_type.eq(jobs::UpdateDownloads::JOB_NAME))
@@ -84,12 +155,59 @@ pub async fn run(command: Command) -> Result<()> {
                 jobs::UpdateDownloads.enqueue(&mut conn).await?;
             }
         }
+        Command::UpdateRecentCrateDownloads => {
+            jobs::UpdateRecentCrateDownloads.enqueue(&mut conn).await?;
+        }
+        Command::CheckRecentCrateDownloads => {
+            jobs::CheckRecentCrateDownloads.enqueue(&mut conn).await?;
+        }
+        Command::UpdateRegistryStats { date } => {
+            date.map(jobs::UpdateRegistryStats::for_date)
+                .unwrap_or_default()
+                .enqueue(&mut conn)
+                .await?;
+        }
+        Command::UpdateTrendingCrates => {
+            jobs::UpdateTrendingCrates.enqueue(&mut conn).await?;
+        }
+        Command::UpdateUserStats => {
+            jobs::UpdateUserStats.enqueue(&mut conn).await?;
+        }
+        Command::UpdateCrateDependentStats => {
+            jobs::UpdateCrateDependentStats.enqueue(&mut conn).await?;
+        }
+        Command::UpdateCrateScores => {
+            jobs::UpdateCrateScores.enqueue(&mut conn).await?;
+        }
+        Command::UpdateAggregateDownloads { date } => {
+            date.map(jobs::UpdateAggregateDownloads::for_date)
+                .unwrap_or_default()
+                .enqueue(&mut conn)
+                .await?;
+        }
         Command::CleanProcessedLogFiles => {
             jobs::CleanProcessedLogFiles.enqueue(&mut conn).await?;
         }
+        Command::DeleteExpiredIdempotencyKeys => {
+            jobs::DeleteExpiredIdempotencyKeys
+                .enqueue(&mut conn)
+                .await?;
+        }
         Command::DumpDb => {
             jobs::DumpDb.enqueue(&mut conn).await?;
         }
+        Command::ExportAnalytics { date } => {
+            date.map(jobs::ExportAnalytics::for_date)
+                .unwrap_or_default()
+                .enqueue(&mut conn)
+                .await?;
+        }
+        Command::ExportMetadataSnapshot => {
+            jobs::ExportMetadataSnapshot.enqueue(&mut conn).await?;
+        }
+        Command::GenerateSitemaps => {
+            jobs::GenerateSitemaps.enqueue(&mut conn).await?;
+        }
         Command::SyncAdmins { force } => {
             if !force {
                 // By default, we don't want to enqueue a sync if one is already
@@ -115,9 +233,26 @@ pub async fn run(command: Command) -> Result<()> {
         Command::DailyDbMaintenance => {
             jobs::DailyDbMaintenance.enqueue(&mut conn).await?;
         }
+        Command::ProcessCdnInvalidationQueue => {
+            jobs::ProcessCdnInvalidationQueue.enqueue(&mut conn).await?;
+        }
         Command::ProcessCdnLogQueue(job) => {
             job.enqueue(&mut conn).await?;
         }
+        Command::PurgeCrateSurrogateKey { name } => {
+            jobs::PurgeSurrogateKeys::krate(&name)
+                .enqueue(&mut conn)
+                .await?;
+        }
+        Command::ReconcileStagedCrateFiles => {
+            jobs::ReconcileStagedCrateFiles.enqueue(&mut conn).await?;
+        }
+        Command::ReconcileStorage => {
+            jobs::ReconcileStorage.enqueue(&mut conn).await?;
+        }
+        Command::RefreshGitHubCache => {
+            jobs::RefreshGitHubCache.enqueue(&mut conn).await?;
+        }
         Command::SquashIndex => {
             jobs::SquashIndex.enqueue(&mut conn).await?;
         }
@@ -142,11 +277,33 @@ pub async fn run(command: Command) -> Result<()> {
 
             jobs::CheckTyposquat::new(&name).enqueue(&mut conn).await?;
         }
+        Command::SendFollowNotifications => {
+            jobs::SendFollowNotifications.enqueue(&mut conn).await?;
+        }
         Command::SendTokenExpiryNotifications => {
             jobs::SendTokenExpiryNotifications
                 .enqueue(&mut conn)
                 .await?;
         }
+        Command::SendWeeklyDigests => {
+            jobs::SendWeeklyDigests.enqueue(&mut conn).await?;
+        }
+        Command::SuggestCategories { name } => {
+            let crate_id = crates::table
+                .filter(crates::name.eq(&name))
+                .select(crates::id)
+                .first::<i32>(&mut conn)
+                .await
+                .optional()?;
+
+            let Some(crate_id) = crate_id else {
+                anyhow::bail!("cannot suggest categories for a crate that doesn't exist: {name}");
+            };
+
+            jobs::SuggestCategories::new(crate_id)
+                .enqueue(&mut conn)
+                .await?;
+        }
         Command::SyncCratesFeed => {
             jobs::rss::SyncCratesFeed.enqueue(&mut conn).await?;
         }
@@ -161,6 +318,9 @@ pub async fn run(command: Command) -> Result<()> {
         Command::SyncUpdatesFeed => {
             jobs::rss::SyncUpdatesFeed.enqueue(&mut conn).await?;
         }
+        Command::VerifyReplication => {
+            jobs::VerifyReplication.enqueue(&mut conn).await?;
+        }
     };
 
     Ok(())
@@ -17,7 +17,9 @@ use crates_io_tarball::{Manifest, StringOrBool};
 use diesel::prelude::*;
 use diesel_async::async_connection_wrapper::AsyncConnectionWrapper;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use hex::ToHex;
 use reqwest::{header, Client};
+use sha2::{Digest, Sha256};
 use std::str::FromStr;
 use tokio_tar::{self, Archive};
 
@@ -51,7 +53,7 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
 
     let mut conn = AsyncConnectionWrapper::<AsyncPgConnection>::from(conn);
 
-    let storage = Arc::new(Storage::from_environment());
+    let storage = Arc::new(Storage::from_environment()?);
 
     let start_time = Utc::now();
 
@@ -111,31 +113,35 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
 
         let mut tasks = Vec::with_capacity(page_size);
         for (version, krate_name) in versions {
-            Version::record_readme_rendering(version.id, &mut conn)
-                .await
-                .context("Couldn't record rendering time")?;
-
+            let version_id = version.id;
             let client = client.clone();
             let storage = storage.clone();
             let handle = tokio::spawn(async move {
                 println!("[{}-{}] Rendering README...", krate_name, version.num);
                 let readme = get_readme(&storage, &client, &version, &krate_name).await?;
-                if !readme.is_empty() {
-                    storage
-                        .upload_readme(&krate_name, &version.num, readme.into())
-                        .await
-                        .context("Failed to upload rendered README file to S3")?;
+                if readme.is_empty() {
+                    return Ok(None);
                 }
 
-                Ok::<_, anyhow::Error>(())
+                let checksum: String = Sha256::digest(&readme).encode_hex();
+                storage
+                    .upload_readme(&krate_name, &checksum, readme.into())
+                    .await
+                    .context("Failed to upload rendered README file to S3")?;
+
+                Ok::<_, anyhow::Error>(Some(checksum))
             });
-            tasks.push(handle);
+            tasks.push((version_id, handle));
         }
-        for handle in tasks {
+        for (version_id, handle) in tasks {
             match handle.await {
                 Err(err) => println!("Task panicked: {err:?}"),
                 Ok(Err(err)) => println!("Task failed: {err:?}"),
-                _ => {}
+                Ok(Ok(checksum)) => {
+                    Version::record_readme_rendering(version_id, checksum.as_deref(), &mut conn)
+                        .await
+                        .context("Couldn't record rendering time")?;
+                }
             }
         }
     }
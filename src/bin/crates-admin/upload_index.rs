@@ -16,7 +16,7 @@ pub struct Opts {
 }
 
 pub async fn run(opts: Opts) -> anyhow::Result<()> {
-    let storage = Storage::from_environment();
+    let storage = Storage::from_environment()?;
 
     println!("fetching git repo");
     let config = RepositoryConfig::from_environment()?;
@@ -1,18 +1,20 @@
 #[macro_use]
 extern crate tracing;
 
+use crates_io::http_server;
 use crates_io::middleware::normalize_path::normalize_path;
 use crates_io::{metrics::LogEncoder, App, Emails};
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use axum::ServiceExt;
 use crates_io_github::RealGitHubClient;
+use crates_io_outbound::OutboundClient;
 use prometheus::Encoder;
 use reqwest::Client;
 use std::io::Write;
-use std::net::SocketAddr;
 use tokio::net::TcpListener;
 use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::oneshot;
 use tower::Layer;
 
 const CORE_THREADS: usize = 4;
@@ -27,9 +29,9 @@ fn main() -> anyhow::Result<()> {
 
     let config = crates_io::config::Server::from_environment()?;
 
-    let emails = Emails::from_environment(&config);
+    let client = OutboundClient::new(Client::new());
+    let emails = Emails::from_config(&config, client.clone());
 
-    let client = Client::new();
     let github = RealGitHubClient::new(client);
     let github = Box::new(github);
 
@@ -55,10 +57,10 @@ fn main() -> anyhow::Result<()> {
 
     let rt = builder.build()?;
 
-    let make_service = axum_router.into_make_service_with_connect_info::<SocketAddr>();
+    let shutdown_timeout = app.config.shutdown_timeout;
 
     // Block the main thread until the server has shutdown
-    rt.block_on(async {
+    let deadline = rt.block_on(async {
         // Create a `TcpListener` using tokio.
         let listener = TcpListener::bind((app.config.ip, app.config.port)).await?;
 
@@ -69,11 +71,53 @@ fn main() -> anyhow::Result<()> {
         info!("Listening at http://{addr}");
 
         // Run the server with graceful shutdown
-        axum::serve(listener, make_service)
-            .with_graceful_shutdown(shutdown_signal())
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = http_server::serve(app.clone(), listener, axum_router, async move {
+            let _ = shutdown_rx.await;
+        });
+        let mut server = tokio::spawn(server);
+
+        // Start the background task periodically persisting the API usage.
+        tokio::spawn(persist_api_usage_task(app.clone()));
+
+        tokio::select! {
+            result = &mut server => {
+                result?;
+                return Ok::<_, anyhow::Error>(Instant::now());
+            }
+            _ = shutdown_signal() => {}
+        }
+
+        // Stop accepting new connections and wait for the in-flight requests
+        // to finish, but don't let a slow client delay the shutdown forever.
+        info!(
+            ?shutdown_timeout,
+            "Shutting down, draining in-flight requests"
+        );
+        let deadline = Instant::now() + shutdown_timeout;
+        let _ = shutdown_tx.send(());
+
+        match tokio::time::timeout_at(deadline.into(), server).await {
+            Ok(result) => result?,
+            Err(_) => warn!("Timed out while draining in-flight requests"),
+        }
+
+        // Save the requests that were counted since the last interval.
+        let persist = persist_api_usage(&app);
+        if tokio::time::timeout_at(deadline.into(), persist)
             .await
+            .is_err()
+        {
+            warn!("Timed out while persisting the API usage");
+        }
+
+        Ok(deadline)
     })?;
 
+    // Give blocking tasks that were spawned by the request handlers the rest
+    // of the shutdown timeout to finish.
+    rt.shutdown_timeout(deadline.saturating_duration_since(Instant::now()));
+
     info!("Server has gracefully shutdown!");
     Ok(())
 }
@@ -99,6 +143,28 @@ async fn shutdown_signal() {
     }
 }
 
+async fn persist_api_usage_task(app: Arc<App>) {
+    let mut interval = tokio::time::interval(app.config.api_usage_persist_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        persist_api_usage(&app).await;
+    }
+}
+
+async fn persist_api_usage(app: &App) {
+    let result = async {
+        let mut conn = app.db_write().await?;
+        app.api_usage.persist(&mut conn).await?;
+        Ok::<_, anyhow::Error>(())
+    };
+
+    if let Err(err) = result.await {
+        error!(?err, "Failed to persist the API usage");
+    }
+}
+
 fn log_instance_metrics_thread(app: Arc<App>) {
     // Only run the thread if the configuration is provided
     let interval = match app.config.instance_metrics_log_every_seconds {
@@ -1,19 +1,34 @@
+use anyhow::anyhow;
 use aws_credential_types::Credentials;
 use aws_sdk_cloudfront::config::retry::RetryConfig;
 use aws_sdk_cloudfront::config::{BehaviorVersion, Region};
 use aws_sdk_cloudfront::types::{InvalidationBatch, Paths};
 use aws_sdk_cloudfront::{Client, Config};
+use crates_io_env_vars::{required_var, var};
+use crates_io_outbound::OutboundClient;
+
+const CLOUDFRONT_API_HOST: &str = "cloudfront.amazonaws.com";
+
+/// The maximum number of paths in a single invalidation request.
+pub const MAX_PATHS_PER_INVALIDATION: usize = 3000;
 
 pub struct CloudFront {
     client: Client,
+    outbound: OutboundClient,
     distribution_id: String,
 }
 
 impl CloudFront {
-    pub fn from_environment() -> Option<Self> {
-        let distribution_id = dotenvy::var("CLOUDFRONT_DISTRIBUTION").ok()?;
-        let access_key = dotenvy::var("AWS_ACCESS_KEY").expect("missing AWS_ACCESS_KEY");
-        let secret_key = dotenvy::var("AWS_SECRET_KEY").expect("missing AWS_SECRET_KEY");
+    /// The CloudFront requests are sent by the AWS SDK, which has its own
+    /// retries, but they share the circuit breaker and the metrics of the
+    /// `outbound` client.
+    pub fn from_environment(outbound: OutboundClient) -> anyhow::Result<Option<Self>> {
+        let Some(distribution_id) = var("CLOUDFRONT_DISTRIBUTION")? else {
+            return Ok(None);
+        };
+
+        let access_key = required_var("AWS_ACCESS_KEY")?;
+        let secret_key = required_var("AWS_SECRET_KEY")?;
 
         let credentials = Credentials::from_keys(access_key, secret_key, None);
 
@@ -26,10 +41,11 @@ impl CloudFront {
 
         let client = Client::from_conf(config);
 
-        Some(Self {
+        Ok(Some(Self {
             client,
+            outbound,
             distribution_id,
-        })
+        }))
     }
 
     /// Invalidate a file on CloudFront
@@ -37,15 +53,39 @@ impl CloudFront {
     /// `path` is the path to the file to invalidate, such as `config.json`, or `re/ge/regex`
     #[instrument(skip(self))]
     pub async fn invalidate(&self, path: &str) -> anyhow::Result<()> {
-        let path = if path.starts_with('/') {
-            path.to_string()
-        } else {
-            format!("/{path}")
-        };
+        self.invalidate_many(&[path]).await
+    }
+
+    /// Invalidate multiple files on CloudFront with a single invalidation
+    /// request
+    ///
+    /// CloudFront accepts at most [`MAX_PATHS_PER_INVALIDATION`] paths per
+    /// invalidation request.
+    #[instrument(skip_all, fields(paths.len = paths.len()))]
+    pub async fn invalidate_many(&self, paths: &[&str]) -> anyhow::Result<()> {
+        if paths.len() > MAX_PATHS_PER_INVALIDATION {
+            return Err(anyhow!(
+                "CloudFront accepts at most {MAX_PATHS_PER_INVALIDATION} paths per invalidation"
+            ));
+        }
+
+        let paths = paths
+            .iter()
+            .map(|path| {
+                if path.starts_with('/') {
+                    path.to_string()
+                } else {
+                    format!("/{path}")
+                }
+            })
+            .collect::<Vec<_>>();
 
         let now = chrono::offset::Utc::now().timestamp_micros();
 
-        let paths = Paths::builder().quantity(1).items(path).build()?;
+        let paths = Paths::builder()
+            .quantity(paths.len() as i32)
+            .set_items(Some(paths))
+            .build()?;
 
         let invalidation_batch = InvalidationBatch::builder()
             .caller_reference(format!("{now}"))
@@ -60,14 +100,24 @@ impl CloudFront {
 
         debug!("Sending invalidation request");
 
-        match invalidation_request.send().await {
+        let result = self
+            .outbound
+            .guard(CLOUDFRONT_API_HOST, async {
+                invalidation_request
+                    .send()
+                    .await
+                    .map_err(anyhow::Error::from)
+            })
+            .await;
+
+        match result {
             Ok(_) => {
                 debug!("Invalidation request successful");
                 Ok(())
             }
             Err(error) => {
                 warn!(?error, "Invalidation request failed");
-                Err(error.into())
+                Err(error)
             }
         }
     }
@@ -2,6 +2,10 @@ mod base;
 mod cdn_log_queue;
 mod cdn_log_storage;
 mod database_pools;
+mod email;
+mod http_server;
+mod oauth;
+mod security_headers;
 mod sentry;
 mod server;
 
@@ -9,5 +13,9 @@ pub use self::base::Base;
 pub use self::cdn_log_queue::CdnLogQueueConfig;
 pub use self::cdn_log_storage::CdnLogStorageConfig;
 pub use self::database_pools::{DatabasePools, DbPoolConfig};
+pub use self::email::{EmailConfig, MailgunConfig, SesConfig, SmtpConfig};
+pub use self::http_server::HttpServerConfig;
+pub use self::oauth::OAuthClientConfig;
+pub use self::security_headers::{SecurityHeadersConfig, CSP_REPORT_PATH};
 pub use self::sentry::SentryConfig;
-pub use self::server::Server;
+pub use self::server::{AllowedOrigins, Server};
@@ -4,6 +4,10 @@
 //! - `READ_ONLY_REPLICA_URL`: The URL of an optional postgres read-only replica database.
 //! - `DB_PRIMARY_ASYNC_POOL_SIZE`: The number of connections of the primary database.
 //! - `DB_REPLICA_ASYNC_POOL_SIZE`: The number of connections of the read-only / replica database.
+//! - `DB_PRIMARY_ASYNC_MAX_POOL_SIZE`: The number of connections the primary pool can be resized to
+//!   through the admin API. Defaults to `DB_PRIMARY_ASYNC_POOL_SIZE`.
+//! - `DB_REPLICA_ASYNC_MAX_POOL_SIZE`: The number of connections the replica pool can be resized to
+//!   through the admin API. Defaults to `DB_REPLICA_ASYNC_POOL_SIZE`.
 //! - `DB_PRIMARY_MIN_IDLE`: The primary pool will maintain at least this number of connections.
 //! - `DB_REPLICA_MIN_IDLE`: The replica pool will maintain at least this number of connections.
 //! - `DB_OFFLINE`: If set to `leader` then use the read-only follower as if it was the leader.
@@ -48,6 +52,9 @@ pub struct DbPoolConfig {
     pub url: SecretString,
     pub read_only_mode: bool,
     pub pool_size: usize,
+    /// Upper bound for resizing the pool at runtime. Never lower than
+    /// `pool_size`.
+    pub max_pool_size: usize,
     pub min_idle: Option<u32>,
 }
 
@@ -75,6 +82,13 @@ impl DatabasePools {
         let replica_async_pool_size =
             var_parsed("DB_REPLICA_ASYNC_POOL_SIZE")?.unwrap_or(Self::DEFAULT_POOL_SIZE);
 
+        let primary_async_max_pool_size = var_parsed("DB_PRIMARY_ASYNC_MAX_POOL_SIZE")?
+            .unwrap_or(primary_async_pool_size)
+            .max(primary_async_pool_size);
+        let replica_async_max_pool_size = var_parsed("DB_REPLICA_ASYNC_MAX_POOL_SIZE")?
+            .unwrap_or(replica_async_pool_size)
+            .max(replica_async_pool_size);
+
         let primary_min_idle = var_parsed("DB_PRIMARY_MIN_IDLE")?;
         let replica_min_idle = var_parsed("DB_REPLICA_MIN_IDLE")?;
 
@@ -101,6 +115,7 @@ impl DatabasePools {
                     })?,
                     read_only_mode: true,
                     pool_size: primary_async_pool_size,
+                    max_pool_size: primary_async_max_pool_size,
                     min_idle: primary_min_idle,
                 },
                 replica: None,
@@ -116,6 +131,7 @@ impl DatabasePools {
                     url: leader_url,
                     read_only_mode,
                     pool_size: primary_async_pool_size,
+                    max_pool_size: primary_async_max_pool_size,
                     min_idle: primary_min_idle,
                 },
                 replica: None,
@@ -130,6 +146,7 @@ impl DatabasePools {
                     url: leader_url,
                     read_only_mode,
                     pool_size: primary_async_pool_size,
+                    max_pool_size: primary_async_max_pool_size,
                     min_idle: primary_min_idle,
                 },
                 replica: follower_url.map(|url| DbPoolConfig {
@@ -139,6 +156,7 @@ impl DatabasePools {
                     // connection is opened read-only even when attached to a writeable database.
                     read_only_mode: true,
                     pool_size: replica_async_pool_size,
+                    max_pool_size: replica_async_max_pool_size,
                     min_idle: replica_min_idle,
                 }),
                 tcp_timeout_ms,
@@ -0,0 +1,299 @@
+//! Configuration of the backend that is used to send emails
+//!
+//! - `EMAIL_BACKEND`: Either `smtp`, `mailgun` or `ses`. Defaults to `smtp` if the
+//!   `MAILGUN_SMTP_*` variables are set, otherwise the emails are stored on the local file system.
+//! - `EMAIL_FROM`: The sender address for the `mailgun` and `ses` backends. Defaults to
+//!   `noreply@crates.io`.
+//!
+//! The `smtp` backend uses the following variables:
+//!
+//! - `MAILGUN_SMTP_LOGIN`: The login, which is also used as the sender address.
+//! - `MAILGUN_SMTP_PASSWORD`: The password of the login.
+//! - `MAILGUN_SMTP_SERVER`: The host name of the SMTP server.
+//!
+//! The `mailgun` backend sends the emails through the HTTP API of Mailgun, for deployments that
+//! can't connect to SMTP servers:
+//!
+//! - `MAILGUN_API_KEY`: The API key of the Mailgun account.
+//! - `MAILGUN_DOMAIN`: The sending domain of the Mailgun account.
+//! - `MAILGUN_API_URL`: The base URL of the API. Defaults to `https://api.mailgun.net`, which
+//!   needs to be changed to `https://api.eu.mailgun.net` for domains in the EU region.
+//!
+//! The `ses` backend sends the emails through the HTTP API of Amazon SES:
+//!
+//! - `SES_REGION`: The AWS region of the SES account, e.g. `us-west-1`.
+//! - `SES_ENDPOINT`: The base URL of the API. Defaults to the regional endpoint, e.g.
+//!   `https://email.us-west-1.amazonaws.com`.
+//! - `AWS_ACCESS_KEY` and `AWS_SECRET_KEY`: The credentials of the AWS account.
+
+use anyhow::{anyhow, bail, Context};
+use crates_io_env_vars::{required_var, var};
+use lettre::Address;
+use secrecy::SecretString;
+
+const DEFAULT_FROM: &str = "noreply@crates.io";
+const DEFAULT_MAILGUN_API_URL: &str = "https://api.mailgun.net";
+
+#[derive(Debug)]
+pub enum EmailConfig {
+    Smtp(SmtpConfig),
+    Mailgun(MailgunConfig),
+    Ses(SesConfig),
+}
+
+#[derive(Debug)]
+pub struct SmtpConfig {
+    pub login: Address,
+    pub password: SecretString,
+    pub server: String,
+}
+
+#[derive(Debug)]
+pub struct MailgunConfig {
+    pub from: Address,
+    pub api_key: SecretString,
+    pub domain: String,
+    pub api_url: String,
+}
+
+#[derive(Debug)]
+pub struct SesConfig {
+    pub from: Address,
+    pub region: String,
+    pub endpoint: String,
+    pub access_key: String,
+    pub secret_key: SecretString,
+}
+
+impl EmailConfig {
+    /// Returns `None` if no backend is configured, in which case the emails
+    /// are stored on the local file system.
+    pub fn from_environment() -> anyhow::Result<Option<Self>> {
+        let backend = var("EMAIL_BACKEND")?;
+        match backend.as_deref() {
+            Some("smtp") => {
+                let smtp = SmtpConfig::from_environment()?;
+                let smtp = smtp.ok_or_else(|| {
+                    anyhow!("MAILGUN_SMTP_* must be set when using EMAIL_BACKEND=smtp")
+                })?;
+                Ok(Some(Self::Smtp(smtp)))
+            }
+            Some("mailgun") => Ok(Some(Self::Mailgun(MailgunConfig::from_environment()?))),
+            Some("ses") => Ok(Some(Self::Ses(SesConfig::from_environment()?))),
+            Some(backend) => {
+                bail!("EMAIL_BACKEND must be one of `smtp`, `mailgun` or `ses`, got `{backend}`")
+            }
+            None => Ok(SmtpConfig::from_environment()?.map(Self::Smtp)),
+        }
+    }
+}
+
+impl SmtpConfig {
+    /// Returns `None` if none of the environment variables are set, and fails
+    /// if only some of them are set.
+    pub fn from_environment() -> anyhow::Result<Option<Self>> {
+        let login = var("MAILGUN_SMTP_LOGIN")?;
+        let password = var("MAILGUN_SMTP_PASSWORD")?;
+        let server = var("MAILGUN_SMTP_SERVER")?;
+
+        match (login, password, server) {
+            (Some(login), Some(password), Some(server)) => Ok(Some(Self {
+                login: login
+                    .parse()
+                    .context("MAILGUN_SMTP_LOGIN must be an email address")?,
+                password: password.into(),
+                server,
+            })),
+            (None, None, None) => Ok(None),
+            _ => bail!(
+                "MAILGUN_SMTP_LOGIN, MAILGUN_SMTP_PASSWORD and MAILGUN_SMTP_SERVER must be set together"
+            ),
+        }
+    }
+}
+
+impl MailgunConfig {
+    pub fn from_environment() -> anyhow::Result<Self> {
+        Ok(Self {
+            from: from_address()?,
+            api_key: required_var("MAILGUN_API_KEY")?.into(),
+            domain: required_var("MAILGUN_DOMAIN")?,
+            api_url: var("MAILGUN_API_URL")?.unwrap_or_else(|| DEFAULT_MAILGUN_API_URL.into()),
+        })
+    }
+}
+
+impl SesConfig {
+    pub fn from_environment() -> anyhow::Result<Self> {
+        let region = required_var("SES_REGION")?;
+        let endpoint =
+            var("SES_ENDPOINT")?.unwrap_or_else(|| format!("https://email.{region}.amazonaws.com"));
+
+        Ok(Self {
+            from: from_address()?,
+            region,
+            endpoint,
+            access_key: required_var("AWS_ACCESS_KEY")?,
+            secret_key: required_var("AWS_SECRET_KEY")?.into(),
+        })
+    }
+}
+
+fn from_address() -> anyhow::Result<Address> {
+    var("EMAIL_FROM")?
+        .as_deref()
+        .unwrap_or(DEFAULT_FROM)
+        .parse()
+        .context("EMAIL_FROM must be an email address")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::*;
+    use std::sync::{LazyLock, Mutex};
+
+    const VARS: &[&str] = &[
+        "EMAIL_BACKEND",
+        "EMAIL_FROM",
+        "MAILGUN_SMTP_LOGIN",
+        "MAILGUN_SMTP_PASSWORD",
+        "MAILGUN_SMTP_SERVER",
+        "MAILGUN_API_KEY",
+        "MAILGUN_DOMAIN",
+        "MAILGUN_API_URL",
+        "SES_REGION",
+        "SES_ENDPOINT",
+        "AWS_ACCESS_KEY",
+        "AWS_SECRET_KEY",
+    ];
+
+    /// A mutex to ensure that the tests don't run in parallel, since they all
+    /// modify the same environment variables.
+    static MUTEX: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+    /// Runs `f` with only the given email variables set.
+    fn with_vars<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+        let _guard = MUTEX.lock().unwrap_or_else(|error| error.into_inner());
+
+        // Loads the `.env` file, so that it can't overwrite the variables
+        // of the test later on.
+        let _ = var("EMAIL_BACKEND");
+
+        for name in VARS {
+            std::env::remove_var(name);
+        }
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+
+        let result = f();
+
+        for name in VARS {
+            std::env::remove_var(name);
+        }
+
+        result
+    }
+
+    const SMTP_VARS: &[(&str, &str)] = &[
+        ("MAILGUN_SMTP_LOGIN", "postmaster@example.com"),
+        ("MAILGUN_SMTP_PASSWORD", "password"),
+        ("MAILGUN_SMTP_SERVER", "smtp.example.com"),
+    ];
+
+    #[test]
+    fn test_no_backend() {
+        let config = with_vars(&[], EmailConfig::from_environment);
+        assert_none!(assert_ok!(config));
+    }
+
+    #[test]
+    fn test_smtp_backend() {
+        let config = with_vars(SMTP_VARS, EmailConfig::from_environment);
+        let config = assert_some!(assert_ok!(config));
+        let EmailConfig::Smtp(smtp) = config else {
+            panic!("expected the smtp backend, got {config:?}");
+        };
+        assert_eq!(smtp.login.to_string(), "postmaster@example.com");
+        assert_eq!(smtp.server, "smtp.example.com");
+
+        let vars = [&[("EMAIL_BACKEND", "smtp")][..], SMTP_VARS].concat();
+        let config = with_vars(&vars, EmailConfig::from_environment);
+        assert_matches!(assert_ok!(config), Some(EmailConfig::Smtp(_)));
+
+        let config = with_vars(&[("EMAIL_BACKEND", "smtp")], EmailConfig::from_environment);
+        let error = assert_err!(config);
+        assert_eq!(
+            error.to_string(),
+            "MAILGUN_SMTP_* must be set when using EMAIL_BACKEND=smtp"
+        );
+    }
+
+    #[test]
+    fn test_partial_smtp_vars() {
+        let vars = &SMTP_VARS[..2];
+        let error = assert_err!(with_vars(vars, EmailConfig::from_environment));
+        assert_eq!(
+            error.to_string(),
+            "MAILGUN_SMTP_LOGIN, MAILGUN_SMTP_PASSWORD and MAILGUN_SMTP_SERVER must be set together"
+        );
+    }
+
+    #[test]
+    fn test_mailgun_backend() {
+        let vars = [
+            ("EMAIL_BACKEND", "mailgun"),
+            ("MAILGUN_API_KEY", "key"),
+            ("MAILGUN_DOMAIN", "mg.example.com"),
+        ];
+        let config = with_vars(&vars, EmailConfig::from_environment);
+        let config = assert_some!(assert_ok!(config));
+        let EmailConfig::Mailgun(mailgun) = config else {
+            panic!("expected the mailgun backend, got {config:?}");
+        };
+        assert_eq!(mailgun.from.to_string(), DEFAULT_FROM);
+        assert_eq!(mailgun.domain, "mg.example.com");
+        assert_eq!(mailgun.api_url, DEFAULT_MAILGUN_API_URL);
+
+        let config = with_vars(&vars[..2], EmailConfig::from_environment);
+        let error = assert_err!(config);
+        assert_eq!(
+            error.to_string(),
+            "Failed to find required MAILGUN_DOMAIN environment variable"
+        );
+    }
+
+    #[test]
+    fn test_ses_backend() {
+        let vars = [
+            ("EMAIL_BACKEND", "ses"),
+            ("EMAIL_FROM", "crates@example.com"),
+            ("SES_REGION", "us-west-1"),
+            ("AWS_ACCESS_KEY", "access"),
+            ("AWS_SECRET_KEY", "secret"),
+        ];
+        let config = with_vars(&vars, EmailConfig::from_environment);
+        let config = assert_some!(assert_ok!(config));
+        let EmailConfig::Ses(ses) = config else {
+            panic!("expected the ses backend, got {config:?}");
+        };
+        assert_eq!(ses.from.to_string(), "crates@example.com");
+        assert_eq!(ses.region, "us-west-1");
+        assert_eq!(ses.endpoint, "https://email.us-west-1.amazonaws.com");
+        assert_eq!(ses.access_key, "access");
+    }
+
+    #[test]
+    fn test_unknown_backend() {
+        let config = with_vars(
+            &[("EMAIL_BACKEND", "pigeon")],
+            EmailConfig::from_environment,
+        );
+        let error = assert_err!(config);
+        assert_eq!(
+            error.to_string(),
+            "EMAIL_BACKEND must be one of `smtp`, `mailgun` or `ses`, got `pigeon`"
+        );
+    }
+}
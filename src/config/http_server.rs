@@ -0,0 +1,72 @@
+//! Configuration of the HTTP connections of the server
+//!
+//! - `SERVER_HTTP2`: If set to `true`, clients may use HTTP/2 with prior knowledge in addition
+//!   to HTTP/1.1. Defaults to `false`.
+//! - `SERVER_KEEP_ALIVE`: If set to `false`, HTTP/1.1 connections are closed after each
+//!   request. Defaults to `true`.
+//! - `SERVER_KEEP_ALIVE_TIMEOUT_SECONDS`: How long an idle HTTP/1.1 connection is kept open
+//!   while waiting for the headers of the next request. Defaults to 30 seconds.
+//! - `SERVER_HTTP2_KEEP_ALIVE_INTERVAL_SECONDS`: Interval of the HTTP/2 keep-alive pings. Pings
+//!   are disabled if unset.
+//! - `SERVER_HTTP2_KEEP_ALIVE_TIMEOUT_SECONDS`: How long to wait for the acknowledgement of a
+//!   keep-alive ping before the HTTP/2 connection is closed. Defaults to 20 seconds.
+//! - `SERVER_HTTP2_MAX_CONCURRENT_STREAMS`: Number of requests a client can send concurrently
+//!   over a single HTTP/2 connection. Defaults to 200.
+
+use crates_io_env_vars::var_parsed;
+use std::time::Duration;
+
+const DEFAULT_KEEP_ALIVE_TIMEOUT: u64 = 30;
+const DEFAULT_HTTP2_KEEP_ALIVE_TIMEOUT: u64 = 20;
+const DEFAULT_HTTP2_MAX_CONCURRENT_STREAMS: u32 = 200;
+
+#[derive(Debug, Clone)]
+pub struct HttpServerConfig {
+    /// Whether HTTP/2 connections with prior knowledge are accepted.
+    pub http2: bool,
+    /// Whether HTTP/1.1 connections are kept open for further requests.
+    pub keep_alive: bool,
+    /// Time to wait for the request headers on an HTTP/1.1 connection,
+    /// including the time the connection is idle between two requests.
+    pub keep_alive_timeout: Duration,
+    /// Interval of the HTTP/2 keep-alive pings, or `None` to disable them.
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// Time to wait for the acknowledgement of an HTTP/2 keep-alive ping.
+    pub http2_keep_alive_timeout: Duration,
+    /// Maximum number of concurrent requests per HTTP/2 connection.
+    pub http2_max_concurrent_streams: u32,
+}
+
+impl Default for HttpServerConfig {
+    fn default() -> Self {
+        Self {
+            http2: false,
+            keep_alive: true,
+            keep_alive_timeout: Duration::from_secs(DEFAULT_KEEP_ALIVE_TIMEOUT),
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: Duration::from_secs(DEFAULT_HTTP2_KEEP_ALIVE_TIMEOUT),
+            http2_max_concurrent_streams: DEFAULT_HTTP2_MAX_CONCURRENT_STREAMS,
+        }
+    }
+}
+
+impl HttpServerConfig {
+    pub fn from_environment() -> anyhow::Result<Self> {
+        let default = Self::default();
+
+        let seconds =
+            |name: &str| var_parsed(name).map(|secs: Option<u64>| secs.map(Duration::from_secs));
+
+        Ok(Self {
+            http2: var_parsed("SERVER_HTTP2")?.unwrap_or(default.http2),
+            keep_alive: var_parsed("SERVER_KEEP_ALIVE")?.unwrap_or(default.keep_alive),
+            keep_alive_timeout: seconds("SERVER_KEEP_ALIVE_TIMEOUT_SECONDS")?
+                .unwrap_or(default.keep_alive_timeout),
+            http2_keep_alive_interval: seconds("SERVER_HTTP2_KEEP_ALIVE_INTERVAL_SECONDS")?,
+            http2_keep_alive_timeout: seconds("SERVER_HTTP2_KEEP_ALIVE_TIMEOUT_SECONDS")?
+                .unwrap_or(default.http2_keep_alive_timeout),
+            http2_max_concurrent_streams: var_parsed("SERVER_HTTP2_MAX_CONCURRENT_STREAMS")?
+                .unwrap_or(default.http2_max_concurrent_streams),
+        })
+    }
+}
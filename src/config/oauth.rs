@@ -0,0 +1,37 @@
+use anyhow::Context;
+use crates_io_env_vars::{required_var, var};
+use oauth2::{ClientId, ClientSecret};
+
+/// Client credentials of an additional OAuth identity provider.
+pub struct OAuthClientConfig {
+    pub client_id: ClientId,
+    pub client_secret: ClientSecret,
+    /// The URL that the identity provider redirects to after the user
+    /// authorized the application.
+    pub redirect_url: String,
+}
+
+impl OAuthClientConfig {
+    /// Reads the `{prefix}_CLIENT_ID`, `{prefix}_CLIENT_SECRET` and optional
+    /// `{prefix}_REDIRECT_URL` environment variables. Returns `None` if no
+    /// client ID is configured, which disables the corresponding identity
+    /// provider.
+    pub fn from_environment(prefix: &str, domain_name: &str) -> anyhow::Result<Option<Self>> {
+        let Some(client_id) = var(&format!("{prefix}_CLIENT_ID"))? else {
+            return Ok(None);
+        };
+
+        let secret_var = format!("{prefix}_CLIENT_SECRET");
+        let client_secret = required_var(&secret_var)
+            .with_context(|| format!("{secret_var} must be set when using {prefix}_CLIENT_ID"))?;
+
+        let redirect_url = var(&format!("{prefix}_REDIRECT_URL"))?
+            .unwrap_or_else(|| format!("https://{domain_name}/github-redirect.html"));
+
+        Ok(Some(Self {
+            client_id: ClientId::new(client_id),
+            client_secret: ClientSecret::new(client_secret),
+            redirect_url,
+        }))
+    }
+}
@@ -0,0 +1,109 @@
+//! Configuration of the security headers that are added to all responses
+//!
+//! - `CSP_REPORT_ONLY`: If set to `true`, the Content-Security-Policy is sent in the
+//!   `Content-Security-Policy-Report-Only` header, so that violations are only reported to
+//!   `/api/csp-report` instead of being blocked by the browser. Defaults to `false`.
+//! - `HSTS_MAX_AGE_SECONDS`: The `max-age` of the `Strict-Transport-Security` header. `0`
+//!   disables the header. Defaults to one year in production, and to `0` otherwise, since the
+//!   other environments are usually not served over HTTPS.
+//! - `HSTS_INCLUDE_SUBDOMAINS`: Whether the `Strict-Transport-Security` header applies to all
+//!   subdomains. Defaults to `false`.
+//! - `WEB_FRAME_OPTIONS`: The value of the `X-Frame-Options` header. Defaults to `SAMEORIGIN`.
+//! - `WEB_REFERRER_POLICY`: The value of the `Referrer-Policy` header. Defaults to
+//!   `strict-origin-when-cross-origin`.
+
+use crate::Env;
+use crates_io_env_vars::{var, var_parsed};
+use http::HeaderValue;
+
+/// Path of the endpoint that collects the reports of CSP violations.
+pub const CSP_REPORT_PATH: &str = "/api/csp-report";
+
+const ONE_YEAR: u64 = 365 * 24 * 60 * 60;
+const DEFAULT_FRAME_OPTIONS: &str = "SAMEORIGIN";
+const DEFAULT_REFERRER_POLICY: &str = "strict-origin-when-cross-origin";
+
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersConfig {
+    /// The `Content-Security-Policy` of all responses, if any.
+    pub content_security_policy: Option<HeaderValue>,
+    /// Whether violations of the policy are only reported instead of blocked.
+    pub csp_report_only: bool,
+    /// The `Strict-Transport-Security` header, or `None` if it is disabled.
+    pub strict_transport_security: Option<HeaderValue>,
+    pub frame_options: HeaderValue,
+    pub referrer_policy: HeaderValue,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            content_security_policy: None,
+            csp_report_only: false,
+            strict_transport_security: Some(strict_transport_security(ONE_YEAR, false)),
+            frame_options: HeaderValue::from_static(DEFAULT_FRAME_OPTIONS),
+            referrer_policy: HeaderValue::from_static(DEFAULT_REFERRER_POLICY),
+        }
+    }
+}
+
+impl SecurityHeadersConfig {
+    /// Loads the configuration, with a Content-Security-Policy that allows the
+    /// frontend to load files from the CDN at `cdn_prefix`.
+    pub fn from_environment(env: Env, cdn_prefix: Option<&str>) -> anyhow::Result<Self> {
+        let default_hsts_max_age = match env {
+            Env::Production => ONE_YEAR,
+            _ => 0,
+        };
+
+        let hsts_max_age = var_parsed("HSTS_MAX_AGE_SECONDS")?.unwrap_or(default_hsts_max_age);
+        let hsts_include_subdomains = var_parsed("HSTS_INCLUDE_SUBDOMAINS")?.unwrap_or(false);
+        let strict_transport_security = (hsts_max_age > 0)
+            .then(|| strict_transport_security(hsts_max_age, hsts_include_subdomains));
+
+        let frame_options = var("WEB_FRAME_OPTIONS")?;
+        let frame_options = frame_options.as_deref().unwrap_or(DEFAULT_FRAME_OPTIONS);
+
+        let referrer_policy = var("WEB_REFERRER_POLICY")?;
+        let referrer_policy = referrer_policy
+            .as_deref()
+            .unwrap_or(DEFAULT_REFERRER_POLICY);
+
+        Ok(Self {
+            content_security_policy: Some(content_security_policy(cdn_prefix).parse()?),
+            csp_report_only: var_parsed("CSP_REPORT_ONLY")?.unwrap_or(false),
+            strict_transport_security,
+            frame_options: frame_options.parse()?,
+            referrer_policy: referrer_policy.parse()?,
+        })
+    }
+}
+
+fn strict_transport_security(max_age: u64, include_subdomains: bool) -> HeaderValue {
+    let value = if include_subdomains {
+        format!("max-age={max_age}; includeSubDomains")
+    } else {
+        format!("max-age={max_age}")
+    };
+
+    HeaderValue::try_from(value).expect("the header value only contains valid characters")
+}
+
+fn content_security_policy(cdn_prefix: Option<&str>) -> String {
+    let cdn_domain = cdn_prefix
+        .map(|cdn_prefix| format!("https://{cdn_prefix}"))
+        .unwrap_or_default();
+
+    // `sha256-dbf9FMl76C7BnK1CC3eWb3pvsQAUaTYSHAlBy9tNTG0=` refers to
+    // the `script` in `public/github-redirect.html`
+    format!(
+        "default-src 'self'; \
+        connect-src 'self' *.ingest.sentry.io https://docs.rs https://play.rust-lang.org {cdn_domain}; \
+        script-src 'self' 'unsafe-eval' 'sha256-n1+BB7Ckjcal1Pr7QNBh/dKRTtBQsIytFodRiIosXdE=' 'sha256-dbf9FMl76C7BnK1CC3eWb3pvsQAUaTYSHAlBy9tNTG0='; \
+        style-src 'self' 'unsafe-inline' https://code.cdn.mozilla.net; \
+        font-src https://code.cdn.mozilla.net; \
+        img-src *; \
+        object-src 'none'; \
+        report-uri {CSP_REPORT_PATH}"
+    )
+}
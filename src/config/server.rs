@@ -1,17 +1,22 @@
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, ensure, Context};
 use ipnetwork::IpNetwork;
 use oauth2::{ClientId, ClientSecret};
+use secrecy::SecretString;
 
+use crate::abuse_throttle::AbuseThrottleConfig;
+use crate::rate_limiter::requests::{EndpointGroup, RateLimitPolicy, RequestRateLimiterConfig};
 use crate::rate_limiter::{LimitedAction, RateLimiterConfig};
 use crate::Env;
 
 use super::base::Base;
 use super::database_pools::DatabasePools;
 use crate::config::cdn_log_storage::CdnLogStorageConfig;
-use crate::config::CdnLogQueueConfig;
+use crate::config::{
+    CdnLogQueueConfig, EmailConfig, HttpServerConfig, OAuthClientConfig, SecurityHeadersConfig,
+};
 use crate::middleware::cargo_compat::StatusCodeConfig;
 use crate::storage::StorageConfig;
-use crates_io_env_vars::{list, list_parsed, required_var, var, var_parsed};
+use crates_io_env_vars::{list, list_parsed, required_var, var, var_parsed, Errors};
 use http::HeaderValue;
 use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
@@ -21,6 +26,12 @@ use std::time::Duration;
 
 const DEFAULT_VERSION_ID_CACHE_SIZE: u64 = 10_000;
 const DEFAULT_VERSION_ID_CACHE_TTL: u64 = 5 * 60; // 5 minutes
+const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+const DEFAULT_BLOCKING_POOL_SIZE: usize = 32;
+const DEFAULT_BLOCKING_POOL_QUEUE_SIZE: usize = 128;
+const DEFAULT_REQUEST_BODY_LIMIT: usize = 2 * 1024 * 1024; // 2 MiB
+const DEFAULT_REQUEST_TIMEOUT: u64 = 30;
+const DEFAULT_PUBLISH_REQUEST_TIMEOUT: u64 = 60;
 
 /// Maximum number of features a crate can have or that a feature itself can
 /// enable. This value can be overridden in the database on a per-crate basis.
@@ -34,18 +45,51 @@ pub struct Server {
     pub ip: IpAddr,
     pub port: u16,
     pub max_blocking_threads: Option<usize>,
+    /// How long the server waits for in-flight requests and blocking tasks
+    /// to finish after receiving a shutdown signal.
+    pub shutdown_timeout: Duration,
+    /// Protocol and keep-alive settings of the HTTP connections.
+    pub http: HttpServerConfig,
+    /// The fraction of successful requests that are logged, between `0.0`
+    /// and `1.0`. All other requests are always logged.
+    pub request_log_sample_rate: f64,
+    /// Number of closures that can run on the blocking pool at the same time.
+    pub blocking_pool_size: usize,
+    /// Number of closures that can wait for a free worker of the blocking
+    /// pool before further requests are rejected.
+    pub blocking_pool_queue_size: usize,
+    /// Maximum size of the request body for all routes except publishing.
+    pub request_body_limit: usize,
+    /// Maximum time to process a request for all routes except publishing.
+    pub request_timeout: Duration,
+    /// Maximum time to process a publish request.
+    pub publish_request_timeout: Duration,
     pub db: DatabasePools,
     pub storage: StorageConfig,
+    /// The storage of the standby region that crate files and index files
+    /// are replicated to, or `None` to disable the replication.
+    pub standby_storage: Option<StorageConfig>,
+    /// Whether download redirects point to the CDN of the standby region
+    /// instead of the primary one, e.g. during an outage of the primary
+    /// region.
+    pub downloads_failover: bool,
     pub cdn_log_storage: CdnLogStorageConfig,
     pub cdn_log_queue: CdnLogQueueConfig,
     pub session_key: cookie::Key,
     pub gh_client_id: ClientId,
     pub gh_client_secret: ClientSecret,
+    pub gitlab_oauth: Option<OAuthClientConfig>,
+    pub google_oauth: Option<OAuthClientConfig>,
+    /// The backend that is used to send emails, or `None` to store the
+    /// emails on the local file system instead.
+    pub email: Option<EmailConfig>,
     pub max_upload_size: u32,
     pub max_unpack_size: u64,
     pub max_dependencies: usize,
     pub max_features: usize,
     pub rate_limiter: HashMap<LimitedAction, RateLimiterConfig>,
+    pub request_rate_limiter: RequestRateLimiterConfig,
+    pub abuse_throttle: AbuseThrottleConfig,
     pub new_version_rate_limit: Option<u32>,
     pub blocked_traffic: Vec<(String, Vec<String>)>,
     pub blocked_ips: HashSet<IpAddr>,
@@ -54,20 +98,46 @@ pub struct Server {
     pub page_offset_cidr_blocklist: Vec<IpNetwork>,
     pub excluded_crate_names: Vec<String>,
     pub domain_name: String,
+    /// Whether `/robots.txt` allows search engines to crawl the site.
+    pub allow_crawlers: bool,
     pub allowed_origins: AllowedOrigins,
     pub downloads_persist_interval: Duration,
+    /// How often the API usage counts are added to the database.
+    pub api_usage_persist_interval: Duration,
     pub ownership_invitations_expiration_days: u64,
     pub metrics_authorization_token: Option<String>,
+    /// Token that docs.rs uses to report build results, or `None` to
+    /// disable the build result callback.
+    pub docs_rs_callback_token: Option<SecretString>,
+    /// Tokens of the trusted services that may read the crate change feed.
+    /// The feed is disabled if the list is empty.
+    pub crate_changes_tokens: Vec<SecretString>,
     pub instance_metrics_log_every_seconds: Option<u64>,
     pub blocked_routes: HashSet<String>,
     pub version_id_cache_size: u64,
     pub version_id_cache_ttl: Duration,
     pub cdn_user_agent: String,
 
+    /// New crates are quarantined if the automated malware scan of their
+    /// first version reaches at least this score.
+    pub malware_quarantine_score: i32,
+
+    /// New crates are quarantined if their name matches at least this many
+    /// typosquatting checks.
+    pub typosquat_quarantine_matches: usize,
+
+    /// Orphaned crate files and index files are deleted by the
+    /// `ReconcileStorage` job once they have been found for this long, or
+    /// never if this is `None`.
+    pub storage_orphan_quarantine: Option<Duration>,
+
     /// Instructs the `cargo_compat` middleware whether to adjust response
     /// status codes to `200 OK` for all endpoints that are relevant for cargo.
     pub cargo_compat_status_code_config: StatusCodeConfig,
 
+    /// The commit SHA1 of the deployed version, if known.
+    pub deployed_sha: Option<String>,
+
     /// Should the server serve the frontend assets in the `dist` directory?
     pub serve_dist: bool,
 
@@ -75,7 +145,9 @@ pub struct Server {
     /// non-API requests?
     pub serve_html: bool,
 
-    pub content_security_policy: Option<HeaderValue>,
+    /// The security headers that are added to all responses, e.g. the
+    /// Content-Security-Policy.
+    pub security_headers: SecurityHeadersConfig,
 }
 
 impl Server {
@@ -106,17 +178,80 @@ impl Server {
     ///   to block IP addresses, e.g. `192.168.1.0/24`. If not set or empty, no blocking will occur.
     /// - `INSTANCE_METRICS_LOG_EVERY_SECONDS`: How frequently should instance metrics be logged.
     ///   If the environment variable is not present instance metrics are not logged.
+    /// - `REQUEST_LOG_SAMPLE_RATE`: The fraction of successful (`2xx`) requests that are logged.
+    ///   Requests with other status codes are always logged. Defaults to `1.0`.
+    /// - `SERVER_SHUTDOWN_TIMEOUT_SECONDS`: How long to wait for in-flight requests to finish
+    ///   after receiving `SIGINT` or `SIGTERM`. Defaults to 30 seconds.
+    /// - `BLOCKING_POOL_SIZE`: The number of blocking closures of request handlers that can run
+    ///   at the same time. Defaults to 32.
+    /// - `BLOCKING_POOL_QUEUE_SIZE`: The number of blocking closures that can wait for a free
+    ///   worker before requests are rejected with `503 Service Unavailable`. Defaults to 128.
+    /// - `WEB_REQUEST_BODY_LIMIT`: The maximum size of request bodies in bytes. The publish
+    ///   endpoint uses the upload size limits of the crates instead. Defaults to 2 MiB.
+    /// - `WEB_REQUEST_TIMEOUT_SECONDS`: The maximum time to process a request before responding
+    ///   with `408 Request Timeout`. Defaults to 30 seconds.
+    /// - `WEB_PUBLISH_REQUEST_TIMEOUT_SECONDS`: The maximum time to process a publish request.
+    ///   Defaults to 60 seconds.
+    /// - `REQUEST_RATE_LIMITER_ENABLED`: Whether API requests are rate limited per client.
+    ///   Defaults to `false`.
+    /// - `REQUEST_RATE_LIMITER_{SEARCH,LIST,CRAWLER_LIST,READ,WRITE}_REQUESTS` and
+    ///   `REQUEST_RATE_LIMITER_{SEARCH,LIST,CRAWLER_LIST,READ,WRITE}_WINDOW_SECONDS`: The number
+    ///   of requests that a client can send to the endpoint group in each window. See
+    ///   `src/rate_limiter/requests.rs` for the defaults.
+    /// - `REDIS_URL`: The Redis server that stores the request rate limit counters, so that they
+    ///   are shared by all instances. If missing, the counters are kept in memory.
+    /// - `ABUSE_THROTTLE_ENABLED`: Whether IP networks that send too many requests or cause too
+    ///   many errors are banned temporarily. Defaults to `false`.
+    /// - `ABUSE_THROTTLE_WINDOW_SECONDS`, `ABUSE_THROTTLE_MAX_REQUESTS` and
+    ///   `ABUSE_THROTTLE_MAX_ERRORS`: The number of requests and `4xx` responses that a subnet
+    ///   can have in each window before it is banned. Defaults to 10,000 requests and 1,000
+    ///   errors per 60 seconds.
+    /// - `ABUSE_THROTTLE_BAN_SECONDS` and `ABUSE_THROTTLE_MAX_BAN_SECONDS`: The duration of the
+    ///   first ban of a subnet, which doubles with every repeated ban up to the maximum. Defaults
+    ///   to 5 minutes and 24 hours.
+    /// - `API_USAGE_PERSIST_INTERVAL_SECONDS`: How often the API requests that are counted per
+    ///   user and per crate are added to the database. Defaults to 60 seconds.
     /// - `FORCE_UNCONDITIONAL_REDIRECTS`: Whether to force unconditional redirects in the download
     ///   endpoint even with a healthy database pool.
+    /// - `STANDBY_S3_BUCKET`, `STANDBY_S3_REGION`, `STANDBY_S3_CDN`, `STANDBY_S3_INDEX_BUCKET`,
+    ///   `STANDBY_S3_INDEX_REGION`, `STANDBY_S3_PRIVATE_BUCKET` and `STANDBY_S3_PRIVATE_REGION`:
+    ///   The storage of the standby region that crate files and index files are replicated to.
+    ///   Replication is disabled if `STANDBY_S3_BUCKET` is not set.
+    /// - `DOWNLOADS_FAILOVER`: Whether download redirects point to the CDN of the standby region.
+    ///   Defaults to `false`.
     /// - `BLOCKED_ROUTES`: A comma separated list of HTTP route patterns that are manually blocked
     ///   by an operator (e.g. `/crates/{crate_id}/{version}/download`).
+    /// - `MALWARE_QUARANTINE_SCORE`: The malware scan score at which new crates are quarantined.
+    ///   Defaults to 50.
+    /// - `TYPOSQUAT_QUARANTINE_MATCHES`: The number of typosquatting matches at which new crates
+    ///   are quarantined. Defaults to 1.
+    /// - `STORAGE_ORPHAN_QUARANTINE_DAYS`: The number of days after which orphaned crate files
+    ///   and index files are deleted by the `ReconcileStorage` job. If not set, orphaned files
+    ///   are only reported.
+    /// - `EMAIL_BACKEND`: The backend that is used to send emails. Required in production. See
+    ///   `src/config/email.rs` for the variables of each backend.
+    /// - `HEROKU_SLUG_COMMIT`: The commit SHA1 of the deployed version.
+    /// - `ALLOW_CRAWLERS`: Whether `/robots.txt` allows search engines to crawl the site. Defaults
+    ///   to `true` in production and `false` otherwise, so that staging environments are not
+    ///   indexed.
+    /// - `CSP_REPORT_ONLY`, `HSTS_MAX_AGE_SECONDS`, `HSTS_INCLUDE_SUBDOMAINS`, `WEB_FRAME_OPTIONS`
+    ///   and `WEB_REFERRER_POLICY`: The security headers of all responses. See
+    ///   `src/config/security_headers.rs` for the defaults.
+    ///
+    /// All of these values can also be set in the TOML file that the `CRATES_IO_CONFIG_FILE`
+    /// environment variable points to, with the environment variables taking precedence.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function panics if the Server configuration is invalid.
+    /// This function fails with a list of all missing and invalid values if the Server
+    /// configuration is invalid.
     pub fn from_environment() -> anyhow::Result<Self> {
-        let docker = var("DEV_DOCKER")?.is_some();
-        let heroku = var("HEROKU")?.is_some();
+        // Collect all missing and invalid values, so that they can be fixed
+        // at once instead of one deployment at a time.
+        let mut errors = Errors::default();
+
+        let docker = errors.check(var("DEV_DOCKER")).is_some();
+        let heroku = errors.check(var("HEROKU")).is_some();
 
         let ip = if heroku || docker {
             [0, 0, 0, 0].into()
@@ -124,98 +259,288 @@ impl Server {
             [127, 0, 0, 1].into()
         };
 
-        let port = var_parsed("PORT")?.unwrap_or(8888);
+        let port = errors.check(var_parsed("PORT")).unwrap_or(8888);
 
-        let blocked_ips = HashSet::from_iter(list_parsed("BLOCKED_IPS", IpAddr::from_str)?);
+        let blocked_ips = errors.check(list_parsed("BLOCKED_IPS", IpAddr::from_str));
+        let blocked_ips = HashSet::from_iter(blocked_ips);
 
-        let allowed_origins = AllowedOrigins::from_default_env()?;
-        let page_offset_ua_blocklist = list("WEB_PAGE_OFFSET_UA_BLOCKLIST")?;
-        let page_offset_cidr_blocklist =
-            list_parsed("WEB_PAGE_OFFSET_CIDR_BLOCKLIST", parse_cidr_block)?;
+        let allowed_origins = errors.check(AllowedOrigins::from_default_env());
+        let page_offset_ua_blocklist = errors.check(list("WEB_PAGE_OFFSET_UA_BLOCKLIST"));
+        let page_offset_cidr_blocklist = errors.check(list_parsed(
+            "WEB_PAGE_OFFSET_CIDR_BLOCKLIST",
+            parse_cidr_block,
+        ));
 
         let base = Base::from_environment()?;
-        let excluded_crate_names = list("EXCLUDED_CRATE_NAMES")?;
+        let excluded_crate_names = errors.check(list("EXCLUDED_CRATE_NAMES"));
 
-        let max_blocking_threads = var_parsed("SERVER_THREADS")?;
+        let max_blocking_threads = errors.check(var_parsed("SERVER_THREADS"));
+        let shutdown_timeout = Duration::from_secs(
+            errors
+                .check(var_parsed("SERVER_SHUTDOWN_TIMEOUT_SECONDS"))
+                .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT),
+        );
+        let http = errors.check(HttpServerConfig::from_environment());
+        let request_log_sample_rate = errors
+            .check(var_parsed("REQUEST_LOG_SAMPLE_RATE"))
+            .unwrap_or(1.0);
 
         // Dynamically load the configuration for all the rate limiting actions. See
         // `src/rate_limiter.rs` for their definition.
         let mut rate_limiter = HashMap::new();
         for action in LimitedAction::VARIANTS {
             let env_var_key = action.env_var_key();
+            let rate = var_parsed(&format!("RATE_LIMITER_{env_var_key}_RATE_SECONDS"));
+            let burst = var_parsed(&format!("RATE_LIMITER_{env_var_key}_BURST"));
             rate_limiter.insert(
                 *action,
                 RateLimiterConfig {
                     rate: Duration::from_secs(
-                        var_parsed(&format!("RATE_LIMITER_{env_var_key}_RATE_SECONDS"))?
+                        errors
+                            .check(rate)
                             .unwrap_or_else(|| action.default_rate_seconds()),
                     ),
-                    burst: var_parsed(&format!("RATE_LIMITER_{env_var_key}_BURST"))?
+                    burst: errors
+                        .check(burst)
                         .unwrap_or_else(|| action.default_burst()),
                 },
             );
         }
 
-        let storage = StorageConfig::from_environment();
-
-        // `sha256-dbf9FMl76C7BnK1CC3eWb3pvsQAUaTYSHAlBy9tNTG0=` refers to
-        // the `script` in `public/github-redirect.html`
-        let content_security_policy = format!(
-            "default-src 'self'; \
-            connect-src 'self' *.ingest.sentry.io https://docs.rs https://play.rust-lang.org {cdn_domain}; \
-            script-src 'self' 'unsafe-eval' 'sha256-n1+BB7Ckjcal1Pr7QNBh/dKRTtBQsIytFodRiIosXdE=' 'sha256-dbf9FMl76C7BnK1CC3eWb3pvsQAUaTYSHAlBy9tNTG0='; \
-            style-src 'self' 'unsafe-inline' https://code.cdn.mozilla.net; \
-            font-src https://code.cdn.mozilla.net; \
-            img-src *; \
-            object-src 'none'",
-            cdn_domain = storage.cdn_prefix.as_ref().map(|cdn_prefix| format!("https://{cdn_prefix}")).unwrap_or_default()
-        );
+        let mut request_rate_limits = HashMap::new();
+        for group in EndpointGroup::VARIANTS {
+            let env_var_key = group.env_var_key();
+            let default = group.default_policy();
+            let requests = var_parsed(&format!("REQUEST_RATE_LIMITER_{env_var_key}_REQUESTS"));
+            let window = var_parsed(&format!(
+                "REQUEST_RATE_LIMITER_{env_var_key}_WINDOW_SECONDS"
+            ));
+            request_rate_limits.insert(
+                *group,
+                RateLimitPolicy {
+                    requests: errors.check(requests).unwrap_or(default.requests),
+                    window: errors
+                        .check(window)
+                        .map(Duration::from_secs)
+                        .unwrap_or(default.window),
+                },
+            );
+        }
+
+        let request_rate_limiter = RequestRateLimiterConfig {
+            enabled: errors
+                .check(var_parsed("REQUEST_RATE_LIMITER_ENABLED"))
+                .unwrap_or(false),
+            policies: request_rate_limits,
+            redis_url: errors.check(var("REDIS_URL")).map(SecretString::from),
+        };
+
+        let default_abuse_throttle = AbuseThrottleConfig::default();
+        let abuse_throttle = AbuseThrottleConfig {
+            enabled: errors
+                .check(var_parsed("ABUSE_THROTTLE_ENABLED"))
+                .unwrap_or(false),
+            window: errors
+                .check(var_parsed("ABUSE_THROTTLE_WINDOW_SECONDS"))
+                .map(Duration::from_secs)
+                .unwrap_or(default_abuse_throttle.window),
+            max_requests: errors
+                .check(var_parsed("ABUSE_THROTTLE_MAX_REQUESTS"))
+                .unwrap_or(default_abuse_throttle.max_requests),
+            max_errors: errors
+                .check(var_parsed("ABUSE_THROTTLE_MAX_ERRORS"))
+                .unwrap_or(default_abuse_throttle.max_errors),
+            ban_duration: errors
+                .check(var_parsed("ABUSE_THROTTLE_BAN_SECONDS"))
+                .map(Duration::from_secs)
+                .unwrap_or(default_abuse_throttle.ban_duration),
+            max_ban_duration: errors
+                .check(var_parsed("ABUSE_THROTTLE_MAX_BAN_SECONDS"))
+                .map(Duration::from_secs)
+                .unwrap_or(default_abuse_throttle.max_ban_duration),
+        };
+
+        let domain_name = errors
+            .check(var("DOMAIN_NAME"))
+            .unwrap_or_else(|| "crates.io".into());
+        let allow_crawlers = errors
+            .check(var_parsed("ALLOW_CRAWLERS"))
+            .unwrap_or(base.env == Env::Production);
+
+        let session_key = required_var("SESSION_KEY").and_then(|session_key| {
+            ensure!(
+                session_key.len() >= 32,
+                "SESSION_KEY must be at least 32 bytes long"
+            );
+            Ok(session_key)
+        });
+        let session_key = errors.check(session_key);
+
+        let gh_client_id = errors.check(required_var("GH_CLIENT_ID"));
+        let gh_client_secret = errors.check(required_var("GH_CLIENT_SECRET"));
+        let gitlab_oauth =
+            errors.check(OAuthClientConfig::from_environment("GITLAB", &domain_name));
+        let google_oauth =
+            errors.check(OAuthClientConfig::from_environment("GOOGLE", &domain_name));
+
+        let email = errors.check(EmailConfig::from_environment());
+        if base.env == Env::Production && email.is_none() {
+            errors.push(anyhow!("An email backend must be configured in production"));
+        }
+
+        let blocking_pool_size = errors
+            .check(var_parsed("BLOCKING_POOL_SIZE"))
+            .unwrap_or(DEFAULT_BLOCKING_POOL_SIZE);
+        let blocking_pool_queue_size = errors
+            .check(var_parsed("BLOCKING_POOL_QUEUE_SIZE"))
+            .unwrap_or(DEFAULT_BLOCKING_POOL_QUEUE_SIZE);
+        let request_body_limit = errors
+            .check(var_parsed("WEB_REQUEST_BODY_LIMIT"))
+            .unwrap_or(DEFAULT_REQUEST_BODY_LIMIT);
+        let request_timeout = errors
+            .check(var_parsed("WEB_REQUEST_TIMEOUT_SECONDS"))
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT);
+        let publish_request_timeout = errors
+            .check(var_parsed("WEB_PUBLISH_REQUEST_TIMEOUT_SECONDS"))
+            .unwrap_or(DEFAULT_PUBLISH_REQUEST_TIMEOUT);
+
+        let new_version_rate_limit = errors.check(var_parsed("MAX_NEW_VERSIONS_DAILY"));
+        let blocked_traffic = errors.check(blocked_traffic());
+        let max_allowed_page_offset = errors
+            .check(var_parsed("WEB_MAX_ALLOWED_PAGE_OFFSET"))
+            .unwrap_or(200);
+        let downloads_persist_interval = errors
+            .check(var_parsed("DOWNLOADS_PERSIST_INTERVAL_MS"))
+            .map(Duration::from_millis)
+            .unwrap_or(Duration::from_secs(60));
+        let api_usage_persist_interval = errors
+            .check(var_parsed("API_USAGE_PERSIST_INTERVAL_SECONDS"))
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(60));
+        let metrics_authorization_token = errors.check(var("METRICS_AUTHORIZATION_TOKEN"));
+        let docs_rs_callback_token = errors
+            .check(var("DOCS_RS_CALLBACK_TOKEN"))
+            .map(SecretString::from);
+        let crate_changes_tokens = errors
+            .check(list("CRATE_CHANGES_TOKENS"))
+            .into_iter()
+            .map(SecretString::from)
+            .collect();
+        let downloads_failover = errors
+            .check(var_parsed("DOWNLOADS_FAILOVER"))
+            .unwrap_or(false);
+        let instance_metrics_log_every_seconds =
+            errors.check(var_parsed("INSTANCE_METRICS_LOG_EVERY_SECONDS"));
+        let blocked_routes = HashSet::from_iter(errors.check(list("BLOCKED_ROUTES")));
+        let version_id_cache_size = errors
+            .check(var_parsed("VERSION_ID_CACHE_SIZE"))
+            .unwrap_or(DEFAULT_VERSION_ID_CACHE_SIZE);
+        let version_id_cache_ttl = errors
+            .check(var_parsed("VERSION_ID_CACHE_TTL"))
+            .unwrap_or(DEFAULT_VERSION_ID_CACHE_TTL);
+        let cdn_user_agent = errors
+            .check(var("WEB_CDN_USER_AGENT"))
+            .unwrap_or_else(|| "Amazon CloudFront".into());
+        let malware_quarantine_score = errors
+            .check(var_parsed("MALWARE_QUARANTINE_SCORE"))
+            .unwrap_or(50);
+        let typosquat_quarantine_matches = errors
+            .check(var_parsed("TYPOSQUAT_QUARANTINE_MATCHES"))
+            .unwrap_or(1);
+        let storage_orphan_quarantine = errors
+            .check(var_parsed::<u64>("STORAGE_ORPHAN_QUARANTINE_DAYS"))
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60));
+        let cargo_compat_status_code_config = errors
+            .check(var_parsed("CARGO_COMPAT_STATUS_CODES"))
+            .unwrap_or(StatusCodeConfig::AdjustAll);
+        let deployed_sha = errors.check(var("HEROKU_SLUG_COMMIT"));
+
+        // The nested configurations can't be replaced by default values, so
+        // they are only loaded if they can be used afterwards.
+        let db = DatabasePools::full_from_environment(&base).map(Some);
+        let db = errors.check(db);
+        let storage = errors.check(StorageConfig::from_environment().map(Some));
+        let standby_storage = errors.check(StorageConfig::standby_from_environment().map(Some));
+        let cdn_log_storage = errors.check(CdnLogStorageConfig::from_env().map(Some));
+        let cdn_log_queue = errors.check(CdnLogQueueConfig::from_env().map(Some));
+
+        errors.finish()?;
+
+        let (
+            Some(db),
+            Some(storage),
+            Some(standby_storage),
+            Some(cdn_log_storage),
+            Some(cdn_log_queue),
+        ) = (db, storage, standby_storage, cdn_log_storage, cdn_log_queue)
+        else {
+            unreachable!("the errors of the nested configurations are reported above");
+        };
+
+        let security_headers =
+            SecurityHeadersConfig::from_environment(base.env, storage.cdn_prefix.as_deref())?;
 
         Ok(Server {
-            db: DatabasePools::full_from_environment(&base)?,
+            db,
             storage,
-            cdn_log_storage: CdnLogStorageConfig::from_env()?,
-            cdn_log_queue: CdnLogQueueConfig::from_env()?,
+            standby_storage,
+            downloads_failover,
+            cdn_log_storage,
+            cdn_log_queue,
             base,
             ip,
             port,
             max_blocking_threads,
-            session_key: cookie::Key::derive_from(required_var("SESSION_KEY")?.as_bytes()),
-            gh_client_id: ClientId::new(required_var("GH_CLIENT_ID")?),
-            gh_client_secret: ClientSecret::new(required_var("GH_CLIENT_SECRET")?),
+            shutdown_timeout,
+            http,
+            request_log_sample_rate,
+            blocking_pool_size,
+            blocking_pool_queue_size,
+            request_body_limit,
+            request_timeout: Duration::from_secs(request_timeout),
+            publish_request_timeout: Duration::from_secs(publish_request_timeout),
+            session_key: cookie::Key::derive_from(session_key.as_bytes()),
+            gh_client_id: ClientId::new(gh_client_id),
+            gh_client_secret: ClientSecret::new(gh_client_secret),
+            gitlab_oauth,
+            google_oauth,
+            email,
             max_upload_size: 10 * 1024 * 1024, // 10 MB default file upload size limit
             max_unpack_size: 512 * 1024 * 1024, // 512 MB max when decompressed
             max_dependencies: DEFAULT_MAX_DEPENDENCIES,
             max_features: DEFAULT_MAX_FEATURES,
             rate_limiter,
-            new_version_rate_limit: var_parsed("MAX_NEW_VERSIONS_DAILY")?,
-            blocked_traffic: blocked_traffic(),
+            request_rate_limiter,
+            abuse_throttle,
+            new_version_rate_limit,
+            blocked_traffic,
             blocked_ips,
-            max_allowed_page_offset: var_parsed("WEB_MAX_ALLOWED_PAGE_OFFSET")?.unwrap_or(200),
+            max_allowed_page_offset,
             page_offset_ua_blocklist,
             page_offset_cidr_blocklist,
             excluded_crate_names,
-            domain_name: dotenvy::var("DOMAIN_NAME").unwrap_or_else(|_| "crates.io".into()),
+            domain_name,
+            allow_crawlers,
             allowed_origins,
-            downloads_persist_interval: var_parsed("DOWNLOADS_PERSIST_INTERVAL_MS")?
-                .map(Duration::from_millis)
-                .unwrap_or(Duration::from_secs(60)),
+            downloads_persist_interval,
+            api_usage_persist_interval,
             ownership_invitations_expiration_days: 30,
-            metrics_authorization_token: var("METRICS_AUTHORIZATION_TOKEN")?,
-            instance_metrics_log_every_seconds: var_parsed("INSTANCE_METRICS_LOG_EVERY_SECONDS")?,
-            blocked_routes: HashSet::from_iter(list("BLOCKED_ROUTES")?),
-            version_id_cache_size: var_parsed("VERSION_ID_CACHE_SIZE")?
-                .unwrap_or(DEFAULT_VERSION_ID_CACHE_SIZE),
-            version_id_cache_ttl: Duration::from_secs(
-                var_parsed("VERSION_ID_CACHE_TTL")?.unwrap_or(DEFAULT_VERSION_ID_CACHE_TTL),
-            ),
-            cdn_user_agent: var("WEB_CDN_USER_AGENT")?
-                .unwrap_or_else(|| "Amazon CloudFront".into()),
-            cargo_compat_status_code_config: var_parsed("CARGO_COMPAT_STATUS_CODES")?
-                .unwrap_or(StatusCodeConfig::AdjustAll),
+            metrics_authorization_token,
+            docs_rs_callback_token,
+            crate_changes_tokens,
+            instance_metrics_log_every_seconds,
+            blocked_routes,
+            version_id_cache_size,
+            version_id_cache_ttl: Duration::from_secs(version_id_cache_ttl),
+            cdn_user_agent,
+            malware_quarantine_score,
+            typosquat_quarantine_matches,
+            storage_orphan_quarantine,
+            cargo_compat_status_code_config,
+            deployed_sha,
             serve_dist: true,
             serve_html: true,
-            content_security_policy: Some(content_security_policy.parse()?),
+            security_headers,
         })
     }
 }
@@ -252,13 +577,13 @@ fn parse_cidr_block(block: &str) -> anyhow::Result<IpNetwork> {
     Ok(cidr)
 }
 
-fn blocked_traffic() -> Vec<(String, Vec<String>)> {
-    let pattern_list = dotenvy::var("BLOCKED_TRAFFIC").unwrap_or_default();
+fn blocked_traffic() -> anyhow::Result<Vec<(String, Vec<String>)>> {
+    let pattern_list = var("BLOCKED_TRAFFIC")?.unwrap_or_default();
     parse_traffic_patterns(&pattern_list)
         .map(|(header, value_env_var)| {
-            let value_list = dotenvy::var(value_env_var).unwrap_or_default();
+            let value_list = var(value_env_var)?.unwrap_or_default();
             let values = value_list.split(',').map(String::from).collect();
-            (header.into(), values)
+            Ok((header.into(), values))
         })
         .collect()
 }
@@ -1,15 +1,27 @@
 pub mod helpers;
 pub mod util;
 
+pub mod admin;
+pub mod announcement;
 pub mod category;
+pub mod crate_changes;
 pub mod crate_owner_invitation;
+pub mod csp_report;
+pub mod db_dumps;
+pub mod dependency_report;
+pub mod docs_rs;
 pub mod git;
 pub mod github;
+pub mod health;
 pub mod keyword;
 pub mod krate;
 pub mod metrics;
+pub mod organization;
+pub mod robots;
 pub mod session;
 pub mod site_metadata;
+pub mod snapshot;
+pub mod stats;
 pub mod summary;
 pub mod team;
 pub mod token;
@@ -0,0 +1,40 @@
+//! Endpoints of the admin console.
+//!
+//! All of these endpoints are only available to crates.io administrators, and
+//! only through a cookie session on the crates.io website.
+
+use crate::auth::{AuthCheck, Authentication};
+use crate::util::errors::{forbidden, AppResult};
+use diesel_async::AsyncPgConnection;
+use http::request::Parts;
+
+pub mod announcements;
+pub mod broadcast_warnings;
+pub mod crates;
+pub mod database_pools;
+pub mod featured_crates;
+pub mod features;
+pub mod ip_access;
+pub mod keywords;
+pub mod publish_policy;
+pub mod read_only_mode;
+pub mod reclaim_requests;
+pub mod redirect_requests;
+pub mod reports;
+pub mod reserved_names;
+pub mod reserved_prefixes;
+pub mod storage_discrepancies;
+pub mod users;
+
+/// Authenticates the request and ensures that it was made by an administrator.
+pub async fn authenticate_admin(
+    parts: &Parts,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<Authentication> {
+    let auth = AuthCheck::only_cookie().check(parts, conn).await?;
+    if !auth.user().is_admin {
+        return Err(forbidden("this action requires admin privileges"));
+    }
+
+    Ok(auth)
+}
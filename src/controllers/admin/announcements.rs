@@ -0,0 +1,242 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::{Announcement, AnnouncementAudience, AnnouncementSeverity, NewAnnouncement};
+use crate::schema::{announcements, users};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::EncodableAdminAnnouncement;
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+use url::Url;
+
+/// List all announcements, including the scheduled and the ended ones.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/announcements",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_all_announcements(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let announcements = load_announcements(&mut conn, None).await?;
+
+    Ok(json!({ "announcements": announcements }))
+}
+
+#[derive(Deserialize)]
+pub struct AnnouncementRequest {
+    announcement: AnnouncementData,
+}
+
+#[derive(Deserialize)]
+pub struct AnnouncementData {
+    message: String,
+    /// URL of a page with more information about the announcement.
+    docs_url: Option<String>,
+    #[serde(default = "default_severity")]
+    severity: AnnouncementSeverity,
+    #[serde(default = "default_audience")]
+    audience: AnnouncementAudience,
+    /// Date and time from which on the announcement is shown. Without it, the
+    /// announcement is shown immediately.
+    starts_at: Option<DateTime<Utc>>,
+    /// Date and time when the announcement stops being shown. Without it, the
+    /// announcement is shown until it is deleted.
+    ends_at: Option<DateTime<Utc>>,
+}
+
+fn default_severity() -> AnnouncementSeverity {
+    AnnouncementSeverity::Info
+}
+
+fn default_audience() -> AnnouncementAudience {
+    AnnouncementAudience::All
+}
+
+/// An announcement that has been checked by [`AnnouncementData::validate`].
+struct ValidAnnouncement<'a> {
+    message: &'a str,
+    docs_url: Option<&'a str>,
+    starts_at: DateTime<Utc>,
+}
+
+impl AnnouncementData {
+    fn validate(&self) -> AppResult<ValidAnnouncement<'_>> {
+        let message = self.message.trim();
+        if message.is_empty() {
+            return Err(bad_request(
+                "the message of the announcement must not be empty",
+            ));
+        }
+
+        let docs_url = self.docs_url.as_deref().map(str::trim);
+        let docs_url = docs_url.filter(|docs_url| !docs_url.is_empty());
+        if let Some(docs_url) = docs_url {
+            let is_valid = Url::parse(docs_url).is_ok_and(|url| url.scheme() == "https");
+            if !is_valid {
+                return Err(bad_request(format!(
+                    "`{docs_url}` is not a valid https URL"
+                )));
+            }
+        }
+
+        let starts_at = self.starts_at.unwrap_or_else(Utc::now);
+        if self.ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+            return Err(bad_request("the announcement must end after it starts"));
+        }
+
+        Ok(ValidAnnouncement {
+            message,
+            docs_url,
+            starts_at,
+        })
+    }
+}
+
+/// Add an announcement that is shown in a banner on the crates.io website.
+///
+/// The announcement can be scheduled with `starts_at` and `ends_at`, and
+/// targeted at crate owners or API token users with `audience`.
+#[utoipa::path(
+    post,
+    path = "/api/private/admin/announcements",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_announcement(
+    app: AppState,
+    req: Parts,
+    Json(request): Json<AnnouncementRequest>,
+) -> AppResult<ErasedJson> {
+    let data = request.announcement;
+    let valid = data.validate()?;
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let new_announcement = NewAnnouncement {
+        message: valid.message,
+        docs_url: valid.docs_url,
+        severity: data.severity,
+        audience: data.audience,
+        starts_at: valid.starts_at,
+        ends_at: data.ends_at,
+        created_by: auth.user_id(),
+    };
+
+    let announcement = new_announcement.insert(&mut conn).await?;
+
+    let announcement = load_announcements(&mut conn, Some(announcement.id))
+        .await?
+        .pop();
+    Ok(json!({ "announcement": announcement }))
+}
+
+/// Change an announcement.
+///
+/// All fields of the announcement are replaced by the ones in the request.
+#[utoipa::path(
+    patch,
+    path = "/api/private/admin/announcements/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the announcement"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_announcement(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+    Json(request): Json<AnnouncementRequest>,
+) -> AppResult<ErasedJson> {
+    let data = request.announcement;
+    let valid = data.validate()?;
+
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let updated = diesel::update(announcements::table.find(id))
+        .set((
+            announcements::message.eq(valid.message),
+            announcements::docs_url.eq(valid.docs_url),
+            announcements::severity.eq(data.severity),
+            announcements::audience.eq(data.audience),
+            announcements::starts_at.eq(valid.starts_at),
+            announcements::ends_at.eq(data.ends_at),
+            announcements::updated_at.eq(Utc::now()),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+    if updated == 0 {
+        return Err(not_found());
+    }
+
+    let announcement = load_announcements(&mut conn, Some(id)).await?.pop();
+    Ok(json!({ "announcement": announcement }))
+}
+
+/// Remove an announcement.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/announcements/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the announcement"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_announcement(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(announcements::table.find(id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    ok_true()
+}
+
+async fn load_announcements(
+    conn: &mut AsyncPgConnection,
+    id: Option<i32>,
+) -> QueryResult<Vec<EncodableAdminAnnouncement>> {
+    let mut query = announcements::table
+        .left_join(users::table)
+        .select((Announcement::as_select(), users::gh_login.nullable()))
+        .order(announcements::id)
+        .into_boxed();
+
+    if let Some(id) = id {
+        query = query.filter(announcements::id.eq(id));
+    }
+
+    let announcements: Vec<(Announcement, Option<String>)> = query.load(conn).await?;
+
+    Ok(announcements
+        .into_iter()
+        .map(|(announcement, created_by)| EncodableAdminAnnouncement::new(announcement, created_by))
+        .collect())
+}
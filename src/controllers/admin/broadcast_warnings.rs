@@ -0,0 +1,161 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::{BroadcastWarning, NewBroadcastWarning};
+use crate::schema::{broadcast_warnings, users};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::EncodableBroadcastWarning;
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+use url::Url;
+
+/// List all broadcast warnings, including the expired ones.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/broadcast_warnings",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_broadcast_warnings(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let warnings = load_warnings(&mut conn, None).await?;
+
+    Ok(json!({ "warnings": warnings }))
+}
+
+#[derive(Deserialize)]
+pub struct CreateWarningRequest {
+    warning: NewWarning,
+}
+
+#[derive(Deserialize)]
+pub struct NewWarning {
+    message: String,
+    /// URL of a page with more information about the warning.
+    docs_url: Option<String>,
+    /// Date and time when the warning stops being returned. Without it, the
+    /// warning is returned until it is deleted.
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Add a warning that is returned for every publish.
+///
+/// Cargo shows the warning to everyone that publishes a crate, so it can be
+/// used to announce upcoming changes of crates.io.
+#[utoipa::path(
+    post,
+    path = "/api/private/admin/broadcast_warnings",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_broadcast_warning(
+    app: AppState,
+    req: Parts,
+    Json(request): Json<CreateWarningRequest>,
+) -> AppResult<ErasedJson> {
+    let NewWarning {
+        message,
+        docs_url,
+        expires_at,
+    } = request.warning;
+
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(bad_request("the message of the warning must not be empty"));
+    }
+
+    let docs_url = docs_url.as_deref().map(str::trim);
+    let docs_url = docs_url.filter(|docs_url| !docs_url.is_empty());
+    if let Some(docs_url) = docs_url {
+        let is_valid = Url::parse(docs_url).is_ok_and(|url| url.scheme() == "https");
+        if !is_valid {
+            return Err(bad_request(format!(
+                "`{docs_url}` is not a valid https URL"
+            )));
+        }
+    }
+
+    if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(bad_request(
+            "the expiry date of the warning must be in the future",
+        ));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let new_warning = NewBroadcastWarning {
+        message,
+        docs_url,
+        expires_at,
+        created_by: auth.user_id(),
+    };
+
+    let warning = new_warning.insert(&mut conn).await?;
+
+    let warning = load_warnings(&mut conn, Some(warning.id)).await?.pop();
+    Ok(json!({ "warning": warning }))
+}
+
+/// Remove a broadcast warning.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/broadcast_warnings/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the warning"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_broadcast_warning(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(broadcast_warnings::table.find(id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    ok_true()
+}
+
+async fn load_warnings(
+    conn: &mut AsyncPgConnection,
+    id: Option<i32>,
+) -> QueryResult<Vec<EncodableBroadcastWarning>> {
+    let mut query = broadcast_warnings::table
+        .left_join(users::table)
+        .select((BroadcastWarning::as_select(), users::gh_login.nullable()))
+        .order(broadcast_warnings::id)
+        .into_boxed();
+
+    if let Some(id) = id {
+        query = query.filter(broadcast_warnings::id.eq(id));
+    }
+
+    let warnings: Vec<(BroadcastWarning, Option<String>)> = query.load(conn).await?;
+
+    Ok(warnings
+        .into_iter()
+        .map(|(warning, created_by)| EncodableBroadcastWarning::new(warning, created_by))
+        .collect())
+}
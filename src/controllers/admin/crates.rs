@@ -0,0 +1,547 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::krate::{load_crate, CratePath};
+use crate::controllers::version::update::perform_version_yank_update;
+use crate::controllers::version::CrateVersionPath;
+use crate::email::Email;
+use crate::models::{AuditLogAction, Crate, NewAuditLogEntry, Owner, Version};
+use crate::schema::{crates, versions};
+use crate::util::errors::{bad_request, version_not_found, AppResult, BoxedAppError};
+use crate::views::{EncodableCrateModeration, EncodableDeletedVersion};
+use crate::worker::jobs::rss::{SyncCrateFeed, SyncUpdatesFeed};
+use crate::worker::jobs::{
+    ExportMetadataSnapshot, SyncCrateFileQuarantine, SyncToGitIndex, SyncToSparseIndex,
+    UpdateDefaultVersion,
+};
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+
+/// Get the moderation state of a crate.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/crates/{name}",
+    params(CratePath),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_crate_moderation(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+    let moderation = load_moderation(&mut conn, &krate).await?;
+
+    Ok(json!({ "crate": moderation }))
+}
+
+#[derive(Deserialize)]
+pub struct ModerationRequest {
+    /// Hide the crate from search and listings, or make it visible again.
+    hidden: Option<bool>,
+    /// Freeze the crate against new publishes, or unfreeze it again.
+    frozen: Option<bool>,
+    /// Quarantine the crate, or approve a quarantined crate.
+    quarantined: Option<bool>,
+    /// The reason for the change, which is recorded in the audit log.
+    reason: String,
+    /// Whether the owners of the crate should be notified by email.
+    #[serde(default)]
+    notify_owners: bool,
+}
+
+/// Hide, freeze or quarantine a crate.
+///
+/// Hidden crates are excluded from search and all crate listings. Frozen
+/// crates can not receive new versions. Quarantined crates are additionally
+/// only downloadable by their owners, until they are approved by setting
+/// `quarantined` to `false`. Each change is recorded in the audit log of the
+/// crate, together with the reason.
+#[utoipa::path(
+    patch,
+    path = "/api/private/admin/crates/{name}",
+    params(CratePath),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_crate_moderation(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Json(request): Json<ModerationRequest>,
+) -> AppResult<ErasedJson> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("a reason is required for moderation actions"));
+    }
+
+    if request.hidden.is_none() && request.frozen.is_none() && request.quarantined.is_none() {
+        return Err(bad_request(
+            "one of `hidden`, `frozen` or `quarantined` must be set",
+        ));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+    let admin_id = auth.user_id();
+
+    let krate = path.load_crate(&mut conn).await?;
+    let krate_id = krate.id;
+
+    let actions = conn
+        .transaction(|conn| {
+            async move {
+                let mut actions = Vec::new();
+
+                if let Some(hidden) = request.hidden {
+                    let query = crates::table
+                        .find(krate_id)
+                        .filter(crates::hidden_at.is_null().eq(hidden));
+
+                    let hidden_at = hidden.then(Utc::now);
+                    let updated = diesel::update(query)
+                        .set(crates::hidden_at.eq(hidden_at))
+                        .execute(conn)
+                        .await?;
+
+                    if updated > 0 {
+                        actions.push(match hidden {
+                            true => AuditLogAction::CrateHide,
+                            false => AuditLogAction::CrateUnhide,
+                        });
+                    }
+                }
+
+                if let Some(frozen) = request.frozen {
+                    let query = crates::table
+                        .find(krate_id)
+                        .filter(crates::frozen_at.is_null().eq(frozen));
+
+                    let frozen_at = frozen.then(Utc::now);
+                    let updated = diesel::update(query)
+                        .set(crates::frozen_at.eq(frozen_at))
+                        .execute(conn)
+                        .await?;
+
+                    if updated > 0 {
+                        actions.push(match frozen {
+                            true => AuditLogAction::CrateFreeze,
+                            false => AuditLogAction::CrateUnfreeze,
+                        });
+                    }
+                }
+
+                if let Some(quarantined) = request.quarantined {
+                    let query = crates::table
+                        .find(krate_id)
+                        .filter(crates::quarantined_at.is_null().eq(quarantined));
+
+                    let quarantined_at = quarantined.then(Utc::now);
+                    let updated = diesel::update(query)
+                        .set(crates::quarantined_at.eq(quarantined_at))
+                        .execute(conn)
+                        .await?;
+
+                    if updated > 0 {
+                        SyncCrateFileQuarantine::enqueue_for_crate(conn, krate_id).await?;
+                        actions.push(match quarantined {
+                            true => AuditLogAction::CrateQuarantine,
+                            false => AuditLogAction::CrateRelease,
+                        });
+                    }
+                }
+
+                for action in &actions {
+                    NewAuditLogEntry::builder()
+                        .user_id(admin_id)
+                        .crate_id(krate_id)
+                        .action(*action)
+                        .details(serde_json::json!({ "reason": reason }))
+                        .build()
+                        .insert(conn)
+                        .await?;
+                }
+
+                Ok::<_, BoxedAppError>(actions)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    if request.notify_owners {
+        for action in actions {
+            let name = &krate.name;
+            let change = match action {
+                AuditLogAction::CrateHide => {
+                    format!("hidden your \"{name}\" crate from search and crate listings")
+                }
+                AuditLogAction::CrateUnhide => {
+                    format!("made your \"{name}\" crate visible in search and crate listings again")
+                }
+                AuditLogAction::CrateFreeze => {
+                    format!("frozen your \"{name}\" crate, so no new versions can be published")
+                }
+                AuditLogAction::CrateUnfreeze => format!(
+                    "unfrozen your \"{name}\" crate, so new versions can be published again"
+                ),
+                AuditLogAction::CrateQuarantine => format!(
+                    "quarantined your \"{name}\" crate, so it can only be downloaded by its owners until it has been reviewed"
+                ),
+                _ => format!(
+                    "approved your \"{name}\" crate, so it is available to everyone again"
+                ),
+            };
+
+            notify_owners(&app, &mut conn, &krate, &change, reason).await?;
+        }
+    }
+
+    let moderation = load_moderation(&mut conn, &krate).await?;
+
+    Ok(json!({ "crate": moderation }))
+}
+
+#[derive(Deserialize)]
+pub struct ForceYankRequest {
+    /// The reason for yanking the version, which is used as the yank message.
+    reason: String,
+    /// Whether the owners of the crate should be notified by email.
+    #[serde(default)]
+    notify_owners: bool,
+}
+
+/// Yank a version of a crate on behalf of the crates.io team.
+///
+/// The reason is used as the yank message of the version and recorded in the
+/// audit log of the crate.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/crates/{name}/{version}/yank",
+    params(CrateVersionPath),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn force_yank_version(
+    app: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+    Json(request): Json<ForceYankRequest>,
+) -> AppResult<ErasedJson> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("a reason is required for moderation actions"));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let (mut version, krate) = path.load_version_and_crate(&mut conn).await?;
+
+    perform_version_yank_update(
+        &app,
+        &mut conn,
+        &mut version,
+        &krate,
+        &auth,
+        Some(true),
+        Some(reason.to_string()),
+    )
+    .await?;
+
+    if request.notify_owners {
+        let change = format!(
+            "yanked version {} of your \"{}\" crate",
+            version.num, krate.name
+        );
+        notify_owners(&app, &mut conn, &krate, &change, reason).await?;
+    }
+
+    let moderation = load_moderation(&mut conn, &krate).await?;
+
+    Ok(json!({ "crate": moderation }))
+}
+
+#[derive(Deserialize)]
+pub struct VersionDeletionRequest {
+    /// The reason for the change, which is recorded in the audit log.
+    reason: String,
+    /// Whether the owners of the crate should be notified by email.
+    #[serde(default)]
+    notify_owners: bool,
+}
+
+/// Soft-delete a version of a crate.
+///
+/// Deleted versions are removed from the index and the API, and their
+/// `.crate` file is moved to a quarantine, where it can't be downloaded
+/// anymore. The database rows and the file are kept as evidence, so that the
+/// version can be restored. The last remaining version of a crate can't be
+/// deleted.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/crates/{name}/{version}/delete",
+    params(CrateVersionPath),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_version(
+    app: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+    Json(request): Json<VersionDeletionRequest>,
+) -> AppResult<ErasedJson> {
+    update_version_deletion(app, path, req, request, true).await
+}
+
+/// Restore a soft-deleted version of a crate.
+///
+/// The version is added back to the index and the API, and its `.crate`
+/// file is moved back from the quarantine.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/crates/{name}/{version}/restore",
+    params(CrateVersionPath),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn restore_version(
+    app: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+    Json(request): Json<VersionDeletionRequest>,
+) -> AppResult<ErasedJson> {
+    update_version_deletion(app, path, req, request, false).await
+}
+
+async fn update_version_deletion(
+    app: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+    request: VersionDeletionRequest,
+    deleted: bool,
+) -> AppResult<ErasedJson> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("a reason is required for moderation actions"));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+    let admin_id = auth.user_id();
+
+    // Deleted versions are not found by `CrateVersionPath`, so the version is
+    // looked up directly.
+    let krate = load_crate(&mut conn, &path.name).await?;
+    let (version_id, num): (i32, String) = Version::belonging_to(&krate)
+        .filter(versions::num.eq(&path.version))
+        .select((versions::id, versions::num))
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| version_not_found(&krate.name, &path.version))?;
+
+    let krate_id = krate.id;
+    let krate_name = &krate.name;
+    let num = &num;
+    let updated = conn
+        .transaction(|conn| {
+            async move {
+                if deleted {
+                    // The remaining versions are locked, so that concurrent
+                    // requests can't delete all of them.
+                    let remaining: Vec<i32> = versions::table
+                        .filter(versions::crate_id.eq(krate_id))
+                        .filter(Version::not_deleted())
+                        .select(versions::id)
+                        .for_update()
+                        .load(conn)
+                        .await?;
+
+                    if remaining.iter().all(|id| *id == version_id) {
+                        return Err(bad_request(
+                            "the last version of a crate can not be deleted",
+                        ));
+                    }
+                }
+
+                let query = versions::table
+                    .find(version_id)
+                    .filter(versions::deleted_at.is_null().eq(deleted));
+
+                let deleted_at = deleted.then(Utc::now);
+                let updated = diesel::update(query)
+                    .set(versions::deleted_at.eq(deleted_at))
+                    .execute(conn)
+                    .await?;
+
+                if updated == 0 {
+                    return Ok(false);
+                }
+
+                let action = match deleted {
+                    true => AuditLogAction::VersionDelete,
+                    false => AuditLogAction::VersionRestore,
+                };
+                NewAuditLogEntry::builder()
+                    .user_id(admin_id)
+                    .crate_id(krate_id)
+                    .action(action)
+                    .details(serde_json::json!({ "version": num, "reason": reason }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                let git_index_job = SyncToGitIndex::new(krate_name);
+                let sparse_index_job = SyncToSparseIndex::new(krate_name);
+                let update_default_version_job = UpdateDefaultVersion::new(krate_id);
+                let quarantine_job = SyncCrateFileQuarantine::new(version_id);
+                let crate_feed_job = SyncCrateFeed::new(krate_name.clone());
+
+                tokio::try_join!(
+                    git_index_job.enqueue(conn),
+                    sparse_index_job.enqueue(conn),
+                    update_default_version_job.enqueue(conn),
+                    quarantine_job.enqueue(conn),
+                    ExportMetadataSnapshot.enqueue(conn),
+                    crate_feed_job.enqueue(conn),
+                    SyncUpdatesFeed.enqueue(conn),
+                )?;
+
+                Ok::<_, BoxedAppError>(true)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    if updated {
+        app.dependency_graph_cache.clear();
+    }
+
+    if updated && request.notify_owners {
+        let change = match deleted {
+            true => format!(
+                "deleted version {num} of your \"{}\" crate, so it can't be downloaded anymore",
+                krate.name
+            ),
+            false => format!(
+                "restored version {num} of your \"{}\" crate, so it is available again",
+                krate.name
+            ),
+        };
+        notify_owners(&app, &mut conn, &krate, &change, reason).await?;
+    }
+
+    let moderation = load_moderation(&mut conn, &krate).await?;
+
+    Ok(json!({ "crate": moderation }))
+}
+
+async fn load_moderation(
+    conn: &mut AsyncPgConnection,
+    krate: &Crate,
+) -> QueryResult<EncodableCrateModeration> {
+    type Timestamp = Option<DateTime<Utc>>;
+
+    let (hidden_at, frozen_at, quarantined_at): (Timestamp, Timestamp, Timestamp) = crates::table
+        .find(krate.id)
+        .select((crates::hidden_at, crates::frozen_at, crates::quarantined_at))
+        .get_result(conn)
+        .await?;
+
+    let deleted_versions = Version::belonging_to(krate)
+        .filter(versions::deleted_at.is_not_null())
+        .select((versions::num, versions::deleted_at.assume_not_null()))
+        .order(versions::deleted_at.desc())
+        .load::<(String, DateTime<Utc>)>(conn)
+        .await?
+        .into_iter()
+        .map(|(num, deleted_at)| EncodableDeletedVersion { num, deleted_at })
+        .collect();
+
+    Ok(EncodableCrateModeration {
+        name: krate.name.clone(),
+        hidden_at,
+        frozen_at,
+        quarantined_at,
+        deleted_versions,
+    })
+}
+
+async fn notify_owners(
+    app: &AppState,
+    conn: &mut AsyncPgConnection,
+    krate: &Crate,
+    change: &str,
+    reason: &str,
+) -> QueryResult<()> {
+    for owner in krate.owners(conn).await? {
+        let Owner::User(owner, _) = owner else {
+            continue;
+        };
+
+        let email = CrateModerationEmail {
+            user: &owner.gh_login,
+            krate: &krate.name,
+            change,
+            reason,
+        };
+
+        let email_future = async {
+            if let Some(recipient) = owner.verified_email(conn).await? {
+                app.emails.send(&recipient, email).await?
+            }
+
+            Ok::<_, anyhow::Error>(())
+        };
+
+        if let Err(err) = email_future.await {
+            warn!("Failed to send crate moderation email: {err}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Email template for notifying the owners of a crate about a moderation
+/// action of the crates.io team.
+#[derive(Debug, Clone)]
+struct CrateModerationEmail<'a> {
+    user: &'a str,
+    krate: &'a str,
+    change: &'a str,
+    reason: &'a str,
+}
+
+impl Email for CrateModerationEmail<'_> {
+    fn subject(&self) -> String {
+        format!("crates.io: Moderation of the \"{}\" crate", self.krate)
+    }
+
+    fn body(&self) -> String {
+        format!(
+            "Hi {},
+
+the crates.io team has {}.
+
+Reason: {}
+
+If you have any questions, please contact us at help@crates.io.",
+            self.user, self.change, self.reason
+        )
+    }
+}
@@ -0,0 +1,113 @@
+use crate::app::{App, AppState};
+use crate::config::DbPoolConfig;
+use crate::controllers::admin::authenticate_admin;
+use crate::util::errors::{bad_request, not_found, AppResult};
+use axum::extract::Path;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::AsyncPgConnection;
+use http::request::Parts;
+
+/// List the database pools of this instance.
+///
+/// Waiting times for connections are exposed as a histogram on the instance
+/// metrics endpoint.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/database_pools",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_database_pools(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let pools = ["primary", "replica"]
+        .into_iter()
+        .filter_map(|name| find_pool(&app, name))
+        .map(|(name, pool, config)| encode(name, pool, config))
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "database_pools": pools }))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateDatabasePoolRequest {
+    max_size: usize,
+}
+
+/// Change the maximum number of connections of a database pool.
+///
+/// The size must be between 1 and the configured upper bound of the pool.
+/// The change only applies to the instance that handles the request, and is
+/// reset to the configured size when the instance restarts.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/database_pools/{name}",
+    params(
+        ("name" = String, Path, description = "Name of the pool, either `primary` or `replica`"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_database_pool(
+    app: AppState,
+    Path(name): Path<String>,
+    req: Parts,
+    Json(request): Json<UpdateDatabasePoolRequest>,
+) -> AppResult<ErasedJson> {
+    // The connection is returned to the pool before the pool is resized.
+    {
+        let mut conn = app.db_read_prefer_primary().await?;
+        authenticate_admin(&req, &mut conn).await?;
+    }
+
+    let (name, pool, config) = find_pool(&app, &name).ok_or_else(not_found)?;
+
+    let max_size = request.max_size;
+    if !(1..=config.max_pool_size).contains(&max_size) {
+        let detail = format!(
+            "the size of the {name} pool must be between 1 and {}",
+            config.max_pool_size
+        );
+        return Err(bad_request(detail));
+    }
+
+    pool.resize(max_size);
+    info!("Resized the {name} database pool to {max_size} connections");
+
+    Ok(json!({ "database_pool": encode(name, pool, config) }))
+}
+
+fn find_pool<'a>(
+    app: &'a App,
+    name: &str,
+) -> Option<(&'static str, &'a Pool<AsyncPgConnection>, &'a DbPoolConfig)> {
+    match name {
+        "primary" => Some(("primary", &app.primary_database, &app.config.db.primary)),
+        "replica" => {
+            let pool = app.replica_database.as_ref()?;
+            let config = app.config.db.replica.as_ref()?;
+            Some(("replica", pool, config))
+        }
+        _ => None,
+    }
+}
+
+fn encode(name: &str, pool: &Pool<AsyncPgConnection>, config: &DbPoolConfig) -> serde_json::Value {
+    let status = pool.status();
+
+    serde_json::json!({
+        "name": name,
+        "max_size": status.max_size,
+        "configured_size": config.pool_size,
+        "max_allowed_size": config.max_pool_size,
+        "size": status.size,
+        "idle": status.available,
+        "waiting": status.waiting,
+    })
+}
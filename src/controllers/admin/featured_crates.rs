@@ -0,0 +1,137 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::{Crate, FeaturedCrate, NewFeaturedCrate};
+use crate::schema::{crates, featured_crates};
+use crate::util::errors::{bad_request, crate_not_found, not_found, AppResult};
+use crate::views::EncodableFeaturedCrate;
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+/// List all highlighted crates, including the scheduled and expired ones.
+///
+/// The entries are sorted by their start date, the most recent first.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/featured_crates",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_featured_crates(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let featured_crates = featured_crates::table
+        .inner_join(crates::table)
+        .select((FeaturedCrate::as_select(), crates::name))
+        .order((
+            featured_crates::starts_at.desc(),
+            featured_crates::id.desc(),
+        ))
+        .load::<(FeaturedCrate, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(featured, crate_name)| EncodableFeaturedCrate::new(featured, crate_name))
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "featured_crates": featured_crates }))
+}
+
+#[derive(Deserialize)]
+pub struct CreateFeaturedCrateRequest {
+    /// Name of the crate to highlight.
+    #[serde(rename = "crate")]
+    crate_name: String,
+    /// When the crate starts being highlighted. Defaults to now.
+    starts_at: Option<DateTime<Utc>>,
+    /// When the crate stops being highlighted. Defaults to never.
+    ends_at: Option<DateTime<Utc>>,
+    note: Option<String>,
+}
+
+/// Highlight a crate on the front page.
+///
+/// The crate is listed in the `featured_crates` field of the summary
+/// endpoint between the start and end date of the entry.
+#[utoipa::path(
+    post,
+    path = "/api/private/admin/featured_crates",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_featured_crate(
+    app: AppState,
+    req: Parts,
+    Json(request): Json<CreateFeaturedCrateRequest>,
+) -> AppResult<ErasedJson> {
+    let starts_at = request.starts_at.unwrap_or_else(Utc::now);
+    if request.ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+        return Err(bad_request("the end date must be after the start date"));
+    }
+
+    let note = request.note.as_deref().map(str::trim);
+    let note = note.filter(|note| !note.is_empty());
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let crate_name = &request.crate_name;
+    let (crate_id, crate_name): (i32, String) = Crate::by_name(crate_name)
+        .select((crates::id, crates::name))
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| crate_not_found(crate_name))?;
+
+    let featured = NewFeaturedCrate::builder()
+        .crate_id(crate_id)
+        .starts_at(starts_at)
+        .maybe_ends_at(request.ends_at)
+        .maybe_note(note)
+        .created_by(auth.user_id())
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    let featured = EncodableFeaturedCrate::new(featured, crate_name);
+    Ok(json!({ "featured_crate": featured }))
+}
+
+/// Stop highlighting a crate and delete the entry.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/featured_crates/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the entry"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_featured_crate(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(featured_crates::table.find(id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    ok_true()
+}
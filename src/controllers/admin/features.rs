@@ -0,0 +1,264 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::{FeatureFlag, FeatureOverride, NewFeatureFlag, User};
+use crate::schema::{feature_overrides, features, users};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::{EncodableFeatureFlag, EncodableFeatureOverride};
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+use std::collections::HashMap;
+
+const MAX_NAME_LENGTH: usize = 64;
+
+/// List all feature flags and their per-user overrides.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/features",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_features(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let features = load_features(&mut conn, None).await?;
+
+    Ok(json!({ "features": features }))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateFeatureRequest {
+    /// Whether the feature is switched on for all users.
+    enabled: bool,
+    /// Percentage of users for which the feature is switched on, if it is
+    /// not enabled for all users.
+    #[serde(default)]
+    rollout_percentage: i16,
+    description: Option<String>,
+}
+
+/// Create or update a feature flag.
+///
+/// The change applies to all server instances within a few seconds.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/features/{name}",
+    params(
+        ("name" = String, Path, description = "Name of the feature"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_feature(
+    app: AppState,
+    Path(name): Path<String>,
+    req: Parts,
+    Json(request): Json<UpdateFeatureRequest>,
+) -> AppResult<ErasedJson> {
+    if !is_valid_name(&name) {
+        return Err(bad_request(format!(
+            "invalid feature name, only lowercase letters, digits and underscores are allowed, \
+            and the name must not be longer than {MAX_NAME_LENGTH} characters"
+        )));
+    }
+
+    if !(0..=100).contains(&request.rollout_percentage) {
+        return Err(bad_request(
+            "the rollout percentage must be between 0 and 100",
+        ));
+    }
+
+    let description = request.description.as_deref().map(str::trim);
+    let description = description.filter(|description| !description.is_empty());
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    NewFeatureFlag::builder()
+        .name(&name)
+        .enabled(request.enabled)
+        .rollout_percentage(request.rollout_percentage)
+        .maybe_description(description)
+        .updated_by(auth.user_id())
+        .build()
+        .upsert(&mut conn)
+        .await?;
+
+    app.feature_flags.invalidate();
+
+    let feature = load_features(&mut conn, Some(&name)).await?.pop();
+    Ok(json!({ "feature": feature }))
+}
+
+/// Delete a feature flag, which switches the feature off for all users.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/features/{name}",
+    params(
+        ("name" = String, Path, description = "Name of the feature"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_feature(
+    app: AppState,
+    Path(name): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(features::table.find(&name))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    app.feature_flags.invalidate();
+
+    ok_true()
+}
+
+#[derive(Deserialize)]
+pub struct UpdateOverrideRequest {
+    enabled: bool,
+}
+
+/// Switch a feature on or off for a single user.
+///
+/// Overrides take precedence over the rollout of the feature, e.g. to let
+/// a user test a new feature before it is rolled out.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/features/{name}/overrides/{user}",
+    params(
+        ("name" = String, Path, description = "Name of the feature"),
+        ("user" = String, Path, description = "Login of the user"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_feature_override(
+    app: AppState,
+    Path((name, login)): Path<(String, String)>,
+    req: Parts,
+    Json(request): Json<UpdateOverrideRequest>,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    FeatureFlag::find(&mut conn, &name)
+        .await?
+        .ok_or_else(not_found)?;
+
+    let user = User::find_by_login(&mut conn, &login).await?;
+    let feature_override = FeatureOverride::set(&mut conn, &name, user.id, request.enabled).await?;
+
+    app.feature_flags.invalidate();
+
+    let feature_override = EncodableFeatureOverride::new(feature_override, user.gh_login);
+    Ok(json!({ "override": feature_override }))
+}
+
+/// Remove the override of a feature for a single user.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/features/{name}/overrides/{user}",
+    params(
+        ("name" = String, Path, description = "Name of the feature"),
+        ("user" = String, Path, description = "Login of the user"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_feature_override(
+    app: AppState,
+    Path((name, login)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let user = User::find_by_login(&mut conn, &login).await?;
+
+    let deleted = diesel::delete(feature_overrides::table.find((&name, user.id)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    app.feature_flags.invalidate();
+
+    ok_true()
+}
+
+/// Loads all features, or only the feature with the given name, including
+/// their overrides.
+async fn load_features(
+    conn: &mut AsyncPgConnection,
+    name: Option<&str>,
+) -> QueryResult<Vec<EncodableFeatureFlag>> {
+    let mut query = features::table
+        .left_join(users::table)
+        .select((FeatureFlag::as_select(), users::gh_login.nullable()))
+        .order(features::name)
+        .into_boxed();
+
+    let mut overrides_query = feature_overrides::table
+        .inner_join(users::table)
+        .select((FeatureOverride::as_select(), users::gh_login))
+        .order((feature_overrides::feature, users::gh_login))
+        .into_boxed();
+
+    if let Some(name) = name {
+        query = query.filter(features::name.eq(name));
+        overrides_query = overrides_query.filter(feature_overrides::feature.eq(name));
+    }
+
+    let flags: Vec<(FeatureFlag, Option<String>)> = query.load(conn).await?;
+    let overrides: Vec<(FeatureOverride, String)> = overrides_query.load(conn).await?;
+
+    let mut overrides_by_feature: HashMap<String, Vec<EncodableFeatureOverride>> = HashMap::new();
+    for (feature_override, login) in overrides {
+        let feature = feature_override.feature.clone();
+        let feature_override = EncodableFeatureOverride::new(feature_override, login);
+        overrides_by_feature
+            .entry(feature)
+            .or_default()
+            .push(feature_override);
+    }
+
+    let features = flags
+        .into_iter()
+        .map(|(flag, updated_by)| {
+            let overrides = overrides_by_feature.remove(&flag.name).unwrap_or_default();
+            EncodableFeatureFlag::new(flag, updated_by, overrides)
+        })
+        .collect();
+
+    Ok(features)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
@@ -0,0 +1,180 @@
+use crate::abuse_throttle::parse_network;
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::{IpAccessRule, IpAccessRuleKind, IpBan, NewIpAccessRule};
+use crate::schema::{ip_access_rules, ip_bans};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::{EncodableIpAccessRule, EncodableIpBan};
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error};
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+/// List the IP networks that are currently banned.
+///
+/// Networks are banned automatically for a limited time when they send too
+/// many requests or cause too many errors.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/ip_bans",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_ip_bans(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let bans = IpBan::active(&mut conn).await?;
+    let bans = bans
+        .into_iter()
+        .map(EncodableIpBan::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "ip_bans": bans }))
+}
+
+/// Lift the ban of an IP network.
+///
+/// The ban is lifted immediately on the server instance that handles this
+/// request, and on all other instances once they reload the bans.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/ip_bans/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the ban"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_ip_ban(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let network: String = diesel::delete(ip_bans::table.find(id))
+        .returning(ip_bans::network)
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    app.abuse_throttle.lift(&network);
+
+    ok_true()
+}
+
+/// List the permanent IP access rules.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/ip_rules",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_ip_rules(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let rules = IpAccessRule::all(&mut conn).await?;
+    let rules = rules
+        .into_iter()
+        .map(EncodableIpAccessRule::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "ip_rules": rules }))
+}
+
+#[derive(Deserialize)]
+pub struct CreateIpRuleRequest {
+    /// The IP network in CIDR notation, e.g. `192.0.2.0/24`.
+    network: String,
+    kind: IpAccessRuleKind,
+    reason: Option<String>,
+}
+
+/// Create a permanent IP access rule.
+///
+/// Networks with an `allow` rule are exempt from the automatic bans, while
+/// networks with a `deny` rule are denied access entirely. Allow rules take
+/// precedence over deny rules. Like bans, the rules are reloaded periodically
+/// by the other server instances.
+#[utoipa::path(
+    post,
+    path = "/api/private/admin/ip_rules",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_ip_rule(
+    app: AppState,
+    req: Parts,
+    Json(request): Json<CreateIpRuleRequest>,
+) -> AppResult<ErasedJson> {
+    let Some(network) = parse_network(request.network.trim()) else {
+        return Err(bad_request("invalid IP network"));
+    };
+
+    let reason = request.reason.as_deref().map(str::trim);
+    let reason = reason.filter(|reason| !reason.is_empty());
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let network = network.to_string();
+    let rule = NewIpAccessRule::builder()
+        .network(&network)
+        .kind(request.kind)
+        .maybe_reason(reason)
+        .created_by(auth.user_id())
+        .build()
+        .insert(&mut conn)
+        .await
+        .map_err(|error| match error {
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                bad_request(format!("a rule for `{network}` already exists"))
+            }
+            error => error.into(),
+        })?;
+
+    app.abuse_throttle.invalidate();
+
+    Ok(json!({ "ip_rule": EncodableIpAccessRule::from(rule) }))
+}
+
+/// Delete a permanent IP access rule.
+///
+/// The rule is removed immediately on the server instance that handles this
+/// request, and on all other instances once they reload the rules.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/ip_rules/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the rule"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_ip_rule(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(ip_access_rules::table.find(id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    app.abuse_throttle.invalidate();
+
+    ok_true()
+}
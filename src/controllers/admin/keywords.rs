@@ -0,0 +1,201 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::Keyword;
+use crate::schema::{keyword_aliases, keywords};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::{EncodableKeyword, EncodableKeywordAlias};
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+
+#[derive(Deserialize)]
+pub struct MergeKeywordRequest {
+    /// Name of the keyword that the crates are moved to.
+    into: String,
+}
+
+/// Merge a keyword into another keyword.
+///
+/// All crates of the keyword are moved to the other keyword, and the keyword
+/// is replaced by an alias, so that e.g. `/api/v1/keywords/{keyword}` and
+/// newly published crates with the keyword keep working.
+#[utoipa::path(
+    post,
+    path = "/api/private/admin/keywords/{keyword}/merge",
+    params(
+        ("keyword" = String, Path, description = "The keyword to merge"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn merge_keyword(
+    app: AppState,
+    Path(keyword): Path<String>,
+    req: Parts,
+    Json(request): Json<MergeKeywordRequest>,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let source = find_keyword(&mut conn, &keyword).await?;
+    let target = find_keyword(&mut conn, &request.into).await?;
+    if source.id == target.id {
+        return Err(bad_request("a keyword cannot be merged into itself"));
+    }
+
+    source
+        .merge_into(&mut conn, &target, Some(auth.user_id()))
+        .await?;
+
+    let target = find_keyword(&mut conn, &target.keyword).await?;
+    Ok(json!({ "keyword": EncodableKeyword::from(target) }))
+}
+
+/// List all keyword aliases.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/keyword_aliases",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_keyword_aliases(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let aliases = keyword_aliases::table
+        .inner_join(keywords::table)
+        .select((
+            keyword_aliases::alias,
+            keywords::keyword,
+            keyword_aliases::created_at,
+        ))
+        .order(keyword_aliases::alias)
+        .load::<(String, String, DateTime<Utc>)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(alias, keyword, created_at)| EncodableKeywordAlias {
+            alias,
+            keyword,
+            created_at,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "keyword_aliases": aliases }))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateKeywordAliasRequest {
+    /// Name of the keyword that the alias redirects to.
+    keyword: String,
+}
+
+/// Create or update a keyword alias.
+///
+/// Crates that are published with the alias as a keyword get the keyword
+/// that the alias redirects to instead. Use the merge endpoint for aliases
+/// that are already in use as keywords.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/keyword_aliases/{alias}",
+    params(
+        ("alias" = String, Path, description = "The alias"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_keyword_alias(
+    app: AppState,
+    Path(alias): Path<String>,
+    req: Parts,
+    Json(request): Json<UpdateKeywordAliasRequest>,
+) -> AppResult<ErasedJson> {
+    let alias = alias.to_lowercase();
+    if !Keyword::valid_name(&alias) {
+        return Err(bad_request(format!("invalid keyword alias: `{alias}`")));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    if Keyword::find_by_keyword(&mut conn, &alias)
+        .await
+        .optional()?
+        .is_some()
+    {
+        return Err(bad_request(format!(
+            "`{alias}` is an existing keyword, merge it into the other keyword instead"
+        )));
+    }
+
+    let keyword = find_keyword(&mut conn, &request.keyword).await?;
+
+    let created_at = diesel::insert_into(keyword_aliases::table)
+        .values((
+            keyword_aliases::alias.eq(&alias),
+            keyword_aliases::keyword_id.eq(keyword.id),
+            keyword_aliases::created_by.eq(auth.user_id()),
+        ))
+        .on_conflict(keyword_aliases::alias)
+        .do_update()
+        .set(keyword_aliases::keyword_id.eq(keyword.id))
+        .returning(keyword_aliases::created_at)
+        .get_result(&mut conn)
+        .await?;
+
+    let alias = EncodableKeywordAlias {
+        alias,
+        keyword: keyword.keyword,
+        created_at,
+    };
+
+    Ok(json!({ "keyword_alias": alias }))
+}
+
+/// Delete a keyword alias.
+///
+/// Deleting the alias of a merged keyword doesn't restore the keyword.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/keyword_aliases/{alias}",
+    params(
+        ("alias" = String, Path, description = "The alias"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_keyword_alias(
+    app: AppState,
+    Path(alias): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(keyword_aliases::table.find(alias.to_lowercase()))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    ok_true()
+}
+
+async fn find_keyword(conn: &mut AsyncPgConnection, name: &str) -> AppResult<Keyword> {
+    Keyword::find_by_keyword(conn, name)
+        .await
+        .optional()?
+        .ok_or_else(|| bad_request(format!("keyword `{name}` does not exist")))
+}
@@ -0,0 +1,227 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::{NewPublishPolicyRule, PolicyRuleKind, PublishPolicyRule};
+use crate::publish_policy::Rule;
+use crate::schema::{publish_policy_rules, users};
+use crate::util::errors::{bad_request, not_found, AppResult, BoxedAppError};
+use crate::views::EncodablePublishPolicyRule;
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+
+/// List all publish policy rules.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/publish_policy_rules",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_publish_policy_rules(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let rules = load_rules(&mut conn, None).await?;
+
+    Ok(json!({ "rules": rules }))
+}
+
+#[derive(Deserialize)]
+pub struct CreateRuleRequest {
+    rule: NewRule,
+}
+
+#[derive(Deserialize)]
+pub struct NewRule {
+    kind: PolicyRuleKind,
+    /// The maximum number, the SPDX identifier of the banned license or the
+    /// name of the required field, depending on the kind of the rule.
+    value: String,
+    /// Additional explanation that is shown to users when their crate
+    /// violates the rule.
+    message: Option<String>,
+    /// Date and time from which on the rule is enforced. Until then, crates
+    /// that violate the rule are published with a warning.
+    enforced_at: Option<DateTime<Utc>>,
+}
+
+/// Add a rule to the publish policy.
+///
+/// The rule applies to all versions that are published afterwards, or from
+/// its `enforced_at` date on. Until then, crates that violate the rule are
+/// published with a warning.
+#[utoipa::path(
+    post,
+    path = "/api/private/admin/publish_policy_rules",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_publish_policy_rule(
+    app: AppState,
+    req: Parts,
+    Json(request): Json<CreateRuleRequest>,
+) -> AppResult<ErasedJson> {
+    let NewRule {
+        kind,
+        value,
+        message,
+        enforced_at,
+    } = request.rule;
+
+    let value = value.trim();
+    Rule::parse(kind, value).map_err(bad_request)?;
+
+    let message = message.as_deref().map(str::trim);
+    let message = message.filter(|message| !message.is_empty());
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let new_rule = NewPublishPolicyRule {
+        kind,
+        value,
+        message,
+        created_by: auth.user_id(),
+        enforced_at,
+    };
+
+    let rule = new_rule
+        .insert(&mut conn)
+        .await
+        .map_err(map_unique_violation)?;
+
+    let rule = load_rules(&mut conn, Some(rule.id)).await?.pop();
+    Ok(json!({ "rule": rule }))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateRuleRequest {
+    rule: UpdatedRule,
+}
+
+#[derive(Deserialize)]
+pub struct UpdatedRule {
+    value: String,
+    message: Option<String>,
+    enforced_at: Option<DateTime<Utc>>,
+}
+
+/// Change the value or message of a publish policy rule.
+#[utoipa::path(
+    patch,
+    path = "/api/private/admin/publish_policy_rules/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the rule"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_publish_policy_rule(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+    Json(request): Json<UpdateRuleRequest>,
+) -> AppResult<ErasedJson> {
+    let value = request.rule.value.trim();
+
+    let message = request.rule.message.as_deref().map(str::trim);
+    let message = message.filter(|message| !message.is_empty());
+
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let rule: PublishPolicyRule = publish_policy_rules::table
+        .find(id)
+        .select(PublishPolicyRule::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    Rule::parse(rule.kind, value).map_err(bad_request)?;
+
+    diesel::update(&rule)
+        .set((
+            publish_policy_rules::value.eq(value),
+            publish_policy_rules::message.eq(message),
+            publish_policy_rules::enforced_at.eq(request.rule.enforced_at),
+            publish_policy_rules::updated_at.eq(Utc::now()),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(map_unique_violation)?;
+
+    let rule = load_rules(&mut conn, Some(id)).await?.pop();
+    Ok(json!({ "rule": rule }))
+}
+
+/// Remove a rule from the publish policy.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/publish_policy_rules/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the rule"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_publish_policy_rule(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(publish_policy_rules::table.find(id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    ok_true()
+}
+
+fn map_unique_violation(error: Error) -> BoxedAppError {
+    match error {
+        Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            bad_request("the publish policy already has this rule")
+        }
+        error => error.into(),
+    }
+}
+
+async fn load_rules(
+    conn: &mut AsyncPgConnection,
+    id: Option<i32>,
+) -> QueryResult<Vec<EncodablePublishPolicyRule>> {
+    let mut query = publish_policy_rules::table
+        .left_join(users::table)
+        .select((PublishPolicyRule::as_select(), users::gh_login.nullable()))
+        .order(publish_policy_rules::id)
+        .into_boxed();
+
+    if let Some(id) = id {
+        query = query.filter(publish_policy_rules::id.eq(id));
+    }
+
+    let rules: Vec<(PublishPolicyRule, Option<String>)> = query.load(conn).await?;
+
+    Ok(rules
+        .into_iter()
+        .map(|(rule, created_by)| EncodablePublishPolicyRule::new(rule, created_by))
+        .collect())
+}
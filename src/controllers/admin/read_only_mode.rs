@@ -0,0 +1,86 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::models::FeatureFlag;
+use crate::schema::{features, users};
+use crate::util::errors::AppResult;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+/// Get the state of the read-only mode.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/read_only_mode",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_read_only_mode(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let flag: Option<(FeatureFlag, Option<String>)> = features::table
+        .left_join(users::table)
+        .filter(features::name.eq(FeatureFlag::READ_ONLY_MODE))
+        .select((FeatureFlag::as_select(), users::gh_login.nullable()))
+        .first(&mut conn)
+        .await
+        .optional()?;
+
+    Ok(match flag {
+        Some((flag, updated_by)) => encode(flag.enabled, updated_by, Some(flag.updated_at)),
+        None => encode(false, None, None),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct UpdateReadOnlyModeRequest {
+    enabled: bool,
+}
+
+/// Enable or disable the read-only mode.
+///
+/// While the read-only mode is enabled, all mutating API requests except for
+/// this one are rejected with `503 Service Unavailable`, and the background
+/// jobs are paused. The change applies to the other server instances within
+/// a few seconds.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/read_only_mode",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_read_only_mode(
+    app: AppState,
+    req: Parts,
+    Json(request): Json<UpdateReadOnlyModeRequest>,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let name = FeatureFlag::READ_ONLY_MODE;
+    let flag = FeatureFlag::set(&mut conn, name, request.enabled, Some(auth.user_id())).await?;
+    app.feature_flags.invalidate();
+
+    let updated_by = Some(auth.user().gh_login.clone());
+    Ok(encode(flag.enabled, updated_by, Some(flag.updated_at)))
+}
+
+fn encode(
+    enabled: bool,
+    updated_by: Option<String>,
+    updated_at: Option<DateTime<Utc>>,
+) -> ErasedJson {
+    json!({
+        "read_only_mode": {
+            "enabled": enabled,
+            "updated_by": updated_by,
+            "updated_at": updated_at,
+        },
+    })
+}
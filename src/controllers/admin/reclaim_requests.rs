@@ -0,0 +1,299 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::email::Email;
+use crate::models::{Crate, CrateReclaimRequest, NewCrateTransfer, Owner, ReclaimStatus, User};
+use crate::schema::{crate_reclaim_requests, crates, users};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::EncodableCrateReclaimRequest;
+use axum::extract::{Path, Query};
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use http::request::Parts;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// Only list reclaim requests with this status. By default, all pending
+    /// requests are listed.
+    #[param(value_type = Option<String>, example = "pending")]
+    status: Option<ReclaimStatus>,
+}
+
+/// List crate reclaim requests.
+///
+/// The requests are sorted from oldest to newest.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/reclaim_requests",
+    params(ListParams),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_reclaim_requests(
+    app: AppState,
+    Query(params): Query<ListParams>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let status = params.status.unwrap_or(ReclaimStatus::Pending);
+
+    let query = crate_reclaim_requests::table
+        .inner_join(crates::table)
+        .inner_join(users::table)
+        .filter(crate_reclaim_requests::status.eq(status))
+        .order(crate_reclaim_requests::id.asc())
+        .select((
+            CrateReclaimRequest::as_select(),
+            crates::name,
+            users::gh_login,
+        ))
+        .pages_pagination(PaginationOptions::builder().gather(&req)?);
+
+    let data: Paginated<(CrateReclaimRequest, String, String)> = query.load(&mut conn).await?;
+    let total = data.total();
+
+    let requests = data
+        .into_iter()
+        .map(|(request, crate_name, requester)| {
+            EncodableCrateReclaimRequest::new(request, &crate_name, &requester)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "reclaim_requests": requests,
+        "meta": { "total": total },
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct DecideReclaimRequest {
+    status: ReclaimStatus,
+    /// Explanation of the decision, which is sent to the requester.
+    decision: Option<String>,
+}
+
+/// Approve or reject a crate reclaim request.
+///
+/// Approving a request transfers the crate to the requester, which is only
+/// possible once the waiting period is over. The requester is notified by
+/// email about the decision, and the previous owners are notified if the
+/// crate has been transferred.
+#[utoipa::path(
+    patch,
+    path = "/api/private/admin/reclaim_requests/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the reclaim request"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn decide_reclaim_request(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+    Json(request): Json<DecideReclaimRequest>,
+) -> AppResult<ErasedJson> {
+    if request.status == ReclaimStatus::Pending {
+        return Err(bad_request(
+            "a reclaim request can only be approved or rejected",
+        ));
+    }
+
+    let decision = request.decision.as_deref().map(str::trim);
+    let Some(decision) = decision.filter(|decision| !decision.is_empty()) else {
+        return Err(bad_request("a decision is required"));
+    };
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+    let admin_id = auth.user_id();
+
+    let reclaim: CrateReclaimRequest = crate_reclaim_requests::table
+        .find(id)
+        .select(CrateReclaimRequest::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    if reclaim.status != ReclaimStatus::Pending {
+        return Err(bad_request("the reclaim request has already been decided"));
+    }
+
+    let approved = request.status == ReclaimStatus::Approved;
+    if approved && reclaim.eligible_at() > Utc::now() {
+        let eligible_at = reclaim.eligible_at().format("%Y-%m-%d");
+        let detail = format!("the reclaim request can not be approved before {eligible_at}");
+        return Err(bad_request(detail));
+    }
+
+    let krate: Crate = crates::table
+        .find(reclaim.crate_id)
+        .select(Crate::as_select())
+        .first(&mut conn)
+        .await?;
+
+    let requester = User::find(&mut conn, reclaim.requester_id).await?;
+
+    // The owners need to be loaded before the transfer removes them.
+    let previous_owners = match approved {
+        true => User::owning(&krate, &mut conn).await?,
+        false => vec![],
+    };
+
+    let status = request.status;
+    let transfer = NewCrateTransfer {
+        crate_id: krate.id,
+        requested_by: admin_id,
+        recipient_id: requester.id,
+    };
+
+    let reclaim: CrateReclaimRequest = conn
+        .transaction(|conn| {
+            let reclaim = &reclaim;
+            let transfer = &transfer;
+            async move {
+                if approved {
+                    transfer.insert(conn).await?.accept(conn).await?;
+                }
+
+                diesel::update(reclaim)
+                    .set((
+                        crate_reclaim_requests::status.eq(status),
+                        crate_reclaim_requests::decision.eq(decision),
+                        crate_reclaim_requests::decided_by.eq(admin_id),
+                        crate_reclaim_requests::decided_at.eq(Utc::now()),
+                    ))
+                    .returning(CrateReclaimRequest::as_returning())
+                    .get_result(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let email = CrateReclaimDecisionEmail {
+        krate: &krate.name,
+        approved,
+        decision,
+    };
+
+    let email_future = async {
+        if let Some(recipient) = requester.verified_email(&mut conn).await? {
+            app.emails.send(&recipient, email).await?
+        }
+
+        Ok::<_, anyhow::Error>(())
+    };
+
+    if let Err(err) = email_future.await {
+        warn!("Failed to send crate reclaim decision email: {err}");
+    }
+
+    for owner in previous_owners {
+        let Owner::User(owner, _) = owner else {
+            continue;
+        };
+
+        let email = CrateReclaimedEmail {
+            krate: &krate.name,
+            requester: &requester.gh_login,
+            decision,
+        };
+
+        let email_future = async {
+            if let Some(recipient) = owner.verified_email(&mut conn).await? {
+                app.emails.send(&recipient, email).await?
+            }
+
+            Ok::<_, anyhow::Error>(())
+        };
+
+        if let Err(err) = email_future.await {
+            warn!("Failed to send crate reclaimed email: {err}");
+        }
+    }
+
+    let reclaim = EncodableCrateReclaimRequest::new(reclaim, &krate.name, &requester.gh_login);
+    Ok(json!({ "reclaim_request": reclaim }))
+}
+
+/// Email template for notifying a requester about the decision on their
+/// reclaim request.
+#[derive(Debug, Clone)]
+struct CrateReclaimDecisionEmail<'a> {
+    krate: &'a str,
+    approved: bool,
+    decision: &'a str,
+}
+
+impl Email for CrateReclaimDecisionEmail<'_> {
+    fn subject(&self) -> String {
+        format!(
+            "crates.io: Your request to reclaim the \"{}\" crate",
+            self.krate
+        )
+    }
+
+    fn body(&self) -> String {
+        let outcome = match self.approved {
+            true => "approved it. You are now the only owner of the crate",
+            false => "rejected it",
+        };
+
+        format!(
+            "Hello,
+
+the crates.io team has reviewed your request to reclaim the \"{}\" crate and {outcome}:
+
+{}
+
+If you have any questions, please contact us at help@crates.io.",
+            self.krate, self.decision
+        )
+    }
+}
+
+/// Email template for notifying the previous owners of a crate that it has
+/// been transferred to the requester of a reclaim request.
+#[derive(Debug, Clone)]
+struct CrateReclaimedEmail<'a> {
+    krate: &'a str,
+    requester: &'a str,
+    decision: &'a str,
+}
+
+impl Email for CrateReclaimedEmail<'_> {
+    fn subject(&self) -> String {
+        format!(
+            "crates.io: The \"{}\" crate has been transferred",
+            self.krate
+        )
+    }
+
+    fn body(&self) -> String {
+        format!(
+            "Hello,
+
+the crates.io team has transferred your crate \"{krate}\" to {requester}, who requested it because it appeared to be unused:
+
+{decision}
+
+If you have any questions, please contact us at help@crates.io.",
+            krate = self.krate,
+            requester = self.requester,
+            decision = self.decision,
+        )
+    }
+}
@@ -0,0 +1,218 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::email::Email;
+use crate::models::{CrateRedirect, RedirectStatus, User};
+use crate::schema::{crate_redirects, crates, users};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::EncodableCrateRedirect;
+use axum::extract::{Path, Query};
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// Only list redirect requests with this status. By default, all pending
+    /// requests are listed.
+    #[param(value_type = Option<String>, example = "pending")]
+    status: Option<RedirectStatus>,
+}
+
+/// List crate redirect requests.
+///
+/// The requests are sorted from oldest to newest.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/redirect_requests",
+    params(ListParams),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_redirect_requests(
+    app: AppState,
+    Query(params): Query<ListParams>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let status = params.status.unwrap_or(RedirectStatus::Pending);
+
+    let query = crate_redirects::table
+        .inner_join(crates::table)
+        .left_join(users::table)
+        .filter(crate_redirects::status.eq(status))
+        .order(crate_redirects::id.asc())
+        .select((
+            CrateRedirect::as_select(),
+            crates::name,
+            users::gh_login.nullable(),
+        ))
+        .pages_pagination(PaginationOptions::builder().gather(&req)?);
+
+    let data: Paginated<(CrateRedirect, String, Option<String>)> = query.load(&mut conn).await?;
+    let total = data.total();
+
+    let requests = data
+        .into_iter()
+        .map(|(redirect, target, requested_by)| {
+            EncodableCrateRedirect::new(redirect, &target, requested_by)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "redirect_requests": requests,
+        "meta": { "total": total },
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct DecideRedirectRequest {
+    status: RedirectStatus,
+    /// Explanation of the decision, which is sent to the requester.
+    decision: Option<String>,
+}
+
+/// Approve or reject a crate redirect request.
+///
+/// Once approved, the metadata endpoints of the renamed crate redirect to
+/// its successor, and the name can't be registered again. The requester is
+/// notified by email about the decision.
+#[utoipa::path(
+    patch,
+    path = "/api/private/admin/redirect_requests/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the redirect request"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn decide_redirect_request(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+    Json(request): Json<DecideRedirectRequest>,
+) -> AppResult<ErasedJson> {
+    if request.status == RedirectStatus::Pending {
+        return Err(bad_request(
+            "a redirect request can only be approved or rejected",
+        ));
+    }
+
+    let decision = request.decision.as_deref().map(str::trim);
+    let Some(decision) = decision.filter(|decision| !decision.is_empty()) else {
+        return Err(bad_request("a decision is required"));
+    };
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+    let admin_id = auth.user_id();
+
+    let (redirect, target): (CrateRedirect, String) = crate_redirects::table
+        .inner_join(crates::table)
+        .filter(crate_redirects::id.eq(id))
+        .select((CrateRedirect::as_select(), crates::name))
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    if redirect.status != RedirectStatus::Pending {
+        return Err(bad_request("the redirect request has already been decided"));
+    }
+
+    let approved = request.status == RedirectStatus::Approved;
+    if approved {
+        if let Some(successor) = CrateRedirect::target_of(&mut conn, &target).await? {
+            let detail = format!("the `{target}` crate is already redirected to `{successor}`");
+            return Err(bad_request(detail));
+        }
+    }
+
+    let redirect: CrateRedirect = diesel::update(&redirect)
+        .set((
+            crate_redirects::status.eq(request.status),
+            crate_redirects::decision.eq(decision),
+            crate_redirects::decided_by.eq(admin_id),
+            crate_redirects::decided_at.eq(Utc::now()),
+        ))
+        .returning(CrateRedirect::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    let requester = match redirect.requested_by {
+        Some(user_id) => Some(User::find(&mut conn, user_id).await?),
+        None => None,
+    };
+
+    if let Some(requester) = &requester {
+        let email = CrateRedirectDecisionEmail {
+            krate: &redirect.name,
+            target: &target,
+            approved,
+            decision,
+        };
+
+        let email_future = async {
+            if let Some(recipient) = requester.verified_email(&mut conn).await? {
+                app.emails.send(&recipient, email).await?
+            }
+
+            Ok::<_, anyhow::Error>(())
+        };
+
+        if let Err(err) = email_future.await {
+            warn!("Failed to send crate redirect decision email: {err}");
+        }
+    }
+
+    let requested_by = requester.map(|requester| requester.gh_login);
+    let redirect = EncodableCrateRedirect::new(redirect, &target, requested_by);
+    Ok(json!({ "redirect_request": redirect }))
+}
+
+/// Email template for notifying a requester about the decision on their
+/// redirect request.
+#[derive(Debug, Clone)]
+struct CrateRedirectDecisionEmail<'a> {
+    krate: &'a str,
+    target: &'a str,
+    approved: bool,
+    decision: &'a str,
+}
+
+impl Email for CrateRedirectDecisionEmail<'_> {
+    fn subject(&self) -> String {
+        format!(
+            "crates.io: Your request to redirect the \"{}\" crate",
+            self.krate
+        )
+    }
+
+    fn body(&self) -> String {
+        let outcome = match self.approved {
+            true => "approved it. Requests for the crate are now redirected to the new crate",
+            false => "rejected it",
+        };
+
+        format!(
+            "Hello,
+
+the crates.io team has reviewed your request to redirect the \"{}\" crate to \"{}\" and {outcome}:
+
+{}
+
+If you have any questions, please contact us at help@crates.io.",
+            self.krate, self.target, self.decision
+        )
+    }
+}
@@ -0,0 +1,227 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::email::Email;
+use crate::models::{CrateReport, ReportStatus, User};
+use crate::schema::{crate_reports, crates, users};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::EncodableCrateReport;
+use axum::extract::{Path, Query};
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// Only list reports with this status. By default, all reports that have
+    /// not been resolved or dismissed yet are listed.
+    #[param(value_type = Option<String>, example = "open")]
+    status: Option<ReportStatus>,
+}
+
+/// List crate reports in the triage queue.
+///
+/// Reports with a higher severity score from the automated malware scan are
+/// listed first, otherwise the reports are sorted from oldest to newest.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/reports",
+    params(ListParams),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_reports(
+    app: AppState,
+    Query(params): Query<ListParams>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let statuses = match params.status {
+        Some(status) => vec![status],
+        None => vec![ReportStatus::Open, ReportStatus::Triaged],
+    };
+
+    let query = crate_reports::table
+        .inner_join(crates::table)
+        .left_join(users::table)
+        .filter(crate_reports::status.eq_any(statuses))
+        .order((
+            crate_reports::score.desc().nulls_last(),
+            crate_reports::id.asc(),
+        ))
+        .select((
+            crate_reports::all_columns,
+            crates::name,
+            users::gh_login.nullable(),
+        ))
+        .pages_pagination(PaginationOptions::builder().gather(&req)?);
+
+    let data: Paginated<(CrateReport, String, Option<String>)> = query.load(&mut conn).await?;
+    let total = data.total();
+
+    let reports = data
+        .into_iter()
+        .map(|(report, crate_name, reporter)| {
+            EncodableCrateReport::new(report, &crate_name, reporter.as_deref())
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "reports": reports,
+        "meta": { "total": total },
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateReportRequest {
+    status: ReportStatus,
+    /// Explanation of the outcome, which is sent to the reporter. Required
+    /// when resolving or dismissing a report.
+    resolution: Option<String>,
+}
+
+/// Triage, resolve or dismiss a crate report.
+///
+/// The reporter is notified by email once the report has been resolved or
+/// dismissed.
+#[utoipa::path(
+    patch,
+    path = "/api/private/admin/reports/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the report"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_report(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+    Json(request): Json<UpdateReportRequest>,
+) -> AppResult<ErasedJson> {
+    let resolution = request.resolution.as_deref().map(str::trim);
+    let resolution = resolution.filter(|resolution| !resolution.is_empty());
+
+    let closing = request.status.is_closed();
+    if closing && resolution.is_none() {
+        return Err(bad_request(
+            "a resolution is required to resolve or dismiss a report",
+        ));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let report: CrateReport = crate_reports::table
+        .find(id)
+        .select(CrateReport::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    if report.status.is_closed() {
+        return Err(bad_request("the report has already been closed"));
+    }
+
+    let (resolved_by, resolved_at) = match closing {
+        true => (Some(auth.user_id()), Some(Utc::now())),
+        false => (None, None),
+    };
+
+    let report: CrateReport = diesel::update(&report)
+        .set((
+            crate_reports::status.eq(request.status),
+            crate_reports::resolution.eq(resolution),
+            crate_reports::resolved_by.eq(resolved_by),
+            crate_reports::resolved_at.eq(resolved_at),
+        ))
+        .returning(CrateReport::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    let crate_name: String = crates::table
+        .find(report.crate_id)
+        .select(crates::name)
+        .first(&mut conn)
+        .await?;
+
+    let reporter = match report.reporter_id {
+        Some(reporter_id) => User::find(&mut conn, reporter_id).await.optional()?,
+        None => None,
+    };
+
+    if let (true, Some(resolution)) = (closing, &report.resolution) {
+        let email = CrateReportClosedEmail {
+            krate: &crate_name,
+            status: report.status,
+            resolution,
+        };
+
+        let email_future = async {
+            let recipient = match (&report.contact, &reporter) {
+                (Some(contact), _) => Some(contact.clone()),
+                (None, Some(reporter)) => reporter.verified_email(&mut conn).await?,
+                (None, None) => None,
+            };
+
+            if let Some(recipient) = recipient {
+                app.emails.send(&recipient, email).await?
+            }
+
+            Ok::<_, anyhow::Error>(())
+        };
+
+        if let Err(err) = email_future.await {
+            warn!("Failed to send crate report resolution email: {err}");
+        }
+    }
+
+    let reporter = reporter.as_ref().map(|reporter| reporter.gh_login.as_str());
+    let report = EncodableCrateReport::new(report, &crate_name, reporter);
+
+    Ok(json!({ "report": report }))
+}
+
+/// Email template for notifying a reporter that their report has been
+/// resolved or dismissed.
+#[derive(Debug, Clone)]
+struct CrateReportClosedEmail<'a> {
+    krate: &'a str,
+    status: ReportStatus,
+    resolution: &'a str,
+}
+
+impl Email for CrateReportClosedEmail<'_> {
+    fn subject(&self) -> String {
+        format!("crates.io: Your report of the \"{}\" crate", self.krate)
+    }
+
+    fn body(&self) -> String {
+        let outcome = match self.status {
+            ReportStatus::Dismissed => "dismissed",
+            _ => "resolved",
+        };
+
+        format!(
+            "Hello,
+
+thank you for reporting the \"{}\" crate. The crates.io team has reviewed your report and {outcome} it:
+
+{}
+
+If you have any questions, please contact us at help@crates.io.",
+            self.krate, self.resolution
+        )
+    }
+}
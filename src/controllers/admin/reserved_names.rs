@@ -0,0 +1,139 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::{Crate, NewReservedCrateName, ReservedCrateName};
+use crate::schema::{reserved_crate_names, users};
+use crate::util::errors::{bad_request, not_found, AppResult};
+use crate::views::EncodableReservedCrateName;
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use crates_io_diesel_helpers::canon_crate_name;
+use diesel::dsl::{exists, select};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+/// List all reserved crate names.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/reserved_crate_names",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_reserved_crate_names(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let reserved_names = reserved_crate_names::table
+        .left_join(users::table)
+        .select((ReservedCrateName::as_select(), users::gh_login.nullable()))
+        .order(reserved_crate_names::name)
+        .load::<(ReservedCrateName, Option<String>)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(reserved, created_by)| EncodableReservedCrateName::new(reserved, created_by))
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "reserved_crate_names": reserved_names }))
+}
+
+#[derive(Deserialize)]
+pub struct ReserveCrateNameRequest {
+    /// Why the name is reserved, for the crates.io team.
+    reason: Option<String>,
+}
+
+/// Reserve a crate name, so that it can not be used for new crates.
+///
+/// The change applies to all server instances within a minute. Names of
+/// existing crates can not be reserved.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/reserved_crate_names/{name}",
+    params(
+        ("name" = String, Path, description = "The crate name to reserve"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn reserve_crate_name(
+    app: AppState,
+    Path(name): Path<String>,
+    req: Parts,
+    Json(request): Json<ReserveCrateNameRequest>,
+) -> AppResult<ErasedJson> {
+    Crate::validate_crate_name("crate", &name).map_err(bad_request)?;
+
+    let reason = request.reason.as_deref().map(str::trim);
+    let reason = reason.filter(|reason| !reason.is_empty());
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    if let Some(reserved) = ReservedCrateName::find_by_name(&mut conn, &name).await? {
+        let detail = format!("the crate name `{}` is already reserved", reserved.name);
+        return Err(bad_request(detail));
+    }
+
+    let crate_exists = select(exists(Crate::by_name(&name)))
+        .get_result(&mut conn)
+        .await?;
+
+    if crate_exists {
+        let detail = format!("a crate with the name `{name}` already exists");
+        return Err(bad_request(detail));
+    }
+
+    let new_reserved = NewReservedCrateName {
+        name: &name,
+        reason,
+        created_by: auth.user_id(),
+    };
+
+    let reserved = new_reserved.insert(&mut conn).await?;
+
+    app.reserved_names.invalidate();
+
+    let created_by = Some(auth.user().gh_login.clone());
+    let reserved = EncodableReservedCrateName::new(reserved, created_by);
+    Ok(json!({ "reserved_crate_name": reserved }))
+}
+
+/// Remove the reservation of a crate name, so that it can be used for new
+/// crates again.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/reserved_crate_names/{name}",
+    params(
+        ("name" = String, Path, description = "The reserved crate name"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_reserved_crate_name(
+    app: AppState,
+    Path(name): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(reserved_crate_names::table)
+        .filter(canon_crate_name(reserved_crate_names::name).eq(canon_crate_name(&name)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    app.reserved_names.invalidate();
+
+    ok_true()
+}
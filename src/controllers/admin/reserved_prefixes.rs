@@ -0,0 +1,131 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::ok_true;
+use crate::models::ReservedPrefix;
+use crate::schema::{organizations, reserved_prefixes};
+use crate::util::errors::{not_found, AppResult};
+use crate::views::EncodableReservedPrefix;
+use axum::extract::Path;
+use axum::response::Response;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+/// List all crate name prefix reservations of organizations.
+///
+/// The reservations that are waiting for approval are listed first, and
+/// are sorted from oldest to newest request.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/reserved_prefixes",
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_reserved_prefix_requests(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let reserved_prefixes = reserved_prefixes::table
+        .inner_join(organizations::table)
+        .select((ReservedPrefix::as_select(), organizations::login))
+        .order((
+            reserved_prefixes::approved_at.desc().nulls_first(),
+            reserved_prefixes::created_at,
+        ))
+        .load::<(ReservedPrefix, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(reserved, login)| EncodableReservedPrefix::new(reserved, login))
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "reserved_prefixes": reserved_prefixes }))
+}
+
+/// Approve a crate name prefix reservation.
+///
+/// From then on, only members of the organization with at least the
+/// publisher role can publish new crates with the prefix.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/reserved_prefixes/{id}/approve",
+    params(
+        ("id" = i32, Path, description = "ID of the reservation"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn approve_reserved_prefix(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let reserved = diesel::update(reserved_prefixes::table.find(id))
+        .filter(reserved_prefixes::approved_at.is_null())
+        .set((
+            reserved_prefixes::approved_at.eq(Utc::now()),
+            reserved_prefixes::approved_by.eq(auth.user_id()),
+        ))
+        .returning(ReservedPrefix::as_returning())
+        .get_result(&mut conn)
+        .await
+        .optional()?;
+
+    // Approving a reservation twice doesn't change who approved it and when.
+    let reserved = match reserved {
+        Some(reserved) => reserved,
+        None => reserved_prefixes::table
+            .find(id)
+            .select(ReservedPrefix::as_select())
+            .first(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(not_found)?,
+    };
+
+    let login = organizations::table
+        .find(reserved.organization_id)
+        .select(organizations::login)
+        .first(&mut conn)
+        .await?;
+
+    let reserved = EncodableReservedPrefix::new(reserved, login);
+    Ok(json!({ "reserved_prefix": reserved }))
+}
+
+/// Reject a pending crate name prefix reservation, or revoke an approved one.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/reserved_prefixes/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the reservation"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_reserved_prefix(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(reserved_prefixes::table.find(id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    ok_true()
+}
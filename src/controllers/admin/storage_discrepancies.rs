@@ -0,0 +1,68 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::models::{StorageDiscrepancy, StorageDiscrepancyKind};
+use crate::schema::storage_discrepancies;
+use crate::util::errors::AppResult;
+use crate::views::EncodableStorageDiscrepancy;
+use axum::extract::Query;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// Only list discrepancies of this kind.
+    #[param(value_type = Option<String>, example = "orphaned_crate_file")]
+    kind: Option<StorageDiscrepancyKind>,
+}
+
+/// List the files in the storage that don't match the database.
+///
+/// The discrepancies are found by the `reconcile_storage` background job.
+/// Orphaned files are deleted by the job once their quarantine period has
+/// passed, while missing files have to be restored manually.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/storage_discrepancies",
+    params(ListParams),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_storage_discrepancies(
+    app: AppState,
+    Query(params): Query<ListParams>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let mut query = storage_discrepancies::table
+        .select(StorageDiscrepancy::as_select())
+        .order((storage_discrepancies::kind, storage_discrepancies::path))
+        .into_boxed();
+
+    if let Some(kind) = params.kind {
+        query = query.filter(storage_discrepancies::kind.eq(kind));
+    }
+
+    let query = query.pages_pagination(PaginationOptions::builder().gather(&req)?);
+    let data: Paginated<StorageDiscrepancy> = query.load(&mut conn).await?;
+    let total = data.total();
+
+    let quarantine = app.config.storage_orphan_quarantine;
+    let discrepancies = data
+        .into_iter()
+        .map(|discrepancy| EncodableStorageDiscrepancy::new(discrepancy, quarantine))
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "storage_discrepancies": discrepancies,
+        "meta": { "total": total },
+    }))
+}
@@ -0,0 +1,382 @@
+use crate::app::AppState;
+use crate::controllers::admin::authenticate_admin;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::models::{AuditLogAction, Email, NewAuditLogEntry, PublishRateOverride, User};
+use crate::rate_limiter::LimitedAction;
+use crate::schema::{emails, publish_rate_overrides, users};
+use crate::util::errors::{bad_request, AppResult, BoxedAppError};
+use crate::util::rfc3339;
+use crate::views::EncodableAdminUser;
+use axum::extract::{Path, Query};
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::NaiveDateTime;
+use crates_io_diesel_helpers::lower;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    /// Part of the login or of an email address of the users to find.
+    q: String,
+}
+
+/// Search users by login or email address.
+///
+/// The search is case-insensitive and matches substrings of the login and of
+/// all email addresses of a user.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/users",
+    params(SearchParams),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn search_users(
+    app: AppState,
+    Query(params): Query<SearchParams>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let q = params.q.trim();
+    if q.is_empty() {
+        return Err(bad_request("missing search query"));
+    }
+
+    let pattern = format!("%{}%", escape_like(&q.to_lowercase()));
+    let matching_email = emails::table
+        .filter(emails::user_id.eq(users::id))
+        .filter(lower(emails::email).like(pattern.clone()));
+
+    let query = users::table
+        .filter(
+            lower(users::gh_login)
+                .like(pattern.clone())
+                .or(exists(matching_email)),
+        )
+        .order((users::gh_login.asc(), users::id.asc()))
+        .select(User::as_select())
+        .pages_pagination(PaginationOptions::builder().gather(&req)?);
+
+    let data: Paginated<User> = query.load(&mut conn).await?;
+    let total = data.total();
+    let users = data.into_iter().collect::<Vec<_>>();
+
+    let emails = Email::belonging_to(&users)
+        .order(emails::id)
+        .load::<Email>(&mut conn)
+        .await?
+        .grouped_by(&users);
+
+    let overrides = PublishRateOverride::belonging_to(&users)
+        .select(PublishRateOverride::as_select())
+        .load(&mut conn)
+        .await?
+        .grouped_by(&users);
+
+    let users = users
+        .into_iter()
+        .zip(emails)
+        .zip(overrides)
+        .map(|((user, emails), overrides)| EncodableAdminUser::from(user, emails, overrides))
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "users": users,
+        "meta": { "total": total },
+    }))
+}
+
+/// Get the details of a user.
+#[utoipa::path(
+    get,
+    path = "/api/private/admin/users/{user}",
+    params(
+        ("user" = String, Path, description = "Login of the user"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn find_user(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let user = User::find_by_login(&mut conn, &login).await?;
+    let user = encode_user(&mut conn, user).await?;
+
+    Ok(json!({ "user": user }))
+}
+
+#[derive(Deserialize)]
+pub struct LockRequest {
+    /// The reason for the lock, which is shown to the user.
+    reason: String,
+    /// Time at which the lock expires. The lock never expires if omitted.
+    #[serde(default, with = "rfc3339::option")]
+    until: Option<NaiveDateTime>,
+}
+
+/// Lock the account of a user.
+///
+/// Locked users can not log in or use their API tokens. The reason is shown
+/// to them whenever they try to.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/users/{user}/lock",
+    params(
+        ("user" = String, Path, description = "Login of the user"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn lock_user(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+    Json(lock): Json<LockRequest>,
+) -> AppResult<ErasedJson> {
+    let reason = lock.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("a reason is required to lock an account"));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let user = User::find_by_login(&mut conn, &login).await?;
+    if user.id == auth.user_id() {
+        return Err(bad_request("you can not lock your own account"));
+    }
+
+    let user = conn
+        .transaction(|conn| {
+            async move {
+                let user: User = diesel::update(&user)
+                    .set((
+                        users::account_lock_reason.eq(reason),
+                        users::account_lock_until.eq(lock.until),
+                    ))
+                    .returning(User::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                let details = serde_json::json!({
+                    "user": user.gh_login,
+                    "reason": reason,
+                    "until": lock.until.map(|until| until.and_utc()),
+                });
+
+                NewAuditLogEntry::builder()
+                    .user_id(auth.user_id())
+                    .action(AuditLogAction::AccountLock)
+                    .details(details)
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, BoxedAppError>(user)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let user = encode_user(&mut conn, user).await?;
+
+    Ok(json!({ "user": user }))
+}
+
+/// Unlock the account of a user.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/users/{user}/lock",
+    params(
+        ("user" = String, Path, description = "Login of the user"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn unlock_user(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let user = User::find_by_login(&mut conn, &login).await?;
+
+    let user = conn
+        .transaction(|conn| {
+            async move {
+                let user: User = diesel::update(&user)
+                    .set((
+                        users::account_lock_reason.eq(None::<String>),
+                        users::account_lock_until.eq(None::<NaiveDateTime>),
+                    ))
+                    .returning(User::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                NewAuditLogEntry::builder()
+                    .user_id(auth.user_id())
+                    .action(AuditLogAction::AccountUnlock)
+                    .details(serde_json::json!({ "user": user.gh_login }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, BoxedAppError>(user)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let user = encode_user(&mut conn, user).await?;
+
+    Ok(json!({ "user": user }))
+}
+
+#[derive(Deserialize)]
+pub struct RateLimitOverrideRequest {
+    action: LimitedAction,
+    /// The number of actions the user may perform in a burst.
+    burst: i32,
+    /// Time at which the override expires. The override never expires if omitted.
+    #[serde(default, with = "rfc3339::option")]
+    expires_at: Option<NaiveDateTime>,
+}
+
+/// Override the publish rate limit of a user.
+///
+/// Replaces any existing override for the same action.
+#[utoipa::path(
+    put,
+    path = "/api/private/admin/users/{user}/rate_limits",
+    params(
+        ("user" = String, Path, description = "Login of the user"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn override_rate_limit(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+    Json(request): Json<RateLimitOverrideRequest>,
+) -> AppResult<ErasedJson> {
+    if request.burst < 1 {
+        return Err(bad_request("the burst must be a positive number"));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = authenticate_admin(&req, &mut conn).await?;
+
+    let user = User::find_by_login(&mut conn, &login).await?;
+
+    let rate_override = PublishRateOverride {
+        user_id: user.id,
+        burst: request.burst,
+        expires_at: request.expires_at,
+        action: request.action,
+    };
+
+    let user_login = &user.gh_login;
+    conn.transaction(|conn| {
+        async move {
+            rate_override.upsert(conn).await?;
+
+            let details = serde_json::json!({
+                "user": user_login,
+                "action": rate_override.action,
+                "burst": rate_override.burst,
+                "expires_at": rate_override.expires_at.map(|expires_at| expires_at.and_utc()),
+            });
+
+            NewAuditLogEntry::builder()
+                .user_id(auth.user_id())
+                .action(AuditLogAction::RateLimitOverride)
+                .details(details)
+                .build()
+                .insert(conn)
+                .await?;
+
+            Ok::<_, BoxedAppError>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    let user = encode_user(&mut conn, user).await?;
+
+    Ok(json!({ "user": user }))
+}
+
+/// Remove a publish rate limit override of a user.
+#[utoipa::path(
+    delete,
+    path = "/api/private/admin/users/{user}/rate_limits/{action}",
+    params(
+        ("user" = String, Path, description = "Login of the user"),
+        ("action" = String, Path, description = "The rate limited action, e.g. `publish_new`"),
+    ),
+    security(("cookie" = [])),
+    tag = "admin",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn remove_rate_limit_override(
+    app: AppState,
+    Path((login, action)): Path<(String, LimitedAction)>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    authenticate_admin(&req, &mut conn).await?;
+
+    let user = User::find_by_login(&mut conn, &login).await?;
+
+    diesel::delete(publish_rate_overrides::table.find((user.id, action)))
+        .execute(&mut conn)
+        .await?;
+
+    let user = encode_user(&mut conn, user).await?;
+
+    Ok(json!({ "user": user }))
+}
+
+async fn encode_user(conn: &mut AsyncPgConnection, user: User) -> QueryResult<EncodableAdminUser> {
+    let emails = Email::belonging_to(&user)
+        .order(emails::id)
+        .load(conn)
+        .await?;
+
+    let overrides = PublishRateOverride::belonging_to(&user)
+        .select(PublishRateOverride::as_select())
+        .load(conn)
+        .await?;
+
+    Ok(EncodableAdminUser::from(user, emails, overrides))
+}
+
+/// Escapes the special characters of a `LIKE` pattern.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -0,0 +1,41 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::models::Announcement;
+use crate::util::errors::AppResult;
+use crate::views::EncodableAnnouncement;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use http::request::Parts;
+
+/// List the current announcements.
+///
+/// Returns the announcements that the crates.io website shows in a banner,
+/// sorted by severity and then from newest to oldest. Authenticated users
+/// additionally get the announcements that are targeted at crate owners or
+/// API token users, if they belong to these groups.
+#[utoipa::path(
+    get,
+    path = "/api/v1/announcements",
+    security(
+        (),
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "other",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_announcements(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+
+    // Anonymous visitors only get the announcements for everyone.
+    let auth = AuthCheck::default().check(&req, &mut conn).await.ok();
+    let user_id = auth.map(|auth| auth.user_id());
+
+    let announcements = Announcement::active_for(&mut conn, user_id)
+        .await?
+        .into_iter()
+        .map(EncodableAnnouncement::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "announcements": announcements }))
+}
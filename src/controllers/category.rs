@@ -1,14 +1,20 @@
 use super::helpers::pagination::*;
+use super::helpers::LIST_CACHE_CONTROL;
 use crate::app::AppState;
-use crate::models::Category;
+use crate::models::{AggregateDownload, Category};
 use crate::schema::categories;
 use crate::util::errors::AppResult;
-use crate::views::{EncodableCategory, EncodableCategoryWithSubcategories};
+use crate::views::{
+    EncodableAggregateDownload, EncodableCategory, EncodableCategoryWithSubcategories,
+};
 use axum::extract::{FromRequestParts, Path, Query};
+use axum::response::{IntoResponse, Response};
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
+use chrono::{Duration, Utc};
 use diesel::QueryDsl;
 use diesel_async::RunQueryDsl;
+use http::header;
 use http::request::Parts;
 
 #[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
@@ -35,7 +41,7 @@ pub async fn list_categories(
     app: AppState,
     params: ListQueryParams,
     req: Parts,
-) -> AppResult<ErasedJson> {
+) -> AppResult<Response> {
     // FIXME: There are 69 categories, 47 top level. This isn't going to
     // grow by an OoM. We need a limit for /summary, but we don't need
     // to paginate this.
@@ -56,10 +62,13 @@ pub async fn list_categories(
     // Query for the total count of categories
     let total = Category::count_toplevel(&mut conn).await?;
 
-    Ok(json!({
+    let json = json!({
         "categories": categories,
         "meta": { "total": total },
-    }))
+    });
+
+    let headers = [(header::CACHE_CONTROL, LIST_CACHE_CONTROL)];
+    Ok((headers, json).into_response())
 }
 
 /// Get category metadata.
@@ -104,6 +113,39 @@ pub async fn find_category(state: AppState, Path(slug): Path<String>) -> AppResu
     Ok(json!({ "category": cat_with_subcats }))
 }
 
+/// Get the download counts for a category.
+///
+/// This includes the per-day downloads of all crates in the category for
+/// the last 90 days. Crates that are only in a subcategory are not included.
+/// The downloads of a day are aggregated once the day is over, so the
+/// current day is not included.
+#[utoipa::path(
+    get,
+    path = "/api/v1/categories/{category}/downloads",
+    params(
+        ("category" = String, Path, description = "Name of the category"),
+    ),
+    tag = "categories",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_category_downloads(
+    state: AppState,
+    Path(slug): Path<String>,
+) -> AppResult<ErasedJson> {
+    let mut conn = state.db_read().await?;
+
+    let cat: Category = Category::by_slug(&slug).first(&mut conn).await?;
+
+    let start_date = Utc::now().date_naive() - Duration::days(89);
+    let downloads = AggregateDownload::for_category(&mut conn, cat.id, start_date)
+        .await?
+        .into_iter()
+        .map(AggregateDownload::into)
+        .collect::<Vec<EncodableAggregateDownload>>();
+
+    Ok(json!({ "downloads": downloads }))
+}
+
 /// List all available category slugs.
 #[utoipa::path(
     get,
@@ -111,7 +153,7 @@ pub async fn find_category(state: AppState, Path(slug): Path<String>) -> AppResu
     tag = "categories",
     responses((status = 200, description = "Successful Response")),
 )]
-pub async fn list_category_slugs(state: AppState) -> AppResult<ErasedJson> {
+pub async fn list_category_slugs(state: AppState) -> AppResult<Response> {
     let mut conn = state.db_read().await?;
 
     let slugs: Vec<Slug> = categories::table
@@ -127,5 +169,8 @@ pub async fn list_category_slugs(state: AppState) -> AppResult<ErasedJson> {
         description: String,
     }
 
-    Ok(json!({ "category_slugs": slugs }))
+    let json = json!({ "category_slugs": slugs });
+
+    let headers = [(header::CACHE_CONTROL, LIST_CACHE_CONTROL)];
+    Ok((headers, json).into_response())
 }
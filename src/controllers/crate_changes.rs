@@ -0,0 +1,80 @@
+use crate::app::AppState;
+use crate::models::CrateChange;
+use crate::schema::crate_changes;
+use crate::util::errors::{custom, forbidden, AppResult};
+use crate::util::token::constant_time_eq;
+use crate::views::EncodableCrateChange;
+use axum::extract::Query;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+use http::{header, StatusCode};
+use secrecy::ExposeSecret;
+
+/// The maximum number of changes per response.
+const MAX_CHANGES: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct ListQueryParams {
+    /// Only changes with a higher sequence number are returned.
+    #[serde(default)]
+    since_seq: i64,
+}
+
+/// Handles the `GET /api/private/crate_changes` endpoint.
+///
+/// Returns the creations, deletions, yanks and unyanks of crates and
+/// versions, and the soft-deletions and restores of versions, in the order of their sequence numbers, so that trusted services
+/// like docs.rs and crater can follow the changes of the registry without
+/// polling the index. Consumers pass the `meta.next_since_seq` value of the
+/// previous response to get the next changes; an empty list means that they
+/// have seen all changes.
+pub async fn list_crate_changes(
+    app: AppState,
+    Query(params): Query<ListQueryParams>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    if app.config.crate_changes_tokens.is_empty() {
+        let detail = "the crate change feed is disabled on this crates.io instance";
+        return Err(custom(StatusCode::NOT_FOUND, detail));
+    }
+
+    let provided_token = req
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let is_trusted = provided_token.is_some_and(|provided| {
+        let tokens = &app.config.crate_changes_tokens;
+        let provided = provided.as_bytes();
+        tokens
+            .iter()
+            .any(|token| constant_time_eq(token.expose_secret().as_bytes(), provided))
+    });
+    if !is_trusted {
+        return Err(forbidden("invalid or missing authorization token"));
+    }
+
+    let mut conn = app.db_read().await?;
+    let changes: Vec<CrateChange> = crate_changes::table
+        .filter(crate_changes::seq.gt(params.since_seq))
+        .order(crate_changes::seq)
+        .limit(MAX_CHANGES)
+        .select(CrateChange::as_select())
+        .load(&mut conn)
+        .await?;
+
+    let next_since_seq = changes.last().map_or(params.since_seq, |change| change.seq);
+    let changes = changes
+        .into_iter()
+        .map(EncodableCrateChange::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "changes": changes,
+        "meta": { "next_since_seq": next_since_seq },
+    }))
+}
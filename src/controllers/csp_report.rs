@@ -0,0 +1,161 @@
+//! Collects the reports of Content-Security-Policy violations, which browsers
+//! send to the `report-uri` of the policy, and forwards them to Sentry.
+//!
+//! Browsers either send a single report in the legacy `application/csp-report`
+//! format, or a list of reports in the `application/reports+json` format of
+//! the Reporting API. Both are accepted, regardless of the `Content-Type`.
+
+use crate::util::errors::{bad_request, AppResult};
+use axum::body::Bytes;
+use http::StatusCode;
+use sentry::Level;
+
+/// Reports beyond this number are ignored, so that a single request can't
+/// flood Sentry.
+const MAX_REPORTS_PER_REQUEST: usize = 10;
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Payload {
+    Legacy {
+        #[serde(rename = "csp-report")]
+        csp_report: LegacyViolation,
+    },
+    Reports(Vec<Report>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LegacyViolation {
+    document_uri: Option<String>,
+    effective_directive: Option<String>,
+    violated_directive: Option<String>,
+    blocked_uri: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Report {
+    r#type: String,
+    body: ReportBody,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportBody {
+    #[serde(rename = "documentURL")]
+    document_url: Option<String>,
+    effective_directive: Option<String>,
+    #[serde(rename = "blockedURL")]
+    blocked_url: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Violation {
+    document_uri: Option<String>,
+    directive: Option<String>,
+    blocked_uri: Option<String>,
+}
+
+impl Payload {
+    fn into_violations(self) -> Vec<Violation> {
+        match self {
+            Payload::Legacy { csp_report } => vec![Violation {
+                document_uri: csp_report.document_uri,
+                directive: csp_report
+                    .effective_directive
+                    .or(csp_report.violated_directive),
+                blocked_uri: csp_report.blocked_uri,
+            }],
+            Payload::Reports(reports) => reports
+                .into_iter()
+                .filter(|report| report.r#type == "csp-violation")
+                .map(|report| Violation {
+                    document_uri: report.body.document_url,
+                    directive: report.body.effective_directive,
+                    blocked_uri: report.body.blocked_url,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Handles the `POST /api/csp-report` route.
+pub async fn collect_csp_report(body: Bytes) -> AppResult<StatusCode> {
+    let payload: Payload =
+        serde_json::from_slice(&body).map_err(|_| bad_request("invalid CSP report"))?;
+
+    let violations = payload.into_violations();
+    for violation in violations.into_iter().take(MAX_REPORTS_PER_REQUEST) {
+        report_violation(violation);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn report_violation(violation: Violation) {
+    let directive = violation.directive.as_deref().unwrap_or("unknown");
+    let blocked_uri = violation.blocked_uri.as_deref().unwrap_or("unknown");
+    let document_uri = violation.document_uri.as_deref().unwrap_or("unknown");
+
+    warn!(%directive, %blocked_uri, %document_uri, "Content-Security-Policy violation");
+
+    let message = format!("Content-Security-Policy violation of `{directive}`");
+    sentry::with_scope(
+        |scope| {
+            scope.set_tag("csp.directive", directive);
+            scope.set_extra("blocked_uri", blocked_uri.into());
+            scope.set_extra("document_uri", document_uri.into());
+        },
+        || sentry::capture_message(&message, Level::Warning),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violations(json: &str) -> Vec<Violation> {
+        let payload: Payload = serde_json::from_str(json).unwrap();
+        payload.into_violations()
+    }
+
+    #[test]
+    fn test_legacy_report() {
+        let json = r#"{"csp-report": {
+            "document-uri": "https://crates.io/crates/foo",
+            "violated-directive": "script-src-elem",
+            "effective-directive": "script-src",
+            "blocked-uri": "https://example.com/script.js"
+        }}"#;
+
+        assert_eq!(
+            violations(json),
+            vec![Violation {
+                document_uri: Some("https://crates.io/crates/foo".into()),
+                directive: Some("script-src".into()),
+                blocked_uri: Some("https://example.com/script.js".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reporting_api() {
+        let json = r#"[
+            {"type": "csp-violation", "body": {
+                "documentURL": "https://crates.io/",
+                "effectiveDirective": "img-src",
+                "blockedURL": "inline"
+            }},
+            {"type": "deprecation", "body": {}}
+        ]"#;
+
+        assert_eq!(
+            violations(json),
+            vec![Violation {
+                document_uri: Some("https://crates.io/".into()),
+                directive: Some("img-src".into()),
+                blocked_uri: Some("inline".into()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::app::AppState;
+use crate::controllers::helpers::StreamingJson;
+use crate::models::DbDump;
+use crate::schema::db_dumps;
+use crate::util::errors::AppResult;
+use crate::views::EncodableDbDump;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+/// List the available database dumps.
+///
+/// Returns the download URL, size and SHA256 checksum of the latest full
+/// database dump and of the incremental dumps of the last 30 days, ordered
+/// from newest to oldest.
+#[utoipa::path(
+    get,
+    path = "/api/v1/db_dumps",
+    tag = "other",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_db_dumps(state: AppState) -> AppResult<StreamingJson> {
+    let mut conn = state.db_read().await?;
+
+    let dumps: Vec<DbDump> = db_dumps::table
+        .select(DbDump::as_select())
+        .order((db_dumps::created_at.desc(), db_dumps::id.asc()))
+        .load(&mut conn)
+        .await?;
+
+    let dumps = dumps
+        .into_iter()
+        .map(|dump| EncodableDbDump::from(dump, &state.storage))
+        .collect::<Vec<_>>();
+
+    Ok(StreamingJson::new().array("dumps", dumps))
+}
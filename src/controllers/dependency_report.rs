@@ -0,0 +1,276 @@
+//! Health report for the dependencies in a `Cargo.lock` file.
+//!
+//! The report is meant for CI pipelines that want to fail if a project
+//! depends on yanked versions, or falls behind new major versions of its
+//! dependencies.
+//!
+//! Security advisories and deprecation notices are not tracked by crates.io,
+//! so they are not part of the report yet.
+
+use crate::app::AppState;
+use crate::schema::{crates, versions};
+use crate::util::errors::{bad_request, AppResult};
+use axum::Json;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use semver::{Version, VersionReq};
+use std::collections::{BTreeSet, HashMap};
+
+/// The maximum number of packages in a lockfile.
+const MAX_PACKAGES: usize = 5_000;
+
+/// The sources of packages that were downloaded from crates.io.
+const CRATES_IO_SOURCES: &[&str] = &[
+    "registry+https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io/",
+];
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyReport {
+    packages: Vec<PackageReport>,
+    meta: ReportMeta,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportMeta {
+    /// Number of crates.io packages in the lockfile.
+    total: usize,
+    /// Number of packages from other sources, like git repositories, path
+    /// dependencies or other registries, which are not part of the report.
+    skipped: usize,
+    /// Number of packages with at least one issue.
+    with_issues: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct PackageReport {
+    name: String,
+    version: String,
+    /// The newest version of the crate that is not yanked and not a
+    /// pre-release.
+    latest_version: Option<String>,
+    yank_message: Option<String>,
+    issues: Vec<DependencyIssue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum DependencyIssue {
+    /// The crate or version does not exist on crates.io.
+    NotFound,
+    /// The version has been yanked.
+    Yanked,
+    /// The latest version of the crate is not semver compatible with the
+    /// locked version.
+    NewerMajorVersion,
+    /// The crate has been quarantined by the crates.io team while it is
+    /// reviewed, e.g. because it was reported as malware.
+    Quarantined,
+}
+
+struct VersionInfo {
+    num: String,
+    yanked: bool,
+    yank_message: Option<String>,
+}
+
+/// Create a health report for the dependencies of a `Cargo.lock` file.
+///
+/// The request body is the content of the `Cargo.lock` file. The report lists
+/// all packages from crates.io with their issues, e.g. yanked versions or
+/// newer major versions.
+#[utoipa::path(
+    post,
+    path = "/api/v1/dependency_report",
+    tag = "versions",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_dependency_report(
+    app: AppState,
+    body: String,
+) -> AppResult<Json<DependencyReport>> {
+    let lockfile: Lockfile =
+        toml::from_str(&body).map_err(|error| bad_request(format!("invalid lockfile: {error}")))?;
+
+    if lockfile.package.len() > MAX_PACKAGES {
+        let detail = format!("lockfiles with more than {MAX_PACKAGES} packages are not supported");
+        return Err(bad_request(detail));
+    }
+
+    let (packages, skipped): (Vec<_>, Vec<_>) = lockfile.package.into_iter().partition(|package| {
+        package
+            .source
+            .as_deref()
+            .is_some_and(|source| CRATES_IO_SOURCES.contains(&source))
+    });
+
+    let names = packages
+        .iter()
+        .map(|package| package.name.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let mut conn = app.db_read().await?;
+    let rows: Vec<(String, bool, String, bool, Option<String>)> = crates::table
+        .inner_join(versions::table)
+        .filter(crates::name.eq_any(&names))
+        .filter(crate::models::Version::not_deleted())
+        .select((
+            crates::name,
+            crates::quarantined_at.is_not_null(),
+            versions::num,
+            versions::yanked,
+            versions::yank_message,
+        ))
+        .load(&mut conn)
+        .await?;
+
+    let mut quarantined = HashMap::new();
+    let mut crate_versions: HashMap<String, Vec<VersionInfo>> = HashMap::new();
+    for (name, is_quarantined, num, yanked, yank_message) in rows {
+        quarantined.insert(name.clone(), is_quarantined);
+        let info = VersionInfo {
+            num,
+            yanked,
+            yank_message,
+        };
+        crate_versions.entry(name).or_default().push(info);
+    }
+
+    let packages = packages
+        .into_iter()
+        .map(|package| {
+            let versions = crate_versions.get(&package.name).map(Vec::as_slice);
+            let is_quarantined = quarantined.get(&package.name).copied();
+            check_package(package, versions.unwrap_or_default(), is_quarantined)
+        })
+        .collect::<Vec<_>>();
+
+    let meta = ReportMeta {
+        total: packages.len(),
+        skipped: skipped.len(),
+        with_issues: packages.iter().filter(|p| !p.issues.is_empty()).count(),
+    };
+
+    Ok(Json(DependencyReport { packages, meta }))
+}
+
+fn check_package(
+    package: LockedPackage,
+    versions: &[VersionInfo],
+    quarantined: Option<bool>,
+) -> PackageReport {
+    let mut issues = Vec::new();
+
+    let latest_version = versions
+        .iter()
+        .filter(|version| !version.yanked)
+        .filter_map(|version| Version::parse(&version.num).ok())
+        .filter(|version| version.pre.is_empty())
+        .max();
+
+    let locked = versions
+        .iter()
+        .find(|version| version.num == package.version);
+    match locked {
+        None => issues.push(DependencyIssue::NotFound),
+        Some(locked) if locked.yanked => issues.push(DependencyIssue::Yanked),
+        Some(_) => {}
+    }
+
+    if let (Some(latest), Ok(req)) = (&latest_version, VersionReq::parse(&package.version)) {
+        let is_newer = Version::parse(&package.version).is_ok_and(|locked| *latest > locked);
+        if is_newer && !req.matches(latest) {
+            issues.push(DependencyIssue::NewerMajorVersion);
+        }
+    }
+
+    if quarantined == Some(true) {
+        issues.push(DependencyIssue::Quarantined);
+    }
+
+    PackageReport {
+        name: package.name,
+        version: package.version,
+        latest_version: latest_version.map(|version| version.to_string()),
+        yank_message: locked.and_then(|version| version.yank_message.clone()),
+        issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(version: &str) -> LockedPackage {
+        LockedPackage {
+            name: "foo".to_string(),
+            version: version.to_string(),
+            source: Some(CRATES_IO_SOURCES[0].to_string()),
+        }
+    }
+
+    fn versions(versions: &[(&str, bool)]) -> Vec<VersionInfo> {
+        versions
+            .iter()
+            .map(|(num, yanked)| VersionInfo {
+                num: num.to_string(),
+                yanked: *yanked,
+                yank_message: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_check_package() {
+        let versions = versions(&[
+            ("0.1.0", false),
+            ("0.1.1", true),
+            ("0.2.0", false),
+            ("1.0.0", false),
+            ("1.1.0", false),
+            ("2.0.0-beta.1", false),
+            ("2.0.0", true),
+        ]);
+
+        let report = check_package(package("1.0.0"), &versions, Some(false));
+        assert_eq!(report.latest_version.as_deref(), Some("1.1.0"));
+        assert!(report.issues.is_empty());
+
+        let report = check_package(package("0.1.1"), &versions, Some(false));
+        assert_eq!(
+            report.issues,
+            [DependencyIssue::Yanked, DependencyIssue::NewerMajorVersion]
+        );
+
+        let report = check_package(package("0.2.0"), &versions, Some(true));
+        assert_eq!(
+            report.issues,
+            [
+                DependencyIssue::NewerMajorVersion,
+                DependencyIssue::Quarantined
+            ]
+        );
+
+        let report = check_package(package("3.0.0"), &versions, Some(false));
+        assert_eq!(report.issues, [DependencyIssue::NotFound]);
+
+        let report = check_package(package("1.0.0"), &[], None);
+        assert_eq!(report.latest_version, None);
+        assert_eq!(report.issues, [DependencyIssue::NotFound]);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::app::AppState;
+use crate::controllers::version::CrateVersionPath;
+use crate::models::DocsRsStatus;
+use crate::schema::versions;
+use crate::util::errors::{bad_request, custom, forbidden, AppResult};
+use crate::util::token::constant_time_eq;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+use http::{header, StatusCode};
+use secrecy::ExposeSecret;
+
+#[derive(Debug, Deserialize)]
+pub struct DocsRsBuild {
+    /// The name of the crate.
+    name: String,
+    /// The version of the crate that was built.
+    version: String,
+    status: DocsRsStatus,
+    /// The percentage of documented items, if available.
+    doc_coverage: Option<f32>,
+}
+
+/// Handles the `PUT /api/private/docs_rs/builds` endpoint.
+///
+/// This is called by docs.rs whenever a documentation build has finished,
+/// and stores the build result on the corresponding version.
+pub async fn record_build(
+    app: AppState,
+    req: Parts,
+    Json(build): Json<DocsRsBuild>,
+) -> AppResult<ErasedJson> {
+    let Some(expected_token) = &app.config.docs_rs_callback_token else {
+        let detail = "docs.rs build results are disabled on this crates.io instance";
+        return Err(custom(StatusCode::NOT_FOUND, detail));
+    };
+
+    let provided_token = req
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let expected_token = expected_token.expose_secret().as_bytes();
+    let is_valid = provided_token
+        .is_some_and(|provided| constant_time_eq(expected_token, provided.as_bytes()));
+    if !is_valid {
+        return Err(forbidden("invalid or missing authorization token"));
+    }
+
+    if let Some(coverage) = build.doc_coverage {
+        if !(0.0..=100.0).contains(&coverage) {
+            return Err(bad_request("`doc_coverage` must be between 0 and 100"));
+        }
+    }
+
+    let mut conn = app.db_write().await?;
+
+    let path = CrateVersionPath {
+        name: build.name,
+        version: build.version,
+    };
+    let version = path.load_version(&mut conn).await?;
+
+    diesel::update(versions::table.find(version.id))
+        .set((
+            versions::docs_rs_status.eq(build.status),
+            versions::docs_rs_doc_coverage.eq(build.doc_coverage),
+            versions::docs_rs_built_at.eq(now),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(json!({ "ok": true }))
+}
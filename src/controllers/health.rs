@@ -0,0 +1,103 @@
+//! Health check endpoints for the load balancer and the orchestration.
+//!
+//! `/healthz` only checks that the server is able to handle requests, so that
+//! the server isn't restarted just because one of its dependencies is down.
+//! `/readyz` additionally checks the dependencies of the server, so that
+//! requests are only routed to servers that are able to handle them.
+
+use crate::app::AppState;
+use axum::response::{IntoResponse, Response};
+use axum_extra::json;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Paths of the health check endpoints, which are exempt from the checks for
+/// regular requests, e.g. the `User-Agent` header requirement.
+pub const HEALTH_CHECK_PATHS: &[&str] = &["/healthz", "/readyz"];
+
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(2);
+const STORAGE_TIMEOUT: Duration = Duration::from_secs(3);
+const INDEX_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Returns `200 OK` as long as the server is able to handle requests.
+pub async fn liveness() -> impl IntoResponse {
+    json!({ "status": "ok" })
+}
+
+/// Returns `200 OK` if all dependencies of the server are healthy, or
+/// `503 Service Unavailable` otherwise, together with the result of each
+/// check.
+pub async fn readiness(state: AppState) -> Response {
+    let (database, storage, index) = tokio::join!(
+        check(DATABASE_TIMEOUT, check_database(&state)),
+        check(STORAGE_TIMEOUT, state.storage.check_health()),
+        check(INDEX_TIMEOUT, state.storage.check_index_health()),
+    );
+
+    let checks = [
+        ("database", &database),
+        ("storage", &storage),
+        ("index", &index),
+    ];
+    for (name, check) in checks {
+        if let Some(error) = &check.error {
+            warn!("Readiness check `{name}` failed: {error}");
+        }
+    }
+
+    let ready = checks.iter().all(|(_, check)| check.error.is_none());
+    let (status, status_code) = match ready {
+        true => ("ok", StatusCode::OK),
+        false => ("unavailable", StatusCode::SERVICE_UNAVAILABLE),
+    };
+
+    let body = json!({
+        "status": status,
+        "checks": {
+            "database": database,
+            "storage": storage,
+            "index": index,
+        },
+    });
+
+    (status_code, body).into_response()
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    status: &'static str,
+    duration_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+async fn check<F, E>(timeout: Duration, future: F) -> Check
+where
+    F: Future<Output = Result<(), E>>,
+    E: Display,
+{
+    let start = Instant::now();
+
+    let error = match tokio::time::timeout(timeout, future).await {
+        Ok(Ok(())) => None,
+        Ok(Err(error)) => Some(error.to_string()),
+        Err(_) => Some(format!("timed out after {timeout:?}")),
+    };
+
+    Check {
+        status: if error.is_none() { "ok" } else { "error" },
+        duration_ms: start.elapsed().as_millis(),
+        error,
+    }
+}
+
+/// Checks out a connection from the primary database pool and runs a trivial
+/// query on it.
+async fn check_database(state: &AppState) -> anyhow::Result<()> {
+    let mut conn = state.db_write().await?;
+    diesel::sql_query("SELECT 1").execute(&mut conn).await?;
+    Ok(())
+}
@@ -2,9 +2,17 @@ use crate::util::errors::AppResult;
 use axum::response::{IntoResponse, Response};
 use axum_extra::json;
 
+pub(crate) mod crate_list;
 pub(crate) mod pagination;
+pub(crate) mod streaming_json;
 
 pub(crate) use self::pagination::Paginate;
+pub(crate) use self::streaming_json::StreamingJson;
+
+/// The `Cache-Control` header of the endpoints that list rarely changing
+/// resources like keywords and categories, which crawlers tend to enumerate
+/// page by page.
+pub(crate) const LIST_CACHE_CONTROL: &str = "public, max-age=3600, stale-while-revalidate=86400";
 
 pub fn ok_true() -> AppResult<Response> {
     let json = json!({ "ok": true });
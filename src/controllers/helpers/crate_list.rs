@@ -0,0 +1,175 @@
+//! Batched loading of the owners, keywords and categories of crate lists.
+//!
+//! Loading these associations crate by crate would cost one query per crate
+//! and association. Instead, each association is loaded for all crates of a
+//! page at once, so that a page needs at most three additional queries.
+
+use crate::models::{Category, Keyword, Organization, Owner, OwnerKind, OwnerRole, Team, User};
+use crate::schema::{
+    categories, crate_owners, crates_categories, crates_keywords, keywords, organizations, teams,
+    users,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::HashMap;
+
+/// The associations that are loaded for the crates of a list.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CrateListInclude {
+    pub owners: bool,
+    pub keywords: bool,
+    pub categories: bool,
+}
+
+/// The requested associations of a page of crates, grouped by crate ID.
+#[derive(Debug, Default)]
+pub struct CrateListIncludes {
+    owners: Option<HashMap<i32, Vec<Owner>>>,
+    keywords: Option<HashMap<i32, Vec<Keyword>>>,
+    categories: Option<HashMap<i32, Vec<Category>>>,
+}
+
+impl CrateListIncludes {
+    /// Loads the requested associations of the given crates, using a single
+    /// query per association.
+    pub async fn load(
+        conn: &mut AsyncPgConnection,
+        crate_ids: &[i32],
+        include: CrateListInclude,
+    ) -> QueryResult<Self> {
+        let mut includes = Self::default();
+
+        if include.owners {
+            includes.owners = Some(load_owners(conn, crate_ids).await?);
+        }
+        if include.keywords {
+            includes.keywords = Some(load_keywords(conn, crate_ids).await?);
+        }
+        if include.categories {
+            includes.categories = Some(load_categories(conn, crate_ids).await?);
+        }
+
+        Ok(includes)
+    }
+
+    /// Removes the owners of the crate, if they were requested.
+    pub fn take_owners(&mut self, crate_id: i32) -> Option<Vec<Owner>> {
+        take(&mut self.owners, crate_id)
+    }
+
+    /// Removes the keywords of the crate, if they were requested.
+    pub fn take_keywords(&mut self, crate_id: i32) -> Option<Vec<Keyword>> {
+        take(&mut self.keywords, crate_id)
+    }
+
+    /// Removes the categories of the crate, if they were requested.
+    pub fn take_categories(&mut self, crate_id: i32) -> Option<Vec<Category>> {
+        take(&mut self.categories, crate_id)
+    }
+}
+
+fn take<T>(map: &mut Option<HashMap<i32, Vec<T>>>, crate_id: i32) -> Option<Vec<T>> {
+    map.as_mut()
+        .map(|map| map.remove(&crate_id).unwrap_or_default())
+}
+
+/// Loads the user, team and organization owners of the crates, in the same
+/// order as [`crate::models::Crate::owners`].
+async fn load_owners(
+    conn: &mut AsyncPgConnection,
+    crate_ids: &[i32],
+) -> QueryResult<HashMap<i32, Vec<Owner>>> {
+    type Row = (
+        i32,
+        OwnerRole,
+        Option<User>,
+        Option<Team>,
+        Option<Organization>,
+    );
+
+    let is_kind = |kind| crate_owners::owner_kind.eq(kind);
+
+    let rows: Vec<Row> = crate_owners::table
+        .left_join(
+            users::table.on(crate_owners::owner_id
+                .eq(users::id)
+                .and(is_kind(OwnerKind::User))),
+        )
+        .left_join(
+            teams::table.on(crate_owners::owner_id
+                .eq(teams::id)
+                .and(is_kind(OwnerKind::Team))),
+        )
+        .left_join(
+            organizations::table.on(crate_owners::owner_id
+                .eq(organizations::id)
+                .and(is_kind(OwnerKind::Organization))),
+        )
+        .filter(crate_owners::crate_id.eq_any(crate_ids))
+        .filter(crate_owners::deleted.eq(false))
+        .order((
+            crate_owners::crate_id,
+            crate_owners::owner_kind,
+            crate_owners::owner_id,
+        ))
+        .select((
+            crate_owners::crate_id,
+            crate_owners::role,
+            Option::<User>::as_select(),
+            Option::<Team>::as_select(),
+            Option::<Organization>::as_select(),
+        ))
+        .load(conn)
+        .await?;
+
+    let mut owners: HashMap<i32, Vec<Owner>> = HashMap::new();
+    for (crate_id, role, user, team, organization) in rows {
+        let owner = match (user, team, organization) {
+            (Some(user), _, _) => Owner::User(user, role),
+            (_, Some(team), _) => Owner::Team(team),
+            (_, _, Some(organization)) => Owner::Organization(organization),
+            _ => continue,
+        };
+        owners.entry(crate_id).or_default().push(owner);
+    }
+
+    Ok(owners)
+}
+
+async fn load_keywords(
+    conn: &mut AsyncPgConnection,
+    crate_ids: &[i32],
+) -> QueryResult<HashMap<i32, Vec<Keyword>>> {
+    let rows: Vec<(i32, Keyword)> = crates_keywords::table
+        .inner_join(keywords::table)
+        .filter(crates_keywords::crate_id.eq_any(crate_ids))
+        .order((crates_keywords::crate_id, keywords::keyword))
+        .select((crates_keywords::crate_id, Keyword::as_select()))
+        .load(conn)
+        .await?;
+
+    Ok(group_by_crate(rows))
+}
+
+async fn load_categories(
+    conn: &mut AsyncPgConnection,
+    crate_ids: &[i32],
+) -> QueryResult<HashMap<i32, Vec<Category>>> {
+    let rows: Vec<(i32, Category)> = crates_categories::table
+        .inner_join(categories::table)
+        .filter(crates_categories::crate_id.eq_any(crate_ids))
+        .order((crates_categories::crate_id, categories::slug))
+        .select((crates_categories::crate_id, Category::as_select()))
+        .load(conn)
+        .await?;
+
+    Ok(group_by_crate(rows))
+}
+
+fn group_by_crate<T>(rows: Vec<(i32, T)>) -> HashMap<i32, Vec<T>> {
+    let mut map: HashMap<i32, Vec<T>> = HashMap::new();
+    for (crate_id, item) in rows {
+        map.entry(crate_id).or_default().push(item);
+    }
+    map
+}
@@ -0,0 +1,183 @@
+//! JSON responses that are serialized while they are sent.
+//!
+//! [`ErasedJson`](axum_extra::response::ErasedJson) serializes the whole
+//! response into a single buffer before the first byte is sent. For large
+//! lists, [`StreamingJson`] serializes the list items in chunks instead, so
+//! that the serialized response never has to be held in memory at once.
+
+use axum::body::Body;
+use axum::response::{IntoResponse, Response};
+use bytes::Bytes;
+use futures_util::stream;
+use http::header;
+use serde::Serialize;
+use std::iter;
+
+/// Approximate size of the chunks that are written to the response body.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+type Fragments = Box<dyn Iterator<Item = serde_json::Result<Vec<u8>>> + Send>;
+
+/// A JSON object response, whose array fields are serialized lazily.
+///
+/// The fields are serialized in the order in which they are added.
+#[derive(Default)]
+pub struct StreamingJson {
+    fields: Vec<Fragments>,
+}
+
+impl StreamingJson {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a field that is serialized immediately.
+    pub fn field(mut self, name: &str, value: impl Serialize) -> Self {
+        let fragment = serde_json::to_vec(&value).map(|value| {
+            let mut fragment = key(name);
+            fragment.extend(value);
+            fragment
+        });
+
+        self.fields.push(Box::new(iter::once(fragment)));
+        self
+    }
+
+    /// Adds an array field, whose items are serialized while the response
+    /// body is sent.
+    pub fn array<T>(mut self, name: &str, items: Vec<T>) -> Self
+    where
+        T: Serialize + Send + 'static,
+    {
+        let mut start = key(name);
+        start.push(b'[');
+
+        let items = items.into_iter().enumerate().map(|(index, item)| {
+            let mut fragment = if index == 0 { Vec::new() } else { vec![b','] };
+            serde_json::to_writer(&mut fragment, &item).map(|_| fragment)
+        });
+
+        let fragments = iter::once(Ok(start))
+            .chain(items)
+            .chain(iter::once(Ok(vec![b']'])));
+
+        self.fields.push(Box::new(fragments));
+        self
+    }
+}
+
+impl IntoResponse for StreamingJson {
+    fn into_response(self) -> Response {
+        let fields = self
+            .fields
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, fragments)| {
+                let separator = (index > 0).then(|| Ok(vec![b',']));
+                separator.into_iter().chain(fragments)
+            });
+
+        let fragments = iter::once(Ok(vec![b'{']))
+            .chain(fields)
+            .chain(iter::once(Ok(vec![b'}'])));
+
+        let body = Body::from_stream(stream::iter(Chunks { fragments }));
+        ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+    }
+}
+
+fn key(name: &str) -> Vec<u8> {
+    let mut key = serde_json::Value::from(name).to_string().into_bytes();
+    key.push(b':');
+    key
+}
+
+/// Combines the serialized fragments into chunks of roughly [`CHUNK_SIZE`]
+/// bytes.
+struct Chunks<I> {
+    fragments: I,
+}
+
+impl<I> Iterator for Chunks<I>
+where
+    I: Iterator<Item = serde_json::Result<Vec<u8>>>,
+{
+    type Item = serde_json::Result<Bytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = Vec::new();
+        for fragment in self.fragments.by_ref() {
+            match fragment {
+                Ok(fragment) => chunk.extend(fragment),
+                Err(error) => {
+                    // The status code has already been sent at this point,
+                    // so the response can only be aborted.
+                    error!(%error, "Failed to serialize streaming JSON response");
+                    return Some(Err(error));
+                }
+            }
+
+            if chunk.len() >= CHUNK_SIZE {
+                break;
+            }
+        }
+
+        (!chunk.is_empty()).then(|| Ok(chunk.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    async fn collect(response: StreamingJson) -> (Vec<Bytes>, serde_json::Value) {
+        let response = response.into_response();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+
+        let mut stream = response.into_body().into_data_stream();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            chunks.push(chunk.unwrap());
+        }
+
+        let json = serde_json::from_slice(&chunks.concat()).unwrap();
+        (chunks, json)
+    }
+
+    #[tokio::test]
+    async fn test_streaming_json() {
+        let response = StreamingJson::new()
+            .array("items", vec![json!({ "id": 1 }), json!({ "id": 2 })])
+            .array("empty", Vec::<u32>::new())
+            .field("meta", json!({ "total": 2, "name": "\"quoted\"" }));
+
+        let (chunks, json) = collect(response).await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(
+            json,
+            json!({
+                "items": [{ "id": 1 }, { "id": 2 }],
+                "empty": [],
+                "meta": { "total": 2, "name": "\"quoted\"" },
+            })
+        );
+
+        let (_, json) = collect(StreamingJson::new()).await;
+        assert_eq!(json, json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_large_arrays_are_chunked() {
+        let items = (0..50_000)
+            .map(|id| format!("item-{id}"))
+            .collect::<Vec<_>>();
+        let response = StreamingJson::new().array("items", items.clone());
+
+        let (chunks, json) = collect(response).await;
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.len() < 2 * CHUNK_SIZE));
+        assert_eq!(json, json!({ "items": items }));
+    }
+}
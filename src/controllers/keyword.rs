@@ -1,13 +1,17 @@
 use crate::app::AppState;
 use crate::controllers::helpers::pagination::PaginationOptions;
-use crate::controllers::helpers::{pagination::Paginated, Paginate};
-use crate::models::Keyword;
-use crate::util::errors::AppResult;
-use crate::views::EncodableKeyword;
+use crate::controllers::helpers::{pagination::Paginated, Paginate, LIST_CACHE_CONTROL};
+use crate::models::{AggregateDownload, Keyword};
+use crate::util::errors::{not_found, AppResult};
+use crate::util::redirect;
+use crate::views::{EncodableAggregateDownload, EncodableKeyword};
 use axum::extract::{Path, Query};
+use axum::response::{IntoResponse, Response};
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
+use chrono::{Duration, Utc};
 use diesel::prelude::*;
+use http::header;
 use http::request::Parts;
 
 #[derive(Deserialize)]
@@ -26,7 +30,7 @@ pub async fn list_keywords(
     state: AppState,
     qp: Query<IndexQuery>,
     req: Parts,
-) -> AppResult<ErasedJson> {
+) -> AppResult<Response> {
     use crate::schema::keywords;
 
     let mut query = keywords::table.into_boxed();
@@ -46,13 +50,19 @@ pub async fn list_keywords(
         .map(Keyword::into)
         .collect::<Vec<EncodableKeyword>>();
 
-    Ok(json!({
+    let json = json!({
         "keywords": kws,
         "meta": { "total": total },
-    }))
+    });
+
+    let headers = [(header::CACHE_CONTROL, LIST_CACHE_CONTROL)];
+    Ok((headers, json).into_response())
 }
 
 /// Get keyword metadata.
+///
+/// Aliases of keywords, e.g. of keywords that were merged into another
+/// keyword, redirect to the keyword.
 #[utoipa::path(
     get,
     path = "/api/v1/keywords/{keyword}",
@@ -62,9 +72,61 @@ pub async fn list_keywords(
     tag = "keywords",
     responses((status = 200, description = "Successful Response")),
 )]
-pub async fn find_keyword(Path(name): Path<String>, state: AppState) -> AppResult<ErasedJson> {
+pub async fn find_keyword(Path(name): Path<String>, state: AppState) -> AppResult<Response> {
+    let mut conn = state.db_read().await?;
+    let kw = match Keyword::find_by_keyword(&mut conn, &name).await {
+        Err(diesel::result::Error::NotFound) => {
+            let Some(kw) = Keyword::find_by_alias(&mut conn, &name).await? else {
+                return Err(not_found());
+            };
+
+            return Ok(redirect(format!("/api/v1/keywords/{}", kw.keyword)));
+        }
+        result => result?,
+    };
+
+    Ok(json!({ "keyword": EncodableKeyword::from(kw) }).into_response())
+}
+
+/// Get the download counts for a keyword.
+///
+/// This includes the per-day downloads of all crates with the keyword for
+/// the last 90 days. The downloads of a day are aggregated once the day is
+/// over, so the current day is not included.
+#[utoipa::path(
+    get,
+    path = "/api/v1/keywords/{keyword}/downloads",
+    params(
+        ("keyword" = String, Path, description = "The keyword to find"),
+    ),
+    tag = "keywords",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_keyword_downloads(
+    Path(name): Path<String>,
+    state: AppState,
+) -> AppResult<Response> {
     let mut conn = state.db_read().await?;
-    let kw = Keyword::find_by_keyword(&mut conn, &name).await?;
+    let kw = match Keyword::find_by_keyword(&mut conn, &name).await {
+        Err(diesel::result::Error::NotFound) => {
+            let Some(kw) = Keyword::find_by_alias(&mut conn, &name).await? else {
+                return Err(not_found());
+            };
+
+            return Ok(redirect(format!(
+                "/api/v1/keywords/{}/downloads",
+                kw.keyword
+            )));
+        }
+        result => result?,
+    };
+
+    let start_date = Utc::now().date_naive() - Duration::days(89);
+    let downloads = AggregateDownload::for_keyword(&mut conn, kw.id, start_date)
+        .await?
+        .into_iter()
+        .map(AggregateDownload::into)
+        .collect::<Vec<EncodableAggregateDownload>>();
 
-    Ok(json!({ "keyword": EncodableKeyword::from(kw) }))
+    Ok(json!({ "downloads": downloads }).into_response())
 }
@@ -6,14 +6,30 @@ use diesel::{OptionalExtension, QueryDsl};
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use utoipa::IntoParams;
 
+pub mod activity;
+pub mod api_usage;
+pub mod audit;
+pub mod contact;
 pub mod delete;
 pub mod downloads;
 pub mod follow;
 pub mod metadata;
+pub mod most_depended;
 pub mod owners;
+pub mod page;
 pub mod publish;
+pub mod reclaim;
+pub mod redirect;
+pub mod report;
+pub mod repository;
 pub mod rev_deps;
+pub mod score;
 pub mod search;
+pub mod semver_check;
+pub mod settings;
+pub mod suggested_categories;
+pub mod transfer;
+pub mod trending;
 pub mod versions;
 
 #[derive(Deserialize, FromRequestParts, IntoParams)]
@@ -0,0 +1,66 @@
+use crate::app::AppState;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::controllers::krate::CratePath;
+use crate::models::{AuditLogAction, AuditLogEntry};
+use crate::schema::{audit_log, users};
+use crate::util::errors::AppResult;
+use crate::views::EncodableCrateActivity;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use http::request::Parts;
+
+/// The audit log actions that are part of the public activity timeline.
+///
+/// Pending invitations, transfer requests and moderation actions are only
+/// visible in the audit log of the crate owners.
+const PUBLIC_ACTIONS: &[AuditLogAction] = &[
+    AuditLogAction::Publish,
+    AuditLogAction::Yank,
+    AuditLogAction::Unyank,
+    AuditLogAction::OwnerAdd,
+    AuditLogAction::OwnerRemove,
+    AuditLogAction::TransferAccept,
+];
+
+/// List the recent activity of a crate.
+///
+/// Returns a timeline of publishes, yanks and ownership changes, sorted from
+/// newest to oldest.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/activity",
+    params(CratePath),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_crate_activity(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read().await?;
+    let crate_id = path.load_crate_id(&mut conn).await?;
+
+    let query = audit_log::table
+        .left_join(users::table)
+        .filter(audit_log::crate_id.eq(crate_id))
+        .filter(audit_log::action.eq_any(PUBLIC_ACTIONS))
+        .order(audit_log::id.desc())
+        .select((audit_log::all_columns, users::gh_login.nullable()))
+        .pages_pagination(PaginationOptions::builder().gather(&req)?);
+
+    let data: Paginated<(AuditLogEntry, Option<String>)> = query.load(&mut conn).await?;
+    let total = data.total();
+
+    let activity = data
+        .into_iter()
+        .map(|(entry, user)| EncodableCrateActivity::new(entry, user))
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "activity": activity,
+        "meta": { "total": total },
+    }))
+}
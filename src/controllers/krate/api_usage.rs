@@ -0,0 +1,53 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::krate::CratePath;
+use crate::models::{ApiUsageCount, Rights};
+use crate::util::errors::{forbidden, AppResult};
+use crate::views::EncodableApiUsage;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{Days, Utc};
+use http::request::Parts;
+
+/// The number of days of API usage that are returned.
+const USAGE_DAYS: u64 = 90;
+
+/// Get the API usage of a crate.
+///
+/// Returns the daily number of downloads through the API and of other read
+/// requests about the crate in the last 90 days, ordered from oldest to
+/// newest. Only owners of the crate can see its API usage.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/api_usage",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_crate_api_usage(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+    let owners = krate.owners(&mut conn).await?;
+    if auth.user().rights(&app, &mut conn, &owners).await? < Rights::Publish {
+        return Err(forbidden("only owners of this crate can see its API usage"));
+    }
+
+    let since = Utc::now().date_naive() - Days::new(USAGE_DAYS - 1);
+    let usage = ApiUsageCount::for_crate(&mut conn, krate.id, since).await?;
+    let usage = usage
+        .into_iter()
+        .map(EncodableApiUsage::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "usage": usage }))
+}
@@ -0,0 +1,63 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::controllers::krate::CratePath;
+use crate::models::{AuditLogEntry, Rights};
+use crate::schema::{audit_log, users};
+use crate::util::errors::{forbidden, AppResult};
+use crate::views::EncodableAuditLogEntry;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use http::request::Parts;
+
+/// List the audit log entries of a crate.
+///
+/// Only owners of the crate can see its audit log. The entries are sorted
+/// from newest to oldest.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/audit",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_crate_audit_log(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+    let owners = krate.owners(&mut conn).await?;
+    if auth.user().rights(&app, &mut conn, &owners).await? < Rights::Publish {
+        return Err(forbidden("only owners of this crate can see its audit log"));
+    }
+
+    let query = audit_log::table
+        .left_join(users::table)
+        .filter(audit_log::crate_id.eq(krate.id))
+        .order(audit_log::id.desc())
+        .select((audit_log::all_columns, users::gh_login.nullable()))
+        .pages_pagination(PaginationOptions::builder().gather(&req)?);
+
+    let data: Paginated<(AuditLogEntry, Option<String>)> = query.load(&mut conn).await?;
+    let total = data.total();
+
+    let entries = data
+        .into_iter()
+        .map(|(entry, user)| EncodableAuditLogEntry::new(entry, user, Some(krate.name.clone())))
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "entries": entries,
+        "meta": { "total": total },
+    }))
+}
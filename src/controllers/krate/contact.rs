@@ -0,0 +1,201 @@
+//! Endpoint for sending a message to the owners of a crate.
+//!
+//! The email addresses of the owners are never revealed to the sender. The
+//! message is relayed by crates.io to the owners that accept contact requests,
+//! and includes the verified email address of the sender so that the owners
+//! can reply if they want to.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::ok_true;
+use crate::controllers::krate::CratePath;
+use crate::email::Email;
+use crate::models::{Owner, OwnerKind};
+use crate::rate_limiter::LimitedAction;
+use crate::schema::{crate_owners, emails, users};
+use crate::util::errors::{bad_request, AppResult};
+use axum::response::Response;
+use axum::Json;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+/// The maximum length of the subject of a message.
+const MAX_SUBJECT_LENGTH: usize = 100;
+
+/// The maximum length of the body of a message.
+const MAX_BODY_LENGTH: usize = 5_000;
+
+#[derive(Deserialize)]
+pub struct ContactRequest {
+    message: ContactRequestData,
+}
+
+#[derive(Deserialize)]
+pub struct ContactRequestData {
+    subject: String,
+    body: String,
+}
+
+/// Send a message to the owners of a crate.
+///
+/// The message is sent by email to all user owners of the crate that accept
+/// contact requests. The verified email address of the sender is included in
+/// the message so that the owners can reply. The email addresses of the
+/// owners are not revealed to the sender.
+#[utoipa::path(
+    post,
+    path = "/api/v1/crates/{name}/contact_owners",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn contact_owners(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Json(request): Json<ContactRequest>,
+) -> AppResult<Response> {
+    let request = request.message;
+
+    let subject = request.subject.trim();
+    if subject.is_empty() {
+        return Err(bad_request("the message subject must not be empty"));
+    }
+
+    if subject.chars().count() > MAX_SUBJECT_LENGTH {
+        let detail = format!("the message subject must not exceed {MAX_SUBJECT_LENGTH} characters");
+        return Err(bad_request(detail));
+    }
+
+    if subject.contains(['\r', '\n']) {
+        return Err(bad_request("the message subject must be a single line"));
+    }
+
+    let body = request.body.trim();
+    if body.is_empty() {
+        return Err(bad_request("the message body must not be empty"));
+    }
+
+    if body.chars().count() > MAX_BODY_LENGTH {
+        let detail = format!("the message body must not exceed {MAX_BODY_LENGTH} characters");
+        return Err(bad_request(detail));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let krate = path.load_crate(&mut conn).await?;
+
+    let Some(sender_email) = user.verified_email(&mut conn).await? else {
+        let detail = "a verified email address is required to contact the owners of a crate";
+        return Err(bad_request(detail));
+    };
+
+    let owners = krate.owners(&mut conn).await?;
+    let is_owner = owners
+        .iter()
+        .any(|owner| matches!(owner, Owner::User(owner, _) if owner.id == user.id));
+    if is_owner {
+        return Err(bad_request("you are an owner of this crate"));
+    }
+
+    let recipients = crate_owners::table
+        .filter(crate_owners::deleted.eq(false))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+        .filter(crate_owners::crate_id.eq(krate.id))
+        .inner_join(users::table)
+        .filter(users::contact_requests.eq(true))
+        .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+        .filter(emails::is_primary)
+        .filter(emails::verified.eq(true))
+        .select((users::gh_login, emails::email))
+        .load::<(String, String)>(&mut conn)
+        .await?;
+
+    if recipients.is_empty() {
+        let detail = "none of the owners of this crate accept contact requests";
+        return Err(bad_request(detail));
+    }
+
+    app.rate_limiter
+        .check_rate_limit(user.id, LimitedAction::ContactOwners, &mut conn)
+        .await?;
+
+    info!(
+        sender = %user.gh_login,
+        krate = %krate.name,
+        "Sending contact request to {} owners",
+        recipients.len()
+    );
+
+    for (recipient, email_address) in recipients {
+        let email = ContactOwnersEmail {
+            recipient: &recipient,
+            sender: &user.gh_login,
+            sender_email: &sender_email,
+            krate: &krate.name,
+            subject,
+            body,
+            domain: &app.emails.domain,
+        };
+
+        if let Err(error) = app.emails.send(&email_address, email).await {
+            warn!("Failed to send contact request email to {recipient}: {error}");
+        }
+    }
+
+    ok_true()
+}
+
+/// Email template for relaying a message to an owner of a crate.
+struct ContactOwnersEmail<'a> {
+    recipient: &'a str,
+    sender: &'a str,
+    sender_email: &'a str,
+    krate: &'a str,
+    subject: &'a str,
+    body: &'a str,
+    domain: &'a str,
+}
+
+impl Email for ContactOwnersEmail<'_> {
+    fn subject(&self) -> String {
+        format!(
+            "crates.io: Message about \"{}\" from {}",
+            self.krate, self.sender
+        )
+    }
+
+    fn body(&self) -> String {
+        format!(
+            "Hi {recipient},
+
+{sender} (https://{domain}/users/{sender}) sent you a message
+about the \"{krate}\" crate:
+
+Subject: {subject}
+
+{body}
+
+You can reply to {sender} at {sender_email}.
+Your email address is not shared with {sender} unless you reply.
+
+You received this email because you accept contact requests from
+other users. You can turn them off in your account settings at
+https://{domain}/settings/profile.",
+            recipient = self.recipient,
+            sender = self.sender,
+            domain = self.domain,
+            krate = self.krate,
+            subject = self.subject,
+            body = self.body,
+            sender_email = self.sender_email,
+        )
+    }
+}
@@ -6,12 +6,12 @@
 use crate::app::AppState;
 use crate::controllers::krate::CratePath;
 use crate::models::{Version, VersionDownload};
-use crate::schema::{version_downloads, versions};
+use crate::schema::versions;
 use crate::util::errors::AppResult;
 use crate::views::EncodableVersionDownload;
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
-use crates_io_diesel_helpers::to_char;
+use chrono::{Duration, Utc};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use std::cmp;
@@ -31,45 +31,44 @@ use std::cmp;
 pub async fn get_crate_downloads(state: AppState, path: CratePath) -> AppResult<ErasedJson> {
     let mut conn = state.db_read().await?;
 
-    use diesel::dsl::*;
-    use diesel::sql_types::BigInt;
-
     let crate_id: i32 = path.load_crate_id(&mut conn).await?;
 
     let mut versions: Vec<Version> = versions::table
         .filter(versions::crate_id.eq(crate_id))
+        .filter(Version::not_deleted())
         .select(Version::as_select())
         .load(&mut conn)
         .await?;
 
     versions.sort_by_cached_key(|version| cmp::Reverse(semver::Version::parse(&version.num).ok()));
     let (latest_five, rest) = versions.split_at(cmp::min(5, versions.len()));
+    let latest_five = latest_five
+        .iter()
+        .map(|version| version.id)
+        .collect::<Vec<_>>();
+    let rest = rest.iter().map(|version| version.id).collect::<Vec<_>>();
 
-    let downloads = VersionDownload::belonging_to(latest_five)
-        .filter(version_downloads::date.gt(date(now - 90.days())))
-        .order((
-            version_downloads::date.asc(),
-            version_downloads::version_id.desc(),
-        ))
-        .load(&mut conn)
-        .await?
+    let end_date = Utc::now().date_naive();
+    let start_date = end_date - Duration::days(89);
+
+    let mut downloads =
+        VersionDownload::load_between(&mut conn, &latest_five, start_date, end_date).await?;
+    downloads.sort_by_key(|download| (download.date, cmp::Reverse(download.version_id)));
+    let downloads = downloads
         .into_iter()
         .map(VersionDownload::into)
         .collect::<Vec<EncodableVersionDownload>>();
 
-    let sum_downloads = sql::<BigInt>("SUM(version_downloads.downloads)");
-    let extra: Vec<ExtraDownload> = VersionDownload::belonging_to(rest)
-        .select((
-            to_char(version_downloads::date, "YYYY-MM-DD"),
-            sum_downloads,
-        ))
-        .filter(version_downloads::date.gt(date(now - 90.days())))
-        .group_by(version_downloads::date)
-        .order(version_downloads::date.asc())
-        .load(&mut conn)
-        .await?;
+    let extra = VersionDownload::sum_between(&mut conn, &rest, start_date, end_date)
+        .await?
+        .into_iter()
+        .map(|(date, downloads)| ExtraDownload {
+            date: date.to_string(),
+            downloads,
+        })
+        .collect::<Vec<_>>();
 
-    #[derive(Serialize, Queryable)]
+    #[derive(Serialize)]
     struct ExtraDownload {
         date: String,
         downloads: i64,
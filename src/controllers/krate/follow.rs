@@ -6,13 +6,35 @@ use crate::controllers::helpers::ok_true;
 use crate::controllers::krate::CratePath;
 use crate::models::{Crate, Follow};
 use crate::schema::*;
-use crate::util::errors::{crate_not_found, AppResult};
+use crate::util::errors::{bad_request, crate_not_found, AppResult};
 use axum::response::Response;
+use axum::Json;
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
+use diesel::dsl::now;
 use diesel::prelude::*;
+use diesel::upsert::excluded;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::request::Parts;
+use url::Url;
+
+/// The notification channels of a followed crate, which are notified when
+/// the crate publishes or yanks a version.
+#[derive(Debug, Default, Deserialize, Serialize, Queryable)]
+pub struct FollowNotifications {
+    /// Whether an email is sent to the primary email address of the user.
+    #[serde(default)]
+    email: bool,
+    /// An HTTPS URL that receives a JSON `POST` request.
+    #[serde(default)]
+    webhook_url: Option<String>,
+}
+
+impl FollowNotifications {
+    fn is_enabled(&self) -> bool {
+        self.email || self.webhook_url.is_some()
+    }
+}
 
 async fn follow_target(
     crate_name: &str,
@@ -76,6 +98,9 @@ pub async fn unfollow_crate(app: AppState, path: CratePath, req: Parts) -> AppRe
 }
 
 /// Check if a crate is followed.
+///
+/// If the crate is followed, the response also contains the notification
+/// channels of the crate.
 #[utoipa::path(
     get,
     path = "/api/v1/crates/{name}/following",
@@ -89,8 +114,6 @@ pub async fn get_following_crate(
     path: CratePath,
     req: Parts,
 ) -> AppResult<ErasedJson> {
-    use diesel::dsl::exists;
-
     let mut conn = app.db_read_prefer_primary().await?;
     let user_id = AuthCheck::only_cookie()
         .check(&req, &mut conn)
@@ -98,9 +121,91 @@ pub async fn get_following_crate(
         .user_id();
 
     let follow = follow_target(&path.name, &mut conn, user_id).await?;
-    let following = diesel::select(exists(follows::table.find(follow.id())))
-        .get_result::<bool>(&mut conn)
-        .await?;
+    let notifications = follows::table
+        .find(follow.id())
+        .select((follows::notify_email, follows::webhook_url))
+        .first::<FollowNotifications>(&mut conn)
+        .await
+        .optional()?;
+
+    Ok(match notifications {
+        Some(notifications) => json!({ "following": true, "notifications": notifications }),
+        None => json!({ "following": false }),
+    })
+}
+
+/// Update the notification channels of a followed crate.
+///
+/// The crate is followed if it isn't already. Notifications are batched, so
+/// that multiple releases in quick succession result in a single
+/// notification.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/following",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_following_crate(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Json(notifications): Json<FollowNotifications>,
+) -> AppResult<ErasedJson> {
+    if let Some(webhook_url) = &notifications.webhook_url {
+        let url = Url::parse(webhook_url).map_err(|_| bad_request("invalid webhook URL"))?;
+        if url.scheme() != "https" {
+            return Err(bad_request("the webhook URL must use `https`"));
+        }
+    }
+
+    let mut conn = app.db_write().await?;
+    let user_id = AuthCheck::default().check(&req, &mut conn).await?.user_id();
+    let follow = follow_target(&path.name, &mut conn, user_id).await?;
+
+    let previous = follows::table
+        .find(follow.id())
+        .select((follows::notify_email, follows::webhook_url))
+        .first::<FollowNotifications>(&mut conn)
+        .await
+        .optional()?;
+
+    let values = (
+        follows::user_id.eq(follow.user_id),
+        follows::crate_id.eq(follow.crate_id),
+        follows::notify_email.eq(notifications.email),
+        follows::webhook_url.eq(&notifications.webhook_url),
+    );
+
+    let query = diesel::insert_into(follows::table)
+        .values(values)
+        .on_conflict((follows::user_id, follows::crate_id))
+        .do_update();
+
+    // Only notify about releases after the notifications were switched on,
+    // instead of everything that happened since the crate was followed.
+    if previous.is_some_and(|previous| previous.is_enabled()) {
+        query
+            .set((
+                follows::notify_email.eq(excluded(follows::notify_email)),
+                follows::webhook_url.eq(excluded(follows::webhook_url)),
+            ))
+            .execute(&mut conn)
+            .await?;
+    } else {
+        query
+            .set((
+                follows::notify_email.eq(excluded(follows::notify_email)),
+                follows::webhook_url.eq(excluded(follows::webhook_url)),
+                follows::notified_at.eq(now),
+            ))
+            .execute(&mut conn)
+            .await?;
+    }
 
-    Ok(json!({ "following": following }))
+    Ok(json!({ "following": true, "notifications": notifications }))
 }
@@ -5,19 +5,23 @@
 //! `Cargo.toml` file.
 
 use crate::app::AppState;
+use crate::controllers::krate::redirect::check_redirect;
 use crate::controllers::krate::CratePath;
 use crate::models::{
-    Category, Crate, CrateCategory, CrateKeyword, Keyword, RecentCrateDownloads, TopVersions, User,
-    Version, VersionOwnerAction,
+    Category, Crate, CrateCategory, CrateKeyword, FundingLink, Keyword, LatestVersions,
+    RecentCrateDownloads, TopVersions, User, Version, VersionOwnerAction,
 };
 use crate::schema::*;
+use crate::surrogate_keys::SurrogateKeys;
 use crate::util::errors::{bad_request, crate_not_found, AppResult, BoxedAppError};
-use crate::views::{EncodableCategory, EncodableCrate, EncodableKeyword, EncodableVersion};
+use crate::views::{v2, EncodableCategory, EncodableCrate, EncodableKeyword, EncodableVersion};
 use axum::extract::{FromRequestParts, Query};
+use axum::Json;
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
+use http::Uri;
 use std::str::FromStr;
 
 #[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
@@ -47,9 +51,13 @@ pub struct FindQueryParams {
     tag = "crates",
     responses((status = 200, description = "Successful Response")),
 )]
-pub async fn find_new_crate(app: AppState, params: FindQueryParams) -> AppResult<ErasedJson> {
+pub async fn find_new_crate(
+    app: AppState,
+    params: FindQueryParams,
+    uri: Uri,
+) -> AppResult<(SurrogateKeys, ErasedJson)> {
     let name = "new".to_string();
-    find_crate(app, CratePath { name }, params).await
+    find_crate(app, CratePath { name }, params, uri).await
 }
 
 /// Get crate metadata.
@@ -64,33 +72,132 @@ pub async fn find_crate(
     app: AppState,
     path: CratePath,
     params: FindQueryParams,
-) -> AppResult<ErasedJson> {
-    let mut conn = app.db_read().await?;
-
+    uri: Uri,
+) -> AppResult<(SurrogateKeys, ErasedJson)> {
     let include = params
         .include
         .map(|mode| ShowIncludeMode::from_str(&mode))
         .transpose()?
         .unwrap_or_default();
 
-    let (krate, downloads, default_version, yanked): (Crate, i64, Option<String>, Option<bool>) =
-        Crate::by_name(&path.name)
-            .inner_join(crate_downloads::table)
-            .left_join(default_versions::table)
-            .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
-            .select((
-                Crate::as_select(),
-                crate_downloads::downloads,
-                versions::num.nullable(),
-                versions::yanked.nullable(),
-            ))
-            .first(&mut conn)
-            .await
-            .optional()?
-            .ok_or_else(|| crate_not_found(&path.name))?;
+    let metadata = load_crate(&app, &path.name, &include, &uri).await?;
+
+    let json = json!({
+        "crate": metadata.krate,
+        "versions": metadata.versions,
+        "keywords": metadata.keywords,
+        "categories": metadata.categories,
+    });
+
+    Ok((SurrogateKeys::krate(&path.name), json))
+}
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct FindQueryParamsV2 {
+    /// Related resources to include in the response.
+    ///
+    /// Valid values: `versions`, `keywords`, `categories`, `downloads`,
+    /// `default_version`, or `full`.
+    ///
+    /// Defaults to no related resources.
+    ///
+    /// This parameter expects a comma-separated list of values.
+    include: Option<String>,
+}
+
+/// Get crate metadata.
+///
+/// Returns the same data as the `GET /api/v1/crates/{name}` endpoint, but
+/// with the crate in the `data` field and the related resources in the
+/// `included` field of the response.
+#[utoipa::path(
+    get,
+    path = "/api/v2/crates/{name}",
+    params(CratePath, FindQueryParamsV2),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn find_crate_v2(
+    app: AppState,
+    path: CratePath,
+    params: FindQueryParamsV2,
+    uri: Uri,
+) -> AppResult<(
+    SurrogateKeys,
+    Json<v2::Document<v2::Crate, v2::CrateIncluded>>,
+)> {
+    let include = params
+        .include
+        .map(|mode| ShowIncludeMode::from_str(&mode))
+        .transpose()?
+        .unwrap_or_else(ShowIncludeMode::none);
+
+    let metadata = load_crate(&app, &path.name, &include, &uri).await?;
+
+    let included = v2::CrateIncluded {
+        versions: metadata
+            .versions
+            .map(|versions| versions.into_iter().map(Into::into).collect()),
+        keywords: metadata
+            .keywords
+            .map(|keywords| keywords.into_iter().map(Into::into).collect()),
+        categories: metadata
+            .categories
+            .map(|categories| categories.into_iter().map(Into::into).collect()),
+    };
+
+    let document = v2::Document {
+        data: metadata.krate.into(),
+        included: Some(included),
+    };
+
+    Ok((SurrogateKeys::krate(&path.name), Json(document)))
+}
+
+/// The crate metadata that is shared by the `v1` and `v2` endpoints.
+struct CrateMetadata {
+    krate: EncodableCrate,
+    versions: Option<Vec<EncodableVersion>>,
+    keywords: Option<Vec<EncodableKeyword>>,
+    categories: Option<Vec<EncodableCategory>>,
+}
+
+async fn load_crate(
+    app: &AppState,
+    name: &str,
+    include: &ShowIncludeMode,
+    uri: &Uri,
+) -> AppResult<CrateMetadata> {
+    let mut conn = app.db_read().await?;
+    check_redirect(&mut conn, name, uri).await?;
+
+    let (krate, downloads, default_version, yanked, msrv): (
+        Crate,
+        i64,
+        Option<String>,
+        Option<bool>,
+        Option<String>,
+    ) = Crate::by_name(name)
+        .inner_join(crate_downloads::table)
+        .left_join(default_versions::table)
+        .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .select((
+            Crate::as_select(),
+            crate_downloads::downloads,
+            versions::num.nullable(),
+            versions::yanked.nullable(),
+            versions::rust_version.nullable(),
+        ))
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| crate_not_found(name))?;
 
     let mut versions_publishers_and_audit_actions = if include.versions {
         let versions_and_publishers: Vec<(Version, Option<User>)> = Version::belonging_to(&krate)
+            .filter(Version::not_deleted())
             .left_outer_join(users::table)
             .select(<(Version, Option<User>)>::as_select())
             .order_by(versions::id.desc())
@@ -173,13 +280,33 @@ pub async fn find_crate(
             .map(|(v, _, _)| (v.created_at, v.num));
         Some(TopVersions::from_date_version_pairs(pairs))
     } else {
-        None
+        // The highest versions are saved in the `default_versions` table, so
+        // that crates with thousands of versions don't need to load all of
+        // them. Clients can use the paginated versions endpoint instead.
+        let latest = LatestVersions::load(krate.id, &mut conn).await?;
+        let newest: Option<String> = Version::belonging_to(&krate)
+            .filter(versions::yanked.eq(false))
+            .filter(Version::not_deleted())
+            .select(versions::num)
+            .order((versions::created_at.desc(), versions::id.desc()))
+            .first(&mut conn)
+            .await
+            .optional()?;
+
+        Some(TopVersions::from_latest(
+            latest.stable.as_deref(),
+            latest.prerelease.as_deref(),
+            newest.as_deref(),
+        ))
     };
 
-    let encodable_crate = EncodableCrate::from(
+    let funding_links = FundingLink::for_crate(&mut conn, krate.id).await?;
+
+    let mut encodable_crate = EncodableCrate::from(
         krate.clone(),
         default_version.as_deref(),
         yanked,
+        msrv.as_deref(),
         top_versions.as_ref(),
         ids,
         kws.as_deref(),
@@ -188,6 +315,7 @@ pub async fn find_crate(
         downloads,
         recent_downloads,
     );
+    encodable_crate.funding_links = funding_links.into_iter().map(Into::into).collect();
 
     let encodable_versions = versions_publishers_and_audit_actions.map(|vpa| {
         vpa.into_iter()
@@ -207,12 +335,12 @@ pub async fn find_crate(
             .collect::<Vec<EncodableCategory>>()
     });
 
-    Ok(json!({
-        "crate": encodable_crate,
-        "versions": encodable_versions,
-        "keywords": encodable_keywords,
-        "categories": encodable_cats,
-    }))
+    Ok(CrateMetadata {
+        krate: encodable_crate,
+        versions: encodable_versions,
+        keywords: encodable_keywords,
+        categories: encodable_cats,
+    })
 }
 
 #[derive(Debug)]
@@ -240,6 +368,17 @@ impl Default for ShowIncludeMode {
 }
 
 impl ShowIncludeMode {
+    fn none() -> Self {
+        Self {
+            versions: false,
+            keywords: false,
+            categories: false,
+            badges: false,
+            downloads: false,
+            default_version: false,
+        }
+    }
+
     const INVALID_COMPONENT: &'static str =
         "invalid component for ?include= (expected 'versions', 'keywords', 'categories', 'badges', 'downloads', 'default_version', or 'full')";
 }
@@ -248,14 +387,7 @@ impl FromStr for ShowIncludeMode {
     type Err = BoxedAppError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut mode = Self {
-            versions: false,
-            keywords: false,
-            categories: false,
-            badges: false,
-            downloads: false,
-            default_version: false,
-        };
+        let mut mode = Self::none();
         for component in s.split(',') {
             match component {
                 "" => {}
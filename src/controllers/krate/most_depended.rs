@@ -0,0 +1,101 @@
+//! Endpoint for listing the crates with the most dependent crates, as
+//! counted by the `UpdateCrateDependentStats` background job.
+
+use crate::app::AppState;
+use crate::controllers::helpers::pagination::{
+    Paginated, PaginationOptions, PaginationQueryParams,
+};
+use crate::controllers::helpers::Paginate;
+use crate::schema::{crate_dependent_stats, crates, default_versions, versions};
+use crate::util::errors::{bad_request, AppResult};
+use crate::views::EncodableMostDependedCrate;
+use axum::extract::FromRequestParts;
+use axum_extra::extract::Query;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use http::request::Parts;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct MostDependedQueryParams {
+    /// The sort order of the crates.
+    ///
+    /// Valid values: `dependents`, and `weighted`. `weighted` ranks the
+    /// crates by the sum of the recent downloads of their dependent crates.
+    ///
+    /// Defaults to `dependents`.
+    sort: Option<String>,
+}
+
+/// List the most depended upon crates.
+///
+/// Returns the crates ordered by the number of distinct crates whose
+/// default version directly depends on them. Yanked dependent crates are
+/// not counted. The counts are updated once per day.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/most_depended",
+    params(MostDependedQueryParams, PaginationQueryParams),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_most_depended_crates(
+    state: AppState,
+    params: MostDependedQueryParams,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut query = crate_dependent_stats::table
+        .inner_join(crates::table)
+        .left_join(default_versions::table.on(default_versions::crate_id.eq(crates::id)))
+        .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .filter(crates::hidden_at.is_null())
+        .filter(crates::quarantined_at.is_null())
+        .select((
+            crates::name,
+            crates::description,
+            versions::num.nullable(),
+            crate_dependent_stats::dependents,
+            crate_dependent_stats::weighted_dependents,
+        ))
+        .into_boxed();
+
+    query = match params.sort.as_deref() {
+        Some("dependents") | None => {
+            query.order((crate_dependent_stats::dependents.desc(), crates::name.asc()))
+        }
+        Some("weighted") => query.order((
+            crate_dependent_stats::weighted_dependents.desc(),
+            crates::name.asc(),
+        )),
+        Some(sort) => return Err(bad_request(format!("invalid sort order: {sort}"))),
+    };
+
+    let query = query.pages_pagination(PaginationOptions::builder().gather(&req)?);
+
+    let mut conn = state.db_read().await?;
+    let data: Paginated<(String, Option<String>, Option<String>, i32, i64)> =
+        query.load(&mut conn).await?;
+    let total = data.total();
+
+    let crates = data
+        .into_iter()
+        .map(
+            |(name, description, default_version, dependents, weighted_dependents)| {
+                EncodableMostDependedCrate {
+                    name,
+                    description,
+                    default_version,
+                    dependents,
+                    weighted_dependents,
+                }
+            },
+        )
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "crates": crates,
+        "meta": { "total": total },
+    }))
+}
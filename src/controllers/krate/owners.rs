@@ -1,18 +1,22 @@
 //! All routes related to managing owners of a crate
 
+use crate::auth::AuthCheck;
 use crate::controllers::krate::CratePath;
+use crate::email::{i18n, Email};
 use crate::models::{krate::NewOwnerInvite, token::EndpointScope};
-use crate::models::{Crate, Owner, Rights, Team, User};
+use crate::models::{
+    AuditLogAction, Crate, NewAuditLogEntry, Owner, OwnerRole, Rights, Team, User,
+};
 use crate::util::errors::{bad_request, crate_not_found, custom, AppResult};
 use crate::views::EncodableOwner;
 use crate::{app::AppState, models::krate::OwnerAddError};
-use crate::{auth::AuthCheck, email::Email};
 use axum::Json;
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, RunQueryDsl};
+use fluent_bundle::FluentArgs;
 use http::request::Parts;
 use http::StatusCode;
 use secrecy::{ExposeSecret, SecretString};
@@ -129,6 +133,9 @@ pub async fn remove_owners(
 pub struct ChangeOwnersRequest {
     #[serde(alias = "users")]
     owners: Vec<String>,
+    /// The role that invited users will have once they accept the
+    /// invitation. Defaults to `admin`.
+    role: Option<OwnerRole>,
 }
 
 async fn modify_owners(
@@ -139,6 +146,7 @@ async fn modify_owners(
     add: bool,
 ) -> AppResult<ErasedJson> {
     let logins = body.owners;
+    let role = body.role.unwrap_or(OwnerRole::Admin);
 
     // Bound the number of invites processed per request to limit the cost of
     // processing them all.
@@ -156,6 +164,7 @@ async fn modify_owners(
         .await?;
 
     let user = auth.user();
+    let api_token_id = auth.api_token_id();
 
     let (comma_sep_msg, emails) = conn
         .transaction(|conn| {
@@ -169,9 +178,15 @@ async fn modify_owners(
 
                 let owners = krate.owners(conn).await?;
 
-                match user.rights(&app, &owners).await? {
+                match user.rights(&app, conn, &owners).await? {
                     Rights::Full => {}
                     // Yes!
+                    Rights::Publish if is_user_owner(&owners, user) => {
+                        return Err(custom(
+                            StatusCode::FORBIDDEN,
+                            "owners with the `publish` role don't have permission to modify owners",
+                        ));
+                    }
                     Rights::Publish => {
                         return Err(custom(
                             StatusCode::FORBIDDEN,
@@ -190,6 +205,9 @@ async fn modify_owners(
                 // the database transaction has committed.
                 let mut emails = Vec::with_capacity(logins.len());
 
+                // The owner changes to record in the audit log.
+                let mut audit_entries = Vec::with_capacity(logins.len());
+
                 let comma_sep_msg = if add {
                     let mut msgs = Vec::with_capacity(logins.len());
                     for login in &logins {
@@ -199,7 +217,7 @@ async fn modify_owners(
                             return Err(bad_request(format_args!("`{login}` is already an owner")));
                         }
 
-                        match krate.owner_add(&app, conn, user, login).await {
+                        match krate.owner_add(&app, conn, user, login, role).await {
                             // A user was successfully invited, and they must accept
                             // the invite, and a best-effort attempt should be made
                             // to email them the invite token for one-click
@@ -210,6 +228,12 @@ async fn modify_owners(
                                     invitee.gh_login, krate.name,
                                 ));
 
+                                let details = serde_json::json!({
+                                    "owner": invitee.gh_login,
+                                    "role": role,
+                                });
+                                audit_entries.push((AuditLogAction::OwnerInvite, details));
+
                                 if let Some(recipient) =
                                     invitee.verified_email(conn).await.ok().flatten()
                                 {
@@ -219,16 +243,36 @@ async fn modify_owners(
                                         domain: app.emails.domain.clone(),
                                         crate_name: krate.name.clone(),
                                         token,
+                                        locale: invitee.locale.clone(),
                                     });
                                 }
                             }
 
                             // A team was successfully invited. They are immediately
                             // added, and do not have an invite token.
-                            Ok(NewOwnerInvite::Team(team)) => msgs.push(format!(
-                                "team {} has been added as an owner of crate {}",
-                                team.login, krate.name
-                            )),
+                            Ok(NewOwnerInvite::Team(team)) => {
+                                msgs.push(format!(
+                                    "team {} has been added as an owner of crate {}",
+                                    team.login, krate.name
+                                ));
+
+                                let details = serde_json::json!({ "owner": team.login });
+                                audit_entries.push((AuditLogAction::OwnerAdd, details));
+                            }
+
+                            // An organization was added. Like teams, they are
+                            // immediately added as an owner.
+                            Ok(NewOwnerInvite::Organization(organization)) => {
+                                msgs.push(format!(
+                                    "organization {} has been added as an owner of crate {}",
+                                    organization.owner_login(),
+                                    krate.name
+                                ));
+
+                                let details =
+                                    serde_json::json!({ "owner": organization.owner_login() });
+                                audit_entries.push((AuditLogAction::OwnerAdd, details));
+                            }
 
                             // This user has a pending invite.
                             Err(OwnerAddError::AlreadyInvited(user)) => msgs.push(format!(
@@ -244,17 +288,42 @@ async fn modify_owners(
                 } else {
                     for login in &logins {
                         krate.owner_remove(conn, login).await?;
+
+                        let details = serde_json::json!({ "owner": login });
+                        audit_entries.push((AuditLogAction::OwnerRemove, details));
                     }
-                    if User::owning(&krate, conn).await?.is_empty() {
+                    let user_owners = User::owning(&krate, conn).await?;
+                    if user_owners.is_empty() {
                         return Err(bad_request(
                             "cannot remove all individual owners of a crate. \
                      Team member don't have permission to modify owners, so \
                      at least one individual owner is required.",
                         ));
                     }
+                    let is_admin =
+                        |owner: &Owner| matches!(owner, Owner::User(_, OwnerRole::Admin));
+                    if !user_owners.iter().any(is_admin) {
+                        return Err(bad_request(
+                            "cannot remove all owners with the `admin` role of a crate. \
+                     At least one owner with the `admin` role is required to \
+                     manage the owners of the crate.",
+                        ));
+                    }
                     "owners successfully removed".to_owned()
                 };
 
+                for (action, details) in audit_entries {
+                    NewAuditLogEntry::builder()
+                        .user_id(user.id)
+                        .maybe_api_token_id(api_token_id)
+                        .crate_id(krate.id)
+                        .action(action)
+                        .details(details)
+                        .build()
+                        .insert(conn)
+                        .await?;
+                }
+
                 Ok((comma_sep_msg, emails))
             }
             .scope_boxed()
@@ -265,8 +334,13 @@ async fn modify_owners(
     // committed.
     for email in emails {
         let addr = email.recipient_email_address().to_string();
+        let key = email.idempotency_key();
 
-        if let Err(e) = app.emails.send(&addr, email).await {
+        if let Err(e) = app
+            .emails
+            .send_idempotent(&mut conn, &key, &addr, email)
+            .await
+        {
             warn!("Failed to send co-owner invite email: {e}");
         }
     }
@@ -274,6 +348,13 @@ async fn modify_owners(
     Ok(json!({ "msg": comma_sep_msg, "ok": true }))
 }
 
+/// Returns `true` if `user` is one of the user owners in `owners`.
+fn is_user_owner(owners: &[Owner], user: &User) -> bool {
+    owners
+        .iter()
+        .any(|owner| matches!(owner, Owner::User(owner, _) if owner.id == user.id))
+}
+
 pub struct OwnerInviteEmail {
     /// The destination email address for this email.
     recipient_email_address: String,
@@ -283,31 +364,39 @@ pub struct OwnerInviteEmail {
     domain: String,
     crate_name: String,
     token: SecretString,
+    locale: Option<String>,
 }
 
 impl OwnerInviteEmail {
     pub fn recipient_email_address(&self) -> &str {
         &self.recipient_email_address
     }
+
+    /// The key that ensures the invitation is only sent once to the recipient.
+    pub fn idempotency_key(&self) -> String {
+        format!(
+            "crate-owner-invite:{}:{}",
+            self.token.expose_secret(),
+            self.recipient_email_address
+        )
+    }
 }
 
 impl Email for OwnerInviteEmail {
     fn subject(&self) -> String {
-        format!(
-            "crates.io: Ownership invitation for \"{}\"",
-            self.crate_name
-        )
+        let mut args = FluentArgs::new();
+        args.set("crate_name", self.crate_name.as_str());
+
+        i18n::translate(self.locale.as_deref(), "owner-invite-subject", &args)
     }
 
     fn body(&self) -> String {
-        format!(
-            "{user_name} has invited you to become an owner of the crate {crate_name}!\n
-Visit https://{domain}/accept-invite/{token} to accept this invitation,
-or go to https://{domain}/me/pending-invites to manage all of your crate ownership invitations.",
-            user_name = self.inviter,
-            domain = self.domain,
-            crate_name = self.crate_name,
-            token = self.token.expose_secret(),
-        )
+        let mut args = FluentArgs::new();
+        args.set("user_name", self.inviter.as_str());
+        args.set("domain", self.domain.as_str());
+        args.set("crate_name", self.crate_name.as_str());
+        args.set("token", self.token.expose_secret());
+
+        i18n::translate(self.locale.as_deref(), "owner-invite-body", &args)
     }
 }
@@ -0,0 +1,122 @@
+//! Minimal HTML rendering of crate pages.
+//!
+//! The crate pages are usually rendered by the Ember.js frontend, which
+//! doesn't help clients that don't run JavaScript, like link preview bots
+//! and some crawlers. The `ember_html` middleware passes their requests for
+//! `/crates/{name}` to this endpoint instead, which renders the name,
+//! description, readme and recent versions of the crate on the server.
+
+use crate::app::AppState;
+use crate::controllers::krate::redirect::check_redirect;
+use crate::controllers::krate::CratePath;
+use crate::models::Version;
+use crate::schema::{readme_renderings, versions};
+use crate::surrogate_keys::SurrogateKeys;
+use crate::util::crawlers::is_crawler;
+use crate::util::errors::AppResult;
+use axum::response::{Html, IntoResponse, Response};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::{header, HeaderMap, Uri};
+use minijinja::{context, Environment, Value};
+
+/// The number of versions that are listed on the page.
+const MAX_VERSIONS: i64 = 20;
+
+/// Returns `true` if the path is the main page of a crate, like
+/// `/crates/serde`.
+pub fn is_crate_page(path: &str) -> bool {
+    path.strip_prefix("/crates/")
+        .is_some_and(|name| !name.is_empty() && !name.contains('/'))
+}
+
+/// Returns `true` if the client is unlikely to run the frontend, because it
+/// doesn't accept HTML, or because it identifies as a bot.
+pub fn prefers_rendered_page(headers: &HeaderMap, accepts_html: bool) -> bool {
+    if !accepts_html {
+        return true;
+    }
+
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    is_crawler(user_agent)
+}
+
+#[derive(Serialize)]
+struct PageVersion {
+    num: String,
+    created_at: String,
+}
+
+/// Renders the page of a crate as plain HTML.
+pub async fn render_crate_page(app: AppState, path: CratePath, uri: Uri) -> AppResult<Response> {
+    let mut conn = app.db_read().await?;
+    check_redirect(&mut conn, &path.name, &uri).await?;
+    let krate = path.load_crate(&mut conn).await?;
+
+    let versions: Vec<(i32, String, NaiveDateTime)> = versions::table
+        .filter(versions::crate_id.eq(krate.id))
+        .filter(versions::yanked.eq(false))
+        .filter(Version::not_deleted())
+        .order(versions::created_at.desc())
+        .limit(MAX_VERSIONS)
+        .select((versions::id, versions::num, versions::created_at))
+        .load(&mut conn)
+        .await?;
+
+    // The readme of the most recent version is shown, like on the crate
+    // page of the frontend.
+    let readme = match versions.first() {
+        Some((version_id, num, _)) => {
+            let checksum: Option<String> = readme_renderings::table
+                .find(version_id)
+                .select(readme_renderings::checksum)
+                .first::<Option<String>>(&mut conn)
+                .await
+                .optional()?
+                .flatten();
+
+            let result = app
+                .storage
+                .download_readme(&krate.name, num, checksum.as_deref())
+                .await;
+
+            result
+                .inspect_err(|error| debug!("Failed to load readme of {}: {error}", krate.name))
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+        }
+        None => None,
+    };
+
+    let versions = versions
+        .into_iter()
+        .map(|(_, num, created_at)| PageVersion {
+            num,
+            created_at: created_at.format("%Y-%m-%d").to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    let domain = &app.config.domain_name;
+    let context = context! {
+        name => krate.name,
+        description => krate.description,
+        homepage => krate.homepage,
+        documentation => krate.documentation,
+        repository => krate.repository,
+        url => format!("https://{domain}/crates/{}", krate.name),
+        // Readmes are sanitized when they are rendered during publishing.
+        readme => readme.map(Value::from_safe_string),
+        versions => versions,
+    };
+
+    let mut templates = Environment::new();
+    templates.add_template("crate.html", include_str!("page.html.j2"))?;
+    let html = templates.get_template("crate.html")?.render(context)?;
+
+    Ok((SurrogateKeys::krate(&krate.name), Html(html)).into_response())
+}
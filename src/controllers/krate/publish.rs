@@ -2,16 +2,13 @@
 
 use crate::app::AppState;
 use crate::auth::AuthCheck;
-use crate::worker::jobs::{
-    self, CheckTyposquat, SendPublishNotificationsJob, UpdateDefaultVersion,
-};
+use crate::worker::jobs::{self, CheckTyposquat, UpdateDefaultVersion};
 use axum::body::{Body, Bytes};
 use axum::Json;
 use cargo_manifest::{Dependency, DepsSet, TargetDepsSet};
 use chrono::{DateTime, SecondsFormat, Utc};
 use crates_io_tarball::{process_tarball, TarballError};
 use crates_io_worker::{BackgroundJob, EnqueueError};
-use diesel::dsl::{exists, select};
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
@@ -22,20 +19,28 @@ use http::request::Parts;
 use http::StatusCode;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::sync::OnceLock;
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio_util::io::StreamReader;
 use url::Url;
 
 use crate::models::{
-    default_versions::Version as DefaultVersion, Category, Crate, DependencyKind, Keyword,
-    NewCrate, NewVersion, NewVersionOwnerAction, Rights, VersionAction,
+    default_versions::Version as DefaultVersion, AuditLogAction, BroadcastWarning, Category, Crate,
+    CrateRedirect, DependencyKind, FundingLink, Keyword, NewAuditLogEntry, NewCrate,
+    NewPublishIdempotencyKey, NewVersion, NewVersionOwnerAction, PublishIdempotencyKey,
+    PublishMethod, PublishPolicyRule, ReservedPrefix, Rights, User, Version, VersionAction,
+    VersionFiles,
 };
 
+use crate::funding;
 use crate::licenses::parse_license_expr;
 use crate::middleware::log_request::RequestLogExt;
 use crate::models::token::EndpointScope;
+use crate::publish_policy::{self, PolicyViolations};
+use crate::publish_warnings::{self, TarballSize};
 use crate::rate_limiter::LimitedAction;
 use crate::schema::*;
+use crate::tarball_diff;
 use crate::util::errors::{bad_request, custom, internal, AppResult, BoxedAppError};
 use crate::views::{
     EncodableCrate, EncodableCrateDependency, GoodCrate, PublishMetadata, PublishWarnings,
@@ -49,10 +54,21 @@ const MISSING_RIGHTS_ERROR_MESSAGE: &str = "this crate exists but you don't seem
 
 const MAX_DESCRIPTION_LENGTH: usize = 1000;
 
+/// The header that clients can send to make retries of a publish request,
+/// e.g. after a network timeout, return the result of the original request
+/// instead of a "crate version is already uploaded" error.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
 /// Publish a new crate/version.
 ///
 /// Used by `cargo publish` to publish a new crate or to publish a new version of an
 /// existing crate.
+///
+/// Requests with an `Idempotency-Key` header return the result of the
+/// previous request of the same user with the same key for 24 hours, if it
+/// published the same `.crate` file.
 #[utoipa::path(
     put,
     path = "/api/v1/crates/new",
@@ -93,6 +109,8 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
     // Convert the version back to a string to deal with any inconsistencies
     let version_string = semver.to_string();
 
+    let idempotency_key = idempotency_key(&req)?;
+
     let request_log = req.request_log();
     request_log.add("crate_name", &*metadata.name);
     request_log.add("crate_version", &version_string);
@@ -143,6 +161,22 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         ))
     })?;
 
+    let max_upload_size = existing_crate
+        .as_ref()
+        .and_then(|c| c.max_upload_size())
+        .unwrap_or(app.config.max_upload_size);
+
+    // Retries of a successful request are answered before the rate limit is
+    // checked, since they don't publish anything.
+    if let Some(key) = idempotency_key {
+        let user_id = auth.user().id;
+        if let Some(previous) = PublishIdempotencyKey::find(&mut conn, user_id, key).await? {
+            request_log.add("idempotent_replay", true);
+            let tarball_bytes = read_tarball_bytes(&mut reader, max_upload_size).await?;
+            return replay_publish(previous, &metadata.name, &version_string, &tarball_bytes);
+        }
+    }
+
     // Use a different rate limit whether this is a new or an existing crate.
     let rate_limit_action = match existing_crate {
         Some(_) => LimitedAction::PublishUpdate,
@@ -153,11 +187,6 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         .check_rate_limit(auth.user().id, rate_limit_action, &mut conn)
         .await?;
 
-    let max_upload_size = existing_crate
-        .as_ref()
-        .and_then(|c| c.max_upload_size())
-        .unwrap_or(app.config.max_upload_size);
-
     let tarball_bytes = read_tarball_bytes(&mut reader, max_upload_size).await?;
     let content_length = tarball_bytes.len() as u64;
 
@@ -165,6 +194,14 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
     let max_unpack_size = std::cmp::max(app.config.max_unpack_size, max_upload_size as u64);
     let tarball_info = process_tarball(&pkg_name, &*tarball_bytes, max_unpack_size).await?;
 
+    let files = {
+        let tarball_bytes = tarball_bytes.clone();
+        app.blocking_pool
+            .run(move || tarball_diff::list_files(&tarball_bytes))
+            .await?
+            .map_err(|e| internal(format!("failed to list tarball files: {e}")))?
+    };
+
     // `unwrap()` is safe here since `process_tarball()` validates that
     // we only accept manifests with a `package` section and without
     // inheritance.
@@ -229,6 +266,19 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         validate_rust_version(rust_version)?;
     }
 
+    // Funding links from the manifest take precedence over a `FUNDING.yml`
+    // file in the package.
+    let funding_links = funding::from_manifest_metadata(package.metadata.as_ref())
+        .map_err(|error| bad_request(format!("invalid `package.metadata.funding`: {error}")))?;
+    let funding_links = if funding_links.is_empty() {
+        let funding_yml = tarball_info.funding_yml.as_deref();
+        funding_yml
+            .map(funding::from_funding_yml)
+            .unwrap_or_default()
+    } else {
+        funding_links
+    };
+
     let keywords = package
         .keywords
         .map(|it| it.as_local().unwrap())
@@ -322,12 +372,53 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         validate_dependency(dep)?;
     }
 
+    let policy_rules = PublishPolicyRule::all(&mut conn).await?;
+    let policy_metadata = publish_policy::CrateMetadata {
+        num_dependencies: deps.len(),
+        num_features,
+        license: license.as_deref(),
+        description: description.as_deref(),
+        documentation: documentation.as_deref(),
+        homepage: homepage.as_deref(),
+        readme: metadata.readme.as_deref(),
+        repository: repository.as_deref(),
+        rust_version: rust_version.as_deref(),
+        keywords: &keywords,
+        categories: &categories,
+    };
+
+    let violations = publish_policy::check(&policy_rules, &policy_metadata);
+    if !violations.is_empty() {
+        return Err(PolicyViolations::boxed(violations));
+    }
+
+    let broadcast_warnings = BroadcastWarning::active(&mut conn).await?;
+    let tarball_size = TarballSize {
+        size: content_length,
+        max_upload_size: max_upload_size as u64,
+    };
+    let warnings = publish_warnings::check(
+        &policy_metadata,
+        tarball_size,
+        &policy_rules,
+        &broadcast_warnings,
+    );
+
     let api_token_id = auth.api_token_id();
+    let publish_method = match api_token_id {
+        Some(_) => PublishMethod::ApiToken,
+        None => PublishMethod::Web,
+    };
     let user = auth.user();
 
+    // The tarball is staged before the transaction is committed, so it is
+    // deleted again right away if the transaction fails afterwards. If that
+    // fails too, the `ReconcileStagedCrateFiles` job cleans it up later.
+    let staged_version_id = &OnceLock::new();
+
     // Create a transaction on the database, if there are no errors,
     // commit the transactions to record a new or updated crate.
-    conn.transaction(|conn| async move {
+    let result = conn.transaction(|conn| async move {
         let name = metadata.name;
         let keywords = keywords.iter().map(|s| s.as_str()).collect::<Vec<_>>();
         let categories = categories.iter().map(|s| s.as_str()).collect::<Vec<_>>();
@@ -344,10 +435,22 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             max_features: None,
         };
 
-        if is_reserved_name(persist.name, conn).await? {
+        if app.reserved_names.is_reserved(&app, persist.name).await? {
             return Err(bad_request("cannot upload a crate with a reserved name"));
         }
 
+        if existing_crate.is_none() {
+            check_reserved_prefix(persist.name, user, conn).await?;
+
+            if let Some(target) = CrateRedirect::target_of(conn, persist.name).await? {
+                let detail = format!(
+                    "the crate name `{}` has been redirected to `{target}` and can not be registered again",
+                    persist.name
+                );
+                return Err(bad_request(detail));
+            }
+        }
+
         // To avoid race conditions, we try to insert
         // first so we know whether to add an owner
         let krate = match persist.create(conn, user.id).await.optional()? {
@@ -356,10 +459,17 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         };
 
         let owners = krate.owners(conn).await?;
-        if user.rights(&app, &owners).await? < Rights::Publish {
+        if user.rights(&app, conn, &owners).await? < Rights::Publish {
             return Err(custom(StatusCode::FORBIDDEN, MISSING_RIGHTS_ERROR_MESSAGE));
         }
 
+        if krate.is_frozen(conn).await? {
+            return Err(custom(StatusCode::FORBIDDEN, format!(
+                "crate `{}` has been frozen by the crates.io team and does not accept new versions",
+                krate.name
+            )));
+        }
+
         if krate.name != *name {
             return Err(bad_request(format_args!(
                 "crate was previously named `{}`",
@@ -398,6 +508,7 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             // to get here, and max upload sizes are way less than i32 max
             .size(content_length as i32)
             .published_by(user.id)
+            .publish_method(publish_method)
             .checksum(&hex_cksum)
             .maybe_links(package.links.as_deref())
             .maybe_rust_version(rust_version.as_deref())
@@ -430,6 +541,20 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             .insert(conn)
             .await?;
 
+        // Save the file listing, so that the files of the version can be
+        // browsed without downloading the `.crate` file.
+        VersionFiles::new(version.id, &files)?.insert(conn).await?;
+
+        NewAuditLogEntry::builder()
+            .user_id(user.id)
+            .maybe_api_token_id(api_token_id)
+            .crate_id(krate.id)
+            .action(AuditLogAction::Publish)
+            .details(serde_json::json!({ "version": version.num }))
+            .build()
+            .insert(conn)
+            .await?;
+
         // Link this new version to all dependencies
         add_dependencies(conn, &deps, version.id).await?;
 
@@ -464,17 +589,31 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
                     .execute(conn)
                     .await?;
             } else {
-                default_version = Some(existing_default_version.num.to_string());
+                let msrv = versions::table
+                    .find(existing_default_version.id)
+                    .select(versions::rust_version)
+                    .first(conn)
+                    .await?;
+
+                default_version = Some((existing_default_version.num.to_string(), msrv));
             }
 
             // Update the default version asynchronously in a background job
             // to ensure correctness and eventual consistency.
             UpdateDefaultVersion::new(krate.id).enqueue(conn).await?;
         } else {
+            let (latest_stable, latest_prerelease) = if semver.pre.is_empty() {
+                (Some(version.id), None)
+            } else {
+                (None, Some(version.id))
+            };
+
             diesel::insert_into(default_versions::table)
                 .values((
                     default_versions::crate_id.eq(krate.id),
                     default_versions::version_id.eq(version.id),
+                    default_versions::latest_stable_version_id.eq(latest_stable),
+                    default_versions::latest_prerelease_version_id.eq(latest_prerelease),
                 ))
                 .execute(conn)
                 .await?;
@@ -492,6 +631,8 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             return Err(bad_request(format!("The following category slugs are not currently supported on crates.io: {}\n\nSee https://{}/category_slugs for a list of supported slugs.", unknown_categories, domain)));
         }
 
+        FundingLink::update_crate(conn, krate.id, &funding_links).await?;
+
         let top_versions = krate.top_versions(conn).await?;
 
         let downloads: i64 = crate_downloads::table.select(crate_downloads::downloads)
@@ -515,21 +656,20 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             }
         }
 
-        // Upload crate tarball
-        app.storage.upload_crate_file(&krate.name, &version_string, tarball_bytes)
+        // Stage the crate tarball. It is moved to its public location by the
+        // `PublishCrateFile` job, which also updates the index, so that
+        // neither happens unless this transaction is committed.
+        app.storage.upload_staged_crate_file(version.id, tarball_bytes)
             .await
             .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
+        let _ = staged_version_id.set(version.id);
 
-        let git_index_job = jobs::SyncToGitIndex::new(&krate.name);
-        let sparse_index_job = jobs::SyncToSparseIndex::new(&krate.name);
-        let publish_notifications_job = SendPublishNotificationsJob::new(version.id);
+        let publish_crate_file_job = jobs::PublishCrateFile::new(version.id);
         let crate_feed_job = jobs::rss::SyncCrateFeed::new(krate.name.clone());
         let updates_feed_job = jobs::rss::SyncUpdatesFeed;
 
         tokio::try_join!(
-            git_index_job.enqueue(conn),
-            sparse_index_job.enqueue(conn),
-            publish_notifications_job.enqueue(conn),
+            publish_crate_file_job.enqueue(conn),
             crate_feed_job.enqueue(conn).or_else(|error| async move {
                 error!("Failed to enqueue `rss::SyncCrateFeed` job: {error}");
                 Ok::<_, EnqueueError>(None)
@@ -540,6 +680,13 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             }),
         )?;
 
+        if categories.is_empty() {
+            let suggest_categories_job = jobs::SuggestCategories::new(krate.id);
+            if let Err(error) = suggest_categories_job.enqueue(conn).await {
+                error!("Failed to enqueue `SuggestCategories` job: {error}");
+            }
+        }
+
         // Experiment: check new crates for potential typosquatting.
         if existing_crate.is_none() {
             let crates_feed_job = jobs::rss::SyncCratesFeed;
@@ -557,28 +704,98 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             )?;
         }
 
-        // The `other` field on `PublishWarnings` was introduced to handle a temporary warning
-        // that is no longer needed. As such, crates.io currently does not return any `other`
-        // warnings at this time, but if we need to, the field is available.
+        // Cargo only shows the `other` warnings, so the messages of the
+        // structured warnings are duplicated there.
         let warnings = PublishWarnings {
             invalid_categories: vec![],
             invalid_badges: vec![],
-            other: vec![],
+            other: warnings.iter().map(ToString::to_string).collect(),
+            structured: warnings,
         };
 
-        Ok(Json(GoodCrate {
+        let (default_version, default_msrv) =
+            default_version.unwrap_or((version_string, rust_version));
+
+        let good_crate = GoodCrate {
             krate: EncodableCrate::from_minimal(
                 krate,
-                default_version.or(Some(version_string)).as_deref(),
+                Some(&default_version),
                 Some(false),
+                default_msrv.as_deref(),
                 Some(&top_versions),
                 false,
                 downloads,
                 None,
             ),
             warnings,
-        }))
-    }.scope_boxed()).await
+        };
+
+        // Save the response, so that retries of this request return it
+        // instead of a "crate version is already uploaded" error.
+        if let Some(key) = idempotency_key {
+            NewPublishIdempotencyKey::builder()
+                .user_id(user.id)
+                .key(key)
+                .crate_name(&name)
+                .version(&version.num)
+                .checksum(&hex_cksum)
+                .response(serde_json::to_value(&good_crate)?)
+                .build()
+                .insert(conn)
+                .await?;
+        }
+
+        Ok(Json(good_crate))
+    }.scope_boxed()).await;
+
+    if result.is_err() {
+        if let Some(version_id) = staged_version_id.get() {
+            if let Err(error) = app.storage.delete_staged_crate_file(*version_id).await {
+                error!("Failed to delete staged crate file of failed publish: {error}");
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns the value of the `Idempotency-Key` header, if the request has one.
+fn idempotency_key(req: &Parts) -> AppResult<Option<&str>> {
+    let Some(value) = req.headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = value.to_str().map_err(|_| {
+        bad_request("the `Idempotency-Key` header must only contain ASCII characters")
+    })?;
+
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(bad_request(format!(
+            "the `Idempotency-Key` header must have between 1 and {MAX_IDEMPOTENCY_KEY_LENGTH} characters"
+        )));
+    }
+
+    Ok(Some(key))
+}
+
+/// Returns the response of a previous publish request with the same
+/// `Idempotency-Key`, unless the key was used to publish something else.
+fn replay_publish(
+    previous: PublishIdempotencyKey,
+    name: &str,
+    version: &str,
+    tarball_bytes: &[u8],
+) -> AppResult<Json<GoodCrate>> {
+    let checksum: String = Sha256::digest(tarball_bytes).encode_hex();
+    if previous.crate_name != name || previous.version != version || previous.checksum != checksum {
+        return Err(custom(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "the `Idempotency-Key` header has already been used for a different publish request",
+        ));
+    }
+
+    let response = serde_json::from_value(previous.response)?;
+    Ok(Json(response))
 }
 
 /// Counts the number of versions for `crate_id` that were published within
@@ -660,13 +877,28 @@ async fn read_tarball_bytes<R: AsyncRead + Unpin>(
     Ok(Bytes::from(tarball_bytes))
 }
 
-#[instrument(skip_all)]
-async fn is_reserved_name(name: &str, conn: &mut AsyncPgConnection) -> QueryResult<bool> {
-    select(exists(reserved_crate_names::table.filter(
-        canon_crate_name(reserved_crate_names::name).eq(canon_crate_name(name)),
-    )))
-    .get_result(conn)
-    .await
+/// Makes sure that the user is allowed to publish a new crate with the given
+/// name, if the name starts with a prefix that an organization reserved.
+async fn check_reserved_prefix(
+    name: &str,
+    user: &User,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<()> {
+    let Some((reserved, organization)) = ReservedPrefix::find_approved_match(conn, name).await?
+    else {
+        return Ok(());
+    };
+
+    let role = organization.role_of(conn, user.id).await?;
+    if role.is_some_and(|role| role.crate_rights() >= Rights::Publish) {
+        return Ok(());
+    }
+
+    let detail = format!(
+        "the crate name prefix `{}` is reserved by the organization `{}`",
+        reserved.prefix, organization.login
+    );
+    Err(custom(StatusCode::FORBIDDEN, detail))
 }
 
 fn validate_url(url: Option<&str>, field: &str) -> AppResult<()> {
@@ -702,12 +934,12 @@ fn duplicate_version_error(version: &str) -> BoxedAppError {
 }
 
 fn validate_rust_version(value: &str) -> AppResult<()> {
-    match semver::VersionReq::parse(value) {
-        // Exclude semver operators like `^` and pre-release identifiers
-        Ok(_) if value.chars().all(|c| c.is_ascii_digit() || c == '.') => Ok(()),
-        Ok(_) | Err(..) => Err(bad_request(
+    if Version::is_valid_rust_version(value) {
+        Ok(())
+    } else {
+        Err(bad_request(
             "failed to parse `Cargo.toml` manifest file\n\ninvalid `rust-version` value",
-        )),
+        ))
     }
 }
 
@@ -0,0 +1,208 @@
+//! Endpoints for requesting the name of an unused crate.
+//!
+//! The owners of the crate are notified of new requests, and the crates.io
+//! team decides about them after a waiting period, in which the owners can
+//! object to the request.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::krate::CratePath;
+use crate::email::Email;
+use crate::models::{
+    CrateReclaimRequest, NewCrateReclaimRequest, Owner, Rights, User, RECLAIM_WAITING_PERIOD_DAYS,
+};
+use crate::schema::{crate_reclaim_requests, users};
+use crate::util::errors::{bad_request, AppResult};
+use crate::views::EncodableCrateReclaimRequest;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error};
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+/// The maximum length of the reason of a reclaim request.
+const MAX_REASON_LENGTH: usize = 10_000;
+
+#[derive(Deserialize)]
+pub struct ReclaimRequest {
+    reclaim_request: ReclaimRequestData,
+}
+
+#[derive(Deserialize)]
+pub struct ReclaimRequestData {
+    /// Why the crate is unused, and what the requester plans to do with the
+    /// name.
+    reason: String,
+}
+
+/// Request to take over the name of an unused crate.
+///
+/// The owners of the crate are notified by email. Once the waiting period is
+/// over, the crates.io team either rejects the request or transfers the crate
+/// to the requester.
+#[utoipa::path(
+    post,
+    path = "/api/v1/crates/{name}/reclaim_requests",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_reclaim_request(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Json(body): Json<ReclaimRequest>,
+) -> AppResult<ErasedJson> {
+    let reason = body.reclaim_request.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("the reason must not be empty"));
+    }
+
+    if reason.chars().count() > MAX_REASON_LENGTH {
+        let detail = format!("the reason must not exceed {MAX_REASON_LENGTH} characters");
+        return Err(bad_request(detail));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let krate = path.load_crate(&mut conn).await?;
+    let owners = krate.owners(&mut conn).await?;
+    if user.rights(&app, &mut conn, &owners).await? >= Rights::Publish {
+        return Err(bad_request("owners of a crate can not reclaim it"));
+    }
+
+    let new_request = NewCrateReclaimRequest {
+        crate_id: krate.id,
+        requester_id: user.id,
+        reason,
+    };
+
+    let request = new_request
+        .insert(&mut conn)
+        .await
+        .map_err(|error| match error {
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                bad_request("you already requested this crate")
+            }
+            error => error.into(),
+        })?;
+
+    let eligible_at = request.eligible_at().format("%Y-%m-%d").to_string();
+    for owner in User::owning(&krate, &mut conn).await? {
+        let Owner::User(owner, _) = owner else {
+            continue;
+        };
+
+        let email = CrateReclaimRequestEmail {
+            requester: &user.gh_login,
+            krate: &krate.name,
+            reason,
+            eligible_at: &eligible_at,
+        };
+
+        let email_future = async {
+            if let Some(recipient) = owner.verified_email(&mut conn).await? {
+                app.emails.send(&recipient, email).await?
+            }
+
+            Ok::<_, anyhow::Error>(())
+        };
+
+        if let Err(err) = email_future.await {
+            warn!("Failed to send crate reclaim request email: {err}");
+        }
+    }
+
+    let request = EncodableCrateReclaimRequest::new(request, &krate.name, &user.gh_login);
+    Ok(json!({ "reclaim_request": request }))
+}
+
+/// List the reclaim requests of a crate.
+///
+/// Owners of the crate can see all requests, other users only see their
+/// own requests. The requests are sorted from newest to oldest.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/reclaim_requests",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_crate_reclaim_requests(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let krate = path.load_crate(&mut conn).await?;
+    let owners = krate.owners(&mut conn).await?;
+    let is_owner = user.rights(&app, &mut conn, &owners).await? >= Rights::Publish;
+
+    let mut query = crate_reclaim_requests::table
+        .inner_join(users::table)
+        .filter(crate_reclaim_requests::crate_id.eq(krate.id))
+        .select((CrateReclaimRequest::as_select(), users::gh_login))
+        .order(crate_reclaim_requests::id.desc())
+        .into_boxed();
+
+    if !is_owner {
+        query = query.filter(crate_reclaim_requests::requester_id.eq(user.id));
+    }
+
+    let requests: Vec<(CrateReclaimRequest, String)> = query.load(&mut conn).await?;
+
+    let requests = requests
+        .into_iter()
+        .map(|(request, requester)| {
+            EncodableCrateReclaimRequest::new(request, &krate.name, &requester)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "reclaim_requests": requests }))
+}
+
+/// Email template for notifying the owners of a crate that another user
+/// requested the name of the crate.
+struct CrateReclaimRequestEmail<'a> {
+    requester: &'a str,
+    krate: &'a str,
+    reason: &'a str,
+    eligible_at: &'a str,
+}
+
+impl Email for CrateReclaimRequestEmail<'_> {
+    fn subject(&self) -> String {
+        format!("crates.io: Request to reclaim the \"{}\" crate", self.krate)
+    }
+
+    fn body(&self) -> String {
+        format!(
+            "Hello,
+
+{requester} has asked the crates.io team to transfer your crate \"{krate}\" to them, because it appears to be unused:
+
+{reason}
+
+If you are still using the crate, please let us know at help@crates.io within {RECLAIM_WAITING_PERIOD_DAYS} days. From {eligible_at} on, the crates.io team may transfer the crate to {requester}.",
+            requester = self.requester,
+            krate = self.krate,
+            reason = self.reason,
+            eligible_at = self.eligible_at,
+        )
+    }
+}
@@ -0,0 +1,201 @@
+//! Endpoints for redirecting the name of a renamed or superseded crate to
+//! its successor.
+//!
+//! The owners of both crates request the redirect, and the crates.io team
+//! approves or rejects it. Once approved, the metadata endpoints of the old
+//! name respond with a `308 Permanent Redirect` to the same endpoint of the
+//! successor, and the old name can't be registered again.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::krate::{load_crate, CratePath};
+use crate::models::{CrateRedirect, NewCrateRedirect, Rights};
+use crate::util::errors::{bad_request, forbidden, AppError, AppResult};
+use crate::views::EncodableCrateRedirect;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::result::{DatabaseErrorKind, Error};
+use diesel_async::AsyncPgConnection;
+use http::request::Parts;
+use http::{header, StatusCode, Uri};
+use std::fmt;
+
+/// The maximum length of the reason of a redirect request.
+const MAX_REASON_LENGTH: usize = 10_000;
+
+#[derive(Deserialize)]
+pub struct RedirectRequest {
+    redirect_request: RedirectRequestData,
+}
+
+#[derive(Deserialize)]
+pub struct RedirectRequestData {
+    /// Name of the crate that supersedes this crate.
+    target: String,
+    /// Why the crate has been renamed or superseded.
+    reason: String,
+}
+
+/// Request to redirect a crate to its successor.
+///
+/// The user needs to be an owner of both crates. Once the crates.io team
+/// has approved the request, the metadata endpoints of this crate redirect
+/// to the successor, and the name of this crate can't be registered again.
+#[utoipa::path(
+    post,
+    path = "/api/v1/crates/{name}/redirect_requests",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_redirect_request(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Json(body): Json<RedirectRequest>,
+) -> AppResult<ErasedJson> {
+    let reason = body.redirect_request.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("the reason must not be empty"));
+    }
+
+    if reason.chars().count() > MAX_REASON_LENGTH {
+        let detail = format!("the reason must not exceed {MAX_REASON_LENGTH} characters");
+        return Err(bad_request(detail));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let krate = path.load_crate(&mut conn).await?;
+    let target = load_crate(&mut conn, body.redirect_request.target.trim()).await?;
+    if target.id == krate.id {
+        return Err(bad_request("a crate can not be redirected to itself"));
+    }
+
+    for krate in [&krate, &target] {
+        let owners = krate.owners(&mut conn).await?;
+        if user.rights(&app, &mut conn, &owners).await? < Rights::Publish {
+            let detail = format!("only owners of the `{}` crate can do this", krate.name);
+            return Err(forbidden(detail));
+        }
+    }
+
+    if let Some(successor) = CrateRedirect::target_of(&mut conn, &target.name).await? {
+        let detail = format!(
+            "the `{}` crate is already redirected to `{successor}`",
+            target.name
+        );
+        return Err(bad_request(detail));
+    }
+
+    let new_redirect = NewCrateRedirect {
+        name: &krate.name,
+        target_crate_id: target.id,
+        requested_by: user.id,
+        reason,
+    };
+
+    let redirect = new_redirect
+        .insert(&mut conn)
+        .await
+        .map_err(|error| match error {
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                bad_request("a redirect of this crate has already been requested")
+            }
+            error => error.into(),
+        })?;
+
+    let requested_by = Some(user.gh_login.clone());
+    let redirect = EncodableCrateRedirect::new(redirect, &target.name, requested_by);
+    Ok(json!({ "redirect_request": redirect }))
+}
+
+/// Returns a [`CrateRedirected`] error if the crate name has an approved
+/// redirect, so that metadata endpoints can redirect the client to the
+/// successor of the crate.
+pub async fn check_redirect(conn: &mut AsyncPgConnection, name: &str, uri: &Uri) -> AppResult<()> {
+    let Some(target) = CrateRedirect::target_of(conn, name).await? else {
+        return Ok(());
+    };
+
+    let location = redirect_location(uri, &target);
+    let name = name.to_string();
+    Err(Box::new(CrateRedirected {
+        name,
+        target,
+        location,
+    }))
+}
+
+/// Replaces the crate name in the path of a `/api/*/crates/{name}/…` URI
+/// with the given name, keeping the query string.
+fn redirect_location(uri: &Uri, target: &str) -> String {
+    let mut segments = uri.path().split('/').collect::<Vec<_>>();
+    if let Some(index) = segments.iter().position(|segment| *segment == "crates") {
+        if let Some(segment) = segments.get_mut(index + 1) {
+            *segment = target;
+        }
+    }
+
+    let mut location = segments.join("/");
+    if let Some(query) = uri.query() {
+        location.push('?');
+        location.push_str(query);
+    }
+
+    location
+}
+
+#[derive(Debug)]
+pub struct CrateRedirected {
+    name: String,
+    target: String,
+    location: String,
+}
+
+impl AppError for CrateRedirected {
+    fn response(&self) -> Response {
+        let detail = self.to_string();
+        let json = json!({ "errors": [{ "detail": detail }] });
+        let location = [(header::LOCATION, self.location.clone())];
+        (StatusCode::PERMANENT_REDIRECT, location, json).into_response()
+    }
+}
+
+impl fmt::Display for CrateRedirected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the crate `{}` has been renamed to `{}`",
+            self.name, self.target
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_location() {
+        let uri = Uri::from_static("/api/v1/crates/foo");
+        assert_eq!(redirect_location(&uri, "bar"), "/api/v1/crates/bar");
+
+        let uri = Uri::from_static("/api/v2/crates/foo/versions?per_page=10&sort=date");
+        assert_eq!(
+            redirect_location(&uri, "bar"),
+            "/api/v2/crates/bar/versions?per_page=10&sort=date"
+        );
+
+        let uri = Uri::from_static("/api/v1/crates/foo/1.0.0");
+        assert_eq!(redirect_location(&uri, "bar"), "/api/v1/crates/bar/1.0.0");
+    }
+}
@@ -0,0 +1,96 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::krate::CratePath;
+use crate::models::{NewCrateReport, ReportCategory};
+use crate::rate_limiter::LimitedAction;
+use crate::util::errors::{bad_request, AppResult};
+use crate::views::EncodableCrateReport;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use http::request::Parts;
+use lettre::Address;
+
+/// The maximum length of the description of a report.
+const MAX_DESCRIPTION_LENGTH: usize = 10_000;
+
+#[derive(Deserialize)]
+pub struct ReportRequest {
+    report: ReportRequestData,
+}
+
+#[derive(Deserialize)]
+pub struct ReportRequestData {
+    category: ReportCategory,
+    description: String,
+    /// Email address under which the reporter can be contacted about the
+    /// report. Defaults to the verified email address of the reporter.
+    contact: Option<String>,
+}
+
+/// Report a crate to the crates.io team.
+///
+/// Reports end up in the triage queue of the crates.io team. The reporter is
+/// notified by email once the report has been resolved.
+#[utoipa::path(
+    post,
+    path = "/api/v1/crates/{name}/report",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn report_crate(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Json(request): Json<ReportRequest>,
+) -> AppResult<ErasedJson> {
+    let request = request.report;
+
+    let description = request.description.trim();
+    if description.is_empty() {
+        return Err(bad_request("the report description must not be empty"));
+    }
+
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        let detail =
+            format!("the report description must not exceed {MAX_DESCRIPTION_LENGTH} characters");
+        return Err(bad_request(detail));
+    }
+
+    let contact = request.contact.as_deref().map(str::trim);
+    let contact = contact.filter(|contact| !contact.is_empty());
+    if let Some(contact) = contact {
+        contact
+            .parse::<Address>()
+            .map_err(|_| bad_request("invalid contact email address"))?;
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let krate = path.load_crate(&mut conn).await?;
+
+    app.rate_limiter
+        .check_rate_limit(user.id, LimitedAction::ReportCrate, &mut conn)
+        .await?;
+
+    let report = NewCrateReport::builder()
+        .crate_id(krate.id)
+        .reporter_id(user.id)
+        .category(request.category)
+        .description(description)
+        .maybe_contact(contact)
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    let report = EncodableCrateReport::new(report, &krate.name, Some(&user.gh_login));
+
+    Ok(json!({ "report": report }))
+}
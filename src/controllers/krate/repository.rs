@@ -0,0 +1,189 @@
+//! Endpoint for verifying that the owners of a crate control its repository.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::krate::CratePath;
+use crate::models::{AuditLogAction, NewAuditLogEntry, Rights};
+use crate::schema::crates;
+use crate::util::errors::{bad_request, forbidden, AppResult, BoxedAppError};
+use crate::worker::jobs::PurgeSurrogateKeys;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use crates_io_github::GitHubError;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use http::request::Parts;
+use oauth2::AccessToken;
+use url::Url;
+
+/// Verify the repository of a crate.
+///
+/// Checks with the GitHub account of the authenticated user that they have
+/// push access to the `repository` of the crate. Only repositories on GitHub
+/// can be verified for now. The verification is removed automatically when
+/// the `repository` of the crate changes.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/repository_verification",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn verify_repository(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let krate = path.load_crate(&mut conn).await?;
+    let owners = krate.owners(&mut conn).await?;
+    if user.rights(&app, &mut conn, &owners).await? != Rights::Full {
+        return Err(forbidden(
+            "only user owners of this crate can verify its repository",
+        ));
+    }
+
+    let Some(repository) = krate.repository else {
+        return Err(bad_request("the crate has no repository"));
+    };
+
+    let Some((owner, repo)) = parse_github_repository(&repository) else {
+        return Err(bad_request("only repositories on GitHub can be verified"));
+    };
+
+    let token = AccessToken::new(user.gh_access_token.clone());
+    let github_repository =
+        app.github
+            .repository(&owner, &repo, &token)
+            .await
+            .map_err(|error| match error {
+                GitHubError::NotFound(_) => bad_request(format_args!(
+                    "could not find the GitHub repository {owner}/{repo}"
+                )),
+                error => error.into(),
+            })?;
+
+    let can_push = github_repository
+        .permissions
+        .is_some_and(|permissions| permissions.admin || permissions.push);
+    if !can_push {
+        let detail = format!("you need push access to the GitHub repository {owner}/{repo}");
+        return Err(forbidden(detail));
+    }
+
+    let crate_id = krate.id;
+    let user_id = auth.user_id();
+    let api_token_id = auth.api_token_id();
+
+    let verified_at = conn
+        .transaction(|conn| {
+            async move {
+                // Only verify the repository that was checked, in case it was
+                // changed by a publish in the meantime.
+                let verified_at = diesel::update(crates::table)
+                    .filter(crates::id.eq(crate_id))
+                    .filter(crates::repository.eq(&repository))
+                    .set(crates::repository_verified_at.eq(Utc::now()))
+                    .returning(crates::repository_verified_at)
+                    .get_result::<Option<DateTime<Utc>>>(conn)
+                    .await
+                    .optional()?
+                    .flatten()
+                    .ok_or_else(|| bad_request("the repository of the crate has changed"))?;
+
+                NewAuditLogEntry::builder()
+                    .user_id(user_id)
+                    .maybe_api_token_id(api_token_id)
+                    .crate_id(crate_id)
+                    .action(AuditLogAction::RepositoryVerify)
+                    .details(serde_json::json!({ "repository": repository }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, BoxedAppError>(verified_at)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    PurgeSurrogateKeys::krate(&path.name)
+        .enqueue(&mut conn)
+        .await?;
+
+    Ok(json!({ "repository_verified_at": verified_at }))
+}
+
+/// Returns the owner and name of a `https://github.com/{owner}/{repo}`
+/// repository URL. Additional path segments, e.g. for crates in a
+/// subdirectory of the repository, are ignored.
+fn parse_github_repository(repository: &str) -> Option<(String, String)> {
+    let url = Url::parse(repository).ok()?;
+    if !matches!(url.scheme(), "https" | "http") {
+        return None;
+    }
+    if !matches!(url.host_str()?, "github.com" | "www.github.com") {
+        return None;
+    }
+
+    let mut segments = url.path_segments()?.filter(|segment| !segment.is_empty());
+    let owner = segments.next()?;
+    let repo = segments.next()?;
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+
+    fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    }
+
+    if !is_valid_name(owner) || !is_valid_name(repo) {
+        return None;
+    }
+
+    Some((owner.to_string(), repo.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_github_repository() {
+        let expected = Some(("rust-lang".to_string(), "cargo".to_string()));
+        let urls = [
+            "https://github.com/rust-lang/cargo",
+            "https://github.com/rust-lang/cargo/",
+            "https://github.com/rust-lang/cargo.git",
+            "http://www.github.com/rust-lang/cargo",
+            "https://github.com/rust-lang/cargo/tree/master/crates/cargo-util",
+        ];
+        for url in urls {
+            assert_eq!(parse_github_repository(url), expected, "{url}");
+        }
+
+        assert_eq!(
+            parse_github_repository("https://github.com/rust-lang"),
+            None
+        );
+        assert_eq!(parse_github_repository("https://gitlab.com/foo/bar"), None);
+        assert_eq!(parse_github_repository("git@github.com:foo/bar.git"), None);
+        assert_eq!(
+            parse_github_repository("https://github.com/foo/%2E%2E"),
+            None
+        );
+        assert_eq!(parse_github_repository("not a url"), None);
+    }
+}
@@ -1,15 +1,15 @@
 use crate::app::AppState;
 use crate::controllers::helpers::pagination::PaginationOptions;
+use crate::controllers::helpers::StreamingJson;
 use crate::controllers::krate::CratePath;
 use crate::models::{CrateName, User, Version, VersionOwnerAction};
 use crate::util::errors::AppResult;
 use crate::views::{EncodableDependency, EncodableVersion};
-use axum_extra::json;
-use axum_extra::response::ErasedJson;
 use crates_io_database::schema::{crates, users, versions};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use http::request::Parts;
+use serde_json::json;
 
 /// List reverse dependencies of a crate.
 #[utoipa::path(
@@ -23,7 +23,7 @@ pub async fn list_reverse_dependencies(
     app: AppState,
     path: CratePath,
     req: Parts,
-) -> AppResult<ErasedJson> {
+) -> AppResult<StreamingJson> {
     let mut conn = app.db_read().await?;
 
     let pagination_options = PaginationOptions::builder().gather(&req)?;
@@ -43,6 +43,7 @@ pub async fn list_reverse_dependencies(
 
     let versions_and_publishers: Vec<(Version, CrateName, Option<User>)> = versions::table
         .filter(versions::id.eq_any(version_ids))
+        .filter(Version::not_deleted())
         .inner_join(crates::table)
         .left_outer_join(users::table)
         .select(<(Version, CrateName, Option<User>)>::as_select())
@@ -64,9 +65,8 @@ pub async fn list_reverse_dependencies(
         })
         .collect::<Vec<_>>();
 
-    Ok(json!({
-        "dependencies": rev_deps,
-        "versions": versions,
-        "meta": { "total": total },
-    }))
+    Ok(StreamingJson::new()
+        .array("dependencies", rev_deps)
+        .array("versions", versions)
+        .field("meta", json!({ "total": total })))
 }
@@ -0,0 +1,36 @@
+use crate::app::AppState;
+use crate::controllers::krate::CratePath;
+use crate::models::CrateScore;
+use crate::util::errors::{custom, AppResult};
+use crate::views::EncodableCrateScore;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use http::StatusCode;
+
+/// Get the quality signals of a crate.
+///
+/// Returns a composite score between 0 and 1 together with the individual
+/// signals it is calculated from, so that tooling can base policy decisions
+/// on them. The signals are updated once per day, so newly published crates
+/// might not have a score yet.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/score",
+    params(CratePath),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_crate_score(state: AppState, path: CratePath) -> AppResult<ErasedJson> {
+    let mut conn = state.db_read().await?;
+    let crate_id = path.load_crate_id(&mut conn).await?;
+
+    let Some(score) = CrateScore::for_crate(&mut conn, crate_id).await? else {
+        let detail = format!(
+            "the score of crate `{}` has not been calculated yet",
+            path.name
+        );
+        return Err(custom(StatusCode::NOT_FOUND, detail));
+    };
+
+    Ok(json!({ "score": EncodableCrateScore::from(score) }))
+}
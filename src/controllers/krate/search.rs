@@ -6,7 +6,7 @@ use axum_extra::extract::Query;
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
 use derive_more::Deref;
-use diesel::dsl::{exists, InnerJoinQuerySource, LeftJoinQuerySource};
+use diesel::dsl::{exists, not, InnerJoinQuerySource, LeftJoinQuerySource};
 use diesel::prelude::*;
 use diesel::sql_types::Bool;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
@@ -16,8 +16,9 @@ use tracing::Instrument;
 use utoipa::IntoParams;
 
 use crate::app::AppState;
+use crate::controllers::helpers::crate_list::{CrateListInclude, CrateListIncludes};
 use crate::controllers::helpers::Paginate;
-use crate::models::{Crate, CrateOwner, OwnerKind, TopVersions, Version};
+use crate::models::{Crate, CrateOwner, Owner, OwnerKind, TopVersions, Version};
 use crate::schema::*;
 use crate::util::errors::{bad_request, AppResult};
 use crate::views::EncodableCrate;
@@ -68,11 +69,13 @@ pub async fn list_crates(
 
     let mut conn = app.db_read().await?;
 
-    use diesel::sql_types::Float;
+    use diesel::dsl::sql;
+    use diesel::sql_types::{Array, Float, Integer, Nullable};
     use seek::*;
 
     let filter_params = FilterParams::from(params, &req, &mut conn).await?;
     let sort = filter_params.sort.as_deref();
+    let include = filter_params.include()?;
 
     let selection = (
         ALL_COLUMNS,
@@ -82,6 +85,7 @@ pub async fn list_crates(
         0_f32.into_sql::<Float>(),
         versions::num.nullable(),
         versions::yanked.nullable(),
+        versions::rust_version.nullable(),
     );
 
     let mut seek: Option<Seek> = None;
@@ -113,6 +117,7 @@ pub async fn list_crates(
                     rank,
                     versions::num.nullable(),
                     versions::yanked.nullable(),
+                    versions::rust_version.nullable(),
                 ));
                 seek = Some(Seek::Relevance);
                 query = query.then_order_by(rank.desc())
@@ -125,6 +130,7 @@ pub async fn list_crates(
                     0_f32.into_sql::<Float>(),
                     versions::num.nullable(),
                     versions::yanked.nullable(),
+                    versions::rust_version.nullable(),
                 ));
                 seek = Some(Seek::Query);
             }
@@ -151,6 +157,15 @@ pub async fn list_crates(
     } else if sort == Some("new") {
         seek = Some(Seek::New);
         query = query.order((crates::created_at.desc(), crates::id.desc()));
+    } else if sort == Some("msrv") {
+        // The `rust-version` values are compared numerically, so that e.g.
+        // `1.9` sorts before `1.70`. Seek-based pagination is not supported
+        // for this sort order.
+        seek = None;
+        let msrv = sql::<Nullable<Array<Integer>>>(
+            "string_to_array(versions.rust_version, '.')::integer[]",
+        );
+        query = query.order((msrv.asc().nulls_last(), crates::name.asc()));
     } else {
         seek = seek.or(Some(Seek::Name));
         // Since the name is unique value, the inherent ordering becomes naturally unique.
@@ -207,11 +222,13 @@ pub async fn list_crates(
         )
     };
 
+    let crate_ids = data.iter().map(|r| r.krate.id).collect::<Vec<_>>();
     let crates = data.iter().map(|r| &r.krate).collect::<Vec<_>>();
 
     let span = info_span!("db.query", message = "SELECT ... FROM versions");
     let versions: Vec<Version> = Version::belonging_to(&crates)
         .filter(versions::yanked.eq(false))
+        .filter(Version::not_deleted())
         .select(Version::as_select())
         .load(&mut conn)
         .instrument(span)
@@ -221,18 +238,37 @@ pub async fn list_crates(
         .into_iter()
         .map(TopVersions::from_versions);
 
+    let span = info_span!(
+        "db.query",
+        message = "SELECT ... FROM crate_owners, keywords, categories"
+    );
+    let mut includes = CrateListIncludes::load(&mut conn, &crate_ids, include)
+        .instrument(span)
+        .await?;
+
     let crates = versions
         .zip(data)
         .map(|(max_version, record)| {
-            EncodableCrate::from_minimal(
+            let crate_id = record.krate.id;
+            let keywords = includes.take_keywords(crate_id);
+            let categories = includes.take_categories(crate_id);
+            let mut krate = EncodableCrate::from(
                 record.krate,
                 record.default_version.as_deref(),
                 record.yanked,
+                record.msrv.as_deref(),
                 Some(&max_version),
+                None,
+                keywords.as_deref(),
+                categories.as_deref(),
                 record.exact_match,
                 record.downloads,
                 Some(record.recent_downloads.unwrap_or(0)),
-            )
+            );
+            krate.owners = includes
+                .take_owners(crate_id)
+                .map(|owners| owners.into_iter().map(Owner::into).collect());
+            krate
         })
         .collect::<Vec<_>>();
 
@@ -253,7 +289,7 @@ pub struct ListQueryParams {
     /// The sort order of the crates.
     ///
     /// Valid values: `alphabetical`, `relevance`, `downloads`,
-    /// `recent-downloads`, `recent-updates`, `new`.
+    /// `recent-downloads`, `recent-updates`, `new`, `msrv`.
     ///
     /// Defaults to `relevance` if `q` is set, otherwise `alphabetical`.
     sort: Option<String>,
@@ -267,6 +303,12 @@ pub struct ListQueryParams {
     #[param(example = "yes")]
     include_yanked: Option<String>,
 
+    /// Set to `yes` to include crates that their owners marked as internal
+    /// or test crates. They are always included when listing the crates of
+    /// a user or team, or crates by name.
+    #[param(example = "yes")]
+    include_internal: Option<String>,
+
     /// If set, only return crates that belong to this category, or one
     /// of its subcategories.
     #[param(inline)]
@@ -310,6 +352,15 @@ pub struct ListQueryParams {
     #[serde(rename = "ids[]", default)]
     #[param(inline)]
     ids: Vec<StringExclNull>,
+
+    /// Additional data to include for each crate.
+    ///
+    /// Valid values: `owners`, `keywords`, and `categories`.
+    ///
+    /// Defaults to no additional data.
+    ///
+    /// This parameter expects a comma-separated list of values.
+    include: Option<String>,
 }
 
 impl ListQueryParams {
@@ -317,6 +368,30 @@ impl ListQueryParams {
         let include_yanked = self.include_yanked.as_ref();
         include_yanked.map(|s| s == "yes").unwrap_or(true)
     }
+
+    fn include_internal(&self) -> bool {
+        self.include_internal.as_deref() == Some("yes")
+            || self.user_id.is_some()
+            || self.team_id.is_some()
+            || !self.ids.is_empty()
+    }
+
+    fn include(&self) -> AppResult<CrateListInclude> {
+        const INVALID_COMPONENT: &str =
+            "invalid component for ?include= (expected 'owners', 'keywords', or 'categories')";
+
+        let mut include = CrateListInclude::default();
+        for component in self.include.iter().flat_map(|s| s.split(',')) {
+            match component {
+                "" => {}
+                "owners" => include.owners = true,
+                "keywords" => include.keywords = true,
+                "categories" => include.categories = true,
+                _ => return Err(bad_request(INVALID_COMPONENT)),
+            }
+        }
+        Ok(include)
+    }
 }
 
 #[derive(Deref)]
@@ -354,7 +429,12 @@ impl FilterParams {
 
 impl FilterParams {
     fn make_query(&self) -> crates::BoxedQuery<'_, diesel::pg::Pg> {
-        let mut query = crates::table.into_boxed();
+        // Crates that have been hidden or quarantined by the crates.io team
+        // are never listed.
+        let mut query = crates::table
+            .filter(crates::hidden_at.is_null())
+            .filter(crates::quarantined_at.is_null())
+            .into_boxed();
 
         if let Some(q_string) = &self.q_string {
             if !q_string.is_empty() {
@@ -404,7 +484,15 @@ impl FilterParams {
                     crates_keywords::table
                         .select(crates_keywords::crate_id)
                         .inner_join(keywords::table)
-                        .filter(lower(keywords::keyword).eq(lower(kw.as_str()))),
+                        .filter(
+                            lower(keywords::keyword)
+                                .eq(lower(kw.as_str()))
+                                .or(keywords::id.eq_any(
+                                    keyword_aliases::table
+                                        .filter(keyword_aliases::alias.eq(lower(kw.as_str())))
+                                        .select(keyword_aliases::keyword_id),
+                                )),
+                        ),
                 ),
             );
         } else if let Some(letter) = self.letter {
@@ -438,11 +526,20 @@ impl FilterParams {
             query = query.filter(crates::name.eq_any(self.ids.iter().map(|s| s.as_str())));
         }
 
+        if !self.include_internal() {
+            query = query.filter(not(exists(
+                crate_settings::table
+                    .filter(crate_settings::crate_id.eq(crates::id))
+                    .filter(crate_settings::internal),
+            )));
+        }
+
         if !self.include_yanked() {
             query = query.filter(exists(
                 versions::table
                     .filter(versions::crate_id.eq(crates::id))
-                    .filter(versions::yanked.eq(false)),
+                    .filter(versions::yanked.eq(false))
+                    .filter(Version::not_deleted()),
             ));
         }
 
@@ -704,6 +801,7 @@ struct Record {
     rank: f32,
     default_version: Option<String>,
     yanked: Option<bool>,
+    msrv: Option<String>,
 }
 
 type QuerySource = LeftJoinQuerySource<
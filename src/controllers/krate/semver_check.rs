@@ -0,0 +1,315 @@
+//! Compatibility check between two versions of a crate.
+//!
+//! The check helps users to decide whether they can update a dependency by
+//! bumping its version requirement, and which releases they would skip.
+
+use crate::app::AppState;
+use crate::controllers::krate::CratePath;
+use crate::schema::versions;
+use crate::util::errors::{bad_request, version_not_found, AppResult};
+use axum::extract::Query;
+use axum::Json;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use semver::{Prerelease, Version};
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SemverCheckParams {
+    /// The version that is currently used, e.g. `1.2.0`.
+    from: String,
+    /// The version to update to, e.g. `2.0.0`.
+    to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SemverCheck {
+    from: Release,
+    to: Release,
+    /// Whether the versions are semver incompatible according to the rules
+    /// of cargo, e.g. `1.2.0` and `2.0.0`, or `0.1.0` and `0.2.0`.
+    breaking: bool,
+    /// Whether `to` is lower than `from`.
+    downgrade: bool,
+    /// The releases between the two versions, from lowest to highest.
+    releases: Vec<Release>,
+    warnings: Vec<Warning>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Release {
+    num: String,
+    yanked: bool,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize)]
+struct Warning {
+    kind: WarningKind,
+    detail: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum WarningKind {
+    /// `to` is a pre-release, which cargo only selects if the version
+    /// requirement contains a pre-release.
+    ToIsPrerelease,
+    /// `from` is a pre-release, so its caret requirement also matches later
+    /// pre-releases, which may contain breaking changes.
+    FromIsPrerelease,
+    /// Some pre-release identifiers like `beta10` are compared as strings,
+    /// so they sort before e.g. `beta9`.
+    LexicalPrereleaseOrdering,
+}
+
+/// Check the compatibility of two versions of a crate.
+///
+/// Reports whether updating from one version to the other is a breaking
+/// change, lists the releases in between with their yank status, and warns
+/// about surprising pre-release ordering.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/semver_check",
+    params(CratePath, SemverCheckParams),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn check_semver_compatibility(
+    app: AppState,
+    path: CratePath,
+    Query(params): Query<SemverCheckParams>,
+) -> AppResult<Json<SemverCheck>> {
+    let from = parse_version("from", &params.from)?;
+    let to = parse_version("to", &params.to)?;
+
+    let mut conn = app.db_read().await?;
+    let crate_id = path.load_crate_id(&mut conn).await?;
+
+    let rows: Vec<(String, bool, NaiveDateTime)> = versions::table
+        .filter(versions::crate_id.eq(crate_id))
+        .filter(crate::models::Version::not_deleted())
+        .select((versions::num, versions::yanked, versions::created_at))
+        .load(&mut conn)
+        .await?;
+
+    let releases = rows
+        .into_iter()
+        .filter_map(|(num, yanked, created_at)| {
+            let version = Version::parse(&num).ok()?;
+            let release = Release {
+                num,
+                yanked,
+                created_at,
+            };
+            Some((version, release))
+        })
+        .collect::<Vec<_>>();
+
+    let find_release = |version: &Version| {
+        releases
+            .iter()
+            .find(|(v, _)| v == version)
+            .map(|(_, release)| release.clone())
+            .ok_or_else(|| version_not_found(&path.name, &version.to_string()))
+    };
+
+    let from_release = find_release(&from)?;
+    let to_release = find_release(&to)?;
+
+    Ok(Json(check(from, from_release, to, to_release, releases)))
+}
+
+fn parse_version(param: &str, value: &str) -> AppResult<Version> {
+    Version::parse(value.trim())
+        .map_err(|error| bad_request(format!("`{param}` is not a valid semver version: {error}")))
+}
+
+fn check(
+    from: Version,
+    from_release: Release,
+    to: Version,
+    to_release: Release,
+    releases: Vec<(Version, Release)>,
+) -> SemverCheck {
+    let downgrade = to < from;
+    let (low, high) = if downgrade {
+        (&to, &from)
+    } else {
+        (&from, &to)
+    };
+
+    let mut between = releases
+        .into_iter()
+        .filter(|(version, _)| version > low && version < high)
+        .collect::<Vec<_>>();
+
+    between.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut warnings = Vec::new();
+
+    if !to.pre.is_empty() {
+        let detail = format!(
+            "`{to}` is a pre-release, which cargo only selects if the version requirement also contains a pre-release, e.g. `\"{to}\"`"
+        );
+        warnings.push(Warning {
+            kind: WarningKind::ToIsPrerelease,
+            detail,
+        });
+    }
+
+    if !from.pre.is_empty() {
+        let detail = format!(
+            "the version requirement `\"{from}\"` also matches later pre-releases of `{}.{}.{}`, which may contain breaking changes",
+            from.major, from.minor, from.patch
+        );
+        warnings.push(Warning {
+            kind: WarningKind::FromIsPrerelease,
+            detail,
+        });
+    }
+
+    let lexical = [&from, &to]
+        .into_iter()
+        .chain(between.iter().map(|(version, _)| version))
+        .filter(|version| has_lexical_identifier(&version.pre))
+        .map(|version| format!("`{version}`"))
+        .collect::<Vec<_>>();
+
+    if !lexical.is_empty() {
+        let detail = format!(
+            "the pre-release identifiers of {} are compared as strings, so e.g. `beta10` sorts before `beta9`. Numbers in separate identifiers like `beta.10` are compared numerically.",
+            lexical.join(", ")
+        );
+        warnings.push(Warning {
+            kind: WarningKind::LexicalPrereleaseOrdering,
+            detail,
+        });
+    }
+
+    SemverCheck {
+        from: from_release,
+        to: to_release,
+        breaking: !is_compatible(&from, &to),
+        downgrade,
+        releases: between.into_iter().map(|(_, release)| release).collect(),
+        warnings,
+    }
+}
+
+/// Mirrors the rules of caret requirements in cargo, ignoring pre-releases:
+/// versions are compatible if their left-most non-zero component is equal.
+fn is_compatible(a: &Version, b: &Version) -> bool {
+    if a.major != b.major {
+        false
+    } else if a.major != 0 {
+        true
+    } else if a.minor != b.minor {
+        false
+    } else {
+        a.minor != 0 || a.patch == b.patch
+    }
+}
+
+/// Returns `true` if any identifier mixes letters and digits, like `rc2`.
+fn has_lexical_identifier(pre: &Prerelease) -> bool {
+    pre.split('.').any(|identifier| {
+        let has_digit = identifier.chars().any(|c| c.is_ascii_digit());
+        let is_numeric = identifier.chars().all(|c| c.is_ascii_digit());
+        has_digit && !is_numeric
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: &str) -> (Version, Release) {
+        let release = Release {
+            num: version.to_string(),
+            yanked: version.ends_with(".1"),
+            created_at: NaiveDateTime::default(),
+        };
+        (Version::parse(version).unwrap(), release)
+    }
+
+    fn run(from: &str, to: &str, versions: &[&str]) -> SemverCheck {
+        let releases = versions.iter().copied().map(release).collect();
+        let (from, from_release) = release(from);
+        let (to, to_release) = release(to);
+        check(from, from_release, to, to_release, releases)
+    }
+
+    fn nums(check: &SemverCheck) -> Vec<&str> {
+        check.releases.iter().map(|r| r.num.as_str()).collect()
+    }
+
+    fn kinds(check: &SemverCheck) -> Vec<WarningKind> {
+        check.warnings.iter().map(|w| w.kind).collect()
+    }
+
+    #[test]
+    fn test_is_compatible() {
+        let compatible =
+            |a, b| is_compatible(&Version::parse(a).unwrap(), &Version::parse(b).unwrap());
+
+        assert!(compatible("1.2.0", "1.9.3"));
+        assert!(!compatible("1.2.0", "2.0.0"));
+        assert!(compatible("0.2.0", "0.2.5"));
+        assert!(!compatible("0.2.0", "0.3.0"));
+        assert!(!compatible("0.0.1", "0.0.2"));
+        assert!(compatible("1.0.0-alpha.1", "1.0.0"));
+    }
+
+    #[test]
+    fn test_check() {
+        let versions = [
+            "1.2.0",
+            "1.2.1",
+            "1.3.0",
+            "2.0.0-rc.1",
+            "2.0.0",
+            "2.1.0",
+            "3.0.0",
+        ];
+
+        let check = run("1.2.0", "2.0.0", &versions);
+        assert!(check.breaking);
+        assert!(!check.downgrade);
+        assert_eq!(nums(&check), ["1.2.1", "1.3.0", "2.0.0-rc.1"]);
+        assert!(check.releases[0].yanked);
+        assert!(check.warnings.is_empty());
+
+        let check = run("2.1.0", "2.0.0", &versions);
+        assert!(!check.breaking);
+        assert!(check.downgrade);
+        assert!(check.releases.is_empty());
+
+        let check = run("1.3.0", "2.0.0-rc.1", &versions);
+        assert_eq!(kinds(&check), [WarningKind::ToIsPrerelease]);
+
+        let check = run("2.0.0-rc.1", "2.1.0", &versions);
+        assert!(!check.breaking);
+        assert_eq!(nums(&check), ["2.0.0"]);
+        assert_eq!(kinds(&check), [WarningKind::FromIsPrerelease]);
+    }
+
+    #[test]
+    fn test_lexical_prerelease_ordering() {
+        let versions = ["1.0.0-beta9", "1.0.0-beta10", "1.0.0"];
+
+        let check = run("1.0.0-beta9", "1.0.0", &versions);
+        assert!(check.releases.is_empty());
+        assert_eq!(
+            kinds(&check),
+            [
+                WarningKind::FromIsPrerelease,
+                WarningKind::LexicalPrereleaseOrdering
+            ]
+        );
+
+        let check = run("1.0.0-beta.9", "1.0.0-beta.10", &[]);
+        assert!(!kinds(&check).contains(&WarningKind::LexicalPrereleaseOrdering));
+    }
+}
@@ -0,0 +1,122 @@
+//! Endpoints for the settings of a crate that can be changed by its owners.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::krate::CratePath;
+use crate::models::{AuditLogAction, CrateSettings, NewAuditLogEntry, Rights};
+use crate::util::errors::{forbidden, AppResult, BoxedAppError};
+use crate::views::EncodableCrateSettings;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::AsyncConnection;
+use http::request::Parts;
+
+/// The settings that are changed by a request. Settings that are missing
+/// keep their current value.
+#[derive(Debug, Deserialize)]
+pub struct SettingsUpdate {
+    digest_emails: Option<bool>,
+    version_diffs: Option<bool>,
+    internal: Option<bool>,
+}
+
+/// Get the settings of a crate.
+///
+/// Only owners of the crate can see its settings.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/settings",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_crate_settings(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+    let owners = krate.owners(&mut conn).await?;
+    if auth.user().rights(&app, &mut conn, &owners).await? < Rights::Publish {
+        return Err(forbidden("only owners of this crate can see its settings"));
+    }
+
+    let settings = CrateSettings::for_crate(&mut conn, krate.id).await?;
+    let settings = EncodableCrateSettings::from(settings);
+
+    Ok(json!({ "settings": settings }))
+}
+
+/// Update the settings of a crate.
+///
+/// Only user owners of the crate can change its settings. Settings that are
+/// missing from the request body keep their current value.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/settings",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_crate_settings(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Json(update): Json<SettingsUpdate>,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+    let owners = krate.owners(&mut conn).await?;
+    if auth.user().rights(&app, &mut conn, &owners).await? != Rights::Full {
+        return Err(forbidden(
+            "only user owners of this crate can change its settings",
+        ));
+    }
+
+    let user_id = auth.user_id();
+    let api_token_id = auth.api_token_id();
+
+    let settings = conn
+        .transaction(|conn| {
+            async move {
+                let mut settings = CrateSettings::for_crate(conn, krate.id).await?;
+                settings.digest_emails = update.digest_emails.unwrap_or(settings.digest_emails);
+                settings.version_diffs = update.version_diffs.unwrap_or(settings.version_diffs);
+                settings.internal = update.internal.unwrap_or(settings.internal);
+                settings.upsert(conn).await?;
+
+                let settings = EncodableCrateSettings::from(settings);
+
+                NewAuditLogEntry::builder()
+                    .user_id(user_id)
+                    .maybe_api_token_id(api_token_id)
+                    .crate_id(krate.id)
+                    .action(AuditLogAction::CrateSettingsUpdate)
+                    .details(serde_json::to_value(&settings)?)
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, BoxedAppError>(settings)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(json!({ "settings": settings }))
+}
@@ -0,0 +1,200 @@
+//! Endpoints for the categories that are suggested for crates without
+//! categories by the `SuggestCategories` background job.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::ok_true;
+use crate::controllers::krate::CratePath;
+use crate::models::{Crate, Rights, User};
+use crate::schema::{categories, category_suggestions, crates_categories};
+use crate::util::errors::{bad_request, forbidden, AppResult, BoxedAppError};
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+use std::collections::BTreeSet;
+
+/// The maximum number of categories of a crate, as enforced by the publish
+/// endpoint.
+const MAX_CATEGORIES: usize = 5;
+
+#[derive(Debug, Serialize, Queryable)]
+struct SuggestedCategory {
+    slug: String,
+    category: String,
+    description: String,
+    score: f32,
+}
+
+async fn check_ownership(
+    app: &AppState,
+    conn: &mut AsyncPgConnection,
+    user: &User,
+    krate: &Crate,
+) -> AppResult<()> {
+    let owners = krate.owners(conn).await?;
+    if user.rights(app, conn, &owners).await? < Rights::Publish {
+        return Err(forbidden(
+            "only owners of this crate can manage its categories",
+        ));
+    }
+
+    Ok(())
+}
+
+/// List the suggested categories of a crate.
+///
+/// Only owners of the crate can see its suggested categories. The
+/// categories are sorted from best to worst match.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/suggested_categories",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_suggested_categories(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+    check_ownership(&app, &mut conn, auth.user(), &krate).await?;
+
+    let suggested_categories: Vec<SuggestedCategory> = category_suggestions::table
+        .inner_join(categories::table)
+        .filter(category_suggestions::crate_id.eq(krate.id))
+        .order((category_suggestions::score.desc(), categories::slug.asc()))
+        .select((
+            categories::slug,
+            categories::category,
+            categories::description,
+            category_suggestions::score,
+        ))
+        .load(&mut conn)
+        .await?;
+
+    Ok(json!({ "suggested_categories": suggested_categories }))
+}
+
+#[derive(Deserialize)]
+pub struct AcceptRequest {
+    /// Slugs of the suggested categories to add to the crate.
+    categories: BTreeSet<String>,
+}
+
+/// Add suggested categories to a crate.
+///
+/// The categories are added to the existing categories of the crate. Since
+/// publishing a new version replaces the categories of the crate with the
+/// ones in its `Cargo.toml` file, they should be added there as well.
+#[utoipa::path(
+    post,
+    path = "/api/v1/crates/{name}/suggested_categories/accept",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn accept_suggested_categories(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Json(request): Json<AcceptRequest>,
+) -> AppResult<Response> {
+    if request.categories.is_empty() {
+        return Err(bad_request("no categories to accept"));
+    }
+
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+    check_ownership(&app, &mut conn, auth.user(), &krate).await?;
+
+    let slugs = request.categories.iter().collect::<Vec<_>>();
+    let suggestions: Vec<(i32, String)> = category_suggestions::table
+        .inner_join(categories::table)
+        .filter(category_suggestions::crate_id.eq(krate.id))
+        .filter(categories::slug.eq_any(slugs))
+        .select((categories::id, categories::slug))
+        .load(&mut conn)
+        .await?;
+
+    let suggested_slugs = suggestions
+        .iter()
+        .map(|(_, slug)| slug)
+        .collect::<BTreeSet<_>>();
+    if let Some(slug) = request
+        .categories
+        .iter()
+        .find(|slug| !suggested_slugs.contains(slug))
+    {
+        let detail = format!("`{slug}` is not a suggested category of this crate");
+        return Err(bad_request(detail));
+    }
+
+    let existing: Vec<i32> = crates_categories::table
+        .filter(crates_categories::crate_id.eq(krate.id))
+        .select(crates_categories::category_id)
+        .load(&mut conn)
+        .await?;
+
+    let category_ids = suggestions
+        .iter()
+        .map(|(id, _)| *id)
+        .filter(|id| !existing.contains(id))
+        .collect::<Vec<_>>();
+
+    if existing.len() + category_ids.len() > MAX_CATEGORIES {
+        let detail = format!("expected at most {MAX_CATEGORIES} categories per crate");
+        return Err(bad_request(detail));
+    }
+
+    let accepted_ids = suggestions.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+
+    conn.transaction(|conn| {
+        async move {
+            let rows = category_ids
+                .iter()
+                .map(|category_id| {
+                    (
+                        crates_categories::crate_id.eq(krate.id),
+                        crates_categories::category_id.eq(category_id),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            diesel::insert_into(crates_categories::table)
+                .values(&rows)
+                .execute(conn)
+                .await?;
+
+            diesel::delete(category_suggestions::table)
+                .filter(category_suggestions::crate_id.eq(krate.id))
+                .filter(category_suggestions::category_id.eq_any(accepted_ids))
+                .execute(conn)
+                .await?;
+
+            Ok::<_, BoxedAppError>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    ok_true()
+}
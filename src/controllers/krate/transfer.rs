@@ -0,0 +1,355 @@
+//! Endpoints for transferring the ownership of a crate to a single new owner.
+//!
+//! Unlike adding a new owner and then removing the existing ones, a transfer
+//! is completed atomically once the recipient accepts it.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::ok_true;
+use crate::controllers::krate::CratePath;
+use crate::email::Email;
+use crate::models::token::EndpointScope;
+use crate::models::{
+    AuditLogAction, CrateTransfer, NewAuditLogEntry, NewCrateTransfer, Owner, Rights, User,
+};
+use crate::schema::users;
+use crate::util::errors::{bad_request, custom, forbidden, not_found, AppResult};
+use crate::views::EncodableCrateTransfer;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+use http::StatusCode;
+
+#[derive(Deserialize)]
+pub struct TransferRequest {
+    transfer: TransferRequestData,
+}
+
+#[derive(Deserialize)]
+pub struct TransferRequestData {
+    /// Login of the user that should become the only owner of the crate.
+    owner: String,
+}
+
+/// Request a transfer of the crate to a new owner.
+///
+/// The new owner has to accept the transfer, after which they become the
+/// only owner of the crate and all existing owners are removed. Requesting a
+/// transfer replaces any pending transfer of the crate.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/transfer",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn request_transfer(
+    app: AppState,
+    path: CratePath,
+    parts: Parts,
+    Json(body): Json<TransferRequest>,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ChangeOwners)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+
+    let user = auth.user();
+    let krate = path.load_crate(&mut conn).await?;
+
+    let owners = krate.owners(&mut conn).await?;
+    if user.rights(&app, &mut conn, &owners).await? != Rights::Full {
+        let detail = "only owners with the `admin` role can transfer crates";
+        return Err(custom(StatusCode::FORBIDDEN, detail));
+    }
+
+    let login = &body.transfer.owner;
+    let recipient = User::find_by_login(&mut conn, login)
+        .await
+        .optional()?
+        .ok_or_else(|| bad_request(format!("could not find user with login `{login}`")))?;
+
+    let is_only_owner = matches!(&owners[..], [Owner::User(owner, _)] if owner.id == recipient.id);
+    if is_only_owner {
+        let detail = format!("`{login}` is already the only owner of this crate");
+        return Err(bad_request(detail));
+    }
+
+    let new_transfer = NewCrateTransfer {
+        crate_id: krate.id,
+        requested_by: user.id,
+        recipient_id: recipient.id,
+    };
+
+    let transfer = new_transfer.insert(&mut conn).await?;
+
+    NewAuditLogEntry::builder()
+        .user_id(user.id)
+        .maybe_api_token_id(auth.api_token_id())
+        .crate_id(krate.id)
+        .action(AuditLogAction::TransferRequest)
+        .details(serde_json::json!({ "transfer_id": transfer.id, "recipient": recipient.gh_login }))
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    let email = CrateTransferRequestEmail {
+        requested_by: &user.gh_login,
+        krate: &krate.name,
+        domain: &app.emails.domain,
+    };
+
+    let email_future = async {
+        if let Some(recipient_email) = recipient.verified_email(&mut conn).await? {
+            app.emails.send(&recipient_email, email).await?
+        }
+
+        Ok::<_, anyhow::Error>(())
+    };
+
+    if let Err(err) = email_future.await {
+        warn!("Failed to send crate transfer request email: {err}");
+    }
+
+    let expires_at = transfer.expires_at(&app.config);
+    let transfer = EncodableCrateTransfer::new(transfer, &krate.name, user, &recipient, expires_at);
+
+    Ok(json!({ "transfer": transfer }))
+}
+
+/// Get the pending transfer of a crate.
+///
+/// Only the owners of the crate and the recipient of the transfer can see it.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/transfer",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_transfer(app: AppState, path: CratePath, parts: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::default().check(&parts, &mut conn).await?;
+
+    let user = auth.user();
+    let krate = path.load_crate(&mut conn).await?;
+
+    let transfer = CrateTransfer::find_pending(&mut conn, krate.id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    if transfer.recipient_id != user.id {
+        let owners = krate.owners(&mut conn).await?;
+        if user.rights(&app, &mut conn, &owners).await? != Rights::Full {
+            return Err(not_found());
+        }
+    }
+
+    let requested_by = load_user(&mut conn, transfer.requested_by).await?;
+    let recipient = load_user(&mut conn, transfer.recipient_id).await?;
+
+    let expires_at = transfer.expires_at(&app.config);
+    let transfer =
+        EncodableCrateTransfer::new(transfer, &krate.name, &requested_by, &recipient, expires_at);
+
+    Ok(json!({ "transfer": transfer }))
+}
+
+/// Cancel or decline the pending transfer of a crate.
+///
+/// Owners with the `admin` role can cancel the transfer, and the recipient
+/// can decline it.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/crates/{name}/transfer",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn cancel_transfer(app: AppState, path: CratePath, parts: Parts) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ChangeOwners)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+
+    let user = auth.user();
+    let krate = path.load_crate(&mut conn).await?;
+
+    let transfer = CrateTransfer::find_pending(&mut conn, krate.id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    if transfer.recipient_id != user.id {
+        let owners = krate.owners(&mut conn).await?;
+        if user.rights(&app, &mut conn, &owners).await? != Rights::Full {
+            let detail = "only owners with the `admin` role can cancel transfers";
+            return Err(forbidden(detail));
+        }
+    }
+
+    diesel::delete(&transfer).execute(&mut conn).await?;
+
+    ok_true()
+}
+
+/// Accept the pending transfer of a crate.
+///
+/// The authenticated user has to be the recipient of the transfer. All
+/// existing owners of the crate are removed, and the recipient becomes the
+/// only owner.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/transfer/accept",
+    params(CratePath),
+    security(("cookie" = [])),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn accept_transfer(
+    app: AppState,
+    path: CratePath,
+    parts: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+
+    let user = auth.user();
+    let krate = path.load_crate(&mut conn).await?;
+
+    let transfer = CrateTransfer::find_pending(&mut conn, krate.id)
+        .await?
+        .filter(|transfer| transfer.recipient_id == user.id)
+        .ok_or_else(not_found)?;
+
+    if transfer.is_expired(&app.config) {
+        let detail = format!(
+            "The transfer of the {} crate expired. \
+            Please reach out to an owner of the crate to request a new transfer.",
+            krate.name
+        );
+        return Err(custom(StatusCode::GONE, detail));
+    }
+
+    let previous_owners = User::owning(&krate, &mut conn).await?;
+
+    let transfer = transfer.accept(&mut conn).await?;
+
+    // Let the previous owners know that they no longer own the crate.
+    for owner in previous_owners {
+        let Owner::User(owner, _) = owner else {
+            continue;
+        };
+
+        if owner.id == user.id {
+            continue;
+        }
+
+        let email = CrateTransferCompletedEmail {
+            user: &owner.gh_login,
+            recipient: &user.gh_login,
+            krate: &krate.name,
+        };
+
+        let email_future = async {
+            if let Some(recipient_email) = owner.verified_email(&mut conn).await? {
+                app.emails.send(&recipient_email, email).await?
+            }
+
+            Ok::<_, anyhow::Error>(())
+        };
+
+        if let Err(err) = email_future.await {
+            warn!("Failed to send crate transfer completion email: {err}");
+        }
+    }
+
+    let requested_by = load_user(&mut conn, transfer.requested_by).await?;
+
+    let expires_at = transfer.expires_at(&app.config);
+    let transfer =
+        EncodableCrateTransfer::new(transfer, &krate.name, &requested_by, user, expires_at);
+
+    Ok(json!({ "transfer": transfer }))
+}
+
+async fn load_user(conn: &mut AsyncPgConnection, id: i32) -> QueryResult<User> {
+    users::table
+        .find(id)
+        .select(User::as_select())
+        .first(conn)
+        .await
+}
+
+/// Email template for asking a user to accept the transfer of a crate.
+struct CrateTransferRequestEmail<'a> {
+    requested_by: &'a str,
+    krate: &'a str,
+    domain: &'a str,
+}
+
+impl Email for CrateTransferRequestEmail<'_> {
+    fn subject(&self) -> String {
+        format!(
+            "crates.io: Ownership transfer request for \"{}\"",
+            self.krate
+        )
+    }
+
+    fn body(&self) -> String {
+        format!(
+            "{requested_by} has requested to transfer the crate {krate} to you!
+
+Once you accept the transfer, you will become the only owner of the crate, and all existing owners will be removed.
+
+Visit https://{domain}/crates/{krate}/transfer to accept or decline this transfer.",
+            requested_by = self.requested_by,
+            krate = self.krate,
+            domain = self.domain,
+        )
+    }
+}
+
+/// Email template for notifying the previous owners of a crate that the crate
+/// has been transferred.
+struct CrateTransferCompletedEmail<'a> {
+    user: &'a str,
+    recipient: &'a str,
+    krate: &'a str,
+}
+
+impl Email for CrateTransferCompletedEmail<'_> {
+    fn subject(&self) -> String {
+        format!("crates.io: Transferred \"{}\" crate", self.krate)
+    }
+
+    fn body(&self) -> String {
+        format!(
+            "Hi {},
+
+the \"{}\" crate has been transferred to {}, and you are no longer an owner of the crate.
+
+If you did not expect this transfer, please contact us at help@crates.io.",
+            self.user, self.krate, self.recipient
+        )
+    }
+}
@@ -0,0 +1,92 @@
+//! Endpoint for listing the crates with the fastest growing number of
+//! downloads, as ranked by the `UpdateTrendingCrates` background job.
+
+use crate::app::AppState;
+use crate::models::TrendingWindow;
+use crate::schema::{crates, default_versions, trending_crates, versions};
+use crate::util::errors::{bad_request, AppResult};
+use crate::views::EncodableTrendingCrate;
+use axum::extract::FromRequestParts;
+use axum_extra::extract::Query;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct TrendingQueryParams {
+    /// The time window that the growth of the downloads is calculated for.
+    ///
+    /// Valid values: `7d`, and `30d`.
+    ///
+    /// Defaults to `7d`.
+    window: Option<String>,
+}
+
+/// List trending crates.
+///
+/// Returns the crates whose downloads grew the most in the last complete
+/// days of the window compared to the window before, ordered by growth.
+/// Crates with too few downloads in the previous window are not included.
+/// The ranking is updated once per day.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/trending",
+    params(TrendingQueryParams),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_trending_crates(
+    state: AppState,
+    params: TrendingQueryParams,
+) -> AppResult<ErasedJson> {
+    let window = params
+        .window
+        .as_deref()
+        .map(str::parse::<TrendingWindow>)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+
+    let mut conn = state.db_read().await?;
+
+    let crates = trending_crates::table
+        .inner_join(crates::table)
+        .left_join(default_versions::table.on(default_versions::crate_id.eq(crates::id)))
+        .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .filter(trending_crates::window_days.eq(window.days()))
+        .filter(crates::hidden_at.is_null())
+        .filter(crates::quarantined_at.is_null())
+        .order(trending_crates::rank)
+        .select((
+            crates::name,
+            crates::description,
+            versions::num.nullable(),
+            trending_crates::downloads,
+            trending_crates::previous_downloads,
+            trending_crates::growth,
+        ))
+        .load::<(String, Option<String>, Option<String>, i64, i64, f64)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(
+            |(name, description, default_version, downloads, previous_downloads, growth)| {
+                EncodableTrendingCrate {
+                    name,
+                    description,
+                    default_version,
+                    downloads,
+                    previous_downloads,
+                    growth,
+                }
+            },
+        )
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "crates": crates,
+        "meta": { "window": window.to_string() },
+    }))
+}
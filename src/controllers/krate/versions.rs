@@ -16,13 +16,15 @@ use crate::app::AppState;
 use crate::controllers::helpers::pagination::{
     encode_seek, Page, PaginationOptions, PaginationQueryParams,
 };
+use crate::controllers::krate::redirect::check_redirect;
 use crate::controllers::krate::CratePath;
 use crate::models::{User, Version, VersionOwnerAction};
 use crate::schema::{users, versions};
+use crate::surrogate_keys::SurrogateKeys;
 use crate::util::errors::{bad_request, AppResult, BoxedAppError};
 use crate::util::string_excl_null::StringExclNull;
 use crate::util::RequestUtils;
-use crate::views::EncodableVersion;
+use crate::views::{v2, EncodableVersion};
 
 #[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
 #[from_request(via(Query))]
@@ -39,11 +41,16 @@ pub struct ListQueryParams {
 
     /// The sort order of the versions.
     ///
-    /// Valid values: `date`, and `semver`.
+    /// Valid values: `date`, `downloads`, and `semver`.
     ///
     /// Defaults to `semver`.
     sort: Option<String>,
 
+    /// Whether to include yanked versions.
+    ///
+    /// Defaults to `true`.
+    include_yanked: Option<bool>,
+
     /// If set, only versions with the specified semver strings are returned.
     #[serde(rename = "nums[]", default)]
     #[param(inline)]
@@ -60,6 +67,10 @@ impl ListQueryParams {
             .unwrap_or_default();
         Ok(include)
     }
+
+    fn include_yanked(&self) -> bool {
+        self.include_yanked.unwrap_or(true)
+    }
 }
 
 /// List all versions of a crate.
@@ -76,11 +87,7 @@ pub async fn list_versions(
     params: ListQueryParams,
     pagination: PaginationQueryParams,
     req: Parts,
-) -> AppResult<ErasedJson> {
-    let mut conn = state.db_read().await?;
-
-    let crate_id = path.load_crate_id(&mut conn).await?;
-
+) -> AppResult<(SurrogateKeys, ErasedJson)> {
     // To keep backward compatibility, we paginate only if per_page is provided
     let pagination = match pagination.per_page {
         Some(_) => Some(
@@ -92,12 +99,81 @@ pub async fn list_versions(
         None => None,
     };
 
+    let (versions, meta) = load_versions(&state, &path, &params, pagination, &req).await?;
+
+    let json = json!({ "versions": versions, "meta": meta });
+
+    Ok((SurrogateKeys::krate(&path.name), json))
+}
+
+/// List all versions of a crate.
+///
+/// Returns the same data as the `GET /api/v1/crates/{name}/versions`
+/// endpoint, but the versions are in the `data` field of the response, the
+/// release tracks are in the `included` field, and the list is always
+/// paginated.
+#[utoipa::path(
+    get,
+    path = "/api/v2/crates/{name}/versions",
+    params(CratePath, ListQueryParams, PaginationQueryParams),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_versions_v2(
+    state: AppState,
+    path: CratePath,
+    params: ListQueryParams,
+    req: Parts,
+) -> AppResult<(SurrogateKeys, ErasedJson)> {
+    #[derive(Serialize)]
+    struct Included {
+        release_tracks: ReleaseTracks,
+    }
+
+    let pagination = PaginationOptions::builder()
+        .enable_seek(true)
+        .enable_pages(false)
+        .gather(&req)?;
+
+    let (versions, meta) = load_versions(&state, &path, &params, Some(pagination), &req).await?;
+
+    let json = ErasedJson::new(v2::ListDocument {
+        data: versions.into_iter().map(v2::Version::from).collect(),
+        meta: v2::PaginationMeta {
+            total: meta.total,
+            next_page: meta.next_page,
+        },
+        included: meta
+            .release_tracks
+            .map(|release_tracks| Included { release_tracks }),
+    });
+
+    Ok((SurrogateKeys::krate(&path.name), json))
+}
+
+async fn load_versions(
+    state: &AppState,
+    path: &CratePath,
+    params: &ListQueryParams,
+    pagination: Option<PaginationOptions>,
+    req: &Parts,
+) -> AppResult<(Vec<EncodableVersion>, ResponseMeta)> {
+    let mut conn = state.db_read().await?;
+
+    check_redirect(&mut conn, &path.name, &req.uri).await?;
+    let crate_id = path.load_crate_id(&mut conn).await?;
+
     // Sort by semver by default
     let versions_and_publishers = match &params.sort.as_ref().map(|s| s.to_lowercase()).as_deref() {
         Some("date") => {
-            list_by_date(crate_id, pagination.as_ref(), &params, &req, &mut conn).await?
+            let seek = seek::Seek::Date;
+            list_by_column(crate_id, seek, pagination.as_ref(), params, req, &mut conn).await?
         }
-        _ => list_by_semver(crate_id, pagination.as_ref(), &params, &req, &mut conn).await?,
+        Some("downloads") => {
+            let seek = seek::Seek::Downloads;
+            list_by_column(crate_id, seek, pagination.as_ref(), params, req, &mut conn).await?
+        }
+        _ => list_by_semver(crate_id, pagination.as_ref(), params, req, &mut conn).await?,
     };
 
     let versions = versions_and_publishers
@@ -113,16 +189,18 @@ pub async fn list_versions(
         .map(|((v, pb), aas)| EncodableVersion::from(v, &path.name, pb, aas))
         .collect::<Vec<_>>();
 
-    Ok(json!({ "versions": versions, "meta": versions_and_publishers.meta }))
+    Ok((versions, versions_and_publishers.meta))
 }
 
-/// Seek-based pagination of versions by date
+/// Seek-based pagination of versions by date or by downloads
 ///
 /// # Panics
 ///
-/// This function will panic if `option` is built with `enable_pages` set to true.
-async fn list_by_date(
+/// This function will panic if `option` is built with `enable_pages` set to true, or if `seek`
+/// is `Seek::Semver`.
+async fn list_by_column(
     crate_id: i32,
+    seek: seek::Seek,
     options: Option<&PaginationOptions>,
     params: &ListQueryParams,
     req: &Parts,
@@ -133,6 +211,7 @@ async fn list_by_date(
     let make_base_query = || {
         let mut query = versions::table
             .filter(versions::crate_id.eq(crate_id))
+            .filter(Version::not_deleted())
             .left_outer_join(users::table)
             .select(<(Version, Option<User>)>::as_select())
             .into_boxed();
@@ -140,6 +219,9 @@ async fn list_by_date(
         if !params.nums.is_empty() {
             query = query.filter(versions::num.eq_any(params.nums.iter().map(|s| s.as_str())));
         }
+        if !params.include_yanked() {
+            query = query.filter(not(versions::yanked));
+        }
         query
     };
 
@@ -150,23 +232,38 @@ async fn list_by_date(
             !matches!(&options.page, Page::Numeric(_)),
             "?page= is not supported"
         );
-        if let Some(SeekPayload::Date(Date { created_at, id })) = Seek::Date.after(&options.page)? {
-            query = query.filter(
-                versions::created_at
-                    .eq(created_at)
-                    .and(versions::id.lt(id))
-                    .or(versions::created_at.lt(created_at)),
-            )
+        match seek.after(&options.page)? {
+            Some(SeekPayload::Date(Date { created_at, id })) => {
+                query = query.filter(
+                    versions::created_at
+                        .eq(created_at)
+                        .and(versions::id.lt(id))
+                        .or(versions::created_at.lt(created_at)),
+                )
+            }
+            Some(SeekPayload::Downloads(Downloads { downloads, id })) => {
+                query = query.filter(
+                    versions::downloads
+                        .eq(downloads)
+                        .and(versions::id.lt(id))
+                        .or(versions::downloads.lt(downloads)),
+                )
+            }
+            _ => {}
         }
         query = query.limit(options.per_page);
     }
 
-    query = query.order((versions::created_at.desc(), versions::id.desc()));
+    query = match seek {
+        Seek::Date => query.order((versions::created_at.desc(), versions::id.desc())),
+        Seek::Downloads => query.order((versions::downloads.desc(), versions::id.desc())),
+        Seek::Semver => unreachable!("versions are sorted by semver in `list_by_semver`"),
+    };
 
     let data: Vec<(Version, Option<User>)> = query.load(conn).await?;
     let mut next_page = None;
     if let Some(options) = options {
-        next_page = next_seek_params(&data, options, |last| Seek::Date.to_payload(last))?
+        next_page = next_seek_params(&data, options, |last| seek.to_payload(last))?
             .map(|p| req.query_with_params(p));
     };
 
@@ -176,6 +273,7 @@ async fn list_by_date(
             versions::table
                 .filter(versions::crate_id.eq(crate_id))
                 .filter(not(versions::yanked))
+                .filter(Version::not_deleted())
                 .select(versions::num)
                 .load_stream::<String>(conn)
                 .await?
@@ -225,8 +323,8 @@ async fn list_by_date(
 
 /// Seek-based pagination of versions by semver
 ///
-/// Unfortunately, Heroku Postgres has no support for the semver PG extension.
-/// Therefore, we need to perform both sorting and pagination manually on the server.
+/// The versions are sorted by the precomputed `semver_ord` column. Versions without a sort key,
+/// e.g. because their version number is not valid semver, are sorted last.
 ///
 /// # Panics
 ///
@@ -240,131 +338,125 @@ async fn list_by_semver(
 ) -> AppResult<PaginatedVersionsAndPublishers> {
     use seek::*;
 
-    let include = params.include()?;
-    let mut query = versions::table
-        .filter(versions::crate_id.eq(crate_id))
-        .into_boxed();
-
-    if !params.nums.is_empty() {
-        query = query.filter(versions::num.eq_any(params.nums.iter().map(|s| s.as_str())));
-    }
-
-    let (data, total, release_tracks) = if let Some(options) = options {
-        // Since versions will only increase in the future and both sorting and pagination need to
-        // happen on the app server, implementing it with fetching only the data needed for sorting
-        // and pagination, then making another query for the data to respond with, would minimize
-        // payload and memory usage. This way, we can utilize the sorted map and enrich it later
-        // without sorting twice.
-        // Sorting by semver but opted for id as the seek key because num can be quite lengthy,
-        // while id values are significantly smaller.
+    let make_base_query = || {
+        let mut query = versions::table
+            .filter(versions::crate_id.eq(crate_id))
+            .filter(Version::not_deleted())
+            .left_outer_join(users::table)
+            .select(<(Version, Option<User>)>::as_select())
+            .into_boxed();
 
-        let mut sorted_versions = IndexMap::new();
+        if !params.nums.is_empty() {
+            query = query.filter(versions::num.eq_any(params.nums.iter().map(|s| s.as_str())));
+        }
+        if !params.include_yanked() {
+            query = query.filter(not(versions::yanked));
+        }
         query
-            .select((versions::id, versions::num, versions::yanked))
-            .load_stream::<(i32, String, bool)>(conn)
-            .await?
-            .try_for_each(|(id, num, yanked)| {
-                let semver = semver::Version::parse(&num).ok();
-                sorted_versions.insert(id, (semver, yanked, None));
-                future::ready(Ok(()))
-            })
-            .await?;
+    };
 
-        sorted_versions
-            .sort_unstable_by(|_, (semver_a, _, _), _, (semver_b, _, _)| semver_b.cmp(semver_a));
+    let mut query = make_base_query();
+    let mut seek_found = true;
 
+    if let Some(options) = options {
         assert!(
             !matches!(&options.page, Page::Numeric(_)),
             "?page= is not supported"
         );
-
-        let release_tracks = include.release_tracks.then(|| {
-            ReleaseTracks::from_sorted_semver_iter(
-                sorted_versions
-                    .values()
-                    .filter(|(_, yanked, _)| !yanked)
-                    .filter_map(|(semver, _, _)| semver.as_ref()),
-            )
-        });
-
-        let mut idx = Some(0);
+        // Sorting by semver but opted for id as the seek key because num can be quite lengthy,
+        // while id values are significantly smaller. The sort key of the version is looked up
+        // to continue after it.
         if let Some(SeekPayload::Semver(Semver { id })) = Seek::Semver.after(&options.page)? {
-            idx = sorted_versions
-                .get_index_of(&id)
-                .filter(|i| i + 1 < sorted_versions.len())
-                .map(|i| i + 1);
-        }
-        if let Some(start) = idx {
-            let end = (start + options.per_page as usize).min(sorted_versions.len());
-            let ids = sorted_versions[start..end]
-                .keys()
-                .cloned()
-                .collect::<Vec<_>>();
-            versions::table
+            let semver_ord: Option<Option<String>> = versions::table
+                .find(id)
                 .filter(versions::crate_id.eq(crate_id))
-                .left_outer_join(users::table)
-                .select(<(Version, Option<User>)>::as_select())
-                .filter(versions::id.eq_any(ids))
-                .load_stream::<(Version, Option<User>)>(conn)
-                .await?
-                .try_for_each(|row| {
-                    // The versions are already sorted, and we only need to enrich the fetched rows into them.
-                    // Therefore, other values can now be safely ignored.
-                    sorted_versions
-                        .entry(row.0.id)
-                        .and_modify(|entry| *entry = (None, false, Some(row)));
+                .select(versions::semver_ord)
+                .first(conn)
+                .await
+                .optional()?;
+
+            match semver_ord {
+                Some(Some(semver_ord)) => {
+                    query = query.filter(
+                        versions::semver_ord
+                            .eq(semver_ord.clone())
+                            .and(versions::id.lt(id))
+                            .or(versions::semver_ord.lt(semver_ord))
+                            .or(versions::semver_ord.is_null()),
+                    )
+                }
+                Some(None) => {
+                    query = query.filter(versions::semver_ord.is_null().and(versions::id.lt(id)))
+                }
+                None => seek_found = false,
+            }
+        }
+        query = query.limit(options.per_page);
+    }
 
-                    future::ready(Ok(()))
-                })
-                .await?;
+    query = query.order((
+        versions::semver_ord.desc().nulls_last(),
+        versions::id.desc(),
+    ));
 
-            let len = sorted_versions.len();
-            (
-                sorted_versions
-                    .into_values()
-                    .filter_map(|(_, _, v)| v)
-                    .collect(),
-                len,
-                release_tracks,
-            )
-        } else {
-            (vec![], 0, release_tracks)
-        }
+    let data: Vec<(Version, Option<User>)> = if seek_found {
+        query.load(conn).await?
     } else {
-        let mut data = IndexMap::new();
+        vec![]
+    };
+
+    let mut next_page = None;
+    if let Some(options) = options {
+        next_page = next_seek_params(&data, options, |last| Seek::Semver.to_payload(last))?
+            .map(|p| req.query_with_params(p))
+    };
+
+    let release_tracks = if params.include()?.release_tracks {
+        let mut query = versions::table
+            .filter(versions::crate_id.eq(crate_id))
+            .filter(not(versions::yanked))
+            .filter(Version::not_deleted())
+            .select(versions::num)
+            .into_boxed();
+
+        if !params.nums.is_empty() {
+            query = query.filter(versions::num.eq_any(params.nums.iter().map(|s| s.as_str())));
+        }
+
+        // The `semver_ord` column might not be backfilled yet, so the release tracks are
+        // calculated from the parsed version numbers instead.
+        let mut sorted_versions = IndexSet::new();
         query
-            .left_outer_join(users::table)
-            .select(<(Version, Option<User>)>::as_select())
-            .load_stream::<(Version, Option<User>)>(conn)
+            .load_stream::<String>(conn)
             .await?
-            .try_for_each(|row| {
-                if let Ok(semver) = semver::Version::parse(&row.0.num) {
-                    data.insert(semver, row);
+            .try_for_each(|num| {
+                if let Ok(semver) = semver::Version::parse(&num) {
+                    sorted_versions.insert(semver);
                 };
                 future::ready(Ok(()))
             })
             .await?;
-        data.sort_unstable_by(|a, _, b, _| b.cmp(a));
-        let total = data.len();
-        let release_tracks = include.release_tracks.then(|| {
-            ReleaseTracks::from_sorted_semver_iter(
-                data.iter()
-                    .flat_map(|(semver, (version, _))| (!version.yanked).then_some(semver)),
-            )
-        });
-        (data.into_values().collect(), total, release_tracks)
+
+        sorted_versions.sort_unstable_by(|a, b| b.cmp(a));
+        Some(ReleaseTracks::from_sorted_semver_iter(
+            sorted_versions.iter(),
+        ))
+    } else {
+        None
     };
 
-    let mut next_page = None;
-    if let Some(options) = options {
-        next_page = next_seek_params(&data, options, |last| Seek::Semver.to_payload(last))?
-            .map(|p| req.query_with_params(p))
+    // Since the total count is retrieved through an additional query, to maintain consistency
+    // with other pagination methods, we only make a count query while data is not empty.
+    let total = if !data.is_empty() {
+        make_base_query().count().get_result(conn).await?
+    } else {
+        0
     };
 
     Ok(PaginatedVersionsAndPublishers {
         data,
         meta: ResponseMeta {
-            total: total as i64,
+            total,
             next_page,
             release_tracks,
         },
@@ -389,15 +481,28 @@ mod seek {
                 created_at: chrono::NaiveDateTime,
                 id: i32,
             },
+            Downloads {
+                downloads: i32,
+                id: i32,
+            },
         }
     );
 
     impl Seek {
         pub(crate) fn to_payload(&self, record: &(Version, Option<User>)) -> SeekPayload {
-            let (Version { id, created_at, .. }, _) = *record;
+            let (
+                Version {
+                    id,
+                    created_at,
+                    downloads,
+                    ..
+                },
+                _,
+            ) = *record;
             match *self {
                 Seek::Semver => SeekPayload::Semver(Semver { id }),
                 Seek::Date => SeekPayload::Date(Date { created_at, id }),
+                Seek::Downloads => SeekPayload::Downloads(Downloads { downloads, id }),
             }
         }
     }
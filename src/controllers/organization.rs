@@ -0,0 +1,511 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::ok_true;
+use crate::models::{
+    Crate, NewOrganization, NewOrganizationMember, NewReservedPrefix, Organization,
+    OrganizationMember, OrganizationRole, ReservedPrefix, User,
+};
+use crate::schema::{organization_members, organizations, reserved_prefixes, users};
+use crate::util::errors::{bad_request, custom, not_found, AppResult, BoxedAppError};
+use crate::views::{EncodableOrganization, EncodableOrganizationMember, EncodableReservedPrefix};
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+use http::StatusCode;
+
+/// The maximum length of an organization name.
+const MAX_LOGIN_LENGTH: usize = 39;
+
+/// The minimum length of a reserved crate name prefix, including the
+/// trailing `-` or `_`.
+const MIN_PREFIX_LENGTH: usize = 3;
+
+#[derive(Deserialize)]
+pub struct NewOrganizationRequest {
+    organization: NewOrganizationData,
+}
+
+#[derive(Deserialize)]
+pub struct NewOrganizationData {
+    login: String,
+    name: Option<String>,
+}
+
+/// Create a new organization.
+///
+/// The authenticated user becomes the first admin of the organization.
+#[utoipa::path(
+    post,
+    path = "/api/v1/organizations",
+    security(("cookie" = [])),
+    tag = "organizations",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_organization(
+    app: AppState,
+    req: Parts,
+    Json(body): Json<NewOrganizationRequest>,
+) -> AppResult<ErasedJson> {
+    let login = body.organization.login.trim();
+    validate_login(login)?;
+
+    let name = body.organization.name.as_deref().map(str::trim);
+    let name = name.filter(|name| !name.is_empty());
+
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let new_organization = NewOrganization { login, name };
+    let organization = new_organization
+        .create(&mut conn, auth.user())
+        .await
+        .map_err(|error| match error {
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                bad_request(format!("organization `{login}` already exists"))
+            }
+            error => error.into(),
+        })?;
+
+    Ok(json!({ "organization": EncodableOrganization::from(organization) }))
+}
+
+/// Find organization by name.
+#[utoipa::path(
+    get,
+    path = "/api/v1/organizations/{organization}",
+    params(
+        ("organization" = String, Path, description = "Name of the organization"),
+    ),
+    tag = "organizations",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn find_organization(app: AppState, Path(login): Path<String>) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read().await?;
+    let organization = find_by_login(&mut conn, &login).await?;
+
+    Ok(json!({ "organization": EncodableOrganization::from(organization) }))
+}
+
+/// List the members of an organization.
+///
+/// Only members of the organization can see its members.
+#[utoipa::path(
+    get,
+    path = "/api/v1/organizations/{organization}/members",
+    params(
+        ("organization" = String, Path, description = "Name of the organization"),
+    ),
+    security(("cookie" = [])),
+    tag = "organizations",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_organization_members(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let organization = find_by_login(&mut conn, &login).await?;
+    if organization
+        .role_of(&mut conn, auth.user_id())
+        .await?
+        .is_none()
+    {
+        return Err(custom(
+            StatusCode::FORBIDDEN,
+            "only members can see the members of an organization",
+        ));
+    }
+
+    let members: Vec<(OrganizationMember, User)> = OrganizationMember::belonging_to(&organization)
+        .inner_join(users::table)
+        .select((OrganizationMember::as_select(), User::as_select()))
+        .order(users::gh_login)
+        .load(&mut conn)
+        .await?;
+
+    let members = members
+        .into_iter()
+        .map(EncodableOrganizationMember::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "members": members }))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMemberRequest {
+    member: UpdateMemberData,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMemberData {
+    login: String,
+    role: OrganizationRole,
+}
+
+/// Add a member to an organization, or change the role of an existing member.
+///
+/// Only admins of the organization can manage its members.
+#[utoipa::path(
+    put,
+    path = "/api/v1/organizations/{organization}/members",
+    params(
+        ("organization" = String, Path, description = "Name of the organization"),
+    ),
+    security(("cookie" = [])),
+    tag = "organizations",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn update_organization_member(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+    Json(body): Json<UpdateMemberRequest>,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let organization = find_by_login(&mut conn, &login).await?;
+    ensure_admin(
+        &mut conn,
+        &organization,
+        auth.user_id(),
+        "only admins can manage the members of an organization",
+    )
+    .await?;
+
+    let user = User::find_by_login(&mut conn, &body.member.login)
+        .await
+        .optional()?
+        .ok_or_else(|| {
+            let detail = format!("could not find user with login `{}`", body.member.login);
+            bad_request(detail)
+        })?;
+
+    let role = body.member.role;
+
+    let member = conn
+        .transaction(|conn| {
+            async move {
+                if role != OrganizationRole::Admin {
+                    ensure_other_admins(conn, &organization, user.id).await?;
+                }
+
+                let new_member = NewOrganizationMember {
+                    organization_id: organization.id,
+                    user_id: user.id,
+                    role,
+                };
+
+                let member = new_member.upsert(conn).await?;
+
+                Ok::<_, BoxedAppError>((member, user))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(json!({ "member": EncodableOrganizationMember::from(member) }))
+}
+
+/// Remove a member from an organization.
+///
+/// Admins can remove any member, and all members can remove themselves.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/organizations/{organization}/members/{user}",
+    params(
+        ("organization" = String, Path, description = "Name of the organization"),
+        ("user" = String, Path, description = "Login of the member"),
+    ),
+    security(("cookie" = [])),
+    tag = "organizations",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn remove_organization_member(
+    app: AppState,
+    Path((login, member_login)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let organization = find_by_login(&mut conn, &login).await?;
+
+    let user = User::find_by_login(&mut conn, &member_login)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    if user.id != auth.user_id() {
+        ensure_admin(
+            &mut conn,
+            &organization,
+            auth.user_id(),
+            "only admins can manage the members of an organization",
+        )
+        .await?;
+    }
+
+    conn.transaction(|conn| {
+        async move {
+            ensure_other_admins(conn, &organization, user.id).await?;
+
+            let deleted =
+                diesel::delete(organization_members::table.find((organization.id, user.id)))
+                    .execute(conn)
+                    .await?;
+
+            if deleted == 0 {
+                return Err(not_found());
+            }
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    ok_true()
+}
+
+/// List the approved crate name prefix reservations of all organizations.
+///
+/// Only members of the organization that reserved a prefix can publish new
+/// crates whose name starts with the prefix.
+#[utoipa::path(
+    get,
+    path = "/api/v1/reserved_prefixes",
+    tag = "organizations",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_reserved_prefixes(app: AppState) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read().await?;
+
+    let reserved_prefixes: Vec<(ReservedPrefix, String)> = reserved_prefixes::table
+        .inner_join(organizations::table)
+        .filter(reserved_prefixes::approved_at.is_not_null())
+        .select((ReservedPrefix::as_select(), organizations::login))
+        .order(reserved_prefixes::prefix)
+        .load(&mut conn)
+        .await?;
+
+    let reserved_prefixes = reserved_prefixes
+        .into_iter()
+        .map(|(reserved, login)| EncodableReservedPrefix::new(reserved, login))
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "reserved_prefixes": reserved_prefixes }))
+}
+
+/// List the crate name prefix reservations of an organization.
+///
+/// Only members of the organization can see its reservations, including the
+/// ones that are still waiting for the approval of a crates.io admin.
+#[utoipa::path(
+    get,
+    path = "/api/v1/organizations/{organization}/reserved_prefixes",
+    params(
+        ("organization" = String, Path, description = "Name of the organization"),
+    ),
+    security(("cookie" = [])),
+    tag = "organizations",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_organization_reserved_prefixes(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let organization = find_by_login(&mut conn, &login).await?;
+    if organization
+        .role_of(&mut conn, auth.user_id())
+        .await?
+        .is_none()
+    {
+        return Err(custom(
+            StatusCode::FORBIDDEN,
+            "only members can see the reserved prefixes of an organization",
+        ));
+    }
+
+    let reserved_prefixes: Vec<ReservedPrefix> = reserved_prefixes::table
+        .filter(reserved_prefixes::organization_id.eq(organization.id))
+        .select(ReservedPrefix::as_select())
+        .order(reserved_prefixes::prefix)
+        .load(&mut conn)
+        .await?;
+
+    let reserved_prefixes = reserved_prefixes
+        .into_iter()
+        .map(|reserved| EncodableReservedPrefix::new(reserved, organization.login.clone()))
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "reserved_prefixes": reserved_prefixes }))
+}
+
+#[derive(Deserialize)]
+pub struct ReservePrefixRequest {
+    reserved_prefix: ReservePrefixData,
+}
+
+#[derive(Deserialize)]
+pub struct ReservePrefixData {
+    /// The crate name prefix, e.g. `aws-sdk-` or `aws-sdk-*`.
+    prefix: String,
+}
+
+/// Request to reserve a crate name prefix for an organization.
+///
+/// Only admins of the organization can request reservations. A crates.io
+/// admin has to approve the reservation before it is enforced, and crates
+/// that already exist with the prefix are not affected.
+#[utoipa::path(
+    post,
+    path = "/api/v1/organizations/{organization}/reserved_prefixes",
+    params(
+        ("organization" = String, Path, description = "Name of the organization"),
+    ),
+    security(("cookie" = [])),
+    tag = "organizations",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn request_reserved_prefix(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+    Json(body): Json<ReservePrefixRequest>,
+) -> AppResult<ErasedJson> {
+    let prefix = body.reserved_prefix.prefix.trim();
+    let prefix = prefix.strip_suffix('*').unwrap_or(prefix).to_lowercase();
+    validate_prefix(&prefix)?;
+
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let organization = find_by_login(&mut conn, &login).await?;
+    ensure_admin(
+        &mut conn,
+        &organization,
+        auth.user_id(),
+        "only admins can reserve prefixes for an organization",
+    )
+    .await?;
+
+    let reserved = conn
+        .transaction(|conn| {
+            async move {
+                let existing: Vec<ReservedPrefix> = reserved_prefixes::table
+                    .select(ReservedPrefix::as_select())
+                    .load(conn)
+                    .await?;
+
+                if let Some(existing) = existing.iter().find(|r| r.overlaps(&prefix)) {
+                    let detail = format!(
+                        "prefix `{prefix}` overlaps with the reserved prefix `{}`",
+                        existing.prefix
+                    );
+                    return Err(bad_request(detail));
+                }
+
+                let new_reserved = NewReservedPrefix {
+                    organization_id: organization.id,
+                    prefix: &prefix,
+                    requested_by: auth.user_id(),
+                };
+
+                let reserved = new_reserved.insert(conn).await?;
+
+                Ok::<_, BoxedAppError>(EncodableReservedPrefix::new(reserved, organization.login))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(json!({ "reserved_prefix": reserved }))
+}
+
+fn validate_prefix(prefix: &str) -> AppResult<()> {
+    Crate::validate_crate_name("prefix", prefix).map_err(bad_request)?;
+
+    if prefix.len() < MIN_PREFIX_LENGTH || !prefix.ends_with(['-', '_']) {
+        let detail = format!(
+            "reserved prefixes must be at least {MIN_PREFIX_LENGTH} characters long and end with `-` or `_`"
+        );
+        return Err(bad_request(detail));
+    }
+
+    Ok(())
+}
+
+fn validate_login(login: &str) -> AppResult<()> {
+    if login.is_empty() || login.len() > MAX_LOGIN_LENGTH {
+        let detail =
+            format!("organization names must be between 1 and {MAX_LOGIN_LENGTH} characters long");
+        return Err(bad_request(detail));
+    }
+
+    let mut chars = login.chars();
+    let valid_start = chars.next().is_some_and(|c| c.is_ascii_alphanumeric());
+    let valid_rest = chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_start || !valid_rest {
+        return Err(bad_request(
+            "organization names must start with a letter or digit and may only contain \
+            letters, digits, `-` and `_`",
+        ));
+    }
+
+    Ok(())
+}
+
+async fn find_by_login(conn: &mut AsyncPgConnection, login: &str) -> AppResult<Organization> {
+    Organization::find_by_login(conn, login)
+        .await
+        .optional()?
+        .ok_or_else(not_found)
+}
+
+async fn ensure_admin(
+    conn: &mut AsyncPgConnection,
+    organization: &Organization,
+    user_id: i32,
+    detail: &'static str,
+) -> AppResult<()> {
+    match organization.role_of(conn, user_id).await? {
+        Some(OrganizationRole::Admin) => Ok(()),
+        _ => Err(custom(StatusCode::FORBIDDEN, detail)),
+    }
+}
+
+/// Makes sure that the organization still has an admin if the given user
+/// stops being one.
+async fn ensure_other_admins(
+    conn: &mut AsyncPgConnection,
+    organization: &Organization,
+    user_id: i32,
+) -> AppResult<()> {
+    let other_admins: i64 = OrganizationMember::belonging_to(organization)
+        .filter(organization_members::role.eq(OrganizationRole::Admin))
+        .filter(organization_members::user_id.ne(user_id))
+        .count()
+        .get_result(conn)
+        .await?;
+
+    if other_admins == 0 {
+        return Err(bad_request("an organization must have at least one admin"));
+    }
+
+    Ok(())
+}
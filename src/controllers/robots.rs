@@ -0,0 +1,62 @@
+//! The crawler policy of the site.
+//!
+//! `/robots.txt` is served by the backend instead of the frontend, so that
+//! it can depend on the environment. Staging environments disallow all
+//! crawling, while production points search engines to the sitemaps of the
+//! crate pages and keeps them away from the API and from pages that only
+//! make sense for signed in users.
+
+use crate::app::AppState;
+use crate::worker::jobs::generate_sitemaps::{absolute_url, SITEMAP_INDEX};
+use axum::response::{IntoResponse, Redirect, Response};
+use http::header;
+
+/// Paths of the crawler policy endpoints, which are served by the backend
+/// even though they are outside of the API.
+pub const CRAWLER_POLICY_PATHS: &[&str] = &["/robots.txt", "/sitemap.xml"];
+
+/// Paths that crawlers should not request, even if they are linked from
+/// crawlable pages.
+const DISALLOWED_PATHS: &[&str] = &[
+    "/api/",
+    "/me",
+    "/settings",
+    "/dashboard",
+    "/search",
+    "/confirm/",
+    "/accept-invite/",
+    "/crates/*/settings",
+    "/crates/*/delete",
+];
+
+/// Returns the crawler policy of the site.
+pub async fn robots_txt(app: AppState) -> Response {
+    let mut content = String::from("User-agent: *\n");
+
+    if app.config.allow_crawlers {
+        for path in DISALLOWED_PATHS {
+            content.push_str(&format!("Disallow: {path}\n"));
+        }
+
+        content.push_str(&format!("\nSitemap: {}\n", sitemap_url(&app)));
+    } else {
+        content.push_str("Disallow: /\n");
+    }
+
+    let headers = [
+        (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+        (header::CACHE_CONTROL, "public, max-age=3600"),
+    ];
+    (headers, content).into_response()
+}
+
+/// Redirects to the sitemap index, which is generated by the
+/// `GenerateSitemaps` background job and served from the storage.
+pub async fn sitemap(app: AppState) -> Redirect {
+    Redirect::temporary(&sitemap_url(&app))
+}
+
+fn sitemap_url(app: &AppState) -> String {
+    let location = app.storage.sitemap_location(SITEMAP_INDEX);
+    absolute_url(&app.config.domain_name, location)
+}
@@ -5,23 +5,44 @@ use axum_extra::response::ErasedJson;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::request::Parts;
-use oauth2::{AuthorizationCode, CsrfToken, Scope, TokenResponse};
+use oauth2::{AuthorizationCode, CsrfToken};
 
 use crate::app::AppState;
+use crate::auth::{ensure_not_locked, AuthCheck};
 use crate::email::Emails;
+use crate::middleware::csrf;
 use crate::middleware::log_request::RequestLogExt;
-use crate::models::{NewUser, User};
-use crate::schema::users;
+use crate::models::{LinkedIdentity, NewLinkedIdentity, NewUser, User};
+use crate::oauth::{ExternalIdentity, IdentityProvider};
+use crate::schema::{linked_identities, users};
 use crate::util::diesel::is_read_only_error;
 use crate::util::errors::{bad_request, server_error, AppResult};
 use crate::views::EncodableMe;
-use crates_io_github::GithubUser;
+use crate::worker::jobs::PurgeSurrogateKeys;
 use crates_io_session::SessionExtension;
+use crates_io_worker::BackgroundJob;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct BeginQuery {
+    /// The identity provider to authenticate with. Defaults to `github`.
+    #[param(value_type = Option<String>, example = "gitlab")]
+    provider: Option<IdentityProvider>,
+    /// Link the account of the identity provider to the currently
+    /// authenticated user instead of logging in.
+    #[serde(default)]
+    link: bool,
+}
 
 /// Begin authentication flow.
 ///
-/// This route will return an authorization URL for the GitHub OAuth flow including the crates.io
-/// `client_id` and a randomly generated `state` secret.
+/// This route will return an authorization URL for the OAuth flow of the requested identity
+/// provider including the crates.io `client_id` and a randomly generated `state` secret.
+///
+/// GitHub is used by default. Other identity providers (`gitlab` and `google`) can only be used
+/// to log in to accounts that they have previously been linked to. To link such an account, an
+/// authenticated user has to begin the flow with `link=true`.
 ///
 /// see <https://developer.github.com/v3/oauth/#redirect-users-to-request-github-access>
 ///
@@ -36,20 +57,44 @@ use crates_io_session::SessionExtension;
 #[utoipa::path(
     get,
     path = "/api/private/session/begin",
+    params(BeginQuery),
     tag = "session",
     responses((status = 200, description = "Successful Response")),
 )]
-pub async fn begin_session(app: AppState, session: SessionExtension) -> ErasedJson {
-    let (url, state) = app
-        .github_oauth
-        .authorize_url(oauth2::CsrfToken::new_random)
-        .add_scope(Scope::new("read:org".to_string()))
-        .url();
+pub async fn begin_session(
+    query: BeginQuery,
+    app: AppState,
+    session: SessionExtension,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let provider = query.provider.unwrap_or(IdentityProvider::GitHub);
+
+    if query.link {
+        if provider == IdentityProvider::GitHub {
+            return Err(bad_request("GitHub accounts can not be linked"));
+        }
+
+        let mut conn = app.db_read_prefer_primary().await?;
+        AuthCheck::only_cookie().check(&req, &mut conn).await?;
+    }
+
+    let oauth = app.oauth.get(provider).ok_or_else(|| {
+        let detail = format!("{} login is not available", provider.display_name());
+        bad_request(detail)
+    })?;
+
+    let (url, state) = oauth.authorize_url();
 
     let state = state.secret().to_string();
-    session.insert("github_oauth_state".to_string(), state.clone());
+    session.insert("oauth_state".to_string(), state.clone());
+    session.insert("oauth_provider".to_string(), provider.to_string());
+    if query.link {
+        session.insert("oauth_link".to_string(), "true".to_string());
+    } else {
+        session.remove("oauth_link");
+    }
 
-    json!({ "url": url.to_string(), "state": state })
+    Ok(json!({ "url": url.to_string(), "state": state }))
 }
 
 #[derive(Clone, Debug, Deserialize, FromRequestParts)]
@@ -61,17 +106,21 @@ pub struct AuthorizeQuery {
 
 /// Complete authentication flow.
 ///
-/// This route is called from the GitHub API OAuth flow after the user accepted or rejected
-/// the data access permissions. It will check the `state` parameter and then call the GitHub API
-/// to exchange the temporary `code` for an API token. The API token is returned together with
-/// the corresponding user information.
+/// This route is called from the OAuth flow of the identity provider after the user accepted or
+/// rejected the data access permissions. It will check the `state` parameter and then call the
+/// identity provider to exchange the temporary `code` for an API token. The API token is used to
+/// look up the account at the identity provider, and the corresponding user information is
+/// returned.
+///
+/// If the flow was started with `link=true`, the account is linked to the currently
+/// authenticated user instead.
 ///
 /// see <https://developer.github.com/v3/oauth/#github-redirects-back-to-your-site>
 ///
 /// ## Query Parameters
 ///
-/// - `code` – temporary code received from the GitHub API  **(Required)**
-/// - `state` – state parameter received from the GitHub API  **(Required)**
+/// - `code` – temporary code received from the identity provider  **(Required)**
+/// - `state` – state parameter received from the identity provider  **(Required)**
 ///
 /// ## Response Body Example
 ///
@@ -100,48 +149,81 @@ pub async fn authorize_session(
 ) -> AppResult<Json<EncodableMe>> {
     // Make sure that the state we just got matches the session state that we
     // should have issued earlier.
-    let session_state = session.remove("github_oauth_state").map(CsrfToken::new);
+    let session_state = session.remove("oauth_state").map(CsrfToken::new);
     if session_state.is_none_or(|state| query.state.secret() != state.secret()) {
         return Err(bad_request("invalid state parameter"));
     }
 
-    // Fetch the access token from GitHub using the code we just got
-    let client = reqwest::Client::builder()
-        .redirect(reqwest::redirect::Policy::none())
-        .build()?;
+    let provider = session
+        .remove("oauth_provider")
+        .and_then(|provider| provider.parse().ok())
+        .unwrap_or(IdentityProvider::GitHub);
 
-    let token = app
-        .github_oauth
-        .exchange_code(query.code)
-        .request_async(&client)
-        .await
-        .map_err(|err| {
-            req.request_log().add("cause", err);
-            server_error("Error obtaining token")
-        })?;
+    let link = session.remove("oauth_link").is_some();
+
+    let oauth = app
+        .oauth
+        .get(provider)
+        .ok_or_else(|| bad_request("invalid identity provider"))?;
 
-    let token = token.access_token();
+    // Fetch the access token from the identity provider using the code we just got
+    let token = oauth.exchange_code(query.code).await.map_err(|err| {
+        req.request_log().add("cause", err);
+        server_error("Error obtaining token")
+    })?;
 
-    // Fetch the user info from GitHub using the access token we just got and create a user record
-    let ghuser = app.github.current_user(token).await?;
+    // Fetch the account info from the identity provider using the access token we just got
+    let identity = oauth.fetch_identity(&token).await?;
 
     let mut conn = app.db_write().await?;
-    let user = save_user_to_database(&ghuser, token.secret(), &app.emails, &mut conn).await?;
+
+    if link {
+        let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+        link_identity(auth.user(), &identity, &mut conn).await?;
+        drop(conn);
+
+        return super::user::me::get_authenticated_user(app, req).await;
+    }
+
+    let user = match provider {
+        IdentityProvider::GitHub => {
+            save_user_to_database(&identity, token.secret(), &app.emails, &mut conn).await?
+        }
+        _ => User::find_by_linked_identity(&mut conn, provider.as_str(), &identity.account_id)
+            .await?
+            .ok_or_else(|| {
+                let detail = format!(
+                    "This {} account is not linked to a crates.io account. Please log in with GitHub and link it in your account settings first.",
+                    provider.display_name()
+                );
+                bad_request(detail)
+            })?,
+    };
+
+    // Locked users are told why they can't log in instead of getting a
+    // session that is rejected on every request.
+    ensure_not_locked(&user)?;
 
     // Log in by setting a cookie and the middleware authentication
     session.insert("user_id".to_string(), user.id.to_string());
+    csrf::issue_token(&session);
 
     super::user::me::get_authenticated_user(app, req).await
 }
 
 async fn save_user_to_database(
-    user: &GithubUser,
+    user: &ExternalIdentity,
     access_token: &str,
     emails: &Emails,
     conn: &mut AsyncPgConnection,
 ) -> AppResult<User> {
+    let gh_id = user
+        .account_id
+        .parse()
+        .map_err(|_| server_error("Invalid GitHub account ID"))?;
+
     let new_user = NewUser::new(
-        user.id,
+        gh_id,
         &user.login,
         user.name.as_deref(),
         user.avatar_url.as_deref(),
@@ -152,11 +234,18 @@ async fn save_user_to_database(
         .create_or_update(user.email.as_deref(), emails, conn)
         .await
     {
-        Ok(user) => Ok(user),
+        Ok(user) => {
+            // The name and avatar of the user might have changed on GitHub.
+            if let Err(error) = PurgeSurrogateKeys::user(user.id).enqueue(conn).await {
+                warn!("Failed to enqueue surrogate key purge: {error}");
+            }
+
+            Ok(user)
+        }
         Err(error) if is_read_only_error(&error) => {
             // If we're in read only mode, we can't update their details
             // just look for an existing user
-            find_user_by_gh_id(conn, user.id)
+            find_user_by_gh_id(conn, gh_id)
                 .await?
                 .ok_or_else(|| error.into())
         }
@@ -172,6 +261,51 @@ async fn find_user_by_gh_id(conn: &mut AsyncPgConnection, gh_id: i32) -> QueryRe
         .optional()
 }
 
+/// Links the account of an additional identity provider to the given user.
+async fn link_identity(
+    user: &User,
+    identity: &ExternalIdentity,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<()> {
+    let provider = identity.provider;
+
+    if let Some(existing) =
+        LinkedIdentity::find(conn, provider.as_str(), &identity.account_id).await?
+    {
+        if existing.user_id != user.id {
+            let detail = format!(
+                "This {} account is already linked to another crates.io account",
+                provider.display_name()
+            );
+            return Err(bad_request(detail));
+        }
+
+        return Ok(());
+    }
+
+    let already_linked = LinkedIdentity::belonging_to(user)
+        .filter(linked_identities::provider.eq(provider.as_str()))
+        .select(linked_identities::id)
+        .first::<i32>(conn)
+        .await
+        .optional()?
+        .is_some();
+
+    if already_linked {
+        let detail = format!(
+            "Another {} account is already linked to your crates.io account. Please unlink it first.",
+            provider.display_name()
+        );
+        return Err(bad_request(detail));
+    }
+
+    NewLinkedIdentity::new(user.id, identity)
+        .insert(conn)
+        .await?;
+
+    Ok(())
+}
+
 /// End the current session.
 #[utoipa::path(
     delete,
@@ -182,6 +316,7 @@ async fn find_user_by_gh_id(conn: &mut AsyncPgConnection, gh_id: i32) -> QueryRe
 )]
 pub async fn end_session(session: SessionExtension) -> Json<bool> {
     session.remove("user_id");
+    session.remove(csrf::SESSION_KEY);
     Json(true)
 }
 
@@ -197,11 +332,12 @@ mod tests {
         let test_db = TestDatabase::new();
         let mut conn = test_db.async_connect().await;
 
-        let gh_user = GithubUser {
+        let gh_user = ExternalIdentity {
+            provider: IdentityProvider::GitHub,
+            account_id: "-1".into(),
             email: Some("String.Format(\"{0}.{1}@live.com\", FirstName, LastName)".into()),
             name: Some("My Name".into()),
             login: "github_user".into(),
-            id: -1,
             avatar_url: None,
         };
         let result = save_user_to_database(&gh_user, "arbitrary_token", &emails, &mut conn).await;
@@ -211,4 +347,53 @@ mod tests {
             "Creating a User from a GitHub user failed when it shouldn't have, {result:?}"
         );
     }
+
+    fn gitlab_identity(account_id: &str) -> ExternalIdentity {
+        ExternalIdentity {
+            provider: IdentityProvider::GitLab,
+            account_id: account_id.into(),
+            login: "gitlab_user".into(),
+            name: None,
+            email: None,
+            avatar_url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn link_identity_conflicts() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let emails = Emails::new_in_memory();
+        let foo = NewUser::new(1, "foo", None, None, "token");
+        let foo = foo
+            .create_or_update(None, &emails, &mut conn)
+            .await
+            .unwrap();
+        let bar = NewUser::new(2, "bar", None, None, "token");
+        let bar = bar
+            .create_or_update(None, &emails, &mut conn)
+            .await
+            .unwrap();
+
+        let identity = gitlab_identity("1234");
+        link_identity(&foo, &identity, &mut conn).await.unwrap();
+
+        // Linking the same account again is a no-op
+        link_identity(&foo, &identity, &mut conn).await.unwrap();
+
+        let user = User::find_by_linked_identity(&mut conn, "gitlab", "1234").await;
+        assert_eq!(user.unwrap().unwrap().id, foo.id);
+
+        // The account can not be linked to a second user
+        let result = link_identity(&bar, &identity, &mut conn).await;
+        assert!(result.is_err());
+
+        // A user can only link a single account per provider
+        let result = link_identity(&foo, &gitlab_identity("5678"), &mut conn).await;
+        assert!(result.is_err());
+
+        let user = User::find_by_linked_identity(&mut conn, "gitlab", "5678").await;
+        assert!(user.unwrap().is_none());
+    }
 }
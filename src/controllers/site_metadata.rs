@@ -1,4 +1,5 @@
 use crate::app::AppState;
+use crate::models::FeatureFlag;
 use axum::response::IntoResponse;
 use axum_extra::json;
 
@@ -13,14 +14,15 @@ use axum_extra::json;
     responses((status = 200, description = "Successful Response")),
 )]
 pub async fn get_site_metadata(state: AppState) -> impl IntoResponse {
-    let read_only = state.config.db.are_all_read_only();
+    let name = FeatureFlag::READ_ONLY_MODE;
+    let read_only =
+        state.config.db.are_all_read_only() || state.feature_flags.is_enabled(&state, name).await;
 
-    let deployed_sha =
-        dotenvy::var("HEROKU_SLUG_COMMIT").unwrap_or_else(|_| String::from("unknown"));
+    let deployed_sha = state.config.deployed_sha.as_deref().unwrap_or("unknown");
 
     json!({
-        "deployed_sha": &deployed_sha[..],
-        "commit": &deployed_sha[..],
+        "deployed_sha": deployed_sha,
+        "commit": deployed_sha,
         "read_only": read_only,
     })
 }
@@ -0,0 +1,48 @@
+use crate::app::AppState;
+use crate::util::{redirect, RequestUtils};
+use crate::worker::jobs::metadata_snapshot::{SNAPSHOT_FILE, SNAPSHOT_INDEX_FILE};
+use axum::response::{IntoResponse, Response};
+use axum_extra::json;
+use http::request::Parts;
+
+/// Download the metadata snapshot.
+///
+/// Redirects to a gzip compressed JSON Lines file on the CDN, which contains
+/// one line per crate with the essential metadata of the crate and all of
+/// its versions. The snapshot is regenerated periodically, so mirrors and
+/// offline tooling can use it to bootstrap instead of querying the API for
+/// every crate.
+#[utoipa::path(
+    get,
+    path = "/api/v1/snapshot/crates.jsonl.gz",
+    tag = "other",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn download_snapshot(app: AppState, req: Parts) -> Response {
+    redirect_to_file(&app, &req, SNAPSHOT_FILE)
+}
+
+/// Download the index of the metadata snapshot.
+///
+/// Redirects to a JSON file on the CDN with the size and SHA256 checksum of
+/// the current snapshot, and the byte range of each crate in the compressed
+/// snapshot file. Each crate is compressed separately, so single crates can
+/// be fetched with HTTP range requests.
+#[utoipa::path(
+    get,
+    path = "/api/v1/snapshot/crates.index.json",
+    tag = "other",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn download_snapshot_index(app: AppState, req: Parts) -> Response {
+    redirect_to_file(&app, &req, SNAPSHOT_INDEX_FILE)
+}
+
+fn redirect_to_file(app: &AppState, req: &Parts, file: &str) -> Response {
+    let url = app.storage.metadata_snapshot_location(file);
+    if req.wants_json() {
+        json!({ "url": url }).into_response()
+    } else {
+        redirect(url)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::app::AppState;
+use crate::models::RegistryStats;
+use crate::schema::registry_stats;
+use crate::util::errors::{bad_request, AppResult};
+use crate::views::EncodableRegistryStats;
+use axum::extract::Query;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+/// The maximum number of days that can be requested at once.
+const MAX_DAYS: u32 = 3650;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsQueryParams {
+    /// The number of days to return, counting back from the most recent day
+    /// with statistics. Defaults to 90.
+    days: Option<u32>,
+}
+
+/// Get registry-wide statistics.
+///
+/// Returns a daily time series of the total number of crates and versions,
+/// the number of published versions, and the number of downloads, ordered
+/// from oldest to newest.
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats",
+    params(StatsQueryParams),
+    tag = "other",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_stats(
+    state: AppState,
+    Query(params): Query<StatsQueryParams>,
+) -> AppResult<ErasedJson> {
+    let days = params.days.unwrap_or(90);
+    if days == 0 || days > MAX_DAYS {
+        let detail = format!("`days` must be between 1 and {MAX_DAYS}");
+        return Err(bad_request(detail));
+    }
+
+    let mut conn = state.db_read().await?;
+
+    let mut stats: Vec<RegistryStats> = registry_stats::table
+        .select(RegistryStats::as_select())
+        .order(registry_stats::date.desc())
+        .limit(days.into())
+        .load(&mut conn)
+        .await?;
+
+    stats.reverse();
+
+    let stats = stats
+        .into_iter()
+        .map(EncodableRegistryStats::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "stats": stats }))
+}
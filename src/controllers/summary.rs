@@ -1,28 +1,94 @@
 use crate::app::AppState;
-use crate::models::{Category, Crate, Keyword, TopVersions, Version};
+use crate::auth::AuthCheck;
+use crate::models::{
+    Category, Crate, CrateOwner, DocsRsStatus, FeaturedCrate, Keyword, OwnerKind, TopVersions,
+    Version,
+};
 use crate::schema::{
-    crate_downloads, crates, default_versions, keywords, metadata, recent_crate_downloads, versions,
+    crate_downloads, crate_owners, crates, default_versions, follows, keywords, metadata,
+    recent_crate_downloads, versions,
 };
-use crate::util::errors::AppResult;
+use crate::util::errors::{bad_request, AppResult, BoxedAppError};
 use crate::views::{EncodableCategory, EncodableCrate, EncodableKeyword};
-use axum_extra::json;
-use axum_extra::response::ErasedJson;
+use axum::extract::FromRequestParts;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use axum_extra::extract::Query;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use futures_util::FutureExt;
+use http::header;
+use http::request::Parts;
+use serde_json::json;
+use std::collections::HashSet;
 use std::future::Future;
+use std::str::FromStr;
+
+/// The maximum number of crates listed in each of the personal sections.
+const MAX_PERSONAL_CRATES: i64 = 10;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct SummaryQueryParams {
+    /// Additional data to include in the response.
+    ///
+    /// Valid values: `personal`.
+    ///
+    /// Defaults to no additional data. Including the `personal` data
+    /// requires authentication.
+    ///
+    /// This parameter expects a comma-separated list of values.
+    include: Option<String>,
+}
+
+impl SummaryQueryParams {
+    fn include(&self) -> AppResult<SummaryIncludeMode> {
+        let include = self
+            .include
+            .as_ref()
+            .map(|mode| SummaryIncludeMode::from_str(mode))
+            .transpose()?
+            .unwrap_or_default();
+        Ok(include)
+    }
+}
 
 /// Get front page data.
 ///
 /// This endpoint returns a summary of the most important data for the front
 /// page of crates.io.
+///
+/// With `?include=personal`, the response also contains the crates of the
+/// authenticated user that need attention, and the recently updated crates
+/// that the user follows. These responses must not be cached by shared caches.
 #[utoipa::path(
     get,
     path = "/api/v1/summary",
+    params(SummaryQueryParams),
+    security(
+        (),
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
     tag = "other",
     responses((status = 200, description = "Successful Response")),
 )]
-pub async fn get_summary(state: AppState) -> AppResult<ErasedJson> {
+pub async fn get_summary(
+    state: AppState,
+    params: SummaryQueryParams,
+    req: Parts,
+) -> AppResult<Response> {
+    let include = params.include()?;
+
+    let personal = if include.personal {
+        let mut conn = state.db_read_prefer_primary().await?;
+        let user_id = AuthCheck::default().check(&req, &mut conn).await?.user_id();
+        Some(load_personal_summary(&mut conn, user_id).await?)
+    } else {
+        None
+    };
+
     let mut conn = state.db_read().await?;
 
     let config = &state.config;
@@ -47,6 +113,8 @@ pub async fn get_summary(state: AppState) -> AppResult<ErasedJson> {
             .left_join(recent_crate_downloads::table)
             .left_join(default_versions::table)
             .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+            .filter(crates::hidden_at.is_null())
+            .filter(crates::quarantined_at.is_null())
             .order(crates::created_at.desc())
             .select(Record::as_select())
             .limit(10)
@@ -57,6 +125,8 @@ pub async fn get_summary(state: AppState) -> AppResult<ErasedJson> {
             .left_join(recent_crate_downloads::table)
             .left_join(default_versions::table)
             .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+            .filter(crates::hidden_at.is_null())
+            .filter(crates::quarantined_at.is_null())
             .filter(crates::updated_at.ne(crates::created_at))
             .order(crates::updated_at.desc())
             .select(Record::as_select())
@@ -68,6 +138,8 @@ pub async fn get_summary(state: AppState) -> AppResult<ErasedJson> {
             .left_join(recent_crate_downloads::table)
             .left_join(default_versions::table)
             .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+            .filter(crates::hidden_at.is_null())
+            .filter(crates::quarantined_at.is_null())
             .filter(crates::name.ne_all(&config.excluded_crate_names))
             .then_order_by(crate_downloads::downloads.desc())
             .select(Record::as_select())
@@ -79,6 +151,8 @@ pub async fn get_summary(state: AppState) -> AppResult<ErasedJson> {
             .inner_join(recent_crate_downloads::table)
             .left_join(default_versions::table)
             .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+            .filter(crates::hidden_at.is_null())
+            .filter(crates::quarantined_at.is_null())
             .filter(crates::name.ne_all(&config.excluded_crate_names))
             .then_order_by(recent_crate_downloads::downloads.desc())
             .select(Record::as_select())
@@ -93,11 +167,14 @@ pub async fn get_summary(state: AppState) -> AppResult<ErasedJson> {
             .boxed(),
     )?;
 
-    let (new_crates, most_downloaded, most_recently_downloaded, just_updated) = tokio::try_join!(
+    let featured_crates = load_featured_crates(&mut conn).await?;
+
+    let (new_crates, most_downloaded, most_recently_downloaded, just_updated, featured_crates) = tokio::try_join!(
         encode_crates(&mut conn, new_crates),
         encode_crates(&mut conn, most_downloaded),
         encode_crates(&mut conn, most_recently_downloaded),
         encode_crates(&mut conn, just_updated),
+        encode_crates(&mut conn, featured_crates),
     )?;
 
     let popular_categories = popular_categories
@@ -110,16 +187,148 @@ pub async fn get_summary(state: AppState) -> AppResult<ErasedJson> {
         .map(Keyword::into)
         .collect::<Vec<EncodableKeyword>>();
 
-    Ok(json!({
+    let mut summary = json!({
         "num_downloads": num_downloads,
         "num_crates": num_crates,
         "new_crates": new_crates,
         "most_downloaded": most_downloaded,
         "most_recently_downloaded": most_recently_downloaded,
         "just_updated": just_updated,
+        "featured_crates": featured_crates,
         "popular_keywords": popular_keywords,
         "popular_categories": popular_categories,
-    }))
+    });
+
+    let Some(personal) = personal else {
+        return Ok(Json(summary).into_response());
+    };
+
+    summary["personal"] = json!(personal);
+
+    // The personal data must not end up in the shared caches that the
+    // public summary is served from.
+    let headers = [(header::CACHE_CONTROL, "private, no-store")];
+    Ok((headers, Json(summary)).into_response())
+}
+
+/// Loads the crates that are currently highlighted by the crates.io team,
+/// the most recently started first.
+async fn load_featured_crates(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Record>> {
+    let mut crate_ids = FeaturedCrate::active_crate_ids(conn).await?;
+
+    // A crate might be highlighted by multiple overlapping entries.
+    let mut seen = HashSet::new();
+    crate_ids.retain(|id| seen.insert(*id));
+    crate_ids.truncate(10);
+
+    let mut records = crates::table
+        .inner_join(crate_downloads::table)
+        .left_join(recent_crate_downloads::table)
+        .left_join(default_versions::table)
+        .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .filter(crates::id.eq_any(&crate_ids))
+        .filter(crates::hidden_at.is_null())
+        .filter(crates::quarantined_at.is_null())
+        .select(Record::as_select())
+        .load(conn)
+        .await?;
+
+    records.sort_by_key(|record| crate_ids.iter().position(|id| *id == record.krate.id));
+
+    Ok(records)
+}
+
+#[derive(Debug, Default)]
+struct SummaryIncludeMode {
+    personal: bool,
+}
+
+impl SummaryIncludeMode {
+    const INVALID_COMPONENT: &'static str = "invalid component for ?include= (expected 'personal')";
+}
+
+impl FromStr for SummaryIncludeMode {
+    type Err = BoxedAppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut mode = Self::default();
+        for component in s.split(',') {
+            match component {
+                "" => {}
+                "personal" => mode.personal = true,
+                _ => return Err(bad_request(Self::INVALID_COMPONENT)),
+            }
+        }
+        Ok(mode)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PersonalSummary {
+    /// Crates owned by the user whose default version needs attention.
+    your_crates: Vec<CrateNeedingAttention>,
+    /// Recently updated crates that the user follows.
+    followed_crates: Vec<EncodableCrate>,
+}
+
+#[derive(Debug, Serialize)]
+struct CrateNeedingAttention {
+    name: String,
+    version: String,
+    issues: Vec<&'static str>,
+}
+
+async fn load_personal_summary(
+    conn: &mut AsyncPgConnection,
+    user_id: i32,
+) -> AppResult<PersonalSummary> {
+    let owned_crates = CrateOwner::by_owner_kind(OwnerKind::User)
+        .filter(crate_owners::owner_id.eq(user_id))
+        .select(crate_owners::crate_id);
+
+    // Security advisories are not tracked by crates.io yet, so failed
+    // docs.rs builds are currently the only issue that is reported.
+    let your_crates = crates::table
+        .inner_join(default_versions::table)
+        .inner_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .filter(crates::id.eq_any(owned_crates))
+        .filter(versions::docs_rs_status.eq(DocsRsStatus::Failure))
+        .select((crates::name, versions::num))
+        .order(crates::name)
+        .limit(MAX_PERSONAL_CRATES)
+        .load::<(String, String)>(conn)
+        .await?
+        .into_iter()
+        .map(|(name, version)| CrateNeedingAttention {
+            name,
+            version,
+            issues: vec!["docs_build_failed"],
+        })
+        .collect();
+
+    let followed_crates = follows::table
+        .filter(follows::user_id.eq(user_id))
+        .select(follows::crate_id);
+    let followed_crates = crates::table
+        .inner_join(crate_downloads::table)
+        .left_join(recent_crate_downloads::table)
+        .left_join(default_versions::table)
+        .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .filter(crates::id.eq_any(followed_crates))
+        .filter(crates::hidden_at.is_null())
+        .filter(crates::quarantined_at.is_null())
+        .order(crates::updated_at.desc())
+        .select(Record::as_select())
+        .limit(MAX_PERSONAL_CRATES)
+        .load(conn)
+        .await?;
+
+    let followed_crates = encode_crates(conn, followed_crates).await?;
+
+    Ok(PersonalSummary {
+        your_crates,
+        followed_crates,
+    })
 }
 
 #[derive(Debug, Queryable, Selectable)]
@@ -135,6 +344,8 @@ struct Record {
     default_version: Option<String>,
     #[diesel(select_expression = versions::columns::yanked.nullable())]
     yanked: Option<bool>,
+    #[diesel(select_expression = versions::columns::rust_version.nullable())]
+    msrv: Option<String>,
 }
 
 fn encode_crates(
@@ -166,6 +377,7 @@ fn encode_crates(
                     record.krate,
                     record.default_version.as_deref(),
                     record.yanked,
+                    record.msrv.as_deref(),
                     Some(&top_versions),
                     false,
                     record.total_downloads,
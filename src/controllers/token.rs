@@ -1,4 +1,4 @@
-use crate::models::ApiToken;
+use crate::models::{ApiToken, AuditLogAction, NewAuditLogEntry, Organization, OrganizationRole};
 use crate::schema::api_tokens;
 use crate::util::rfc3339;
 use crate::views::EncodableApiTokenWithToken;
@@ -6,7 +6,7 @@ use crate::views::EncodableApiTokenWithToken;
 use crate::app::AppState;
 use crate::auth::AuthCheck;
 use crate::models::token::{CrateScope, EndpointScope};
-use crate::util::errors::{bad_request, AppResult};
+use crate::util::errors::{bad_request, custom, AppResult};
 use axum::extract::{Path, Query};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
@@ -75,6 +75,9 @@ pub struct NewApiToken {
     endpoint_scopes: Option<Vec<String>>,
     #[serde(default, with = "rfc3339::option")]
     expired_at: Option<NaiveDateTime>,
+    /// Name of an organization that should own the token. Organization
+    /// tokens can only be used for crates owned by the organization.
+    organization: Option<String>,
 }
 
 /// The incoming serialization format for the `ApiToken` model.
@@ -146,11 +149,35 @@ pub async fn create_api_token(
         .transpose()
         .map_err(|_err| bad_request("invalid endpoint scope"))?;
 
+    let organization_id = match &new.api_token.organization {
+        Some(login) => {
+            let organization = Organization::find_by_login(&mut conn, login)
+                .await
+                .optional()?
+                .ok_or_else(|| bad_request(format!("could not find organization `{login}`")))?;
+
+            let role = organization.role_of(&mut conn, user.id).await?;
+            if !matches!(
+                role,
+                Some(OrganizationRole::Admin | OrganizationRole::Publisher)
+            ) {
+                return Err(custom(
+                    StatusCode::FORBIDDEN,
+                    format!("only admins and publishers of organization `{login}` can create tokens for it"),
+                ));
+            }
+
+            Some(organization.id)
+        }
+        None => None,
+    };
+
     let recipient = user.email(&mut conn).await?;
 
-    let api_token = ApiToken::insert_with_scopes(
+    let api_token = ApiToken::insert_for_organization(
         &mut conn,
         user.id,
+        organization_id,
         &new.api_token.name,
         crate_scopes,
         endpoint_scopes,
@@ -158,6 +185,16 @@ pub async fn create_api_token(
     )
     .await?;
 
+    NewAuditLogEntry::builder()
+        .user_id(user.id)
+        .action(AuditLogAction::TokenCreate)
+        .details(
+            serde_json::json!({ "token_id": api_token.model.id, "name": api_token.model.name }),
+        )
+        .build()
+        .insert(&mut conn)
+        .await?;
+
     if let Some(recipient) = recipient {
         let email = NewTokenEmail {
             token_name: &new.api_token.name,
@@ -232,11 +269,22 @@ pub async fn revoke_api_token(
     let mut conn = app.db_write().await?;
     let auth = AuthCheck::default().check(&req, &mut conn).await?;
     let user = auth.user();
-    diesel::update(ApiToken::belonging_to(user).find(id))
+    let updated = diesel::update(ApiToken::belonging_to(user).find(id))
         .set(api_tokens::revoked.eq(true))
         .execute(&mut conn)
         .await?;
 
+    if updated > 0 {
+        NewAuditLogEntry::builder()
+            .user_id(user.id)
+            .maybe_api_token_id(auth.api_token_id())
+            .action(AuditLogAction::TokenRevoke)
+            .details(serde_json::json!({ "token_id": id }))
+            .build()
+            .insert(&mut conn)
+            .await?;
+    }
+
     Ok(json!({}))
 }
 
@@ -263,6 +311,15 @@ pub async fn revoke_current_api_token(app: AppState, req: Parts) -> AppResult<Re
         .execute(&mut conn)
         .await?;
 
+    NewAuditLogEntry::builder()
+        .user_id(auth.user_id())
+        .api_token_id(api_token_id)
+        .action(AuditLogAction::TokenRevoke)
+        .details(serde_json::json!({ "token_id": api_token_id }))
+        .build()
+        .insert(&mut conn)
+        .await?;
+
     Ok(StatusCode::NO_CONTENT.into_response())
 }
 
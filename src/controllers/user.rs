@@ -1,8 +1,15 @@
+pub mod audit;
+pub mod dependency_updates;
 pub mod email_notifications;
 pub mod email_verification;
+pub mod emails;
+pub mod export;
+pub mod following;
+pub mod identities;
 pub mod me;
 pub mod other;
 pub mod update;
+pub mod usage;
 
 pub use email_verification::resend_email_verification;
 pub use update::update_user;
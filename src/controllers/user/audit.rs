@@ -0,0 +1,50 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::models::AuditLogEntry;
+use crate::schema::{audit_log, crates};
+use crate::util::errors::AppResult;
+use crate::views::EncodableAuditLogEntry;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use http::request::Parts;
+
+/// List the audit log entries of actions performed by the authenticated user.
+///
+/// The entries are sorted from newest to oldest.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/audit",
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_user_audit_log(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let query = audit_log::table
+        .left_join(crates::table)
+        .filter(audit_log::user_id.eq(user.id))
+        .order(audit_log::id.desc())
+        .select((audit_log::all_columns, crates::name.nullable()))
+        .pages_pagination(PaginationOptions::builder().gather(&req)?);
+
+    let data: Paginated<(AuditLogEntry, Option<String>)> = query.load(&mut conn).await?;
+    let total = data.total();
+
+    let entries = data
+        .into_iter()
+        .map(|(entry, crate_name)| {
+            EncodableAuditLogEntry::new(entry, Some(user.gh_login.clone()), crate_name)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "entries": entries,
+        "meta": { "total": total },
+    }))
+}
@@ -0,0 +1,252 @@
+//! Overview of the dependencies of the crates that a user owns, which got new
+//! versions since the crates were last published.
+//!
+//! The dependency requirements are taken from the default version of each
+//! crate, as stored when the version was published.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::models::{CrateOwner, DependencyKind, OwnerKind};
+use crate::schema::{crate_owners, crates, default_versions, dependencies, versions};
+use crate::util::errors::AppResult;
+use axum::Json;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+use semver::{Version, VersionReq};
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize)]
+pub struct DependencyUpdates {
+    crates: Vec<CrateUpdates>,
+}
+
+#[derive(Debug, Serialize)]
+struct CrateUpdates {
+    name: String,
+    /// The default version of the crate, whose dependencies are checked.
+    version: String,
+    dependencies: Vec<DependencyUpdate>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct DependencyUpdate {
+    name: String,
+    req: String,
+    kind: DependencyKind,
+    /// The newest version that matches the requirement and was published
+    /// after the version of the owned crate, so it is only used after
+    /// updating the lockfile.
+    compatible: Option<String>,
+    /// The newest version, if it is newer than all versions that match the
+    /// requirement, so the requirement has to be changed to use it.
+    incompatible: Option<String>,
+}
+
+struct DependencyVersion {
+    num: Version,
+    created_at: NaiveDateTime,
+}
+
+/// List new versions of the dependencies of the crates that the
+/// authenticated user owns.
+///
+/// For each owned crate, the direct dependencies of its default version are
+/// checked for newer semver compatible versions that were published after
+/// the default version, and for newer versions that are not compatible with
+/// the dependency requirement. Yanked versions and pre-releases are ignored.
+/// Crates without dependency updates are not included.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/dependency_updates",
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_dependency_updates(
+    app: AppState,
+    req: Parts,
+) -> AppResult<Json<DependencyUpdates>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let user_id = AuthCheck::default().check(&req, &mut conn).await?.user_id();
+
+    let owned: Vec<(String, i32, String, NaiveDateTime)> =
+        CrateOwner::by_owner_kind(OwnerKind::User)
+            .inner_join(crates::table)
+            .inner_join(default_versions::table.on(default_versions::crate_id.eq(crates::id)))
+            .inner_join(versions::table.on(versions::id.eq(default_versions::version_id)))
+            .filter(crate_owners::owner_id.eq(user_id))
+            .order(crates::name.asc())
+            .select((
+                crates::name,
+                versions::id,
+                versions::num,
+                versions::created_at,
+            ))
+            .load(&mut conn)
+            .await?;
+
+    let version_ids = owned.iter().map(|(_, id, ..)| *id).collect::<Vec<_>>();
+    let deps: Vec<(i32, i32, String, String, DependencyKind)> = dependencies::table
+        .inner_join(crates::table)
+        .filter(dependencies::version_id.eq_any(&version_ids))
+        .order((crates::name.asc(), dependencies::kind.asc()))
+        .select((
+            dependencies::version_id,
+            dependencies::crate_id,
+            crates::name,
+            dependencies::req,
+            dependencies::kind,
+        ))
+        .load(&mut conn)
+        .await?;
+
+    let dependency_ids = deps.iter().map(|(_, id, ..)| *id).collect::<Vec<_>>();
+    let rows: Vec<(i32, String, NaiveDateTime)> = versions::table
+        .filter(versions::crate_id.eq_any(&dependency_ids))
+        .filter(versions::yanked.eq(false))
+        .filter(crate::models::Version::not_deleted())
+        .select((versions::crate_id, versions::num, versions::created_at))
+        .load(&mut conn)
+        .await?;
+
+    let mut dependency_versions: HashMap<i32, Vec<DependencyVersion>> = HashMap::new();
+    for (crate_id, num, created_at) in rows {
+        let Ok(num) = Version::parse(&num) else {
+            continue;
+        };
+        if num.pre.is_empty() {
+            let version = DependencyVersion { num, created_at };
+            dependency_versions
+                .entry(crate_id)
+                .or_default()
+                .push(version);
+        }
+    }
+
+    let mut deps_by_version: HashMap<i32, Vec<_>> = HashMap::new();
+    for (version_id, crate_id, name, req, kind) in deps {
+        let entry = deps_by_version.entry(version_id).or_default();
+        entry.push((crate_id, name, req, kind));
+    }
+
+    let crates = owned
+        .into_iter()
+        .filter_map(|(name, version_id, version, published_at)| {
+            let dependencies = deps_by_version
+                .remove(&version_id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(crate_id, name, req, kind)| {
+                    let versions = dependency_versions.get(&crate_id).map(Vec::as_slice);
+                    let versions = versions.unwrap_or_default();
+                    check_dependency(name, req, kind, versions, published_at)
+                })
+                .collect::<Vec<_>>();
+
+            (!dependencies.is_empty()).then_some(CrateUpdates {
+                name,
+                version,
+                dependencies,
+            })
+        })
+        .collect();
+
+    Ok(Json(DependencyUpdates { crates }))
+}
+
+/// Returns the updates of a dependency, or `None` if there are no updates.
+fn check_dependency(
+    name: String,
+    req: String,
+    kind: DependencyKind,
+    versions: &[DependencyVersion],
+    published_at: NaiveDateTime,
+) -> Option<DependencyUpdate> {
+    let parsed_req = VersionReq::parse(&req).ok()?;
+
+    let latest_matching = versions
+        .iter()
+        .filter(|version| parsed_req.matches(&version.num))
+        .max_by(|a, b| a.num.cmp(&b.num));
+
+    let compatible = latest_matching
+        .filter(|version| version.created_at > published_at)
+        .map(|version| version.num.to_string());
+
+    // Requirements without any matching versions are skipped, since it's
+    // not clear whether the newest version is actually newer than them.
+    let incompatible = versions
+        .iter()
+        .map(|version| &version.num)
+        .max()
+        .filter(|latest| latest_matching.is_some_and(|matching| **latest > matching.num))
+        .map(|latest| latest.to_string());
+
+    if compatible.is_none() && incompatible.is_none() {
+        return None;
+    }
+
+    Some(DependencyUpdate {
+        name,
+        req,
+        kind,
+        compatible,
+        incompatible,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn date(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn versions(versions: &[(&str, u32)]) -> Vec<DependencyVersion> {
+        versions
+            .iter()
+            .map(|(num, day)| DependencyVersion {
+                num: Version::parse(num).unwrap(),
+                created_at: date(*day),
+            })
+            .collect()
+    }
+
+    fn check(
+        req: &str,
+        versions: &[DependencyVersion],
+    ) -> Option<(Option<String>, Option<String>)> {
+        let kind = DependencyKind::Normal;
+        check_dependency("foo".into(), req.into(), kind, versions, date(10))
+            .map(|update| (update.compatible, update.incompatible))
+    }
+
+    #[test]
+    fn test_check_dependency() {
+        let versions = versions(&[("1.0.0", 1), ("1.1.0", 5), ("1.2.0", 15), ("2.0.0", 20)]);
+
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            check("^1.0", &versions),
+            Some((some("1.2.0"), some("2.0.0")))
+        );
+        assert_eq!(check("^2.0", &versions), Some((some("2.0.0"), None)));
+        assert_eq!(check("=1.1.0", &versions), Some((None, some("2.0.0"))));
+        assert_eq!(check("^3.0", &versions), None);
+        assert_eq!(check("not a requirement", &versions), None);
+
+        // Compatible versions that were published before the owned crate
+        // were already available when it was published.
+        assert_eq!(check("^1.0", &versions[..2]), None);
+    }
+}
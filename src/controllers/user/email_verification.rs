@@ -68,22 +68,26 @@ pub async fn resend_email_verification(
 
     conn.transaction(|conn| {
         async move {
-            let email: Email = diesel::update(Email::belonging_to(auth.user()))
-                .set(emails::token.eq(sql("DEFAULT")))
-                .get_result(conn)
-                .await
-                .optional()?
-                .ok_or_else(|| bad_request("Email could not be found"))?;
+            let email: Email =
+                diesel::update(Email::belonging_to(auth.user()).filter(emails::is_primary))
+                    .set(emails::token.eq(sql("DEFAULT")))
+                    .get_result(conn)
+                    .await
+                    .optional()?
+                    .ok_or_else(|| bad_request("Email could not be found"))?;
 
             let email1 = UserConfirmEmail {
                 user_name: &auth.user().gh_login,
                 domain: &state.emails.domain,
                 token: email.token,
+                locale: auth.user().locale.as_deref(),
             };
 
+            let key = email1.idempotency_key(&email.email);
+
             state
                 .emails
-                .send(&email.email, email1)
+                .send_idempotent(conn, &key, &email.email, email1)
                 .await
                 .map_err(BoxedAppError::from)
         }
@@ -0,0 +1,282 @@
+use super::update::UserConfirmEmail;
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::ok_true;
+use crate::models::{AuditLogAction, Email, NewAuditLogEntry, NewEmail};
+use crate::schema::emails;
+use crate::util::errors::{bad_request, not_found, AppResult, BoxedAppError};
+use crate::views::EncodableEmail;
+use axum::extract::Path;
+use axum::response::Response;
+use axum::Json;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use crates_io_diesel_helpers::lower;
+use diesel::dsl::{count_star, exists};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use http::request::Parts;
+use lettre::Address;
+
+/// The maximum number of email addresses a single user may register.
+const MAX_EMAILS_PER_USER: i64 = 32;
+
+#[derive(Deserialize)]
+pub struct NewEmailRequest {
+    email: String,
+}
+
+/// List all email addresses of the authenticated user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/emails",
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_emails(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let emails: Vec<Email> = Email::belonging_to(auth.user())
+        .order(emails::id)
+        .load(&mut conn)
+        .await?;
+
+    let emails = emails
+        .into_iter()
+        .map(EncodableEmail::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "emails": emails }))
+}
+
+/// Add an email address to the authenticated user.
+///
+/// A confirmation email is sent to the new address, which has to be verified
+/// before it can be used as the primary email address. If the user has no
+/// email address yet, the new address automatically becomes the primary one.
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/emails",
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn add_email(
+    app: AppState,
+    req: Parts,
+    Json(new): Json<NewEmailRequest>,
+) -> AppResult<ErasedJson> {
+    let address = new.email.trim();
+
+    if address.is_empty() {
+        return Err(bad_request("empty email rejected"));
+    }
+
+    address
+        .parse::<Address>()
+        .map_err(|_| bad_request("invalid email address"))?;
+
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let email = conn
+        .transaction(|conn| {
+            async move {
+                let existing: i64 = Email::belonging_to(user)
+                    .select(count_star())
+                    .get_result(conn)
+                    .await?;
+
+                if existing >= MAX_EMAILS_PER_USER {
+                    let detail =
+                        format!("maximum email addresses per user reached ({MAX_EMAILS_PER_USER})");
+                    return Err(bad_request(detail));
+                }
+
+                let duplicate = Email::belonging_to(user)
+                    .filter(lower(emails::email).eq(address.to_lowercase()))
+                    .select(emails::id);
+
+                let duplicate = diesel::select(exists(duplicate))
+                    .get_result::<bool>(conn)
+                    .await?;
+
+                if duplicate {
+                    return Err(bad_request("email address has already been added"));
+                }
+
+                let new_email = NewEmail {
+                    user_id: user.id,
+                    email: address,
+                    is_primary: existing == 0,
+                };
+
+                let email: Email = diesel::insert_into(emails::table)
+                    .values(&new_email)
+                    .get_result(conn)
+                    .await?;
+
+                NewAuditLogEntry::builder()
+                    .user_id(user.id)
+                    .action(AuditLogAction::EmailAdd)
+                    .details(serde_json::json!({ "email": email.email }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, BoxedAppError>(email)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let confirm_email = UserConfirmEmail {
+        user_name: &user.gh_login,
+        domain: &app.emails.domain,
+        token: email.token.clone(),
+        locale: user.locale.as_deref(),
+    };
+
+    // Similar to `update_user()`, this swallows any errors that occur while
+    // attempting to send the email, since the user can request a new
+    // confirmation email later on.
+    let key = confirm_email.idempotency_key(&email.email);
+    let result = app
+        .emails
+        .send_idempotent(&mut conn, &key, &email.email, confirm_email)
+        .await;
+
+    if let Err(error) = result {
+        warn!(
+            "Failed to send confirmation email to {}: {error}",
+            email.email
+        );
+    }
+
+    Ok(json!({ "email": EncodableEmail::from(email) }))
+}
+
+/// Remove an email address from the authenticated user.
+///
+/// The primary email address can not be removed. Use
+/// `PUT /api/v1/me/emails/{id}/primary` to choose a different primary
+/// address first.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/emails/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the email address"),
+    ),
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn delete_email(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let email: Email = Email::belonging_to(auth.user())
+        .find(id)
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    if email.is_primary {
+        return Err(bad_request("the primary email address can not be removed"));
+    }
+
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(&email).execute(conn).await?;
+
+            NewAuditLogEntry::builder()
+                .user_id(email.user_id)
+                .action(AuditLogAction::EmailRemove)
+                .details(serde_json::json!({ "email": email.email }))
+                .build()
+                .insert(conn)
+                .await?;
+
+            Ok::<_, BoxedAppError>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    ok_true()
+}
+
+/// Make an email address the primary address of the authenticated user.
+///
+/// Only verified email addresses can become the primary address. The primary
+/// address is used for all notifications sent by crates.io.
+#[utoipa::path(
+    put,
+    path = "/api/v1/me/emails/{id}/primary",
+    params(
+        ("id" = i32, Path, description = "ID of the email address"),
+    ),
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn set_primary_email(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    conn.transaction(|conn| {
+        async move {
+            let email: Email = Email::belonging_to(user)
+                .find(id)
+                .for_update()
+                .first(conn)
+                .await
+                .optional()?
+                .ok_or_else(not_found)?;
+
+            if !email.verified {
+                return Err(bad_request(
+                    "only verified email addresses can be used as the primary email address",
+                ));
+            }
+
+            if email.is_primary {
+                return Ok(());
+            }
+
+            diesel::update(Email::belonging_to(user).filter(emails::is_primary))
+                .set(emails::is_primary.eq(false))
+                .execute(conn)
+                .await?;
+
+            diesel::update(&email)
+                .set(emails::is_primary.eq(true))
+                .execute(conn)
+                .await?;
+
+            NewAuditLogEntry::builder()
+                .user_id(user.id)
+                .action(AuditLogAction::EmailPrimary)
+                .details(serde_json::json!({ "email": email.email }))
+                .build()
+                .insert(conn)
+                .await?;
+
+            Ok::<_, BoxedAppError>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    ok_true()
+}
@@ -0,0 +1,118 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::models::AccountExport;
+use crate::util::errors::{not_found, AppResult, BoxedAppError};
+use crate::views::EncodableAccountExport;
+use crate::worker::jobs::ExportAccountData;
+use axum::extract::Path;
+use axum::response::{IntoResponse, Response};
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use crates_io_worker::BackgroundJob;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::AsyncConnection;
+use http::header;
+use http::request::Parts;
+
+/// Request an export of all data associated with the authenticated user.
+///
+/// If the user has no pending or downloadable export, a new export is
+/// enqueued and the user is notified by email once it is ready. Otherwise
+/// the existing export is returned.
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/export",
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn create_account_export(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+    let user_id = auth.user_id();
+
+    let export = conn
+        .transaction(|conn| {
+            async move {
+                if let Some(export) = AccountExport::find_active(conn, user_id).await? {
+                    return Ok(export);
+                }
+
+                let export = AccountExport::insert(conn, user_id).await?;
+                ExportAccountData::new(export.id).enqueue(conn).await?;
+
+                Ok::<_, BoxedAppError>(export)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(json!({ "export": EncodableAccountExport::from(export) }))
+}
+
+/// Get the current account data export of the authenticated user.
+///
+/// The response contains the state of the most recent export that is still
+/// pending or downloadable and, once it has been generated, the secret
+/// download URL.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/export",
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_account_export(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let export = AccountExport::find_active(&mut conn, auth.user_id())
+        .await?
+        .ok_or_else(not_found)?;
+
+    Ok(json!({ "export": EncodableAccountExport::from(export) }))
+}
+
+/// Download a previously generated account data export.
+///
+/// The secret token in the URL serves as the authorization for this
+/// endpoint, and is only valid until the export expires.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/export/{token}",
+    params(
+        ("token" = String, Path, description = "Secret download token of the export"),
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn download_account_export(
+    app: AppState,
+    Path(token): Path<String>,
+) -> AppResult<Response> {
+    let mut conn = app.db_read_prefer_primary().await?;
+
+    let export = AccountExport::find_by_token(&mut conn, &token)
+        .await?
+        .ok_or_else(not_found)?;
+
+    let bytes = app
+        .storage
+        .download_account_export(export.user_id, export.id)
+        .await
+        .map_err(|error| match error {
+            object_store::Error::NotFound { .. } => not_found(),
+            error => Box::new(error),
+        })?;
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/json"),
+        (
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"crates-io-export.json\"",
+        ),
+        (header::CACHE_CONTROL, "private, no-store"),
+    ];
+
+    Ok((headers, bytes).into_response())
+}
@@ -0,0 +1,206 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::pagination::{
+    Paginated, PaginationOptions, PaginationQueryParams,
+};
+use crate::controllers::helpers::Paginate;
+use crate::controllers::krate::follow::FollowNotifications;
+use crate::models::Follow;
+use crate::schema::{crates, follows};
+use crate::util::errors::{bad_request, AppResult, BoxedAppError};
+use axum::extract::FromRequestParts;
+use axum::Json;
+use axum_extra::extract::Query;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use http::request::Parts;
+use std::collections::BTreeSet;
+
+/// The maximum number of crates that can be followed or unfollowed in a
+/// single bulk request.
+const MAX_BULK_CRATES: usize = 1000;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct ListQueryParams {
+    /// The sort order of the crates.
+    ///
+    /// Valid values: `alpha`, and `recent-updates`.
+    ///
+    /// Defaults to `alpha`.
+    sort: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct FollowedCrate {
+    id: i32,
+    name: String,
+    updated_at: DateTime<Utc>,
+    notifications: FollowNotifications,
+}
+
+/// List the crates that the authenticated user follows.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/following",
+    params(ListQueryParams, PaginationQueryParams),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_followed_crates(
+    app: AppState,
+    params: ListQueryParams,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let user_id = AuthCheck::default().check(&req, &mut conn).await?.user_id();
+
+    let mut query = follows::table
+        .inner_join(crates::table)
+        .filter(follows::user_id.eq(user_id))
+        .select((
+            crates::id,
+            crates::name,
+            crates::updated_at,
+            (follows::notify_email, follows::webhook_url),
+        ))
+        .into_boxed();
+
+    query = match params.sort.as_deref() {
+        Some("recent-updates") => query.order((crates::updated_at.desc(), crates::id.desc())),
+        Some("alpha") | None => query.order(crates::name.asc()),
+        Some(sort) => return Err(bad_request(format!("invalid sort order: {sort}"))),
+    };
+
+    let query = query.pages_pagination(PaginationOptions::builder().gather(&req)?);
+    let data: Paginated<(i32, String, DateTime<Utc>, FollowNotifications)> =
+        query.load(&mut conn).await?;
+
+    let total = data.total();
+    let more = data.next_page_params().is_some();
+
+    let crates = data
+        .into_iter()
+        .map(|(id, name, updated_at, notifications)| FollowedCrate {
+            id,
+            name,
+            updated_at,
+            notifications,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "crates": crates,
+        "meta": { "total": total, "more": more },
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkFollowRequest {
+    /// Names of the crates to follow.
+    #[serde(default)]
+    follow: BTreeSet<String>,
+    /// Names of the crates to unfollow.
+    #[serde(default)]
+    unfollow: BTreeSet<String>,
+}
+
+/// Follow and unfollow multiple crates at once.
+///
+/// Crates that don't exist are skipped and listed in the `not_found` field
+/// of the response, so that e.g. all dependencies of a `Cargo.lock` file can
+/// be followed without filtering out git and path dependencies first.
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/following/bulk",
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn bulk_update_followed_crates(
+    app: AppState,
+    req: Parts,
+    Json(request): Json<BulkFollowRequest>,
+) -> AppResult<ErasedJson> {
+    if request.follow.len() + request.unfollow.len() > MAX_BULK_CRATES {
+        let detail =
+            format!("cannot follow or unfollow more than {MAX_BULK_CRATES} crates at once");
+        return Err(bad_request(detail));
+    }
+
+    if let Some(name) = request.follow.intersection(&request.unfollow).next() {
+        let detail = format!("cannot follow and unfollow `{name}` at the same time");
+        return Err(bad_request(detail));
+    }
+
+    let mut conn = app.db_write().await?;
+    let user_id = AuthCheck::default().check(&req, &mut conn).await?.user_id();
+
+    let names = request.follow.iter().chain(&request.unfollow);
+    let found: Vec<(i32, String)> = crates::table
+        .filter(crates::name.eq_any(names.collect::<Vec<_>>()))
+        .select((crates::id, crates::name))
+        .load(&mut conn)
+        .await?;
+
+    let found_names = found.iter().map(|(_, name)| name).collect::<BTreeSet<_>>();
+    let not_found = request
+        .follow
+        .iter()
+        .chain(&request.unfollow)
+        .filter(|name| !found_names.contains(name))
+        .collect::<BTreeSet<_>>();
+
+    let (to_follow, to_unfollow): (Vec<_>, Vec<_>) = found
+        .iter()
+        .partition(|(_, name)| request.follow.contains(name));
+
+    let follows = to_follow
+        .iter()
+        .map(|(crate_id, _)| Follow {
+            user_id,
+            crate_id: *crate_id,
+        })
+        .collect::<Vec<_>>();
+
+    let unfollow_ids = to_unfollow.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+
+    let (followed, unfollowed) = conn
+        .transaction(|conn| {
+            async move {
+                let followed = diesel::insert_into(follows::table)
+                    .values(&follows)
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+                    .await?;
+
+                let unfollowed = diesel::delete(follows::table)
+                    .filter(follows::user_id.eq(user_id))
+                    .filter(follows::crate_id.eq_any(unfollow_ids))
+                    .execute(conn)
+                    .await?;
+
+                Ok::<_, BoxedAppError>((followed, unfollowed))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(json!({
+        "followed": followed,
+        "unfollowed": unfollowed,
+        "not_found": not_found,
+    }))
+}
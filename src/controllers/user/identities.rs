@@ -0,0 +1,72 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::ok_true;
+use crate::models::LinkedIdentity;
+use crate::schema::linked_identities;
+use crate::util::errors::{not_found, AppResult};
+use crate::views::EncodableLinkedIdentity;
+use axum::extract::Path;
+use axum::response::Response;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+
+/// List the accounts of additional identity providers that are linked to
+/// the authenticated user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/identities",
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_linked_identities(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let identities: Vec<LinkedIdentity> = LinkedIdentity::belonging_to(auth.user())
+        .select(LinkedIdentity::as_select())
+        .order(linked_identities::id)
+        .load(&mut conn)
+        .await?;
+
+    let identities = identities
+        .into_iter()
+        .map(EncodableLinkedIdentity::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "identities": identities }))
+}
+
+/// Unlink the account of an additional identity provider from the
+/// authenticated user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/identities/{id}",
+    params(
+        ("id" = i32, Path, description = "ID of the linked identity"),
+    ),
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn unlink_identity(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let deleted = diesel::delete(LinkedIdentity::belonging_to(auth.user()).find(id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(not_found());
+    }
+
+    ok_true()
+}
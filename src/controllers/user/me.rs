@@ -32,7 +32,7 @@ pub async fn get_authenticated_user(app: AppState, req: Parts) -> AppResult<Json
     let (user, verified, email, verification_sent): (User, Option<bool>, Option<String>, bool) =
         users::table
             .find(user_id)
-            .left_join(emails::table)
+            .left_join(emails::table.on(emails::user_id.eq(users::id).and(emails::is_primary)))
             .select((
                 User::as_select(),
                 emails::verified.nullable(),
@@ -84,6 +84,7 @@ pub async fn get_authenticated_user_updates(app: AppState, req: Parts) -> AppRes
         .inner_join(crates::table)
         .left_outer_join(users::table)
         .filter(crates::id.eq_any(followed_crates))
+        .filter(Version::not_deleted())
         .order(versions::created_at.desc())
         .select(<(Version, CrateName, Option<User>)>::as_select())
         .pages_pagination(PaginationOptions::builder().gather(&req)?);
@@ -1,18 +1,33 @@
-use axum::extract::Path;
+use axum::extract::{FromRequestParts, Path};
+use axum_extra::extract::Query;
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
 use bigdecimal::{BigDecimal, ToPrimitive};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
+use http::request::Parts;
 
 use crate::app::AppState;
-use crate::models::{CrateOwner, OwnerKind, User};
-use crate::schema::{crate_downloads, crate_owners, crates};
+use crate::controllers::helpers::pagination::{
+    Paginated, PaginationOptions, PaginationQueryParams,
+};
+use crate::controllers::helpers::Paginate;
+use crate::models::krate::CrateName;
+use crate::models::{CrateOwner, OwnerKind, User, UserStats, Version, VersionOwnerAction};
+use crate::schema::{crate_downloads, crate_owners, crates, user_stats, users, versions};
+use crate::surrogate_keys::SurrogateKeys;
 use crate::util::errors::AppResult;
-use crate::views::EncodablePublicUser;
-use crates_io_diesel_helpers::lower;
+use crate::util::string_excl_null::StringExclNull;
+use crate::util::RequestUtils;
+use crate::views::{EncodablePublicUser, EncodableUserStats, EncodableVersion};
+use crates_io_diesel_helpers::{canon_crate_name, lower};
 
 /// Find user by login.
+///
+/// If the user opted in to public stats, the response also contains their
+/// total downloads, the number of versions they published per year, and the
+/// users they most frequently own crates with. The stats are updated once
+/// per day.
 #[utoipa::path(
     get,
     path = "/api/v1/users/{user}",
@@ -22,19 +37,29 @@ use crates_io_diesel_helpers::lower;
     tag = "users",
     responses((status = 200, description = "Successful Response")),
 )]
-pub async fn find_user(state: AppState, Path(user_name): Path<String>) -> AppResult<ErasedJson> {
+pub async fn find_user(
+    state: AppState,
+    Path(user_name): Path<String>,
+) -> AppResult<(SurrogateKeys, ErasedJson)> {
     let mut conn = state.db_read_prefer_primary().await?;
 
-    use crate::schema::users::dsl::{gh_login, id, users};
-
     let name = lower(&user_name);
-    let user: User = users
-        .filter(lower(gh_login).eq(name))
-        .order(id.desc())
+    let (user, stats): (User, Option<UserStats>) = users::table
+        .left_join(user_stats::table)
+        .filter(lower(users::gh_login).eq(name))
+        .order(users::id.desc())
+        .select((User::as_select(), Option::<UserStats>::as_select()))
         .first(&mut conn)
         .await?;
 
-    Ok(json!({ "user": EncodablePublicUser::from(user) }))
+    let stats = stats
+        .filter(|_| user.public_stats)
+        .map(EncodableUserStats::from);
+
+    let surrogate_keys = SurrogateKeys::user(user.id);
+    let json = json!({ "user": EncodablePublicUser::from(user), "stats": stats });
+
+    Ok((surrogate_keys, json))
 }
 
 /// Get user stats.
@@ -68,3 +93,85 @@ pub async fn get_user_stats(state: AppState, Path(user_id): Path<i32>) -> AppRes
 
     Ok(json!({ "total_downloads": data }))
 }
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct ListVersionsQueryParams {
+    /// If set, only versions of the crates with the specified names are
+    /// returned.
+    #[serde(rename = "crate[]", default)]
+    #[param(inline)]
+    crates: Vec<StringExclNull>,
+}
+
+/// List versions published by a user.
+///
+/// All versions that were published by the user are returned, including
+/// yanked versions and versions of crates that the user doesn't own anymore,
+/// so that it is possible to audit what an account has published, e.g. after
+/// it has been compromised. The versions are sorted from newest to oldest.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{user}/versions",
+    params(
+        ("user" = String, Path, description = "Login name of the user"),
+        ListVersionsQueryParams,
+        PaginationQueryParams,
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_user_versions(
+    state: AppState,
+    Path(user_name): Path<String>,
+    params: ListVersionsQueryParams,
+    req: Parts,
+) -> AppResult<ErasedJson> {
+    let mut conn = state.db_read_prefer_primary().await?;
+
+    let user = User::find_by_login(&mut conn, &user_name).await?;
+
+    let mut query = versions::table
+        .inner_join(crates::table)
+        .filter(versions::published_by.eq(user.id))
+        .filter(Version::not_deleted())
+        .order((versions::created_at.desc(), versions::id.desc()))
+        .select(<(Version, CrateName)>::as_select())
+        .into_boxed();
+
+    if !params.crates.is_empty() {
+        let names = params.crates.iter().map(|name| canonicalize(name));
+        let names = names.collect::<Vec<_>>();
+        query = query.filter(canon_crate_name(crates::name).eq_any(names));
+    }
+
+    let pagination = PaginationOptions::builder().gather(&req)?;
+    let data: Paginated<(Version, CrateName)> =
+        query.pages_pagination(pagination).load(&mut conn).await?;
+
+    let total = data.total();
+    let next_page = data.next_page_params().map(|p| req.query_with_params(p));
+
+    let versions = data.iter().map(|(v, _)| v).collect::<Vec<_>>();
+    let actions = VersionOwnerAction::for_versions(&mut conn, &versions).await?;
+
+    let versions = data
+        .into_iter()
+        .zip(actions)
+        .map(|((version, crate_name), actions)| {
+            let published_by = Some(user.clone());
+            EncodableVersion::from(version, &crate_name.name, published_by, actions)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "versions": versions,
+        "meta": { "total": total, "next_page": next_page },
+    }))
+}
+
+/// Canonicalizes a crate name like the `canon_crate_name` SQL function.
+fn canonicalize(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
@@ -1,14 +1,21 @@
 use crate::app::AppState;
 use crate::auth::AuthCheck;
 use crate::controllers::helpers::ok_true;
-use crate::models::NewEmail;
+use crate::email::i18n;
+use crate::models::{AuditLogAction, Email, NewAuditLogEntry, NewEmail};
 use crate::schema::{emails, users};
 use crate::util::errors::{bad_request, server_error, AppResult};
+use crate::worker::jobs::PurgeSurrogateKeys;
 use axum::extract::Path;
 use axum::response::Response;
 use axum::Json;
+use crates_io_diesel_helpers::lower;
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::{exists, sql};
 use diesel::prelude::*;
+use diesel::sql_types::Integer;
 use diesel_async::RunQueryDsl;
+use fluent_bundle::FluentArgs;
 use http::request::Parts;
 use lettre::Address;
 use secrecy::{ExposeSecret, SecretString};
@@ -22,11 +29,17 @@ pub struct UserUpdate {
 pub struct User {
     email: Option<String>,
     publish_notifications: Option<bool>,
+    locale: Option<String>,
+    weekly_digest: Option<bool>,
+    contact_requests: Option<bool>,
+    public_stats: Option<bool>,
 }
 
 /// Update user settings.
 ///
-/// This endpoint allows users to update their email address and publish notifications settings.
+/// This endpoint allows users to update their primary email address, publish notifications,
+/// weekly digest, contact request and public stats settings, and the locale that emails are
+/// sent in.
 ///
 /// The `id` parameter needs to match the ID of the currently authenticated user.
 #[utoipa::path(
@@ -82,6 +95,46 @@ pub async fn update_user(
         }
     }
 
+    if let Some(weekly_digest) = user_update.user.weekly_digest {
+        diesel::update(user)
+            .set(users::weekly_digest.eq(weekly_digest))
+            .execute(&mut conn)
+            .await?;
+    }
+
+    if let Some(contact_requests) = user_update.user.contact_requests {
+        diesel::update(user)
+            .set(users::contact_requests.eq(contact_requests))
+            .execute(&mut conn)
+            .await?;
+    }
+
+    if let Some(public_stats) = user_update.user.public_stats {
+        diesel::update(user)
+            .set(users::public_stats.eq(public_stats))
+            .execute(&mut conn)
+            .await?;
+
+        PurgeSurrogateKeys::user(user.id).enqueue(&mut conn).await?;
+    }
+
+    let mut locale = user.locale.as_deref();
+
+    if let Some(new_locale) = &user_update.user.locale {
+        if !i18n::is_supported(new_locale) {
+            let supported = i18n::supported_locales().collect::<Vec<_>>().join(", ");
+            let detail = format!("unsupported locale, expected one of: {supported}");
+            return Err(bad_request(detail));
+        }
+
+        diesel::update(user)
+            .set(users::locale.eq(new_locale))
+            .execute(&mut conn)
+            .await?;
+
+        locale = Some(new_locale.as_str());
+    }
+
     if let Some(user_email) = &user_update.user.email {
         let user_email = user_email.trim();
 
@@ -93,14 +146,33 @@ pub async fn update_user(
             .parse::<Address>()
             .map_err(|_| bad_request("invalid email address"))?;
 
+        // The address can't be stored twice, so secondary addresses have to
+        // be promoted with the `PUT /api/v1/me/emails/{id}/primary` endpoint
+        // instead, which also checks that they have been verified.
+        let secondary = Email::belonging_to(user)
+            .filter(lower(emails::email).eq(user_email.to_lowercase()))
+            .filter(emails::is_primary.eq(false))
+            .select(emails::id);
+
+        let is_secondary = diesel::select(exists(secondary))
+            .get_result::<bool>(&mut conn)
+            .await?;
+
+        if is_secondary {
+            return Err(bad_request(
+                "email address has already been added as a secondary address",
+            ));
+        }
+
         let new_email = NewEmail {
             user_id: user.id,
             email: user_email,
+            is_primary: true,
         };
 
         let token = diesel::insert_into(emails::table)
             .values(&new_email)
-            .on_conflict(emails::user_id)
+            .on_conflict(sql::<Integer>("(user_id) WHERE is_primary"))
             .do_update()
             .set(&new_email)
             .returning(emails::token)
@@ -109,6 +181,14 @@ pub async fn update_user(
             .map(SecretString::from)
             .map_err(|_| server_error("Error in creating token"))?;
 
+        NewAuditLogEntry::builder()
+            .user_id(user.id)
+            .action(AuditLogAction::EmailPrimary)
+            .details(serde_json::json!({ "email": user_email }))
+            .build()
+            .insert(&mut conn)
+            .await?;
+
         // This swallows any errors that occur while attempting to send the email. Some users have
         // an invalid email set in their GitHub profile, and we should let them sign in even though
         // we're trying to silently use their invalid address during signup and can't send them an
@@ -117,9 +197,14 @@ pub async fn update_user(
             user_name: &user.gh_login,
             domain: &state.emails.domain,
             token,
+            locale,
         };
 
-        let _ = state.emails.send(user_email, email).await;
+        let key = email.idempotency_key(user_email);
+        let _ = state
+            .emails
+            .send_idempotent(&mut conn, &key, user_email, email)
+            .await;
     }
 
     ok_true()
@@ -129,11 +214,19 @@ pub struct UserConfirmEmail<'a> {
     pub user_name: &'a str,
     pub domain: &'a str,
     pub token: SecretString,
+    pub locale: Option<&'a str>,
+}
+
+impl UserConfirmEmail<'_> {
+    /// The key that ensures the confirmation is only sent once to the recipient.
+    pub fn idempotency_key(&self, recipient: &str) -> String {
+        format!("confirm-email:{}:{recipient}", self.token.expose_secret())
+    }
 }
 
 impl crate::email::Email for UserConfirmEmail<'_> {
     fn subject(&self) -> String {
-        "crates.io: Please confirm your email address".into()
+        i18n::translate(self.locale, "confirm-email-subject", &FluentArgs::new())
     }
 
     fn body(&self) -> String {
@@ -141,14 +234,12 @@ impl crate::email::Email for UserConfirmEmail<'_> {
         // If user clicks on path, look email/user up in database,
         // make sure tokens match
 
-        format!(
-            "Hello {user_name}! Welcome to crates.io. Please click the
-link below to verify your email address. Thank you!\n
-https://{domain}/confirm/{token}",
-            user_name = self.user_name,
-            domain = self.domain,
-            token = self.token.expose_secret(),
-        )
+        let mut args = FluentArgs::new();
+        args.set("user_name", self.user_name);
+        args.set("domain", self.domain);
+        args.set("token", self.token.expose_secret());
+
+        i18n::translate(self.locale, "confirm-email-body", &args)
     }
 }
 
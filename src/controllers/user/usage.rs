@@ -0,0 +1,41 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::models::ApiUsageCount;
+use crate::util::errors::AppResult;
+use crate::views::EncodableApiUsage;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use chrono::{Days, Utc};
+use http::request::Parts;
+
+/// The number of days of API usage that are returned.
+const USAGE_DAYS: u64 = 90;
+
+/// Get the API usage of the authenticated user.
+///
+/// Returns the daily number of authenticated API requests of the user in the
+/// last 90 days, split by whether they were authenticated by the session
+/// cookie or by an API token, ordered from oldest to newest.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/usage",
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_my_api_usage(app: AppState, req: Parts) -> AppResult<ErasedJson> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+
+    let since = Utc::now().date_naive() - Days::new(USAGE_DAYS - 1);
+    let usage = ApiUsageCount::for_user(&mut conn, auth.user_id(), since).await?;
+    let usage = usage
+        .into_iter()
+        .map(EncodableApiUsage::from)
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "usage": usage }))
+}
@@ -1,6 +1,10 @@
 pub mod authors;
 pub mod dependencies;
+pub mod dependency_graph;
+pub mod diff;
 pub mod downloads;
+pub mod features;
+pub mod files;
 pub mod metadata;
 pub mod readme;
 pub mod update;
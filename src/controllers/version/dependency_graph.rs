@@ -0,0 +1,64 @@
+use super::CrateVersionPath;
+use crate::app::AppState;
+use crate::dependency_graph;
+use crate::util::errors::{bad_request, AppResult};
+use axum::extract::Query;
+use axum_extra::response::ErasedJson;
+use std::sync::Arc;
+
+/// The default number of dependency levels that are resolved.
+const DEFAULT_DEPTH: u32 = 3;
+
+/// The maximum number of dependency levels that can be requested.
+const MAX_DEPTH: u32 = 10;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DependencyGraphQueryParams {
+    /// The number of dependency levels to resolve. Defaults to 3.
+    depth: Option<u32>,
+}
+
+/// Get the resolved dependency graph of a crate version.
+///
+/// The normal dependencies of the version are resolved transitively to the
+/// latest non-yanked versions that match their requirements, using the
+/// default features of the requested version. The graph is returned as a
+/// list of nodes and edges, where the first node is the requested version.
+///
+/// This is an approximation of the cargo resolver, which does not unify
+/// features or deduplicate semver-compatible versions across the graph.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/{version}/dependency_graph",
+    params(CrateVersionPath, DependencyGraphQueryParams),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_version_dependency_graph(
+    state: AppState,
+    path: CrateVersionPath,
+    Query(params): Query<DependencyGraphQueryParams>,
+) -> AppResult<ErasedJson> {
+    let depth = params.depth.unwrap_or(DEFAULT_DEPTH);
+    if depth == 0 || depth > MAX_DEPTH {
+        let detail = format!("`depth` must be between 1 and {MAX_DEPTH}");
+        return Err(bad_request(detail));
+    }
+
+    let mut conn = state.db_read().await?;
+    let version = path.load_version(&mut conn).await?;
+
+    let cache = &state.dependency_graph_cache;
+    let graph = match cache.get(version.id, depth) {
+        Some(graph) => graph,
+        None => {
+            let graph = dependency_graph::resolve(&mut conn, version.id, depth).await?;
+            let graph = Arc::new(graph);
+            cache.insert(version.id, depth, graph.clone());
+            graph
+        }
+    };
+
+    Ok(ErasedJson::new(&*graph))
+}
@@ -0,0 +1,54 @@
+use super::CrateVersionPath;
+use crate::app::AppState;
+use crate::models::VersionDiff;
+use crate::schema::{version_diffs, versions};
+use crate::util::errors::{not_found, AppResult};
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+/// Get the changes of a crate version compared to the previous release.
+///
+/// Returns the number of added, removed and modified files, the difference
+/// of the total unpacked size, and the list of changed files. The diff is
+/// computed in the background after publishing, so it might not be
+/// available yet for very recent versions. The first version of a crate has
+/// no diff.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/{version}/diff",
+    params(CrateVersionPath),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_version_diff(state: AppState, path: CrateVersionPath) -> AppResult<ErasedJson> {
+    let mut conn = state.db_read().await?;
+    let version = path.load_version(&mut conn).await?;
+
+    let diff = version_diffs::table
+        .find(version.id)
+        .select(VersionDiff::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    let previous_version: String = versions::table
+        .find(diff.previous_version_id)
+        .select(versions::num)
+        .first(&mut conn)
+        .await?;
+
+    Ok(json!({
+        "diff": {
+            "version": version.num,
+            "previous_version": previous_version,
+            "files_added": diff.files_added,
+            "files_removed": diff.files_removed,
+            "files_modified": diff.files_modified,
+            "size_delta": diff.size_delta,
+            "files": diff.files,
+        }
+    }))
+}
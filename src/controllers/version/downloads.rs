@@ -4,9 +4,10 @@
 
 use super::CrateVersionPath;
 use crate::app::AppState;
-use crate::models::VersionDownload;
+use crate::auth::AuthCheck;
+use crate::models::{Crate, Rights, VersionDownload};
 use crate::schema::*;
-use crate::util::errors::AppResult;
+use crate::util::errors::{crate_not_found, AppResult};
 use crate::util::{redirect, RequestUtils};
 use crate::views::EncodableVersionDownload;
 use axum::extract::{FromRequestParts, Query};
@@ -15,12 +16,15 @@ use axum_extra::json;
 use axum_extra::response::ErasedJson;
 use chrono::{Duration, NaiveDate, Utc};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::header;
 use http::request::Parts;
 
 /// Download a crate version.
 ///
-/// This returns a URL to the location where the crate is stored.
+/// This returns a URL to the location where the crate is stored. Versions of
+/// quarantined crates are not served by the CDN, and can only be downloaded
+/// through this endpoint by the owners of the crate.
 #[utoipa::path(
     get,
     path = "/api/v1/crates/{name}/{version}/download",
@@ -33,8 +37,37 @@ pub async fn download_version(
     path: CrateVersionPath,
     req: Parts,
 ) -> AppResult<Response> {
+    // Downloads have to keep working while the database is unavailable, so
+    // the quarantine state is only enforced if it can be looked up.
+    if let Ok(mut conn) = app.db_read().await {
+        let quarantined = crates::table
+            .filter(Crate::with_name(&path.name))
+            .select(crates::quarantined_at.is_not_null())
+            .get_result::<bool>(&mut conn)
+            .await
+            .optional();
+
+        match quarantined {
+            Ok(Some(true)) => {
+                ensure_owner(&app, &mut conn, &path.name, &req).await?;
+                drop(conn);
+
+                if let Some(response) = download_quarantined(&app, &path, &req).await? {
+                    return Ok(response);
+                }
+            }
+            Ok(_) => {}
+            Err(error) => warn!(
+                "Failed to look up quarantine state of `{}`: {error}",
+                path.name
+            ),
+        }
+    }
+
     let wants_json = req.wants_json();
-    let redirect_url = app.storage.crate_location(&path.name, &path.version);
+    let redirect_url = app
+        .download_storage()
+        .crate_location(&path.name, &path.version);
     if wants_json {
         Ok(json!({ "url": redirect_url }).into_response())
     } else {
@@ -42,6 +75,69 @@ pub async fn download_version(
     }
 }
 
+/// Serves the `.crate` file of a quarantined crate from the private store.
+///
+/// Returns `None` if the file has not been moved to the quarantine yet, or
+/// has already been restored, in which case the public file can be used.
+async fn download_quarantined(
+    app: &AppState,
+    path: &CrateVersionPath,
+    req: &Parts,
+) -> AppResult<Option<Response>> {
+    let result = app
+        .storage
+        .download_quarantined_crate_file(&path.name, &path.version)
+        .await;
+
+    let bytes = match result {
+        Ok(bytes) => bytes,
+        Err(object_store::Error::NotFound { .. }) => return Ok(None),
+        Err(error) => return Err(Box::new(error)),
+    };
+
+    // The frontend follows the returned URL, which points back to this
+    // endpoint since the file is not available anywhere else.
+    if req.wants_json() {
+        let url = format!("/api/v1/crates/{}/{}/download", path.name, path.version);
+        return Ok(Some(json!({ "url": url }).into_response()));
+    }
+
+    let filename = format!("{}-{}.crate", path.name, path.version);
+    let headers = [
+        (header::CONTENT_TYPE, "application/gzip".to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        ),
+        (header::CACHE_CONTROL, "private, no-store".to_string()),
+    ];
+
+    Ok(Some((headers, bytes).into_response()))
+}
+
+/// Returns a "not found" error, unless the request was made by an owner of
+/// the crate.
+async fn ensure_owner(
+    app: &AppState,
+    conn: &mut AsyncPgConnection,
+    name: &str,
+    req: &Parts,
+) -> AppResult<()> {
+    let krate: Crate = Crate::by_name(name).first(conn).await?;
+    let owners = krate.owners(conn).await?;
+
+    let rights = match AuthCheck::default().check(req, conn).await {
+        Ok(auth) => auth.user().rights(app, conn, &owners).await?,
+        Err(_) => Rights::None,
+    };
+
+    if rights < Rights::Publish {
+        return Err(crate_not_found(name));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
 #[from_request(via(Query))]
 #[into_params(parameter_in = Query)]
@@ -75,14 +171,13 @@ pub async fn get_version_downloads(
 
     let cutoff_start_date = cutoff_end_date - Duration::days(89);
 
-    let downloads = VersionDownload::belonging_to(&version)
-        .filter(version_downloads::date.between(cutoff_start_date, cutoff_end_date))
-        .order(version_downloads::date)
-        .load(&mut conn)
-        .await?
-        .into_iter()
-        .map(VersionDownload::into)
-        .collect::<Vec<EncodableVersionDownload>>();
+    let version_ids = [version.id];
+    let downloads =
+        VersionDownload::load_between(&mut conn, &version_ids, cutoff_start_date, cutoff_end_date)
+            .await?
+            .into_iter()
+            .map(VersionDownload::into)
+            .collect::<Vec<EncodableVersionDownload>>();
 
     Ok(json!({ "version_downloads": downloads }))
 }
@@ -0,0 +1,79 @@
+use super::CrateVersionPath;
+use crate::app::AppState;
+use crate::features::{self, FeatureValue};
+use crate::models::Dependency;
+use crate::util::errors::AppResult;
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use crates_io_database::schema::{crates, dependencies};
+use crates_io_index::features::FeaturesMap;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Get the features of a crate version.
+///
+/// Returns the feature map of the version in a normalized structure,
+/// including the implicit features of optional dependencies, and the
+/// features, optional dependencies and dependency features that are
+/// transitively enabled by the default feature set.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/{version}/features",
+    params(CrateVersionPath),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn get_version_features(
+    state: AppState,
+    path: CrateVersionPath,
+) -> AppResult<ErasedJson> {
+    let mut conn = state.db_read().await?;
+    let version = path.load_version(&mut conn).await?;
+
+    // Features refer to dependencies by their name in the manifest, which
+    // is the explicit name for renamed dependencies.
+    let optional_dependencies: Vec<String> = Dependency::belonging_to(&version)
+        .inner_join(crates::table)
+        .filter(dependencies::optional.eq(true))
+        .select((dependencies::explicit_name, crates::name))
+        .load::<(Option<String>, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(explicit_name, name)| explicit_name.unwrap_or(name))
+        .collect();
+
+    let optional_dependencies = optional_dependencies
+        .iter()
+        .map(String::as_str)
+        .collect::<BTreeSet<_>>();
+
+    let feature_map: FeaturesMap = serde_json::from_value(version.features)?;
+    let implicit_features = features::implicit_features(&feature_map, &optional_dependencies);
+
+    let mut normalized = feature_map
+        .iter()
+        .map(|(name, values)| {
+            let values = values
+                .iter()
+                .map(|value| FeatureValue::parse(value))
+                .collect::<Vec<_>>();
+
+            (name.as_str(), values)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    for name in &implicit_features {
+        let dependency = name.to_string();
+        normalized.insert(name, vec![FeatureValue::Dependency { dependency }]);
+    }
+
+    let default = features::resolve(&feature_map, &optional_dependencies, ["default"]);
+
+    Ok(json!({
+        "features": normalized,
+        "implicit_features": implicit_features,
+        "optional_dependencies": optional_dependencies,
+        "default": default,
+    }))
+}
@@ -0,0 +1,201 @@
+use super::CrateVersionPath;
+use crate::app::AppState;
+use crate::models::{Version, VersionFile, VersionFiles};
+use crate::schema::version_files;
+use crate::tarball_diff;
+use crate::util::errors::{bad_request, internal, not_found, AppResult};
+use axum::body::Bytes;
+use axum::extract::Path;
+use axum::response::{IntoResponse, Response};
+use axum_extra::json;
+use axum_extra::response::ErasedJson;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::HeaderValue;
+
+/// Files larger than this are not served by the preview endpoint.
+const MAX_PREVIEW_SIZE: u64 = 1024 * 1024;
+
+#[derive(Deserialize)]
+pub struct FilePath {
+    /// Path of the file, relative to the package root directory
+    path: String,
+}
+
+/// List the files of a crate version.
+///
+/// Returns the paths and sizes of all files in the `.crate` file, sorted by
+/// path, together with a hint for the syntax highlighting of each file.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/{version}/files",
+    params(CrateVersionPath),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn list_version_files(state: AppState, path: CrateVersionPath) -> AppResult<ErasedJson> {
+    let mut conn = state.db_read().await?;
+    let (version, krate) = path.load_version_and_crate(&mut conn).await?;
+    drop(conn);
+
+    let files = load_files(&state, &krate.name, &version).await?;
+    let files = files
+        .into_iter()
+        .map(|file| {
+            let syntax = syntax(&file.path);
+            serde_json::json!({ "path": file.path, "size": file.size, "syntax": syntax })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "files": files }))
+}
+
+/// Get the contents of a single file of a crate version.
+///
+/// Only text files up to 1 MiB can be previewed. The `x-syntax` response
+/// header contains a hint for the syntax highlighting of the file.
+pub async fn get_version_file(
+    state: AppState,
+    path: CrateVersionPath,
+    Path(file_path): Path<FilePath>,
+) -> AppResult<Response> {
+    let mut conn = state.db_read().await?;
+    let (version, krate) = path.load_version_and_crate(&mut conn).await?;
+    drop(conn);
+
+    let files = load_files(&state, &krate.name, &version).await?;
+    let file = files
+        .into_iter()
+        .find(|file| file.path == file_path.path)
+        .ok_or_else(not_found)?;
+
+    if file.size > MAX_PREVIEW_SIZE {
+        let detail = format!(
+            "file is too large to preview ({} bytes, the maximum is {MAX_PREVIEW_SIZE} bytes)",
+            file.size
+        );
+        return Err(bad_request(detail));
+    }
+
+    let tarball = download_crate_file(&state, &krate.name, &version.num).await?;
+
+    let contents = state
+        .blocking_pool
+        .run(move || tarball_diff::read_file(&tarball, &file.path))
+        .await?
+        .map_err(|e| internal(format!("failed to read tarball: {e}")))?
+        .ok_or_else(not_found)?;
+
+    let Ok(contents) = String::from_utf8(contents) else {
+        return Err(bad_request("file is not a text file"));
+    };
+
+    let mut response = contents.into_response();
+    if let Some(syntax) = syntax(&file_path.path) {
+        let headers = response.headers_mut();
+        headers.insert("x-syntax", HeaderValue::from_static(syntax));
+    }
+
+    Ok(response)
+}
+
+/// Loads the file listing of a version.
+///
+/// The listing is saved when a version is published. For versions that
+/// were published before that, it is read from the `.crate` file and saved
+/// on the first request.
+async fn load_files(
+    state: &AppState,
+    name: &str,
+    version: &Version,
+) -> AppResult<Vec<VersionFile>> {
+    let mut conn = state.db_read().await?;
+    let listing = version_files::table
+        .find(version.id)
+        .select(VersionFiles::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?;
+    drop(conn);
+
+    if let Some(listing) = listing {
+        return Ok(listing.files()?);
+    }
+
+    let tarball = download_crate_file(state, name, &version.num).await?;
+    let files = state
+        .blocking_pool
+        .run(move || tarball_diff::list_files(&tarball))
+        .await?
+        .map_err(|e| internal(format!("failed to list tarball files: {e}")))?;
+
+    let listing = VersionFiles::new(version.id, &files)?;
+    let mut conn = state.db_write().await?;
+    listing.insert(&mut conn).await?;
+
+    Ok(listing.files()?)
+}
+
+async fn download_crate_file(state: &AppState, name: &str, version: &str) -> AppResult<Bytes> {
+    state
+        .storage
+        .download_crate_file(name, version)
+        .await
+        .map_err(|error| match error {
+            object_store::Error::NotFound { .. } => not_found(),
+            error => Box::new(error),
+        })
+}
+
+/// Returns the name of the syntax highlighting that should be used for
+/// the file with the given path.
+fn syntax(path: &str) -> Option<&'static str> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    match file_name {
+        "Cargo.lock" | "Cargo.toml.orig" => return Some("toml"),
+        "Dockerfile" => return Some("dockerfile"),
+        "Makefile" => return Some("makefile"),
+        _ => {}
+    }
+
+    let (_, extension) = file_name.rsplit_once('.')?;
+    let syntax = match extension.to_ascii_lowercase().as_str() {
+        "rs" => "rust",
+        "toml" => "toml",
+        "md" | "markdown" => "markdown",
+        "json" => "json",
+        "yml" | "yaml" => "yaml",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+        "js" | "mjs" => "javascript",
+        "ts" => "typescript",
+        "py" => "python",
+        "sh" | "bash" => "shell",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "xml" => "xml",
+        "proto" => "protobuf",
+        "sql" => "sql",
+        "txt" => "text",
+        _ => return None,
+    };
+
+    Some(syntax)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax() {
+        assert_eq!(syntax("src/lib.rs"), Some("rust"));
+        assert_eq!(syntax("Cargo.toml"), Some("toml"));
+        assert_eq!(syntax("Cargo.toml.orig"), Some("toml"));
+        assert_eq!(syntax("Cargo.lock"), Some("toml"));
+        assert_eq!(syntax("README.MD"), Some("markdown"));
+        assert_eq!(syntax("vendor/zlib/inflate.h"), Some("c"));
+        assert_eq!(syntax("LICENSE"), None);
+        assert_eq!(syntax("assets/logo.png"), None);
+    }
+}
@@ -4,13 +4,17 @@
 //! index or cached metadata which was extracted (client side) from the
 //! `Cargo.toml` file.
 
+use axum::Json;
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
+use http::Uri;
 
 use crate::app::AppState;
+use crate::controllers::krate::redirect::check_redirect;
 use crate::models::VersionOwnerAction;
+use crate::surrogate_keys::SurrogateKeys;
 use crate::util::errors::AppResult;
-use crate::views::EncodableVersion;
+use crate::views::{v2, EncodableVersion};
 
 use super::CrateVersionPath;
 
@@ -22,14 +26,54 @@ use super::CrateVersionPath;
     tag = "versions",
     responses((status = 200, description = "Successful Response")),
 )]
-pub async fn find_version(state: AppState, path: CrateVersionPath) -> AppResult<ErasedJson> {
+pub async fn find_version(
+    state: AppState,
+    path: CrateVersionPath,
+    uri: Uri,
+) -> AppResult<(SurrogateKeys, ErasedJson)> {
+    let version = load_version(&state, &path, &uri).await?;
+    let json = json!({ "version": version });
+    Ok((SurrogateKeys::krate(&path.name), json))
+}
+
+/// Get crate version metadata.
+///
+/// Returns the same data as the `GET /api/v1/crates/{name}/{version}`
+/// endpoint, but with the version in the `data` field of the response.
+#[utoipa::path(
+    get,
+    path = "/api/v2/crates/{name}/{version}",
+    params(CrateVersionPath),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response")),
+)]
+pub async fn find_version_v2(
+    state: AppState,
+    path: CrateVersionPath,
+    uri: Uri,
+) -> AppResult<(SurrogateKeys, Json<v2::Document<v2::Version>>)> {
+    let version = load_version(&state, &path, &uri).await?;
+    let document = v2::Document::new(version.into());
+    Ok((SurrogateKeys::krate(&path.name), Json(document)))
+}
+
+async fn load_version(
+    state: &AppState,
+    path: &CrateVersionPath,
+    uri: &Uri,
+) -> AppResult<EncodableVersion> {
     let mut conn = state.db_read().await?;
+    check_redirect(&mut conn, &path.name, uri).await?;
     let (version, krate) = path.load_version_and_crate(&mut conn).await?;
     let (actions, published_by) = tokio::try_join!(
         VersionOwnerAction::by_version(&mut conn, &version),
         version.published_by(&mut conn),
     )?;
 
-    let version = EncodableVersion::from(version, &krate.name, published_by, actions);
-    Ok(json!({ "version": version }))
+    Ok(EncodableVersion::from(
+        version,
+        &krate.name,
+        published_by,
+        actions,
+    ))
 }
@@ -1,11 +1,18 @@
 use crate::app::AppState;
 use crate::controllers::version::CrateVersionPath;
+use crate::schema::{crates, readme_renderings, versions};
+use crate::util::errors::AppResult;
 use crate::util::{redirect, RequestUtils};
 use axum::response::{IntoResponse, Response};
 use axum_extra::json;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
 use http::request::Parts;
 
 /// Get the readme of a crate version.
+///
+/// Every version keeps the readme that was rendered for it. Identical
+/// readmes of different versions of a crate are stored only once.
 #[utoipa::path(
     get,
     path = "/api/v1/crates/{name}/{version}/readme",
@@ -13,11 +20,34 @@ use http::request::Parts;
     tag = "versions",
     responses((status = 200, description = "Successful Response")),
 )]
-pub async fn get_version_readme(app: AppState, path: CrateVersionPath, req: Parts) -> Response {
-    let redirect_url = app.storage.readme_location(&path.name, &path.version);
+pub async fn get_version_readme(
+    app: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_read().await?;
+    let checksum: Option<String> = readme_renderings::table
+        .inner_join(versions::table.inner_join(crates::table))
+        .filter(crates::name.eq(&path.name))
+        .filter(versions::num.eq(&path.version))
+        .select(readme_renderings::checksum)
+        .first::<Option<String>>(&mut conn)
+        .await
+        .optional()?
+        .flatten();
+
+    // Readmes that were rendered before they were stored by their checksum
+    // are still available at their per-version location.
+    let redirect_url = match checksum {
+        Some(checksum) => app
+            .storage
+            .readme_location_by_checksum(&path.name, &checksum),
+        None => app.storage.readme_location(&path.name, &path.version),
+    };
+
     if req.wants_json() {
-        json!({ "url": redirect_url }).into_response()
+        Ok(json!({ "url": redirect_url }).into_response())
     } else {
-        redirect(redirect_url)
+        Ok(redirect(redirect_url))
     }
 }
@@ -3,7 +3,8 @@ use crate::app::AppState;
 use crate::auth::{AuthCheck, Authentication};
 use crate::models::token::EndpointScope;
 use crate::models::{
-    Crate, NewVersionOwnerAction, Rights, Version, VersionAction, VersionOwnerAction,
+    AuditLogAction, Crate, NewAuditLogEntry, NewVersionOwnerAction, Rights, Version, VersionAction,
+    VersionOwnerAction,
 };
 use crate::rate_limiter::LimitedAction;
 use crate::schema::versions;
@@ -122,7 +123,7 @@ pub async fn perform_version_yank_update(
 
     let yanked = yanked.unwrap_or(version.yanked);
 
-    if user.rights(state, &owners).await? < Rights::Publish {
+    if user.rights(state, conn, &owners).await? < Rights::Publish {
         if user.is_admin {
             let action = if yanked { "yanking" } else { "unyanking" };
             warn!(
@@ -175,6 +176,21 @@ pub async fn perform_version_yank_update(
         .insert(conn)
         .await?;
 
+    let action = if yanked {
+        AuditLogAction::Yank
+    } else {
+        AuditLogAction::Unyank
+    };
+    NewAuditLogEntry::builder()
+        .user_id(user.id)
+        .maybe_api_token_id(api_token_id)
+        .crate_id(krate.id)
+        .action(action)
+        .details(serde_json::json!({ "version": version.num, "message": version.yank_message }))
+        .build()
+        .insert(conn)
+        .await?;
+
     let git_index_job = SyncToGitIndex::new(&krate.name);
     let sparse_index_job = SyncToSparseIndex::new(&krate.name);
     let update_default_version_job = UpdateDefaultVersion::new(krate.id);
@@ -1,12 +1,16 @@
 use crate::certs::CRUNCHY;
+use diesel::connection::{Instrumentation, InstrumentationEvent};
 use diesel::{ConnectionResult, QueryResult};
 use diesel_async::pooled_connection::deadpool::{Hook, HookError};
 use diesel_async::pooled_connection::ManagerConfig;
-use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use native_tls::{Certificate, TlsConnector};
 use postgres_native_tls::MakeTlsConnector;
 use secrecy::ExposeSecret;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
 use crate::config;
@@ -87,10 +91,12 @@ async fn establish_async_connection(
     AsyncPgConnection::try_from_client_and_connection(client, conn).await
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct ConnectionConfig {
     pub statement_timeout: Duration,
     pub read_only: bool,
+    /// The statistics that the queries of the connection are recorded in.
+    pub query_stats: QueryStats,
 }
 
 impl ConnectionConfig {
@@ -99,10 +105,7 @@ impl ConnectionConfig {
             .execute(conn)
             .await?;
 
-        let statement_timeout = self.statement_timeout.as_millis();
-        diesel::sql_query(format!("SET statement_timeout = {statement_timeout}"))
-            .execute(conn)
-            .await?;
+        set_statement_timeout(conn, self.statement_timeout).await?;
 
         if self.read_only {
             diesel::sql_query("SET default_transaction_read_only = 't'")
@@ -114,13 +117,153 @@ impl ConnectionConfig {
     }
 }
 
+/// Sets the `statement_timeout` of the connection, after which the database
+/// cancels running queries.
+///
+/// The timeout is rounded up to whole milliseconds, since a timeout of zero
+/// would disable it.
+pub async fn set_statement_timeout(
+    conn: &mut AsyncPgConnection,
+    timeout: Duration,
+) -> QueryResult<()> {
+    let statement_timeout = statement_timeout_millis(timeout);
+    diesel::sql_query(format!("SET statement_timeout = {statement_timeout}"))
+        .execute(conn)
+        .await?;
+
+    if let Some(state) = connection_state(conn) {
+        state.statement_timeout = statement_timeout;
+    }
+
+    Ok(())
+}
+
+/// Changes the `statement_timeout` of a pooled connection, unless it is
+/// already set to the given timeout.
+///
+/// Connections remember their current timeout, so that a timeout that was
+/// shortened for a previous request is only reset by the next request that
+/// needs a different one, instead of on every checkout.
+pub async fn update_statement_timeout(
+    conn: &mut AsyncPgConnection,
+    timeout: Duration,
+) -> QueryResult<()> {
+    let statement_timeout = statement_timeout_millis(timeout);
+    let current = connection_state(conn).map(|state| state.statement_timeout);
+    if current == Some(statement_timeout) {
+        return Ok(());
+    }
+
+    set_statement_timeout(conn, timeout).await
+}
+
+fn statement_timeout_millis(timeout: Duration) -> u128 {
+    timeout.as_millis().max(1)
+}
+
+fn connection_state(conn: &mut AsyncPgConnection) -> Option<&mut ConnectionState> {
+    conn.instrumentation().downcast_mut::<ConnectionState>()
+}
+
 impl From<ConnectionConfig> for Hook<AsyncPgConnection> {
     fn from(config: ConnectionConfig) -> Self {
         Hook::async_fn(move |conn, _| {
+            let config = config.clone();
             Box::pin(async move {
                 let result = config.apply(conn).await;
-                result.map_err(|err| HookError::message(err.to_string()))
+                result.map_err(|err| HookError::message(err.to_string()))?;
+
+                // The queries of the setup above are not recorded, since
+                // they only run once per connection.
+                let query_stats = QueryStatsInstrumentation::new(config.query_stats);
+                let statement_timeout = statement_timeout_millis(config.statement_timeout);
+                conn.set_instrumentation(ConnectionState {
+                    query_stats,
+                    statement_timeout,
+                });
+                Ok(())
             })
         })
     }
 }
+
+/// The number and total duration of the SQL queries that were executed on
+/// the connections of the application.
+///
+/// The statistics are shared by all connections of the database pools, so
+/// the difference between two readings covers all queries in between. The
+/// test suite uses this to detect N+1 query regressions.
+#[derive(Debug, Clone, Default)]
+pub struct QueryStats(Arc<QueryStatsInner>);
+
+#[derive(Debug, Default)]
+struct QueryStatsInner {
+    count: AtomicU64,
+    duration_micros: AtomicU64,
+}
+
+impl QueryStats {
+    /// The number of queries that have been executed.
+    pub fn count(&self) -> u64 {
+        self.0.count.load(Ordering::Relaxed)
+    }
+
+    /// The total time that the queries took, including the time to send
+    /// them to the database and to receive the results.
+    pub fn duration(&self) -> Duration {
+        Duration::from_micros(self.0.duration_micros.load(Ordering::Relaxed))
+    }
+
+    fn record(&self, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        self.0.count.fetch_add(1, Ordering::Relaxed);
+        self.0.duration_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+}
+
+/// The state of a pooled connection.
+///
+/// Diesel only allows attaching data to a connection as its instrumentation,
+/// so the state also forwards the events of the connection to
+/// [QueryStatsInstrumentation].
+struct ConnectionState {
+    query_stats: QueryStatsInstrumentation,
+    /// The `statement_timeout` that is currently set on the connection, in
+    /// milliseconds.
+    statement_timeout: u128,
+}
+
+impl Instrumentation for ConnectionState {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        self.query_stats.on_connection_event(event);
+    }
+}
+
+/// Records the queries of a connection in [QueryStats].
+struct QueryStatsInstrumentation {
+    stats: QueryStats,
+    /// The start times of the queries that have not finished yet. Queries
+    /// can be pipelined on the same connection, and finish in the order in
+    /// which they were started.
+    started: VecDeque<Instant>,
+}
+
+impl QueryStatsInstrumentation {
+    fn new(stats: QueryStats) -> Self {
+        let started = VecDeque::new();
+        Self { stats, started }
+    }
+}
+
+impl Instrumentation for QueryStatsInstrumentation {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => self.started.push_back(Instant::now()),
+            InstrumentationEvent::FinishQuery { .. } => {
+                let duration = self.started.pop_front().map(|started| started.elapsed());
+                self.stats.record(duration.unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+}
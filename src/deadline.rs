@@ -0,0 +1,90 @@
+//! Request deadlines.
+//!
+//! The `limits` middleware gives every request a [`Deadline`], after which
+//! the response is replaced by a `408 Request Timeout` error and the handler
+//! future is dropped. Dropping the future doesn't stop closures that are
+//! already running on a blocking thread, or queries that are already running
+//! in the database, so the deadline is also made available to them:
+//!
+//! - as a request extension, for handlers that want to check it explicitly,
+//! - as a task-local value, which [`crate::tasks::spawn_blocking`] carries
+//!   over to the blocking thread and which limits the `statement_timeout` of
+//!   the database connections that are obtained from [`crate::App`].
+
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static CURRENT: Deadline;
+}
+
+/// The point in time after which nobody is waiting for the result of a
+/// request anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    pub fn after(timeout: Duration) -> Self {
+        Self(Instant::now() + timeout)
+    }
+
+    /// Returns the deadline of the current request, if there is one.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|deadline| *deadline).ok()
+    }
+
+    /// Runs the future with this deadline as the deadline of the current
+    /// request.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+
+    /// Runs the closure with this deadline as the deadline of the current
+    /// request. This is used to carry the deadline over to blocking threads.
+    pub fn sync_scope<F: FnOnce() -> R, R>(self, f: F) -> R {
+        CURRENT.sync_scope(self, f)
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Returns the time that is left until the deadline, which is zero if
+    /// the deadline has already passed.
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_current_deadline() {
+        assert_eq!(Deadline::current(), None);
+
+        let deadline = Deadline::after(Duration::from_secs(30));
+        let current = deadline.scope(async { Deadline::current() }).await;
+        assert_eq!(current, Some(deadline));
+
+        let current = deadline.sync_scope(Deadline::current);
+        assert_eq!(current, Some(deadline));
+    }
+
+    #[test]
+    fn test_remaining() {
+        let deadline = Deadline::after(Duration::from_secs(30));
+        assert!(!deadline.is_expired());
+        assert!(deadline.remaining() <= Duration::from_secs(30));
+
+        let deadline = Deadline::after(Duration::ZERO);
+        assert_eq!(deadline.remaining(), Duration::ZERO);
+        assert!(deadline.is_expired());
+    }
+}
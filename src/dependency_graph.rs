@@ -0,0 +1,395 @@
+//! Server-side resolution of the dependency graph of a published version.
+//!
+//! The resolution is a simplified version of what cargo does: every
+//! dependency requirement is resolved to the latest non-yanked version that
+//! matches it, using the default features of the root version. Features are
+//! not unified across the graph, so each crate version is resolved with the
+//! features that were requested the first time it was encountered.
+
+use crate::features;
+use crate::models::{DependencyKind, Version};
+use crate::schema::{crates, dependencies, versions};
+use crates_io_index::features::FeaturesMap;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use parking_lot::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The maximum number of nodes in a graph. Resolution stops once this limit
+/// is reached and the graph is marked as truncated.
+const MAX_NODES: usize = 1000;
+
+#[derive(Debug, Serialize)]
+pub struct DependencyGraph {
+    /// The resolved crate versions. The first node is the root version.
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    /// Dependencies that could not be resolved to any published version.
+    pub unresolved: Vec<Unresolved>,
+    /// Whether the graph has been cut off because it got too large.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Node {
+    pub id: usize,
+    pub name: String,
+    pub version: String,
+    /// The features that are enabled for this version.
+    pub features: BTreeSet<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    /// The version requirement of the dependency.
+    pub req: String,
+    pub optional: bool,
+    /// The target platform of the dependency, if it is platform-specific.
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Unresolved {
+    pub from: usize,
+    pub name: String,
+    pub req: String,
+}
+
+/// A normal dependency of one of the versions in the graph.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = dependencies, check_for_backend(diesel::pg::Pg))]
+struct DependencyRow {
+    version_id: i32,
+    crate_id: i32,
+    #[diesel(select_expression = crates::name)]
+    crate_name: String,
+    req: String,
+    optional: bool,
+    default_features: bool,
+    features: Vec<String>,
+    target: Option<String>,
+    explicit_name: Option<String>,
+}
+
+impl DependencyRow {
+    /// The name of the dependency in the manifest, which is used to refer to
+    /// it in the feature map.
+    fn name(&self) -> &str {
+        self.explicit_name.as_deref().unwrap_or(&self.crate_name)
+    }
+}
+
+/// A node that still needs its dependencies resolved.
+struct Pending {
+    node: usize,
+    version_id: i32,
+    requested_features: BTreeSet<String>,
+}
+
+/// Resolves the normal dependencies of the given version, up to `depth`
+/// levels deep.
+pub async fn resolve(
+    conn: &mut AsyncPgConnection,
+    version_id: i32,
+    depth: u32,
+) -> QueryResult<DependencyGraph> {
+    let (name, num): (String, String) = versions::table
+        .inner_join(crates::table)
+        .filter(versions::id.eq(version_id))
+        .select((crates::name, versions::num))
+        .first(conn)
+        .await?;
+
+    let mut graph = DependencyGraph {
+        nodes: vec![Node {
+            id: 0,
+            name,
+            version: num,
+            features: BTreeSet::new(),
+        }],
+        edges: vec![],
+        unresolved: vec![],
+        truncated: false,
+    };
+
+    let mut pending = vec![Pending {
+        node: 0,
+        version_id,
+        requested_features: BTreeSet::from(["default".to_string()]),
+    }];
+
+    // Maps version ids to node ids, so that every version only appears once.
+    let mut node_ids = HashMap::from([(version_id, 0)]);
+
+    for _ in 0..depth {
+        if pending.is_empty() {
+            break;
+        }
+
+        let version_ids = pending.iter().map(|p| p.version_id).collect::<Vec<_>>();
+
+        let features: HashMap<i32, serde_json::Value> = versions::table
+            .filter(versions::id.eq_any(&version_ids))
+            .select((versions::id, versions::features))
+            .load(conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        let deps: Vec<DependencyRow> = dependencies::table
+            .inner_join(crates::table)
+            .filter(dependencies::version_id.eq_any(&version_ids))
+            .filter(dependencies::kind.eq(DependencyKind::Normal))
+            .select(DependencyRow::as_select())
+            .order((dependencies::version_id, crates::name))
+            .load(conn)
+            .await?;
+
+        let crate_ids = deps.iter().map(|dep| dep.crate_id).collect::<BTreeSet<_>>();
+        let candidates = load_candidates(conn, &crate_ids).await?;
+
+        let mut next = Vec::new();
+        for p in pending {
+            let version_deps = deps.iter().filter(|dep| dep.version_id == p.version_id);
+
+            let feature_map = features
+                .get(&p.version_id)
+                .and_then(|f| serde_json::from_value::<FeaturesMap>(f.clone()).ok())
+                .unwrap_or_default();
+
+            let optional = version_deps
+                .clone()
+                .filter(|dep| dep.optional)
+                .map(DependencyRow::name)
+                .collect();
+
+            let requested = p.requested_features.iter().map(String::as_str);
+            let resolved = features::resolve(&feature_map, &optional, requested);
+
+            for dep in version_deps {
+                if dep.optional && !resolved.dependencies.contains(dep.name()) {
+                    continue;
+                }
+
+                let version = candidates
+                    .get(&dep.crate_id)
+                    .and_then(|versions| select_version(versions, &dep.req));
+
+                let Some((dep_version_id, dep_num)) = version else {
+                    graph.unresolved.push(Unresolved {
+                        from: p.node,
+                        name: dep.crate_name.clone(),
+                        req: dep.req.clone(),
+                    });
+                    continue;
+                };
+
+                let to = match node_ids.get(dep_version_id) {
+                    Some(id) => *id,
+                    None => {
+                        if graph.nodes.len() >= MAX_NODES {
+                            graph.truncated = true;
+                            continue;
+                        }
+
+                        let id = graph.nodes.len();
+                        graph.nodes.push(Node {
+                            id,
+                            name: dep.crate_name.clone(),
+                            version: dep_num.to_string(),
+                            features: BTreeSet::new(),
+                        });
+                        node_ids.insert(*dep_version_id, id);
+
+                        let mut requested_features =
+                            dep.features.iter().cloned().collect::<BTreeSet<_>>();
+                        if dep.default_features {
+                            requested_features.insert("default".into());
+                        }
+                        if let Some(features) = resolved.dependency_features.get(dep.name()) {
+                            requested_features.extend(features.iter().cloned());
+                        }
+
+                        next.push(Pending {
+                            node: id,
+                            version_id: *dep_version_id,
+                            requested_features,
+                        });
+
+                        id
+                    }
+                };
+
+                graph.edges.push(Edge {
+                    from: p.node,
+                    to,
+                    req: dep.req.clone(),
+                    optional: dep.optional,
+                    target: dep.target.clone(),
+                });
+            }
+
+            graph.nodes[p.node].features = resolved.features;
+        }
+
+        pending = next;
+    }
+
+    Ok(graph)
+}
+
+/// Loads all non-yanked and non-deleted versions of the given crates, grouped
+/// by crate id.
+async fn load_candidates(
+    conn: &mut AsyncPgConnection,
+    crate_ids: &BTreeSet<i32>,
+) -> QueryResult<HashMap<i32, Vec<(i32, semver::Version)>>> {
+    let versions: Vec<(i32, i32, String)> = versions::table
+        .filter(versions::crate_id.eq_any(crate_ids))
+        .filter(versions::yanked.eq(false))
+        .filter(Version::not_deleted())
+        .select((versions::id, versions::crate_id, versions::num))
+        .load(conn)
+        .await?;
+
+    let mut candidates: HashMap<i32, Vec<(i32, semver::Version)>> = HashMap::new();
+    for (id, crate_id, num) in versions {
+        if let Ok(num) = semver::Version::parse(&num) {
+            candidates.entry(crate_id).or_default().push((id, num));
+        }
+    }
+
+    Ok(candidates)
+}
+
+/// Selects the highest version that matches the requirement.
+fn select_version<'a>(
+    versions: &'a [(i32, semver::Version)],
+    req: &str,
+) -> Option<(&'a i32, &'a semver::Version)> {
+    let req = semver::VersionReq::parse(req).ok()?;
+    versions
+        .iter()
+        .filter(|(_, num)| req.matches(num))
+        .max_by(|(_, a), (_, b)| a.cmp(b))
+        .map(|(id, num)| (id, num))
+}
+
+/// The version id and depth of a cached graph.
+type CacheKey = (i32, u32);
+
+/// An in-memory cache of resolved dependency graphs.
+///
+/// The graphs change whenever a new version of any crate in the graph is
+/// published, so the entries are only kept for a short amount of time.
+pub struct DependencyGraphCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<CacheKey, (Instant, Arc<DependencyGraph>)>>,
+}
+
+impl Default for DependencyGraphCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10 * 60), 1000)
+    }
+}
+
+impl DependencyGraphCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, version_id: i32, depth: u32) -> Option<Arc<DependencyGraph>> {
+        let entries = self.entries.lock();
+        let (inserted_at, graph) = entries.get(&(version_id, depth))?;
+        (inserted_at.elapsed() < self.ttl).then(|| graph.clone())
+    }
+
+    pub fn insert(&self, version_id: i32, depth: u32, graph: Arc<DependencyGraph>) {
+        let mut entries = self.entries.lock();
+        if entries.len() >= self.max_entries {
+            entries.retain(|_, (inserted_at, _)| inserted_at.elapsed() < self.ttl);
+        }
+        if entries.len() >= self.max_entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (inserted_at, _))| *inserted_at)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert((version_id, depth), (Instant::now(), graph));
+    }
+
+    /// Removes all entries, e.g. after a version has been deleted, since it
+    /// might be part of any of the cached graphs.
+    ///
+    /// This only affects the cache of the current instance. The caches of
+    /// other instances expire after their TTL.
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_version() {
+        let versions = ["1.0.0", "1.2.0", "1.10.1", "2.0.0", "2.1.0-beta.1"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, num)| (i as i32, semver::Version::parse(num).unwrap()))
+            .collect::<Vec<_>>();
+
+        let select = |req| select_version(&versions, req).map(|(_, num)| num.to_string());
+        assert_eq!(select("^1.0").as_deref(), Some("1.10.1"));
+        assert_eq!(select("=1.2.0").as_deref(), Some("1.2.0"));
+        assert_eq!(select("^2").as_deref(), Some("2.0.0"));
+        assert_eq!(select("^2.1.0-beta").as_deref(), Some("2.1.0-beta.1"));
+        assert_eq!(select("^3"), None);
+        assert_eq!(select("invalid"), None);
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache = DependencyGraphCache::new(Duration::from_secs(60), 2);
+        let graph = || {
+            Arc::new(DependencyGraph {
+                nodes: vec![],
+                edges: vec![],
+                unresolved: vec![],
+                truncated: false,
+            })
+        };
+
+        cache.insert(1, 1, graph());
+        cache.insert(2, 1, graph());
+        assert!(cache.get(1, 1).is_some());
+        assert!(cache.get(1, 2).is_none());
+
+        // The oldest entry is evicted once the cache is full
+        cache.insert(3, 1, graph());
+        assert!(cache.get(1, 1).is_none());
+        assert!(cache.get(2, 1).is_some());
+        assert!(cache.get(3, 1).is_some());
+
+        cache.clear();
+        assert!(cache.get(3, 1).is_none());
+
+        let cache = DependencyGraphCache::new(Duration::ZERO, 2);
+        cache.insert(1, 1, graph());
+        assert!(cache.get(1, 1).is_none());
+    }
+}
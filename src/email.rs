@@ -1,5 +1,11 @@
-use crate::config;
+use crate::config::{self, EmailConfig};
+use crate::schema::sent_emails;
 use crate::Env;
+use async_trait::async_trait;
+use crates_io_outbound::OutboundClient;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use hex::ToHex;
 use lettre::address::Envelope;
 use lettre::message::header::ContentType;
 use lettre::message::Mailbox;
@@ -9,16 +15,39 @@ use lettre::transport::smtp::AsyncSmtpTransport;
 use lettre::transport::stub::AsyncStubTransport;
 use lettre::{Address, AsyncTransport, Message, Tokio1Executor};
 use rand::distributions::{Alphanumeric, DistString};
+use secrecy::ExposeSecret;
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
 use std::sync::Arc;
 
+pub mod i18n;
+mod mailgun;
+mod ses;
+#[cfg(test)]
+mod test_server;
+
+pub use self::mailgun::MailgunBackend;
+pub use self::ses::SesBackend;
+
 pub trait Email {
     fn subject(&self) -> String;
     fn body(&self) -> String;
 }
 
+/// A service that delivers the emails to their recipients.
+#[async_trait]
+pub trait EmailBackend: Debug + Send + Sync {
+    /// Sends the message, and returns the ID that the provider assigned to
+    /// it, if the provider reports one.
+    async fn send(&self, message: Message) -> anyhow::Result<Option<String>>;
+}
+
 #[derive(Debug, Clone)]
 pub struct Emails {
-    backend: EmailBackend,
+    backend: Arc<dyn EmailBackend>,
+    /// The backend used during tests, which keeps the messages in memory to
+    /// allow tests to retrieve them.
+    memory: Option<AsyncStubTransport>,
     pub domain: String,
     from: Address,
 }
@@ -26,39 +55,48 @@ pub struct Emails {
 const DEFAULT_FROM: &str = "noreply@crates.io";
 
 impl Emails {
-    /// Create a new instance detecting the backend from the environment. This will either connect
-    /// to a SMTP server or store the emails on the local filesystem.
-    pub fn from_environment(config: &config::Server) -> Self {
-        let login = dotenvy::var("MAILGUN_SMTP_LOGIN");
-        let password = dotenvy::var("MAILGUN_SMTP_PASSWORD");
-        let server = dotenvy::var("MAILGUN_SMTP_SERVER");
-
-        let from = login.as_deref().unwrap_or(DEFAULT_FROM).parse().unwrap();
-
-        let backend = match (login, password, server) {
-            (Ok(login), Ok(password), Ok(server)) => {
-                let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(&server)
+    /// Create a new instance with the backend from the configuration, or a backend that stores the
+    /// emails on the local filesystem if no backend is configured.
+    ///
+    /// The HTTP API backends send their requests through the `outbound` client.
+    pub fn from_config(config: &config::Server, outbound: OutboundClient) -> Self {
+        let (backend, from): (Arc<dyn EmailBackend>, _) = match &config.email {
+            Some(EmailConfig::Smtp(smtp)) => {
+                let credentials = Credentials::new(
+                    smtp.login.to_string(),
+                    smtp.password.expose_secret().to_string(),
+                );
+
+                let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.server)
                     .unwrap()
-                    .credentials(Credentials::new(login, password))
+                    .credentials(credentials)
                     .authentication(vec![Mechanism::Plain])
                     .build();
 
-                EmailBackend::Smtp(Box::new(transport))
+                (Arc::new(transport), smtp.login.clone())
+            }
+            Some(EmailConfig::Mailgun(mailgun)) => {
+                let backend = MailgunBackend::new(mailgun, outbound);
+                (Arc::new(backend), mailgun.from.clone())
+            }
+            Some(EmailConfig::Ses(ses)) => {
+                (Arc::new(SesBackend::new(ses, outbound)), ses.from.clone())
             }
-            _ => {
-                let transport = AsyncFileTransport::new("/tmp");
-                EmailBackend::FileSystem(Arc::new(transport))
+            None => {
+                let transport = AsyncFileTransport::<Tokio1Executor>::new("/tmp");
+                (Arc::new(transport), DEFAULT_FROM.parse().unwrap())
             }
         };
 
-        if config.base.env == Env::Production && !matches!(backend, EmailBackend::Smtp { .. }) {
-            panic!("only the smtp backend is allowed in production");
+        if config.base.env == Env::Production && config.email.is_none() {
+            panic!("the file system backend is not allowed in production");
         }
 
         let domain = config.domain_name.clone();
 
         Self {
             backend,
+            memory: None,
             domain,
             from,
         }
@@ -67,20 +105,33 @@ impl Emails {
     /// Create a new test backend that stores all the outgoing emails in memory, allowing for tests
     /// to later assert the mails were sent.
     pub fn new_in_memory() -> Self {
+        let transport = AsyncStubTransport::new_ok();
+
         Self {
-            backend: EmailBackend::Memory(AsyncStubTransport::new_ok()),
+            backend: Arc::new(transport.clone()),
+            memory: Some(transport),
             domain: "crates.io".into(),
             from: DEFAULT_FROM.parse().unwrap(),
         }
     }
 
+    /// Create a new test backend like [`Emails::new_in_memory()`], whose emails fail to send
+    /// while a [`Fault::SendEmail`](crate::tests::util::faults::Fault::SendEmail) is injected.
+    #[cfg(test)]
+    pub fn new_in_memory_with_faults(faults: crate::tests::util::faults::FaultInjector) -> Self {
+        use crate::tests::util::faults::FaultyEmailBackend;
+
+        let mut emails = Self::new_in_memory();
+        emails.backend = Arc::new(FaultyEmailBackend::new(emails.backend, faults));
+        emails
+    }
+
     /// This is supposed to be used only during tests, to retrieve the messages stored in the
     /// "memory" backend. It's not cfg'd away because our integration tests need to access this.
     pub async fn mails_in_memory(&self) -> Option<Vec<(Envelope, String)>> {
-        if let EmailBackend::Memory(transport) = &self.backend {
-            Some(transport.messages().await)
-        } else {
-            None
+        match &self.memory {
+            Some(transport) => Some(transport.messages().await),
+            None => None,
         }
     }
 
@@ -89,7 +140,7 @@ impl Emails {
         recipient: &str,
         subject: String,
         body: String,
-    ) -> Result<Message, EmailError> {
+    ) -> Result<(Message, String), EmailError> {
         // The message ID is normally generated by the SMTP server, but if we let it generate the
         // ID there will be no way for the crates.io application to know the ID of the message it
         // just sent, as it's not included in the SMTP response.
@@ -113,16 +164,78 @@ impl Emails {
             .header(ContentType::TEXT_PLAIN)
             .body(body)?;
 
-        Ok(message)
+        Ok((message, message_id))
     }
 
     pub async fn send<E: Email>(&self, recipient: &str, email: E) -> Result<(), EmailError> {
-        let email = self.build_message(recipient, email.subject(), email.body())?;
+        self.send_with_message_id(recipient, email).await?;
+        Ok(())
+    }
+
+    /// Sends the email, and returns the ID that the provider assigned to the message, or the
+    /// generated `Message-ID` header if the provider doesn't report one.
+    pub async fn send_with_message_id<E: Email>(
+        &self,
+        recipient: &str,
+        email: E,
+    ) -> Result<String, EmailError> {
+        let (message, message_id) = self.build_message(recipient, email.subject(), email.body())?;
 
-        self.backend
-            .send(email)
+        let provider_id = self
+            .backend
+            .send(message)
             .await
-            .map_err(EmailError::TransportError)
+            .map_err(EmailError::TransportError)?;
+
+        let message_id = provider_id.unwrap_or(message_id);
+        debug!(%message_id, "Email sent");
+
+        Ok(message_id)
+    }
+
+    /// Sends the email, unless an email with the same idempotency key has been sent before.
+    ///
+    /// The key should be derived from the event that triggered the email, e.g. the invitation
+    /// token and the recipient, so that retried background jobs and double-submitted requests
+    /// can't send the same notification twice. If sending fails, the key is released again so
+    /// that the next attempt can send the email.
+    pub async fn send_idempotent<E: Email>(
+        &self,
+        conn: &mut AsyncPgConnection,
+        idempotency_key: &str,
+        recipient: &str,
+        email: E,
+    ) -> Result<(), EmailError> {
+        let key: String = Sha256::digest(idempotency_key).encode_hex();
+
+        let inserted = diesel::insert_into(sent_emails::table)
+            .values(sent_emails::idempotency_key.eq(&key))
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?;
+
+        if inserted == 0 {
+            debug!(idempotency_key = %key, "Skipping email that has already been sent");
+            return Ok(());
+        }
+
+        match self.send_with_message_id(recipient, email).await {
+            Ok(message_id) => {
+                diesel::update(sent_emails::table.find(&key))
+                    .set(sent_emails::message_id.eq(message_id))
+                    .execute(conn)
+                    .await?;
+
+                Ok(())
+            }
+            Err(error) => {
+                diesel::delete(sent_emails::table.find(&key))
+                    .execute(conn)
+                    .await?;
+
+                Err(error)
+            }
+        }
     }
 }
 
@@ -134,29 +247,34 @@ pub enum EmailError {
     MessageBuilderError(#[from] lettre::error::Error),
     #[error(transparent)]
     TransportError(anyhow::Error),
+    #[error(transparent)]
+    DatabaseError(#[from] diesel::result::Error),
 }
 
-#[derive(Debug, Clone)]
-enum EmailBackend {
-    /// Backend used in production to send mails using SMTP.
-    ///
-    /// This is using `Box` to avoid a large size difference between variants.
-    Smtp(Box<AsyncSmtpTransport<Tokio1Executor>>),
-    /// Backend used locally during development, will store the emails in the provided directory.
-    FileSystem(Arc<AsyncFileTransport<Tokio1Executor>>),
-    /// Backend used during tests, will keep messages in memory to allow tests to retrieve them.
-    Memory(AsyncStubTransport),
+/// Backend used in production to send mails using SMTP.
+#[async_trait]
+impl EmailBackend for AsyncSmtpTransport<Tokio1Executor> {
+    async fn send(&self, message: Message) -> anyhow::Result<Option<String>> {
+        AsyncTransport::send(self, message).await?;
+        Ok(None)
+    }
 }
 
-impl EmailBackend {
-    async fn send(&self, message: Message) -> anyhow::Result<()> {
-        match self {
-            EmailBackend::Smtp(transport) => transport.send(message).await.map(|_| ())?,
-            EmailBackend::FileSystem(transport) => transport.send(message).await.map(|_| ())?,
-            EmailBackend::Memory(transport) => transport.send(message).await.map(|_| ())?,
-        }
+/// Backend used locally during development, will store the emails in the provided directory.
+#[async_trait]
+impl EmailBackend for AsyncFileTransport<Tokio1Executor> {
+    async fn send(&self, message: Message) -> anyhow::Result<Option<String>> {
+        AsyncTransport::send(self, message).await?;
+        Ok(None)
+    }
+}
 
-        Ok(())
+/// Backend used during tests, will keep messages in memory to allow tests to retrieve them.
+#[async_trait]
+impl EmailBackend for AsyncStubTransport {
+    async fn send(&self, message: Message) -> anyhow::Result<Option<String>> {
+        AsyncTransport::send(self, message).await?;
+        Ok(None)
     }
 }
 
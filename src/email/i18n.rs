@@ -0,0 +1,133 @@
+//! Translations of the emails, based on the [Fluent](https://projectfluent.org/)
+//! catalogs in the `locales` directory.
+//!
+//! Messages that are missing from a catalog, and locales without a catalog,
+//! fall back to English.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use unic_langid::LanguageIdentifier;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// The locales that emails can be sent in, with their translation catalogs.
+const CATALOGS: &[(&str, &str)] = &[
+    ("de", include_str!("locales/de.ftl")),
+    ("en", include_str!("locales/en.ftl")),
+    ("fr", include_str!("locales/fr.ftl")),
+];
+
+type Bundle = FluentBundle<FluentResource>;
+
+static BUNDLES: LazyLock<HashMap<&str, Bundle>> = LazyLock::new(|| {
+    CATALOGS
+        .iter()
+        .map(|(locale, source)| (*locale, build_bundle(locale, source)))
+        .collect()
+});
+
+fn build_bundle(locale: &str, source: &str) -> Bundle {
+    let language: LanguageIdentifier = locale.parse().expect("invalid locale");
+
+    let resource = FluentResource::try_new(source.to_string())
+        .unwrap_or_else(|(_, errors)| panic!("invalid `{locale}` catalog: {errors:?}"));
+
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // The emails are plain text, so the Unicode isolation marks around
+    // the placeables would only show up as garbage.
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .unwrap_or_else(|errors| panic!("invalid `{locale}` catalog: {errors:?}"));
+
+    bundle
+}
+
+/// Returns the locales that emails can be sent in.
+pub fn supported_locales() -> impl Iterator<Item = &'static str> {
+    CATALOGS.iter().map(|(locale, _)| *locale)
+}
+
+pub fn is_supported(locale: &str) -> bool {
+    supported_locales().any(|supported| supported == locale)
+}
+
+/// Translates the message with the given ID to the locale, or to English if
+/// the locale is not supported or its catalog doesn't contain the message.
+pub fn translate(locale: Option<&str>, id: &str, args: &FluentArgs<'_>) -> String {
+    let bundles = [locale.unwrap_or(DEFAULT_LOCALE), DEFAULT_LOCALE]
+        .into_iter()
+        .filter_map(|locale| BUNDLES.get(locale));
+
+    for bundle in bundles {
+        let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+            continue;
+        };
+
+        let mut errors = vec![];
+        let text = bundle.format_pattern(pattern, Some(args), &mut errors);
+        if !errors.is_empty() {
+            warn!(%id, ?errors, "Failed to format email message");
+        }
+
+        return text.into_owned();
+    }
+
+    panic!("missing email message `{id}`");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> FluentArgs<'static> {
+        let mut args = FluentArgs::new();
+        args.set("user_name", "foo");
+        args.set("domain", "crates.io");
+        args.set("token", "secret");
+        args.set("crate_name", "bar");
+        args
+    }
+
+    #[test]
+    fn catalogs_are_complete() {
+        let ids = [
+            "confirm-email-subject",
+            "confirm-email-body",
+            "owner-invite-subject",
+            "owner-invite-body",
+        ];
+
+        for id in ids {
+            for locale in supported_locales() {
+                assert!(
+                    BUNDLES[locale].has_message(id),
+                    "`{id}` is missing in `{locale}`"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn translates_with_english_fallback() {
+        let subject = translate(Some("de"), "owner-invite-subject", &args());
+        assert_eq!(subject, "crates.io: Einladung als Owner von \"bar\"");
+
+        let subject = translate(Some("xx"), "owner-invite-subject", &args());
+        assert_eq!(subject, "crates.io: Ownership invitation for \"bar\"");
+
+        let subject = translate(None, "owner-invite-subject", &args());
+        assert_eq!(subject, "crates.io: Ownership invitation for \"bar\"");
+    }
+
+    #[test]
+    fn preserves_blank_lines() {
+        let body = translate(None, "confirm-email-body", &args());
+        assert_eq!(
+            body,
+            "Hello foo! Welcome to crates.io. Please click the\nlink below to verify your email address. Thank you!\n\nhttps://crates.io/confirm/secret"
+        );
+    }
+}
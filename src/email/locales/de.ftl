@@ -0,0 +1,13 @@
+confirm-email-subject = crates.io: Bitte bestätige deine E-Mail-Adresse
+confirm-email-body =
+    Hallo { $user_name }! Willkommen bei crates.io. Bitte klicke auf den
+    folgenden Link, um deine E-Mail-Adresse zu bestätigen. Vielen Dank!
+
+    https://{ $domain }/confirm/{ $token }
+
+owner-invite-subject = crates.io: Einladung als Owner von "{ $crate_name }"
+owner-invite-body =
+    { $user_name } hat dich eingeladen, Owner des Crates { $crate_name } zu werden!
+
+    Besuche https://{ $domain }/accept-invite/{ $token }, um diese Einladung anzunehmen,
+    oder gehe zu https://{ $domain }/me/pending-invites, um alle deine Einladungen zu verwalten.
@@ -0,0 +1,13 @@
+confirm-email-subject = crates.io: Please confirm your email address
+confirm-email-body =
+    Hello { $user_name }! Welcome to crates.io. Please click the
+    link below to verify your email address. Thank you!
+
+    https://{ $domain }/confirm/{ $token }
+
+owner-invite-subject = crates.io: Ownership invitation for "{ $crate_name }"
+owner-invite-body =
+    { $user_name } has invited you to become an owner of the crate { $crate_name }!
+
+    Visit https://{ $domain }/accept-invite/{ $token } to accept this invitation,
+    or go to https://{ $domain }/me/pending-invites to manage all of your crate ownership invitations.
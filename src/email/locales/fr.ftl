@@ -0,0 +1,13 @@
+confirm-email-subject = crates.io : Veuillez confirmer votre adresse e-mail
+confirm-email-body =
+    Bonjour { $user_name } ! Bienvenue sur crates.io. Veuillez cliquer sur le
+    lien ci-dessous pour vérifier votre adresse e-mail. Merci !
+
+    https://{ $domain }/confirm/{ $token }
+
+owner-invite-subject = crates.io : Invitation à devenir propriétaire de « { $crate_name } »
+owner-invite-body =
+    { $user_name } vous a invité à devenir propriétaire de la crate { $crate_name } !
+
+    Rendez-vous sur https://{ $domain }/accept-invite/{ $token } pour accepter cette invitation,
+    ou sur https://{ $domain }/me/pending-invites pour gérer toutes vos invitations.
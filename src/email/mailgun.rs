@@ -0,0 +1,145 @@
+use super::EmailBackend;
+use crate::config::MailgunConfig;
+use anyhow::Context;
+use async_trait::async_trait;
+use crates_io_outbound::OutboundClient;
+use lettre::Message;
+use reqwest::multipart::{Form, Part};
+use secrecy::{ExposeSecret, SecretString};
+
+/// Backend that sends the emails through the HTTP API of Mailgun.
+///
+/// The message is built by [lettre] and submitted in its MIME form, so the
+/// emails look exactly the same as the ones sent through SMTP.
+#[derive(Debug)]
+pub struct MailgunBackend {
+    client: OutboundClient,
+    url: String,
+    api_key: SecretString,
+}
+
+#[derive(Deserialize)]
+struct MailgunResponse {
+    id: String,
+}
+
+impl MailgunBackend {
+    pub fn new(config: &MailgunConfig, client: OutboundClient) -> Self {
+        let api_url = config.api_url.trim_end_matches('/');
+        let url = format!("{api_url}/v3/{}/messages.mime", config.domain);
+
+        Self {
+            client,
+            url,
+            api_key: config.api_key.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailBackend for MailgunBackend {
+    async fn send(&self, message: Message) -> anyhow::Result<Option<String>> {
+        let recipients = message.envelope().to().iter();
+        let to = recipients.map(ToString::to_string).collect::<Vec<_>>();
+
+        let message = Part::bytes(message.formatted()).file_name("message.mime");
+        let form = Form::new()
+            .text("to", to.join(","))
+            .part("message", message);
+
+        let request = self
+            .client
+            .post(&self.url)
+            .basic_auth("api", Some(self.api_key.expose_secret()))
+            .multipart(form);
+
+        let response = self
+            .client
+            .send(request)
+            .await
+            .context("Failed to send the email to Mailgun")?
+            .error_for_status()
+            .context("Mailgun rejected the email")?;
+
+        let response: MailgunResponse = response
+            .json()
+            .await
+            .context("Failed to parse the Mailgun response")?;
+
+        Ok(Some(response.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::test_server::TestServer;
+    use base64::prelude::*;
+    use http::{header, StatusCode};
+    use reqwest::Client;
+
+    fn backend(server: &TestServer) -> MailgunBackend {
+        let config = MailgunConfig {
+            from: "noreply@crates.io".parse().unwrap(),
+            api_key: "secret-key".into(),
+            domain: "mg.crates.io".into(),
+            api_url: format!("{}/", server.url),
+        };
+
+        MailgunBackend::new(&config, OutboundClient::new(Client::new()))
+    }
+
+    fn message() -> Message {
+        Message::builder()
+            .from("noreply@crates.io".parse().unwrap())
+            .to("someone@example.com".parse().unwrap())
+            .subject("Test subject")
+            .body("Test body".to_string())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let body = r#"{"id":"<20250101.1@mg.crates.io>","message":"Queued. Thank you."}"#;
+        let server = TestServer::start(StatusCode::OK, body).await;
+
+        let message_id = backend(&server).send(message()).await.unwrap();
+        assert_eq!(message_id.as_deref(), Some("<20250101.1@mg.crates.io>"));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+
+        let request = &requests[0];
+        assert_eq!(request.uri.path(), "/v3/mg.crates.io/messages.mime");
+
+        let credentials = BASE64_STANDARD.encode("api:secret-key");
+        let authorization = format!("Basic {credentials}");
+        assert_eq!(request.headers[header::AUTHORIZATION], authorization);
+
+        let content_type = request.headers[header::CONTENT_TYPE].to_str().unwrap();
+        assert!(content_type.starts_with("multipart/form-data; boundary="));
+
+        let body = String::from_utf8_lossy(&request.body);
+        assert!(body.contains("name=\"to\"\r\n\r\nsomeone@example.com\r\n"));
+        assert!(body.contains("name=\"message\"; filename=\"message.mime\""));
+        assert!(body.contains("Subject: Test subject"));
+        assert!(body.contains("Test body"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected() {
+        let body = r#"{"message":"Invalid private key"}"#;
+        let server = TestServer::start(StatusCode::UNAUTHORIZED, body).await;
+
+        let error = backend(&server).send(message()).await.unwrap_err();
+        assert_eq!(error.to_string(), "Mailgun rejected the email");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_response() {
+        let server = TestServer::start(StatusCode::OK, r#"{"message":"Queued."}"#).await;
+
+        let error = backend(&server).send(message()).await.unwrap_err();
+        assert_eq!(error.to_string(), "Failed to parse the Mailgun response");
+    }
+}
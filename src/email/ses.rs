@@ -0,0 +1,175 @@
+use super::EmailBackend;
+use crate::config::SesConfig;
+use anyhow::Context;
+use async_trait::async_trait;
+use aws_credential_types::Credentials;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+use base64::prelude::*;
+use crates_io_outbound::OutboundClient;
+use lettre::Message;
+use reqwest::header::CONTENT_TYPE;
+use secrecy::ExposeSecret;
+use serde_json::json;
+use std::time::SystemTime;
+
+/// Backend that sends the emails through the HTTP API of Amazon SES.
+///
+/// The message is built by [lettre] and submitted as a raw message, so the
+/// emails look exactly the same as the ones sent through SMTP.
+#[derive(Debug)]
+pub struct SesBackend {
+    client: OutboundClient,
+    url: String,
+    region: String,
+    credentials: Credentials,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SesResponse {
+    message_id: String,
+}
+
+impl SesBackend {
+    pub fn new(config: &SesConfig, client: OutboundClient) -> Self {
+        let endpoint = config.endpoint.trim_end_matches('/');
+        let url = format!("{endpoint}/v2/email/outbound-emails");
+
+        let credentials =
+            Credentials::from_keys(&config.access_key, config.secret_key.expose_secret(), None);
+
+        Self {
+            client,
+            url,
+            region: config.region.clone(),
+            credentials,
+        }
+    }
+}
+
+#[async_trait]
+impl EmailBackend for SesBackend {
+    async fn send(&self, message: Message) -> anyhow::Result<Option<String>> {
+        let data = BASE64_STANDARD.encode(message.formatted());
+        let body = json!({ "Content": { "Raw": { "Data": data } } });
+        let body = serde_json::to_vec(&body)?;
+
+        let identity = self.credentials.clone().into();
+        let params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(&self.region)
+            .name("ses")
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()?
+            .into();
+
+        let headers = [(CONTENT_TYPE.as_str(), "application/json")];
+        let signable = SignableRequest::new(
+            "POST",
+            &self.url,
+            headers.into_iter(),
+            SignableBody::Bytes(&body),
+        )?;
+
+        let (instructions, _signature) = sign(signable, &params)?.into_parts();
+
+        let mut request = self.client.post(&self.url).body(body);
+        for (name, value) in headers.into_iter().chain(instructions.headers()) {
+            request = request.header(name, value);
+        }
+
+        let response = self
+            .client
+            .send(request)
+            .await
+            .context("Failed to send the email to SES")?
+            .error_for_status()
+            .context("SES rejected the email")?;
+
+        let response: SesResponse = response
+            .json()
+            .await
+            .context("Failed to parse the SES response")?;
+
+        Ok(Some(response.message_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::test_server::TestServer;
+    use http::{header, StatusCode};
+    use reqwest::Client;
+
+    fn backend(server: &TestServer) -> SesBackend {
+        let config = SesConfig {
+            from: "noreply@crates.io".parse().unwrap(),
+            region: "us-west-1".into(),
+            endpoint: server.url.clone(),
+            access_key: "AKIDEXAMPLE".into(),
+            secret_key: "secret-key".into(),
+        };
+
+        SesBackend::new(&config, OutboundClient::new(Client::new()))
+    }
+
+    fn message() -> Message {
+        Message::builder()
+            .from("noreply@crates.io".parse().unwrap())
+            .to("someone@example.com".parse().unwrap())
+            .subject("Test subject")
+            .body("Test body".to_string())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let body = r#"{"MessageId":"0100018c-example"}"#;
+        let server = TestServer::start(StatusCode::OK, body).await;
+
+        let message_id = backend(&server).send(message()).await.unwrap();
+        assert_eq!(message_id.as_deref(), Some("0100018c-example"));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+
+        let request = &requests[0];
+        assert_eq!(request.uri.path(), "/v2/email/outbound-emails");
+        assert_eq!(request.headers[header::CONTENT_TYPE], "application/json");
+
+        let authorization = request.headers[header::AUTHORIZATION].to_str().unwrap();
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(authorization.contains("/us-west-1/ses/aws4_request, "));
+        assert!(authorization.contains("SignedHeaders=content-type;host;x-amz-date, "));
+        assert!(authorization.contains("Signature="));
+        assert!(request.headers.contains_key("x-amz-date"));
+
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        let data = body["Content"]["Raw"]["Data"].as_str().unwrap();
+        let raw = BASE64_STANDARD.decode(data).unwrap();
+        let raw = String::from_utf8(raw).unwrap();
+        assert!(raw.contains("To: someone@example.com"));
+        assert!(raw.contains("Subject: Test subject"));
+        assert!(raw.contains("Test body"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected() {
+        let body = r#"{"message":"Email address is not verified."}"#;
+        let server = TestServer::start(StatusCode::BAD_REQUEST, body).await;
+
+        let error = backend(&server).send(message()).await.unwrap_err();
+        assert_eq!(error.to_string(), "SES rejected the email");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_response() {
+        let server = TestServer::start(StatusCode::OK, "{}").await;
+
+        let error = backend(&server).send(message()).await.unwrap_err();
+        assert_eq!(error.to_string(), "Failed to parse the SES response");
+    }
+}
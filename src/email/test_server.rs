@@ -0,0 +1,58 @@
+//! A local HTTP server for the tests of the HTTP API backends, which records
+//! the incoming requests and answers them with a fixed response.
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::Router;
+use http::{header, HeaderMap, StatusCode, Uri};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub uri: Uri,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+type Requests = Arc<Mutex<Vec<RecordedRequest>>>;
+
+pub struct TestServer {
+    pub url: String,
+    requests: Requests,
+}
+
+impl TestServer {
+    /// Starts a server on a random port that answers all requests with the
+    /// given status and JSON body.
+    pub async fn start(status: StatusCode, body: &'static str) -> Self {
+        let requests = Requests::default();
+
+        let handler = move |State(requests): State<Requests>,
+                            uri: Uri,
+                            headers: HeaderMap,
+                            request_body: Bytes| async move {
+            let request = RecordedRequest {
+                uri,
+                headers,
+                body: request_body,
+            };
+            requests.lock().push(request);
+
+            (status, [(header::CONTENT_TYPE, "application/json")], body)
+        };
+
+        let router = Router::new().fallback(handler).with_state(requests.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        Self { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().clone()
+    }
+}
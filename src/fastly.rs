@@ -1,25 +1,46 @@
 use anyhow::{anyhow, Context};
+use crates_io_env_vars::{required_var, var};
+use crates_io_outbound::{OutboundClient, Policy};
 use reqwest::header::{HeaderMap, HeaderValue};
-use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 
+const FASTLY_API_HOST: &str = "api.fastly.com";
+
+/// The maximum number of surrogate keys that Fastly purges in one request.
+const MAX_KEYS_PER_PURGE: usize = 256;
+
 #[derive(Debug)]
 pub struct Fastly {
-    client: Client,
+    client: OutboundClient,
     api_token: SecretString,
     static_domain_name: String,
+    /// The ID of the Fastly service that caches API responses, if any.
+    api_service_id: Option<String>,
 }
 
 impl Fastly {
-    pub fn from_environment(client: Client) -> Option<Self> {
-        let api_token = dotenvy::var("FASTLY_API_TOKEN").ok()?.into();
-        let static_domain_name = dotenvy::var("S3_CDN").expect("missing S3_CDN");
+    pub fn from_environment(client: OutboundClient) -> anyhow::Result<Option<Self>> {
+        let Some(api_token) = var("FASTLY_API_TOKEN")? else {
+            return Ok(None);
+        };
+
+        let static_domain_name = required_var("S3_CDN")?;
+        let api_service_id = var("FASTLY_API_SERVICE_ID")?;
 
-        Some(Self {
+        // Purging the same path again is harmless, so purge requests can be
+        // retried even though they are `POST` requests.
+        let policy = Policy {
+            retry_non_idempotent: true,
+            ..Policy::default()
+        };
+        let client = client.with_policy(FASTLY_API_HOST, policy);
+
+        Ok(Some(Self {
             client,
-            api_token,
+            api_token: api_token.into(),
             static_domain_name,
-        })
+            api_service_id,
+        }))
     }
 
     /// Invalidate a path on Fastly
@@ -48,29 +69,64 @@ impl Fastly {
         let path = path.trim_start_matches('/');
 
         for domain in domains.iter() {
-            let url = format!("https://api.fastly.com/purge/{}/{}", domain, path);
+            let url = format!("https://{FASTLY_API_HOST}/purge/{domain}/{path}");
             self.purge_url(&url).await?;
         }
 
         Ok(())
     }
 
-    async fn purge_url(&self, url: &str) -> anyhow::Result<()> {
-        trace!(?url);
+    /// Purge all cached API responses that are tagged with one of the keys
+    ///
+    /// Responses are tagged with surrogate keys by the API endpoints, see
+    /// [`crate::surrogate_keys`]. Keys are purged in batches, since Fastly
+    /// limits the number of keys per request.
+    ///
+    /// This does nothing if no API service is configured.
+    ///
+    /// More information on purging by surrogate key can be found here:
+    /// <https://developer.fastly.com/reference/api/purging/#bulk-purge-tag>
+    #[instrument(skip(self))]
+    pub async fn purge_surrogate_keys(&self, keys: &[String]) -> anyhow::Result<()> {
+        let Some(service_id) = &self.api_service_id else {
+            debug!("Skipping surrogate key purge, since no API service is configured");
+            return Ok(());
+        };
+
+        let url = format!("https://{FASTLY_API_HOST}/service/{service_id}/purge");
 
+        for keys in keys.chunks(MAX_KEYS_PER_PURGE) {
+            let mut headers = self.auth_headers()?;
+            headers.append("Surrogate-Key", HeaderValue::try_from(keys.join(" "))?);
+            self.send_purge(&url, headers).await?;
+        }
+
+        Ok(())
+    }
+
+    fn auth_headers(&self) -> anyhow::Result<HeaderMap> {
         let api_token = self.api_token.expose_secret();
         let mut api_token = HeaderValue::try_from(api_token)?;
         api_token.set_sensitive(true);
 
         let mut headers = HeaderMap::new();
         headers.append("Fastly-Key", api_token);
+        Ok(headers)
+    }
+
+    async fn purge_url(&self, url: &str) -> anyhow::Result<()> {
+        let headers = self.auth_headers()?;
+        self.send_purge(url, headers).await
+    }
+
+    async fn send_purge(&self, url: &str, headers: HeaderMap) -> anyhow::Result<()> {
+        trace!(?url);
 
         debug!("sending invalidation request to Fastly");
+        let request = self.client.post(url).headers(headers);
         let response = self
             .client
-            .post(url)
-            .headers(headers)
-            .send()
+            .send(request)
             .await
             .context("failed to send invalidation request to Fastly")?;
 
@@ -0,0 +1,207 @@
+//! Feature flags for the gradual rollout of new behavior.
+//!
+//! The features are stored in the `features` table. A feature is switched on
+//! for a user if:
+//!
+//! - the user has an override in the `feature_overrides` table, which takes
+//!   precedence over everything else,
+//! - or the feature is `enabled` for all users,
+//! - or the user falls into the `rollout_percentage` of the feature.
+//!
+//! Users are assigned to the rollout by hashing the name of the feature and
+//! their user ID, so that every user consistently sees the same behavior, and
+//! different features are rolled out to different users first. Anonymous
+//! requests only see the features that are enabled for all users.
+//!
+//! The features are cached in memory and reloaded from the database every few
+//! seconds, so that they can be checked for every request.
+
+use crate::app::App;
+use crate::models::{FeatureFlag, FeatureOverride};
+use diesel::QueryResult;
+use diesel_async::AsyncPgConnection;
+use parking_lot::RwLock;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// How long the features are cached.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+pub struct FeatureFlags {
+    /// The cached features and when they were loaded.
+    state: RwLock<(Arc<Snapshot>, Option<Instant>)>,
+    /// Ensures that only one request at a time reloads the features.
+    refresh_lock: Mutex<()>,
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    features: HashMap<String, Feature>,
+}
+
+#[derive(Debug)]
+struct Feature {
+    enabled: bool,
+    rollout_percentage: i16,
+    overrides: HashMap<i32, bool>,
+}
+
+impl FeatureFlags {
+    /// Returns `true` if the feature is switched on for all users.
+    pub async fn is_enabled(&self, app: &App, name: &str) -> bool {
+        self.is_enabled_for(app, name, None).await
+    }
+
+    /// Returns `true` if the feature is switched on for the user, or for all
+    /// users if `user_id` is `None`.
+    ///
+    /// If the features can't be loaded from the database, the previously
+    /// loaded state is used, or all features are assumed to be switched off.
+    pub async fn is_enabled_for(&self, app: &App, name: &str, user_id: Option<i32>) -> bool {
+        self.snapshot(app).await.is_enabled(name, user_id)
+    }
+
+    /// Reloads the features from the database on the next check, e.g. after
+    /// they have been changed through the admin API.
+    pub fn invalidate(&self) {
+        self.state.write().1 = None;
+    }
+
+    async fn snapshot(&self, app: &App) -> Arc<Snapshot> {
+        let (snapshot, refreshed_at) = self.state.read().clone();
+        if refreshed_at.is_some_and(|refreshed_at| refreshed_at.elapsed() < REFRESH_INTERVAL) {
+            return snapshot;
+        }
+
+        let Ok(_guard) = self.refresh_lock.try_lock() else {
+            return snapshot;
+        };
+
+        let result = async {
+            let mut conn = app.db_read_prefer_primary().await?;
+            Ok::<_, anyhow::Error>(Snapshot::load(&mut conn).await?)
+        }
+        .await;
+
+        let mut state = self.state.write();
+        match result {
+            Ok(new_snapshot) => state.0 = Arc::new(new_snapshot),
+            Err(error) => warn!("Failed to load feature flags: {error}"),
+        }
+
+        // Also wait for the next interval after a failed refresh, so that an
+        // unavailable database isn't queried for every request.
+        state.1 = Some(Instant::now());
+        state.0.clone()
+    }
+}
+
+impl Snapshot {
+    async fn load(conn: &mut AsyncPgConnection) -> QueryResult<Self> {
+        let mut features = HashMap::new();
+        for flag in FeatureFlag::all(conn).await? {
+            let feature = Feature {
+                enabled: flag.enabled,
+                rollout_percentage: flag.rollout_percentage,
+                overrides: HashMap::new(),
+            };
+            features.insert(flag.name, feature);
+        }
+
+        for feature_override in FeatureOverride::all(conn).await? {
+            if let Some(feature) = features.get_mut(&feature_override.feature) {
+                let user_id = feature_override.user_id;
+                feature.overrides.insert(user_id, feature_override.enabled);
+            }
+        }
+
+        Ok(Self { features })
+    }
+
+    fn is_enabled(&self, name: &str, user_id: Option<i32>) -> bool {
+        let Some(feature) = self.features.get(name) else {
+            return false;
+        };
+
+        let Some(user_id) = user_id else {
+            return feature.enabled;
+        };
+
+        match feature.overrides.get(&user_id) {
+            Some(enabled) => *enabled,
+            None => feature.enabled || rollout_bucket(name, user_id) < feature.rollout_percentage,
+        }
+    }
+}
+
+/// Returns the bucket between 0 and 99 that the user is assigned to for the
+/// rollout of the feature.
+fn rollout_bucket(name: &str, user_id: i32) -> i16 {
+    let hash = Sha256::digest(format!("{name}:{user_id}"));
+    let value = u64::from_be_bytes(hash[..8].try_into().unwrap());
+    (value % 100) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_search(enabled: bool, rollout_percentage: i16, overrides: &[(i32, bool)]) -> Snapshot {
+        let feature = Feature {
+            enabled,
+            rollout_percentage,
+            overrides: overrides.iter().copied().collect(),
+        };
+
+        Snapshot {
+            features: HashMap::from([("new_search".to_string(), feature)]),
+        }
+    }
+
+    #[test]
+    fn test_unknown_feature() {
+        let snapshot = Snapshot::default();
+        assert!(!snapshot.is_enabled("new_search", None));
+        assert!(!snapshot.is_enabled("new_search", Some(1)));
+    }
+
+    #[test]
+    fn test_enabled_for_all_users() {
+        let snapshot = new_search(true, 0, &[(1, false)]);
+        assert!(snapshot.is_enabled("new_search", None));
+        assert!(snapshot.is_enabled("new_search", Some(2)));
+        assert!(!snapshot.is_enabled("new_search", Some(1)));
+    }
+
+    #[test]
+    fn test_rollout_percentage() {
+        let snapshot = new_search(false, 25, &[]);
+        assert!(!snapshot.is_enabled("new_search", None));
+
+        let enabled = (1..=10_000)
+            .filter(|user_id| snapshot.is_enabled("new_search", Some(*user_id)))
+            .count();
+        assert!((2_000..3_000).contains(&enabled), "{enabled}");
+
+        let snapshot = new_search(false, 100, &[]);
+        assert!((1..=100).all(|user_id| snapshot.is_enabled("new_search", Some(user_id))));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let snapshot = new_search(false, 0, &[(1, true)]);
+        assert!(snapshot.is_enabled("new_search", Some(1)));
+        assert!(!snapshot.is_enabled("new_search", Some(2)));
+        assert!(!snapshot.is_enabled("new_search", None));
+    }
+
+    #[test]
+    fn test_rollout_bucket_is_stable() {
+        assert_eq!(rollout_bucket("foo", 42), rollout_bucket("foo", 42));
+        assert!((0..100).contains(&rollout_bucket("foo", 42)));
+    }
+}
@@ -0,0 +1,270 @@
+//! Resolution of the features of a published version.
+//!
+//! This implements the subset of the cargo feature resolution rules that is
+//! needed to figure out which features and optional dependencies a set of
+//! requested features enables, including the `dep:` and weak dependency
+//! feature syntax.
+//!
+//! See <https://doc.rust-lang.org/cargo/reference/features.html>.
+
+use crates_io_index::features::FeaturesMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A single entry in the list of values that a feature enables.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeatureValue {
+    /// Another feature of the same crate, e.g. `std`.
+    Feature { feature: String },
+    /// An optional dependency, e.g. `dep:serde`.
+    Dependency { dependency: String },
+    /// A feature of a dependency, e.g. `serde/derive` or `serde?/derive`.
+    ///
+    /// Weak dependency features don't enable the dependency if it is
+    /// optional.
+    DependencyFeature {
+        dependency: String,
+        feature: String,
+        weak: bool,
+    },
+}
+
+impl FeatureValue {
+    pub fn parse(value: &str) -> Self {
+        if let Some(dependency) = value.strip_prefix("dep:") {
+            let dependency = dependency.to_string();
+            FeatureValue::Dependency { dependency }
+        } else if let Some((dependency, feature)) = value.split_once('/') {
+            let (dependency, weak) = match dependency.strip_suffix('?') {
+                Some(dependency) => (dependency, true),
+                None => (dependency, false),
+            };
+
+            FeatureValue::DependencyFeature {
+                dependency: dependency.to_string(),
+                feature: feature.to_string(),
+                weak,
+            }
+        } else {
+            let feature = value.to_string();
+            FeatureValue::Feature { feature }
+        }
+    }
+}
+
+/// Returns the optional dependencies that have an implicit feature of the
+/// same name.
+///
+/// Optional dependencies only have an implicit feature if they are not
+/// referenced via the `dep:` syntax anywhere in the feature map.
+pub fn implicit_features<'a>(
+    features: &FeaturesMap,
+    optional_dependencies: &BTreeSet<&'a str>,
+) -> BTreeSet<&'a str> {
+    optional_dependencies
+        .iter()
+        .copied()
+        .filter(|name| {
+            !features.contains_key(*name)
+                && !features
+                    .values()
+                    .flatten()
+                    .any(|value| value.strip_prefix("dep:") == Some(name))
+        })
+        .collect()
+}
+
+/// The result of resolving a set of requested features.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedFeatures {
+    /// All features that are enabled, including the transitively enabled
+    /// ones.
+    pub features: BTreeSet<String>,
+    /// The optional dependencies that are enabled.
+    pub dependencies: BTreeSet<String>,
+    /// The features that are enabled on dependencies, keyed by dependency
+    /// name. Only contains entries for dependencies that are either not
+    /// optional, or that have been enabled.
+    pub dependency_features: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Resolves the `requested` features of a version with the given feature map.
+///
+/// `optional_dependencies` contains the names of the optional dependencies
+/// of the version, which are needed to resolve implicit features and to
+/// decide whether dependency features should be kept.
+pub fn resolve<'a>(
+    features: &FeaturesMap,
+    optional_dependencies: &BTreeSet<&str>,
+    requested: impl IntoIterator<Item = &'a str>,
+) -> ResolvedFeatures {
+    let mut resolved = ResolvedFeatures::default();
+
+    let implicit_features = implicit_features(features, optional_dependencies);
+
+    let mut queue = requested
+        .into_iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    while let Some(value) = queue.pop() {
+        match FeatureValue::parse(&value) {
+            FeatureValue::Dependency { dependency } => {
+                resolved.dependencies.insert(dependency);
+            }
+            FeatureValue::DependencyFeature {
+                dependency,
+                feature,
+                weak,
+            } => {
+                if !weak {
+                    if features.contains_key(&dependency) {
+                        queue.push(dependency.clone());
+                    } else if optional_dependencies.contains(dependency.as_str()) {
+                        resolved.dependencies.insert(dependency.clone());
+                    }
+                }
+
+                resolved
+                    .dependency_features
+                    .entry(dependency)
+                    .or_default()
+                    .insert(feature);
+            }
+            FeatureValue::Feature { feature } => {
+                if let Some(values) = features.get(&feature) {
+                    if resolved.features.insert(feature) {
+                        queue.extend(values.iter().cloned());
+                    }
+                } else if implicit_features.contains(feature.as_str()) {
+                    resolved.dependencies.insert(feature.clone());
+                    resolved.features.insert(feature);
+                }
+            }
+        }
+    }
+
+    resolved.dependency_features.retain(|dependency, _| {
+        !optional_dependencies.contains(dependency.as_str())
+            || resolved.dependencies.contains(dependency)
+    });
+
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    fn features(features: &[(&str, &[&str])]) -> FeaturesMap {
+        features
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(ToString::to_string).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_transitive_features() {
+        let features = features(&[
+            ("default", &["std"]),
+            ("std", &["alloc"]),
+            ("alloc", &[]),
+            ("nightly", &[]),
+        ]);
+
+        let resolved = resolve(&features, &BTreeSet::new(), ["default"]);
+        assert_debug_snapshot!(resolved.features, @r#"
+        {
+            "alloc",
+            "default",
+            "std",
+        }
+        "#);
+    }
+
+    #[test]
+    fn test_optional_dependencies() {
+        let features = features(&[
+            ("default", &["serde", "dep:log", "tokio?/rt"]),
+            ("full", &["tokio/full"]),
+        ]);
+        let optional = BTreeSet::from(["serde", "log", "tokio", "rand"]);
+
+        let resolved = resolve(&features, &optional, ["default"]);
+        assert_debug_snapshot!(resolved, @r#"
+        ResolvedFeatures {
+            features: {
+                "default",
+                "serde",
+            },
+            dependencies: {
+                "log",
+                "serde",
+            },
+            dependency_features: {},
+        }
+        "#);
+
+        let resolved = resolve(&features, &optional, ["default", "full"]);
+        assert_debug_snapshot!(resolved.dependencies, @r#"
+        {
+            "log",
+            "serde",
+            "tokio",
+        }
+        "#);
+        assert_debug_snapshot!(resolved.dependency_features, @r#"
+        {
+            "tokio": {
+                "full",
+                "rt",
+            },
+        }
+        "#);
+    }
+
+    #[test]
+    fn test_dep_syntax_disables_implicit_feature() {
+        let features = features(&[("json", &["dep:serde_json"])]);
+        let optional = BTreeSet::from(["serde_json"]);
+
+        let resolved = resolve(&features, &optional, ["serde_json"]);
+        assert!(resolved.dependencies.is_empty());
+
+        let resolved = resolve(&features, &optional, ["json"]);
+        assert_eq!(resolved.dependencies, BTreeSet::from(["serde_json".into()]));
+
+        let optional = BTreeSet::from(["serde_json", "log"]);
+        let implicit = implicit_features(&features, &optional);
+        assert_eq!(implicit, BTreeSet::from(["log"]));
+    }
+
+    #[test]
+    fn test_parse_feature_value() {
+        assert_debug_snapshot!(FeatureValue::parse("std"), @r#"
+        Feature {
+            feature: "std",
+        }
+        "#);
+        assert_debug_snapshot!(FeatureValue::parse("dep:serde"), @r#"
+        Dependency {
+            dependency: "serde",
+        }
+        "#);
+        assert_debug_snapshot!(FeatureValue::parse("serde/derive"), @r#"
+        DependencyFeature {
+            dependency: "serde",
+            feature: "derive",
+            weak: false,
+        }
+        "#);
+        assert_debug_snapshot!(FeatureValue::parse("serde?/derive"), @r#"
+        DependencyFeature {
+            dependency: "serde",
+            feature: "derive",
+            weak: true,
+        }
+        "#);
+    }
+}
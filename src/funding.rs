@@ -0,0 +1,379 @@
+//! Funding links of crates, which are shown as "Sponsor" links on the crate
+//! page.
+//!
+//! The links are read from the `[package.metadata.funding]` table of the
+//! manifest when a version is published, or from a `FUNDING.yml` file in the
+//! package if the manifest doesn't declare any. Both use the keys of the
+//! [GitHub `FUNDING.yml` format](https://docs.github.com/en/repositories/managing-your-repositorys-settings-and-features/customizing-your-repository/displaying-a-sponsor-button-in-your-repository),
+//! with either a single account name or a list of them:
+//!
+//! ```toml
+//! [package.metadata.funding]
+//! github = ["octocat", "surftocat"]
+//! open_collective = "rust-lang"
+//! custom = ["https://ko-fi.com/octocat"]
+//! ```
+//!
+//! Only links to the platforms in [FundingPlatform] are accepted, including
+//! for `custom` URLs. Invalid links in the manifest are rejected at publish
+//! time, while invalid links in a `FUNDING.yml` file are skipped, since the
+//! file is usually written for GitHub and not for crates.io.
+
+use crate::models::{FundingLink, FundingPlatform};
+use url::Url;
+
+/// The maximum number of funding links of a crate.
+pub const MAX_FUNDING_LINKS: usize = 10;
+
+struct Platform {
+    platform: FundingPlatform,
+    /// The key of the platform in the `FUNDING.yml` format.
+    key: &'static str,
+    /// The URL of the funding page, without the account name.
+    base_url: &'static str,
+    /// The hosts that `custom` URLs of the platform can use.
+    hosts: &'static [&'static str],
+}
+
+const PLATFORMS: &[Platform] = &[
+    Platform {
+        platform: FundingPlatform::Github,
+        key: "github",
+        base_url: "https://github.com/sponsors/",
+        hosts: &["github.com"],
+    },
+    Platform {
+        platform: FundingPlatform::OpenCollective,
+        key: "open_collective",
+        base_url: "https://opencollective.com/",
+        hosts: &["opencollective.com"],
+    },
+    Platform {
+        platform: FundingPlatform::Patreon,
+        key: "patreon",
+        base_url: "https://www.patreon.com/",
+        hosts: &["patreon.com", "www.patreon.com"],
+    },
+    Platform {
+        platform: FundingPlatform::KoFi,
+        key: "ko_fi",
+        base_url: "https://ko-fi.com/",
+        hosts: &["ko-fi.com"],
+    },
+    Platform {
+        platform: FundingPlatform::Liberapay,
+        key: "liberapay",
+        base_url: "https://liberapay.com/",
+        hosts: &["liberapay.com"],
+    },
+    Platform {
+        platform: FundingPlatform::BuyMeACoffee,
+        key: "buy_me_a_coffee",
+        base_url: "https://buymeacoffee.com/",
+        hosts: &["buymeacoffee.com", "www.buymeacoffee.com"],
+    },
+    Platform {
+        platform: FundingPlatform::Polar,
+        key: "polar",
+        base_url: "https://polar.sh/",
+        hosts: &["polar.sh"],
+    },
+    Platform {
+        platform: FundingPlatform::ThanksDev,
+        key: "thanks_dev",
+        base_url: "https://thanks.dev/",
+        hosts: &["thanks.dev"],
+    },
+];
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum FundingError {
+    #[error("expected a table")]
+    InvalidTable,
+    #[error("expected a string or an array of strings for `{0}`")]
+    InvalidValue(String),
+    #[error("unsupported funding platform `{0}`")]
+    UnknownPlatform(String),
+    #[error("invalid account name `{1}` for `{0}`")]
+    InvalidAccount(String, String),
+    #[error("`{0}` is not a link to a supported funding platform")]
+    UnsupportedUrl(String),
+    #[error("expected at most {MAX_FUNDING_LINKS} funding links")]
+    TooManyLinks,
+}
+
+/// Reads the funding links from the `metadata` table of the `[package]`
+/// section of a manifest.
+pub fn from_manifest_metadata(
+    metadata: Option<&toml::Value>,
+) -> Result<Vec<FundingLink>, FundingError> {
+    let Some(funding) = metadata.and_then(|metadata| metadata.get("funding")) else {
+        return Ok(Vec::new());
+    };
+
+    let table = funding.as_table().ok_or(FundingError::InvalidTable)?;
+
+    let mut links = Vec::new();
+    for (key, value) in table {
+        let accounts = match value {
+            toml::Value::String(account) => vec![account.as_str()],
+            toml::Value::Array(array) => array
+                .iter()
+                .map(|value| value.as_str())
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| FundingError::InvalidValue(key.clone()))?,
+            _ => return Err(FundingError::InvalidValue(key.clone())),
+        };
+
+        for account in accounts {
+            push_unique(&mut links, parse_link(key, account)?);
+        }
+    }
+
+    if links.len() > MAX_FUNDING_LINKS {
+        return Err(FundingError::TooManyLinks);
+    }
+
+    Ok(links)
+}
+
+/// Reads the funding links from the contents of a `FUNDING.yml` file.
+///
+/// Unknown platforms and invalid links are skipped, and only the first
+/// [MAX_FUNDING_LINKS] links are returned.
+pub fn from_funding_yml(contents: &str) -> Vec<FundingLink> {
+    let mut links = Vec::new();
+    for (key, accounts) in parse_yml(contents) {
+        for account in accounts {
+            if let Ok(link) = parse_link(&key, &account) {
+                push_unique(&mut links, link);
+            }
+        }
+    }
+
+    links.truncate(MAX_FUNDING_LINKS);
+    links
+}
+
+fn push_unique(links: &mut Vec<FundingLink>, link: FundingLink) {
+    if !links.contains(&link) {
+        links.push(link);
+    }
+}
+
+fn parse_link(key: &str, value: &str) -> Result<FundingLink, FundingError> {
+    if key == "custom" {
+        return parse_custom_url(value);
+    }
+
+    let platform = PLATFORMS
+        .iter()
+        .find(|platform| platform.key == key)
+        .ok_or_else(|| FundingError::UnknownPlatform(key.to_string()))?;
+
+    // `thanks.dev` accounts include the platform of the account, e.g.
+    // `u/gh/octocat`.
+    let allow_slashes = platform.platform == FundingPlatform::ThanksDev;
+    let is_valid_char = |c: char| {
+        c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') || (allow_slashes && c == '/')
+    };
+
+    let is_valid = !value.is_empty()
+        && value.chars().all(is_valid_char)
+        && !value.split('/').any(|part| part.is_empty() || part == "..");
+    if !is_valid {
+        return Err(FundingError::InvalidAccount(
+            key.to_string(),
+            value.to_string(),
+        ));
+    }
+
+    Ok(FundingLink {
+        platform: platform.platform,
+        url: format!("{}{value}", platform.base_url),
+    })
+}
+
+fn parse_custom_url(value: &str) -> Result<FundingLink, FundingError> {
+    let unsupported = || FundingError::UnsupportedUrl(value.to_string());
+
+    let url = Url::parse(value).map_err(|_| unsupported())?;
+    if url.scheme() != "https" {
+        return Err(unsupported());
+    }
+
+    let host = url.host_str().ok_or_else(unsupported)?;
+    let platform = PLATFORMS
+        .iter()
+        .find(|platform| platform.hosts.contains(&host))
+        .ok_or_else(unsupported)?;
+
+    // Only GitHub Sponsors pages are funding links on GitHub.
+    if platform.platform == FundingPlatform::Github && !url.path().starts_with("/sponsors/") {
+        return Err(unsupported());
+    }
+
+    Ok(FundingLink {
+        platform: platform.platform,
+        url: url.to_string(),
+    })
+}
+
+/// Parses the subset of YAML that is used by `FUNDING.yml` files: a mapping
+/// of keys to a single value, a flow sequence (`[a, b]`), or a block
+/// sequence (`- a` on the following lines).
+fn parse_yml(contents: &str) -> Vec<(String, Vec<String>)> {
+    let mut entries: Vec<(String, Vec<String>)> = Vec::new();
+
+    for line in contents.lines() {
+        let line = strip_yml_comment(line);
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix('-') {
+            if let Some((_, values)) = entries.last_mut() {
+                let item = unquote(item);
+                if !item.is_empty() {
+                    values.push(item.to_string());
+                }
+            }
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+
+        let value = value.trim();
+        let values = if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            list.split(',')
+                .map(unquote)
+                .filter(|item| !item.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        } else if value.is_empty() || value == "~" || value == "null" {
+            Vec::new()
+        } else {
+            vec![unquote(value).to_string()]
+        };
+
+        entries.push((key.trim().to_string(), values));
+    }
+
+    entries
+}
+
+fn strip_yml_comment(line: &str) -> &str {
+    if line.trim_start().starts_with('#') {
+        return "";
+    }
+
+    // A `#` only starts a comment after whitespace, so that URLs with a
+    // fragment are kept.
+    match line.find(" #") {
+        Some(index) => &line[..index],
+        None => line,
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(value) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return value;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    fn urls(links: &[FundingLink]) -> Vec<&str> {
+        links.iter().map(|link| link.url.as_str()).collect()
+    }
+
+    #[test]
+    fn test_from_manifest_metadata() {
+        assert_eq!(from_manifest_metadata(None), Ok(vec![]));
+
+        let metadata: toml::Value = toml::from_str(
+            r#"
+            [funding]
+            github = ["octocat", "surftocat"]
+            open_collective = "rust-lang"
+            thanks_dev = "u/gh/octocat"
+            custom = ["https://ko-fi.com/octocat", "https://github.com/sponsors/octocat"]
+            "#,
+        )
+        .unwrap();
+
+        let links = from_manifest_metadata(Some(&metadata)).unwrap();
+        let mut urls = urls(&links);
+        urls.sort();
+        assert_eq!(
+            urls,
+            [
+                "https://github.com/sponsors/octocat",
+                "https://github.com/sponsors/surftocat",
+                "https://ko-fi.com/octocat",
+                "https://opencollective.com/rust-lang",
+                "https://thanks.dev/u/gh/octocat",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_manifest_metadata_errors() {
+        let error = |toml: &str| {
+            let metadata: toml::Value = toml::from_str(toml).unwrap();
+            from_manifest_metadata(Some(&metadata)).unwrap_err()
+        };
+
+        assert_snapshot!(error(r#"funding = "octocat""#), @"expected a table");
+        assert_snapshot!(error("funding.github = 42"), @"expected a string or an array of strings for `github`");
+        assert_snapshot!(error(r#"funding.paypal = "octocat""#), @"unsupported funding platform `paypal`");
+        assert_snapshot!(error(r#"funding.github = "../octocat""#), @"invalid account name `../octocat` for `github`");
+        assert_snapshot!(error(r#"funding.custom = "https://example.com/donate""#), @"`https://example.com/donate` is not a link to a supported funding platform");
+        assert_snapshot!(error(r#"funding.custom = "https://github.com/octocat""#), @"`https://github.com/octocat` is not a link to a supported funding platform");
+        assert_snapshot!(error(r#"funding.custom = "http://ko-fi.com/octocat""#), @"`http://ko-fi.com/octocat` is not a link to a supported funding platform");
+
+        let accounts = (0..11).map(|i| format!("\"user{i}\"")).collect::<Vec<_>>();
+        let toml = format!("funding.github = [{}]", accounts.join(", "));
+        assert_snapshot!(error(&toml), @"expected at most 10 funding links");
+    }
+
+    #[test]
+    fn test_from_funding_yml() {
+        let contents = r#"
+# These are supported funding model platforms
+
+github: [octocat, 'surftocat']
+patreon: octocat # the Patreon account
+open_collective: # Replace with a single Open Collective username
+ko_fi: ~
+tidelift: # Replace with a single Tidelift platform-name/package-name e.g., npm/babel
+custom:
+  - https://www.paypal.me/octocat
+  - "https://www.buymeacoffee.com/octocat"
+"#;
+
+        let links = from_funding_yml(contents);
+        assert_eq!(
+            urls(&links),
+            [
+                "https://github.com/sponsors/octocat",
+                "https://github.com/sponsors/surftocat",
+                "https://www.patreon.com/octocat",
+                "https://www.buymeacoffee.com/octocat",
+            ]
+        );
+    }
+}
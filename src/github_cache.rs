@@ -0,0 +1,237 @@
+//! Read-through cache of GitHub API responses.
+//!
+//! Adding team owners and checking the rights of team members needs to query
+//! GitHub for teams and memberships, which runs into the GitHub rate limits
+//! when done for every request. The responses are therefore cached in the
+//! `github_cache` table, and the entries that are still in use are refreshed
+//! in the background by the [`RefreshGitHubCache`] job before they expire.
+//!
+//! If requests to GitHub fail, e.g. because the circuit breaker of the
+//! outbound client stopped sending them after repeated failures, expired
+//! entries are used instead, so that a GitHub outage doesn't fail requests
+//! that the cache can answer.
+//!
+//! [`RefreshGitHubCache`]: crate::worker::jobs::RefreshGitHubCache
+
+use crate::app::App;
+use crate::models::User;
+use crate::schema::github_cache;
+use crate::util::errors::{custom, AppResult, BoxedAppError};
+use chrono::{DateTime, TimeDelta, Utc};
+use crates_io_github::{GitHubClient, GitHubError};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use oauth2::AccessToken;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// How long team and organization memberships are cached.
+const MEMBERSHIP_TTL: TimeDelta = TimeDelta::minutes(15);
+
+/// How long teams and organizations are cached.
+const METADATA_TTL: TimeDelta = TimeDelta::days(1);
+
+/// How long expired entries may still be used while GitHub is unavailable.
+const MAX_STALENESS: TimeDelta = TimeDelta::days(1);
+
+/// A GitHub API request whose response can be cached.
+///
+/// The requests that depend on the permissions of the GitHub token include
+/// the login of the user, so that their responses are never shared between
+/// users.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GitHubRequest {
+    Organization {
+        org_name: String,
+    },
+    Team {
+        org_name: String,
+        team_name: String,
+        login: String,
+    },
+    TeamMembership {
+        org_id: i32,
+        team_id: i32,
+        login: String,
+    },
+    OrgMembership {
+        org_id: i32,
+        login: String,
+    },
+}
+
+impl GitHubRequest {
+    /// The key of the request in the `github_cache` table.
+    pub fn key(&self) -> String {
+        // Serializing a plain enum can't fail.
+        serde_json::to_string(self).expect("failed to serialize GitHub request")
+    }
+
+    pub fn from_key(key: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(key)
+    }
+
+    fn ttl(&self) -> TimeDelta {
+        match self {
+            Self::Organization { .. } | Self::Team { .. } => METADATA_TTL,
+            Self::TeamMembership { .. } | Self::OrgMembership { .. } => MEMBERSHIP_TTL,
+        }
+    }
+
+    /// Sends the request to GitHub. Resources that don't exist are returned
+    /// as `null`, so that they can be cached too.
+    pub async fn fetch(
+        &self,
+        github: &dyn GitHubClient,
+        token: &AccessToken,
+    ) -> Result<Value, GitHubError> {
+        let result = match self {
+            Self::Organization { org_name } => to_value(github.org_by_name(org_name, token).await),
+            Self::Team {
+                org_name,
+                team_name,
+                ..
+            } => to_value(github.team_by_name(org_name, team_name, token).await),
+            Self::TeamMembership {
+                org_id,
+                team_id,
+                login,
+            } => to_value(
+                github
+                    .team_membership(*org_id, *team_id, login, token)
+                    .await,
+            ),
+            Self::OrgMembership { org_id, login } => {
+                to_value(github.org_membership(*org_id, login, token).await)
+            }
+        };
+
+        match result {
+            Err(GitHubError::NotFound(_)) => Ok(Value::Null),
+            result => result,
+        }
+    }
+}
+
+fn to_value<T: Serialize>(result: Result<T, GitHubError>) -> Result<Value, GitHubError> {
+    serde_json::to_value(result?).map_err(|error| GitHubError::Other(error.into()))
+}
+
+/// Saves the response of a request, which was fetched with the GitHub token
+/// of the given user.
+pub async fn store(
+    conn: &mut AsyncPgConnection,
+    request: &GitHubRequest,
+    value: &Value,
+    user_id: i32,
+) -> QueryResult<()> {
+    let now = Utc::now();
+    let expires_at = now + request.ttl();
+
+    diesel::insert_into(github_cache::table)
+        .values((
+            github_cache::key.eq(request.key()),
+            github_cache::value.eq(value),
+            github_cache::user_id.eq(user_id),
+            github_cache::fetched_at.eq(now),
+            github_cache::expires_at.eq(expires_at),
+        ))
+        .on_conflict(github_cache::key)
+        .do_update()
+        .set((
+            github_cache::value.eq(value),
+            github_cache::user_id.eq(user_id),
+            github_cache::fetched_at.eq(now),
+            github_cache::expires_at.eq(expires_at),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the response of the request from the cache, or fetches it from
+/// GitHub with the token of the given user if it isn't cached or expired.
+///
+/// Returns `None` if the requested resource doesn't exist.
+pub async fn cached<T: DeserializeOwned>(
+    app: &App,
+    request: GitHubRequest,
+    user: &User,
+) -> AppResult<Option<T>> {
+    let entry: Option<(Value, DateTime<Utc>, DateTime<Utc>)> = {
+        let mut conn = app.db_write().await?;
+        diesel::update(github_cache::table.find(request.key()))
+            .set(github_cache::last_used_at.eq(Utc::now()))
+            .returning((
+                github_cache::value,
+                github_cache::fetched_at,
+                github_cache::expires_at,
+            ))
+            .get_result(&mut conn)
+            .await
+            .optional()?
+    };
+
+    let now = Utc::now();
+    if let Some((value, _, expires_at)) = &entry {
+        if *expires_at > now {
+            return Ok(serde_json::from_value(value.clone())?);
+        }
+    }
+
+    let stale = entry
+        .filter(|(_, fetched_at, _)| now - *fetched_at < MAX_STALENESS)
+        .map(|(value, ..)| value);
+
+    let token = AccessToken::new(user.gh_access_token.clone());
+    match request.fetch(&*app.github, &token).await {
+        Ok(value) => {
+            let mut conn = app.db_write().await?;
+            store(&mut conn, &request, &value, user.id).await?;
+
+            Ok(serde_json::from_value(value)?)
+        }
+        Err(GitHubError::Other(error)) => match stale {
+            Some(value) => {
+                warn!("Using expired GitHub response after failed request: {error}");
+                Ok(serde_json::from_value(value)?)
+            }
+            None => {
+                warn!("Request to GitHub failed: {error}");
+                Err(github_unavailable())
+            }
+        },
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn github_unavailable() -> BoxedAppError {
+    custom(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "GitHub is currently unavailable. Please try again later.",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_key() {
+        let request = GitHubRequest::TeamMembership {
+            org_id: 1,
+            team_id: 2,
+            login: "foo".to_string(),
+        };
+        let key = request.key();
+        assert_eq!(
+            key,
+            r#"{"kind":"team_membership","org_id":1,"team_id":2,"login":"foo"}"#
+        );
+        assert_eq!(GitHubRequest::from_key(&key).unwrap(), request);
+    }
+}
@@ -0,0 +1,150 @@
+//! Accept loop of the HTTP server.
+//!
+//! `axum::serve()` does not allow tuning the protocol settings of hyper, so
+//! the connections are served with hyper-util directly instead, based on the
+//! [`HttpServerConfig`]. The connections are also tracked in the instance
+//! metrics, which shows how well clients like cargo reuse their connections.
+
+use crate::app::App;
+use crate::config::HttpServerConfig;
+use axum::body::Body;
+use axum::extract::connect_info::ConnectInfo;
+use axum::response::Response;
+use http::Request;
+use hyper::body::Incoming;
+use hyper::service::service_fn;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tower::{Service, ServiceExt};
+
+/// Serves the connections of the listener until the `shutdown` future
+/// resolves. Afterwards, no new connections are accepted, and the open
+/// connections are closed once their in-flight requests are finished.
+pub async fn serve<S>(
+    app: Arc<App>,
+    listener: TcpListener,
+    service: S,
+    shutdown: impl Future<Output = ()>,
+) where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    let builder = builder(&app.config.http);
+
+    // Every connection holds a receiver of the shutdown signal, so that the
+    // `closed()` future of the sender resolves once all of them are closed.
+    let (signal_tx, signal_rx) = watch::channel(());
+
+    let mut shutdown = pin!(shutdown);
+    loop {
+        let (stream, remote_addr) = tokio::select! {
+            result = listener.accept() => match result {
+                Ok(connection) => connection,
+                Err(error) => {
+                    // e.g. if the process ran out of file descriptors
+                    error!(%error, "Failed to accept HTTP connection");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        tokio::spawn(serve_connection(
+            app.clone(),
+            builder.clone(),
+            stream,
+            remote_addr,
+            service.clone(),
+            signal_rx.clone(),
+        ));
+    }
+
+    drop(listener);
+    drop(signal_rx);
+
+    signal_tx.send_replace(());
+    signal_tx.closed().await;
+}
+
+fn builder(config: &HttpServerConfig) -> Builder<TokioExecutor> {
+    let mut builder = Builder::new(TokioExecutor::new());
+
+    builder
+        .http1()
+        .timer(TokioTimer::new())
+        .keep_alive(config.keep_alive)
+        .header_read_timeout(config.keep_alive_timeout);
+
+    builder
+        .http2()
+        .timer(TokioTimer::new())
+        .keep_alive_interval(config.http2_keep_alive_interval)
+        .keep_alive_timeout(config.http2_keep_alive_timeout)
+        .max_concurrent_streams(config.http2_max_concurrent_streams);
+
+    if config.http2 {
+        builder
+    } else {
+        builder.http1_only()
+    }
+}
+
+async fn serve_connection<S>(
+    app: Arc<App>,
+    builder: Builder<TokioExecutor>,
+    stream: TcpStream,
+    remote_addr: SocketAddr,
+    service: S,
+    mut signal_rx: watch::Receiver<()>,
+) where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    let metrics = &app.instance_metrics;
+    metrics.http_connections_total.inc();
+    metrics.http_connections_open.inc();
+
+    let service_app = app.clone();
+    let service = service_fn(move |mut request: Request<Incoming>| {
+        let version = format!("{:?}", request.version());
+        service_app
+            .instance_metrics
+            .http_requests_by_version_total
+            .with_label_values(&[&version])
+            .inc();
+
+        // Provide the same extension as `into_make_service_with_connect_info()`
+        request.extensions_mut().insert(ConnectInfo(remote_addr));
+        service.clone().oneshot(request.map(Body::new))
+    });
+
+    let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+    let mut connection = pin!(connection);
+
+    let mut shutting_down = false;
+    let result = loop {
+        tokio::select! {
+            result = connection.as_mut() => break result,
+            _ = signal_rx.changed(), if !shutting_down => {
+                shutting_down = true;
+                connection.as_mut().graceful_shutdown();
+            }
+        }
+    };
+
+    if let Err(error) = result {
+        debug!(%error, %remote_addr, "HTTP connection closed with an error");
+        metrics.http_connection_errors_total.inc();
+    }
+
+    metrics.http_connections_open.dec();
+}
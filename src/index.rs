@@ -3,7 +3,7 @@
 //! index files.
 
 use crate::models::{Crate, Dependency, Version};
-use crate::schema::crates;
+use crate::schema::{crates, versions};
 use anyhow::Context;
 use crates_io_index::features::split_features;
 use diesel::prelude::*;
@@ -60,6 +60,7 @@ pub async fn index_metadata(
     conn: &mut AsyncPgConnection,
 ) -> QueryResult<Vec<crates_io_index::Crate>> {
     let mut versions: Vec<Version> = Version::belonging_to(krate)
+        .filter(Version::not_deleted())
         .select(Version::as_select())
         .load(conn)
         .await?;
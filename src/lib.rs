@@ -25,6 +25,8 @@ use tikv_jemallocator::Jemalloc;
 #[global_allocator]
 static ALLOC: Jemalloc = Jemalloc;
 
+pub mod abuse_throttle;
+pub mod api_usage;
 mod app;
 pub mod auth;
 pub mod boot;
@@ -33,23 +35,37 @@ pub mod cloudfront;
 pub mod config;
 pub mod controllers;
 pub mod db;
+pub mod deadline;
+pub mod dependency_graph;
 pub mod email;
 pub mod external_urls;
 pub mod fastly;
+pub mod feature_flags;
+pub mod features;
+pub mod funding;
+pub mod github_cache;
 pub mod headers;
+pub mod http_server;
 pub mod index;
 mod licenses;
+pub mod malware;
 pub mod metrics;
 pub mod middleware;
 pub mod models;
+pub mod oauth;
 pub mod openapi;
+pub mod publish_policy;
+pub mod publish_warnings;
 pub mod rate_limiter;
 mod real_ip;
+pub mod reserved_names;
 mod router;
 pub mod sentry;
 pub mod sqs;
 pub mod ssh;
 pub mod storage;
+pub mod surrogate_keys;
+pub mod tarball_diff;
 pub mod tasks;
 #[cfg(test)]
 pub mod tests;
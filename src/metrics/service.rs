@@ -11,9 +11,12 @@
 //! instance-level metric, and you should add it to `src/metrics/instance.rs`.
 
 use crate::metrics::macros::metrics;
-use crate::schema::{background_jobs, crates, versions};
+use crate::models::{Cdn, StorageDiscrepancyKind};
+use crate::schema::{background_jobs, cdn_invalidations, crates, storage_discrepancies, versions};
 use crate::util::errors::AppResult;
-use diesel::{dsl::count_star, prelude::*};
+use chrono::{DateTime, Utc};
+use diesel::dsl::{count_star, min};
+use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use prometheus::{proto::MetricFamily, IntGauge, IntGaugeVec};
 
@@ -25,6 +28,13 @@ metrics! {
         versions_total: IntGauge,
         /// Number of queued up background jobs
         background_jobs: IntGaugeVec["priority", "job"],
+        /// Number of paths waiting to be invalidated on the CDNs
+        cdn_invalidation_queue_depth: IntGaugeVec["cdn"],
+        /// Number of seconds the oldest path has been waiting to be invalidated on the CDNs
+        cdn_invalidation_queue_age_seconds: IntGaugeVec["cdn"],
+        /// Number of files in the storage that don't match the database, as found by the last
+        /// run of the `ReconcileStorage` job
+        storage_discrepancies: IntGaugeVec["kind"],
     }
 
     // All service metrics will be prefixed with this namespace.
@@ -59,6 +69,49 @@ impl ServiceMetrics {
                 .set(count);
         }
 
+        let cdn_invalidations = cdn_invalidations::table
+            .group_by(cdn_invalidations::cdn)
+            .select((
+                cdn_invalidations::cdn,
+                count_star(),
+                min(cdn_invalidations::queued_at),
+            ))
+            .load::<(Cdn, i64, Option<DateTime<Utc>>)>(conn)
+            .await?;
+
+        self.cdn_invalidation_queue_depth.reset();
+        self.cdn_invalidation_queue_age_seconds.reset();
+        let now = Utc::now();
+        for (cdn, count, oldest) in cdn_invalidations {
+            self.cdn_invalidation_queue_depth
+                .get_metric_with_label_values(&[cdn.name()])?
+                .set(count);
+
+            let age = oldest.map(|oldest| (now - oldest).num_seconds());
+            self.cdn_invalidation_queue_age_seconds
+                .get_metric_with_label_values(&[cdn.name()])?
+                .set(age.unwrap_or_default());
+        }
+
+        let storage_discrepancies = storage_discrepancies::table
+            .group_by(storage_discrepancies::kind)
+            .select((storage_discrepancies::kind, count_star()))
+            .load::<(StorageDiscrepancyKind, i64)>(conn)
+            .await?;
+
+        // All kinds are reported, so that alerts can tell "no discrepancies"
+        // apart from "no data".
+        for kind in StorageDiscrepancyKind::VARIANTS {
+            self.storage_discrepancies
+                .get_metric_with_label_values(&[kind.name()])?
+                .set(0);
+        }
+        for (kind, count) in storage_discrepancies {
+            self.storage_discrepancies
+                .get_metric_with_label_values(&[kind.name()])?
+                .set(count);
+        }
+
         Ok(self.registry.gather())
     }
 }
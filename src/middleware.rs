@@ -1,13 +1,24 @@
+mod abuse_throttle;
+mod api_usage;
 pub mod app;
 mod block_traffic;
 pub mod cargo_compat;
+mod catch_panic;
 mod common_headers;
+mod compression;
+mod cors;
+pub mod csrf;
 mod debug;
 mod ember_html;
+mod limits;
 pub mod log_request;
 pub mod normalize_path;
+mod problem_json;
+mod rate_limit;
+mod read_only_mode;
 pub mod real_ip;
 mod require_user_agent;
+mod security_headers;
 mod static_or_continue;
 mod update_metrics;
 
@@ -19,9 +30,7 @@ use axum_extra::middleware::option_layer;
 use std::time::Duration;
 use tower::layer::util::Identity;
 use tower_http::add_extension::AddExtensionLayer;
-use tower_http::catch_panic::CatchPanicLayer;
-use tower_http::compression::{CompressionLayer, CompressionLevel};
-use tower_http::timeout::{RequestBodyTimeoutLayer, TimeoutLayer};
+use tower_http::timeout::RequestBodyTimeoutLayer;
 
 use crate::app::AppState;
 use crate::Env;
@@ -41,8 +50,8 @@ pub fn apply_axum_middleware(state: AppState, router: Router<()>) -> Router {
         .layer(sentry_tower::NewSentryLayer::new_from_top())
         .layer(sentry_tower::SentryHttpLayer::with_transaction())
         .layer(from_fn(self::real_ip::middleware))
-        .layer(from_fn(log_request::log_requests))
-        .layer(CatchPanicLayer::new())
+        .layer(from_fn_with_state(state.clone(), log_request::log_requests))
+        .layer(catch_panic::layer(state.clone()))
         .layer(from_fn_with_state(
             state.clone(),
             update_metrics::update_metrics,
@@ -54,23 +63,40 @@ pub fn apply_axum_middleware(state: AppState, router: Router<()>) -> Router {
         }));
 
     let middlewares_2 = tower::ServiceBuilder::new()
+        // Added first, so that the responses of the other middleware get the
+        // headers too
+        .layer(from_fn_with_state(
+            state.clone(),
+            security_headers::middleware,
+        ))
+        .layer(from_fn(problem_json::middleware))
         .layer(from_fn_with_state(
             state.config.cargo_compat_status_code_config,
             cargo_compat::middleware,
         ))
+        .layer(from_fn_with_state(state.clone(), cors::middleware))
+        .layer(from_fn_with_state(state.clone(), limits::middleware))
         .layer(from_fn_with_state(
             state.clone(),
-            crates_io_session::attach_session,
+            abuse_throttle::middleware,
         ))
         .layer(from_fn_with_state(
             state.clone(),
-            require_user_agent::require_user_agent,
+            read_only_mode::middleware,
         ))
-        .layer(from_fn_with_state(state.clone(), block_traffic::middleware))
         .layer(from_fn_with_state(
             state.clone(),
-            common_headers::add_common_headers,
+            crates_io_session::attach_session,
+        ))
+        .layer(from_fn(csrf::middleware))
+        .layer(from_fn_with_state(
+            state.clone(),
+            require_user_agent::require_user_agent,
         ))
+        .layer(from_fn_with_state(state.clone(), block_traffic::middleware))
+        .layer(from_fn_with_state(state.clone(), rate_limit::middleware))
+        .layer(from_fn_with_state(state.clone(), api_usage::middleware))
+        .layer(from_fn(common_headers::add_common_headers))
         .layer(conditional_layer(env == Env::Development, || {
             from_fn(static_or_continue::serve_local_uploads)
         }))
@@ -85,9 +111,8 @@ pub fn apply_axum_middleware(state: AppState, router: Router<()>) -> Router {
     router
         .layer(middlewares_2)
         .layer(middlewares_1)
-        .layer(TimeoutLayer::new(Duration::from_secs(30)))
         .layer(RequestBodyTimeoutLayer::new(Duration::from_secs(30)))
-        .layer(CompressionLayer::new().quality(CompressionLevel::Fastest))
+        .layer(compression::layer())
 }
 
 pub fn conditional_layer<L, F: FnOnce() -> L>(condition: bool, layer: F) -> Either<L, Identity> {
@@ -0,0 +1,84 @@
+//! Reject requests of banned or denied IP networks, and ban the networks that
+//! send too many requests or cause too many errors.
+//!
+//! See [crate::abuse_throttle] for more details.
+
+use crate::abuse_throttle::Verdict;
+use crate::app::AppState;
+use crate::middleware::real_ip::RealIp;
+use crate::util::errors::custom_with_code;
+use axum::extract::{Extension, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use http::{header, StatusCode};
+
+pub async fn middleware(
+    Extension(real_ip): Extension<RealIp>,
+    state: AppState,
+    req: Request,
+    next: Next,
+) -> Response {
+    let throttle = &state.abuse_throttle;
+    if !throttle.config().enabled {
+        return next.run(req).await;
+    }
+
+    // Only one request per interval waits for the refresh, so that the rules
+    // are always loaded before the first verdict. The refresh runs in its own
+    // task, so that it completes even if this request is cancelled.
+    if throttle.start_refresh() {
+        let state = state.clone();
+        let refresh = tokio::spawn(async move { state.abuse_throttle.refresh(&state).await });
+        if let Ok(Err(error)) = refresh.await {
+            warn!("Failed to refresh IP access rules and bans: {error}");
+        }
+    }
+
+    match throttle.verdict(*real_ip) {
+        Verdict::Allowed => {}
+        Verdict::Exempt => return next.run(req).await,
+        Verdict::Denied => {
+            let detail = "Access to the crates.io API has been denied for your network. \
+                Please email help@crates.io if you think this is a mistake.";
+
+            return custom_with_code(StatusCode::FORBIDDEN, "ip-denied", detail).into_response();
+        }
+        Verdict::Banned { until } => {
+            let retry_after = (until - Utc::now()).num_seconds().max(1);
+            let detail = format!(
+                "Your network has been temporarily banned because of too many requests or \
+                errors. Please try again in {retry_after} seconds or email help@crates.io \
+                if you think this is a mistake."
+            );
+
+            let error = custom_with_code(StatusCode::TOO_MANY_REQUESTS, "ip-banned", detail);
+            let mut response = error.into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.into());
+            return response;
+        }
+    }
+
+    let response = next.run(req).await;
+
+    if let Some((subnet, reason)) = throttle.record(*real_ip, response.status()) {
+        warn!(%subnet, reason, "Banning IP network because of abusive traffic");
+
+        let state = state.clone();
+        tokio::spawn(async move {
+            let result = async {
+                let mut conn = state.db_write().await?;
+                state.abuse_throttle.ban(subnet, reason, &mut conn).await?;
+                Ok::<_, anyhow::Error>(())
+            };
+
+            if let Err(error) = result.await {
+                warn!(%subnet, "Failed to persist IP ban: {error}");
+            }
+        });
+    }
+
+    response
+}
@@ -0,0 +1,82 @@
+//! Count the successful read requests of every crate.
+//!
+//! See [crate::api_usage] for more details.
+
+use crate::api_usage::CrateUsageKind;
+use crate::app::AppState;
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use http::Method;
+
+const CRATE_ROUTE_PREFIX: &str = "/api/v1/crates/{name}";
+const DOWNLOAD_ROUTE: &str = "/api/v1/crates/{name}/{version}/download";
+
+pub async fn middleware(
+    state: AppState,
+    matched_path: Option<MatchedPath>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let usage = matched_path
+        .as_ref()
+        .filter(|_| req.method() == Method::GET)
+        .and_then(|matched_path| crate_usage_kind(matched_path.as_str()))
+        .and_then(|kind| Some((crate_name(req.uri().path())?.to_string(), kind)));
+
+    let response = next.run(req).await;
+
+    // Redirects count too, since the download endpoint redirects to the CDN.
+    let status = response.status();
+    if let Some((name, kind)) = usage {
+        if !status.is_client_error() && !status.is_server_error() {
+            state.api_usage.record_crate(&name, kind);
+        }
+    }
+
+    response
+}
+
+fn crate_usage_kind(route: &str) -> Option<CrateUsageKind> {
+    if route == DOWNLOAD_ROUTE {
+        return Some(CrateUsageKind::Download);
+    }
+
+    let rest = route.strip_prefix(CRATE_ROUTE_PREFIX)?;
+    (rest.is_empty() || rest.starts_with('/')).then_some(CrateUsageKind::Metadata)
+}
+
+/// Returns the `{name}` segment of a `/api/v1/crates/{name}/...` path.
+fn crate_name(path: &str) -> Option<&str> {
+    path.strip_prefix("/api/v1/crates/")?.split('/').next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crate_usage_kind() {
+        let kind = crate_usage_kind("/api/v1/crates/{name}/{version}/download");
+        assert_eq!(kind, Some(CrateUsageKind::Download));
+
+        let kind = crate_usage_kind("/api/v1/crates/{name}");
+        assert_eq!(kind, Some(CrateUsageKind::Metadata));
+        let kind = crate_usage_kind("/api/v1/crates/{name}/{version}/readme");
+        assert_eq!(kind, Some(CrateUsageKind::Metadata));
+
+        assert_eq!(crate_usage_kind("/api/v1/crates"), None);
+        assert_eq!(crate_usage_kind("/api/v1/crates/new"), None);
+        assert_eq!(crate_usage_kind("/api/v1/me/usage"), None);
+    }
+
+    #[test]
+    fn test_crate_name() {
+        assert_eq!(crate_name("/api/v1/crates/serde"), Some("serde"));
+        assert_eq!(
+            crate_name("/api/v1/crates/serde/1.0.0/download"),
+            Some("serde")
+        );
+        assert_eq!(crate_name("/api/v1/me"), None);
+    }
+}
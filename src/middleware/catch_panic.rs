@@ -0,0 +1,89 @@
+//! Convert panics in request handlers into `500 Internal Server Error`
+//! responses, instead of closing the connection.
+
+use crate::app::AppState;
+use crate::middleware::log_request::ErrorField;
+use axum::body::Body;
+use axum::response::IntoResponse;
+use axum::Extension;
+use axum_extra::json;
+use http::{Response, StatusCode};
+use std::any::Any;
+use tower_http::catch_panic::{CatchPanicLayer, ResponseForPanic};
+
+pub fn layer(state: AppState) -> CatchPanicLayer<PanicHandler> {
+    CatchPanicLayer::custom(PanicHandler { state })
+}
+
+#[derive(Clone)]
+pub struct PanicHandler {
+    state: AppState,
+}
+
+impl ResponseForPanic for PanicHandler {
+    type ResponseBody = Body;
+
+    fn response_for_panic(&mut self, err: Box<dyn Any + Send + 'static>) -> Response<Body> {
+        let message = panic_message(&*err);
+        error!(panic.message = %message, "Request handler panicked");
+
+        self.state.instance_metrics.panics_total.inc();
+
+        let json = json!({ "errors": [{ "detail": "Internal Server Error" }] });
+        let error = ErrorField(format!("panic: {message}"));
+        (StatusCode::INTERNAL_SERVER_ERROR, Extension(error), json).into_response()
+    }
+}
+
+/// Extracts the message from the payload of a panic, which is a `&str` or a
+/// `String` for panics that were raised via `panic!()`.
+fn panic_message(err: &(dyn Any + Send)) -> &str {
+    if let Some(message) = err.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = err.downcast_ref::<String>() {
+        message
+    } else {
+        "<unknown panic payload>"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::util::TestApp;
+    use axum::routing::get;
+    use axum::Router;
+    use http::Request;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_panic_message() {
+        assert_eq!(panic_message(&"foo"), "foo");
+        assert_eq!(panic_message(&String::from("bar")), "bar");
+        assert_eq!(panic_message(&42), "<unknown panic payload>");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_panicking_handler() {
+        let (app, _) = TestApp::init().empty().await;
+        let state = AppState(app.app_arc());
+
+        let router = Router::new()
+            .route("/panic", get(|| async { panic!("oh no") }))
+            .layer(layer(state));
+
+        let request = Request::get("/panic").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let error = response.extensions().get::<ErrorField>().unwrap();
+        assert_eq!(error.0, "panic: oh no");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await;
+        let body = body.unwrap();
+        assert_eq!(body, r#"{"errors":[{"detail":"Internal Server Error"}]}"#);
+
+        let metrics = &app.as_inner().instance_metrics;
+        assert_eq!(metrics.panics_total.get(), 1);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::app::AppState;
+use crate::controllers::krate::page::is_crate_page;
 use axum::extract::Request;
 use axum::middleware::Next;
 use axum::response::IntoResponse;
@@ -12,21 +12,16 @@ const NGINX_SUCCESS_CODES: [u16; 10] = [200, 201, 204, 206, 301, 203, 303, 304,
 const ONE_DAY: Duration = Duration::from_secs(24 * 60 * 60);
 const ONE_YEAR: Duration = Duration::from_secs(365 * 24 * 60 * 60);
 
-pub async fn add_common_headers(
-    state: AppState,
-    request: Request,
-    next: Next,
-) -> impl IntoResponse {
+pub async fn add_common_headers(request: Request, next: Next) -> impl IntoResponse {
     let v = HeaderValue::from_static;
 
     let mut headers = HeaderMap::new();
 
     let path = request.uri().path();
 
-    const STATIC_FILES: [&str; 5] = [
+    const STATIC_FILES: [&str; 4] = [
         "/github-redirect.html",
         "/favicon.ico",
-        "/robots.txt",
         "/opensearch.xml",
         "/.well-known/security.txt",
     ];
@@ -38,19 +33,20 @@ pub async fn add_common_headers(
         expires(&mut headers, 10 * ONE_YEAR);
     }
 
-    let response = next.run(request).await;
+    // Crate pages are rendered by the backend or by the frontend depending
+    // on the `User-Agent` of the client, so caches have to key on it too.
+    let vary = if is_crate_page(path) {
+        v("Accept, Cookie, User-Agent")
+    } else {
+        v("Accept, Cookie")
+    };
 
-    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, v("*"));
-    headers.insert(header::STRICT_TRANSPORT_SECURITY, v("max-age=31536000"));
+    let response = next.run(request).await;
 
     if NGINX_SUCCESS_CODES.contains(&response.status().as_u16()) {
-        headers.insert(header::X_CONTENT_TYPE_OPTIONS, v("nosniff"));
-        headers.insert(header::X_FRAME_OPTIONS, v("SAMEORIGIN"));
-        headers.insert(header::X_XSS_PROTECTION, v("0"));
-        if let Some(ref csp) = state.config.content_security_policy {
-            headers.insert(header::CONTENT_SECURITY_POLICY, csp.clone());
-        }
-        headers.insert(header::VARY, v("Accept, Accept-Encoding, Cookie"));
+        // `Accept-Encoding` is added by the compression layer, but only for
+        // responses that can actually be compressed.
+        headers.insert(header::VARY, vary);
     }
 
     (headers, response)
@@ -0,0 +1,168 @@
+//! Compression of response bodies with Brotli, gzip, deflate or zstd,
+//! depending on the `Accept-Encoding` header of the request.
+//!
+//! Only JSON, HTML and other text responses above a size threshold are
+//! compressed. Crate tarballs are already compressed, and redirects to them
+//! have no meaningful body, so they are passed through unchanged.
+//!
+//! The `Vary: Accept-Encoding` header is added by the compression layer to
+//! all responses that would have been compressed for a client that supports
+//! compression.
+
+use axum::body::HttpBody;
+use http::{header, Response};
+use tower_http::compression::predicate::{And, Predicate, SizeAbove};
+use tower_http::compression::{CompressionLayer, CompressionLevel};
+
+/// Responses smaller than this number of bytes are not worth the overhead of
+/// compression.
+const MIN_SIZE: u16 = 1024;
+
+pub fn layer() -> CompressionLayer<And<SizeAbove, CompressibleContentType>> {
+    let predicate = SizeAbove::new(MIN_SIZE).and(CompressibleContentType);
+
+    CompressionLayer::new()
+        .quality(CompressionLevel::Fastest)
+        .compress_when(predicate)
+}
+
+/// Only compress responses with a textual content type, except for redirects.
+#[derive(Clone, Copy, Debug)]
+pub struct CompressibleContentType;
+
+impl Predicate for CompressibleContentType {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        if response.status().is_redirection() {
+            return false;
+        }
+
+        response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(is_compressible)
+    }
+}
+
+fn is_compressible(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    let essence = essence.to_ascii_lowercase();
+
+    let Some((kind, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+
+    kind == "text"
+        || subtype == "json"
+        || subtype.ends_with("+json")
+        || subtype == "javascript"
+        || subtype == "xml"
+        || subtype.ends_with("+xml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::response::{IntoResponse, Redirect};
+    use axum::routing::get;
+    use axum::Router;
+    use http::{HeaderMap, Request, StatusCode};
+    use tower::ServiceExt;
+
+    fn large_body() -> String {
+        "crates.io ".repeat(1000)
+    }
+
+    fn build_app() -> Router {
+        let json = || async {
+            let json = serde_json::json!({ "readme": large_body() });
+            axum::Json(json).into_response()
+        };
+        let small_json = || async { axum::Json(serde_json::json!({ "ok": true })) };
+        let html = || async { axum::response::Html(large_body()) };
+        let tarball = || async {
+            let headers = [(header::CONTENT_TYPE, "application/gzip")];
+            (headers, large_body())
+        };
+        let redirect = || async { Redirect::temporary("https://static.crates.io/foo.crate") };
+
+        Router::new()
+            .route("/json", get(json))
+            .route("/small_json", get(small_json))
+            .route("/html", get(html))
+            .route("/tarball", get(tarball))
+            .route("/redirect", get(redirect))
+            .layer(layer())
+    }
+
+    async fn request(path: &str, accept_encoding: &str) -> (StatusCode, HeaderMap) {
+        let request = Request::get(path)
+            .header(header::ACCEPT_ENCODING, accept_encoding)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = build_app().oneshot(request).await.unwrap();
+        (response.status(), response.headers().clone())
+    }
+
+    fn encoding(headers: &HeaderMap) -> Option<&str> {
+        let encoding = headers.get(header::CONTENT_ENCODING)?;
+        Some(encoding.to_str().unwrap())
+    }
+
+    fn varies_by_encoding(headers: &HeaderMap) -> bool {
+        headers
+            .get_all(header::VARY)
+            .iter()
+            .any(|vary| vary == "accept-encoding")
+    }
+
+    #[tokio::test]
+    async fn test_compressible_responses() {
+        let (status, headers) = request("/json", "br").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(encoding(&headers), Some("br"));
+        assert!(varies_by_encoding(&headers));
+
+        let (_, headers) = request("/json", "gzip").await;
+        assert_eq!(encoding(&headers), Some("gzip"));
+
+        let (_, headers) = request("/html", "gzip").await;
+        assert_eq!(encoding(&headers), Some("gzip"));
+
+        let (_, headers) = request("/json", "identity").await;
+        assert_eq!(encoding(&headers), None);
+        assert!(varies_by_encoding(&headers));
+    }
+
+    #[tokio::test]
+    async fn test_skipped_responses() {
+        let (_, headers) = request("/small_json", "br, gzip").await;
+        assert_eq!(encoding(&headers), None);
+        assert!(!varies_by_encoding(&headers));
+
+        let (_, headers) = request("/tarball", "br, gzip").await;
+        assert_eq!(encoding(&headers), None);
+
+        let (status, headers) = request("/redirect", "br, gzip").await;
+        assert_eq!(status, StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(encoding(&headers), None);
+    }
+
+    #[test]
+    fn test_is_compressible() {
+        assert!(is_compressible("application/json"));
+        assert!(is_compressible("application/problem+json"));
+        assert!(is_compressible("text/html; charset=utf-8"));
+        assert!(is_compressible("Text/Plain"));
+        assert!(is_compressible("application/javascript"));
+        assert!(!is_compressible("application/gzip"));
+        assert!(!is_compressible("application/octet-stream"));
+        assert!(!is_compressible("image/png"));
+        assert!(!is_compressible("invalid"));
+    }
+}
@@ -0,0 +1,178 @@
+//! Cross-origin resource sharing (CORS) policies of the different routes.
+//!
+//! - The public read API can be used from any origin, but without
+//!   credentials.
+//! - The sparse index and the git index can be fetched from any origin, and
+//!   their caching headers are exposed to scripts.
+//! - Endpoints that can be authenticated, i.e. the private API and all
+//!   mutating requests, are only available to the origins in
+//!   `WEB_ALLOWED_ORIGINS`, with credentials.
+//!
+//! Preflight requests are answered directly by this middleware, without
+//! running the inner middleware or the request handlers.
+
+use crate::app::AppState;
+use crate::config::AllowedOrigins;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+
+/// Browsers cache the result of a preflight request for at most this
+/// number of seconds.
+const MAX_AGE: &str = "86400";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Policy {
+    /// Any origin, read-only, without credentials.
+    Public,
+    /// Any origin, read-only, exposing the caching headers.
+    Index,
+    /// Only the allowed origins, with credentials.
+    Authenticated,
+}
+
+impl Policy {
+    fn for_request(method: &Method, path: &str) -> Self {
+        if path.starts_with("/index/") || path.starts_with("/git/index/") {
+            Policy::Index
+        } else if path.starts_with("/api/private/")
+            || path == "/api/v1/me"
+            || path.starts_with("/api/v1/me/")
+            || !matches!(*method, Method::GET | Method::HEAD)
+        {
+            Policy::Authenticated
+        } else {
+            Policy::Public
+        }
+    }
+}
+
+pub async fn middleware(state: AppState, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let origin = request.headers().get(header::ORIGIN).cloned();
+    let allowed_origins = &state.config.allowed_origins;
+
+    if let Some(method) = preflight_method(&request) {
+        let policy = Policy::for_request(&method, path);
+        return preflight(policy, origin.as_ref(), allowed_origins);
+    }
+
+    let policy = Policy::for_request(request.method(), path);
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.remove(header::ACCESS_CONTROL_ALLOW_ORIGIN);
+    add_origin_headers(headers, policy, origin.as_ref(), allowed_origins);
+
+    if policy == Policy::Index {
+        headers.insert(
+            header::ACCESS_CONTROL_EXPOSE_HEADERS,
+            HeaderValue::from_static("ETag, Last-Modified"),
+        );
+    }
+
+    response
+}
+
+/// Returns the requested method if the request is a CORS preflight request.
+fn preflight_method(request: &Request) -> Option<Method> {
+    if request.method() != Method::OPTIONS || !request.headers().contains_key(header::ORIGIN) {
+        return None;
+    }
+
+    let method = request
+        .headers()
+        .get(header::ACCESS_CONTROL_REQUEST_METHOD)?;
+
+    Method::from_bytes(method.as_bytes()).ok()
+}
+
+fn preflight(
+    policy: Policy,
+    origin: Option<&HeaderValue>,
+    allowed_origins: &AllowedOrigins,
+) -> Response {
+    let v = HeaderValue::from_static;
+
+    let mut headers = HeaderMap::new();
+    if add_origin_headers(&mut headers, policy, origin, allowed_origins) {
+        let (methods, allowed_headers) = match policy {
+            Policy::Public | Policy::Index => ("GET, HEAD", "*"),
+            Policy::Authenticated => (
+                "GET, HEAD, POST, PUT, PATCH, DELETE",
+                "Authorization, Content-Type, X-CSRF-Token",
+            ),
+        };
+
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, v(methods));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, v(allowed_headers));
+        headers.insert(header::ACCESS_CONTROL_MAX_AGE, v(MAX_AGE));
+    }
+
+    (StatusCode::NO_CONTENT, headers).into_response()
+}
+
+/// Adds the `Access-Control-Allow-Origin` header, and the headers that go
+/// along with it, if the origin may access the resource.
+///
+/// Returns `false` if the origin is not allowed.
+fn add_origin_headers(
+    headers: &mut HeaderMap,
+    policy: Policy,
+    origin: Option<&HeaderValue>,
+    allowed_origins: &AllowedOrigins,
+) -> bool {
+    match policy {
+        Policy::Public | Policy::Index => {
+            let any = HeaderValue::from_static("*");
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, any);
+            true
+        }
+        Policy::Authenticated => {
+            // The response depends on the origin, so it must not be served
+            // from a cache to requests from other origins.
+            headers.append(header::VARY, HeaderValue::from_static("Origin"));
+
+            let Some(origin) = origin.filter(|origin| allowed_origins.contains(origin)) else {
+                return false;
+            };
+
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_for_request() {
+        let policy = |method, path| Policy::for_request(&method, path);
+
+        assert_eq!(policy(Method::GET, "/api/v1/crates"), Policy::Public);
+        assert_eq!(policy(Method::HEAD, "/api/v1/crates/foo"), Policy::Public);
+        assert_eq!(policy(Method::GET, "/api/v1/me"), Policy::Authenticated);
+        assert_eq!(
+            policy(Method::GET, "/api/v1/me/updates"),
+            Policy::Authenticated
+        );
+        assert_eq!(policy(Method::GET, "/api/v1/meta"), Policy::Public);
+        assert_eq!(
+            policy(Method::PUT, "/api/v1/crates/new"),
+            Policy::Authenticated
+        );
+        assert_eq!(
+            policy(Method::GET, "/api/private/admin/features"),
+            Policy::Authenticated
+        );
+        assert_eq!(policy(Method::GET, "/index/3/s/syn"), Policy::Index);
+        assert_eq!(policy(Method::GET, "/git/index/HEAD"), Policy::Index);
+    }
+}
@@ -0,0 +1,143 @@
+//! Double-submit CSRF tokens for requests that are authenticated by the
+//! session cookie.
+//!
+//! Every session of a logged-in user holds a random token, which is also sent
+//! to the browser in the `csrf_token` cookie. Unlike the session cookie, this
+//! cookie can be read by the frontend, which sends the token back in the
+//! `X-CSRF-Token` header of mutating requests. Other sites can make a browser
+//! send the cookies of crates.io, but they can't read them, so they are not
+//! able to provide the header.
+//!
+//! The header is checked by [`verify()`] when a mutating request is
+//! authenticated by the session cookie. Requests that are authenticated by an
+//! API token don't need it.
+
+use crate::util::errors::{custom_with_code, AppResult, BoxedAppError};
+use crate::util::token::{constant_time_eq, generate_secure_alphanumeric_string};
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use cookie::time::Duration;
+use cookie::{Cookie, SameSite};
+use crates_io_session::SessionExtension;
+use http::request::Parts;
+use http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+
+/// Key of the token in the session data.
+pub const SESSION_KEY: &str = "csrf_token";
+
+/// Name of the cookie that exposes the token to the frontend.
+pub const COOKIE_NAME: &str = "csrf_token";
+
+/// Header that has to contain the token on mutating requests.
+pub static HEADER: HeaderName = HeaderName::from_static("x-csrf-token");
+
+const TOKEN_LENGTH: usize = 32;
+const MAX_AGE_DAYS: i64 = 90;
+
+/// Stores a new token in the session, e.g. when the user logs in.
+pub fn issue_token(session: &SessionExtension) {
+    let token = generate_secure_alphanumeric_string(TOKEN_LENGTH);
+    session.insert(SESSION_KEY.to_string(), token);
+}
+
+/// Checks that a request authenticated by the session cookie contains the
+/// token of the session, unless the request can't modify any data.
+pub fn verify(parts: &Parts, session: &SessionExtension) -> AppResult<()> {
+    if parts.method.is_safe() {
+        return Ok(());
+    }
+
+    let Some(provided) = parts.headers.get(&HEADER) else {
+        return Err(missing_token());
+    };
+
+    let expected = session.get(SESSION_KEY);
+    let is_valid =
+        expected.is_some_and(|expected| constant_time_eq(expected.as_bytes(), provided.as_bytes()));
+
+    if !is_valid {
+        return Err(invalid_token());
+    }
+
+    Ok(())
+}
+
+fn missing_token() -> BoxedAppError {
+    let detail =
+        "this action requires the `X-CSRF-Token` header when authenticated by a session cookie";
+    custom_with_code(StatusCode::FORBIDDEN, "csrf-token-missing", detail)
+}
+
+fn invalid_token() -> BoxedAppError {
+    let detail = "the `X-CSRF-Token` header does not match the token of the session";
+    custom_with_code(StatusCode::FORBIDDEN, "csrf-token-invalid", detail)
+}
+
+/// Keeps the `csrf_token` cookie in sync with the token of the session.
+///
+/// Sessions of users that logged in before CSRF tokens were introduced are
+/// issued a token on their next request.
+pub async fn middleware(req: Request, next: Next) -> Response {
+    let session = req.extensions().get::<SessionExtension>().cloned();
+    let cookie_token = cookie_token(req.headers());
+
+    let mut response = next.run(req).await;
+
+    // The session is inspected after the request handler, since logging in
+    // and out changes the token.
+    let Some(session) = session else {
+        return response;
+    };
+
+    if session.get(SESSION_KEY).is_none() && session.get("user_id").is_some() {
+        issue_token(&session);
+    }
+
+    let (value, max_age) = match session.get(SESSION_KEY) {
+        Some(token) if cookie_token.as_ref() != Some(&token) => {
+            (token, Duration::days(MAX_AGE_DAYS))
+        }
+        // The user has logged out, so the cookie is removed
+        None if cookie_token.is_some() => (String::new(), Duration::ZERO),
+        _ => return response,
+    };
+
+    let cookie = Cookie::build((COOKIE_NAME, value))
+        .secure(true)
+        .same_site(SameSite::Strict)
+        .max_age(max_age)
+        .path("/");
+
+    if let Ok(value) = HeaderValue::try_from(cookie.to_string()) {
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+
+    response
+}
+
+fn cookie_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(Cookie::split_parse)
+        .filter_map(Result::ok)
+        .find(|cookie| cookie.name() == COOKIE_NAME)
+        .map(|cookie| cookie.value().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookie_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(cookie_token(&headers), None);
+
+        let cookies = "cargo_session=foo; csrf_token=bar";
+        headers.insert(header::COOKIE, HeaderValue::from_static(cookies));
+        assert_eq!(cookie_token(&headers).as_deref(), Some("bar"));
+    }
+}
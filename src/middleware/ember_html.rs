@@ -4,34 +4,56 @@
 //! as normal. Requests not intended for the backend will be served HTML to boot the Ember.js
 //! frontend.
 //!
+//! Crate pages are rendered by the backend for clients that are unlikely to run the frontend, like
+//! link preview bots, so that they get the name and description of the crate.
+//!
 //! For now, there is an additional check to see if the `Accept` header contains "html". This is
 //! likely to be removed in the future.
 
+use crate::controllers::health::HEALTH_CHECK_PATHS;
+use crate::controllers::krate::page::{is_crate_page, prefers_rendered_page};
+use crate::controllers::robots::CRAWLER_POLICY_PATHS;
 use axum::extract::Request;
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
-use http::{header, StatusCode};
+use http::{header, HeaderValue, StatusCode};
 use tower::ServiceExt;
 use tower_http::services::ServeFile;
 
 pub async fn serve_html(request: Request, next: Next) -> Response {
     let path = &request.uri().path();
 
-    // The "/git/" prefix is only used in development (when within a docker container)
-    if path.starts_with("/api/") || path.starts_with("/git/") {
-        next.run(request).await
-    } else if request
+    let accepts_html = request
         .headers()
         .get_all(header::ACCEPT)
         .iter()
-        .any(|val| val.to_str().unwrap_or_default().contains("html"))
+        .any(|val| val.to_str().unwrap_or_default().contains("html"));
+
+    // The "/git/" prefix is only used in development (when within a docker container)
+    if path.starts_with("/api/")
+        || path.starts_with("/git/")
+        || HEALTH_CHECK_PATHS.contains(path)
+        || CRAWLER_POLICY_PATHS.contains(path)
+        || (is_crate_page(path) && prefers_rendered_page(request.headers(), accepts_html))
     {
+        next.run(request).await
+    } else if accepts_html {
+        let is_crate_page = is_crate_page(path);
+
         // Serve static Ember page to bootstrap the frontend
-        ServeFile::new("dist/index.html")
+        let mut response = ServeFile::new("dist/index.html")
             .oneshot(request)
             .await
             .map(|response| response.map(axum::body::Body::new))
-            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
+
+        // Other clients get a page that is rendered by the backend instead.
+        if is_crate_page {
+            let vary = HeaderValue::from_static("User-Agent");
+            response.headers_mut().append(header::VARY, vary);
+        }
+
+        response
     } else {
         // Return a 404 to crawlers that don't send `Accept: text/hml`.
         // This is to preserve legacy behavior and will likely change.
@@ -0,0 +1,106 @@
+//! Enforce request body size limits and request timeouts.
+//!
+//! The publish endpoint uses its own set of limits, since it has to accept
+//! large `.crate` files and might take a while to process them. All other
+//! routes only accept small request bodies.
+//!
+//! The request timeout is also attached to the request as a [`Deadline`], so
+//! that blocking closures and database queries of the request don't keep
+//! running after the client has been sent the timeout error.
+
+use crate::app::AppState;
+use crate::deadline::Deadline;
+use crate::util::errors::{payload_too_large, request_timeout};
+use axum::body::{Body, HttpBody};
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::{header, Method, StatusCode};
+use http_body_util::Limited;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RouteGroup {
+    /// `PUT /api/v1/crates/new`
+    ///
+    /// The size of the request body is not limited here, since the publish
+    /// endpoint enforces the upload size limits of the individual crates
+    /// itself while reading the body.
+    Publish,
+    /// All other routes
+    Default,
+}
+
+impl RouteGroup {
+    fn for_request(method: &Method, path: &str) -> Self {
+        if method == Method::PUT && path == "/api/v1/crates/new" {
+            RouteGroup::Publish
+        } else {
+            RouteGroup::Default
+        }
+    }
+}
+
+pub async fn middleware(state: AppState, req: Request, next: Next) -> Response {
+    let config = &state.config;
+
+    let group = RouteGroup::for_request(req.method(), req.uri().path());
+    let (body_limit, timeout): (Option<usize>, Duration) = match group {
+        RouteGroup::Publish => (None, config.publish_request_timeout),
+        RouteGroup::Default => (Some(config.request_body_limit), config.request_timeout),
+    };
+
+    let mut req = match body_limit {
+        Some(limit) => {
+            // Reject the request early if the `Content-Length` header already
+            // tells us that the body is too large.
+            if req.body().size_hint().lower() > limit as u64 {
+                return payload_too_large(limit).into_response();
+            }
+
+            req.map(|body| Body::new(Limited::new(body, limit)))
+        }
+        None => req,
+    };
+
+    let deadline = Deadline::after(timeout);
+    req.extensions_mut().insert(deadline);
+
+    let response = tokio::time::timeout_at(deadline.instant(), next.run(req));
+    match (deadline.scope(response).await, body_limit) {
+        // Bodies without a `Content-Length` header only hit the limit while
+        // they are being read by the extractors, which respond with a plain
+        // text error.
+        (Ok(response), Some(limit)) if is_plain_payload_too_large(&response) => {
+            payload_too_large(limit).into_response()
+        }
+        (Ok(response), _) => response,
+        (Err(_), _) => request_timeout().into_response(),
+    }
+}
+
+fn is_plain_payload_too_large(response: &Response) -> bool {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+
+    response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_groups() {
+        let group = RouteGroup::for_request(&Method::PUT, "/api/v1/crates/new");
+        assert_eq!(group, RouteGroup::Publish);
+
+        let group = RouteGroup::for_request(&Method::GET, "/api/v1/crates/new");
+        assert_eq!(group, RouteGroup::Default);
+
+        let group = RouteGroup::for_request(&Method::PUT, "/api/v1/crates/foo/owners");
+        assert_eq!(group, RouteGroup::Default);
+    }
+}
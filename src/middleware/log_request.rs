@@ -1,10 +1,21 @@
 //! Log all requests in a format similar to Heroku's router, but with additional
 //! information that we care about like User-Agent
-
+//!
+//! The request log events have structured fields for the route template, the
+//! status code, the latency, the hashed user ID and the Sentry trace ID, so
+//! that they can be queried when the logs are written as JSON (see
+//! `RUST_LOG_FORMAT`). All values that may contain user input are redacted by
+//! [`redact()`] first.
+//!
+//! Successful requests are only logged at the `REQUEST_LOG_SAMPLE_RATE`, while
+//! all other requests are always logged.
+
+use crate::app::AppState;
 use crate::controllers::util::RequestPartsExt;
 use crate::headers::XRequestId;
 use crate::middleware::normalize_path::OriginalPath;
 use crate::middleware::real_ip::RealIp;
+use crate::util::redact::redact;
 use axum::extract::{MatchedPath, Request};
 use axum::middleware::Next;
 use axum::response::IntoResponse;
@@ -12,8 +23,9 @@ use axum::Extension;
 use axum_extra::headers::UserAgent;
 use axum_extra::TypedHeader;
 use derive_more::Deref;
-use http::{Method, Uri};
+use http::{Method, StatusCode, Uri};
 use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Display;
@@ -39,6 +51,7 @@ pub struct RequestMetadata {
 }
 
 pub async fn log_requests(
+    state: AppState,
     request_metadata: RequestMetadata,
     mut req: Request,
     next: Next,
@@ -66,17 +79,42 @@ pub async fn log_requests(
         .unwrap_or_default();
 
     let status = response.status();
+    if !should_log(status, state.config.request_log_sample_rate) {
+        return response;
+    }
 
+    let url = redact(&url);
+
+    let mut user_id_hash = String::new();
     let custom_metadata = {
         let metadata = custom_metadata.lock();
-        let metadata = metadata.iter().cloned().collect::<HashMap<&str, String>>();
+        let metadata = metadata
+            .iter()
+            .filter_map(|(key, value)| match *key {
+                "uid" => {
+                    user_id_hash = hash_user_id(&state, value);
+                    None
+                }
+                _ => Some((*key, redact(value))),
+            })
+            .collect::<HashMap<&str, Cow<'_, str>>>();
         serde_json::to_string(&metadata).unwrap_or_default()
     };
 
+    let trace_id = sentry::configure_scope(|scope| scope.get_span())
+        .map(|span| span.get_trace_context().trace_id.to_string())
+        .unwrap_or_default();
+
+    let cause = response.extensions().get::<CauseField>();
+    let cause = cause.map(|e| redact(&e.0)).unwrap_or_default();
+    let error = response.extensions().get::<ErrorField>();
+    let error = error.map(|e| redact(&e.0)).unwrap_or_default();
+
     event!(
         target: "http",
         Level::INFO,
         duration = duration.as_nanos(),
+        duration_ms = duration.as_millis(),
         network.client.ip = %**request_metadata.real_ip,
         http.method = %method,
         http.url = %url,
@@ -84,8 +122,10 @@ pub async fn log_requests(
         http.request_id = %request_metadata.request_id.as_ref().map(|h| h.as_str()).unwrap_or_default(),
         http.useragent = %request_metadata.user_agent.as_ref().map(|h| h.as_str()).unwrap_or_default(),
         http.status_code = status.as_u16(),
-        cause = response.extensions().get::<CauseField>().map(|e| e.0.as_str()).unwrap_or_default(),
-        error.message = response.extensions().get::<ErrorField>().map(|e| e.0.as_str()).unwrap_or_default(),
+        usr.id_hash = %user_id_hash,
+        trace_id = %trace_id,
+        %cause,
+        error.message = %error,
         %custom_metadata,
         "{method} {url} → {status} ({duration:?})",
     );
@@ -93,6 +133,20 @@ pub async fn log_requests(
     response
 }
 
+/// Successful requests are only logged at the configured sample rate.
+fn should_log(status: StatusCode, sample_rate: f64) -> bool {
+    !status.is_success() || rand::random::<f64>() < sample_rate
+}
+
+/// Hashes the user ID with the session key, so that the requests of a user
+/// can be correlated in the logs without revealing who the user is.
+fn hash_user_id(state: &AppState, user_id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(state.config.session_key.signing());
+    hasher.update(user_id.as_bytes());
+    hex::encode(&hasher.finalize()[..8])
+}
+
 #[derive(Clone, Debug, Deref, Default)]
 pub struct RequestLog(Arc<Mutex<Vec<(&'static str, String)>>>);
 
@@ -101,7 +155,8 @@ impl RequestLog {
         let mut metadata = self.lock();
         metadata.push((key, value.to_string()));
 
-        sentry::configure_scope(|scope| scope.set_extra(key, value.to_string().into()));
+        let value = redact(&value.to_string()).into_owned();
+        sentry::configure_scope(|scope| scope.set_extra(key, value.into()));
     }
 }
 
@@ -116,3 +171,18 @@ impl<T: RequestPartsExt> RequestLogExt for T {
             .expect("Failed to find `RequestLog` request extension")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_log() {
+        assert!(should_log(StatusCode::OK, 1.0));
+        assert!(!should_log(StatusCode::OK, 0.0));
+        assert!(!should_log(StatusCode::NO_CONTENT, 0.0));
+        assert!(should_log(StatusCode::FOUND, 0.0));
+        assert!(should_log(StatusCode::NOT_FOUND, 0.0));
+        assert!(should_log(StatusCode::INTERNAL_SERVER_ERROR, 0.0));
+    }
+}
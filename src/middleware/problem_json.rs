@@ -0,0 +1,193 @@
+//! Convert JSON error responses into RFC 7807 `application/problem+json`
+//! responses for clients that ask for them via the `Accept` header.
+//!
+//! All other clients keep receiving the `{"errors": [{"detail": "..."}]}`
+//! error format, since `cargo` and the frontend depend on it.
+
+use crate::util::errors::ErrorCode;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::{header, HeaderMap, HeaderValue, StatusCode};
+
+const CONTENT_TYPE_PROBLEM_JSON: &str = "application/problem+json";
+
+pub async fn middleware(req: Request, next: Next) -> Response {
+    let wants_problem_json = accepts_problem_json(req.headers());
+
+    let res = next.run(req).await;
+    if !wants_problem_json {
+        return res;
+    }
+
+    let status = res.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return res;
+    }
+
+    let content_type = res.headers().get(header::CONTENT_TYPE);
+    if !matches!(content_type, Some(content_type) if content_type == "application/json") {
+        return res;
+    }
+
+    convert_to_problem_json(res).await.unwrap_or_else(|error| {
+        error!(%error, "Failed to convert response to problem+json");
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    })
+}
+
+/// Checks if `application/problem+json` is one of the media ranges of the
+/// `Accept` header. `*/*` does not count, so that only clients that know about
+/// the format receive it.
+fn accepts_problem_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_range| media_range.split(';').next())
+        .any(|media_type| media_type.trim() == CONTENT_TYPE_PROBLEM_JSON)
+}
+
+async fn convert_to_problem_json(res: Response) -> anyhow::Result<Response> {
+    #[derive(Deserialize)]
+    struct Errors {
+        errors: Vec<ErrorDetail>,
+    }
+
+    #[derive(Deserialize)]
+    struct ErrorDetail {
+        detail: String,
+    }
+
+    #[derive(Serialize)]
+    struct Problem<'a> {
+        r#type: String,
+        title: &'a str,
+        status: u16,
+        detail: String,
+    }
+
+    let (mut parts, body) = res.into_parts();
+
+    let bytes = axum::body::to_bytes(body, 1_000_000).await?;
+    let errors: Errors = serde_json::from_slice(&bytes)?;
+    let detail = errors
+        .errors
+        .into_iter()
+        .map(|error| error.detail)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let code = parts
+        .extensions
+        .get::<ErrorCode>()
+        .map(|code| code.0)
+        .unwrap_or_else(|| status_code(parts.status));
+
+    let problem = Problem {
+        r#type: format!("urn:crates-io:error:{code}"),
+        title: parts.status.canonical_reason().unwrap_or_default(),
+        status: parts.status.as_u16(),
+        detail,
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(CONTENT_TYPE_PROBLEM_JSON),
+    );
+
+    let body = serde_json::to_vec(&problem)?;
+    Ok(Response::from_parts(parts, body.into()))
+}
+
+/// Returns the error code for errors without an explicit [ErrorCode].
+fn status_code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad-request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not-found",
+        StatusCode::METHOD_NOT_ALLOWED => "method-not-allowed",
+        StatusCode::REQUEST_TIMEOUT => "request-timeout",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload-too-large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported-media-type",
+        StatusCode::UNPROCESSABLE_ENTITY => "validation-failed",
+        StatusCode::TOO_MANY_REQUESTS => "rate-limited",
+        StatusCode::SERVICE_UNAVAILABLE => "service-unavailable",
+        status if status.is_server_error() => "internal-server-error",
+        _ => "client-error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::errors::{crate_not_found, not_found, AppResult};
+    use axum::body::Body;
+    use axum::middleware::from_fn;
+    use axum::routing::get;
+    use axum::Router;
+    use bytes::Bytes;
+    use http::response::Parts;
+    use insta::assert_snapshot;
+    use tower::ServiceExt;
+
+    fn build_app() -> Router {
+        async fn crate_error() -> AppResult<()> {
+            Err(crate_not_found("foo"))
+        }
+
+        async fn generic_error() -> AppResult<()> {
+            Err(not_found())
+        }
+
+        Router::new()
+            .route("/api/ok", get(|| async { "Everything is okay" }))
+            .route("/api/crate", get(crate_error))
+            .route("/api/generic", get(generic_error))
+            .layer(from_fn(middleware))
+    }
+
+    async fn request(path: &str, accept: &str) -> anyhow::Result<(Parts, Bytes)> {
+        let request = http::Request::get(path)
+            .header(header::ACCEPT, accept)
+            .body(Body::empty())?;
+        let response = build_app().oneshot(request).await?;
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await?;
+        Ok((parts, bytes))
+    }
+
+    #[tokio::test]
+    async fn test_problem_json() {
+        let accept = "application/problem+json, application/json;q=0.9";
+        let (parts, bytes) = request("/api/crate", accept).await.unwrap();
+        assert_eq!(parts.status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            parts.headers[header::CONTENT_TYPE],
+            CONTENT_TYPE_PROBLEM_JSON
+        );
+        assert_snapshot!(std::str::from_utf8(&bytes).unwrap(), @r#"{"type":"urn:crates-io:error:crate-not-found","title":"Not Found","status":404,"detail":"crate `foo` does not exist"}"#);
+
+        let (parts, bytes) = request("/api/generic", accept).await.unwrap();
+        assert_eq!(parts.status, StatusCode::NOT_FOUND);
+        assert_snapshot!(std::str::from_utf8(&bytes).unwrap(), @r#"{"type":"urn:crates-io:error:not-found","title":"Not Found","status":404,"detail":"Not Found"}"#);
+
+        let (parts, bytes) = request("/api/ok", accept).await.unwrap();
+        assert_eq!(parts.status, StatusCode::OK);
+        assert_snapshot!(std::str::from_utf8(&bytes).unwrap(), @"Everything is okay");
+    }
+
+    #[tokio::test]
+    async fn test_legacy_errors() {
+        for accept in ["application/json", "*/*"] {
+            let (parts, bytes) = request("/api/crate", accept).await.unwrap();
+            assert_eq!(parts.status, StatusCode::NOT_FOUND);
+            assert_eq!(parts.headers[header::CONTENT_TYPE], "application/json");
+            assert_snapshot!(std::str::from_utf8(&bytes).unwrap(), @r#"{"errors":[{"detail":"crate `foo` does not exist"}]}"#);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Rate limit API requests per client and endpoint group, and add the
+//! `RateLimit-*` headers to the responses.
+//!
+//! Clients are identified by the user ID of the session cookie, which is
+//! signed and can't be forged, or otherwise by the IP address of the client.
+//!
+//! Write requests with an `Authorization` header are additionally limited per
+//! token, so that users sharing an IP address (e.g. CI runners) don't exhaust
+//! each other's limits. The token has not been verified at this point, so
+//! these requests are always counted against the IP address of the client as
+//! well. Otherwise clients could bypass the rate limit by sending random
+//! tokens.
+
+use crate::app::AppState;
+use crate::middleware::real_ip::RealIp;
+use crate::rate_limiter::requests::{EndpointGroup, RateLimitStatus};
+use crate::util::errors::custom_with_code;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use crates_io_session::SessionExtension;
+use http::{header, HeaderMap, HeaderName, StatusCode};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+
+static RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+static RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+static RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+pub async fn middleware(state: AppState, req: Request, next: Next) -> Response {
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let Some(group) = EndpointGroup::for_request(req.method(), req.uri().path(), user_agent) else {
+        return next.run(req).await;
+    };
+
+    // Report the most restrictive status of all buckets the request counts
+    // against.
+    let mut status: Option<RateLimitStatus> = None;
+    for key in client_keys(group, &req) {
+        if let Some(key_status) = state.request_rate_limiter.check(group, &key).await {
+            let is_stricter = status.as_ref().is_none_or(|status| {
+                (key_status.exceeded, Reverse(key_status.remaining))
+                    > (status.exceeded, Reverse(status.remaining))
+            });
+            if is_stricter {
+                status = Some(key_status);
+            }
+        }
+    }
+
+    let Some(status) = status else {
+        return next.run(req).await;
+    };
+
+    let mut response = if status.exceeded {
+        state
+            .instance_metrics
+            .rate_limited_requests_total
+            .with_label_values(&[group.as_str()])
+            .inc();
+
+        let detail = format!(
+            "You have sent too many requests in a short period of time. \
+             Please try again in {} seconds.",
+            status.reset.as_secs()
+        );
+
+        let mut response =
+            custom_with_code(StatusCode::TOO_MANY_REQUESTS, "rate-limited", detail).into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, status.reset.as_secs().into());
+        response
+    } else {
+        next.run(req).await
+    };
+
+    add_headers(response.headers_mut(), &status);
+    response
+}
+
+fn client_keys(group: EndpointGroup, req: &Request) -> Vec<String> {
+    if group == EndpointGroup::Write {
+        if let Some(token) = req.headers().get(header::AUTHORIZATION) {
+            let hash = Sha256::digest(token.as_bytes());
+            return vec![format!("token:{}", hex::encode(hash)), ip_key(req)];
+        }
+    }
+
+    let user_id = req
+        .extensions()
+        .get::<SessionExtension>()
+        .and_then(|session| session.get("user_id"));
+    match user_id {
+        Some(user_id) => vec![format!("user:{user_id}")],
+        None => vec![ip_key(req)],
+    }
+}
+
+fn ip_key(req: &Request) -> String {
+    match req.extensions().get::<RealIp>() {
+        Some(real_ip) => format!("ip:{}", **real_ip),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+fn add_headers(headers: &mut HeaderMap, status: &RateLimitStatus) {
+    headers.insert(RATE_LIMIT_LIMIT.clone(), status.limit.into());
+    headers.insert(RATE_LIMIT_REMAINING.clone(), status.remaining.into());
+    headers.insert(RATE_LIMIT_RESET.clone(), status.reset.as_secs().into());
+}
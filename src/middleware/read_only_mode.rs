@@ -0,0 +1,33 @@
+//! Reject mutating API requests with `503 Service Unavailable` while the
+//! read-only mode is enabled, e.g. during database migrations.
+//!
+//! In contrast to the `READ_ONLY_MODE` environment variable, the read-only
+//! mode is a [FeatureFlag], which can be toggled at runtime through the admin
+//! API. The background worker also pauses all jobs while it is enabled.
+
+use crate::app::AppState;
+use crate::models::FeatureFlag;
+use crate::util::errors::read_only_mode;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::Method;
+
+/// Mutating endpoints that remain available in read-only mode, so that
+/// administrators can disable it again, and users can still log out.
+const EXEMPT_PATHS: &[&str] = &["/api/private/admin/read_only_mode", "/api/private/session"];
+
+pub async fn middleware(state: AppState, req: Request, next: Next) -> Response {
+    let is_read = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let path = req.uri().path();
+    if is_read || !path.starts_with("/api/") || EXEMPT_PATHS.contains(&path) {
+        return next.run(req).await;
+    }
+
+    let name = FeatureFlag::READ_ONLY_MODE;
+    if state.feature_flags.is_enabled(&state, name).await {
+        return read_only_mode().into_response();
+    }
+
+    next.run(req).await
+}
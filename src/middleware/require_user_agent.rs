@@ -5,9 +5,11 @@
 //! check, set `WEB_CDN_USER_AGENT` to the empty string.
 //!
 //! Requests to the download endpoint are always allowed, to support versions of cargo older than
-//! 0.17 (released alongside rustc 1.17).
+//! 0.17 (released alongside rustc 1.17). Requests to the health check endpoints are always allowed
+//! too, since load balancers don't necessarily send a user-agent.
 
 use crate::app::AppState;
+use crate::controllers::health::HEALTH_CHECK_PATHS;
 use crate::middleware::log_request::RequestLogExt;
 use axum::extract::Request;
 use axum::middleware::Next;
@@ -31,8 +33,9 @@ pub async fn require_user_agent(
 
     let has_user_agent = !agent.is_empty() && agent != cdn_user_agent;
     let is_download = req.uri().path().ends_with("download");
+    let is_health_check = HEALTH_CHECK_PATHS.contains(&req.uri().path());
 
-    if !has_user_agent && !is_download {
+    if !has_user_agent && !is_download && !is_health_check {
         req.request_log().add("cause", "no user agent");
 
         let request_id = req
@@ -0,0 +1,38 @@
+//! Adds the security headers from the [`SecurityHeadersConfig`] to all
+//! responses.
+//!
+//! [`SecurityHeadersConfig`]: crate::config::SecurityHeadersConfig
+
+use crate::app::AppState;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use http::{header, HeaderValue};
+
+pub async fn middleware(state: AppState, request: Request, next: Next) -> Response {
+    let config = &state.config.security_headers;
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+
+    if let Some(hsts) = &config.strict_transport_security {
+        headers.insert(header::STRICT_TRANSPORT_SECURITY, hsts.clone());
+    }
+
+    let nosniff = HeaderValue::from_static("nosniff");
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, nosniff);
+    headers.insert(header::X_FRAME_OPTIONS, config.frame_options.clone());
+    headers.insert(header::X_XSS_PROTECTION, HeaderValue::from_static("0"));
+    headers.insert(header::REFERRER_POLICY, config.referrer_policy.clone());
+
+    if let Some(csp) = &config.content_security_policy {
+        let name = if config.csp_report_only {
+            header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+        } else {
+            header::CONTENT_SECURITY_POLICY
+        };
+        headers.insert(name, csp.clone());
+    }
+
+    response
+}
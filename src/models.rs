@@ -1,37 +1,110 @@
+pub use self::account_export::AccountExport;
 pub use self::action::{NewVersionOwnerAction, VersionAction, VersionOwnerAction};
+pub use self::announcement::{
+    Announcement, AnnouncementAudience, AnnouncementSeverity, NewAnnouncement,
+};
+pub use self::api_usage::ApiUsageCount;
+pub use self::audit_log::{AuditLogAction, AuditLogEntry, NewAuditLogEntry};
+pub use self::broadcast_warning::{BroadcastWarning, NewBroadcastWarning};
 pub use self::category::{Category, CrateCategory, NewCategory};
+pub use self::cdn_invalidation::{Cdn, CdnInvalidation};
+pub use self::crate_change::{CrateChange, CrateChangeKind};
 pub use self::crate_owner_invitation::{CrateOwnerInvitation, NewCrateOwnerInvitationOutcome};
-pub use self::default_versions::{update_default_version, verify_default_version};
+pub use self::crate_reclaim_request::{
+    CrateReclaimRequest, NewCrateReclaimRequest, ReclaimStatus, RECLAIM_WAITING_PERIOD_DAYS,
+};
+pub use self::crate_redirect::{CrateRedirect, NewCrateRedirect, RedirectStatus};
+pub use self::crate_report::{CrateReport, NewCrateReport, ReportCategory, ReportStatus};
+pub use self::crate_score::CrateScore;
+pub use self::crate_settings::CrateSettings;
+pub use self::crate_transfer::{CrateTransfer, NewCrateTransfer};
+pub use self::db_dump::{DbDump, DbDumpKind, NewDbDump};
+pub use self::default_versions::{update_default_version, verify_default_version, LatestVersions};
 pub use self::deleted_crate::NewDeletedCrate;
 pub use self::dependency::{Dependency, DependencyKind, ReverseDependency};
-pub use self::download::VersionDownload;
+pub use self::download::{AggregateDownload, VersionDownload};
 pub use self::email::{Email, NewEmail};
+pub use self::feature_flag::{FeatureFlag, FeatureOverride, NewFeatureFlag};
+pub use self::featured_crate::{FeaturedCrate, NewFeaturedCrate};
 pub use self::follow::Follow;
+pub use self::funding_link::{FundingLink, FundingPlatform};
+pub use self::ip_access::{IpAccessRule, IpAccessRuleKind, IpBan, NewIpAccessRule};
 pub use self::keyword::{CrateKeyword, Keyword};
 pub use self::krate::{Crate, CrateName, NewCrate, RecentCrateDownloads};
-pub use self::owner::{CrateOwner, Owner, OwnerKind};
+pub use self::linked_identity::{LinkedIdentity, NewLinkedIdentity};
+pub use self::organization::{
+    NewOrganization, NewOrganizationMember, Organization, OrganizationMember, OrganizationRole,
+    ORGANIZATION_OWNER_PREFIX,
+};
+pub use self::owner::{CrateOwner, Owner, OwnerKind, OwnerRole};
+pub use self::publish_idempotency_key::{
+    NewPublishIdempotencyKey, PublishIdempotencyKey, IDEMPOTENCY_KEY_LIFETIME,
+};
+pub use self::publish_policy_rule::{NewPublishPolicyRule, PolicyRuleKind, PublishPolicyRule};
+pub use self::publish_rate_override::PublishRateOverride;
+pub use self::registry_stats::RegistryStats;
+pub use self::reserved_crate_name::{NewReservedCrateName, ReservedCrateName};
+pub use self::reserved_prefix::{NewReservedPrefix, ReservedPrefix};
 pub use self::rights::Rights;
+pub use self::storage_discrepancy::{StorageDiscrepancy, StorageDiscrepancyKind};
 pub use self::team::{NewTeam, Team};
 pub use self::token::{ApiToken, CreatedApiToken};
+pub use self::trending_crate::TrendingWindow;
 pub use self::user::{NewUser, User};
-pub use self::version::{NewVersion, TopVersions, Version};
+pub use self::user_stats::UserStats;
+pub use self::version::{
+    semver_ord, DocsRsStatus, NewVersion, PublishMethod, TopVersions, Version,
+};
+pub use self::version_diff::VersionDiff;
+pub use self::version_files::{VersionFile, VersionFiles};
 
 pub mod helpers;
 
+mod account_export;
 mod action;
+mod announcement;
+mod api_usage;
+mod audit_log;
+mod broadcast_warning;
 pub mod category;
+mod cdn_invalidation;
+mod crate_change;
 mod crate_owner_invitation;
+mod crate_reclaim_request;
+mod crate_redirect;
+mod crate_report;
+mod crate_score;
+mod crate_settings;
+mod crate_transfer;
+mod db_dump;
 pub mod default_versions;
 mod deleted_crate;
 pub mod dependency;
 mod download;
 mod email;
+mod feature_flag;
+mod featured_crate;
 mod follow;
+mod funding_link;
+mod ip_access;
 mod keyword;
 pub mod krate;
+mod linked_identity;
+mod organization;
 mod owner;
+mod publish_idempotency_key;
+mod publish_policy_rule;
+mod publish_rate_override;
+mod registry_stats;
+mod reserved_crate_name;
+mod reserved_prefix;
 mod rights;
+mod storage_discrepancy;
 mod team;
 pub mod token;
+mod trending_crate;
 pub mod user;
+mod user_stats;
 pub mod version;
+mod version_diff;
+mod version_files;
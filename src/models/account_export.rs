@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use secrecy::SecretString;
+
+use crate::models::User;
+use crate::schema::account_exports;
+
+/// The model representing a row in the `account_exports` database table.
+#[derive(Debug, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(belongs_to(User))]
+pub struct AccountExport {
+    pub id: i32,
+    pub user_id: i32,
+    #[diesel(deserialize_as = String)]
+    pub token: SecretString,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl AccountExport {
+    /// Creates a new pending export for the given user.
+    pub async fn insert(conn: &mut AsyncPgConnection, user_id: i32) -> QueryResult<Self> {
+        diesel::insert_into(account_exports::table)
+            .values(account_exports::user_id.eq(user_id))
+            .returning(AccountExport::as_returning())
+            .get_result(conn)
+            .await
+    }
+
+    /// Returns the most recent export of the given user that is either still
+    /// pending or can still be downloaded.
+    pub async fn find_active(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+    ) -> QueryResult<Option<Self>> {
+        account_exports::table
+            .filter(account_exports::user_id.eq(user_id))
+            .filter(
+                account_exports::expires_at
+                    .is_null()
+                    .or(account_exports::expires_at.gt(Utc::now())),
+            )
+            .order(account_exports::created_at.desc())
+            .select(AccountExport::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Returns the downloadable export belonging to the given secret token.
+    pub async fn find_by_token(
+        conn: &mut AsyncPgConnection,
+        token: &str,
+    ) -> QueryResult<Option<Self>> {
+        account_exports::table
+            .filter(account_exports::token.eq(token))
+            .filter(account_exports::completed_at.is_not_null())
+            .filter(account_exports::expires_at.gt(Utc::now()))
+            .select(AccountExport::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed_at.is_some()
+    }
+}
@@ -0,0 +1,133 @@
+use crate::models::OwnerKind;
+use crate::schema::{announcements, api_tokens, crate_owners};
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum AnnouncementSeverity {
+        Info = 0,
+        Warning = 1,
+        Critical = 2,
+    }
+}
+
+pg_enum! {
+    pub enum AnnouncementAudience {
+        // Everyone, including visitors that are not logged in.
+        All = 0,
+        // Users that own at least one crate.
+        CrateOwners = 1,
+        // Users that have at least one active API token.
+        TokenUsers = 2,
+    }
+}
+
+/// An announcement that is shown in a banner on the crates.io website, e.g.
+/// to announce maintenance windows or policy changes.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = announcements, check_for_backend(diesel::pg::Pg))]
+pub struct Announcement {
+    pub id: i32,
+    pub message: String,
+    pub docs_url: Option<String>,
+    pub severity: AnnouncementSeverity,
+    pub audience: AnnouncementAudience,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Announcement {
+    /// Returns the announcements that are currently shown to the given user,
+    /// or to anonymous visitors if `user_id` is `None`, sorted by severity
+    /// and then from newest to oldest.
+    pub async fn active_for(
+        conn: &mut AsyncPgConnection,
+        user_id: Option<i32>,
+    ) -> QueryResult<Vec<Self>> {
+        let audiences = match user_id {
+            Some(user_id) => Self::audiences_of(conn, user_id).await?,
+            None => vec![AnnouncementAudience::All],
+        };
+
+        let now = Utc::now();
+        announcements::table
+            .filter(announcements::starts_at.le(now))
+            .filter(
+                announcements::ends_at
+                    .is_null()
+                    .or(announcements::ends_at.gt(now)),
+            )
+            .filter(announcements::audience.eq_any(audiences))
+            .select(Self::as_select())
+            .order((
+                announcements::severity.desc(),
+                announcements::starts_at.desc(),
+                announcements::id.desc(),
+            ))
+            .load(conn)
+            .await
+    }
+
+    /// Returns the audiences that the given user belongs to.
+    async fn audiences_of(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+    ) -> QueryResult<Vec<AnnouncementAudience>> {
+        let owned_crates = crate_owners::table
+            .filter(crate_owners::owner_id.eq(user_id))
+            .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+            .filter(crate_owners::deleted.eq(false));
+
+        let active_tokens = api_tokens::table
+            .filter(api_tokens::user_id.eq(user_id))
+            .filter(api_tokens::revoked.eq(false))
+            .filter(
+                api_tokens::expired_at
+                    .is_null()
+                    .or(api_tokens::expired_at.gt(Utc::now().naive_utc())),
+            );
+
+        let (is_owner, has_tokens): (bool, bool) =
+            diesel::select((exists(owned_crates), exists(active_tokens)))
+                .get_result(conn)
+                .await?;
+
+        let mut audiences = vec![AnnouncementAudience::All];
+        if is_owner {
+            audiences.push(AnnouncementAudience::CrateOwners);
+        }
+        if has_tokens {
+            audiences.push(AnnouncementAudience::TokenUsers);
+        }
+
+        Ok(audiences)
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = announcements, check_for_backend(diesel::pg::Pg))]
+pub struct NewAnnouncement<'a> {
+    pub message: &'a str,
+    pub docs_url: Option<&'a str>,
+    pub severity: AnnouncementSeverity,
+    pub audience: AnnouncementAudience,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_by: i32,
+}
+
+impl NewAnnouncement<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<Announcement> {
+        diesel::insert_into(announcements::table)
+            .values(self)
+            .returning(Announcement::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
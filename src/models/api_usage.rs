@@ -0,0 +1,55 @@
+use crate::schema::{api_usage_by_crate, api_usage_by_user};
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The number of API requests of one kind on a single day, either of a user
+/// or about a crate.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable)]
+pub struct ApiUsageCount {
+    pub date: NaiveDate,
+    pub kind: String,
+    pub count: i64,
+}
+
+impl ApiUsageCount {
+    /// Returns the requests of the user since the given day, ordered from
+    /// oldest to newest.
+    pub async fn for_user(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+        since: NaiveDate,
+    ) -> QueryResult<Vec<Self>> {
+        api_usage_by_user::table
+            .filter(api_usage_by_user::user_id.eq(user_id))
+            .filter(api_usage_by_user::date.ge(since))
+            .select((
+                api_usage_by_user::date,
+                api_usage_by_user::kind,
+                api_usage_by_user::count,
+            ))
+            .order((api_usage_by_user::date, api_usage_by_user::kind))
+            .load(conn)
+            .await
+    }
+
+    /// Returns the requests about the crate since the given day, ordered from
+    /// oldest to newest.
+    pub async fn for_crate(
+        conn: &mut AsyncPgConnection,
+        crate_id: i32,
+        since: NaiveDate,
+    ) -> QueryResult<Vec<Self>> {
+        api_usage_by_crate::table
+            .filter(api_usage_by_crate::crate_id.eq(crate_id))
+            .filter(api_usage_by_crate::date.ge(since))
+            .select((
+                api_usage_by_crate::date,
+                api_usage_by_crate::kind,
+                api_usage_by_crate::count,
+            ))
+            .order((api_usage_by_crate::date, api_usage_by_crate::kind))
+            .load(conn)
+            .await
+    }
+}
@@ -0,0 +1,74 @@
+use crate::schema::audit_log;
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde_json::Value;
+
+pg_enum! {
+    pub enum AuditLogAction {
+        Publish = 0,
+        Yank = 1,
+        Unyank = 2,
+        OwnerInvite = 3,
+        OwnerAdd = 4,
+        OwnerRemove = 5,
+        TransferRequest = 6,
+        TransferAccept = 7,
+        TokenCreate = 8,
+        TokenRevoke = 9,
+        EmailAdd = 10,
+        EmailRemove = 11,
+        EmailPrimary = 12,
+        AccountLock = 13,
+        AccountUnlock = 14,
+        RateLimitOverride = 15,
+        CrateHide = 16,
+        CrateUnhide = 17,
+        CrateFreeze = 18,
+        CrateUnfreeze = 19,
+        CrateQuarantine = 20,
+        CrateRelease = 21,
+        CrateSettingsUpdate = 22,
+        RepositoryVerify = 23,
+        VersionDelete = 24,
+        VersionRestore = 25,
+    }
+}
+
+/// The model representing a row in the `audit_log` database table.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = audit_log, check_for_backend(diesel::pg::Pg))]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub user_id: Option<i32>,
+    pub api_token_id: Option<i32>,
+    pub crate_id: Option<i32>,
+    pub action: AuditLogAction,
+    pub details: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug, Builder)]
+#[diesel(table_name = audit_log, check_for_backend(diesel::pg::Pg))]
+pub struct NewAuditLogEntry {
+    /// `None` for actions that were taken automatically, without a user.
+    user_id: Option<i32>,
+    api_token_id: Option<i32>,
+    crate_id: Option<i32>,
+    action: AuditLogAction,
+    #[builder(default = Value::Object(Default::default()))]
+    details: Value,
+}
+
+impl NewAuditLogEntry {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::insert_into(audit_log::table)
+            .values(self)
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
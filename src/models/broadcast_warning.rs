@@ -0,0 +1,52 @@
+use crate::schema::broadcast_warnings;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// A warning that is returned to cargo for every publish, e.g. to announce
+/// upcoming changes of crates.io.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = broadcast_warnings, check_for_backend(diesel::pg::Pg))]
+pub struct BroadcastWarning {
+    pub id: i32,
+    pub message: String,
+    pub docs_url: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BroadcastWarning {
+    /// Returns the warnings that have not expired yet, sorted by ID.
+    pub async fn active(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        broadcast_warnings::table
+            .filter(
+                broadcast_warnings::expires_at
+                    .is_null()
+                    .or(broadcast_warnings::expires_at.gt(Utc::now())),
+            )
+            .select(Self::as_select())
+            .order(broadcast_warnings::id)
+            .load(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = broadcast_warnings, check_for_backend(diesel::pg::Pg))]
+pub struct NewBroadcastWarning<'a> {
+    pub message: &'a str,
+    pub docs_url: Option<&'a str>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by: i32,
+}
+
+impl NewBroadcastWarning<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<BroadcastWarning> {
+        diesel::insert_into(broadcast_warnings::table)
+            .values(self)
+            .returning(BroadcastWarning::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -0,0 +1,97 @@
+use crate::schema::cdn_invalidations;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum Cdn {
+        CloudFront = 0,
+        Fastly = 1,
+    }
+}
+
+impl Cdn {
+    pub fn name(self) -> &'static str {
+        match self {
+            Cdn::CloudFront => "cloudfront",
+            Cdn::Fastly => "fastly",
+        }
+    }
+}
+
+/// A path in the queue of paths that are waiting to be invalidated on a CDN.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = cdn_invalidations, check_for_backend(diesel::pg::Pg))]
+pub struct CdnInvalidation {
+    pub cdn: Cdn,
+    pub path: String,
+    pub queued_at: DateTime<Utc>,
+    pub last_queued_at: DateTime<Utc>,
+}
+
+impl CdnInvalidation {
+    /// Adds the paths to the invalidation queue of the CDN.
+    ///
+    /// Paths that are already queued are not added again, so that repeated
+    /// updates of the same file result in a single invalidation.
+    pub async fn queue(
+        conn: &mut AsyncPgConnection,
+        cdn: Cdn,
+        paths: &[&str],
+    ) -> QueryResult<usize> {
+        let now = Utc::now();
+
+        let values = paths
+            .iter()
+            .map(|path| {
+                (
+                    cdn_invalidations::cdn.eq(cdn),
+                    cdn_invalidations::path.eq(*path),
+                    cdn_invalidations::queued_at.eq(now),
+                    cdn_invalidations::last_queued_at.eq(now),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(cdn_invalidations::table)
+            .values(values)
+            .on_conflict((cdn_invalidations::cdn, cdn_invalidations::path))
+            .do_update()
+            .set(cdn_invalidations::last_queued_at.eq(excluded(cdn_invalidations::last_queued_at)))
+            .execute(conn)
+            .await
+    }
+
+    /// Returns the oldest queued paths of the CDN.
+    pub async fn oldest(
+        conn: &mut AsyncPgConnection,
+        cdn: Cdn,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        cdn_invalidations::table
+            .filter(cdn_invalidations::cdn.eq(cdn))
+            .order(cdn_invalidations::queued_at)
+            .limit(limit)
+            .select(Self::as_select())
+            .load(conn)
+            .await
+    }
+
+    /// Removes the paths from the queue, unless they have been queued again
+    /// after their invalidation started at `started_at`.
+    pub async fn dequeue(
+        conn: &mut AsyncPgConnection,
+        cdn: Cdn,
+        paths: &[String],
+        started_at: DateTime<Utc>,
+    ) -> QueryResult<usize> {
+        let query = cdn_invalidations::table
+            .filter(cdn_invalidations::cdn.eq(cdn))
+            .filter(cdn_invalidations::path.eq_any(paths))
+            .filter(cdn_invalidations::last_queued_at.le(started_at));
+
+        diesel::delete(query).execute(conn).await
+    }
+}
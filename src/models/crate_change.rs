@@ -0,0 +1,32 @@
+use crate::schema::crate_changes;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+
+pg_enum! {
+    pub enum CrateChangeKind {
+        CrateCreated = 0,
+        CrateDeleted = 1,
+        VersionPublished = 2,
+        VersionYanked = 3,
+        VersionUnyanked = 4,
+        VersionDeleted = 5,
+        VersionSoftDeleted = 6,
+        VersionRestored = 7,
+    }
+}
+
+/// A row of the crate change feed, which is inserted by database triggers
+/// whenever a crate or version is created, deleted, yanked or unyanked, and
+/// whenever a version is soft-deleted or restored.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = crate_changes, check_for_backend(diesel::pg::Pg))]
+pub struct CrateChange {
+    pub seq: i64,
+    pub kind: CrateChangeKind,
+    pub crate_id: i32,
+    pub crate_name: String,
+    /// `None` for changes of the crate itself.
+    pub version: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
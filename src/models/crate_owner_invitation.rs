@@ -6,7 +6,7 @@ use http::StatusCode;
 use secrecy::SecretString;
 
 use crate::config;
-use crate::models::{CrateOwner, OwnerKind};
+use crate::models::{AuditLogAction, CrateOwner, NewAuditLogEntry, OwnerKind, OwnerRole};
 use crate::schema::{crate_owner_invitations, crate_owners, crates};
 use crate::util::errors::{custom, AppResult};
 
@@ -27,6 +27,7 @@ pub struct CrateOwnerInvitation {
     #[diesel(deserialize_as = String)]
     pub token: SecretString,
     pub token_created_at: Option<NaiveDateTime>,
+    pub role: OwnerRole,
 }
 
 impl CrateOwnerInvitation {
@@ -34,6 +35,7 @@ impl CrateOwnerInvitation {
         invited_user_id: i32,
         invited_by_user_id: i32,
         crate_id: i32,
+        role: OwnerRole,
         conn: &mut AsyncPgConnection,
         config: &config::Server,
     ) -> QueryResult<NewCrateOwnerInvitationOutcome> {
@@ -43,6 +45,7 @@ impl CrateOwnerInvitation {
             invited_user_id: i32,
             invited_by_user_id: i32,
             crate_id: i32,
+            role: OwnerRole,
         }
 
         // Before actually creating the invite, check if an expired invitation already exists
@@ -75,6 +78,7 @@ impl CrateOwnerInvitation {
                 invited_user_id,
                 invited_by_user_id,
                 crate_id,
+                role,
             })
             // The ON CONFLICT DO NOTHING clause results in not creating the invite if another one
             // already exists. This does not cause problems with expired invitation as those are
@@ -142,15 +146,28 @@ impl CrateOwnerInvitation {
                         created_by: self.invited_by_user_id,
                         owner_kind: OwnerKind::User,
                         email_notifications: true,
+                        role: self.role,
                     })
                     .on_conflict(crate_owners::table.primary_key())
                     .do_update()
-                    .set(crate_owners::deleted.eq(false))
+                    .set((
+                        crate_owners::deleted.eq(false),
+                        crate_owners::role.eq(self.role),
+                    ))
                     .execute(conn)
                     .await?;
 
                 diesel::delete(&self).execute(conn).await?;
 
+                NewAuditLogEntry::builder()
+                    .user_id(self.invited_user_id)
+                    .crate_id(self.crate_id)
+                    .action(AuditLogAction::OwnerAdd)
+                    .details(serde_json::json!({ "role": self.role }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
                 Ok(())
             }
             .scope_boxed()
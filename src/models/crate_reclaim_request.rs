@@ -0,0 +1,58 @@
+use crate::schema::crate_reclaim_requests;
+use chrono::{DateTime, TimeDelta, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The number of days that the owners of a crate have to react to a reclaim
+/// request before the crates.io team can approve it.
+pub const RECLAIM_WAITING_PERIOD_DAYS: i64 = 21;
+
+pg_enum! {
+    pub enum ReclaimStatus {
+        Pending = 0,
+        Approved = 1,
+        Rejected = 2,
+    }
+}
+
+/// The model representing a row in the `crate_reclaim_requests` database
+/// table.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate_reclaim_requests, check_for_backend(diesel::pg::Pg))]
+pub struct CrateReclaimRequest {
+    pub id: i32,
+    pub crate_id: i32,
+    pub requester_id: i32,
+    pub reason: String,
+    pub status: ReclaimStatus,
+    pub decision: Option<String>,
+    pub decided_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+impl CrateReclaimRequest {
+    /// The date and time from which on the request can be approved.
+    pub fn eligible_at(&self) -> DateTime<Utc> {
+        self.created_at + TimeDelta::days(RECLAIM_WAITING_PERIOD_DAYS)
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = crate_reclaim_requests, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateReclaimRequest<'a> {
+    pub crate_id: i32,
+    pub requester_id: i32,
+    pub reason: &'a str,
+}
+
+impl NewCrateReclaimRequest<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateReclaimRequest> {
+        diesel::insert_into(crate_reclaim_requests::table)
+            .values(self)
+            .returning(CrateReclaimRequest::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
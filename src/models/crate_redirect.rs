@@ -0,0 +1,71 @@
+use crate::schema::{crate_redirects, crates};
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::{canon_crate_name, pg_enum};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum RedirectStatus {
+        Pending = 0,
+        Approved = 1,
+        Rejected = 2,
+    }
+}
+
+/// A redirect from the name of a renamed or superseded crate to its
+/// successor.
+///
+/// Once approved by an admin, requests to the metadata endpoints of the old
+/// name are redirected to the successor, and the old name can't be
+/// registered again.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate_redirects, check_for_backend(diesel::pg::Pg))]
+pub struct CrateRedirect {
+    pub id: i32,
+    pub name: String,
+    pub target_crate_id: i32,
+    pub requested_by: Option<i32>,
+    pub reason: String,
+    pub status: RedirectStatus,
+    pub decision: Option<String>,
+    pub decided_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+impl CrateRedirect {
+    /// Returns the name of the crate that requests to the given crate name
+    /// are redirected to, if an approved redirect exists.
+    pub async fn target_of(
+        conn: &mut AsyncPgConnection,
+        name: &str,
+    ) -> QueryResult<Option<String>> {
+        crate_redirects::table
+            .inner_join(crates::table)
+            .filter(canon_crate_name(crate_redirects::name).eq(canon_crate_name(name)))
+            .filter(crate_redirects::status.eq(RedirectStatus::Approved))
+            .select(crates::name)
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = crate_redirects, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateRedirect<'a> {
+    pub name: &'a str,
+    pub target_crate_id: i32,
+    pub requested_by: i32,
+    pub reason: &'a str,
+}
+
+impl NewCrateRedirect<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateRedirect> {
+        diesel::insert_into(crate_redirects::table)
+            .values(self)
+            .returning(CrateRedirect::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
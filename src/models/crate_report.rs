@@ -0,0 +1,71 @@
+use crate::schema::crate_reports;
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum ReportCategory {
+        Malware = 0,
+        Typosquat = 1,
+        Spam = 2,
+        Dmca = 3,
+    }
+}
+
+pg_enum! {
+    pub enum ReportStatus {
+        Open = 0,
+        Triaged = 1,
+        Resolved = 2,
+        Dismissed = 3,
+    }
+}
+
+impl ReportStatus {
+    /// Whether the report has been closed, either by resolving or by
+    /// dismissing it.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, ReportStatus::Resolved | ReportStatus::Dismissed)
+    }
+}
+
+/// The model representing a row in the `crate_reports` database table.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate_reports, check_for_backend(diesel::pg::Pg))]
+pub struct CrateReport {
+    pub id: i32,
+    pub crate_id: i32,
+    pub reporter_id: Option<i32>,
+    pub category: ReportCategory,
+    pub description: String,
+    pub contact: Option<String>,
+    pub status: ReportStatus,
+    pub resolution: Option<String>,
+    pub resolved_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub score: Option<i32>,
+}
+
+#[derive(Insertable, Debug, Builder)]
+#[diesel(table_name = crate_reports, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateReport<'a> {
+    crate_id: i32,
+    reporter_id: Option<i32>,
+    category: ReportCategory,
+    description: &'a str,
+    contact: Option<&'a str>,
+    score: Option<i32>,
+}
+
+impl NewCrateReport<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateReport> {
+        diesel::insert_into(crate_reports::table)
+            .values(self)
+            .returning(CrateReport::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -0,0 +1,39 @@
+use crate::schema::crate_scores;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The quality signals of a crate, as calculated by the `UpdateCrateScores`
+/// background job.
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = crate_scores, check_for_backend(diesel::pg::Pg))]
+pub struct CrateScore {
+    pub crate_id: i32,
+    pub has_documentation: bool,
+    pub has_repository: bool,
+    pub has_readme: bool,
+    pub recent_release: bool,
+    pub maintained: bool,
+    /// The share of the versions that are yanked, between 0 and 1.
+    pub yanked_ratio: f64,
+    /// The composite score between 0 and 1.
+    pub score: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CrateScore {
+    /// Returns the signals of the crate, or `None` if they have not been
+    /// calculated yet, e.g. because the crate was published after the last
+    /// run of the background job.
+    pub async fn for_crate(
+        conn: &mut AsyncPgConnection,
+        crate_id: i32,
+    ) -> QueryResult<Option<Self>> {
+        crate_scores::table
+            .find(crate_id)
+            .select(CrateScore::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+}
@@ -0,0 +1,62 @@
+use crate::schema::crate_settings;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The settings of a crate that can be changed by its owners.
+///
+/// Crates without a row in the `crate_settings` table use the
+/// [default settings](CrateSettings::defaults).
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate_settings, check_for_backend(diesel::pg::Pg))]
+pub struct CrateSettings {
+    pub crate_id: i32,
+    /// Whether the crate is included in the weekly digest emails.
+    pub digest_emails: bool,
+    /// Whether the files of new versions are compared to the previous
+    /// release.
+    pub version_diffs: bool,
+    /// Whether the crate is excluded from the default crate search.
+    pub internal: bool,
+}
+
+impl CrateSettings {
+    pub fn defaults(crate_id: i32) -> Self {
+        Self {
+            crate_id,
+            digest_emails: true,
+            version_diffs: true,
+            internal: false,
+        }
+    }
+
+    pub async fn for_crate(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<Self> {
+        let settings = crate_settings::table
+            .find(crate_id)
+            .select(CrateSettings::as_select())
+            .first(conn)
+            .await
+            .optional()?;
+
+        Ok(settings.unwrap_or_else(|| Self::defaults(crate_id)))
+    }
+
+    /// Inserts the settings, or replaces them if the crate already has
+    /// settings.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::insert_into(crate_settings::table)
+            .values(self)
+            .on_conflict(crate_settings::crate_id)
+            .do_update()
+            .set((
+                crate_settings::digest_emails.eq(self.digest_emails),
+                crate_settings::version_diffs.eq(self.version_diffs),
+                crate_settings::internal.eq(self.internal),
+                crate_settings::updated_at.eq(now),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+use crate::config;
+use crate::models::{AuditLogAction, Crate, CrateOwner, NewAuditLogEntry, OwnerKind, OwnerRole};
+use crate::schema::{crate_owner_invitations, crate_owners, crate_transfers};
+
+/// The model representing a row in the `crate_transfers` database table.
+///
+/// Accepted transfers are kept as a record of the ownership change, while
+/// cancelled and declined transfers are deleted.
+#[derive(Clone, Debug, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(belongs_to(Crate))]
+pub struct CrateTransfer {
+    pub id: i32,
+    pub crate_id: i32,
+    pub requested_by: i32,
+    pub recipient_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+}
+
+impl CrateTransfer {
+    /// Returns the pending transfer of the given crate, if there is one.
+    pub async fn find_pending(
+        conn: &mut AsyncPgConnection,
+        crate_id: i32,
+    ) -> QueryResult<Option<Self>> {
+        crate_transfers::table
+            .filter(crate_transfers::crate_id.eq(crate_id))
+            .filter(crate_transfers::accepted_at.is_null())
+            .select(CrateTransfer::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Completes the transfer by removing all existing owners and pending
+    /// owner invitations of the crate, and adding the recipient as the only
+    /// owner with the `admin` role.
+    pub async fn accept(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateTransfer> {
+        conn.transaction(|conn| {
+            async move {
+                diesel::update(crate_owners::table)
+                    .filter(crate_owners::crate_id.eq(self.crate_id))
+                    .set(crate_owners::deleted.eq(true))
+                    .execute(conn)
+                    .await?;
+
+                diesel::delete(crate_owner_invitations::table)
+                    .filter(crate_owner_invitations::crate_id.eq(self.crate_id))
+                    .execute(conn)
+                    .await?;
+
+                diesel::insert_into(crate_owners::table)
+                    .values(&CrateOwner {
+                        crate_id: self.crate_id,
+                        owner_id: self.recipient_id,
+                        created_by: self.requested_by,
+                        owner_kind: OwnerKind::User,
+                        email_notifications: true,
+                        role: OwnerRole::Admin,
+                    })
+                    .on_conflict(crate_owners::table.primary_key())
+                    .do_update()
+                    .set((
+                        crate_owners::deleted.eq(false),
+                        crate_owners::role.eq(OwnerRole::Admin),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                NewAuditLogEntry::builder()
+                    .user_id(self.recipient_id)
+                    .crate_id(self.crate_id)
+                    .action(AuditLogAction::TransferAccept)
+                    .details(serde_json::json!({ "transfer_id": self.id }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                diesel::update(self)
+                    .set(crate_transfers::accepted_at.eq(Utc::now()))
+                    .returning(CrateTransfer::as_returning())
+                    .get_result(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    /// Transfers expire after the same duration as crate owner invitations.
+    pub fn expires_at(&self, config: &config::Server) -> DateTime<Utc> {
+        let days = TimeDelta::days(config.ownership_invitations_expiration_days as i64);
+        self.created_at + days
+    }
+
+    pub fn is_expired(&self, config: &config::Server) -> bool {
+        self.expires_at(config) <= Utc::now()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate_transfers, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateTransfer {
+    pub crate_id: i32,
+    pub requested_by: i32,
+    pub recipient_id: i32,
+}
+
+impl NewCrateTransfer {
+    /// Creates a new pending transfer, replacing any existing pending
+    /// transfer of the crate.
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateTransfer> {
+        conn.transaction(|conn| {
+            async move {
+                diesel::delete(crate_transfers::table)
+                    .filter(crate_transfers::crate_id.eq(self.crate_id))
+                    .filter(crate_transfers::accepted_at.is_null())
+                    .execute(conn)
+                    .await?;
+
+                diesel::insert_into(crate_transfers::table)
+                    .values(self)
+                    .returning(CrateTransfer::as_returning())
+                    .get_result(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
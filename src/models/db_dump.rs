@@ -0,0 +1,91 @@
+use crate::schema::db_dumps;
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum DbDumpKind {
+        Full = 0,
+        Incremental = 1,
+    }
+}
+
+/// The model representing a row in the `db_dumps` database table.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = db_dumps, check_for_backend(diesel::pg::Pg))]
+pub struct DbDump {
+    pub id: i32,
+    pub kind: DbDumpKind,
+    pub path: String,
+    pub size: i64,
+    pub checksum: String,
+    pub since: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DbDump {
+    /// Returns the start time of the most recent dump.
+    pub async fn latest_timestamp(
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Option<DateTime<Utc>>> {
+        db_dumps::table
+            .select(diesel::dsl::max(db_dumps::created_at))
+            .get_result(conn)
+            .await
+    }
+
+    /// Deletes the records of all incremental dumps that were created before
+    /// `cutoff`, and returns their paths so that the archives can be removed
+    /// from the storage.
+    pub async fn delete_incremental_before(
+        conn: &mut AsyncPgConnection,
+        cutoff: DateTime<Utc>,
+    ) -> QueryResult<Vec<String>> {
+        let query = db_dumps::table
+            .filter(db_dumps::kind.eq(DbDumpKind::Incremental))
+            .filter(db_dumps::created_at.lt(cutoff));
+
+        diesel::delete(query)
+            .returning(db_dumps::path)
+            .get_results(conn)
+            .await
+    }
+}
+
+#[derive(Insertable, Debug, Builder)]
+#[diesel(table_name = db_dumps, check_for_backend(diesel::pg::Pg))]
+pub struct NewDbDump<'a> {
+    kind: DbDumpKind,
+    path: &'a str,
+    size: i64,
+    checksum: &'a str,
+    since: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl NewDbDump<'_> {
+    /// Records a new dump archive.
+    ///
+    /// Full dumps are always uploaded to the same path, so the record of the
+    /// archive that was previously stored at the same path is replaced.
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<DbDump> {
+        conn.transaction(|conn| {
+            async move {
+                diesel::delete(db_dumps::table.filter(db_dumps::path.eq(self.path)))
+                    .execute(conn)
+                    .await?;
+
+                diesel::insert_into(db_dumps::table)
+                    .values(self)
+                    .returning(DbDump::as_returning())
+                    .get_result(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
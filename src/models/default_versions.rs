@@ -54,13 +54,20 @@ impl Ord for Version {
 /// 2. The highest non-yanked version.
 /// 3. The highest version.
 ///
-/// The default version is then written to the `default_versions` table.
+/// The default version is then written to the `default_versions` table,
+/// together with the highest stable and pre-release versions that are not
+/// yanked.
 #[instrument(skip(conn))]
 pub async fn update_default_version(
     crate_id: i32,
     conn: &mut AsyncPgConnection,
 ) -> QueryResult<()> {
-    let default_version = calculate_default_version(crate_id, conn).await?;
+    use diesel::result::Error::NotFound;
+
+    let versions = load_versions(crate_id, conn).await?;
+    let default_version = versions.iter().max().ok_or(NotFound)?;
+    let latest_stable = find_latest(&versions, false);
+    let latest_prerelease = find_latest(&versions, true);
 
     debug!(
         "Updating default version to {} (id: {})…",
@@ -71,16 +78,70 @@ pub async fn update_default_version(
         .values((
             default_versions::crate_id.eq(crate_id),
             default_versions::version_id.eq(default_version.id),
+            default_versions::latest_stable_version_id.eq(latest_stable),
+            default_versions::latest_prerelease_version_id.eq(latest_prerelease),
         ))
         .on_conflict(default_versions::crate_id)
         .do_update()
-        .set(default_versions::version_id.eq(default_version.id))
+        .set((
+            default_versions::version_id.eq(default_version.id),
+            default_versions::latest_stable_version_id.eq(latest_stable),
+            default_versions::latest_prerelease_version_id.eq(latest_prerelease),
+        ))
         .execute(conn)
         .await?;
 
     Ok(())
 }
 
+/// Returns the ID of the highest stable or pre-release version that is not
+/// yanked.
+fn find_latest(versions: &[Version], prerelease: bool) -> Option<i32> {
+    versions
+        .iter()
+        .filter(|version| !version.yanked && version.is_prerelease() == prerelease)
+        .max()
+        .map(|version| version.id)
+}
+
+/// The highest stable and pre-release versions of a crate, as saved by
+/// [update_default_version].
+#[derive(Debug, Default)]
+pub struct LatestVersions {
+    pub stable: Option<String>,
+    pub prerelease: Option<String>,
+}
+
+impl LatestVersions {
+    pub async fn load(crate_id: i32, conn: &mut AsyncPgConnection) -> QueryResult<Self> {
+        let (stable, prerelease) = diesel::alias!(
+            versions as stable_versions: StableVersions,
+            versions as prerelease_versions: PrereleaseVersions,
+        );
+
+        let latest = default_versions::table
+            .left_join(
+                stable.on(default_versions::latest_stable_version_id
+                    .eq(stable.field(versions::id).nullable())),
+            )
+            .left_join(
+                prerelease.on(default_versions::latest_prerelease_version_id
+                    .eq(prerelease.field(versions::id).nullable())),
+            )
+            .filter(default_versions::crate_id.eq(crate_id))
+            .select((
+                stable.field(versions::num).nullable(),
+                prerelease.field(versions::num).nullable(),
+            ))
+            .first::<(Option<String>, Option<String>)>(conn)
+            .await
+            .optional()?;
+
+        let (stable, prerelease) = latest.unwrap_or_default();
+        Ok(Self { stable, prerelease })
+    }
+}
+
 /// Verifies that the default version for the specified crate is up-to-date.
 #[instrument(skip(conn))]
 pub async fn verify_default_version(
@@ -121,16 +182,22 @@ async fn calculate_default_version(
 ) -> QueryResult<Version> {
     use diesel::result::Error::NotFound;
 
+    let versions = load_versions(crate_id, conn).await?;
+    versions.into_iter().max().ok_or(NotFound)
+}
+
+async fn load_versions(crate_id: i32, conn: &mut AsyncPgConnection) -> QueryResult<Vec<Version>> {
     debug!("Loading all versions for the crate…");
     let versions = versions::table
         .filter(versions::crate_id.eq(crate_id))
+        .filter(crate::models::Version::not_deleted())
         .select(Version::as_returning())
         .load::<Version>(conn)
         .await?;
 
     debug!("Found {} versions", versions.len());
 
-    versions.into_iter().max().ok_or(NotFound)
+    Ok(versions)
 }
 
 #[cfg(test)]
@@ -199,6 +266,35 @@ mod tests {
         check(&versions, "1.0.0-beta.3");
     }
 
+    #[test]
+    fn test_find_latest() {
+        fn check(versions: &[Version], stable: Option<&str>, prerelease: Option<&str>) {
+            let versions = versions
+                .iter()
+                .enumerate()
+                .map(|(id, version)| Version {
+                    id: id as i32,
+                    ..version.clone()
+                })
+                .collect::<Vec<_>>();
+
+            let num = |id: Option<i32>| id.map(|id| versions[id as usize].num.to_string());
+            assert_eq!(num(find_latest(&versions, false)).as_deref(), stable);
+            assert_eq!(num(find_latest(&versions, true)).as_deref(), prerelease);
+        }
+
+        let versions = vec![
+            v("1.0.0", false),
+            v("1.1.0", true),
+            v("2.0.0-beta.1", false),
+            v("2.0.0-beta.2", true),
+        ];
+        check(&versions, Some("1.0.0"), Some("2.0.0-beta.1"));
+
+        let versions = vec![v("1.0.0", true), v("1.1.0-rc.1", true)];
+        check(&versions, None, None);
+    }
+
     #[test]
     fn test_ord() {
         let mut versions = vec![
@@ -1,6 +1,12 @@
 use crate::models::Version;
-use crate::schema::version_downloads;
+use crate::schema::{
+    category_downloads, keyword_downloads, version_downloads, version_downloads_archive,
+};
 use chrono::NaiveDate;
+use diesel::dsl::sum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::BTreeMap;
 
 #[derive(Queryable, Identifiable, Associations, Debug, Clone, Copy)]
 #[diesel(primary_key(version_id, date), belongs_to(Version))]
@@ -11,3 +17,115 @@ pub struct VersionDownload {
     pub date: NaiveDate,
     pub processed: bool,
 }
+
+impl VersionDownload {
+    /// Loads the daily download counts of the given versions between `start`
+    /// and `end` (inclusive), ordered by date and version ID.
+    ///
+    /// Months that have been moved to the `version_downloads_archive` table
+    /// are included, so callers don't have to know which months have been
+    /// archived already.
+    pub async fn load_between(
+        conn: &mut AsyncPgConnection,
+        version_ids: &[i32],
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> QueryResult<Vec<VersionDownload>> {
+        let mut downloads: Vec<VersionDownload> = version_downloads::table
+            .filter(version_downloads::version_id.eq_any(version_ids))
+            .filter(version_downloads::date.between(start, end))
+            .load(conn)
+            .await?;
+
+        let archived: Vec<VersionDownload> = version_downloads_archive::table
+            .filter(version_downloads_archive::version_id.eq_any(version_ids))
+            .filter(version_downloads_archive::date.between(start, end))
+            .load(conn)
+            .await?;
+
+        downloads.extend(archived);
+        downloads.sort_by_key(|download| (download.date, download.version_id));
+
+        Ok(downloads)
+    }
+
+    /// Sums up the daily download counts of the given versions between
+    /// `start` and `end` (inclusive) per date.
+    ///
+    /// Like [`VersionDownload::load_between`], this includes the months that
+    /// have been archived already.
+    pub async fn sum_between(
+        conn: &mut AsyncPgConnection,
+        version_ids: &[i32],
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> QueryResult<BTreeMap<NaiveDate, i64>> {
+        let live: Vec<(NaiveDate, Option<i64>)> = version_downloads::table
+            .filter(version_downloads::version_id.eq_any(version_ids))
+            .filter(version_downloads::date.between(start, end))
+            .group_by(version_downloads::date)
+            .select((version_downloads::date, sum(version_downloads::downloads)))
+            .load(conn)
+            .await?;
+
+        let archived: Vec<(NaiveDate, Option<i64>)> = version_downloads_archive::table
+            .filter(version_downloads_archive::version_id.eq_any(version_ids))
+            .filter(version_downloads_archive::date.between(start, end))
+            .group_by(version_downloads_archive::date)
+            .select((
+                version_downloads_archive::date,
+                sum(version_downloads_archive::downloads),
+            ))
+            .load(conn)
+            .await?;
+
+        let mut sums = BTreeMap::new();
+        for (date, downloads) in live.into_iter().chain(archived) {
+            *sums.entry(date).or_default() += downloads.unwrap_or_default();
+        }
+
+        Ok(sums)
+    }
+}
+
+/// Daily downloads of all crates with a keyword or in a category, as
+/// aggregated by the `UpdateAggregateDownloads` background job.
+#[derive(Queryable, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggregateDownload {
+    pub date: NaiveDate,
+    pub downloads: i64,
+}
+
+impl AggregateDownload {
+    /// Loads the daily downloads of all crates with the given keyword since
+    /// `start` (inclusive), ordered by date.
+    pub async fn for_keyword(
+        conn: &mut AsyncPgConnection,
+        keyword_id: i32,
+        start: NaiveDate,
+    ) -> QueryResult<Vec<AggregateDownload>> {
+        keyword_downloads::table
+            .filter(keyword_downloads::keyword_id.eq(keyword_id))
+            .filter(keyword_downloads::date.ge(start))
+            .select((keyword_downloads::date, keyword_downloads::downloads))
+            .order(keyword_downloads::date)
+            .load(conn)
+            .await
+    }
+
+    /// Loads the daily downloads of all crates in the given category since
+    /// `start` (inclusive), ordered by date.
+    pub async fn for_category(
+        conn: &mut AsyncPgConnection,
+        category_id: i32,
+        start: NaiveDate,
+    ) -> QueryResult<Vec<AggregateDownload>> {
+        category_downloads::table
+            .filter(category_downloads::category_id.eq(category_id))
+            .filter(category_downloads::date.ge(start))
+            .select((category_downloads::date, category_downloads::downloads))
+            .order(category_downloads::date)
+            .load(conn)
+            .await
+    }
+}
@@ -14,6 +14,7 @@ pub struct Email {
     #[diesel(deserialize_as = String, serialize_as = String)]
     pub token: SecretString,
     pub token_generated_at: Option<NaiveDateTime>,
+    pub is_primary: bool,
 }
 
 #[derive(Debug, Insertable, AsChangeset)]
@@ -21,4 +22,5 @@ pub struct Email {
 pub struct NewEmail<'a> {
     pub user_id: i32,
     pub email: &'a str,
+    pub is_primary: bool,
 }
@@ -0,0 +1,155 @@
+use crate::schema::{feature_overrides, features};
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The model representing a row in the `features` database table.
+///
+/// Features that don't have a row in the table are switched off.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(
+    table_name = features,
+    check_for_backend(diesel::pg::Pg),
+    primary_key(name)
+)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub enabled: bool,
+    pub updated_by: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+    pub description: Option<String>,
+    pub rollout_percentage: i16,
+}
+
+impl FeatureFlag {
+    /// Rejects all mutating API requests and pauses the background jobs,
+    /// e.g. during database migrations.
+    pub const READ_ONLY_MODE: &'static str = "read_only_mode";
+
+    pub async fn all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        features::table
+            .select(FeatureFlag::as_select())
+            .order(features::name)
+            .load(conn)
+            .await
+    }
+
+    pub async fn find(conn: &mut AsyncPgConnection, name: &str) -> QueryResult<Option<Self>> {
+        features::table
+            .find(name)
+            .select(FeatureFlag::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Returns `true` if the feature is switched on for all users.
+    pub async fn is_enabled(conn: &mut AsyncPgConnection, name: &str) -> QueryResult<bool> {
+        let flag = Self::find(conn, name).await?;
+        Ok(flag.is_some_and(|flag| flag.enabled))
+    }
+
+    /// Switches the feature on or off for all users, creating the feature if
+    /// necessary.
+    pub async fn set(
+        conn: &mut AsyncPgConnection,
+        name: &str,
+        enabled: bool,
+        updated_by: Option<i32>,
+    ) -> QueryResult<Self> {
+        let now = Utc::now();
+
+        diesel::insert_into(features::table)
+            .values((
+                features::name.eq(name),
+                features::enabled.eq(enabled),
+                features::updated_by.eq(updated_by),
+                features::updated_at.eq(now),
+            ))
+            .on_conflict(features::name)
+            .do_update()
+            .set((
+                features::enabled.eq(enabled),
+                features::updated_by.eq(updated_by),
+                features::updated_at.eq(now),
+            ))
+            .returning(FeatureFlag::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
+
+#[derive(Insertable, AsChangeset, Debug, Builder)]
+#[diesel(
+    table_name = features,
+    check_for_backend(diesel::pg::Pg),
+    primary_key(name),
+    treat_none_as_null = true
+)]
+pub struct NewFeatureFlag<'a> {
+    name: &'a str,
+    enabled: bool,
+    rollout_percentage: i16,
+    description: Option<&'a str>,
+    updated_by: Option<i32>,
+}
+
+impl NewFeatureFlag<'_> {
+    /// Inserts the feature, or replaces the settings of an existing feature.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<FeatureFlag> {
+        diesel::insert_into(features::table)
+            .values(self)
+            .on_conflict(features::name)
+            .do_update()
+            .set((self, features::updated_at.eq(Utc::now())))
+            .returning(FeatureFlag::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
+
+/// The model representing a row in the `feature_overrides` database table.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(
+    table_name = feature_overrides,
+    check_for_backend(diesel::pg::Pg),
+    primary_key(feature, user_id)
+)]
+pub struct FeatureOverride {
+    pub feature: String,
+    pub user_id: i32,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FeatureOverride {
+    pub async fn all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        feature_overrides::table
+            .select(FeatureOverride::as_select())
+            .load(conn)
+            .await
+    }
+
+    /// Switches the feature on or off for the user, regardless of the
+    /// rollout of the feature.
+    pub async fn set(
+        conn: &mut AsyncPgConnection,
+        feature: &str,
+        user_id: i32,
+        enabled: bool,
+    ) -> QueryResult<Self> {
+        diesel::insert_into(feature_overrides::table)
+            .values((
+                feature_overrides::feature.eq(feature),
+                feature_overrides::user_id.eq(user_id),
+                feature_overrides::enabled.eq(enabled),
+            ))
+            .on_conflict((feature_overrides::feature, feature_overrides::user_id))
+            .do_update()
+            .set(feature_overrides::enabled.eq(enabled))
+            .returning(FeatureOverride::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
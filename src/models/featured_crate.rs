@@ -0,0 +1,61 @@
+use crate::schema::featured_crates;
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The model representing a row in the `featured_crates` database table.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = featured_crates, check_for_backend(diesel::pg::Pg))]
+pub struct FeaturedCrate {
+    pub id: i32,
+    pub crate_id: i32,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FeaturedCrate {
+    /// Returns the IDs of the crates that are currently highlighted, the
+    /// most recently started first.
+    pub async fn active_crate_ids(conn: &mut AsyncPgConnection) -> QueryResult<Vec<i32>> {
+        let now = Utc::now();
+
+        featured_crates::table
+            .filter(featured_crates::starts_at.le(now))
+            .filter(
+                featured_crates::ends_at
+                    .is_null()
+                    .or(featured_crates::ends_at.gt(now)),
+            )
+            .select(featured_crates::crate_id)
+            .order((
+                featured_crates::starts_at.desc(),
+                featured_crates::id.desc(),
+            ))
+            .load(conn)
+            .await
+    }
+}
+
+#[derive(Insertable, Debug, Builder)]
+#[diesel(table_name = featured_crates, check_for_backend(diesel::pg::Pg))]
+pub struct NewFeaturedCrate<'a> {
+    crate_id: i32,
+    starts_at: DateTime<Utc>,
+    ends_at: Option<DateTime<Utc>>,
+    note: Option<&'a str>,
+    created_by: Option<i32>,
+}
+
+impl NewFeaturedCrate<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<FeaturedCrate> {
+        diesel::insert_into(featured_crates::table)
+            .values(self)
+            .returning(FeaturedCrate::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -0,0 +1,68 @@
+use crate::schema::crate_funding_links;
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum FundingPlatform {
+        Github = 0,
+        OpenCollective = 1,
+        Patreon = 2,
+        KoFi = 3,
+        Liberapay = 4,
+        BuyMeACoffee = 5,
+        Polar = 6,
+        ThanksDev = 7,
+    }
+}
+
+/// A funding link of a crate, see [crate::funding] for more details.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Selectable)]
+#[diesel(table_name = crate_funding_links, check_for_backend(diesel::pg::Pg))]
+pub struct FundingLink {
+    pub platform: FundingPlatform,
+    pub url: String,
+}
+
+impl FundingLink {
+    pub async fn for_crate(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<Vec<Self>> {
+        crate_funding_links::table
+            .filter(crate_funding_links::crate_id.eq(crate_id))
+            .select(FundingLink::as_select())
+            .order(crate_funding_links::position)
+            .load(conn)
+            .await
+    }
+
+    /// Replaces all funding links of the crate with the given links.
+    pub async fn update_crate(
+        conn: &mut AsyncPgConnection,
+        crate_id: i32,
+        links: &[FundingLink],
+    ) -> QueryResult<()> {
+        diesel::delete(crate_funding_links::table)
+            .filter(crate_funding_links::crate_id.eq(crate_id))
+            .execute(conn)
+            .await?;
+
+        let rows = links
+            .iter()
+            .enumerate()
+            .map(|(position, link)| {
+                (
+                    crate_funding_links::crate_id.eq(crate_id),
+                    crate_funding_links::position.eq(position as i16),
+                    crate_funding_links::platform.eq(link.platform),
+                    crate_funding_links::url.eq(&link.url),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(crate_funding_links::table)
+            .values(&rows)
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use crate::schema::{ip_access_rules, ip_bans};
+use bon::Builder;
+use chrono::{DateTime, TimeDelta, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::time::Duration;
+
+pg_enum! {
+    pub enum IpAccessRuleKind {
+        Allow = 0,
+        Deny = 1,
+    }
+}
+
+/// The model representing a row in the `ip_access_rules` database table.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = ip_access_rules, check_for_backend(diesel::pg::Pg))]
+pub struct IpAccessRule {
+    pub id: i32,
+    pub network: String,
+    pub kind: IpAccessRuleKind,
+    pub reason: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IpAccessRule {
+    pub async fn all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<IpAccessRule>> {
+        ip_access_rules::table
+            .select(IpAccessRule::as_select())
+            .order(ip_access_rules::id)
+            .load(conn)
+            .await
+    }
+}
+
+#[derive(Insertable, Debug, Builder)]
+#[diesel(table_name = ip_access_rules, check_for_backend(diesel::pg::Pg))]
+pub struct NewIpAccessRule<'a> {
+    network: &'a str,
+    kind: IpAccessRuleKind,
+    reason: Option<&'a str>,
+    created_by: Option<i32>,
+}
+
+impl NewIpAccessRule<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<IpAccessRule> {
+        diesel::insert_into(ip_access_rules::table)
+            .values(self)
+            .returning(IpAccessRule::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
+
+/// The model representing a row in the `ip_bans` database table.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = ip_bans, check_for_backend(diesel::pg::Pg))]
+pub struct IpBan {
+    pub id: i32,
+    pub network: String,
+    pub strikes: i32,
+    pub reason: String,
+    pub banned_until: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IpBan {
+    /// Returns the bans that have not expired yet, the most recent first.
+    pub async fn active(conn: &mut AsyncPgConnection) -> QueryResult<Vec<IpBan>> {
+        ip_bans::table
+            .filter(ip_bans::banned_until.gt(Utc::now()))
+            .select(IpBan::as_select())
+            .order(ip_bans::updated_at.desc())
+            .load(conn)
+            .await
+    }
+
+    /// Bans the network, or renews the ban of a network that has been banned
+    /// before.
+    ///
+    /// The duration of the ban doubles with every strike, starting at
+    /// `duration`, up to `max_duration`. The strikes are forgotten once the
+    /// previous ban has been expired for longer than `max_duration`.
+    pub async fn record_strike(
+        conn: &mut AsyncPgConnection,
+        network: &str,
+        reason: &str,
+        duration: Duration,
+        max_duration: Duration,
+    ) -> QueryResult<IpBan> {
+        let now = Utc::now();
+
+        let previous: Option<IpBan> = ip_bans::table
+            .filter(ip_bans::network.eq(network))
+            .select(IpBan::as_select())
+            .first(conn)
+            .await
+            .optional()?;
+
+        let max_delta = TimeDelta::from_std(max_duration).unwrap_or(TimeDelta::MAX);
+        let strikes = match previous {
+            Some(ban) if now - ban.banned_until < max_delta => ban.strikes + 1,
+            _ => 1,
+        };
+
+        let duration = ban_duration(strikes, duration, max_duration);
+        let duration = TimeDelta::from_std(duration).unwrap_or(TimeDelta::MAX);
+        let banned_until = now
+            .checked_add_signed(duration)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+        diesel::insert_into(ip_bans::table)
+            .values((
+                ip_bans::network.eq(network),
+                ip_bans::strikes.eq(strikes),
+                ip_bans::reason.eq(reason),
+                ip_bans::banned_until.eq(banned_until),
+            ))
+            .on_conflict(ip_bans::network)
+            .do_update()
+            .set((
+                ip_bans::strikes.eq(strikes),
+                ip_bans::reason.eq(reason),
+                ip_bans::banned_until.eq(banned_until),
+                ip_bans::updated_at.eq(now),
+            ))
+            .returning(IpBan::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
+
+fn ban_duration(strikes: i32, duration: Duration, max_duration: Duration) -> Duration {
+    let exponent = u32::try_from(strikes - 1).unwrap_or_default();
+    let factor = 1u32.checked_shl(exponent).unwrap_or(u32::MAX);
+    duration.saturating_mul(factor).min(max_duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ban_duration() {
+        let duration = Duration::from_secs(60);
+        let max_duration = Duration::from_secs(600);
+
+        let durations = (1..=6)
+            .map(|strikes| ban_duration(strikes, duration, max_duration).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(durations, [60, 120, 240, 480, 600, 600]);
+
+        let duration = ban_duration(100, duration, max_duration);
+        assert_eq!(duration, max_duration);
+    }
+}
@@ -1,11 +1,14 @@
 use chrono::NaiveDateTime;
+use diesel::dsl::not;
 use diesel::prelude::*;
+use diesel::sql_types::Integer;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 
 use crate::models::Crate;
 use crate::schema::*;
 use crates_io_diesel_helpers::lower;
+use std::collections::HashMap;
 
 #[derive(Clone, Identifiable, Queryable, Debug, Selectable)]
 pub struct Keyword {
@@ -36,11 +39,43 @@ impl Keyword {
             .await
     }
 
+    /// Returns the keyword that the alias redirects to, if there is one.
+    pub async fn find_by_alias(
+        conn: &mut AsyncPgConnection,
+        alias: &str,
+    ) -> QueryResult<Option<Keyword>> {
+        keyword_aliases::table
+            .inner_join(keywords::table)
+            .filter(keyword_aliases::alias.eq(alias.to_lowercase()))
+            .select(Keyword::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Finds the keywords with the given names, creating the missing ones.
+    ///
+    /// Names that are aliases of other keywords resolve to these keywords.
     pub async fn find_or_create_all(
         conn: &mut AsyncPgConnection,
         names: &[&str],
     ) -> QueryResult<Vec<Keyword>> {
-        let lowercase_names: Vec<_> = names.iter().map(|s| s.to_lowercase()).collect();
+        let mut lowercase_names: Vec<_> = names.iter().map(|s| s.to_lowercase()).collect();
+
+        let aliases: HashMap<String, String> = keyword_aliases::table
+            .inner_join(keywords::table)
+            .filter(keyword_aliases::alias.eq_any(&lowercase_names))
+            .select((keyword_aliases::alias, keywords::keyword))
+            .load::<(String, String)>(conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        for name in &mut lowercase_names {
+            if let Some(keyword) = aliases.get(name) {
+                name.clone_from(keyword);
+            }
+        }
 
         let new_keywords: Vec<_> = lowercase_names
             .iter()
@@ -59,6 +94,72 @@ impl Keyword {
             .await
     }
 
+    /// Moves all crates of the `source` keyword to the `target` keyword,
+    /// deletes the `source` keyword, and leaves an alias that redirects from
+    /// the name of the `source` keyword to the `target` keyword.
+    ///
+    /// Existing aliases of the `source` keyword are moved to the `target`
+    /// keyword too.
+    pub async fn merge_into(
+        &self,
+        conn: &mut AsyncPgConnection,
+        target: &Keyword,
+        created_by: Option<i32>,
+    ) -> QueryResult<()> {
+        conn.transaction(|conn| {
+            async move {
+                // Crates that already have the `target` keyword are skipped
+                // explicitly, since the trigger that counts the crates of the
+                // keywords also fires for conflicting rows.
+                let target_crate_ids = crates_keywords::table
+                    .filter(crates_keywords::keyword_id.eq(target.id))
+                    .select(crates_keywords::crate_id);
+
+                let crate_ids = crates_keywords::table
+                    .filter(crates_keywords::keyword_id.eq(self.id))
+                    .filter(not(crates_keywords::crate_id.eq_any(target_crate_ids)))
+                    .select((crates_keywords::crate_id, target.id.into_sql::<Integer>()));
+
+                diesel::insert_into(crates_keywords::table)
+                    .values(crate_ids)
+                    .into_columns((crates_keywords::crate_id, crates_keywords::keyword_id))
+                    .execute(conn)
+                    .await?;
+
+                diesel::delete(crates_keywords::table)
+                    .filter(crates_keywords::keyword_id.eq(self.id))
+                    .execute(conn)
+                    .await?;
+
+                diesel::update(keyword_aliases::table)
+                    .filter(keyword_aliases::keyword_id.eq(self.id))
+                    .set(keyword_aliases::keyword_id.eq(target.id))
+                    .execute(conn)
+                    .await?;
+
+                diesel::delete(keywords::table.find(self.id))
+                    .execute(conn)
+                    .await?;
+
+                diesel::insert_into(keyword_aliases::table)
+                    .values((
+                        keyword_aliases::alias.eq(&self.keyword),
+                        keyword_aliases::keyword_id.eq(target.id),
+                        keyword_aliases::created_by.eq(created_by),
+                    ))
+                    .on_conflict(keyword_aliases::alias)
+                    .do_update()
+                    .set(keyword_aliases::keyword_id.eq(target.id))
+                    .execute(conn)
+                    .await?;
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
     pub fn valid_name(name: &str) -> bool {
         let mut chars = name.chars();
         let first = match chars.next() {
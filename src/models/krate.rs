@@ -1,4 +1,4 @@
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::associations::Identifiable;
 use diesel::dsl;
 use diesel::pg::Pg;
@@ -13,8 +13,9 @@ use crate::controllers::helpers::pagination::*;
 use crate::models::helpers::with_count::*;
 use crate::models::version::TopVersions;
 use crate::models::{
-    CrateOwner, CrateOwnerInvitation, NewCrateOwnerInvitationOutcome, Owner, OwnerKind,
-    ReverseDependency, User, Version,
+    AuditLogAction, CrateOwner, CrateOwnerInvitation, NewAuditLogEntry,
+    NewCrateOwnerInvitationOutcome, Organization, Owner, OwnerKind, OwnerRole, ReverseDependency,
+    User, Version,
 };
 use crate::schema::*;
 use crate::util::errors::{bad_request, version_not_found, AppResult};
@@ -54,6 +55,7 @@ pub struct Crate {
     pub repository: Option<String>,
     max_upload_size: Option<i32>,
     pub max_features: Option<i16>,
+    pub repository_verified_at: Option<DateTime<Utc>>,
 }
 
 /// We literally never want to select `textsearchable_index_col`
@@ -69,6 +71,7 @@ type AllColumns = (
     crates::repository,
     crates::max_upload_size,
     crates::max_features,
+    crates::repository_verified_at,
 );
 
 pub const ALL_COLUMNS: AllColumns = (
@@ -82,6 +85,7 @@ pub const ALL_COLUMNS: AllColumns = (
     crates::repository,
     crates::max_upload_size,
     crates::max_features,
+    crates::repository_verified_at,
 );
 
 pub const MAX_NAME_LENGTH: usize = 64;
@@ -140,6 +144,7 @@ impl NewCrate<'_> {
                     created_by: user_id,
                     owner_kind: OwnerKind::User,
                     email_notifications: true,
+                    role: OwnerRole::Admin,
                 };
 
                 diesel::insert_into(crate_owners::table)
@@ -212,6 +217,7 @@ impl Crate {
     ) -> AppResult<Version> {
         Version::belonging_to(self)
             .filter(versions::num.eq(version))
+            .filter(Version::not_deleted())
             .select(Version::as_select())
             .first(conn)
             .await
@@ -362,16 +368,65 @@ impl Crate {
         ))
     }
 
+    /// Whether the crate has been frozen by the crates.io team, which
+    /// prevents new versions from being published.
+    pub async fn is_frozen(&self, conn: &mut AsyncPgConnection) -> QueryResult<bool> {
+        crates::table
+            .find(self.id)
+            .select(crates::frozen_at.is_not_null())
+            .get_result(conn)
+            .await
+    }
+
+    /// Automatically quarantines a crate that was flagged by one of the
+    /// publish heuristics, and records the reason in the audit log.
+    ///
+    /// Returns `false` if the crate was already quarantined.
+    pub async fn quarantine(
+        conn: &mut AsyncPgConnection,
+        crate_id: i32,
+        reason: &str,
+    ) -> QueryResult<bool> {
+        conn.transaction(|conn| {
+            async move {
+                let query = crates::table
+                    .find(crate_id)
+                    .filter(crates::quarantined_at.is_null());
+
+                let updated = diesel::update(query)
+                    .set(crates::quarantined_at.eq(Utc::now()))
+                    .execute(conn)
+                    .await?;
+
+                if updated == 0 {
+                    return Ok(false);
+                }
+
+                NewAuditLogEntry::builder()
+                    .crate_id(crate_id)
+                    .action(AuditLogAction::CrateQuarantine)
+                    .details(serde_json::json!({ "reason": reason }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, diesel::result::Error>(true)
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
     pub async fn owners(&self, conn: &mut AsyncPgConnection) -> QueryResult<Vec<Owner>> {
         let users = CrateOwner::by_owner_kind(OwnerKind::User)
             .filter(crate_owners::crate_id.eq(self.id))
             .order((crate_owners::owner_id, crate_owners::owner_kind))
             .inner_join(users::table)
-            .select(User::as_select())
+            .select((User::as_select(), crate_owners::role))
             .load(conn)
             .await?
             .into_iter()
-            .map(Owner::User);
+            .map(|(user, role)| Owner::User(user, role));
 
         let teams = CrateOwner::by_owner_kind(OwnerKind::Team)
             .filter(crate_owners::crate_id.eq(self.id))
@@ -383,28 +438,46 @@ impl Crate {
             .into_iter()
             .map(Owner::Team);
 
-        Ok(users.chain(teams).collect())
+        let organizations = CrateOwner::by_owner_kind(OwnerKind::Organization)
+            .filter(crate_owners::crate_id.eq(self.id))
+            .order((crate_owners::owner_id, crate_owners::owner_kind))
+            .inner_join(organizations::table)
+            .select(Organization::as_select())
+            .load(conn)
+            .await?
+            .into_iter()
+            .map(Owner::Organization);
+
+        Ok(users.chain(teams).chain(organizations).collect())
     }
 
     /// Invite `login` as an owner of this crate, returning the created
     /// [`NewOwnerInvite`].
+    ///
+    /// Invited users will have the given `role` once they accept the
+    /// invitation.
     pub async fn owner_add(
         &self,
         app: &App,
         conn: &mut AsyncPgConnection,
         req_user: &User,
         login: &str,
+        role: OwnerRole,
     ) -> Result<NewOwnerInvite, OwnerAddError> {
-        use diesel::insert_into;
-
-        let owner = Owner::find_or_create_by_login(app, conn, req_user, login).await?;
+        let owner = Owner::find_or_create_by_login(app, conn, req_user, login, role).await?;
         match owner {
             // Users are invited and must accept before being added
-            Owner::User(user) => {
-                let creation_ret =
-                    CrateOwnerInvitation::create(user.id, req_user.id, self.id, conn, &app.config)
-                        .await
-                        .map_err(BoxedAppError::from)?;
+            Owner::User(user, role) => {
+                let creation_ret = CrateOwnerInvitation::create(
+                    user.id,
+                    req_user.id,
+                    self.id,
+                    role,
+                    conn,
+                    &app.config,
+                )
+                .await
+                .map_err(BoxedAppError::from)?;
 
                 match creation_ret {
                     NewCrateOwnerInvitationOutcome::InviteCreated { plaintext_token } => {
@@ -415,28 +488,47 @@ impl Crate {
                     }
                 }
             }
-            // Teams are added as owners immediately
+            // Teams and organizations are added as owners immediately
             Owner::Team(team) => {
-                insert_into(crate_owners::table)
-                    .values(&CrateOwner {
-                        crate_id: self.id,
-                        owner_id: team.id,
-                        created_by: req_user.id,
-                        owner_kind: OwnerKind::Team,
-                        email_notifications: true,
-                    })
-                    .on_conflict(crate_owners::table.primary_key())
-                    .do_update()
-                    .set(crate_owners::deleted.eq(false))
-                    .execute(conn)
-                    .await
-                    .map_err(BoxedAppError::from)?;
+                self.insert_owner(conn, team.id, OwnerKind::Team, req_user)
+                    .await?;
 
                 Ok(NewOwnerInvite::Team(team))
             }
+            Owner::Organization(organization) => {
+                self.insert_owner(conn, organization.id, OwnerKind::Organization, req_user)
+                    .await?;
+
+                Ok(NewOwnerInvite::Organization(organization))
+            }
         }
     }
 
+    async fn insert_owner(
+        &self,
+        conn: &mut AsyncPgConnection,
+        owner_id: i32,
+        owner_kind: OwnerKind,
+        req_user: &User,
+    ) -> AppResult<()> {
+        diesel::insert_into(crate_owners::table)
+            .values(&CrateOwner {
+                crate_id: self.id,
+                owner_id,
+                created_by: req_user.id,
+                owner_kind,
+                email_notifications: true,
+                role: OwnerRole::Admin,
+            })
+            .on_conflict(crate_owners::table.primary_key())
+            .do_update()
+            .set(crate_owners::deleted.eq(false))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn owner_remove(&self, conn: &mut AsyncPgConnection, login: &str) -> AppResult<()> {
         let query = diesel::sql_query(
             r#"WITH crate_owners_with_login AS (
@@ -444,6 +536,8 @@ impl Crate {
                     crate_owners.*,
                     CASE WHEN crate_owners.owner_kind = 1 THEN
                          teams.login
+                    WHEN crate_owners.owner_kind = 2 THEN
+                         'org:' || organizations.login
                     ELSE
                          users.gh_login
                     END AS login
@@ -451,6 +545,9 @@ impl Crate {
                 LEFT JOIN teams
                     ON crate_owners.owner_id = teams.id
                     AND crate_owners.owner_kind = 1
+                LEFT JOIN organizations
+                    ON crate_owners.owner_id = organizations.id
+                    AND crate_owners.owner_kind = 2
                 LEFT JOIN users
                     ON crate_owners.owner_id = users.id
                     AND crate_owners.owner_kind = 0
@@ -512,6 +609,10 @@ pub enum NewOwnerInvite {
 
     /// The invitee was a [`Team`], and they were immediately added as an owner.
     Team(Team),
+
+    /// The invitee was an [`Organization`], and it was immediately added as
+    /// an owner.
+    Organization(Organization),
 }
 
 /// Error results from a [`Crate::owner_add()`] model call.
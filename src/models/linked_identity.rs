@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::models::User;
+use crate::oauth::ExternalIdentity;
+use crate::schema::linked_identities;
+
+/// An account of an additional identity provider that has been linked to a
+/// crates.io user and can be used to log in to it.
+#[derive(Debug, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(table_name = linked_identities, check_for_backend(diesel::pg::Pg), belongs_to(User))]
+pub struct LinkedIdentity {
+    pub id: i32,
+    pub user_id: i32,
+    pub provider: String,
+    pub account_id: String,
+    pub login: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LinkedIdentity {
+    /// Returns the identity of the given provider account, regardless of the
+    /// user it is linked to.
+    pub async fn find(
+        conn: &mut AsyncPgConnection,
+        provider: &str,
+        account_id: &str,
+    ) -> QueryResult<Option<Self>> {
+        linked_identities::table
+            .filter(linked_identities::provider.eq(provider))
+            .filter(linked_identities::account_id.eq(account_id))
+            .select(LinkedIdentity::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = linked_identities, check_for_backend(diesel::pg::Pg))]
+pub struct NewLinkedIdentity<'a> {
+    pub user_id: i32,
+    pub provider: &'a str,
+    pub account_id: &'a str,
+    pub login: &'a str,
+    pub email: Option<&'a str>,
+}
+
+impl<'a> NewLinkedIdentity<'a> {
+    pub fn new(user_id: i32, identity: &'a ExternalIdentity) -> Self {
+        Self {
+            user_id,
+            provider: identity.provider.as_str(),
+            account_id: &identity.account_id,
+            login: &identity.login,
+            email: identity.email.as_deref(),
+        }
+    }
+
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<LinkedIdentity> {
+        diesel::insert_into(linked_identities::table)
+            .values(self)
+            .returning(LinkedIdentity::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
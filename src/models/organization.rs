@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+use crate::models::{Rights, User};
+use crate::schema::{organization_members, organizations};
+use crates_io_diesel_helpers::{lower, pg_enum};
+
+/// The prefix used to refer to an organization in the list of crate owners.
+pub const ORGANIZATION_OWNER_PREFIX: &str = "org:";
+
+pg_enum! {
+    pub enum OrganizationRole {
+        Admin = 0,
+        Publisher = 1,
+        Viewer = 2,
+    }
+}
+
+impl OrganizationRole {
+    /// The rights that members with this role have on crates owned by the
+    /// organization.
+    pub fn crate_rights(self) -> Rights {
+        match self {
+            OrganizationRole::Admin => Rights::Full,
+            OrganizationRole::Publisher => Rights::Publish,
+            OrganizationRole::Viewer => Rights::None,
+        }
+    }
+}
+
+/// An organization that can own crates, independently of any GitHub team.
+#[derive(Clone, Debug, PartialEq, Eq, Queryable, Identifiable, Selectable)]
+pub struct Organization {
+    pub id: i32,
+    pub login: String,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Organization {
+    pub async fn find_by_login(
+        conn: &mut AsyncPgConnection,
+        login: &str,
+    ) -> QueryResult<Organization> {
+        organizations::table
+            .filter(lower(organizations::login).eq(login.to_lowercase()))
+            .select(Organization::as_select())
+            .first(conn)
+            .await
+    }
+
+    /// The name used for this organization in the list of crate owners,
+    /// e.g. `org:rust-lang`.
+    pub fn owner_login(&self) -> String {
+        format!("{ORGANIZATION_OWNER_PREFIX}{}", self.login)
+    }
+
+    /// Returns the role of the given user in this organization, or `None`
+    /// if the user is not a member.
+    pub async fn role_of(
+        &self,
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+    ) -> QueryResult<Option<OrganizationRole>> {
+        organization_members::table
+            .find((self.id, user_id))
+            .select(organization_members::role)
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = organizations, check_for_backend(diesel::pg::Pg))]
+pub struct NewOrganization<'a> {
+    pub login: &'a str,
+    pub name: Option<&'a str>,
+}
+
+impl NewOrganization<'_> {
+    /// Creates the organization and adds the given user as its first admin.
+    pub async fn create(
+        &self,
+        conn: &mut AsyncPgConnection,
+        creator: &User,
+    ) -> QueryResult<Organization> {
+        conn.transaction(|conn| {
+            async move {
+                let organization = diesel::insert_into(organizations::table)
+                    .values(self)
+                    .returning(Organization::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                NewOrganizationMember {
+                    organization_id: organization.id,
+                    user_id: creator.id,
+                    role: OrganizationRole::Admin,
+                }
+                .upsert(conn)
+                .await?;
+
+                Ok(organization)
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
+
+/// The membership of a user in an organization.
+#[derive(Debug, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(
+    primary_key(organization_id, user_id),
+    belongs_to(Organization),
+    belongs_to(User)
+)]
+pub struct OrganizationMember {
+    pub organization_id: i32,
+    pub user_id: i32,
+    pub role: OrganizationRole,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = organization_members, check_for_backend(diesel::pg::Pg))]
+pub struct NewOrganizationMember {
+    pub organization_id: i32,
+    pub user_id: i32,
+    pub role: OrganizationRole,
+}
+
+impl NewOrganizationMember {
+    /// Adds the user to the organization, or changes their role if they are
+    /// already a member.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<OrganizationMember> {
+        diesel::insert_into(organization_members::table)
+            .values(self)
+            .on_conflict((
+                organization_members::organization_id,
+                organization_members::user_id,
+            ))
+            .do_update()
+            .set(organization_members::role.eq(self.role))
+            .returning(OrganizationMember::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
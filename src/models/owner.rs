@@ -1,10 +1,14 @@
 use crate::app::App;
-use crate::util::errors::{bad_request, AppResult};
+use crate::util::errors::{bad_request, custom, AppResult};
 use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel_async::AsyncPgConnection;
+use http::StatusCode;
+use std::borrow::Cow;
 
-use crate::models::{Crate, Team, User};
+use crate::models::{
+    Crate, Organization, OrganizationRole, Rights, Team, User, ORGANIZATION_OWNER_PREFIX,
+};
 use crate::schema::crate_owners;
 use crates_io_diesel_helpers::pg_enum;
 
@@ -23,6 +27,7 @@ pub struct CrateOwner {
     pub created_by: i32,
     pub owner_kind: OwnerKind,
     pub email_notifications: bool,
+    pub role: OwnerRole,
 }
 
 type BoxedQuery<'a> = crate_owners::BoxedQuery<'a, Pg, crate_owners::SqlType>;
@@ -42,14 +47,36 @@ pg_enum! {
     pub enum OwnerKind {
         User = 0,
         Team = 1,
+        Organization = 2,
     }
 }
 
-/// Unifies the notion of a User or a Team.
+// The role of a user owner of a crate. `Admin` owners can publish new
+// versions and manage the owners and settings of the crate, while `Publish`
+// owners can only publish new versions.
+pg_enum! {
+    pub enum OwnerRole {
+        Admin = 0,
+        Publish = 1,
+    }
+}
+
+impl OwnerRole {
+    /// The rights that owners with this role have on the crate.
+    pub fn rights(self) -> Rights {
+        match self {
+            OwnerRole::Admin => Rights::Full,
+            OwnerRole::Publish => Rights::Publish,
+        }
+    }
+}
+
+/// Unifies the notion of a User, a Team or an Organization.
 #[derive(Debug)]
 pub enum Owner {
-    User(User),
+    User(User, OwnerRole),
     Team(Team),
+    Organization(Organization),
 }
 
 impl Owner {
@@ -58,15 +85,33 @@ impl Owner {
     /// database, the team isn't found on GitHub, or if the user isn't a member
     /// of the team on GitHub.
     ///
-    /// May be a user's GH login or a full team name. This is case
-    /// sensitive.
+    /// May be a user's GH login, a full team name or an organization name
+    /// prefixed with `org:`. Organizations can only be added by their admins.
+    /// Users are returned with the given `role`. This is case sensitive.
     pub async fn find_or_create_by_login(
         app: &App,
         conn: &mut AsyncPgConnection,
         req_user: &User,
         name: &str,
+        role: OwnerRole,
     ) -> AppResult<Owner> {
-        if name.contains(':') {
+        if let Some(login) = name.strip_prefix(ORGANIZATION_OWNER_PREFIX) {
+            let organization = Organization::find_by_login(conn, login)
+                .await
+                .optional()?
+                .ok_or_else(|| {
+                    bad_request(format_args!("could not find organization `{login}`"))
+                })?;
+
+            if organization.role_of(conn, req_user.id).await? != Some(OrganizationRole::Admin) {
+                return Err(custom(
+                    StatusCode::FORBIDDEN,
+                    format!("only admins of organization `{login}` can add it as a crate owner"),
+                ));
+            }
+
+            Ok(Owner::Organization(organization))
+        } else if name.contains(':') {
             Ok(Owner::Team(
                 Team::create_or_update(app, conn, name, req_user).await?,
             ))
@@ -74,29 +119,32 @@ impl Owner {
             User::find_by_login(conn, name)
                 .await
                 .optional()?
-                .map(Owner::User)
+                .map(|user| Owner::User(user, role))
                 .ok_or_else(|| bad_request(format_args!("could not find user with login `{name}`")))
         }
     }
 
     pub fn kind(&self) -> i32 {
         match self {
-            Owner::User(_) => OwnerKind::User as i32,
+            Owner::User(..) => OwnerKind::User as i32,
             Owner::Team(_) => OwnerKind::Team as i32,
+            Owner::Organization(_) => OwnerKind::Organization as i32,
         }
     }
 
-    pub fn login(&self) -> &str {
+    pub fn login(&self) -> Cow<'_, str> {
         match self {
-            Owner::User(user) => &user.gh_login,
-            Owner::Team(team) => &team.login,
+            Owner::User(user, _) => Cow::Borrowed(&user.gh_login),
+            Owner::Team(team) => Cow::Borrowed(&team.login),
+            Owner::Organization(organization) => Cow::Owned(organization.owner_login()),
         }
     }
 
     pub fn id(&self) -> i32 {
         match self {
-            Owner::User(user) => user.id,
+            Owner::User(user, _) => user.id,
             Owner::Team(team) => team.id,
+            Owner::Organization(organization) => organization.id,
         }
     }
 }
@@ -0,0 +1,95 @@
+use crate::models::User;
+use crate::schema::publish_idempotency_keys;
+use bon::Builder;
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// How long the result of a publish request with an `Idempotency-Key`
+/// header is returned for retries of the request.
+pub const IDEMPOTENCY_KEY_LIFETIME: TimeDelta = TimeDelta::hours(24);
+
+/// The model representing a row in the `publish_idempotency_keys` database
+/// table.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Selectable)]
+#[diesel(
+    table_name = publish_idempotency_keys,
+    primary_key(user_id, key),
+    belongs_to(User),
+    check_for_backend(diesel::pg::Pg)
+)]
+pub struct PublishIdempotencyKey {
+    pub user_id: i32,
+    pub key: String,
+    pub crate_name: String,
+    pub version: String,
+    pub checksum: String,
+    pub response: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PublishIdempotencyKey {
+    /// Returns the result of the publish request of the given user with the
+    /// given key, unless it has expired.
+    pub async fn find(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+        key: &str,
+    ) -> QueryResult<Option<PublishIdempotencyKey>> {
+        let cutoff = Utc::now() - IDEMPOTENCY_KEY_LIFETIME;
+
+        publish_idempotency_keys::table
+            .find((user_id, key))
+            .filter(publish_idempotency_keys::created_at.gt(cutoff))
+            .select(PublishIdempotencyKey::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Deletes all expired keys and returns the number of deleted rows.
+    pub async fn delete_expired(conn: &mut AsyncPgConnection) -> QueryResult<usize> {
+        let cutoff = Utc::now() - IDEMPOTENCY_KEY_LIFETIME;
+
+        diesel::delete(publish_idempotency_keys::table)
+            .filter(publish_idempotency_keys::created_at.le(cutoff))
+            .execute(conn)
+            .await
+    }
+}
+
+#[derive(Insertable, Debug, Builder)]
+#[diesel(table_name = publish_idempotency_keys, check_for_backend(diesel::pg::Pg))]
+pub struct NewPublishIdempotencyKey<'a> {
+    user_id: i32,
+    key: &'a str,
+    crate_name: &'a str,
+    version: &'a str,
+    checksum: &'a str,
+    response: serde_json::Value,
+}
+
+impl NewPublishIdempotencyKey<'_> {
+    /// Inserts the key, replacing an expired key of the same user that
+    /// hasn't been deleted yet.
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::insert_into(publish_idempotency_keys::table)
+            .values(self)
+            .on_conflict((
+                publish_idempotency_keys::user_id,
+                publish_idempotency_keys::key,
+            ))
+            .do_update()
+            .set((
+                publish_idempotency_keys::crate_name.eq(self.crate_name),
+                publish_idempotency_keys::version.eq(self.version),
+                publish_idempotency_keys::checksum.eq(self.checksum),
+                publish_idempotency_keys::response.eq(&self.response),
+                publish_idempotency_keys::created_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
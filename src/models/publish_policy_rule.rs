@@ -0,0 +1,69 @@
+use crate::schema::publish_policy_rules;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum PolicyRuleKind {
+        MaxDependencies = 0,
+        MaxFeatures = 1,
+        BannedLicense = 2,
+        RequiredField = 3,
+    }
+}
+
+/// A rule that all crates have to follow to be published.
+///
+/// The meaning of the `value` depends on the `kind` of the rule, see
+/// [`crate::publish_policy::Rule`].
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = publish_policy_rules, check_for_backend(diesel::pg::Pg))]
+pub struct PublishPolicyRule {
+    pub id: i32,
+    pub kind: PolicyRuleKind,
+    pub value: String,
+    pub message: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub enforced_at: Option<DateTime<Utc>>,
+}
+
+impl PublishPolicyRule {
+    /// Returns `true` if the rule is not enforced yet, in which case crates
+    /// that violate it are only published with a warning.
+    pub fn is_upcoming(&self) -> bool {
+        self.enforced_at
+            .is_some_and(|enforced_at| enforced_at > Utc::now())
+    }
+
+    /// Returns all rules, sorted by ID.
+    pub async fn all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        publish_policy_rules::table
+            .select(Self::as_select())
+            .order(publish_policy_rules::id)
+            .load(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = publish_policy_rules, check_for_backend(diesel::pg::Pg))]
+pub struct NewPublishPolicyRule<'a> {
+    pub kind: PolicyRuleKind,
+    pub value: &'a str,
+    pub message: Option<&'a str>,
+    pub created_by: i32,
+    pub enforced_at: Option<DateTime<Utc>>,
+}
+
+impl NewPublishPolicyRule<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<PublishPolicyRule> {
+        diesel::insert_into(publish_policy_rules::table)
+            .values(self)
+            .returning(PublishPolicyRule::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -0,0 +1,51 @@
+use crate::models::User;
+use crate::rate_limiter::LimitedAction;
+use crate::schema::publish_rate_overrides;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The model representing a row in the `publish_rate_overrides` database table.
+///
+/// An override replaces the default burst of the rate limiter for a single
+/// user and action, optionally until it expires.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Selectable)]
+#[diesel(
+    table_name = publish_rate_overrides,
+    primary_key(user_id, action),
+    belongs_to(User),
+    check_for_backend(diesel::pg::Pg)
+)]
+pub struct PublishRateOverride {
+    pub user_id: i32,
+    pub burst: i32,
+    pub expires_at: Option<NaiveDateTime>,
+    pub action: LimitedAction,
+}
+
+impl PublishRateOverride {
+    /// Inserts the override, replacing any existing override for the same
+    /// user and action.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::insert_into(publish_rate_overrides::table)
+            .values((
+                publish_rate_overrides::user_id.eq(self.user_id),
+                publish_rate_overrides::burst.eq(self.burst),
+                publish_rate_overrides::expires_at.eq(self.expires_at),
+                publish_rate_overrides::action.eq(self.action),
+            ))
+            .on_conflict((
+                publish_rate_overrides::user_id,
+                publish_rate_overrides::action,
+            ))
+            .do_update()
+            .set((
+                publish_rate_overrides::burst.eq(self.burst),
+                publish_rate_overrides::expires_at.eq(self.expires_at),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
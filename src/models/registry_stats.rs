@@ -0,0 +1,37 @@
+use crate::schema::registry_stats;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// Statistics of the whole registry for a single day.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Selectable, Insertable)]
+#[diesel(table_name = registry_stats, check_for_backend(diesel::pg::Pg))]
+pub struct RegistryStats {
+    pub date: NaiveDate,
+    pub total_crates: i64,
+    pub total_versions: i64,
+    pub publishes: i64,
+    pub downloads: i64,
+}
+
+impl RegistryStats {
+    /// Inserts the statistics, or replaces them if they already exist for
+    /// the same day.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::insert_into(registry_stats::table)
+            .values(self)
+            .on_conflict(registry_stats::date)
+            .do_update()
+            .set((
+                registry_stats::total_crates.eq(excluded(registry_stats::total_crates)),
+                registry_stats::total_versions.eq(excluded(registry_stats::total_versions)),
+                registry_stats::publishes.eq(excluded(registry_stats::publishes)),
+                registry_stats::downloads.eq(excluded(registry_stats::downloads)),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use crate::schema::reserved_crate_names;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::canon_crate_name;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// A crate name that can not be used for new crates.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = reserved_crate_names, check_for_backend(diesel::pg::Pg))]
+pub struct ReservedCrateName {
+    pub name: String,
+    pub reason: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ReservedCrateName {
+    /// Returns all reserved crate names, sorted by name.
+    pub async fn all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        reserved_crate_names::table
+            .select(Self::as_select())
+            .order(reserved_crate_names::name)
+            .load(conn)
+            .await
+    }
+
+    /// Finds the reserved name that is equal to the given name after
+    /// canonicalization.
+    pub async fn find_by_name(
+        conn: &mut AsyncPgConnection,
+        name: &str,
+    ) -> QueryResult<Option<Self>> {
+        reserved_crate_names::table
+            .filter(canon_crate_name(reserved_crate_names::name).eq(canon_crate_name(name)))
+            .select(Self::as_select())
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = reserved_crate_names, check_for_backend(diesel::pg::Pg))]
+pub struct NewReservedCrateName<'a> {
+    pub name: &'a str,
+    pub reason: Option<&'a str>,
+    pub created_by: i32,
+}
+
+impl NewReservedCrateName<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<ReservedCrateName> {
+        diesel::insert_into(reserved_crate_names::table)
+            .values(self)
+            .returning(ReservedCrateName::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -0,0 +1,81 @@
+use crate::models::Organization;
+use crate::schema::{organizations, reserved_prefixes};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// A crate name prefix that an organization requested to reserve.
+///
+/// Once approved by an admin, only members of the organization with at
+/// least the publisher role can publish new crates whose name starts with
+/// the prefix. Existing crates are not affected.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = reserved_prefixes, check_for_backend(diesel::pg::Pg))]
+pub struct ReservedPrefix {
+    pub id: i32,
+    pub organization_id: i32,
+    pub prefix: String,
+    pub requested_by: Option<i32>,
+    pub approved_by: Option<i32>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ReservedPrefix {
+    pub fn is_approved(&self) -> bool {
+        self.approved_at.is_some()
+    }
+
+    /// Returns `true` if the crate name starts with the prefix, treating
+    /// `-` and `_` as equal, like crate names themselves.
+    pub fn matches(&self, crate_name: &str) -> bool {
+        canonicalize(crate_name).starts_with(&canonicalize(&self.prefix))
+    }
+
+    /// Returns `true` if one of the prefixes starts with the other one, so
+    /// that both can't be reserved by different organizations.
+    pub fn overlaps(&self, prefix: &str) -> bool {
+        let (a, b) = (canonicalize(&self.prefix), canonicalize(prefix));
+        a.starts_with(&b) || b.starts_with(&a)
+    }
+
+    /// Finds the approved reservation whose prefix matches the crate name,
+    /// together with the organization that reserved it.
+    pub async fn find_approved_match(
+        conn: &mut AsyncPgConnection,
+        crate_name: &str,
+    ) -> QueryResult<Option<(ReservedPrefix, Organization)>> {
+        let approved: Vec<(ReservedPrefix, Organization)> = reserved_prefixes::table
+            .inner_join(organizations::table)
+            .filter(reserved_prefixes::approved_at.is_not_null())
+            .select((ReservedPrefix::as_select(), Organization::as_select()))
+            .load(conn)
+            .await?;
+
+        Ok(approved
+            .into_iter()
+            .find(|(reserved, _)| reserved.matches(crate_name)))
+    }
+}
+
+fn canonicalize(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = reserved_prefixes, check_for_backend(diesel::pg::Pg))]
+pub struct NewReservedPrefix<'a> {
+    pub organization_id: i32,
+    pub prefix: &'a str,
+    pub requested_by: i32,
+}
+
+impl NewReservedPrefix<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<ReservedPrefix> {
+        diesel::insert_into(reserved_prefixes::table)
+            .values(self)
+            .returning(ReservedPrefix::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -0,0 +1,120 @@
+use crate::schema::storage_discrepancies;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum StorageDiscrepancyKind {
+        OrphanedCrateFile = 0,
+        MissingCrateFile = 1,
+        OrphanedIndexFile = 2,
+        MissingIndexFile = 3,
+    }
+}
+
+impl StorageDiscrepancyKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OrphanedCrateFile => "orphaned_crate_file",
+            Self::MissingCrateFile => "missing_crate_file",
+            Self::OrphanedIndexFile => "orphaned_index_file",
+            Self::MissingIndexFile => "missing_index_file",
+        }
+    }
+}
+
+/// A file in the storage that doesn't match the database, as found by the
+/// `ReconcileStorage` background job.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = storage_discrepancies, check_for_backend(diesel::pg::Pg))]
+pub struct StorageDiscrepancy {
+    pub path: String,
+    pub kind: StorageDiscrepancyKind,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+impl StorageDiscrepancy {
+    /// Records that the paths have been found with the given kind of
+    /// discrepancy at `seen_at`.
+    ///
+    /// Paths that have been found before keep their `first_seen_at` date.
+    pub async fn record(
+        conn: &mut AsyncPgConnection,
+        kind: StorageDiscrepancyKind,
+        paths: &[String],
+        seen_at: DateTime<Utc>,
+    ) -> QueryResult<()> {
+        // Postgres limits the number of bind parameters of a statement.
+        for paths in paths.chunks(10_000) {
+            let values = paths
+                .iter()
+                .map(|path| {
+                    (
+                        storage_discrepancies::kind.eq(kind),
+                        storage_discrepancies::path.eq(path),
+                        storage_discrepancies::first_seen_at.eq(seen_at),
+                        storage_discrepancies::last_seen_at.eq(seen_at),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            diesel::insert_into(storage_discrepancies::table)
+                .values(values)
+                .on_conflict((storage_discrepancies::kind, storage_discrepancies::path))
+                .do_update()
+                .set(
+                    storage_discrepancies::last_seen_at
+                        .eq(excluded(storage_discrepancies::last_seen_at)),
+                )
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes all discrepancies that have not been found again since
+    /// `seen_at`, because they have been fixed in the meantime.
+    pub async fn remove_resolved(
+        conn: &mut AsyncPgConnection,
+        seen_at: DateTime<Utc>,
+    ) -> QueryResult<usize> {
+        let query =
+            storage_discrepancies::table.filter(storage_discrepancies::last_seen_at.lt(seen_at));
+
+        diesel::delete(query).execute(conn).await
+    }
+
+    /// Returns the orphaned files of the given kind that have been found for
+    /// the first time before `cutoff`.
+    pub async fn quarantined_orphans(
+        conn: &mut AsyncPgConnection,
+        kind: StorageDiscrepancyKind,
+        cutoff: DateTime<Utc>,
+    ) -> QueryResult<Vec<String>> {
+        storage_discrepancies::table
+            .filter(storage_discrepancies::kind.eq(kind))
+            .filter(storage_discrepancies::first_seen_at.lt(cutoff))
+            .order(storage_discrepancies::path)
+            .select(storage_discrepancies::path)
+            .load(conn)
+            .await
+    }
+
+    /// Removes the paths of the given kind, e.g. after the orphaned files
+    /// have been deleted.
+    pub async fn remove(
+        conn: &mut AsyncPgConnection,
+        kind: StorageDiscrepancyKind,
+        paths: &[String],
+    ) -> QueryResult<usize> {
+        let query = storage_discrepancies::table
+            .filter(storage_discrepancies::kind.eq(kind))
+            .filter(storage_discrepancies::path.eq_any(paths));
+
+        diesel::delete(query).execute(conn).await
+    }
+}
@@ -4,10 +4,10 @@ use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::StatusCode;
 
 use crate::app::App;
-use crate::util::errors::{bad_request, custom, AppResult};
+use crate::github_cache::{self, GitHubRequest};
+use crate::util::errors::{bad_request, custom, not_found, AppResult};
 
-use crates_io_github::GitHubError;
-use oauth2::AccessToken;
+use crates_io_github::{GitHubOrgMembership, GitHubOrganization, GitHubTeam, GitHubTeamMembership};
 
 use crate::models::{Crate, CrateOwner, Owner, OwnerKind, User};
 use crate::schema::{crate_owners, teams};
@@ -126,15 +126,21 @@ impl Team {
             )));
         }
 
-        let token = AccessToken::new(req_user.gh_access_token.clone());
-        let team = app.github.team_by_name(org_name, team_name, &token).await
-            .map_err(|_| {
-                bad_request(format_args!(
+        let request = GitHubRequest::Team {
+            org_name: org_name.to_lowercase(),
+            team_name: team_name.to_lowercase(),
+            login: req_user.gh_login.clone(),
+        };
+        let team: GitHubTeam = match github_cache::cached(app, request, req_user).await {
+            Ok(Some(team)) => team,
+            Ok(None) | Err(_) => {
+                return Err(bad_request(format_args!(
                     "could not find the github team {org_name}/{team_name}. \
                     Make sure that you have the right permissions in GitHub. \
                     See https://doc.rust-lang.org/cargo/reference/publishing.html#github-permissions"
-                ))
-            })?;
+                )))
+            }
+        };
 
         let org_id = team.organization.id;
 
@@ -145,7 +151,12 @@ impl Team {
             ));
         }
 
-        let org = app.github.org_by_name(org_name, &token).await?;
+        let request = GitHubRequest::Organization {
+            org_name: org_name.to_lowercase(),
+        };
+        let org: GitHubOrganization = github_cache::cached(app, request, req_user)
+            .await?
+            .ok_or_else(not_found)?;
 
         NewTeam::builder()
             .login(&login.to_lowercase())
@@ -197,16 +208,14 @@ async fn can_add_team(app: &App, org_id: i32, team_id: i32, user: &User) -> AppR
 }
 
 async fn is_gh_org_owner(app: &App, org_id: i32, user: &User) -> AppResult<bool> {
-    let token = AccessToken::new(user.gh_access_token.clone());
-    match app
-        .github
-        .org_membership(org_id, &user.gh_login, &token)
-        .await
-    {
-        Ok(membership) => Ok(membership.state == "active" && membership.role == "admin"),
-        Err(GitHubError::NotFound(_)) => Ok(false),
-        Err(e) => Err(e.into()),
-    }
+    let request = GitHubRequest::OrgMembership {
+        org_id,
+        login: user.gh_login.clone(),
+    };
+    let membership: Option<GitHubOrgMembership> = github_cache::cached(app, request, user).await?;
+
+    Ok(membership
+        .is_some_and(|membership| membership.state == "active" && membership.role == "admin"))
 }
 
 async fn team_with_gh_id_contains_user(
@@ -218,18 +227,16 @@ async fn team_with_gh_id_contains_user(
     // GET /organizations/:org_id/team/:team_id/memberships/:username
     // check that "state": "active"
 
-    let token = AccessToken::new(user.gh_access_token.clone());
-    let membership = match app
-        .github
-        .team_membership(github_org_id, github_team_id, &user.gh_login, &token)
-        .await
-    {
-        // Officially how `false` is returned
-        Err(GitHubError::NotFound(_)) => return Ok(false),
-        x => x?,
+    let request = GitHubRequest::TeamMembership {
+        org_id: github_org_id,
+        team_id: github_team_id,
+        login: user.gh_login.clone(),
     };
 
+    // A missing membership is officially how `false` is returned
+    let membership: Option<GitHubTeamMembership> = github_cache::cached(app, request, user).await?;
+
     // There is also `state: pending` for which we could possibly give
     // some feedback, but it's not obvious how that should work.
-    Ok(membership.state == "active")
+    Ok(membership.is_some_and(|membership| membership.state == "active"))
 }
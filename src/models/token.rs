@@ -32,6 +32,10 @@ pub struct ApiToken {
     pub endpoint_scopes: Option<Vec<EndpointScope>>,
     #[serde(with = "rfc3339::option")]
     pub expired_at: Option<NaiveDateTime>,
+    /// The organization that owns this token, which restricts it to crates
+    /// owned by the organization.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<i32>,
 }
 
 impl ApiToken {
@@ -51,12 +55,36 @@ impl ApiToken {
         crate_scopes: Option<Vec<CrateScope>>,
         endpoint_scopes: Option<Vec<EndpointScope>>,
         expired_at: Option<NaiveDateTime>,
+    ) -> QueryResult<CreatedApiToken> {
+        Self::insert_for_organization(
+            conn,
+            user_id,
+            None,
+            name,
+            crate_scopes,
+            endpoint_scopes,
+            expired_at,
+        )
+        .await
+    }
+
+    /// Generates a new named API token for a user, which is optionally owned
+    /// by an organization that the user is a member of.
+    pub async fn insert_for_organization(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+        organization_id: Option<i32>,
+        name: &str,
+        crate_scopes: Option<Vec<CrateScope>>,
+        endpoint_scopes: Option<Vec<EndpointScope>>,
+        expired_at: Option<NaiveDateTime>,
     ) -> QueryResult<CreatedApiToken> {
         let token = PlainToken::generate();
 
         let model: ApiToken = diesel::insert_into(api_tokens::table)
             .values((
                 api_tokens::user_id.eq(user_id),
+                api_tokens::organization_id.eq(organization_id),
                 api_tokens::name.eq(name),
                 api_tokens::token.eq(token.hashed()),
                 api_tokens::crate_scopes.eq(crate_scopes),
@@ -135,6 +163,7 @@ mod tests {
             crate_scopes: None,
             endpoint_scopes: None,
             expired_at: None,
+            organization_id: None,
         };
         let json = serde_json::to_string(&tok).unwrap();
         assert_some!(json
@@ -0,0 +1,61 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The time windows that the `trending_crates` table is maintained for.
+///
+/// The downloads of the last `days` complete days are compared with the
+/// downloads of the `days` days before that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrendingWindow {
+    #[default]
+    Week,
+    Month,
+}
+
+impl TrendingWindow {
+    pub const ALL: [TrendingWindow; 2] = [TrendingWindow::Week, TrendingWindow::Month];
+
+    /// The number of days of the window, which is used as the
+    /// `trending_crates.window_days` value.
+    pub fn days(self) -> i32 {
+        match self {
+            TrendingWindow::Week => 7,
+            TrendingWindow::Month => 30,
+        }
+    }
+}
+
+impl fmt::Display for TrendingWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d", self.days())
+    }
+}
+
+impl FromStr for TrendingWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|window| window.to_string() == s)
+            .ok_or_else(|| {
+                let expected = Self::ALL.map(|window| format!("`{window}`")).join(", ");
+                format!("invalid window `{s}`, expected one of {expected}")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!("7d".parse(), Ok(TrendingWindow::Week));
+        assert_eq!("30d".parse(), Ok(TrendingWindow::Month));
+        assert_eq!(
+            "1d".parse::<TrendingWindow>().unwrap_err(),
+            "invalid window `1d`, expected one of `7d`, `30d`"
+        );
+    }
+}
@@ -10,7 +10,7 @@ use crate::email::Emails;
 use crate::util::errors::AppResult;
 
 use crate::models::{Crate, CrateOwner, Email, NewEmail, Owner, OwnerKind, Rights};
-use crate::schema::{crate_owners, emails, users};
+use crate::schema::{crate_owners, emails, linked_identities, users};
 use crates_io_diesel_helpers::lower;
 
 /// The model representing a row in the `users` database table.
@@ -26,6 +26,10 @@ pub struct User {
     pub account_lock_until: Option<NaiveDateTime>,
     pub is_admin: bool,
     pub publish_notifications: bool,
+    pub locale: Option<String>,
+    pub weekly_digest: bool,
+    pub contact_requests: bool,
+    pub public_stats: bool,
 }
 
 impl User {
@@ -33,6 +37,23 @@ impl User {
         users::table.find(id).first(conn).await
     }
 
+    /// Returns the user that the given account of an additional identity
+    /// provider is linked to.
+    pub async fn find_by_linked_identity(
+        conn: &mut AsyncPgConnection,
+        provider: &str,
+        account_id: &str,
+    ) -> QueryResult<Option<User>> {
+        users::table
+            .inner_join(linked_identities::table)
+            .filter(linked_identities::provider.eq(provider))
+            .filter(linked_identities::account_id.eq(account_id))
+            .select(users::all_columns)
+            .first(conn)
+            .await
+            .optional()
+    }
+
     pub async fn find_by_login(conn: &mut AsyncPgConnection, login: &str) -> QueryResult<User> {
         users::table
             .filter(lower(users::gh_login).eq(login.to_lowercase()))
@@ -45,12 +66,12 @@ impl User {
     pub async fn owning(krate: &Crate, conn: &mut AsyncPgConnection) -> QueryResult<Vec<Owner>> {
         let users = CrateOwner::by_owner_kind(OwnerKind::User)
             .inner_join(users::table)
-            .select(User::as_select())
+            .select((User::as_select(), crate_owners::role))
             .filter(crate_owners::crate_id.eq(krate.id))
             .load(conn)
             .await?
             .into_iter()
-            .map(Owner::User);
+            .map(|(user, role)| Owner::User(user, role));
 
         Ok(users.collect())
     }
@@ -63,18 +84,37 @@ impl User {
     /// `Publish` as well, but this is a non-obvious invariant so we don't bother.
     /// Sweet free optimization if teams are proving burdensome to check.
     /// More than one team isn't really expected, though.
-    pub async fn rights(&self, app: &App, owners: &[Owner]) -> AppResult<Rights> {
+    ///
+    /// User owners get the rights of their `OwnerRole`, and members of an
+    /// owning organization get the rights of their role in the organization.
+    pub async fn rights(
+        &self,
+        app: &App,
+        conn: &mut AsyncPgConnection,
+        owners: &[Owner],
+    ) -> AppResult<Rights> {
         let mut best = Rights::None;
         for owner in owners {
             match *owner {
-                Owner::User(ref other_user) => {
+                Owner::User(ref other_user, role) => {
                     if other_user.id == self.id {
-                        return Ok(Rights::Full);
+                        match role.rights() {
+                            Rights::Full => return Ok(Rights::Full),
+                            rights => best = best.max(rights),
+                        }
                     }
                 }
                 Owner::Team(ref team) => {
                     if team.contains_user(app, self).await? {
-                        best = Rights::Publish;
+                        best = best.max(Rights::Publish);
+                    }
+                }
+                Owner::Organization(ref organization) => {
+                    if let Some(role) = organization.role_of(conn, self.id).await? {
+                        match role.crate_rights() {
+                            Rights::Full => return Ok(Rights::Full),
+                            rights => best = best.max(rights),
+                        }
                     }
                 }
             }
@@ -82,7 +122,7 @@ impl User {
         Ok(best)
     }
 
-    /// Queries the database for the verified emails
+    /// Queries the database for the verified primary email
     /// belonging to a given user
     pub async fn verified_email(
         &self,
@@ -90,16 +130,18 @@ impl User {
     ) -> QueryResult<Option<String>> {
         Email::belonging_to(self)
             .select(emails::email)
+            .filter(emails::is_primary)
             .filter(emails::verified.eq(true))
             .first(conn)
             .await
             .optional()
     }
 
-    /// Queries for the email belonging to a particular user
+    /// Queries for the primary email belonging to a particular user
     pub async fn email(&self, conn: &mut AsyncPgConnection) -> QueryResult<Option<String>> {
         Email::belonging_to(self)
             .select(emails::email)
+            .filter(emails::is_primary)
             .first(conn)
             .await
             .optional()
@@ -174,6 +216,7 @@ impl<'a> NewUser<'a> {
                     let new_email = NewEmail {
                         user_id: user.id,
                         email: user_email,
+                        is_primary: true,
                     };
 
                     let token = insert_into(emails::table)
@@ -191,8 +234,10 @@ impl<'a> NewUser<'a> {
                             user_name: &user.gh_login,
                             domain: &emails.domain,
                             token,
+                            locale: user.locale.as_deref(),
                         };
-                        let _ = emails.send(user_email, email).await;
+                        let key = email.idempotency_key(user_email);
+                        let _ = emails.send_idempotent(conn, &key, user_email, email).await;
                     }
                 }
 
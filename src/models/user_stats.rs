@@ -0,0 +1,19 @@
+use crate::schema::user_stats;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+/// Contribution stats of a user, which are computed by the
+/// [`UpdateUserStats`](crate::worker::jobs::UpdateUserStats) background job
+/// for the users that opted in to public stats.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = user_stats, primary_key(user_id), check_for_backend(diesel::pg::Pg))]
+pub struct UserStats {
+    pub user_id: i32,
+    pub total_downloads: i64,
+    /// A list of `{ "year": …, "count": … }` entries, sorted by year.
+    pub publishes_per_year: serde_json::Value,
+    /// A list of `{ "id": …, "login": …, "avatar": …, "shared_crates": … }`
+    /// entries, sorted by the number of shared crates.
+    pub co_owners: serde_json::Value,
+    pub updated_at: DateTime<Utc>,
+}
@@ -1,7 +1,8 @@
 use std::collections::BTreeMap;
 
 use bon::Builder;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
 use crates_io_index::features::FeaturesMap;
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
@@ -11,6 +12,20 @@ use serde::Deserialize;
 use crate::models::{Crate, User};
 use crate::schema::*;
 
+pg_enum! {
+    pub enum DocsRsStatus {
+        Success = 0,
+        Failure = 1,
+    }
+}
+
+pg_enum! {
+    pub enum PublishMethod {
+        ApiToken = 0,
+        Web = 1,
+    }
+}
+
 // Queryable has a custom implementation below
 #[derive(Clone, Identifiable, Associations, Debug, Queryable, Selectable)]
 #[diesel(belongs_to(Crate))]
@@ -37,20 +52,50 @@ pub struct Version {
     pub homepage: Option<String>,
     pub documentation: Option<String>,
     pub repository: Option<String>,
+    pub docs_rs_status: Option<DocsRsStatus>,
+    pub docs_rs_doc_coverage: Option<f32>,
+    pub docs_rs_built_at: Option<DateTime<Utc>>,
+    pub publish_method: Option<PublishMethod>,
 }
 
+type NotDeleted = diesel::dsl::IsNull<versions::deleted_at>;
+
 impl Version {
+    /// SQL filter that excludes versions that have been soft-deleted by the
+    /// crates.io team. Queries that return versions to API clients or to the
+    /// index have to use it.
+    pub fn not_deleted() -> NotDeleted {
+        versions::deleted_at.is_null()
+    }
+
+    /// Checks whether the given value of the `rust-version` manifest field is
+    /// valid, i.e. a plain version number without semver operators or
+    /// pre-release identifiers.
+    pub fn is_valid_rust_version(value: &str) -> bool {
+        semver::VersionReq::parse(value).is_ok()
+            && value.chars().all(|c| c.is_ascii_digit() || c == '.')
+    }
+
+    /// Records that the readme of the version has been rendered and stored
+    /// under the given checksum, or that it was empty if `checksum` is `None`.
     pub async fn record_readme_rendering(
         version_id: i32,
+        checksum: Option<&str>,
         conn: &mut AsyncPgConnection,
     ) -> QueryResult<usize> {
         use diesel::dsl::now;
 
         diesel::insert_into(readme_renderings::table)
-            .values(readme_renderings::version_id.eq(version_id))
+            .values((
+                readme_renderings::version_id.eq(version_id),
+                readme_renderings::checksum.eq(checksum),
+            ))
             .on_conflict(readme_renderings::version_id)
             .do_update()
-            .set(readme_renderings::rendered_at.eq(now))
+            .set((
+                readme_renderings::rendered_at.eq(now),
+                readme_renderings::checksum.eq(checksum),
+            ))
             .execute(conn)
             .await
     }
@@ -84,6 +129,8 @@ pub struct NewVersion<'a> {
     num: &'a str,
     #[builder(default = strip_build_metadata(num))]
     pub num_no_build: &'a str,
+    #[builder(skip = semver_ord(num))]
+    semver_ord: Option<String>,
     created_at: Option<&'a NaiveDateTime>,
     yanked: Option<bool>,
     #[builder(default = serde_json::Value::Object(Default::default()))]
@@ -92,6 +139,7 @@ pub struct NewVersion<'a> {
     #[builder(default, name = "size")]
     crate_size: i32,
     published_by: i32,
+    publish_method: Option<PublishMethod>,
     checksum: &'a str,
     links: Option<&'a str>,
     rust_version: Option<&'a str>,
@@ -145,6 +193,44 @@ fn strip_build_metadata(version: &str) -> &str {
         .unwrap_or(version)
 }
 
+/// Returns a key for the version number that sorts in the same order as the
+/// semver precedence of the versions when compared bytewise, or `None` if the
+/// version number is not valid semver.
+///
+/// The key is saved in the `semver_ord` column of the `versions` table, so
+/// that the database can sort the versions of a crate by semver.
+pub fn semver_ord(num: &str) -> Option<String> {
+    let version = semver::Version::parse(num).ok()?;
+
+    let (major, minor, patch) = (version.major, version.minor, version.patch);
+    let mut key = format!("{major:020}.{minor:020}.{patch:020}");
+
+    if version.pre.is_empty() {
+        // `~` sorts after `-`, so releases have a higher precedence than
+        // their pre-releases
+        key.push('~');
+        return Some(key);
+    }
+
+    key.push('-');
+    for identifier in version.pre.split('.') {
+        if identifier.bytes().all(|b| b.is_ascii_digit()) {
+            // Numeric identifiers have no leading zeros, so prefixing them
+            // with their length makes them sort numerically. They have a
+            // lower precedence than alphanumeric identifiers.
+            key.push_str(&format!("0{:02}{identifier}", identifier.len()));
+        } else {
+            key.push('1');
+            key.push_str(identifier);
+        }
+        // `!` sorts before all characters that are allowed in identifiers,
+        // so a shorter set of identifiers has a lower precedence
+        key.push('!');
+    }
+
+    Some(key)
+}
+
 /// The highest version (semver order) and the most recently updated version.
 /// Typically used for a single crate.
 /// Note: `TopVersion` itself does not guarantee whether versions are yanked or not,
@@ -155,6 +241,8 @@ pub struct TopVersions {
     pub highest: Option<semver::Version>,
     /// The "highest" non-prerelease version
     pub highest_stable: Option<semver::Version>,
+    /// The "highest" prerelease version
+    pub highest_prerelease: Option<semver::Version>,
     /// The "newest" version in terms of publishing date
     pub newest: Option<semver::Version>,
 }
@@ -166,6 +254,28 @@ impl TopVersions {
         Self::from_date_version_pairs(versions.into_iter().map(|v| (v.created_at, v.num)))
     }
 
+    /// Return the top versions for the highest stable and pre-release
+    /// versions, as saved in the `default_versions` table, and the newest
+    /// version, without loading all versions of a crate.
+    pub fn from_latest(
+        stable: Option<&str>,
+        prerelease: Option<&str>,
+        newest: Option<&str>,
+    ) -> Self {
+        let parse = |version: Option<&str>| version.and_then(|v| semver::Version::parse(v).ok());
+
+        let highest_stable = parse(stable);
+        let highest_prerelease = parse(prerelease);
+        let highest = highest_stable.clone().max(highest_prerelease.clone());
+
+        Self {
+            highest,
+            highest_stable,
+            highest_prerelease,
+            newest: parse(newest),
+        }
+    }
+
     /// Return both the newest (most recently updated) and the
     /// highest version (in semver order) for a collection of date/version pairs.
     pub fn from_date_version_pairs<T>(pairs: T) -> Self
@@ -190,10 +300,17 @@ impl TopVersions {
             .filter(|v| v.pre.is_empty())
             .max()
             .cloned();
+        let highest_prerelease = pairs
+            .iter()
+            .map(|(_, v)| v)
+            .filter(|v| !v.pre.is_empty())
+            .max()
+            .cloned();
 
         Self {
             highest,
             highest_stable,
+            highest_prerelease,
             newest,
         }
     }
@@ -201,7 +318,7 @@ impl TopVersions {
 
 #[cfg(test)]
 mod tests {
-    use super::TopVersions;
+    use super::{semver_ord, TopVersions};
     use chrono::NaiveDateTime;
 
     #[track_caller]
@@ -222,6 +339,7 @@ mod tests {
             TopVersions {
                 highest: None,
                 highest_stable: None,
+                highest_prerelease: None,
                 newest: None,
             }
         );
@@ -235,6 +353,7 @@ mod tests {
             TopVersions {
                 highest: Some(version("1.0.0")),
                 highest_stable: Some(version("1.0.0")),
+                highest_prerelease: None,
                 newest: Some(version("1.0.0")),
             }
         );
@@ -248,11 +367,34 @@ mod tests {
             TopVersions {
                 highest: Some(version("1.0.0-beta.5")),
                 highest_stable: None,
+                highest_prerelease: Some(version("1.0.0-beta.5")),
                 newest: Some(version("1.0.0-beta.5")),
             }
         );
     }
 
+    #[test]
+    fn top_versions_from_latest() {
+        assert_eq!(
+            TopVersions::from_latest(Some("1.1.0"), Some("2.0.0-alpha.1"), Some("1.0.4")),
+            TopVersions {
+                highest: Some(version("2.0.0-alpha.1")),
+                highest_stable: Some(version("1.1.0")),
+                highest_prerelease: Some(version("2.0.0-alpha.1")),
+                newest: Some(version("1.0.4")),
+            }
+        );
+        assert_eq!(
+            TopVersions::from_latest(Some("1.1.0"), Some("1.0.0-beta.1"), None),
+            TopVersions {
+                highest: Some(version("1.1.0")),
+                highest_stable: Some(version("1.1.0")),
+                highest_prerelease: Some(version("1.0.0-beta.1")),
+                newest: None,
+            }
+        );
+    }
+
     #[test]
     fn top_versions_multiple() {
         let versions = vec![
@@ -267,8 +409,49 @@ mod tests {
             TopVersions {
                 highest: Some(version("2.0.0-alpha.1")),
                 highest_stable: Some(version("1.1.0")),
+                highest_prerelease: Some(version("2.0.0-alpha.1")),
                 newest: Some(version("1.0.4")),
             }
         );
     }
+
+    #[test]
+    fn semver_ord_matches_semver_precedence() {
+        let versions = [
+            "0.0.1",
+            "0.2.0",
+            "0.10.0",
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.2",
+            "1.0.0-alpha.10",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-beta11",
+            "1.0.0-beta2",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "2.0.0-0",
+            "2.0.0-a",
+            "2.0.0",
+            "10.0.0",
+            "18446744073709551615.0.0",
+        ];
+
+        let keys = versions.map(|num| semver_ord(num).unwrap());
+        for (i, pair) in keys.windows(2).enumerate() {
+            assert!(
+                pair[0] < pair[1],
+                "{} should sort before {}",
+                versions[i],
+                versions[i + 1]
+            );
+        }
+
+        assert_eq!(semver_ord("1.0.0+build"), semver_ord("1.0.0"));
+        assert_eq!(semver_ord("everything is broken"), None);
+    }
 }
@@ -0,0 +1,42 @@
+use crate::schema::version_diffs;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// Summary of the changes in the `.crate` file of a version compared to the
+/// previous release.
+#[derive(Debug, Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = version_diffs, check_for_backend(diesel::pg::Pg))]
+pub struct VersionDiff {
+    pub version_id: i32,
+    pub previous_version_id: i32,
+    pub files_added: i32,
+    pub files_removed: i32,
+    pub files_modified: i32,
+    pub size_delta: i64,
+    /// A list of [`FileChange`](crate::tarball_diff::FileChange) entries.
+    pub files: serde_json::Value,
+}
+
+impl VersionDiff {
+    /// Inserts the diff, or replaces it if the version already has one.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::insert_into(version_diffs::table)
+            .values(self)
+            .on_conflict(version_diffs::version_id)
+            .do_update()
+            .set((
+                version_diffs::previous_version_id.eq(excluded(version_diffs::previous_version_id)),
+                version_diffs::files_added.eq(excluded(version_diffs::files_added)),
+                version_diffs::files_removed.eq(excluded(version_diffs::files_removed)),
+                version_diffs::files_modified.eq(excluded(version_diffs::files_modified)),
+                version_diffs::size_delta.eq(excluded(version_diffs::size_delta)),
+                version_diffs::files.eq(excluded(version_diffs::files)),
+                version_diffs::created_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::schema::version_files;
+use crate::tarball_diff::FileInfo;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::BTreeMap;
+
+/// A single file in the `.crate` file of a version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionFile {
+    /// The path of the file, relative to the package root directory.
+    pub path: String,
+    /// The size of the file in bytes.
+    pub size: u64,
+}
+
+/// The listing of the files in the `.crate` file of a version.
+#[derive(Debug, Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = version_files, check_for_backend(diesel::pg::Pg))]
+pub struct VersionFiles {
+    pub version_id: i32,
+    /// A list of [`VersionFile`] entries, sorted by path.
+    pub files: serde_json::Value,
+}
+
+impl VersionFiles {
+    pub fn new(version_id: i32, files: &BTreeMap<String, FileInfo>) -> serde_json::Result<Self> {
+        let files = files
+            .iter()
+            .map(|(path, file)| VersionFile {
+                path: path.clone(),
+                size: file.size,
+            })
+            .collect::<Vec<_>>();
+
+        let files = serde_json::to_value(files)?;
+        Ok(Self { version_id, files })
+    }
+
+    pub fn files(&self) -> serde_json::Result<Vec<VersionFile>> {
+        serde_json::from_value(self.files.clone())
+    }
+
+    /// Inserts the listing, unless the version already has one.
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::insert_into(version_files::table)
+            .values(self)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
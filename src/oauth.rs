@@ -0,0 +1,186 @@
+//! OAuth identity providers that can be used to log in to crates.io.
+//!
+//! GitHub is the primary identity provider, and every crates.io account is
+//! backed by a GitHub account. Additional providers like GitLab and Google
+//! can be linked to an existing account and then be used to log in to it.
+
+use crate::config;
+use crate::util::errors::AppResult;
+use async_trait::async_trait;
+use crates_io_github::GitHubClient;
+use oauth2::basic::BasicClient;
+use oauth2::{
+    AccessToken, AuthorizationCode, CsrfToken, EndpointNotSet, EndpointSet, Scope, TokenResponse,
+};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use url::Url;
+
+mod github;
+mod gitlab;
+mod google;
+
+pub use self::github::GitHubProvider;
+pub use self::gitlab::GitLabProvider;
+pub use self::google::GoogleProvider;
+
+/// An OAuth client with the authorization and token endpoints configured.
+pub type OAuthClient =
+    BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>;
+
+/// The identity providers supported by crates.io.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityProvider {
+    GitHub,
+    GitLab,
+    Google,
+}
+
+impl IdentityProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+            Self::Google => "google",
+        }
+    }
+
+    /// The human-readable name of the provider, used in error messages.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::GitHub => "GitHub",
+            Self::GitLab => "GitLab",
+            Self::Google => "Google",
+        }
+    }
+}
+
+impl fmt::Display for IdentityProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for IdentityProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "github" => Ok(Self::GitHub),
+            "gitlab" => Ok(Self::GitLab),
+            "google" => Ok(Self::Google),
+            _ => Err(anyhow::anyhow!("unknown identity provider: {s}")),
+        }
+    }
+}
+
+/// The account information returned by an identity provider after a
+/// successful login.
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub provider: IdentityProvider,
+    /// Unique and stable account ID at the identity provider.
+    pub account_id: String,
+    pub login: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+/// An OAuth identity provider.
+///
+/// The authorization code flow is the same for all providers, so
+/// implementations only have to supply their configured client, the scopes
+/// to request and a way to look up the account belonging to an access token.
+#[async_trait]
+pub trait OAuthProvider: Send + Sync {
+    fn provider(&self) -> IdentityProvider;
+
+    fn client(&self) -> &OAuthClient;
+
+    fn scopes(&self) -> &[&'static str];
+
+    /// Fetches the account information belonging to the given access token.
+    async fn fetch_identity(&self, token: &AccessToken) -> AppResult<ExternalIdentity>;
+
+    /// Returns the URL that the user should be redirected to, together with
+    /// the randomly generated `state` secret.
+    fn authorize_url(&self) -> (Url, CsrfToken) {
+        let scopes = self.scopes().iter().map(|s| Scope::new(s.to_string()));
+
+        self.client()
+            .authorize_url(CsrfToken::new_random)
+            .add_scopes(scopes)
+            .url()
+    }
+
+    /// Exchanges the temporary authorization `code` for an access token.
+    async fn exchange_code(&self, code: AuthorizationCode) -> anyhow::Result<AccessToken> {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        let response = self
+            .client()
+            .exchange_code(code)
+            .request_async(&client)
+            .await?;
+
+        Ok(response.access_token().clone())
+    }
+}
+
+/// All identity providers that are configured for this instance.
+pub struct OAuthProviders {
+    github: GitHubProvider,
+    gitlab: Option<GitLabProvider>,
+    google: Option<GoogleProvider>,
+}
+
+impl OAuthProviders {
+    pub fn new(config: &config::Server, github: Arc<dyn GitHubClient>) -> Self {
+        let github = GitHubProvider::new(
+            config.gh_client_id.clone(),
+            config.gh_client_secret.clone(),
+            github,
+        );
+
+        let gitlab = config.gitlab_oauth.as_ref().map(GitLabProvider::new);
+
+        let google = config.google_oauth.as_ref().map(GoogleProvider::new);
+
+        Self {
+            github,
+            gitlab,
+            google,
+        }
+    }
+
+    /// Returns the given provider, or `None` if it is not configured.
+    pub fn get(&self, provider: IdentityProvider) -> Option<&dyn OAuthProvider> {
+        match provider {
+            IdentityProvider::GitHub => Some(&self.github),
+            IdentityProvider::GitLab => self.gitlab.as_ref().map(|p| p as &dyn OAuthProvider),
+            IdentityProvider::Google => self.google.as_ref().map(|p| p as &dyn OAuthProvider),
+        }
+    }
+}
+
+/// Sends an authenticated `GET` request to a user info endpoint and
+/// deserializes the JSON response.
+async fn fetch_user_info<T: serde::de::DeserializeOwned>(
+    url: &str,
+    token: &AccessToken,
+) -> reqwest::Result<T> {
+    reqwest::Client::new()
+        .get(url)
+        .bearer_auth(token.secret())
+        .header(reqwest::header::USER_AGENT, "crates.io (https://crates.io)")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+}
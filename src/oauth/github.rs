@@ -0,0 +1,58 @@
+use super::{ExternalIdentity, IdentityProvider, OAuthClient, OAuthProvider};
+use crate::util::errors::AppResult;
+use async_trait::async_trait;
+use crates_io_github::GitHubClient;
+use oauth2::basic::BasicClient;
+use oauth2::{AccessToken, AuthUrl, ClientId, ClientSecret, TokenUrl};
+use std::sync::Arc;
+
+const AUTH_URL: &str = "https://github.com/login/oauth/authorize";
+const TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+
+pub struct GitHubProvider {
+    client: OAuthClient,
+    github: Arc<dyn GitHubClient>,
+}
+
+impl GitHubProvider {
+    pub fn new(
+        client_id: ClientId,
+        client_secret: ClientSecret,
+        github: Arc<dyn GitHubClient>,
+    ) -> Self {
+        let client = BasicClient::new(client_id)
+            .set_client_secret(client_secret)
+            .set_auth_uri(AuthUrl::new(AUTH_URL.into()).unwrap())
+            .set_token_uri(TokenUrl::new(TOKEN_URL.into()).unwrap());
+
+        Self { client, github }
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for GitHubProvider {
+    fn provider(&self) -> IdentityProvider {
+        IdentityProvider::GitHub
+    }
+
+    fn client(&self) -> &OAuthClient {
+        &self.client
+    }
+
+    fn scopes(&self) -> &[&'static str] {
+        &["read:org"]
+    }
+
+    async fn fetch_identity(&self, token: &AccessToken) -> AppResult<ExternalIdentity> {
+        let user = self.github.current_user(token).await?;
+
+        Ok(ExternalIdentity {
+            provider: IdentityProvider::GitHub,
+            account_id: user.id.to_string(),
+            login: user.login,
+            name: user.name,
+            email: user.email,
+            avatar_url: user.avatar_url,
+        })
+    }
+}
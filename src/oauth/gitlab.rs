@@ -0,0 +1,106 @@
+use super::{fetch_user_info, ExternalIdentity, IdentityProvider, OAuthClient, OAuthProvider};
+use crate::config::OAuthClientConfig;
+use crate::util::errors::AppResult;
+use async_trait::async_trait;
+use oauth2::basic::BasicClient;
+use oauth2::{AccessToken, AuthUrl, RedirectUrl, TokenUrl};
+
+const AUTH_URL: &str = "https://gitlab.com/oauth/authorize";
+const TOKEN_URL: &str = "https://gitlab.com/oauth/token";
+const USER_URL: &str = "https://gitlab.com/api/v4/user";
+
+pub struct GitLabProvider {
+    client: OAuthClient,
+}
+
+impl GitLabProvider {
+    pub fn new(config: &OAuthClientConfig) -> Self {
+        let client = BasicClient::new(config.client_id.clone())
+            .set_client_secret(config.client_secret.clone())
+            .set_auth_uri(AuthUrl::new(AUTH_URL.into()).unwrap())
+            .set_token_uri(TokenUrl::new(TOKEN_URL.into()).unwrap())
+            .set_redirect_uri(RedirectUrl::new(config.redirect_url.clone()).unwrap());
+
+        Self { client }
+    }
+}
+
+/// The subset of <https://docs.gitlab.com/ee/api/users.html#for-normal-users-1>
+/// that we are interested in.
+#[derive(Debug, Deserialize)]
+struct GitLabUser {
+    id: i64,
+    username: String,
+    name: Option<String>,
+    email: Option<String>,
+    avatar_url: Option<String>,
+}
+
+impl From<GitLabUser> for ExternalIdentity {
+    fn from(user: GitLabUser) -> Self {
+        ExternalIdentity {
+            provider: IdentityProvider::GitLab,
+            account_id: user.id.to_string(),
+            login: user.username,
+            name: user.name,
+            email: user.email,
+            avatar_url: user.avatar_url,
+        }
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for GitLabProvider {
+    fn provider(&self) -> IdentityProvider {
+        IdentityProvider::GitLab
+    }
+
+    fn client(&self) -> &OAuthClient {
+        &self.client
+    }
+
+    fn scopes(&self) -> &[&'static str] {
+        &["read_user"]
+    }
+
+    async fn fetch_identity(&self, token: &AccessToken) -> AppResult<ExternalIdentity> {
+        let user: GitLabUser = fetch_user_info(USER_URL, token).await?;
+        Ok(user.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn test_parse_user() {
+        let json = r#"{
+            "id": 1234,
+            "username": "ferris",
+            "name": "Ferris the Crab",
+            "state": "active",
+            "avatar_url": "https://gitlab.com/uploads/-/system/user/avatar/1234/avatar.png",
+            "email": "ferris@example.com"
+        }"#;
+
+        let user: GitLabUser = serde_json::from_str(json).unwrap();
+        assert_debug_snapshot!(ExternalIdentity::from(user), @r#"
+        ExternalIdentity {
+            provider: GitLab,
+            account_id: "1234",
+            login: "ferris",
+            name: Some(
+                "Ferris the Crab",
+            ),
+            email: Some(
+                "ferris@example.com",
+            ),
+            avatar_url: Some(
+                "https://gitlab.com/uploads/-/system/user/avatar/1234/avatar.png",
+            ),
+        }
+        "#);
+    }
+}
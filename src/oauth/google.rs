@@ -0,0 +1,100 @@
+use super::{fetch_user_info, ExternalIdentity, IdentityProvider, OAuthClient, OAuthProvider};
+use crate::config::OAuthClientConfig;
+use crate::util::errors::AppResult;
+use async_trait::async_trait;
+use oauth2::basic::BasicClient;
+use oauth2::{AccessToken, AuthUrl, RedirectUrl, TokenUrl};
+
+const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const USER_URL: &str = "https://openidconnect.googleapis.com/v1/userinfo";
+
+pub struct GoogleProvider {
+    client: OAuthClient,
+}
+
+impl GoogleProvider {
+    pub fn new(config: &OAuthClientConfig) -> Self {
+        let client = BasicClient::new(config.client_id.clone())
+            .set_client_secret(config.client_secret.clone())
+            .set_auth_uri(AuthUrl::new(AUTH_URL.into()).unwrap())
+            .set_token_uri(TokenUrl::new(TOKEN_URL.into()).unwrap())
+            .set_redirect_uri(RedirectUrl::new(config.redirect_url.clone()).unwrap());
+
+        Self { client }
+    }
+}
+
+/// The standard OpenID Connect claims returned by the Google user info
+/// endpoint.
+#[derive(Debug, Deserialize)]
+struct GoogleUser {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+    picture: Option<String>,
+}
+
+impl From<GoogleUser> for ExternalIdentity {
+    fn from(user: GoogleUser) -> Self {
+        // Google accounts have no username, so we fall back to the email
+        // address or the display name to identify the account to the user.
+        let login = user
+            .email
+            .clone()
+            .or_else(|| user.name.clone())
+            .unwrap_or_else(|| user.sub.clone());
+
+        ExternalIdentity {
+            provider: IdentityProvider::Google,
+            account_id: user.sub,
+            login,
+            name: user.name,
+            email: user.email.filter(|_| user.email_verified),
+            avatar_url: user.picture,
+        }
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for GoogleProvider {
+    fn provider(&self) -> IdentityProvider {
+        IdentityProvider::Google
+    }
+
+    fn client(&self) -> &OAuthClient {
+        &self.client
+    }
+
+    fn scopes(&self) -> &[&'static str] {
+        &["openid", "email", "profile"]
+    }
+
+    async fn fetch_identity(&self, token: &AccessToken) -> AppResult<ExternalIdentity> {
+        let user: GoogleUser = fetch_user_info(USER_URL, token).await?;
+        Ok(user.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unverified_email_is_ignored() {
+        let json = r#"{
+            "sub": "110169484474386276334",
+            "email": "ferris@example.com",
+            "email_verified": false,
+            "name": "Ferris the Crab"
+        }"#;
+
+        let user: GoogleUser = serde_json::from_str(json).unwrap();
+        let identity = ExternalIdentity::from(user);
+        assert_eq!(identity.account_id, "110169484474386276334");
+        assert_eq!(identity.login, "ferris@example.com");
+        assert_eq!(identity.email, None);
+    }
+}
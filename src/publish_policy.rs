@@ -0,0 +1,350 @@
+//! Publish policy rules, which every new version has to follow.
+//!
+//! The rules are stored in the `publish_policy_rules` table and managed by
+//! the crates.io team through the admin API. Unlike the hardcoded limits of
+//! the publish endpoint, they can be changed without a deploy.
+//!
+//! All rules are checked, so that the violations of all of them can be
+//! reported to cargo at once. Rules with an `enforced_at` date in the future
+//! are not checked here, but reported as warnings by the
+//! [`publish_warnings`](crate::publish_warnings) module instead.
+
+use crate::licenses::parse_license_expr;
+use crate::models::{PolicyRuleKind, PublishPolicyRule};
+use crate::util::errors::{AppError, BoxedAppError, ErrorCode};
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum_extra::json;
+use http::StatusCode;
+use spdx::LicenseItem;
+use std::fmt;
+
+/// The manifest fields that can be required by a rule, using their names
+/// in the `[package]` section of `Cargo.toml`.
+pub const REQUIRED_FIELDS: &[&str] = &[
+    "categories",
+    "description",
+    "documentation",
+    "homepage",
+    "keywords",
+    "readme",
+    "repository",
+    "rust-version",
+];
+
+/// The parsed form of a [`PublishPolicyRule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    MaxDependencies(usize),
+    MaxFeatures(usize),
+    /// The SPDX identifier of the banned license.
+    BannedLicense(&'static str),
+    RequiredField(&'static str),
+}
+
+impl Rule {
+    /// Parses the value of a rule, returning an error message suitable for
+    /// the admin that is creating the rule if it is invalid.
+    pub fn parse(kind: PolicyRuleKind, value: &str) -> Result<Self, String> {
+        match kind {
+            PolicyRuleKind::MaxDependencies => parse_limit(value).map(Self::MaxDependencies),
+            PolicyRuleKind::MaxFeatures => parse_limit(value).map(Self::MaxFeatures),
+            PolicyRuleKind::BannedLicense => spdx::license_id(value)
+                .map(|id| Self::BannedLicense(id.name))
+                .ok_or_else(|| format!("`{value}` is not an SPDX license identifier")),
+            PolicyRuleKind::RequiredField => REQUIRED_FIELDS
+                .iter()
+                .copied()
+                .find(|field| *field == value)
+                .map(Self::RequiredField)
+                .ok_or_else(|| {
+                    let fields = REQUIRED_FIELDS.join("`, `");
+                    format!("`{value}` is not one of the supported fields: `{fields}`")
+                }),
+        }
+    }
+
+    /// Returns a description of the violation, if the crate violates the
+    /// rule.
+    fn check(&self, krate: &CrateMetadata<'_>) -> Option<String> {
+        match *self {
+            Self::MaxDependencies(max) if krate.num_dependencies > max => Some(format!(
+                "the crate has {} dependencies, but the publish policy of crates.io only allows {max}",
+                krate.num_dependencies
+            )),
+            Self::MaxFeatures(max) if krate.num_features > max => Some(format!(
+                "the crate declares {} features, but the publish policy of crates.io only allows {max}",
+                krate.num_features
+            )),
+            Self::BannedLicense(banned) if krate.requires_license(banned) => Some(format!(
+                "the crate can not be used without the `{banned}` license, which is not allowed by the publish policy of crates.io"
+            )),
+            Self::RequiredField(field) if krate.is_missing(field) => Some(format!(
+                "the publish policy of crates.io requires the `{field}` field in the `[package]` section of `Cargo.toml`"
+            )),
+            _ => None,
+        }
+    }
+}
+
+fn parse_limit(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a valid non-negative number"))
+}
+
+/// The parts of the crate metadata that the rules are checked against.
+#[derive(Debug, Default)]
+pub struct CrateMetadata<'a> {
+    pub num_dependencies: usize,
+    pub num_features: usize,
+    /// The SPDX license expression, which is `None` for crates that only
+    /// have a license file.
+    pub license: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub documentation: Option<&'a str>,
+    pub homepage: Option<&'a str>,
+    pub readme: Option<&'a str>,
+    pub repository: Option<&'a str>,
+    pub rust_version: Option<&'a str>,
+    pub keywords: &'a [String],
+    pub categories: &'a [String],
+}
+
+impl CrateMetadata<'_> {
+    fn is_missing(&self, field: &str) -> bool {
+        let value = match field {
+            "categories" => return self.categories.is_empty(),
+            "keywords" => return self.keywords.is_empty(),
+            "description" => self.description,
+            "documentation" => self.documentation,
+            "homepage" => self.homepage,
+            "readme" => self.readme,
+            "repository" => self.repository,
+            "rust-version" => self.rust_version,
+            _ => return false,
+        };
+
+        value.is_none_or(|value| value.trim().is_empty())
+    }
+
+    /// Returns `true` if the license expression can't be satisfied without
+    /// the given license, e.g. `MIT AND GPL-3.0-only` for `GPL-3.0-only`, but
+    /// not `MIT OR GPL-3.0-only`.
+    fn requires_license(&self, banned: &str) -> bool {
+        let Some(expression) = self
+            .license
+            .and_then(|license| parse_license_expr(license).ok())
+        else {
+            return false;
+        };
+
+        !expression.evaluate(|req| match &req.license {
+            LicenseItem::Spdx { id, .. } => !id.name.eq_ignore_ascii_case(banned),
+            _ => true,
+        })
+    }
+}
+
+/// A rule that a crate violates.
+#[derive(Debug, Serialize)]
+pub struct PolicyViolation {
+    pub detail: String,
+    pub rule_id: i32,
+    pub kind: PolicyRuleKind,
+}
+
+/// Checks the crate against all enforced rules, returning the violations.
+pub fn check(rules: &[PublishPolicyRule], krate: &CrateMetadata<'_>) -> Vec<PolicyViolation> {
+    rules
+        .iter()
+        .filter(|rule| !rule.is_upcoming())
+        .filter_map(|rule| violation(rule, krate))
+        .collect()
+}
+
+/// Checks the crate against a single rule, regardless of whether it is
+/// enforced yet.
+///
+/// Rules with an invalid value are skipped, since they can't be created
+/// through the admin API.
+pub fn violation(rule: &PublishPolicyRule, krate: &CrateMetadata<'_>) -> Option<PolicyViolation> {
+    let parsed = Rule::parse(rule.kind, &rule.value)
+        .inspect_err(|error| warn!("Invalid publish policy rule {}: {error}", rule.id))
+        .ok()?;
+
+    let mut detail = parsed.check(krate)?;
+    if let Some(message) = &rule.message {
+        detail = format!("{detail}\n\n{message}");
+    }
+
+    Some(PolicyViolation {
+        detail,
+        rule_id: rule.id,
+        kind: rule.kind,
+    })
+}
+
+/// The error returned by the publish endpoint if the crate violates any
+/// rules.
+///
+/// Every violation is reported as a separate error, which cargo shows
+/// together, with the ID and kind of the rule for other clients.
+#[derive(Debug)]
+pub struct PolicyViolations(pub Vec<PolicyViolation>);
+
+impl PolicyViolations {
+    pub fn boxed(violations: Vec<PolicyViolation>) -> BoxedAppError {
+        Box::new(Self(violations))
+    }
+}
+
+impl AppError for PolicyViolations {
+    fn response(&self) -> Response {
+        let code = ErrorCode("publish-policy-violation");
+        let response = (StatusCode::BAD_REQUEST, json!({ "errors": self.0 }));
+        (Extension(code), response).into_response()
+    }
+}
+
+impl fmt::Display for PolicyViolations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "the crate violates the publish policy".fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, Utc};
+
+    fn rule(id: i32, kind: PolicyRuleKind, value: &str) -> PublishPolicyRule {
+        PublishPolicyRule {
+            id,
+            kind,
+            value: value.to_string(),
+            message: None,
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            enforced_at: None,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        use PolicyRuleKind::*;
+
+        assert_eq!(
+            Rule::parse(MaxDependencies, "10"),
+            Ok(Rule::MaxDependencies(10))
+        );
+        assert_eq!(Rule::parse(MaxFeatures, "0"), Ok(Rule::MaxFeatures(0)));
+        assert_err!(Rule::parse(MaxFeatures, "-1"));
+        assert_err!(Rule::parse(MaxFeatures, "many"));
+        assert_eq!(
+            Rule::parse(BannedLicense, "GPL-3.0"),
+            Ok(Rule::BannedLicense("GPL-3.0"))
+        );
+        assert_err!(Rule::parse(BannedLicense, "not-a-license"));
+        assert_eq!(
+            Rule::parse(RequiredField, "repository"),
+            Ok(Rule::RequiredField("repository"))
+        );
+        assert_err!(Rule::parse(RequiredField, "authors"));
+    }
+
+    #[test]
+    fn test_limits() {
+        let rules = [
+            rule(1, PolicyRuleKind::MaxDependencies, "2"),
+            rule(2, PolicyRuleKind::MaxFeatures, "1"),
+        ];
+
+        let krate = CrateMetadata {
+            num_dependencies: 2,
+            num_features: 1,
+            ..Default::default()
+        };
+        assert!(check(&rules, &krate).is_empty());
+
+        let krate = CrateMetadata {
+            num_dependencies: 3,
+            num_features: 2,
+            ..Default::default()
+        };
+        let violations = check(&rules, &krate);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].rule_id, 1);
+        assert_eq!(violations[1].rule_id, 2);
+    }
+
+    #[test]
+    fn test_upcoming_rules() {
+        let mut upcoming = rule(1, PolicyRuleKind::MaxFeatures, "1");
+        upcoming.enforced_at = Some(Utc::now() + TimeDelta::days(30));
+        let mut enforced = rule(2, PolicyRuleKind::MaxDependencies, "1");
+        enforced.enforced_at = Some(Utc::now() - TimeDelta::days(30));
+        let rules = [upcoming, enforced];
+
+        let krate = CrateMetadata {
+            num_dependencies: 2,
+            num_features: 2,
+            ..Default::default()
+        };
+        let violations = check(&rules, &krate);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, 2);
+
+        assert_some!(violation(&rules[0], &krate));
+    }
+
+    #[test]
+    fn test_banned_license() {
+        let rules = [rule(1, PolicyRuleKind::BannedLicense, "GPL-3.0-only")];
+
+        let check_license = |license| {
+            let krate = CrateMetadata {
+                license,
+                ..Default::default()
+            };
+            check(&rules, &krate).len()
+        };
+
+        assert_eq!(check_license(Some("GPL-3.0-only")), 1);
+        assert_eq!(check_license(Some("MIT AND GPL-3.0-only")), 1);
+        assert_eq!(check_license(Some("MIT OR GPL-3.0-only")), 0);
+        assert_eq!(check_license(Some("MIT/GPL-3.0-only")), 0);
+        assert_eq!(check_license(Some("MIT")), 0);
+        assert_eq!(check_license(None), 0);
+    }
+
+    #[test]
+    fn test_required_fields() {
+        let mut repository = rule(1, PolicyRuleKind::RequiredField, "repository");
+        repository.message = Some("Please link to the source code.".to_string());
+        let rules = [
+            repository,
+            rule(2, PolicyRuleKind::RequiredField, "keywords"),
+        ];
+
+        let keywords = ["parser".to_string()];
+        let krate = CrateMetadata {
+            repository: Some("https://github.com/rust-lang/crates.io"),
+            keywords: &keywords,
+            ..Default::default()
+        };
+        assert!(check(&rules, &krate).is_empty());
+
+        let krate = CrateMetadata {
+            repository: Some(" "),
+            ..Default::default()
+        };
+        let violations = check(&rules, &krate);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(
+            violations[0].detail,
+            "the publish policy of crates.io requires the `repository` field in the `[package]` section of `Cargo.toml`\n\nPlease link to the source code."
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Warnings that the publish endpoint returns together with the published
+//! crate.
+//!
+//! Unlike the errors of the publish endpoint, warnings don't prevent the
+//! crate from being published. Cargo shows the messages of all warnings,
+//! which are returned in the `other` list of the response, while the
+//! `structured` list includes the code and category of every warning for
+//! other clients.
+//!
+//! Most warnings are produced by validators for the crate metadata, but the
+//! crates.io team can also add broadcast warnings through the admin API,
+//! which are returned for every publish, e.g. to announce upcoming changes.
+
+use crate::licenses::parse_license_expr;
+use crate::models::{BroadcastWarning, PublishPolicyRule};
+use crate::publish_policy::{self, CrateMetadata};
+use crate::views::{PublishWarning, PublishWarningCategory};
+use spdx::LicenseItem;
+
+/// Crates whose `.crate` file has at least this percentage of the maximum
+/// upload size get a warning, before their next version is rejected.
+const LARGE_TARBALL_PERCENTAGE: u64 = 80;
+
+const LICENSE_DOCS_URL: &str = "https://spdx.org/licenses/";
+const REPOSITORY_DOCS_URL: &str =
+    "https://doc.rust-lang.org/cargo/reference/manifest.html#the-repository-field";
+const EXCLUDE_DOCS_URL: &str =
+    "https://doc.rust-lang.org/cargo/reference/manifest.html#the-exclude-and-include-fields";
+
+/// The size of the uploaded `.crate` file, and the maximum size that the
+/// crate is allowed to upload.
+#[derive(Debug, Clone, Copy)]
+pub struct TarballSize {
+    pub size: u64,
+    pub max_upload_size: u64,
+}
+
+/// Runs all validators against the crate and returns their warnings,
+/// followed by the active broadcast warnings.
+pub fn check(
+    krate: &CrateMetadata<'_>,
+    tarball: TarballSize,
+    rules: &[PublishPolicyRule],
+    broadcasts: &[BroadcastWarning],
+) -> Vec<PublishWarning> {
+    let mut warnings = deprecated_licenses(krate);
+    warnings.extend(missing_repository(krate));
+    warnings.extend(large_tarball(tarball));
+    warnings.extend(upcoming_policy_rules(rules, krate));
+    warnings.extend(broadcasts.iter().map(broadcast));
+    warnings
+}
+
+fn warning(
+    code: &str,
+    category: PublishWarningCategory,
+    message: String,
+    docs_url: Option<&str>,
+) -> PublishWarning {
+    PublishWarning {
+        code: code.to_string(),
+        category,
+        message,
+        docs_url: docs_url.map(ToString::to_string),
+    }
+}
+
+/// Warns about SPDX license identifiers that have been deprecated, like
+/// `GPL-3.0` instead of `GPL-3.0-only` or `GPL-3.0-or-later`.
+fn deprecated_licenses(krate: &CrateMetadata<'_>) -> Vec<PublishWarning> {
+    let Some(expression) = krate
+        .license
+        .and_then(|license| parse_license_expr(license).ok())
+    else {
+        return vec![];
+    };
+
+    let mut deprecated = expression
+        .requirements()
+        .filter_map(|expr| match &expr.req.license {
+            LicenseItem::Spdx { id, .. } if id.is_deprecated() => Some(id.name),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    deprecated.sort_unstable();
+    deprecated.dedup();
+
+    deprecated
+        .into_iter()
+        .map(|name| {
+            let message = format!(
+                "the license identifier `{name}` is deprecated by SPDX, please use a current identifier in the `license` field instead"
+            );
+            let category = PublishWarningCategory::License;
+            warning("deprecated-license", category, message, Some(LICENSE_DOCS_URL))
+        })
+        .collect()
+}
+
+fn missing_repository(krate: &CrateMetadata<'_>) -> Option<PublishWarning> {
+    if krate
+        .repository
+        .is_some_and(|repository| !repository.trim().is_empty())
+    {
+        return None;
+    }
+
+    let message = "the `[package]` section of `Cargo.toml` has no `repository` field, \
+        which helps users to find the source code of the crate"
+        .to_string();
+
+    let category = PublishWarningCategory::Metadata;
+    let docs_url = Some(REPOSITORY_DOCS_URL);
+    Some(warning("missing-repository", category, message, docs_url))
+}
+
+fn large_tarball(tarball: TarballSize) -> Option<PublishWarning> {
+    if tarball.size * 100 < tarball.max_upload_size * LARGE_TARBALL_PERCENTAGE {
+        return None;
+    }
+
+    let message = format!(
+        "the `.crate` file has {} bytes, which is close to the maximum upload size of {} bytes. \
+        Consider excluding files that are not needed to build the crate",
+        tarball.size, tarball.max_upload_size
+    );
+
+    let category = PublishWarningCategory::Size;
+    Some(warning(
+        "large-tarball",
+        category,
+        message,
+        Some(EXCLUDE_DOCS_URL),
+    ))
+}
+
+/// Warns about violations of publish policy rules that are not enforced
+/// yet, so that the crate can be fixed before its versions are rejected.
+fn upcoming_policy_rules(
+    rules: &[PublishPolicyRule],
+    krate: &CrateMetadata<'_>,
+) -> Vec<PublishWarning> {
+    rules
+        .iter()
+        .filter(|rule| rule.is_upcoming())
+        .filter_map(|rule| {
+            let enforced_at = rule.enforced_at?;
+            let violation = publish_policy::violation(rule, krate)?;
+
+            let message = format!(
+                "starting {}, versions like this one will be rejected: {}",
+                enforced_at.format("%Y-%m-%d"),
+                violation.detail
+            );
+
+            let category = PublishWarningCategory::Policy;
+            Some(warning("upcoming-policy-rule", category, message, None))
+        })
+        .collect()
+}
+
+fn broadcast(warning: &BroadcastWarning) -> PublishWarning {
+    PublishWarning {
+        code: "announcement".to_string(),
+        category: PublishWarningCategory::Announcement,
+        message: warning.message.clone(),
+        docs_url: warning.docs_url.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PolicyRuleKind;
+    use chrono::{TimeDelta, Utc};
+
+    const SMALL_TARBALL: TarballSize = TarballSize {
+        size: 1000,
+        max_upload_size: 10_000,
+    };
+
+    fn codes(warnings: &[PublishWarning]) -> Vec<&str> {
+        warnings.iter().map(|w| w.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_no_warnings() {
+        let krate = CrateMetadata {
+            license: Some("MIT OR Apache-2.0"),
+            repository: Some("https://github.com/rust-lang/crates.io"),
+            ..Default::default()
+        };
+        assert!(check(&krate, SMALL_TARBALL, &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_deprecated_license() {
+        let krate = CrateMetadata {
+            license: Some("MIT OR GPL-3.0"),
+            repository: Some("https://github.com/rust-lang/crates.io"),
+            ..Default::default()
+        };
+        let warnings = check(&krate, SMALL_TARBALL, &[], &[]);
+        assert_eq!(codes(&warnings), ["deprecated-license"]);
+        assert!(warnings[0].message.contains("`GPL-3.0`"));
+    }
+
+    #[test]
+    fn test_missing_repository() {
+        let krate = CrateMetadata {
+            repository: Some(" "),
+            ..Default::default()
+        };
+        let warnings = check(&krate, SMALL_TARBALL, &[], &[]);
+        assert_eq!(codes(&warnings), ["missing-repository"]);
+        assert_eq!(warnings[0].category, PublishWarningCategory::Metadata);
+        assert_eq!(warnings[0].docs_url.as_deref(), Some(REPOSITORY_DOCS_URL));
+    }
+
+    #[test]
+    fn test_large_tarball() {
+        let krate = CrateMetadata {
+            repository: Some("https://github.com/rust-lang/crates.io"),
+            ..Default::default()
+        };
+
+        let tarball = TarballSize {
+            size: 7999,
+            max_upload_size: 10_000,
+        };
+        assert!(check(&krate, tarball, &[], &[]).is_empty());
+
+        let tarball = TarballSize {
+            size: 8000,
+            max_upload_size: 10_000,
+        };
+        let warnings = check(&krate, tarball, &[], &[]);
+        assert_eq!(codes(&warnings), ["large-tarball"]);
+    }
+
+    #[test]
+    fn test_upcoming_policy_rules() {
+        let rule = |id, enforced_at| PublishPolicyRule {
+            id,
+            kind: PolicyRuleKind::MaxFeatures,
+            value: "1".to_string(),
+            message: None,
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            enforced_at,
+        };
+
+        let rules = [
+            rule(1, None),
+            rule(2, Some(Utc::now() - TimeDelta::days(1))),
+            rule(3, Some(Utc::now() + TimeDelta::days(1))),
+        ];
+
+        let krate = CrateMetadata {
+            num_features: 2,
+            repository: Some("https://github.com/rust-lang/crates.io"),
+            ..Default::default()
+        };
+        let warnings = check(&krate, SMALL_TARBALL, &rules, &[]);
+        assert_eq!(codes(&warnings), ["upcoming-policy-rule"]);
+        assert!(warnings[0].message.starts_with("starting "));
+    }
+
+    #[test]
+    fn test_broadcasts() {
+        let broadcast = BroadcastWarning {
+            id: 1,
+            message: "crates.io will be read-only on Saturday".to_string(),
+            docs_url: Some("https://blog.rust-lang.org/".to_string()),
+            expires_at: None,
+            created_by: None,
+            created_at: Utc::now(),
+        };
+
+        let krate = CrateMetadata {
+            repository: Some("https://github.com/rust-lang/crates.io"),
+            ..Default::default()
+        };
+        let warnings = check(&krate, SMALL_TARBALL, &[], &[broadcast]);
+        assert_eq!(codes(&warnings), ["announcement"]);
+        assert_eq!(
+            warnings[0].to_string(),
+            "crates.io will be read-only on Saturday (see https://blog.rust-lang.org/)"
+        );
+    }
+}
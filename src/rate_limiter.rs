@@ -10,20 +10,26 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::time::Duration;
 
+pub mod requests;
+
 pg_enum! {
     pub enum LimitedAction {
         PublishNew = 0,
         PublishUpdate = 1,
         YankUnyank = 2,
+        ReportCrate = 3,
+        ContactOwners = 4,
     }
 }
 
 impl LimitedAction {
     pub fn default_rate_seconds(&self) -> u64 {
         match self {
-            LimitedAction::PublishNew => 10 * 60, // 10 minutes
-            LimitedAction::PublishUpdate => 60,   // 1 minute
-            LimitedAction::YankUnyank => 60,      // 1 minute
+            LimitedAction::PublishNew => 10 * 60,    // 10 minutes
+            LimitedAction::PublishUpdate => 60,      // 1 minute
+            LimitedAction::YankUnyank => 60,         // 1 minute
+            LimitedAction::ReportCrate => 10 * 60,   // 10 minutes
+            LimitedAction::ContactOwners => 60 * 60, // 1 hour
         }
     }
 
@@ -32,6 +38,8 @@ impl LimitedAction {
             LimitedAction::PublishNew => 5,
             LimitedAction::PublishUpdate => 30,
             LimitedAction::YankUnyank => 100,
+            LimitedAction::ReportCrate => 10,
+            LimitedAction::ContactOwners => 5,
         }
     }
 
@@ -40,6 +48,8 @@ impl LimitedAction {
             LimitedAction::PublishNew => "PUBLISH_NEW",
             LimitedAction::PublishUpdate => "PUBLISH_UPDATE",
             LimitedAction::YankUnyank => "YANK_UNYANK",
+            LimitedAction::ReportCrate => "REPORT_CRATE",
+            LimitedAction::ContactOwners => "CONTACT_OWNERS",
         }
     }
 
@@ -54,6 +64,12 @@ impl LimitedAction {
             LimitedAction::YankUnyank => {
                 "You have yanked or unyanked too many versions in a short period of time"
             }
+            LimitedAction::ReportCrate => {
+                "You have reported too many crates in a short period of time"
+            }
+            LimitedAction::ContactOwners => {
+                "You have contacted too many crate owners in a short period of time"
+            }
         }
     }
 }
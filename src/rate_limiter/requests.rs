@@ -0,0 +1,346 @@
+//! Rate limiting of API requests.
+//!
+//! In contrast to the [RateLimiter](super::RateLimiter) for select actions,
+//! which is backed by the database, this rate limiter counts the requests of
+//! every client in fixed time windows. The counters are either kept in memory,
+//! or in Redis if a `REDIS_URL` is configured, so that all server instances
+//! share the same counters.
+//!
+//! See the `rate_limit` middleware for how clients are identified.
+
+use crate::util::crawlers::is_crawler;
+use http::Method;
+use parking_lot::Mutex;
+use redis::aio::ConnectionManager;
+use secrecy::{ExposeSecret, SecretString};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+
+/// The memory backend removes expired counters once it holds this many.
+const MAX_MEMORY_COUNTERS: usize = 100_000;
+
+/// The endpoints that list all keywords and categories, which crawlers tend
+/// to enumerate page by page.
+const LIST_ENDPOINTS: &[&str] = &[
+    "/api/v1/keywords",
+    "/api/v1/categories",
+    "/api/v1/category_slugs",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointGroup {
+    /// `GET /api/v1/crates`
+    Search,
+    /// `GET` requests of the keyword and category lists
+    List,
+    /// `GET` requests of the keyword and category lists by crawlers
+    CrawlerList,
+    /// All other `GET` and `HEAD` requests of the API
+    Read,
+    /// All other requests of the API
+    Write,
+}
+
+impl EndpointGroup {
+    pub const VARIANTS: &'static [Self] = &[
+        Self::Search,
+        Self::List,
+        Self::CrawlerList,
+        Self::Read,
+        Self::Write,
+    ];
+
+    /// Returns the group of the request, or `None` if the request is not
+    /// rate limited.
+    ///
+    /// Crate downloads are not rate limited, since they are mostly served by
+    /// the CDN and `cargo` is sending many of them in parallel.
+    ///
+    /// Crawlers, as identified by their `User-Agent` header, have a separate
+    /// and lower limit for the keyword and category lists, so that they
+    /// don't use up the capacity of the database for enumerating them.
+    pub fn for_request(method: &Method, path: &str, user_agent: &str) -> Option<Self> {
+        if !path.starts_with("/api/") {
+            return None;
+        }
+
+        let is_read = method == Method::GET || method == Method::HEAD;
+        if is_read && path.starts_with("/api/v1/crates/") && path.ends_with("/download") {
+            None
+        } else if is_read && path == "/api/v1/crates" {
+            Some(Self::Search)
+        } else if is_read && LIST_ENDPOINTS.contains(&path) && is_crawler(user_agent) {
+            Some(Self::CrawlerList)
+        } else if is_read && LIST_ENDPOINTS.contains(&path) {
+            Some(Self::List)
+        } else if is_read {
+            Some(Self::Read)
+        } else {
+            Some(Self::Write)
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Search => "search",
+            Self::List => "list",
+            Self::CrawlerList => "crawler_list",
+            Self::Read => "read",
+            Self::Write => "write",
+        }
+    }
+
+    pub fn env_var_key(&self) -> &'static str {
+        match self {
+            Self::Search => "SEARCH",
+            Self::List => "LIST",
+            Self::CrawlerList => "CRAWLER_LIST",
+            Self::Read => "READ",
+            Self::Write => "WRITE",
+        }
+    }
+
+    pub fn default_policy(&self) -> RateLimitPolicy {
+        let requests = match self {
+            Self::Search => 60,
+            Self::List => 120,
+            Self::CrawlerList => 20,
+            Self::Read => 600,
+            Self::Write => 120,
+        };
+
+        RateLimitPolicy {
+            requests,
+            window: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Allows `requests` requests per client in each `window`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitPolicy {
+    pub requests: u64,
+    pub window: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct RequestRateLimiterConfig {
+    pub enabled: bool,
+    pub policies: HashMap<EndpointGroup, RateLimitPolicy>,
+    pub redis_url: Option<SecretString>,
+}
+
+/// The state of a rate limit after counting a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub limit: u64,
+    pub remaining: u64,
+    /// Time until the current window ends and the counter is reset.
+    pub reset: Duration,
+    pub exceeded: bool,
+}
+
+pub struct RequestRateLimiter {
+    enabled: bool,
+    policies: HashMap<EndpointGroup, RateLimitPolicy>,
+    backend: Backend,
+}
+
+enum Backend {
+    /// Maps the counter keys to the expiry time and the number of requests.
+    Memory(Mutex<HashMap<String, (u64, u64)>>),
+    Redis {
+        client: redis::Client,
+        connection: OnceCell<ConnectionManager>,
+    },
+}
+
+impl RequestRateLimiter {
+    pub fn new(config: &RequestRateLimiterConfig) -> anyhow::Result<Self> {
+        let backend = match &config.redis_url {
+            Some(url) => Backend::Redis {
+                client: redis::Client::open(url.expose_secret())?,
+                connection: OnceCell::new(),
+            },
+            None => Backend::Memory(Mutex::default()),
+        };
+
+        Ok(Self {
+            enabled: config.enabled,
+            policies: config.policies.clone(),
+            backend,
+        })
+    }
+
+    /// Counts a request of the client identified by `key`, and returns the
+    /// resulting state of its rate limit.
+    ///
+    /// Returns `None` if rate limiting is disabled, or if the Redis backend
+    /// is unavailable, so that requests are not rejected in that case.
+    pub async fn check(&self, group: EndpointGroup, key: &str) -> Option<RateLimitStatus> {
+        if !self.enabled {
+            return None;
+        }
+
+        let policy = self.policy_for_group(group);
+        let window = policy.window.as_secs().max(1);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let window_start = now - now % window;
+        let window_end = window_start + window;
+
+        let key = format!("rate_limit:{}:{key}:{window_start}", group.as_str());
+        let count = match self.increment(&key, window_end, now).await {
+            Ok(count) => count,
+            Err(error) => {
+                warn!("Failed to check request rate limit: {error}");
+                return None;
+            }
+        };
+
+        Some(RateLimitStatus {
+            limit: policy.requests,
+            remaining: policy.requests.saturating_sub(count),
+            reset: Duration::from_secs(window_end - now),
+            exceeded: count > policy.requests,
+        })
+    }
+
+    /// Increments the counter for `key`, which expires at `expires_at`, and
+    /// returns its new value.
+    async fn increment(&self, key: &str, expires_at: u64, now: u64) -> anyhow::Result<u64> {
+        match &self.backend {
+            Backend::Memory(counters) => {
+                let mut counters = counters.lock();
+                if counters.len() >= MAX_MEMORY_COUNTERS {
+                    counters.retain(|_, (expiry, _)| *expiry > now);
+                }
+
+                let (_, count) = counters.entry(key.to_string()).or_insert((expires_at, 0));
+                *count += 1;
+                Ok(*count)
+            }
+            Backend::Redis { client, connection } => {
+                let mut connection = connection
+                    .get_or_try_init(|| ConnectionManager::new(client.clone()))
+                    .await?
+                    .clone();
+
+                let (count,): (u64,) = redis::pipe()
+                    .atomic()
+                    .incr(key, 1)
+                    .expire_at(key, expires_at as i64)
+                    .ignore()
+                    .query_async(&mut connection)
+                    .await?;
+
+                Ok(count)
+            }
+        }
+    }
+
+    fn policy_for_group(&self, group: EndpointGroup) -> RateLimitPolicy {
+        self.policies
+            .get(&group)
+            .copied()
+            .unwrap_or_else(|| group.default_policy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_groups() {
+        let group = |method, path| EndpointGroup::for_request(&method, path, "cargo");
+
+        assert_eq!(
+            group(Method::GET, "/api/v1/crates"),
+            Some(EndpointGroup::Search)
+        );
+        assert_eq!(
+            group(Method::GET, "/api/v1/crates/foo"),
+            Some(EndpointGroup::Read)
+        );
+        assert_eq!(
+            group(Method::HEAD, "/api/v1/crates/foo"),
+            Some(EndpointGroup::Read)
+        );
+        assert_eq!(
+            group(Method::PUT, "/api/v1/crates/new"),
+            Some(EndpointGroup::Write)
+        );
+        assert_eq!(
+            group(Method::DELETE, "/api/v1/crates/foo/1.0.0/yank"),
+            Some(EndpointGroup::Write)
+        );
+        assert_eq!(
+            group(Method::GET, "/api/v1/crates/foo/1.0.0/download"),
+            None
+        );
+        assert_eq!(group(Method::GET, "/crates/foo"), None);
+        assert_eq!(
+            group(Method::GET, "/api/v1/keywords"),
+            Some(EndpointGroup::List)
+        );
+        assert_eq!(
+            group(Method::GET, "/api/v1/keywords/foo"),
+            Some(EndpointGroup::Read)
+        );
+
+        let crawler = "Mozilla/5.0 (compatible; Googlebot/2.1)";
+        assert_eq!(
+            EndpointGroup::for_request(&Method::GET, "/api/v1/categories", crawler),
+            Some(EndpointGroup::CrawlerList)
+        );
+        assert_eq!(
+            EndpointGroup::for_request(&Method::GET, "/api/v1/crates/foo", crawler),
+            Some(EndpointGroup::Read)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend() -> anyhow::Result<()> {
+        let policy = RateLimitPolicy {
+            requests: 2,
+            window: Duration::from_secs(3600),
+        };
+        let config = RequestRateLimiterConfig {
+            enabled: true,
+            policies: HashMap::from([(EndpointGroup::Read, policy)]),
+            redis_url: None,
+        };
+        let limiter = RequestRateLimiter::new(&config)?;
+
+        let status = limiter.check(EndpointGroup::Read, "ip:127.0.0.1").await;
+        let status = assert_some!(status);
+        assert_eq!(status.limit, 2);
+        assert_eq!(status.remaining, 1);
+        assert!(!status.exceeded);
+        assert!(status.reset <= policy.window);
+
+        let status = assert_some!(limiter.check(EndpointGroup::Read, "ip:127.0.0.1").await);
+        assert_eq!(status.remaining, 0);
+        assert!(!status.exceeded);
+
+        let status = assert_some!(limiter.check(EndpointGroup::Read, "ip:127.0.0.1").await);
+        assert_eq!(status.remaining, 0);
+        assert!(status.exceeded);
+
+        // Other clients and endpoint groups have their own counters
+        let status = assert_some!(limiter.check(EndpointGroup::Read, "ip:127.0.0.2").await);
+        assert!(!status.exceeded);
+        let status = assert_some!(limiter.check(EndpointGroup::Write, "ip:127.0.0.1").await);
+        assert!(!status.exceeded);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disabled() -> anyhow::Result<()> {
+        let limiter = RequestRateLimiter::new(&RequestRateLimiterConfig::default())?;
+        assert_none!(limiter.check(EndpointGroup::Read, "ip:127.0.0.1").await);
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Crate names that can not be used for new crates.
+//!
+//! The names are stored in the `reserved_crate_names` table and can be
+//! managed through the admin API. Names are compared after canonicalization,
+//! so reserving `foo-bar` also reserves `Foo_Bar`.
+//!
+//! The names are cached in memory and reloaded from the database every few
+//! seconds, so that they don't need to be queried for every publish.
+
+use crate::app::App;
+use crate::models::ReservedCrateName;
+use crate::util::errors::{AppResult, BoxedAppError};
+use diesel::QueryResult;
+use diesel_async::AsyncPgConnection;
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// How long the names are cached.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+pub struct ReservedNames {
+    /// The cached names, if they have been loaded yet, and when they were
+    /// loaded.
+    state: RwLock<(Option<Arc<Snapshot>>, Option<Instant>)>,
+    /// Ensures that only one request at a time reloads the names.
+    refresh_lock: Mutex<()>,
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    /// The canonicalized reserved names.
+    names: HashSet<String>,
+}
+
+impl ReservedNames {
+    /// Returns `true` if the crate name is reserved.
+    ///
+    /// If the names can't be reloaded from the database, the previously
+    /// loaded names are used. An error is only returned if the names have
+    /// never been loaded successfully.
+    pub async fn is_reserved(&self, app: &App, name: &str) -> AppResult<bool> {
+        Ok(self.snapshot(app).await?.contains(name))
+    }
+
+    /// Reloads the names from the database on the next check, e.g. after
+    /// they have been changed through the admin API.
+    pub fn invalidate(&self) {
+        self.state.write().1 = None;
+    }
+
+    async fn snapshot(&self, app: &App) -> AppResult<Arc<Snapshot>> {
+        if let Some(snapshot) = self.fresh_snapshot() {
+            return Ok(snapshot);
+        }
+
+        // Other requests keep using the previously loaded names while the
+        // names are reloaded, or wait if there are none yet.
+        let snapshot = self.state.read().0.clone();
+        let _guard = match (self.refresh_lock.try_lock(), snapshot) {
+            (Ok(guard), _) => guard,
+            (Err(_), Some(snapshot)) => return Ok(snapshot),
+            (Err(_), None) => self.refresh_lock.lock().await,
+        };
+
+        if let Some(snapshot) = self.fresh_snapshot() {
+            return Ok(snapshot);
+        }
+
+        let result = async {
+            let mut conn = app.db_read_prefer_primary().await?;
+            Ok::<_, BoxedAppError>(Snapshot::load(&mut conn).await?)
+        }
+        .await;
+
+        let mut state = self.state.write();
+        match (result, state.0.is_some()) {
+            (Ok(new_snapshot), _) => state.0 = Some(Arc::new(new_snapshot)),
+            (Err(error), true) => warn!("Failed to load reserved crate names: {error}"),
+            (Err(error), false) => return Err(error),
+        }
+
+        // Also wait for the next interval after a failed refresh, so that an
+        // unavailable database isn't queried for every publish.
+        state.1 = Some(Instant::now());
+        Ok(state.0.clone().unwrap_or_default())
+    }
+
+    fn fresh_snapshot(&self) -> Option<Arc<Snapshot>> {
+        let (snapshot, refreshed_at) = self.state.read().clone();
+        let is_fresh =
+            refreshed_at.is_some_and(|refreshed_at| refreshed_at.elapsed() < REFRESH_INTERVAL);
+
+        snapshot.filter(|_| is_fresh)
+    }
+}
+
+impl Snapshot {
+    async fn load(conn: &mut AsyncPgConnection) -> QueryResult<Self> {
+        let names = ReservedCrateName::all(conn).await?;
+        let names = names.iter().map(|reserved| canonicalize(&reserved.name));
+        Ok(Self {
+            names: names.collect(),
+        })
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.names.contains(&canonicalize(name))
+    }
+}
+
+/// Mirrors the `canon_crate_name()` SQL function.
+fn canonicalize(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains() {
+        let snapshot = Snapshot {
+            names: HashSet::from([canonicalize("std"), canonicalize("foo-bar")]),
+        };
+
+        assert!(snapshot.contains("std"));
+        assert!(snapshot.contains("STD"));
+        assert!(snapshot.contains("foo_bar"));
+        assert!(snapshot.contains("Foo-Bar"));
+        assert!(!snapshot.contains("std2"));
+        assert!(!snapshot.contains("foo"));
+    }
+}
@@ -1,10 +1,11 @@
 use axum::response::IntoResponse;
-use axum::routing::{get, post};
+use axum::routing::{get, post, put};
 use axum::{Json, Router};
 use http::{Method, StatusCode};
 use utoipa_axum::routes;
 
 use crate::app::AppState;
+use crate::config::CSP_REPORT_PATH;
 use crate::controllers::*;
 use crate::openapi::BaseOpenApi;
 use crate::util::errors::not_found;
@@ -15,6 +16,8 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
     let (router, openapi) = BaseOpenApi::router()
         // Route used by both `cargo search` and the frontend
         .routes(routes!(krate::search::list_crates))
+        .routes(routes!(krate::trending::list_trending_crates))
+        .routes(routes!(krate::most_depended::list_most_depended_crates))
         // Routes used by `cargo`
         .routes(routes!(
             krate::publish::publish,
@@ -39,28 +42,96 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         ))
         .routes(routes!(version::readme::get_version_readme))
         .routes(routes!(version::dependencies::get_version_dependencies))
+        .routes(routes!(
+            version::dependency_graph::get_version_dependency_graph
+        ))
+        .routes(routes!(version::features::get_version_features))
+        .routes(routes!(version::diff::get_version_diff))
+        .routes(routes!(version::files::list_version_files))
         .routes(routes!(version::downloads::get_version_downloads))
         .routes(routes!(version::authors::get_version_authors))
         .routes(routes!(krate::downloads::get_crate_downloads))
+        .routes(routes!(krate::score::get_crate_score))
         .routes(routes!(krate::versions::list_versions))
         .routes(routes!(
             krate::follow::follow_crate,
             krate::follow::unfollow_crate
         ))
-        .routes(routes!(krate::follow::get_following_crate))
+        .routes(routes!(
+            krate::follow::get_following_crate,
+            krate::follow::update_following_crate
+        ))
         .routes(routes!(krate::owners::get_team_owners))
         .routes(routes!(krate::owners::get_user_owners))
+        .routes(routes!(
+            krate::transfer::get_transfer,
+            krate::transfer::request_transfer,
+            krate::transfer::cancel_transfer
+        ))
+        .routes(routes!(krate::transfer::accept_transfer))
+        .routes(routes!(krate::audit::list_crate_audit_log))
+        .routes(routes!(krate::api_usage::get_crate_api_usage))
+        .routes(routes!(
+            krate::settings::get_crate_settings,
+            krate::settings::update_crate_settings
+        ))
+        .routes(routes!(krate::repository::verify_repository))
+        .routes(routes!(krate::activity::list_crate_activity))
+        .routes(routes!(krate::report::report_crate))
+        .routes(routes!(krate::contact::contact_owners))
+        .routes(routes!(
+            krate::suggested_categories::list_suggested_categories
+        ))
+        .routes(routes!(
+            krate::suggested_categories::accept_suggested_categories
+        ))
+        .routes(routes!(
+            krate::reclaim::list_crate_reclaim_requests,
+            krate::reclaim::create_reclaim_request
+        ))
+        .routes(routes!(krate::redirect::create_redirect_request))
         .routes(routes!(krate::rev_deps::list_reverse_dependencies))
+        .routes(routes!(krate::semver_check::check_semver_compatibility))
         .routes(routes!(keyword::list_keywords))
         .routes(routes!(keyword::find_keyword))
+        .routes(routes!(keyword::get_keyword_downloads))
         .routes(routes!(category::list_categories))
         .routes(routes!(category::find_category))
+        .routes(routes!(category::get_category_downloads))
         .routes(routes!(category::list_category_slugs))
         .routes(routes!(user::other::find_user, user::update::update_user))
         .routes(routes!(user::other::get_user_stats))
+        .routes(routes!(user::other::list_user_versions))
         .routes(routes!(team::find_team))
+        .routes(routes!(organization::create_organization))
+        .routes(routes!(organization::find_organization))
+        .routes(routes!(
+            organization::list_organization_members,
+            organization::update_organization_member
+        ))
+        .routes(routes!(organization::remove_organization_member))
+        .routes(routes!(
+            organization::list_organization_reserved_prefixes,
+            organization::request_reserved_prefix
+        ))
+        .routes(routes!(organization::list_reserved_prefixes))
         .routes(routes!(user::me::get_authenticated_user))
         .routes(routes!(user::me::get_authenticated_user_updates))
+        .routes(routes!(user::dependency_updates::list_dependency_updates))
+        .routes(routes!(user::following::list_followed_crates))
+        .routes(routes!(user::following::bulk_update_followed_crates))
+        .routes(routes!(
+            user::export::get_account_export,
+            user::export::create_account_export
+        ))
+        .routes(routes!(user::export::download_account_export))
+        .routes(routes!(user::emails::list_emails, user::emails::add_email))
+        .routes(routes!(user::emails::delete_email))
+        .routes(routes!(user::emails::set_primary_email))
+        .routes(routes!(user::audit::list_user_audit_log))
+        .routes(routes!(user::usage::get_my_api_usage))
+        .routes(routes!(user::identities::list_linked_identities))
+        .routes(routes!(user::identities::unlink_identity))
         .routes(routes!(token::list_api_tokens, token::create_api_token))
         .routes(routes!(token::find_api_token, token::revoke_api_token))
         .routes(routes!(token::revoke_current_api_token))
@@ -80,9 +151,105 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
             user::email_notifications::update_email_notifications
         ))
         .routes(routes!(summary::get_summary))
+        .routes(routes!(dependency_report::create_dependency_report))
+        .routes(routes!(stats::get_stats))
         .routes(routes!(user::email_verification::confirm_user_email))
         .routes(routes!(user::email_verification::resend_email_verification))
         .routes(routes!(site_metadata::get_site_metadata))
+        .routes(routes!(announcement::list_announcements))
+        .routes(routes!(db_dumps::list_db_dumps))
+        .routes(routes!(snapshot::download_snapshot))
+        .routes(routes!(snapshot::download_snapshot_index))
+        // API version 2
+        .routes(routes!(krate::metadata::find_crate_v2))
+        .routes(routes!(krate::versions::list_versions_v2))
+        .routes(routes!(version::metadata::find_version_v2))
+        // Admin console
+        .routes(routes!(admin::users::search_users))
+        .routes(routes!(admin::users::find_user))
+        .routes(routes!(admin::users::lock_user, admin::users::unlock_user))
+        .routes(routes!(admin::users::override_rate_limit))
+        .routes(routes!(admin::users::remove_rate_limit_override))
+        .routes(routes!(
+            admin::crates::get_crate_moderation,
+            admin::crates::update_crate_moderation
+        ))
+        .routes(routes!(admin::crates::force_yank_version))
+        .routes(routes!(admin::crates::delete_version))
+        .routes(routes!(admin::crates::restore_version))
+        .routes(routes!(admin::reports::list_reports))
+        .routes(routes!(admin::reports::update_report))
+        .routes(routes!(admin::reclaim_requests::list_reclaim_requests))
+        .routes(routes!(admin::reclaim_requests::decide_reclaim_request))
+        .routes(routes!(admin::redirect_requests::list_redirect_requests))
+        .routes(routes!(admin::redirect_requests::decide_redirect_request))
+        .routes(routes!(
+            admin::publish_policy::list_publish_policy_rules,
+            admin::publish_policy::create_publish_policy_rule
+        ))
+        .routes(routes!(
+            admin::publish_policy::update_publish_policy_rule,
+            admin::publish_policy::delete_publish_policy_rule
+        ))
+        .routes(routes!(
+            admin::broadcast_warnings::list_broadcast_warnings,
+            admin::broadcast_warnings::create_broadcast_warning
+        ))
+        .routes(routes!(admin::broadcast_warnings::delete_broadcast_warning))
+        .routes(routes!(
+            admin::announcements::list_all_announcements,
+            admin::announcements::create_announcement
+        ))
+        .routes(routes!(
+            admin::announcements::update_announcement,
+            admin::announcements::delete_announcement
+        ))
+        .routes(routes!(admin::reserved_names::list_reserved_crate_names))
+        .routes(routes!(
+            admin::reserved_names::reserve_crate_name,
+            admin::reserved_names::delete_reserved_crate_name
+        ))
+        .routes(routes!(admin::ip_access::list_ip_bans))
+        .routes(routes!(admin::ip_access::delete_ip_ban))
+        .routes(routes!(
+            admin::ip_access::list_ip_rules,
+            admin::ip_access::create_ip_rule
+        ))
+        .routes(routes!(admin::ip_access::delete_ip_rule))
+        .routes(routes!(
+            admin::read_only_mode::get_read_only_mode,
+            admin::read_only_mode::update_read_only_mode
+        ))
+        .routes(routes!(admin::database_pools::list_database_pools))
+        .routes(routes!(admin::database_pools::update_database_pool))
+        .routes(routes!(admin::features::list_features))
+        .routes(routes!(
+            admin::features::update_feature,
+            admin::features::delete_feature
+        ))
+        .routes(routes!(
+            admin::features::update_feature_override,
+            admin::features::delete_feature_override
+        ))
+        .routes(routes!(
+            admin::featured_crates::list_featured_crates,
+            admin::featured_crates::create_featured_crate
+        ))
+        .routes(routes!(admin::featured_crates::delete_featured_crate))
+        .routes(routes!(admin::keywords::merge_keyword))
+        .routes(routes!(admin::keywords::list_keyword_aliases))
+        .routes(routes!(
+            admin::keywords::update_keyword_alias,
+            admin::keywords::delete_keyword_alias
+        ))
+        .routes(routes!(
+            admin::reserved_prefixes::list_reserved_prefix_requests
+        ))
+        .routes(routes!(admin::reserved_prefixes::approve_reserved_prefix))
+        .routes(routes!(admin::reserved_prefixes::delete_reserved_prefix))
+        .routes(routes!(
+            admin::storage_discrepancies::list_storage_discrepancies
+        ))
         // Session management
         .routes(routes!(session::begin_session))
         .routes(routes!(session::authorize_session))
@@ -90,8 +257,30 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         .split_for_parts();
 
     let mut router = router
+        // Wildcard paths are not supported by the OpenAPI spec
+        .route(
+            "/api/v1/crates/{name}/{version}/files/{*path}",
+            get(version::files::get_version_file),
+        )
+        // Health checks for the load balancer and the orchestration
+        .route("/healthz", get(health::liveness))
+        .route("/readyz", get(health::readiness))
+        // Crawler policy and sitemaps for search engines
+        .route("/robots.txt", get(robots::robots_txt))
+        .route("/sitemap.xml", get(robots::sitemap))
+        // Plain HTML crate pages for clients that don't run the frontend
+        .route("/crates/{name}", get(krate::page::render_crate_page))
         // Metrics
         .route("/api/private/metrics/{kind}", get(metrics::prometheus))
+        // Build results from docs.rs
+        .route("/api/private/docs_rs/builds", put(docs_rs::record_build))
+        // Change feed for docs.rs and crater
+        .route(
+            "/api/private/crate_changes",
+            get(crate_changes::list_crate_changes),
+        )
+        // Reports of Content-Security-Policy violations from browsers
+        .route(CSP_REPORT_PATH, post(csp_report::collect_csp_report))
         // Alerts from GitHub scanning for exposed API tokens
         .route(
             "/api/github/secret-scanning/verify",
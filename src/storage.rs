@@ -1,5 +1,6 @@
 use anyhow::Context;
-use crates_io_env_vars::required_var;
+use chrono::{DateTime, Utc};
+use crates_io_env_vars::{required_var, var};
 use futures_util::{StreamExt, TryStreamExt};
 use hyper::body::Bytes;
 use object_store::aws::{AmazonS3, AmazonS3Builder};
@@ -9,6 +10,7 @@ use object_store::path::Path;
 use object_store::prefix::PrefixStore;
 use object_store::{Attribute, Attributes, ClientOptions, ObjectStore, PutPayload, Result};
 use secrecy::{ExposeSecret, SecretString};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Cursor;
 use std::path::PathBuf;
@@ -17,19 +19,33 @@ use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
 const PREFIX_CRATES: &str = "crates";
+const PREFIX_STAGED_CRATES: &str = "staged-crates";
+const PREFIX_QUARANTINED_CRATES: &str = "quarantined-crates";
 const PREFIX_READMES: &str = "readmes";
+const PREFIX_ACCOUNT_EXPORTS: &str = "account-exports";
+const PREFIX_METADATA_SNAPSHOT: &str = "snapshot";
+const PREFIX_SITEMAPS: &str = "sitemaps";
+const HEALTH_CHECK_PATH: &str = "health-check";
 const DEFAULT_REGION: &str = "us-west-1";
 const CONTENT_TYPE_CRATE: &str = "application/gzip";
 const CONTENT_TYPE_GZIP: &str = "application/gzip";
 const CONTENT_TYPE_ZIP: &str = "application/zip";
 const CONTENT_TYPE_INDEX: &str = "text/plain";
 const CONTENT_TYPE_README: &str = "text/html";
+const CONTENT_TYPE_JSON: &str = "application/json";
+const CONTENT_TYPE_PARQUET: &str = "application/vnd.apache.parquet";
+const CONTENT_TYPE_SITEMAP: &str = "application/xml; charset=UTF-8";
 const CACHE_CONTROL_IMMUTABLE: &str = "public,max-age=31536000,immutable";
 const CACHE_CONTROL_INDEX: &str = "public,max-age=600";
-const CACHE_CONTROL_README: &str = "public,max-age=604800";
+const CACHE_CONTROL_PRIVATE: &str = "private,no-store";
+const CACHE_CONTROL_ANALYTICS: &str = "public,max-age=3600";
+const CACHE_CONTROL_SITEMAP: &str = "public,max-age=3600";
 
 type StdPath = std::path::Path;
 
+#[cfg(test)]
+use crate::tests::util::faults::{FaultInjector, FaultyStore};
+
 #[derive(Debug)]
 pub struct StorageConfig {
     backend: StorageBackend,
@@ -39,9 +55,19 @@ pub struct StorageConfig {
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum StorageBackend {
-    S3 { default: S3Config, index: S3Config },
-    LocalFileSystem { path: PathBuf },
+    S3 {
+        default: S3Config,
+        index: S3Config,
+        /// Bucket for files that must not be served by the CDN.
+        private: S3Config,
+    },
+    LocalFileSystem {
+        path: PathBuf,
+        private_path: PathBuf,
+    },
     InMemory,
+    #[cfg(test)]
+    Faulty(FaultInjector),
 }
 
 #[derive(Debug)]
@@ -60,16 +86,27 @@ impl StorageConfig {
         }
     }
 
-    pub fn from_environment() -> Self {
-        if let Ok(bucket) = dotenvy::var("S3_BUCKET") {
-            let region = dotenvy::var("S3_REGION").ok();
-            let cdn_prefix = dotenvy::var("S3_CDN").ok();
+    #[cfg(test)]
+    pub fn in_memory_with_faults(faults: FaultInjector) -> Self {
+        Self {
+            backend: StorageBackend::Faulty(faults),
+            cdn_prefix: None,
+        }
+    }
+
+    pub fn from_environment() -> anyhow::Result<Self> {
+        if let Some(bucket) = var("S3_BUCKET")? {
+            let region = var("S3_REGION")?;
+            let cdn_prefix = var("S3_CDN")?;
+
+            let index_bucket = required_var("S3_INDEX_BUCKET")?;
+            let index_region = var("S3_INDEX_REGION")?;
 
-            let index_bucket = required_var("S3_INDEX_BUCKET").unwrap();
-            let index_region = dotenvy::var("S3_INDEX_REGION").ok();
+            let private_bucket = required_var("S3_PRIVATE_BUCKET")?;
+            let private_region = var("S3_PRIVATE_REGION")?;
 
-            let access_key = required_var("AWS_ACCESS_KEY").unwrap();
-            let secret_key: SecretString = required_var("AWS_SECRET_KEY").unwrap().into();
+            let access_key = required_var("AWS_ACCESS_KEY")?;
+            let secret_key: SecretString = required_var("AWS_SECRET_KEY")?.into();
 
             let default = S3Config {
                 bucket,
@@ -81,30 +118,94 @@ impl StorageConfig {
             let index = S3Config {
                 bucket: index_bucket,
                 region: index_region,
+                access_key: access_key.clone(),
+                secret_key: secret_key.clone(),
+            };
+
+            let private = S3Config {
+                bucket: private_bucket,
+                region: private_region,
                 access_key,
                 secret_key,
             };
 
-            let backend = StorageBackend::S3 { default, index };
+            let backend = StorageBackend::S3 {
+                default,
+                index,
+                private,
+            };
 
-            return Self {
+            return Ok(Self {
                 backend,
                 cdn_prefix,
-            };
+            });
         }
 
-        let current_dir = std::env::current_dir()
-            .context("Failed to read the current directory")
-            .unwrap();
+        let current_dir =
+            std::env::current_dir().context("Failed to read the current directory")?;
 
         let path = current_dir.join("local_uploads");
+        let private_path = current_dir.join("local_private_uploads");
 
-        let backend = StorageBackend::LocalFileSystem { path };
+        let backend = StorageBackend::LocalFileSystem { path, private_path };
 
-        Self {
+        Ok(Self {
             backend,
             cdn_prefix: None,
-        }
+        })
+    }
+
+    /// Reads the configuration of the standby region that crate files and
+    /// index files are replicated to, or returns `None` if no standby region
+    /// is configured.
+    pub fn standby_from_environment() -> anyhow::Result<Option<Self>> {
+        let Some(bucket) = var("STANDBY_S3_BUCKET")? else {
+            return Ok(None);
+        };
+
+        let region = var("STANDBY_S3_REGION")?;
+        let cdn_prefix = Some(required_var("STANDBY_S3_CDN")?);
+
+        let index_bucket = required_var("STANDBY_S3_INDEX_BUCKET")?;
+        let index_region = var("STANDBY_S3_INDEX_REGION")?;
+
+        let private_bucket = required_var("STANDBY_S3_PRIVATE_BUCKET")?;
+        let private_region = var("STANDBY_S3_PRIVATE_REGION")?;
+
+        let access_key = required_var("AWS_ACCESS_KEY")?;
+        let secret_key: SecretString = required_var("AWS_SECRET_KEY")?.into();
+
+        let default = S3Config {
+            bucket,
+            region,
+            access_key: access_key.clone(),
+            secret_key: secret_key.clone(),
+        };
+
+        let index = S3Config {
+            bucket: index_bucket,
+            region: index_region,
+            access_key: access_key.clone(),
+            secret_key: secret_key.clone(),
+        };
+
+        let private = S3Config {
+            bucket: private_bucket,
+            region: private_region,
+            access_key,
+            secret_key,
+        };
+
+        let backend = StorageBackend::S3 {
+            default,
+            index,
+            private,
+        };
+
+        Ok(Some(Self {
+            backend,
+            cdn_prefix,
+        }))
     }
 }
 
@@ -112,19 +213,26 @@ pub struct Storage {
     cdn_prefix: Option<String>,
     store: Arc<dyn ObjectStore>,
     index_store: Arc<dyn ObjectStore>,
+    /// Store for files that must not be publicly accessible, like account
+    /// data exports. Unlike `store`, it is not served by the CDN.
+    private_store: Arc<dyn ObjectStore>,
     supports_attributes: bool,
 }
 
 impl Storage {
-    pub fn from_environment() -> Self {
-        Self::from_config(&StorageConfig::from_environment())
+    pub fn from_environment() -> anyhow::Result<Self> {
+        Ok(Self::from_config(&StorageConfig::from_environment()?))
     }
 
     pub fn from_config(config: &StorageConfig) -> Self {
         let cdn_prefix = config.cdn_prefix.clone();
 
         match &config.backend {
-            StorageBackend::S3 { default, index } => {
+            StorageBackend::S3 {
+                default,
+                index,
+                private,
+            } => {
                 let options = ClientOptions::default()
                     // Apply default content types for the version downloads archive
                     .with_content_type_for_suffix("html", "text/html")
@@ -134,12 +242,16 @@ impl Storage {
                     // specifying any file attributes, so we need to set the
                     // content type here instead for the database dump upload.
                     .with_content_type_for_suffix("gz", CONTENT_TYPE_GZIP)
-                    .with_content_type_for_suffix("zip", CONTENT_TYPE_ZIP);
+                    .with_content_type_for_suffix("zip", CONTENT_TYPE_ZIP)
+                    // Same for the Parquet files of the analytics export.
+                    .with_content_type_for_suffix("parquet", CONTENT_TYPE_PARQUET);
 
                 let store = build_s3(default, options);
 
                 let index_store = build_s3(index, Default::default());
 
+                let private_store = build_s3(private, Default::default());
+
                 if cdn_prefix.is_none() {
                     panic!("Missing S3_CDN environment variable");
                 }
@@ -148,11 +260,12 @@ impl Storage {
                     cdn_prefix,
                     store: Arc::new(store),
                     index_store: Arc::new(index_store),
+                    private_store: Arc::new(private_store),
                     supports_attributes: true,
                 }
             }
 
-            StorageBackend::LocalFileSystem { path } => {
+            StorageBackend::LocalFileSystem { path, private_path } => {
                 warn!(?path, "Using local file system for file storage");
 
                 let index_path = path.join("index");
@@ -161,6 +274,10 @@ impl Storage {
                     .context("Failed to create file storage directories")
                     .unwrap();
 
+                fs::create_dir_all(private_path)
+                    .context("Failed to create file storage directories")
+                    .unwrap();
+
                 let local = LocalFileSystem::new_with_prefix(path)
                     .context("Failed to initialize local file system storage")
                     .unwrap();
@@ -169,13 +286,19 @@ impl Storage {
                     .context("Failed to initialize local file system storage")
                     .unwrap();
 
+                let local_private = LocalFileSystem::new_with_prefix(private_path)
+                    .context("Failed to initialize local file system storage")
+                    .unwrap();
+
                 let store: Arc<dyn ObjectStore> = Arc::new(local);
                 let index_store: Arc<dyn ObjectStore> = Arc::new(local_index);
+                let private_store: Arc<dyn ObjectStore> = Arc::new(local_private);
 
                 Self {
                     cdn_prefix,
                     store,
                     index_store,
+                    private_store,
                     supports_attributes: false,
                 }
             }
@@ -188,6 +311,24 @@ impl Storage {
                     cdn_prefix,
                     store: store.clone(),
                     index_store: Arc::new(PrefixStore::new(store, "index")),
+                    private_store: Arc::new(InMemory::new()),
+                    supports_attributes: true,
+                }
+            }
+
+            #[cfg(test)]
+            StorageBackend::Faulty(faults) => {
+                let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+                let store: Arc<dyn ObjectStore> = Arc::new(FaultyStore::new(store, faults.clone()));
+
+                let private_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+                let private_store = FaultyStore::new(private_store, faults.clone());
+
+                Self {
+                    cdn_prefix,
+                    store: store.clone(),
+                    index_store: Arc::new(PrefixStore::new(store, "index")),
+                    private_store: Arc::new(private_store),
                     supports_attributes: true,
                 }
             }
@@ -201,13 +342,36 @@ impl Storage {
         apply_cdn_prefix(&self.cdn_prefix, &crate_file_path(name, version)).replace('+', "%2B")
     }
 
-    /// Returns the URL of an uploaded crate's version readme.
+    /// Returns the URL of a crate's version readme that was uploaded per
+    /// version, before readmes were stored by their checksum.
     ///
     /// The function doesn't check for the existence of the file.
     pub fn readme_location(&self, name: &str, version: &str) -> String {
         apply_cdn_prefix(&self.cdn_prefix, &readme_path(name, version)).replace('+', "%2B")
     }
 
+    /// Returns the URL of an uploaded crate readme with the given checksum.
+    ///
+    /// The function doesn't check for the existence of the file.
+    pub fn readme_location_by_checksum(&self, name: &str, checksum: &str) -> String {
+        apply_cdn_prefix(&self.cdn_prefix, &readme_checksum_path(name, checksum))
+    }
+
+    /// Returns the URL of an uploaded database dump archive.
+    pub fn db_dump_location(&self, path: &str) -> String {
+        apply_cdn_prefix(&self.cdn_prefix, &path.into())
+    }
+
+    /// Returns the URL of an uploaded file of the metadata snapshot.
+    pub fn metadata_snapshot_location(&self, file: &str) -> String {
+        apply_cdn_prefix(&self.cdn_prefix, &metadata_snapshot_path(file))
+    }
+
+    /// Returns the URL of an uploaded sitemap file.
+    pub fn sitemap_location(&self, file: &str) -> String {
+        apply_cdn_prefix(&self.cdn_prefix, &sitemap_path(file))
+    }
+
     /// Returns the URL of an uploaded RSS feed.
     pub fn feed_url(&self, feed_id: &FeedId<'_>) -> String {
         apply_cdn_prefix(&self.cdn_prefix, &feed_id.into()).replace('+', "%2B")
@@ -237,6 +401,12 @@ impl Storage {
         self.store.delete(&path).await
     }
 
+    #[instrument(skip(self))]
+    pub async fn delete_readme_by_checksum(&self, name: &str, checksum: &str) -> Result<()> {
+        let path = readme_checksum_path(name, checksum);
+        self.store.delete(&path).await
+    }
+
     #[instrument(skip(self))]
     pub async fn delete_feed(&self, feed_id: &FeedId<'_>) -> Result<()> {
         let path = feed_id.into();
@@ -255,18 +425,206 @@ impl Storage {
         Ok(())
     }
 
+    /// Uploads the `.crate` file of a version that is being published to a
+    /// staging location, from which it is moved to its public location by
+    /// [`Storage::publish_staged_crate_file()`] once the version has been
+    /// committed to the database.
     #[instrument(skip(self, bytes))]
-    pub async fn upload_readme(&self, name: &str, version: &str, bytes: Bytes) -> Result<()> {
-        let path = readme_path(name, version);
+    pub async fn upload_staged_crate_file(&self, version_id: i32, bytes: Bytes) -> Result<()> {
+        let path = staged_crate_file_path(version_id);
+        let attributes = self.attrs([
+            (Attribute::ContentType, CONTENT_TYPE_CRATE),
+            (Attribute::CacheControl, CACHE_CONTROL_PRIVATE),
+        ]);
+        let opts = attributes.into();
+        self.store.put_opts(&path, bytes.into(), opts).await?;
+        Ok(())
+    }
+
+    /// Moves a staged `.crate` file to its public location.
+    ///
+    /// If the staged file doesn't exist anymore, because a previous attempt
+    /// already moved it, this only checks that the public file exists.
+    #[instrument(skip(self))]
+    pub async fn publish_staged_crate_file(
+        &self,
+        version_id: i32,
+        name: &str,
+        version: &str,
+    ) -> Result<()> {
+        let staged_path = staged_crate_file_path(version_id);
+        let bytes = match self.store.get(&staged_path).await {
+            Ok(result) => result.bytes().await?,
+            Err(object_store::Error::NotFound { .. }) => {
+                self.store.head(&crate_file_path(name, version)).await?;
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+
+        self.upload_crate_file(name, version, bytes).await?;
+        self.store.delete(&staged_path).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_staged_crate_file(&self, version_id: i32) -> Result<()> {
+        let path = staged_crate_file_path(version_id);
+        self.store.delete(&path).await
+    }
+
+    /// Returns the version IDs and upload times of all staged `.crate`
+    /// files.
+    #[instrument(skip(self))]
+    pub async fn list_staged_crate_files(&self) -> Result<Vec<(i32, DateTime<Utc>)>> {
+        let prefix = Path::from(PREFIX_STAGED_CRATES);
+        let objects = self.store.list(Some(&prefix));
+        let objects = objects.try_collect::<Vec<_>>().await?;
+
+        let files = objects
+            .into_iter()
+            .filter_map(|meta| {
+                let filename = meta.location.filename()?;
+                let version_id = filename.strip_suffix(".crate")?.parse().ok()?;
+                Some((version_id, meta.last_modified))
+            })
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Moves the `.crate` file of a deleted version to the quarantine in the
+    /// private store, where it can't be downloaded through the CDN anymore,
+    /// but is kept as evidence.
+    ///
+    /// If the public file doesn't exist anymore, because a previous attempt
+    /// already moved it, this only checks that the quarantined file exists.
+    #[instrument(skip(self))]
+    pub async fn quarantine_crate_file(&self, name: &str, version: &str) -> Result<()> {
+        let path = crate_file_path(name, version);
+        let quarantined_path = quarantined_crate_file_path(name, version);
+        let bytes = match self.store.get(&path).await {
+            Ok(result) => result.bytes().await?,
+            Err(object_store::Error::NotFound { .. }) => {
+                self.private_store.head(&quarantined_path).await?;
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+
+        let attributes = self.attrs([
+            (Attribute::ContentType, CONTENT_TYPE_CRATE),
+            (Attribute::CacheControl, CACHE_CONTROL_PRIVATE),
+        ]);
+        let opts = attributes.into();
+        self.private_store
+            .put_opts(&quarantined_path, bytes.into(), opts)
+            .await?;
+        self.store.delete(&path).await
+    }
+
+    /// Moves the `.crate` file of a restored version from the quarantine
+    /// back to its public location.
+    ///
+    /// If the quarantined file doesn't exist anymore, because a previous
+    /// attempt already moved it, this only checks that the public file
+    /// exists.
+    #[instrument(skip(self))]
+    pub async fn restore_crate_file(&self, name: &str, version: &str) -> Result<()> {
+        let quarantined_path = quarantined_crate_file_path(name, version);
+        let bytes = match self.private_store.get(&quarantined_path).await {
+            Ok(result) => result.bytes().await?,
+            Err(object_store::Error::NotFound { .. }) => {
+                self.store.head(&crate_file_path(name, version)).await?;
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+
+        self.upload_crate_file(name, version, bytes).await?;
+        self.private_store.delete(&quarantined_path).await
+    }
+
+    /// Returns the contents of a previously uploaded `.crate` file.
+    #[instrument(skip(self))]
+    pub async fn download_crate_file(&self, name: &str, version: &str) -> Result<Bytes> {
+        let path = crate_file_path(name, version);
+        self.store.get(&path).await?.bytes().await
+    }
+
+    /// Returns the contents of a quarantined `.crate` file, which is not
+    /// served through the CDN.
+    #[instrument(skip(self))]
+    pub async fn download_quarantined_crate_file(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<Bytes> {
+        let path = quarantined_crate_file_path(name, version);
+        self.private_store.get(&path).await?.bytes().await
+    }
+
+    /// Returns the contents of a rendered readme, which is stored by its
+    /// checksum, or by version if it was rendered before readmes were stored
+    /// by their checksum.
+    #[instrument(skip(self))]
+    pub async fn download_readme(
+        &self,
+        name: &str,
+        version: &str,
+        checksum: Option<&str>,
+    ) -> Result<Bytes> {
+        let path = match checksum {
+            Some(checksum) => readme_checksum_path(name, checksum),
+            None => readme_path(name, version),
+        };
+        self.store.get(&path).await?.bytes().await
+    }
+
+    /// Uploads a rendered readme under its checksum, so that versions with
+    /// identical readmes share the same file.
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_readme(&self, name: &str, checksum: &str, bytes: Bytes) -> Result<()> {
+        let path = readme_checksum_path(name, checksum);
         let attributes = self.attrs([
             (Attribute::ContentType, CONTENT_TYPE_README),
-            (Attribute::CacheControl, CACHE_CONTROL_README),
+            (Attribute::CacheControl, CACHE_CONTROL_IMMUTABLE),
         ]);
         let opts = attributes.into();
         self.store.put_opts(&path, bytes.into(), opts).await?;
         Ok(())
     }
 
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_account_export(&self, user_id: i32, id: i32, bytes: Bytes) -> Result<()> {
+        let path = account_export_path(user_id, id);
+        let attributes = self.attrs([
+            (Attribute::ContentType, CONTENT_TYPE_JSON),
+            (Attribute::CacheControl, CACHE_CONTROL_PRIVATE),
+        ]);
+        let opts = attributes.into();
+        self.private_store
+            .put_opts(&path, bytes.into(), opts)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the contents of a previously generated account data export.
+    ///
+    /// Account exports are kept in the private store, which is not served
+    /// through the CDN, since they contain private information, so they have
+    /// to be read back from the storage.
+    #[instrument(skip(self))]
+    pub async fn download_account_export(&self, user_id: i32, id: i32) -> Result<Bytes> {
+        let path = account_export_path(user_id, id);
+        self.private_store.get(&path).await?.bytes().await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_account_export(&self, user_id: i32, id: i32) -> Result<()> {
+        let path = account_export_path(user_id, id);
+        self.private_store.delete(&path).await
+    }
+
     #[instrument(skip(self, channel))]
     pub async fn upload_feed(
         &self,
@@ -286,6 +644,18 @@ impl Storage {
         Ok(())
     }
 
+    #[instrument(skip(self, content))]
+    pub async fn upload_sitemap(&self, file: &str, content: String) -> Result<()> {
+        let path = sitemap_path(file);
+        let attributes = self.attrs([
+            (Attribute::ContentType, CONTENT_TYPE_SITEMAP),
+            (Attribute::CacheControl, CACHE_CONTROL_SITEMAP),
+        ]);
+        let opts = attributes.into();
+        self.store.put_opts(&path, content.into(), opts).await?;
+        Ok(())
+    }
+
     #[instrument(skip(self, content))]
     pub async fn sync_index(&self, name: &str, content: Option<String>) -> Result<()> {
         let path = crates_io_index::Repository::relative_index_file_for_url(name).into();
@@ -306,6 +676,48 @@ impl Storage {
 
     #[instrument(skip(self))]
     pub async fn upload_db_dump(&self, target: &str, local_path: &StdPath) -> anyhow::Result<()> {
+        self.upload_local_file(target, local_path).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_db_dump(&self, target: &str) -> Result<()> {
+        let path = target.into();
+        self.store.delete(&path).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn upload_metadata_snapshot(
+        &self,
+        file: &str,
+        local_path: &StdPath,
+    ) -> anyhow::Result<()> {
+        let target = metadata_snapshot_path(file);
+        self.upload_local_file(target.as_ref(), local_path).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn upload_analytics_file(
+        &self,
+        target: &str,
+        local_path: &StdPath,
+    ) -> anyhow::Result<()> {
+        self.upload_local_file(target, local_path).await
+    }
+
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_analytics_schema(&self, target: &str, bytes: Bytes) -> Result<()> {
+        let path = target.into();
+        let attributes = self.attrs([
+            (Attribute::ContentType, CONTENT_TYPE_JSON),
+            (Attribute::CacheControl, CACHE_CONTROL_ANALYTICS),
+        ]);
+        let opts = attributes.into();
+        self.store.put_opts(&path, bytes.into(), opts).await?;
+        Ok(())
+    }
+
+    /// Uploads a potentially large local file via a multipart upload.
+    async fn upload_local_file(&self, target: &str, local_path: &StdPath) -> anyhow::Result<()> {
         let store = self.store.clone();
 
         // Open the local tarball file
@@ -328,11 +740,124 @@ impl Storage {
         Ok(())
     }
 
+    /// Copies a crate file to the storage of the standby region.
+    #[instrument(skip(self, standby))]
+    pub async fn replicate_crate_file(
+        &self,
+        standby: &Storage,
+        name: &str,
+        version: &str,
+    ) -> Result<()> {
+        let path = crate_file_path(name, version);
+        copy_object(&*self.store, standby, &*standby.store, &path).await
+    }
+
+    /// Copies the index file of a crate to the storage of the standby region,
+    /// or deletes it there if it doesn't exist anymore.
+    #[instrument(skip(self, standby))]
+    pub async fn replicate_index_file(&self, standby: &Storage, name: &str) -> Result<()> {
+        let path = crates_io_index::Repository::relative_index_file_for_url(name).into();
+        match copy_object(&*self.index_store, standby, &*standby.index_store, &path).await {
+            Err(object_store::Error::NotFound { .. }) => standby.index_store.delete(&path).await,
+            result => result,
+        }
+    }
+
+    /// Compares the crate files and index files with the storage of the
+    /// standby region.
+    ///
+    /// Files are compared by their size, since the checksums that the
+    /// backends calculate depend on how the files were uploaded.
+    #[instrument(skip_all)]
+    pub async fn compare_replica(&self, standby: &Storage) -> Result<ReplicaDiff> {
+        let prefix = Path::from(PREFIX_CRATES);
+        let crate_files = compare_stores(&*self.store, &*standby.store, Some(&prefix)).await?;
+        let index_files = compare_stores(&*self.index_store, &*standby.index_store, None).await?;
+
+        Ok(ReplicaDiff {
+            crate_files,
+            index_files,
+        })
+    }
+
+    /// Copies the outdated files of a [`ReplicaDiff`] to the storage of the
+    /// standby region and deletes the files that only exist there.
+    #[instrument(skip_all)]
+    pub async fn repair_replica(&self, standby: &Storage, diff: &ReplicaDiff) -> Result<()> {
+        for path in &diff.crate_files.outdated {
+            copy_object(&*self.store, standby, &*standby.store, path).await?;
+        }
+        for path in &diff.crate_files.extra {
+            standby.store.delete(path).await?;
+        }
+        for path in &diff.index_files.outdated {
+            copy_object(&*self.index_store, standby, &*standby.index_store, path).await?;
+        }
+        for path in &diff.index_files.extra {
+            standby.index_store.delete(path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the paths and modification times of all published `.crate`
+    /// files.
+    #[instrument(skip(self))]
+    pub async fn list_crate_files(&self) -> Result<Vec<(String, DateTime<Utc>)>> {
+        let prefix = Path::from(PREFIX_CRATES);
+        list_files(&*self.store, Some(&prefix)).await
+    }
+
+    /// Returns the paths and modification times of all sparse index files.
+    #[instrument(skip(self))]
+    pub async fn list_index_files(&self) -> Result<Vec<(String, DateTime<Utc>)>> {
+        let files = list_files(&*self.index_store, None).await?;
+
+        // Index files are always stored in a directory, unlike the
+        // `config.json` file, which doesn't belong to a crate.
+        let files = files
+            .into_iter()
+            .filter(|(path, _)| path.contains('/'))
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Deletes a `.crate` file by the path that
+    /// [`list_crate_files`](Self::list_crate_files) returned for it.
+    #[instrument(skip(self))]
+    pub async fn delete_crate_file_at(&self, path: &str) -> Result<()> {
+        self.store.delete(&Path::parse(path)?).await
+    }
+
+    /// Deletes a sparse index file by the path that
+    /// [`list_index_files`](Self::list_index_files) returned for it.
+    #[instrument(skip(self))]
+    pub async fn delete_index_file_at(&self, path: &str) -> Result<()> {
+        self.index_store.delete(&Path::parse(path)?).await
+    }
+
+    /// Checks that the storage backend for crate files and other large
+    /// objects is reachable.
+    pub async fn check_health(&self) -> Result<()> {
+        check_store_health(&*self.store).await
+    }
+
+    /// Checks that the storage backend for the sparse index is reachable.
+    pub async fn check_index_health(&self) -> Result<()> {
+        check_store_health(&*self.index_store).await
+    }
+
     /// This should only be used for assertions in the test suite!
     pub fn as_inner(&self) -> Arc<dyn ObjectStore> {
         self.store.clone()
     }
 
+    /// This should only be used for assertions in the test suite!
+    pub fn as_private_inner(&self) -> Arc<dyn ObjectStore> {
+        self.private_store.clone()
+    }
+
     async fn delete_all_with_prefix(&self, prefix: &Path) -> Result<()> {
         let objects = self.store.list(Some(prefix));
         let locations = objects.map(|meta| meta.map(|m| m.location)).boxed();
@@ -354,6 +879,102 @@ impl Storage {
     }
 }
 
+/// The differences between the files of the primary storage and the storage
+/// of the standby region.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReplicaDiff {
+    pub crate_files: StoreDiff,
+    pub index_files: StoreDiff,
+}
+
+impl ReplicaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.crate_files.is_empty() && self.index_files.is_empty()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct StoreDiff {
+    /// Files that are missing in the replica or have a different size.
+    pub outdated: Vec<Path>,
+    /// Files that only exist in the replica, e.g. because they have been
+    /// deleted from the primary storage.
+    pub extra: Vec<Path>,
+}
+
+impl StoreDiff {
+    pub fn is_empty(&self) -> bool {
+        self.outdated.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Copies a file from one store to another, including its attributes, if
+/// the target storage supports them.
+async fn copy_object(
+    source: &dyn ObjectStore,
+    target_storage: &Storage,
+    target: &dyn ObjectStore,
+    path: &Path,
+) -> Result<()> {
+    let result = source.get(path).await?;
+    let attributes = if target_storage.supports_attributes {
+        result.attributes.clone()
+    } else {
+        Attributes::new()
+    };
+    let bytes = result.bytes().await?;
+    target
+        .put_opts(path, bytes.into(), attributes.into())
+        .await?;
+    Ok(())
+}
+
+async fn compare_stores(
+    primary: &dyn ObjectStore,
+    replica: &dyn ObjectStore,
+    prefix: Option<&Path>,
+) -> Result<StoreDiff> {
+    let mut replica_sizes = replica
+        .list(prefix)
+        .map_ok(|meta| (meta.location, meta.size))
+        .try_collect::<BTreeMap<_, _>>()
+        .await?;
+
+    let mut diff = StoreDiff::default();
+
+    let mut objects = primary.list(prefix);
+    while let Some(meta) = objects.try_next().await? {
+        if replica_sizes.remove(&meta.location) != Some(meta.size) {
+            diff.outdated.push(meta.location);
+        }
+    }
+    diff.outdated.sort();
+
+    diff.extra = replica_sizes.into_keys().collect();
+
+    Ok(diff)
+}
+
+async fn list_files(
+    store: &dyn ObjectStore,
+    prefix: Option<&Path>,
+) -> Result<Vec<(String, DateTime<Utc>)>> {
+    store
+        .list(prefix)
+        .map_ok(|meta| (meta.location.to_string(), meta.last_modified))
+        .try_collect()
+        .await
+}
+
+/// Looks up a file that usually doesn't exist, which is enough to check that
+/// the backend is reachable and accepts the credentials.
+async fn check_store_health(store: &dyn ObjectStore) -> Result<()> {
+    match store.head(&Path::from(HEALTH_CHECK_PATH)).await {
+        Ok(_) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
 fn build_s3(config: &S3Config, client_options: ClientOptions) -> AmazonS3 {
     AmazonS3Builder::new()
         .with_region(config.region.as_deref().unwrap_or(DEFAULT_REGION))
@@ -366,14 +987,38 @@ fn build_s3(config: &S3Config, client_options: ClientOptions) -> AmazonS3 {
         .unwrap()
 }
 
-fn crate_file_path(name: &str, version: &str) -> Path {
+pub(crate) fn crate_file_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_CRATES}/{name}/{name}-{version}.crate").into()
 }
 
+fn staged_crate_file_path(version_id: i32) -> Path {
+    format!("{PREFIX_STAGED_CRATES}/{version_id}.crate").into()
+}
+
+fn quarantined_crate_file_path(name: &str, version: &str) -> Path {
+    format!("{PREFIX_QUARANTINED_CRATES}/{name}/{name}-{version}.crate").into()
+}
+
 fn readme_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_READMES}/{name}/{name}-{version}.html").into()
 }
 
+fn readme_checksum_path(name: &str, checksum: &str) -> Path {
+    format!("{PREFIX_READMES}/{name}/sha256/{checksum}.html").into()
+}
+
+fn account_export_path(user_id: i32, id: i32) -> Path {
+    format!("{PREFIX_ACCOUNT_EXPORTS}/{user_id}/{id}.json").into()
+}
+
+fn metadata_snapshot_path(file: &str) -> Path {
+    format!("{PREFIX_METADATA_SNAPSHOT}/{file}").into()
+}
+
+fn sitemap_path(file: &str) -> Path {
+    format!("{PREFIX_SITEMAPS}/{file}").into()
+}
+
 fn apply_cdn_prefix(cdn_prefix: &Option<String>, path: &Path) -> String {
     match cdn_prefix {
         Some(cdn_prefix) if !cdn_prefix.starts_with("https://") => {
@@ -465,6 +1110,11 @@ mod tests {
         for (name, version, expected) in readme_tests {
             assert_eq!(storage.readme_location(name, version), expected);
         }
+
+        assert_eq!(
+            storage.readme_location_by_checksum("foo", "b94d27b9"),
+            "https://static.crates.io/readmes/foo/sha256/b94d27b9.html"
+        );
     }
 
     #[test]
@@ -555,6 +1205,31 @@ mod tests {
         assert_eq!(stored_files(&storage.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn delete_readme_by_checksum() {
+        let storage = prepare().await;
+
+        let bytes = Bytes::from_static(b"hello world");
+        storage
+            .upload_readme("foo", "b94d27b9", bytes)
+            .await
+            .unwrap();
+        storage
+            .delete_readme_by_checksum("foo", "b94d27b9")
+            .await
+            .unwrap();
+
+        let expected_files = vec![
+            "crates/bar/bar-2.0.0.crate",
+            "crates/foo/foo-1.0.0.crate",
+            "crates/foo/foo-1.2.3.crate",
+            "readmes/bar/bar-2.0.0.html",
+            "readmes/foo/foo-1.0.0.html",
+            "readmes/foo/foo-1.2.3.html",
+        ];
+        assert_eq!(stored_files(&storage.store).await, expected_files);
+    }
+
     #[tokio::test]
     async fn upload_crate_file() {
         let s = Storage::from_config(&StorageConfig::in_memory());
@@ -577,23 +1252,94 @@ mod tests {
         assert_eq!(stored_files(&s.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn publish_staged_crate_file() {
+        let s = Storage::from_config(&StorageConfig::in_memory());
+
+        s.upload_staged_crate_file(42, Bytes::from_static(b"foo"))
+            .await
+            .unwrap();
+
+        let expected_files = vec!["staged-crates/42.crate"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        let staged = s.list_staged_crate_files().await.unwrap();
+        assert_eq!(staged.len(), 1);
+        assert_eq!(staged[0].0, 42);
+
+        s.publish_staged_crate_file(42, "foo", "1.2.3")
+            .await
+            .unwrap();
+
+        let expected_files = vec!["crates/foo/foo-1.2.3.crate"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+        let bytes = s.download_crate_file("foo", "1.2.3").await.unwrap();
+        assert_eq!(bytes, Bytes::from_static(b"foo"));
+
+        // Moving the file again is a no-op...
+        s.publish_staged_crate_file(42, "foo", "1.2.3")
+            .await
+            .unwrap();
+
+        // ... unless the public file is missing too.
+        let result = s.publish_staged_crate_file(43, "foo", "2.0.0").await;
+        assert!(matches!(result, Err(object_store::Error::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn quarantine_and_restore_crate_file() {
+        let s = Storage::from_config(&StorageConfig::in_memory());
+
+        s.upload_crate_file("foo", "1.2.3", Bytes::from_static(b"foo"))
+            .await
+            .unwrap();
+
+        s.quarantine_crate_file("foo", "1.2.3").await.unwrap();
+
+        // The quarantined file is not kept in the public store.
+        assert_eq!(stored_files(&s.store).await, Vec::<String>::new());
+        let expected_files = vec!["quarantined-crates/foo/foo-1.2.3.crate"];
+        assert_eq!(stored_files(&s.private_store).await, expected_files);
+        let result = s.download_crate_file("foo", "1.2.3").await;
+        assert!(matches!(result, Err(object_store::Error::NotFound { .. })));
+        let bytes = s.download_quarantined_crate_file("foo", "1.2.3").await;
+        assert_eq!(bytes.unwrap(), Bytes::from_static(b"foo"));
+
+        // Moving the file again is a no-op.
+        s.quarantine_crate_file("foo", "1.2.3").await.unwrap();
+
+        s.restore_crate_file("foo", "1.2.3").await.unwrap();
+
+        let expected_files = vec!["crates/foo/foo-1.2.3.crate"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+        assert_eq!(stored_files(&s.private_store).await, Vec::<String>::new());
+        let bytes = s.download_crate_file("foo", "1.2.3").await.unwrap();
+        assert_eq!(bytes, Bytes::from_static(b"foo"));
+
+        s.restore_crate_file("foo", "1.2.3").await.unwrap();
+
+        // Files that don't exist in either location can't be moved.
+        let result = s.quarantine_crate_file("foo", "2.0.0").await;
+        assert!(matches!(result, Err(object_store::Error::NotFound { .. })));
+    }
+
     #[tokio::test]
     async fn upload_readme() {
         let s = Storage::from_config(&StorageConfig::in_memory());
 
         let bytes = Bytes::from_static(b"hello world");
-        s.upload_readme("foo", "1.2.3", bytes.clone())
+        s.upload_readme("foo", "b94d27b9", bytes.clone())
             .await
             .unwrap();
 
-        let expected_files = vec!["readmes/foo/foo-1.2.3.html"];
+        let expected_files = vec!["readmes/foo/sha256/b94d27b9.html"];
         assert_eq!(stored_files(&s.store).await, expected_files);
 
-        s.upload_readme("foo", "2.0.0+foo", bytes).await.unwrap();
+        s.upload_readme("bar", "b94d27b9", bytes).await.unwrap();
 
         let expected_files = vec![
-            "readmes/foo/foo-1.2.3.html",
-            "readmes/foo/foo-2.0.0+foo.html",
+            "readmes/bar/sha256/b94d27b9.html",
+            "readmes/foo/sha256/b94d27b9.html",
         ];
         assert_eq!(stored_files(&s.store).await, expected_files);
     }
@@ -0,0 +1,132 @@
+//! Surrogate keys for CDN caching of API responses.
+//!
+//! Cacheable API responses are tagged with `Surrogate-Key` headers that name
+//! the crates and users they contain. Fastly caches these responses, and
+//! mutations purge all responses of a crate or user by key with the
+//! [`PurgeSurrogateKeys`] job, instead of having to know every URL that
+//! might contain the changed data.
+//!
+//! Fastly strips the `Surrogate-Key` and `Surrogate-Control` headers before
+//! the responses are sent to clients, and other caches ignore them.
+//!
+//! [`PurgeSurrogateKeys`]: crate::worker::jobs::PurgeSurrogateKeys
+
+use axum::response::{IntoResponseParts, ResponseParts};
+use http::header::{HeaderName, HeaderValue};
+use std::convert::Infallible;
+use std::fmt;
+
+static SURROGATE_KEY: HeaderName = HeaderName::from_static("surrogate-key");
+static SURROGATE_CONTROL: HeaderName = HeaderName::from_static("surrogate-control");
+
+/// How long Fastly may cache tagged responses.
+///
+/// Mutations purge the affected responses right away, so this only limits
+/// how stale data that changes without a purge can get, like download counts.
+const MAX_AGE_SECONDS: u64 = 5 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SurrogateKey {
+    /// All responses that contain data of the crate.
+    Crate(String),
+    /// All responses that contain data of the user.
+    User(i32),
+}
+
+impl SurrogateKey {
+    /// Returns the key of the crate, which is the same for all spellings of
+    /// the crate name that refer to the same crate.
+    pub fn krate(name: &str) -> Self {
+        Self::Crate(name.to_lowercase().replace('-', "_"))
+    }
+
+    pub fn user(user_id: i32) -> Self {
+        Self::User(user_id)
+    }
+}
+
+impl fmt::Display for SurrogateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SurrogateKey::Crate(name) => write!(f, "crate/{name}"),
+            SurrogateKey::User(user_id) => write!(f, "user/{user_id}"),
+        }
+    }
+}
+
+/// Marks a response as cacheable by the CDN and tags it with the given keys.
+///
+/// This can be returned from a handler as part of a tuple with the response
+/// body, e.g. `(SurrogateKeys, ErasedJson)`.
+#[derive(Debug, Clone, Default)]
+pub struct SurrogateKeys(Vec<SurrogateKey>);
+
+impl SurrogateKeys {
+    pub fn new(keys: impl IntoIterator<Item = SurrogateKey>) -> Self {
+        Self(keys.into_iter().collect())
+    }
+
+    pub fn krate(name: &str) -> Self {
+        Self::new([SurrogateKey::krate(name)])
+    }
+
+    pub fn user(user_id: i32) -> Self {
+        Self::new([SurrogateKey::user(user_id)])
+    }
+
+    fn header_value(&self) -> Option<HeaderValue> {
+        let keys = self
+            .0
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        HeaderValue::try_from(keys).ok()
+    }
+}
+
+impl IntoResponseParts for SurrogateKeys {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        // Responses without keys can't be purged, so they are not cached.
+        if self.0.is_empty() {
+            return Ok(res);
+        }
+
+        // Crate names only contain characters that are valid in header
+        // values, so this is not expected to fail.
+        let Some(value) = self.header_value() else {
+            warn!(keys = ?self.0, "Failed to encode surrogate keys");
+            return Ok(res);
+        };
+
+        let headers = res.headers_mut();
+        headers.insert(SURROGATE_KEY.clone(), value);
+
+        let surrogate_control = format!("max-age={MAX_AGE_SECONDS}");
+        let surrogate_control = HeaderValue::try_from(surrogate_control).unwrap();
+        headers.insert(SURROGATE_CONTROL.clone(), surrogate_control);
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crate_keys_are_canonical() {
+        assert_eq!(SurrogateKey::krate("Foo-Bar").to_string(), "crate/foo_bar");
+        assert_eq!(SurrogateKey::krate("foo_bar").to_string(), "crate/foo_bar");
+    }
+
+    #[test]
+    fn test_header_value() {
+        let keys = SurrogateKeys::new([SurrogateKey::krate("serde"), SurrogateKey::user(42)]);
+        let value = keys.header_value().unwrap();
+        assert_eq!(value, "crate/serde user/42");
+    }
+}
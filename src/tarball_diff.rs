@@ -0,0 +1,244 @@
+//! Reading and comparison of the files in the `.crate` files of versions.
+//!
+//! The diff only contains which files have been added, removed or modified,
+//! and how their size has changed. It is meant to help reviewers spot
+//! unexpected changes between two releases, not to show the changed lines.
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io::Read;
+
+/// A single file of a crate tarball.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    pub size: u64,
+    checksum: [u8; 32],
+}
+
+/// Reads the regular files of a crate tarball, keyed by their path relative
+/// to the package root directory.
+pub fn list_files(tarball: &[u8]) -> anyhow::Result<BTreeMap<String, FileInfo>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(tarball));
+
+    let mut files = BTreeMap::new();
+    for entry in archive.entries().context("Failed to read tarball")? {
+        let mut entry = entry.context("Failed to read tarball entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path().context("Failed to read entry path")?;
+        let path = path.to_string_lossy();
+        // Strip the `{name}-{version}/` prefix of the package directory.
+        let path = path.split_once('/').map_or(&*path, |(_, path)| path);
+        let path = path.to_string();
+
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .with_context(|| format!("Failed to read {path}"))?;
+
+        let size = contents.len() as u64;
+        let checksum = Sha256::digest(&contents).into();
+        files.insert(path, FileInfo { size, checksum });
+    }
+
+    Ok(files)
+}
+
+/// Reads the contents of the file with the given path, relative to the
+/// package root directory, from a crate tarball.
+pub fn read_file(tarball: &[u8], path: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(tarball));
+
+    for entry in archive.entries().context("Failed to read tarball")? {
+        let mut entry = entry.context("Failed to read tarball entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let entry_path = entry.path().context("Failed to read entry path")?;
+        let entry_path = entry_path.to_string_lossy();
+        let entry_path = entry_path.split_once('/').map(|(_, path)| path);
+        if entry_path != Some(path) {
+            continue;
+        }
+
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .with_context(|| format!("Failed to read {path}"))?;
+
+        return Ok(Some(contents));
+    }
+
+    Ok(None)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A file that differs between the two versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub status: FileStatus,
+    /// The difference of the file size in bytes.
+    pub size_delta: i64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TarballDiff {
+    pub files_added: i32,
+    pub files_removed: i32,
+    pub files_modified: i32,
+    /// The difference of the total size of all files in bytes.
+    pub size_delta: i64,
+    /// The changed files, sorted by path.
+    pub files: Vec<FileChange>,
+}
+
+/// Compares the files of the `old` and `new` versions.
+pub fn diff(old: &BTreeMap<String, FileInfo>, new: &BTreeMap<String, FileInfo>) -> TarballDiff {
+    let mut diff = TarballDiff::default();
+
+    let mut old_files = old.iter().peekable();
+    let mut new_files = new.iter().peekable();
+
+    loop {
+        let ordering = match (old_files.peek(), new_files.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((old_path, _)), Some((new_path, _))) => old_path.cmp(new_path),
+        };
+
+        let change = match ordering {
+            Ordering::Less => {
+                let (path, old) = old_files.next().unwrap();
+                diff.files_removed += 1;
+                (path, FileStatus::Removed, -(old.size as i64))
+            }
+            Ordering::Greater => {
+                let (path, new) = new_files.next().unwrap();
+                diff.files_added += 1;
+                (path, FileStatus::Added, new.size as i64)
+            }
+            Ordering::Equal => {
+                let (path, old) = old_files.next().unwrap();
+                let (_, new) = new_files.next().unwrap();
+                if old == new {
+                    continue;
+                }
+
+                diff.files_modified += 1;
+                (
+                    path,
+                    FileStatus::Modified,
+                    new.size as i64 - old.size as i64,
+                )
+            }
+        };
+
+        let (path, status, size_delta) = change;
+        diff.size_delta += size_delta;
+        diff.files.push(FileChange {
+            path: path.clone(),
+            status,
+            size_delta,
+        });
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crates_io_tarball::TarballBuilder;
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn test_list_files() {
+        let tarball = TarballBuilder::new()
+            .add_file("foo-0.1.0/Cargo.toml", b"[package]\nname = \"foo\"\n")
+            .add_file("foo-0.1.0/src/lib.rs", b"pub fn foo() {}")
+            .build();
+
+        let files = list_files(&tarball).unwrap();
+        let sizes = files
+            .iter()
+            .map(|(path, file)| (path.as_str(), file.size))
+            .collect::<Vec<_>>();
+
+        assert_eq!(sizes, vec![("Cargo.toml", 23), ("src/lib.rs", 15)]);
+    }
+
+    #[test]
+    fn test_read_file() {
+        let tarball = TarballBuilder::new()
+            .add_file("foo-0.1.0/Cargo.toml", b"[package]\nname = \"foo\"\n")
+            .add_file("foo-0.1.0/src/lib.rs", b"pub fn foo() {}")
+            .build();
+
+        let contents = read_file(&tarball, "src/lib.rs").unwrap();
+        assert_eq!(contents.as_deref(), Some(&b"pub fn foo() {}"[..]));
+
+        assert_eq!(read_file(&tarball, "src/main.rs").unwrap(), None);
+        assert_eq!(read_file(&tarball, "foo-0.1.0/src/lib.rs").unwrap(), None);
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = TarballBuilder::new()
+            .add_file("foo-0.1.0/Cargo.toml", b"[package]\nname = \"foo\"\n")
+            .add_file("foo-0.1.0/README.md", b"# foo")
+            .add_file("foo-0.1.0/src/lib.rs", b"pub fn foo() {}")
+            .build();
+
+        let new = TarballBuilder::new()
+            .add_file("foo-0.2.0/Cargo.toml", b"[package]\nname = \"foo\"\n")
+            .add_file("foo-0.2.0/build.rs", b"fn main() {}")
+            .add_file("foo-0.2.0/src/lib.rs", b"pub fn foo() {}\npub fn bar() {}")
+            .build();
+
+        let old = list_files(&old).unwrap();
+        let new = list_files(&new).unwrap();
+
+        assert_debug_snapshot!(diff(&old, &new), @r#"
+        TarballDiff {
+            files_added: 1,
+            files_removed: 1,
+            files_modified: 1,
+            size_delta: 23,
+            files: [
+                FileChange {
+                    path: "README.md",
+                    status: Removed,
+                    size_delta: -5,
+                },
+                FileChange {
+                    path: "build.rs",
+                    status: Added,
+                    size_delta: 12,
+                },
+                FileChange {
+                    path: "src/lib.rs",
+                    status: Modified,
+                    size_delta: 16,
+                },
+            ],
+        }
+        "#);
+
+        assert_eq!(diff(&old, &old), TarballDiff::default());
+    }
+}
@@ -1,10 +1,17 @@
+use crate::deadline::Deadline;
+use crate::metrics::InstanceMetrics;
+use crate::util::errors::{request_timeout, service_unavailable, AppResult};
+use prometheus::IntGauge;
 use sentry::Hub;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 
 /// Runs the provided closure on a thread where blocking is acceptable.
 ///
 /// This is using [tokio::task::spawn_blocking] internally, but automatically
-/// runs the callback function in the context of the current Sentry [Hub].
+/// runs the callback function in the context of the current Sentry [Hub],
+/// and with the [Deadline] of the current request, if there is one.
 pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
 where
     F: FnOnce() -> R + Send + 'static,
@@ -12,13 +19,113 @@ where
 {
     let current_span = tracing::Span::current();
     let hub = Hub::current();
-    tokio::task::spawn_blocking(move || current_span.in_scope(|| Hub::run(hub, f)))
+    let deadline = Deadline::current();
+    tokio::task::spawn_blocking(move || {
+        current_span.in_scope(|| {
+            Hub::run(hub, || match deadline {
+                Some(deadline) => deadline.sync_scope(f),
+                None => f(),
+            })
+        })
+    })
+}
+
+/// A pool for the blocking work of request handlers.
+///
+/// At most `size` closures run at the same time, and at most `queue_size`
+/// closures wait for a free worker. Further closures are rejected with a
+/// `503 Service Unavailable` error, so that a burst of expensive requests
+/// can't exhaust the blocking threads of the runtime.
+///
+/// Closures of requests that have passed their [Deadline] while waiting for a
+/// free worker are not run at all, since nobody is waiting for their result
+/// anymore.
+pub struct BlockingPool {
+    workers: Arc<Semaphore>,
+    queue_size: i64,
+    queue_depth: IntGauge,
+    active_workers: IntGauge,
+}
+
+impl BlockingPool {
+    pub fn new(size: usize, queue_size: usize, metrics: &InstanceMetrics) -> Self {
+        Self {
+            workers: Arc::new(Semaphore::new(size)),
+            queue_size: queue_size as i64,
+            queue_depth: metrics.blocking_pool_queue_depth.clone(),
+            active_workers: metrics.blocking_pool_active_workers.clone(),
+        }
+    }
+
+    /// Runs the provided closure on the pool, see [spawn_blocking].
+    pub async fn run<F, R>(&self, f: F) -> AppResult<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let deadline = Deadline::current();
+
+        let permit = {
+            let _queued = GaugeGuard::inc(&self.queue_depth);
+            if self.queue_depth.get() > self.queue_size {
+                warn!("Blocking pool is saturated, rejecting request");
+                return Err(service_unavailable());
+            }
+
+            // The semaphore is never closed, so this can't fail.
+            let permit = self.workers.clone().acquire_owned();
+            match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline.instant(), permit)
+                    .await
+                    .map_err(|_| request_timeout())?
+                    .unwrap(),
+                None => permit.await.unwrap(),
+            }
+        };
+
+        let active_workers = self.active_workers.clone();
+        let result = spawn_blocking(move || {
+            let _permit = permit;
+
+            // The blocking threads of the runtime might be busy with other
+            // work, so the deadline can pass before the closure starts.
+            if deadline.is_some_and(|deadline| deadline.is_expired()) {
+                return None;
+            }
+
+            let _active = GaugeGuard::inc(&active_workers);
+            Some(f())
+        });
+
+        result.await?.ok_or_else(request_timeout)
+    }
+}
+
+/// Decrements the gauge when dropped, even if the future waiting for a free
+/// worker is cancelled or the closure panics.
+struct GaugeGuard<'a> {
+    gauge: &'a IntGauge,
+}
+
+impl<'a> GaugeGuard<'a> {
+    fn inc(gauge: &'a IntGauge) -> Self {
+        gauge.inc();
+        Self { gauge }
+    }
+}
+
+impl Drop for GaugeGuard<'_> {
+    fn drop(&mut self) {
+        self.gauge.dec();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::util::errors::BoxedAppError;
+    use http::StatusCode;
+    use std::time::Duration;
 
     /// Test that [spawn_blocking] works with [anyhow].
     #[tokio::test]
@@ -37,4 +144,53 @@ mod tests {
             .unwrap()
             .unwrap()
     }
+
+    /// Test that [BlockingPool] rejects closures once the queue is full.
+    #[tokio::test]
+    async fn test_blocking_pool_load_shedding() {
+        let metrics = InstanceMetrics::new().unwrap();
+        let pool = Arc::new(BlockingPool::new(1, 1, &metrics));
+
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let running = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(move || rx.recv().unwrap()).await }
+        });
+        while metrics.blocking_pool_active_workers.get() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(|| ()).await }
+        });
+        while metrics.blocking_pool_queue_depth.get() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let error = pool.run(|| ()).await.unwrap_err();
+        assert_eq!(error.response().status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        tx.send(()).unwrap();
+        assert_ok!(running.await.unwrap());
+        assert_ok!(queued.await.unwrap());
+
+        assert_eq!(metrics.blocking_pool_queue_depth.get(), 0);
+        assert_eq!(metrics.blocking_pool_active_workers.get(), 0);
+    }
+
+    /// Test that [BlockingPool] doesn't run closures of requests that have
+    /// passed their deadline.
+    #[tokio::test]
+    async fn test_blocking_pool_expired_deadline() {
+        let metrics = InstanceMetrics::new().unwrap();
+        let pool = BlockingPool::new(1, 1, &metrics);
+
+        let deadline = Deadline::after(Duration::ZERO);
+        let result = deadline.scope(pool.run(|| panic!("closure was run"))).await;
+
+        let error = result.unwrap_err();
+        assert_eq!(error.response().status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(metrics.blocking_pool_active_workers.get(), 0);
+    }
 }
@@ -1,9 +1,10 @@
 use crate::tests::util::{MockRequestExt, RequestHelper, Response};
 use crate::tests::TestApp;
 
-use crate::tests::util::encode_session_header;
+use crate::tests::util::{encode_session, encode_session_header, CSRF_TOKEN};
 use http::{header, Method, StatusCode};
 use insta::assert_snapshot;
+use std::collections::HashMap;
 
 static URL: &str = "/api/v1/me/updates";
 
@@ -43,3 +44,86 @@ async fn cookie_auth_cannot_find_user() {
     let error = anon.run::<()>(request).await;
     assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
 }
+
+static MUTATING_URL: &str = "/api/v1/me/email_notifications";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cookie_auth_requires_csrf_token() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+
+    let session_key = app.as_inner().session_key();
+    let cookie = encode_session_header(session_key, user.as_model().id);
+
+    let mut request = anon.request_builder(Method::PUT, MUTATING_URL);
+    request.header(header::COOKIE, &cookie);
+    let response: Response<()> = anon.run(request.with_body("[]".into())).await;
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires the `X-CSRF-Token` header when authenticated by a session cookie"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cookie_auth_rejects_invalid_csrf_token() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+
+    let session_key = app.as_inner().session_key();
+    let cookie = encode_session_header(session_key, user.as_model().id);
+
+    let mut request = anon.request_builder(Method::PUT, MUTATING_URL);
+    request.header(header::COOKIE, &cookie);
+    request.header("x-csrf-token", "not-the-session-token");
+    let response: Response<()> = anon.run(request.with_body("[]".into())).await;
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the `X-CSRF-Token` header does not match the token of the session"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cookie_auth_with_csrf_token() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+
+    let session_key = app.as_inner().session_key();
+    let cookie = encode_session_header(session_key, user.as_model().id);
+
+    let mut request = anon.request_builder(Method::PUT, MUTATING_URL);
+    request.header(header::COOKIE, &cookie);
+    request.header("x-csrf-token", CSRF_TOKEN);
+    let response: Response<()> = anon.run(request.with_body("[]".into())).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_auth_does_not_require_csrf_token() {
+    let (_, _, _, token) = TestApp::init().with_token().await;
+
+    let request = token.request_builder(Method::PUT, MUTATING_URL);
+    let response: Response<()> = token.run(request.with_body("[]".into())).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn csrf_token_is_issued_to_existing_sessions() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+
+    // Sessions that were created before CSRF tokens were introduced
+    let session_key = app.as_inner().session_key();
+    let session = HashMap::from([("user_id".to_string(), user.as_model().id.to_string())]);
+    let cookie = encode_session(session_key, &session);
+
+    let mut request = anon.request_builder(Method::GET, URL);
+    request.header(header::COOKIE, &cookie);
+    let response: Response<()> = anon.run(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let cookies = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert!(cookies.iter().any(|c| c.starts_with("cargo_session=")));
+    assert!(cookies.iter().any(|c| c.starts_with("csrf_token=")));
+}
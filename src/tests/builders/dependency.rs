@@ -5,6 +5,7 @@ pub struct DependencyBuilder {
     explicit_name_in_toml: Option<String>,
     name: String,
     features: Vec<String>,
+    optional: bool,
     registry: Option<String>,
     version_req: String,
 }
@@ -16,6 +17,7 @@ impl DependencyBuilder {
             explicit_name_in_toml: None,
             name: name.to_string(),
             features: vec![],
+            optional: false,
             registry: None,
             version_req: "> 0".to_string(),
         }
@@ -44,6 +46,12 @@ impl DependencyBuilder {
         self
     }
 
+    /// Mark this dependency as optional.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
     pub fn add_feature<T: Into<String>>(mut self, feature: T) -> Self {
         self.features.push(feature.into());
         self
@@ -54,7 +62,7 @@ impl DependencyBuilder {
     pub fn build(self) -> u::EncodableCrateDependency {
         u::EncodableCrateDependency {
             name: self.name,
-            optional: false,
+            optional: self.optional,
             default_features: true,
             features: self.features,
             version_req: self.version_req,
@@ -1,5 +1,5 @@
 use crate::models::{update_default_version, Category, Crate, Keyword, NewCrate};
-use crate::schema::{crate_downloads, crates, version_downloads};
+use crate::schema::{crate_downloads, crates, recent_crate_downloads, version_downloads};
 use crate::util::errors::AppResult;
 
 use super::VersionBuilder;
@@ -64,6 +64,12 @@ impl<'a> CrateBuilder<'a> {
         self
     }
 
+    /// Sets the crate's `repository` URL.
+    pub fn repository(mut self, repository: &'a str) -> Self {
+        self.krate.repository = Some(repository);
+        self
+    }
+
     /// Sets the crate's `max_upload_size` override value.
     pub fn max_upload_size(mut self, max_upload_size: i32) -> Self {
         self.krate.max_upload_size = Some(max_upload_size);
@@ -115,7 +121,7 @@ impl<'a> CrateBuilder<'a> {
     }
 
     pub async fn build(mut self, connection: &mut AsyncPgConnection) -> AppResult<Crate> {
-        use diesel::{insert_into, select, update};
+        use diesel::{insert_into, update};
 
         let mut krate = self.krate.create(connection, self.owner_id).await?;
 
@@ -150,8 +156,11 @@ impl<'a> CrateBuilder<'a> {
                 .execute(connection)
                 .await?;
 
-            define_sql_function!(fn refresh_recent_crate_downloads());
-            select(refresh_recent_crate_downloads())
+            insert_into(recent_crate_downloads::table)
+                .values((
+                    recent_crate_downloads::crate_id.eq(krate.id),
+                    recent_crate_downloads::downloads.eq(i64::from(downloads)),
+                ))
                 .execute(connection)
                 .await?;
         }
@@ -23,6 +23,7 @@ pub struct PublishBuilder {
     license_file: Option<String>,
     manifest: Manifest,
     readme: Option<String>,
+    repository: Option<String>,
     version: semver::Version,
     features: BTreeMap<String, Vec<String>>,
 }
@@ -49,6 +50,7 @@ impl PublishBuilder {
             license_file: None,
             manifest: Manifest::Generated,
             readme: None,
+            repository: None,
             version: semver::Version::parse(version).unwrap(),
             features: BTreeMap::new(),
         }
@@ -85,6 +87,12 @@ impl PublishBuilder {
         self
     }
 
+    /// Set the repository URL of this crate
+    pub fn repository(mut self, repository: &str) -> Self {
+        self.repository = Some(repository.to_string());
+        self
+    }
+
     /// Add a keyword to this crate.
     pub fn keyword(mut self, keyword: &str) -> Self {
         self.keywords.push(keyword.into());
@@ -159,6 +167,7 @@ impl PublishBuilder {
                 package.keywords = self.keywords.none_or_filled().map(MaybeInherited::Local);
                 package.license = self.license.map(MaybeInherited::Local);
                 package.license_file = self.license_file.map(MaybeInherited::Local);
+                package.repository = self.repository.map(MaybeInherited::Local);
 
                 let mut build_deps = DepsSet::new();
                 let mut deps = DepsSet::new();
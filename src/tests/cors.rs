@@ -1,6 +1,6 @@
 use crate::tests::util::{MockRequestExt, RequestHelper};
 use crate::tests::TestApp;
-use http::StatusCode;
+use http::{header, Method, StatusCode};
 use insta::assert_snapshot;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -17,6 +17,13 @@ async fn test_with_matching_origin() {
 
     let response = cookie.run::<()>(request).await;
     assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers();
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://crates.io"
+    );
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    assert!(headers.get_all(header::VARY).iter().any(|v| v == "Origin"));
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -53,3 +60,100 @@ async fn test_with_multiple_origins() {
     assert_eq!(response.status(), StatusCode::FORBIDDEN);
     assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid origin header"}]}"#);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_public_preflight() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let mut request = anon.request_builder(Method::OPTIONS, "/api/v1/crates");
+    request.header("Origin", "https://example.com");
+    request.header("Access-Control-Request-Method", "GET");
+
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let headers = response.headers();
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET, HEAD");
+    assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "86400");
+    assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+
+    let mut request = anon.get_request("/api/v1/crates");
+    request.header("Origin", "https://example.com");
+
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers();
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_authenticated_preflight() {
+    let (_, anon) = TestApp::init()
+        .with_config(|server| {
+            server.allowed_origins = "https://crates.io".parse().unwrap();
+        })
+        .empty()
+        .await;
+
+    let mut request = anon.request_builder(Method::OPTIONS, "/api/v1/crates/foo/owners");
+    request.header("Origin", "https://crates.io");
+    request.header("Access-Control-Request-Method", "PUT");
+
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let headers = response.headers();
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://crates.io"
+    );
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_METHODS],
+        "GET, HEAD, POST, PUT, PATCH, DELETE"
+    );
+    assert_eq!(headers[header::VARY], "Origin");
+
+    let mut request = anon.request_builder(Method::OPTIONS, "/api/v1/crates/foo/owners");
+    request.header("Origin", "https://evil.hacker.io");
+    request.header("Access-Control-Request-Method", "PUT");
+
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let headers = response.headers();
+    assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_METHODS));
+
+    let mut request = anon.request_builder(Method::OPTIONS, "/api/private/session/begin");
+    request.header("Origin", "https://evil.hacker.io");
+    request.header("Access-Control-Request-Method", "GET");
+
+    let response = anon.run::<()>(request).await;
+    assert!(!response
+        .headers()
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_index_requests() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let mut request = anon.request_builder(Method::OPTIONS, "/index/3/f/foo");
+    request.header("Origin", "https://example.com");
+    request.header("Access-Control-Request-Method", "GET");
+
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+
+    let mut request = anon.get_request("/index/3/f/foo");
+    request.header("Origin", "https://example.com");
+
+    let response = anon.run::<()>(request).await;
+    let headers = response.headers();
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_EXPOSE_HEADERS],
+        "ETag, Last-Modified"
+    );
+}
@@ -1,9 +1,10 @@
 use crate::tests::builders::CrateBuilder;
-use crate::tests::util::TestApp;
+use crate::tests::util::{RequestHelper, TestApp};
 use crate::worker::jobs::DumpDb;
 use bytes::Buf;
 use crates_io_worker::BackgroundJob;
 use flate2::read::GzDecoder;
+use http::StatusCode;
 use insta::{assert_debug_snapshot, assert_snapshot};
 use regex::Regex;
 use std::io::{Cursor, Read};
@@ -13,6 +14,9 @@ use tar::Archive;
 static PATH_DATE_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}-\d{6}").unwrap());
 
+static INCREMENTAL_PATH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(db-dump-incremental/)\d{4}-\d{2}-\d{2}-\d{6}").unwrap());
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_dump_db_job() -> anyhow::Result<()> {
     let (app, _, _, token) = TestApp::full().with_token().await;
@@ -53,6 +57,7 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
         "YYYY-MM-DD-HHMMSS/data/crates.csv",
         "YYYY-MM-DD-HHMMSS/data/keywords.csv",
         "YYYY-MM-DD-HHMMSS/data/metadata.csv",
+        "YYYY-MM-DD-HHMMSS/data/organizations.csv",
         "YYYY-MM-DD-HHMMSS/data/reserved_crate_names.csv",
         "YYYY-MM-DD-HHMMSS/data/teams.csv",
         "YYYY-MM-DD-HHMMSS/data/users.csv",
@@ -85,6 +90,7 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
         "data/crates.csv",
         "data/keywords.csv",
         "data/metadata.csv",
+        "data/organizations.csv",
         "data/reserved_crate_names.csv",
         "data/teams.csv",
         "data/users.csv",
@@ -101,6 +107,114 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_incremental_dump_db_job() -> anyhow::Result<()> {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("test-crate", token.as_model().user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    DumpDb.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    CrateBuilder::new("other-crate", token.as_model().user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    DumpDb.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let files = app.stored_files().await;
+    let files = files
+        .iter()
+        .map(|path| INCREMENTAL_PATH_RE.replace(path, "${1}YYYY-MM-DD-HHMMSS"))
+        .collect::<Vec<_>>();
+    assert_snapshot!(files.join("\n"), @r"
+    db-dump-incremental/YYYY-MM-DD-HHMMSS.tar.gz
+    db-dump-incremental/YYYY-MM-DD-HHMMSS.zip
+    db-dump.tar.gz
+    db-dump.zip
+    ");
+
+    let path = app
+        .stored_files()
+        .await
+        .into_iter()
+        .find(|path| path.starts_with("db-dump-incremental/") && path.ends_with(".zip"))
+        .unwrap();
+    let path = object_store::path::Path::parse(path)?;
+    let result = app.as_inner().storage.as_inner().get(&path).await?;
+    let bytes = result.bytes().await?;
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let zip_paths = archive.file_names().map(String::from).collect::<Vec<_>>();
+    assert_debug_snapshot!(zip_paths, @r#"
+    [
+        "README.md",
+        "export.sql",
+        "metadata.json",
+        "schema.sql",
+        "data/",
+        "data/categories.csv",
+        "data/crate_downloads.csv",
+        "data/crates.csv",
+        "data/keywords.csv",
+        "data/metadata.csv",
+        "data/organizations.csv",
+        "data/teams.csv",
+        "data/users.csv",
+        "data/crates_categories.csv",
+        "data/crates_keywords.csv",
+        "data/crate_owners.csv",
+        "data/versions.csv",
+        "data/default_versions.csv",
+        "data/dependencies.csv",
+        "data/version_downloads.csv",
+    ]
+    "#);
+
+    let mut crates_csv = String::new();
+    archive
+        .by_name("data/crates.csv")?
+        .read_to_string(&mut crates_csv)?;
+    assert!(crates_csv.contains("other-crate"));
+    assert!(!crates_csv.contains("test-crate"));
+
+    let response = anon.get::<()>("/api/v1/db_dumps").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    let dumps = json["dumps"].as_array().unwrap();
+    assert_eq!(dumps.len(), 4);
+
+    let mut urls = dumps
+        .iter()
+        .map(|dump| {
+            assert_eq!(dump["checksum"].as_str().unwrap().len(), 64);
+            assert!(dump["size"].as_i64().unwrap() > 0);
+            let url = dump["url"].as_str().unwrap();
+            let url = INCREMENTAL_PATH_RE.replace(url, "${1}YYYY-MM-DD-HHMMSS");
+            format!(
+                "{} {url} since={}",
+                dump["kind"].as_str().unwrap(),
+                !dump["since"].is_null()
+            )
+        })
+        .collect::<Vec<_>>();
+    urls.sort();
+    assert_debug_snapshot!(urls, @r#"
+    [
+        "full https://static.crates.io/db-dump.tar.gz since=false",
+        "full https://static.crates.io/db-dump.zip since=false",
+        "incremental https://static.crates.io/db-dump-incremental/YYYY-MM-DD-HHMMSS.tar.gz since=true",
+        "incremental https://static.crates.io/db-dump-incremental/YYYY-MM-DD-HHMMSS.zip since=true",
+    ]
+    "#);
+
+    Ok(())
+}
+
 fn tar_paths<R: Read>(archive: &mut Archive<R>) -> Vec<String> {
     archive
         .entries()
@@ -0,0 +1,64 @@
+use crate::email::Email;
+use crate::schema::sent_emails;
+use crate::tests::util::TestApp;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+struct TestEmail;
+
+impl Email for TestEmail {
+    fn subject(&self) -> String {
+        "test".into()
+    }
+
+    fn body(&self) -> String {
+        "test".into()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idempotent_emails_are_only_sent_once() {
+    let (app, _) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+    let emails = &app.as_inner().emails;
+
+    let recipient = "someone@example.com";
+    for _ in 0..2 {
+        let result = emails.send_idempotent(&mut conn, "foo", recipient, TestEmail);
+        assert_ok!(result.await);
+    }
+    assert_eq!(app.emails().await.len(), 1);
+
+    let result = emails.send_idempotent(&mut conn, "bar", recipient, TestEmail);
+    assert_ok!(result.await);
+    assert_eq!(app.emails().await.len(), 2);
+
+    let message_ids: Vec<Option<String>> = sent_emails::table
+        .select(sent_emails::message_id)
+        .load(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(message_ids.len(), 2);
+    assert!(message_ids.iter().all(Option::is_some));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_idempotent_emails_can_be_retried() {
+    let (app, _) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+    let emails = &app.as_inner().emails;
+
+    let result = emails.send_idempotent(&mut conn, "foo", "invalid", TestEmail);
+    assert_err!(result.await);
+
+    let count: i64 = sent_emails::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    let result = emails.send_idempotent(&mut conn, "foo", "someone@example.com", TestEmail);
+    assert_ok!(result.await);
+    assert_eq!(app.emails().await.len(), 1);
+}
@@ -0,0 +1,100 @@
+use crate::http_server::serve;
+use crate::tests::util::TestApp;
+use http::{header, Request, StatusCode, Version};
+use http_body_util::BodyExt;
+use hyper::client::conn::{http1, http2};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+fn request(addr: SocketAddr) -> Request<String> {
+    Request::get(format!("http://{addr}/api/v1/site_metadata"))
+        .header(header::USER_AGENT, "crates.io test")
+        .body(String::new())
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn http_connections() {
+    let (app, _) = TestApp::init()
+        .with_config(|config| config.http.http2 = true)
+        .empty()
+        .await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let shutdown = async move {
+        let _ = shutdown_rx.await;
+    };
+    let server = serve(app.app_arc(), listener, app.router().clone(), shutdown);
+    let server = tokio::spawn(server);
+
+    // Two requests over the same HTTP/1.1 connection
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (mut sender, connection) = http1::handshake(TokioIo::new(stream)).await.unwrap();
+    let connection = tokio::spawn(connection);
+    for _ in 0..2 {
+        sender.ready().await.unwrap();
+        let response = sender.send_request(request(addr)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.version(), Version::HTTP_11);
+        response.into_body().collect().await.unwrap();
+    }
+    drop(sender);
+    connection.await.unwrap().unwrap();
+
+    // One request over an HTTP/2 connection with prior knowledge
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (mut sender, connection) = http2::handshake(TokioExecutor::new(), TokioIo::new(stream))
+        .await
+        .unwrap();
+    let connection = tokio::spawn(connection);
+    let response = sender.send_request(request(addr)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.version(), Version::HTTP_2);
+    response.into_body().collect().await.unwrap();
+    drop(sender);
+    connection.await.unwrap().unwrap();
+
+    shutdown_tx.send(()).unwrap();
+    server.await.unwrap();
+
+    let metrics = &app.as_inner().instance_metrics;
+    assert_eq!(metrics.http_connections_total.get(), 2);
+    assert_eq!(metrics.http_connections_open.get(), 0);
+    let requests = &metrics.http_requests_by_version_total;
+    assert_eq!(requests.with_label_values(&["HTTP/1.1"]).get(), 2);
+    assert_eq!(requests.with_label_values(&["HTTP/2.0"]).get(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn http2_can_be_disabled() {
+    let (app, _) = TestApp::init().empty().await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let shutdown = async move {
+        let _ = shutdown_rx.await;
+    };
+    let server = serve(app.app_arc(), listener, app.router().clone(), shutdown);
+    let server = tokio::spawn(server);
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (mut sender, connection) = http2::handshake(TokioExecutor::new(), TokioIo::new(stream))
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+    assert_err!(sender.send_request(request(addr)).await);
+    drop(sender);
+
+    shutdown_tx.send(()).unwrap();
+    server.await.unwrap();
+
+    let metrics = &app.as_inner().instance_metrics;
+    assert_eq!(metrics.http_connection_errors_total.get(), 1);
+}
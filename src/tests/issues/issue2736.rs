@@ -1,4 +1,4 @@
-use crate::models::{CrateOwner, OwnerKind};
+use crate::models::{CrateOwner, OwnerKind, OwnerRole};
 use crate::tests::builders::CrateBuilder;
 use crate::tests::util::{RequestHelper, TestApp};
 use crates_io_database::schema::{crate_owners, users};
@@ -30,6 +30,7 @@ async fn test_issue_2736() -> anyhow::Result<()> {
             created_by: someone_else.as_model().id,
             owner_kind: OwnerKind::User,
             email_notifications: true,
+            role: OwnerRole::Admin,
         })
         .execute(&mut conn)
         .await?;
@@ -10,7 +10,7 @@ async fn assert_is_following(crate_name: &str, expected: bool, user: &impl Reque
         .get::<()>(&format!("/api/v1/crates/{crate_name}/following"))
         .await;
     assert_eq!(response.status(), StatusCode::OK);
-    assert_eq!(response.json(), json!({ "following": expected }));
+    assert_eq!(response.json()["following"], expected);
 }
 
 async fn follow(crate_name: &str, user: &impl RequestHelper) {
@@ -146,3 +146,44 @@ async fn test_api_token_auth() {
     let json = token.search("following=1").await;
     assert_that!(json.crates, len(eq(1)));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_following_notifications() {
+    const CRATE_NAME: &str = "foo_following";
+    const URL: &str = "/api/v1/crates/foo_following/following";
+
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new(CRATE_NAME, user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"following":false}"#);
+
+    // Switching on notifications also follows the crate
+    let body = json!({ "email": true });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"following":true,"notifications":{"email":true,"webhook_url":null}}"#);
+
+    let body = json!({ "email": false, "webhook_url": "https://example.com/hook" });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"following":true,"notifications":{"email":false,"webhook_url":"https://example.com/hook"}}"#);
+
+    let body = json!({ "webhook_url": "http://example.com/hook" });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the webhook URL must use `https`"}]}"#);
+
+    // Unfollowing the crate also switches off the notifications
+    unfollow(CRATE_NAME, &user).await;
+    follow(CRATE_NAME, &user).await;
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"following":true,"notifications":{"email":false,"webhook_url":null}}"#);
+}
@@ -0,0 +1,78 @@
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use googletest::prelude::*;
+use http::StatusCode;
+use insta::assert_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn funding_links_from_manifest() {
+    let (_app, anon, _, token) = TestApp::full().with_token().await;
+
+    let manifest = r#"
+    [package]
+    name = "foo"
+    version = "1.0.0"
+    description = "foo?!"
+    license = "MIT"
+
+    [package.metadata.funding]
+    github = "octocat"
+    "#;
+
+    // The manifest takes precedence over the `FUNDING.yml` file
+    let pb = PublishBuilder::new("foo", "1.0.0")
+        .custom_manifest(manifest)
+        .add_file("foo-1.0.0/FUNDING.yml", "patreon: octocat\n");
+    token.publish_crate(pb).await.good();
+
+    let response = anon.get::<()>("/api/v1/crates/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_snapshot!(json["crate"]["funding_links"], @r#"[{"platform":"github","url":"https://github.com/sponsors/octocat"}]"#);
+
+    // Publishing a version without funding links removes them
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    let krate = anon.show_crate("foo").await.krate;
+    assert_eq!(krate.funding_links, []);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn funding_links_from_funding_yml() {
+    let (_app, anon, _, token) = TestApp::full().with_token().await;
+
+    let funding_yml = "github: [octocat]\ncustom: ['https://www.paypal.me/octocat', 'https://ko-fi.com/octocat']\n";
+    let pb =
+        PublishBuilder::new("foo", "1.0.0").add_file("foo-1.0.0/.github/FUNDING.yml", funding_yml);
+    token.publish_crate(pb).await.good();
+
+    let response = anon.get::<()>("/api/v1/crates/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_snapshot!(json["crate"]["funding_links"], @r#"[{"platform":"github","url":"https://github.com/sponsors/octocat"},{"platform":"ko_fi","url":"https://ko-fi.com/octocat"}]"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_funding_links() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+
+    let manifest = r#"
+    [package]
+    name = "foo"
+    version = "1.0.0"
+    description = "foo?!"
+    license = "MIT"
+
+    [package.metadata.funding]
+    custom = "https://example.com/donate"
+    "#;
+
+    let pb = PublishBuilder::new("foo", "1.0.0").custom_manifest(manifest);
+    let response = token.publish_crate(pb).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid `package.metadata.funding`: `https://example.com/donate` is not a link to a supported funding platform"}]}"#);
+    assert_that!(app.stored_files().await, empty());
+}
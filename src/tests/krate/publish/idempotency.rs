@@ -0,0 +1,123 @@
+use crate::schema::{publish_idempotency_keys, versions};
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{MockRequestExt, RequestHelper, Response, TestApp};
+use crate::worker::jobs::DeleteExpiredIdempotencyKeys;
+use bytes::Bytes;
+use chrono::{TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::{Method, StatusCode};
+use insta::assert_snapshot;
+
+async fn publish_with_key(user: &impl RequestHelper, body: Bytes, key: &str) -> Response<()> {
+    let mut request = user
+        .request_builder(Method::PUT, "/api/v1/crates/new")
+        .with_body(body);
+    request.header("idempotency-key", key);
+    user.run(request).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_returns_original_response() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = publish_with_key(&token, body.clone(), "retry-1").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let original = response.json();
+
+    let response = publish_with_key(&token, body, "retry-1").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json(), original);
+
+    let num_versions: i64 = versions::table.count().get_result(&mut conn).await.unwrap();
+    assert_eq!(num_versions, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_without_key_fails() {
+    let (_, _, _, token) = TestApp::full().with_token().await;
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = publish_with_key(&token, body.clone(), "retry-1").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = token.put::<()>("/api/v1/crates/new", body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate version `1.0.0` is already uploaded"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn key_reused_for_different_request() {
+    let (_, _, _, token) = TestApp::full().with_token().await;
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = publish_with_key(&token, body, "reused").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = PublishBuilder::new("foo", "1.1.0").body();
+    let response = publish_with_key(&token, body, "reused").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the `Idempotency-Key` header has already been used for a different publish request"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keys_are_scoped_to_users() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let another_user = app.db_new_user("bar").await;
+    let another_token = another_user.db_new_token("bar").await;
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = publish_with_key(&token, body, "shared").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = PublishBuilder::new("bar", "1.0.0").body();
+    let response = publish_with_key(&another_token, body, "shared").await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_key() {
+    let (_, _, _, token) = TestApp::full().with_token().await;
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = publish_with_key(&token, body, &"a".repeat(256)).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the `Idempotency-Key` header must have between 1 and 255 characters"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn expired_keys() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = publish_with_key(&token, body.clone(), "expiring").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    diesel::update(publish_idempotency_keys::table)
+        .set(publish_idempotency_keys::created_at.eq(Utc::now() - TimeDelta::hours(25)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    // Expired keys are ignored, even before they have been deleted.
+    let response = publish_with_key(&token, body, "expiring").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate version `1.0.0` is already uploaded"}]}"#);
+
+    DeleteExpiredIdempotencyKeys
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+    app.run_pending_background_jobs().await;
+
+    let num_keys: i64 = publish_idempotency_keys::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(num_keys, 0);
+}
@@ -8,7 +8,9 @@ mod dependencies;
 mod edition;
 mod emails;
 mod features;
+mod funding;
 mod git;
+mod idempotency;
 mod inheritance;
 mod keywords;
 mod links;
@@ -16,6 +18,7 @@ mod manifest;
 mod max_size;
 mod rate_limit;
 mod readme;
+mod rollback;
 mod similar_names;
 mod tarball;
 mod timestamps;
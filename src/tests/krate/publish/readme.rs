@@ -18,13 +18,39 @@ async fn new_krate_with_readme() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_readme/foo_readme-1.0.0.crate
     index/fo/o_/foo_readme
-    readmes/foo_readme/foo_readme-1.0.0.html
+    readmes/foo_readme/sha256/7585c6af954d3221d3852207762d35b9d514883034efd48a774b7235c1bd8a70.html
     rss/crates.xml
     rss/crates/foo_readme.xml
     rss/updates.xml
     ");
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn new_versions_with_identical_readme() {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+
+    let crate_to_publish = PublishBuilder::new("foo_readme", "1.0.0").readme("hello world");
+    token.publish_crate(crate_to_publish).await.good();
+
+    let crate_to_publish = PublishBuilder::new("foo_readme", "1.1.0").readme("hello world");
+    token.publish_crate(crate_to_publish).await.good();
+
+    let crate_to_publish = PublishBuilder::new("foo_readme", "2.0.0").readme("hello again");
+    token.publish_crate(crate_to_publish).await.good();
+
+    let readmes = app.stored_files().await;
+    let readmes = readmes.iter().filter(|path| path.starts_with("readmes/"));
+    assert_eq!(readmes.count(), 2);
+
+    anon.get::<()>("/api/v1/crates/foo_readme/1.0.0/readme")
+        .await
+        .assert_redirect_ends_with("/readmes/foo_readme/sha256/7585c6af954d3221d3852207762d35b9d514883034efd48a774b7235c1bd8a70.html");
+
+    anon.get::<()>("/api/v1/crates/foo_readme/1.1.0/readme")
+        .await
+        .assert_redirect_ends_with("/readmes/foo_readme/sha256/7585c6af954d3221d3852207762d35b9d514883034efd48a774b7235c1bd8a70.html");
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn new_krate_with_empty_readme() {
     let (app, _, _, token) = TestApp::full().with_token().await;
@@ -61,7 +87,7 @@ async fn new_krate_with_readme_and_plus_version() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_readme/foo_readme-1.0.0+foo.crate
     index/fo/o_/foo_readme
-    readmes/foo_readme/foo_readme-1.0.0+foo.html
+    readmes/foo_readme/sha256/7585c6af954d3221d3852207762d35b9d514883034efd48a774b7235c1bd8a70.html
     rss/crates.xml
     rss/crates/foo_readme.xml
     rss/updates.xml
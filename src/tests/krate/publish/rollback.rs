@@ -0,0 +1,107 @@
+use crate::schema::{crates, versions};
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::faults::{
+    fail_commits_inserting_into, restore_commits_inserting_into, Fault,
+};
+use crate::tests::util::{RequestHelper, TestApp};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use googletest::prelude::*;
+use http::StatusCode;
+use insta::assert_snapshot;
+
+async fn count_crates_and_versions(conn: &mut AsyncPgConnection) -> (i64, i64) {
+    let crates = crates::table.count().get_result(conn).await.unwrap();
+    let versions = versions::table.count().get_result(conn).await.unwrap();
+    (crates, versions)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_crate_upload_is_rolled_back() {
+    let (app, _, _, token) = TestApp::full().with_fault_injection().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    app.faults().inject(Fault::StorageUpload("staged-crates/"));
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    assert_eq!(count_crates_and_versions(&mut conn).await, (0, 0));
+    assert_that!(app.stored_files().await, empty());
+    assert!(!app.upstream_index().crate_exists("foo").unwrap());
+
+    // Publishing works again once the storage has recovered.
+    app.faults().clear_all();
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(count_crates_and_versions(&mut conn).await, (1, 1));
+    assert!(app.upstream_index().crate_exists("foo").unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_commit_deletes_uploaded_crate_file() {
+    let (app, _, _, token) = TestApp::full().with_fault_injection().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    // The transaction fails after the tarball has already been staged.
+    fail_commits_inserting_into(&mut conn, "versions").await;
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    // Neither the tarball nor the index file may be left behind, and no
+    // index sync jobs may have been enqueued.
+    assert_eq!(count_crates_and_versions(&mut conn).await, (0, 0));
+    assert_that!(app.stored_files().await, empty());
+    assert!(!app.upstream_index().crate_exists("foo").unwrap());
+
+    restore_commits_inserting_into(&mut conn, "versions").await;
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    crates/foo/foo-1.0.0.crate
+    index/3/f/foo
+    rss/crates.xml
+    rss/crates/foo.xml
+    rss/updates.xml
+    ");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_commit_of_new_version_keeps_existing_files() {
+    let (app, _, _, token) = TestApp::full().with_fault_injection().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    fail_commits_inserting_into(&mut conn, "versions").await;
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    // Only the staged tarball of the failed version is deleted, and the
+    // index file still contains only the first version.
+    assert_eq!(count_crates_and_versions(&mut conn).await, (1, 1));
+    let files = app.stored_files().await;
+    assert!(files.contains(&"crates/foo/foo-1.0.0.crate".to_string()));
+    assert!(!files.contains(&"crates/foo/foo-1.1.0.crate".to_string()));
+
+    let versions = app.crates_from_index_head("foo");
+    let versions = versions.iter().map(|v| v.vers.as_str()).collect::<Vec<_>>();
+    assert_eq!(versions, ["1.0.0"]);
+}
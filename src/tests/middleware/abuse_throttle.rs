@@ -0,0 +1,112 @@
+use crate::abuse_throttle::Verdict;
+use crate::config::Server;
+use crate::models::{IpAccessRuleKind, IpBan, NewIpAccessRule};
+use crate::schema::ip_bans;
+use crate::tests::util::{RequestHelper, TestApp};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::{header, StatusCode};
+use std::time::Duration;
+
+fn enable_throttle(config: &mut Server) {
+    let config = &mut config.abuse_throttle;
+    config.enabled = true;
+    config.max_requests = 100;
+    config.max_errors = 2;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ban_after_too_many_errors() {
+    let (app, anon) = TestApp::init().with_config(enable_throttle).empty().await;
+
+    for _ in 0..3 {
+        let response = anon.get::<()>("/api/v1/crates/missing").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_some!(response.headers().get(header::RETRY_AFTER));
+    assert!(response
+        .text()
+        .starts_with(r#"{"errors":[{"detail":"Your network has been temporarily banned"#));
+
+    // Other networks are not affected by the ban
+    let throttle = &app.as_inner().abuse_throttle;
+    let verdict = throttle.verdict("192.0.2.1".parse().unwrap());
+    assert_eq!(verdict, Verdict::Allowed);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn persisted_bans_are_loaded() {
+    let (app, anon) = TestApp::init().with_config(enable_throttle).empty().await;
+    let mut conn = app.db_conn().await;
+
+    let duration = Duration::from_secs(60);
+    IpBan::record_strike(&mut conn, "127.0.0.0/24", "test", duration, duration)
+        .await
+        .unwrap();
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deny_rule() {
+    let (app, anon) = TestApp::init().with_config(enable_throttle).empty().await;
+    let mut conn = app.db_conn().await;
+
+    NewIpAccessRule::builder()
+        .network("127.0.0.1/32")
+        .kind(IpAccessRuleKind::Deny)
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(response
+        .text()
+        .starts_with(r#"{"errors":[{"detail":"Access to the crates.io API has been denied"#));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn allow_rule() {
+    let (app, anon) = TestApp::init().with_config(enable_throttle).empty().await;
+    let mut conn = app.db_conn().await;
+
+    for (network, kind) in [
+        ("127.0.0.0/8", IpAccessRuleKind::Allow),
+        ("127.0.0.1/32", IpAccessRuleKind::Deny),
+    ] {
+        NewIpAccessRule::builder()
+            .network(network)
+            .kind(kind)
+            .build()
+            .insert(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    for _ in 0..5 {
+        let response = anon.get::<()>("/api/v1/crates/missing").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let bans: i64 = ip_bans::table.count().get_result(&mut conn).await.unwrap();
+    assert_eq!(bans, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn throttle_disabled() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    for _ in 0..5 {
+        let response = anon.get::<()>("/api/v1/crates/missing").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
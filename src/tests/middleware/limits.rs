@@ -0,0 +1,66 @@
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use axum::body::Body;
+use http::{header, HeaderValue, Method, StatusCode};
+use insta::assert_snapshot;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn body_limit() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| config.request_body_limit = 16)
+        .empty()
+        .await;
+
+    let body = format!(r#"{{"data": "{}"}}"#, "x".repeat(32));
+    let response = anon.put::<()>("/api/v1/me/email_notifications", body).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"request body is too large, the maximum is 16 bytes"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn body_limit_for_chunked_body() {
+    let (_, _, cookie) = TestApp::init()
+        .with_config(|config| config.request_body_limit = 16)
+        .with_user()
+        .await;
+
+    // A streamed body has no `Content-Length`, so the limit is only hit
+    // while the body is being read.
+    let chunks = [r#"{"data": ""#, "xxxxxxxxxxxxxxxx", r#""}"#];
+    let stream = futures_util::stream::iter(chunks.map(Ok::<_, std::io::Error>));
+
+    let url = "/api/v1/me/email_notifications";
+    let request = cookie.request_builder(Method::PUT, url);
+    let mut request = request.map(|_| Body::from_stream(stream));
+    let content_type = HeaderValue::from_static("application/json");
+    request
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, content_type);
+    let response = cookie.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"request body is too large, the maximum is 16 bytes"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn body_limit_does_not_apply_to_publish() {
+    let (_, _, _, token) = TestApp::full()
+        .with_config(|config| config.request_body_limit = 16)
+        .with_token()
+        .await;
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn request_timeout() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| config.request_timeout = Duration::ZERO)
+        .empty()
+        .await;
+
+    let response = anon.get::<()>("/api/v1/summary").await;
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Request timed out"}]}"#);
+}
@@ -1 +1,5 @@
+mod abuse_throttle;
 mod head;
+mod limits;
+mod rate_limit;
+mod security_headers;
@@ -0,0 +1,112 @@
+use crate::rate_limiter::requests::{EndpointGroup, RateLimitPolicy};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::{header, Request, StatusCode};
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rate_limit_headers() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| {
+            let policy = RateLimitPolicy {
+                requests: 1,
+                window: Duration::from_secs(3600),
+            };
+
+            let config = &mut config.request_rate_limiter;
+            config.enabled = true;
+            config.policies.insert(EndpointGroup::Read, policy);
+        })
+        .empty()
+        .await;
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ratelimit-limit"], "1");
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+    assert_some!(response.headers().get("ratelimit-reset"));
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+    let retry_after = assert_some!(response.headers().get(header::RETRY_AFTER));
+    assert_eq!(retry_after, &response.headers()["ratelimit-reset"]);
+    assert!(response.text().starts_with(
+        r#"{"errors":[{"detail":"You have sent too many requests in a short period of time."#
+    ));
+
+    // Other endpoint groups have their own limits
+    let response = anon.get::<()>("/api/v1/crates").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ratelimit-limit"], "60");
+
+    // Requests outside of the API are not rate limited
+    let response = anon.get::<()>("/").await;
+    assert_none!(response.headers().get("ratelimit-limit"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crawlers_have_a_separate_list_limit() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| config.request_rate_limiter.enabled = true)
+        .empty()
+        .await;
+
+    let response = anon.get::<()>("/api/v1/keywords").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ratelimit-limit"], "120");
+
+    let request = Request::get("/api/v1/keywords?page=2")
+        .header(header::USER_AGENT, "Mozilla/5.0 (compatible; bingbot/2.0)")
+        .body("")
+        .unwrap();
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ratelimit-limit"], "20");
+    assert_eq!(response.headers()["ratelimit-remaining"], "19");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rate_limit_disabled() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_none!(response.headers().get("ratelimit-limit"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn random_tokens_do_not_bypass_the_write_limit() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| {
+            let policy = RateLimitPolicy {
+                requests: 1,
+                window: Duration::from_secs(3600),
+            };
+
+            let config = &mut config.request_rate_limiter;
+            config.enabled = true;
+            config.policies.insert(EndpointGroup::Write, policy);
+        })
+        .empty()
+        .await;
+
+    let request = Request::put("/api/v1/me/tokens")
+        .header(header::USER_AGENT, "crates.io test")
+        .header(header::AUTHORIZATION, "random-token-1")
+        .body("")
+        .unwrap();
+    let response = anon.run::<()>(request).await;
+    assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+
+    // A different token has its own bucket, but is still counted against
+    // the IP address of the client
+    let request = Request::put("/api/v1/me/tokens")
+        .header(header::USER_AGENT, "crates.io test")
+        .header(header::AUTHORIZATION, "random-token-2")
+        .body("")
+        .unwrap();
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+}
@@ -0,0 +1,77 @@
+use crate::tests::util::{RequestHelper, TestApp};
+use http::{header, HeaderValue, StatusCode};
+use insta::assert_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn security_headers() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| {
+            let csp = HeaderValue::from_static("default-src 'self'");
+            config.security_headers.content_security_policy = Some(csp);
+        })
+        .empty()
+        .await;
+
+    for path in ["/api/v1/site_metadata", "/api/v1/crates/unknown"] {
+        let response = anon.get::<()>(path).await;
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000"
+        );
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "SAMEORIGIN");
+        assert_eq!(
+            headers[header::REFERRER_POLICY],
+            "strict-origin-when-cross-origin"
+        );
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            "default-src 'self'"
+        );
+        assert!(!headers.contains_key(header::CONTENT_SECURITY_POLICY_REPORT_ONLY));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn csp_report_only() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| {
+            let csp = HeaderValue::from_static("default-src 'self'");
+            config.security_headers.content_security_policy = Some(csp);
+            config.security_headers.csp_report_only = true;
+            config.security_headers.strict_transport_security = None;
+        })
+        .empty()
+        .await;
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    let headers = response.headers();
+    assert_eq!(
+        headers[header::CONTENT_SECURITY_POLICY_REPORT_ONLY],
+        "default-src 'self'"
+    );
+    assert!(!headers.contains_key(header::CONTENT_SECURITY_POLICY));
+    assert!(!headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn csp_report() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let report = r#"{"csp-report": {
+        "document-uri": "https://crates.io/",
+        "violated-directive": "img-src",
+        "blocked-uri": "https://example.com/image.png"
+    }}"#;
+    let response = anon.post::<()>("/api/csp-report", report).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let reports = r#"[{"type": "csp-violation", "body": {"effectiveDirective": "img-src"}}]"#;
+    let response = anon.post::<()>("/api/csp-report", reports).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = anon.post::<()>("/api/csp-report", "not a report").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid CSP report"}]}"#);
+}
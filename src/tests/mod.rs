@@ -1,4 +1,6 @@
-use crate::models::{Crate, CrateOwner, NewCategory, NewTeam, NewUser, OwnerKind, Team, User};
+use crate::models::{
+    Crate, CrateOwner, NewCategory, NewTeam, NewUser, OwnerKind, OwnerRole, Team, User,
+};
 use crate::schema::crate_owners;
 use crate::tests::util::{RequestHelper, TestApp};
 use crate::views::{
@@ -17,16 +19,20 @@ pub mod builders;
 mod categories;
 mod cors;
 mod dump_db;
+mod email;
 mod github_secret_scanning;
+mod http_server;
 mod issues;
 mod krate;
 mod middleware;
 mod not_found_error;
 mod owners;
 mod pagination;
+mod query_counts;
 mod read_only_mode;
 mod routes;
 mod server;
+mod statement_timeout;
 mod team;
 mod token;
 mod unhealthy_database;
@@ -122,6 +128,7 @@ pub async fn add_team_to_crate(
         created_by: u.id,
         owner_kind: OwnerKind::Team,
         email_notifications: true,
+        role: OwnerRole::Admin,
     };
 
     diesel::insert_into(crate_owners::table)
@@ -0,0 +1,107 @@
+//! Guards against N+1 query regressions in frequently used endpoints.
+//!
+//! Each test records the queries of a request, adds more data, and asserts
+//! that the same request does not need more queries afterwards.
+
+use crate::models::User;
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{MockAnonymousUser, RecordedQueries, RequestHelper, TestApp};
+use diesel_async::AsyncPgConnection;
+use http::StatusCode;
+use std::time::Duration;
+
+/// Generous upper bound for the total query time of a single request, which
+/// only catches pathological queries.
+const MAX_DURATION: Duration = Duration::from_secs(5);
+
+async fn record(app: &TestApp, anon: &MockAnonymousUser, url: &str) -> RecordedQueries {
+    let (response, queries) = app.record_queries(anon.get::<()>(url)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(queries.count, 0);
+    queries.assert_duration_at_most(MAX_DURATION);
+    queries
+}
+
+async fn create_crates(conn: &mut AsyncPgConnection, user: &User, names: &[&str]) {
+    for name in names {
+        CrateBuilder::new(name, user.id)
+            .keyword(name)
+            .version("1.0.0")
+            .version("1.1.0")
+            .expect_build(conn)
+            .await;
+    }
+}
+
+async fn create_versions(conn: &mut AsyncPgConnection, user: &User, name: &str, count: u64) {
+    let mut builder = CrateBuilder::new(name, user.id);
+    for minor in 0..count {
+        builder = builder.version(VersionBuilder::new(&format!("1.{minor}.0")));
+    }
+    builder.expect_build(conn).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_list() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let url = "/api/v1/crates?include=owners,keywords,categories";
+    create_crates(&mut conn, user, &["foo", "bar"]).await;
+    record(&app, &anon, url).await;
+    let queries = record(&app, &anon, url).await;
+
+    create_crates(&mut conn, user, &["baz", "qux", "quux", "corge", "grault"]).await;
+    let more_queries = record(&app, &anon, url).await;
+    more_queries.assert_count_at_most(queries.count);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn summary() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let url = "/api/v1/summary";
+    create_crates(&mut conn, user, &["foo", "bar"]).await;
+    record(&app, &anon, url).await;
+    let queries = record(&app, &anon, url).await;
+
+    create_crates(&mut conn, user, &["baz", "qux", "quux", "corge", "grault"]).await;
+    let more_queries = record(&app, &anon, url).await;
+    more_queries.assert_count_at_most(queries.count);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_versions() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    create_versions(&mut conn, user, "foo", 2).await;
+    create_versions(&mut conn, user, "bar", 10).await;
+
+    for query in ["", "?sort=date", "?per_page=20&include=release_tracks"] {
+        record(&app, &anon, &format!("/api/v1/crates/foo/versions{query}")).await;
+        let queries = record(&app, &anon, &format!("/api/v1/crates/foo/versions{query}")).await;
+        let more_queries =
+            record(&app, &anon, &format!("/api/v1/crates/bar/versions{query}")).await;
+        more_queries.assert_count_at_most(queries.count);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_show() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    create_versions(&mut conn, user, "foo", 2).await;
+    create_versions(&mut conn, user, "bar", 10).await;
+
+    record(&app, &anon, "/api/v1/crates/foo").await;
+    let queries = record(&app, &anon, "/api/v1/crates/foo").await;
+    let more_queries = record(&app, &anon, "/api/v1/crates/bar").await;
+    more_queries.assert_count_at_most(queries.count);
+}
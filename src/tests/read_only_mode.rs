@@ -1,10 +1,12 @@
+use crate::schema::users;
 use crate::tests::builders::CrateBuilder;
 use crate::tests::{RequestHelper, TestApp};
 
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use http::StatusCode;
-use insta::assert_json_snapshot;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn can_hit_read_only_endpoints_in_read_only_mode() {
@@ -74,3 +76,74 @@ async fn can_download_crate_in_read_only_mode() {
         .await;
     assert_ok_eq!(dl_count, None);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn toggle_read_only_mode_via_admin_api() {
+    let (app, anon, user, token) = TestApp::init().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    diesel::update(user.as_model())
+        .set(users::is_admin.eq(true))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    CrateBuilder::new("foo_flag_read_only", user.as_model().id)
+        .version("1.0.0")
+        .version("1.0.1")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.get::<()>("/api/private/admin/read_only_mode").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"read_only_mode":{"enabled":false,"updated_at":null,"updated_by":null}}"#);
+
+    let body = json!({ "enabled": true });
+    let response = user
+        .put::<()>("/api/private/admin/read_only_mode", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["read_only_mode"]["enabled"], true);
+    assert_eq!(response.json()["read_only_mode"]["updated_by"], "foo");
+
+    let response = token
+        .delete::<()>("/api/v1/crates/foo_flag_read_only/1.0.0/yank")
+        .await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crates.io is currently in read-only mode for maintenance. Please try again later."}]}"#);
+
+    let response = anon.get::<()>("/api/v1/crates/foo_flag_read_only").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.json()["read_only"], true);
+
+    let body = json!({ "enabled": false });
+    let response = user
+        .put::<()>("/api/private/admin/read_only_mode", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["read_only_mode"]["enabled"], false);
+
+    let response = token
+        .delete::<()>("/api/v1/crates/foo_flag_read_only/1.0.1/yank")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = anon.get::<()>("/api/v1/site_metadata").await;
+    assert_eq!(response.json()["read_only"], false);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn read_only_mode_admin_api_requires_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>("/api/private/admin/read_only_mode").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "enabled": true });
+    let response = user
+        .put::<()>("/api/private/admin/read_only_mode", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,100 @@
+use super::new_admin;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+const URL: &str = "/api/private/admin/announcements";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manage_announcements() {
+    let (app, anon, _) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+
+    let body = json!({ "announcement": { "message": " " } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the message of the announcement must not be empty"}]}"#);
+
+    let body = json!({ "announcement": { "message": "hello", "docs_url": "http://example.com" } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`http://example.com` is not a valid https URL"}]}"#);
+
+    let body = json!({ "announcement": {
+        "message": "hello",
+        "starts_at": "2099-01-02T00:00:00Z",
+        "ends_at": "2099-01-01T00:00:00Z",
+    } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the announcement must end after it starts"}]}"#);
+
+    let body = json!({ "announcement": { "message": "crates.io will be down for maintenance" } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["announcement"]["severity"], "info");
+    assert_eq!(json["announcement"]["audience"], "all");
+    assert_eq!(json["announcement"]["created_by"], "admin");
+    let id = json["announcement"]["id"].as_i64().unwrap();
+
+    let response = anon.get::<()>("/api/v1/announcements").await;
+    assert_eq!(response.json()["announcements"][0]["id"], id);
+
+    let url = format!("{URL}/{id}");
+    let body = json!({ "announcement": {
+        "message": "crates.io will be down for maintenance on 2099-01-01",
+        "docs_url": "https://status.crates.io/",
+        "severity": "critical",
+        "audience": "crate_owners",
+    } });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["announcement"]["severity"], "critical");
+    assert_eq!(json["announcement"]["audience"], "crate_owners");
+    assert_eq!(
+        json["announcement"]["docs_url"],
+        "https://status.crates.io/"
+    );
+
+    let response = admin.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json()["announcements"].as_array().unwrap().len(),
+        1
+    );
+
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = admin.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"announcements":[]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn announcements_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "announcement": { "message": "hello" } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user
+        .patch::<()>(&format!("{URL}/1"), body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.delete::<()>(&format!("{URL}/1")).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,118 @@
+use super::new_admin;
+use crate::schema::broadcast_warnings;
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+const URL: &str = "/api/private/admin/broadcast_warnings";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn broadcast_warnings_are_returned_on_publish() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let admin = new_admin(&app).await;
+
+    let body = json!({ "warning": {
+        "message": "crates.io will be read-only on 2099-01-01 for maintenance",
+        "docs_url": "https://status.crates.io/",
+    } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["warning"]["created_by"], "admin");
+
+    let body = json!({ "warning": {
+        "message": "this warning has expired",
+        "expires_at": "2099-01-01T00:00:00Z",
+    } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let expiring_id = response.json()["warning"]["id"].as_i64().unwrap();
+
+    let mut conn = app.db_conn().await;
+    diesel::update(broadcast_warnings::table.find(expiring_id as i32))
+        .set(broadcast_warnings::expires_at.eq(Utc::now() - TimeDelta::hours(1)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let crate_to_publish =
+        PublishBuilder::new("foo", "1.0.0").repository("https://github.com/rust-lang/crates.io");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["warnings"], @r#"
+    {
+      "invalid_badges": [],
+      "invalid_categories": [],
+      "other": [
+        "crates.io will be read-only on 2099-01-01 for maintenance (see https://status.crates.io/)"
+      ],
+      "structured": [
+        {
+          "category": "announcement",
+          "code": "announcement",
+          "docs_url": "https://status.crates.io/",
+          "message": "crates.io will be read-only on 2099-01-01 for maintenance"
+        }
+      ]
+    }
+    "#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manage_broadcast_warnings() {
+    let (app, _, _) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+
+    let body = json!({ "warning": { "message": " " } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the message of the warning must not be empty"}]}"#);
+
+    let body = json!({ "warning": { "message": "hello", "docs_url": "http://example.com" } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`http://example.com` is not a valid https URL"}]}"#);
+
+    let body = json!({ "warning": { "message": "hello", "expires_at": "2000-01-01T00:00:00Z" } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the expiry date of the warning must be in the future"}]}"#);
+
+    let body = json!({ "warning": { "message": "hello" } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let id = response.json()["warning"]["id"].as_i64().unwrap();
+
+    let response = admin.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["warnings"].as_array().unwrap().len(), 1);
+
+    let url = format!("{URL}/{id}");
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = admin.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"warnings":[]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn broadcast_warnings_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "warning": { "message": "hello" } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.delete::<()>(&format!("{URL}/1")).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,413 @@
+use super::new_admin;
+use crate::tests::builders::{CrateBuilder, PublishBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::tests::OkBool;
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hide_and_unhide_crate() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    CrateBuilder::new("foo_hidden", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "hidden": true, "reason": "malware", "notify_owners": true });
+    let response = admin
+        .patch::<()>("/api/private/admin/crates/foo_hidden", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.json()["crate"]["hidden_at"].is_string());
+    assert_eq!(response.json()["crate"]["frozen_at"], json!(null));
+
+    let json = anon.search("q=foo_hidden").await;
+    assert_eq!(json.meta.total, 0);
+
+    let json = anon.search("").await;
+    assert_eq!(json.meta.total, 0);
+
+    assert_snapshot!(app.emails_snapshot().await);
+
+    let body = json!({ "hidden": false, "reason": "false positive" });
+    let response = admin
+        .patch::<()>("/api/private/admin/crates/foo_hidden", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["crate"]["hidden_at"], json!(null));
+
+    let json = anon.search("q=foo_hidden").await;
+    assert_eq!(json.meta.total, 1);
+
+    let response = user.get::<()>("/api/v1/crates/foo_hidden/audit").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let entries = response.json()["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            let action = entry["action"].as_str().unwrap().to_string();
+            let reason = entry["details"]["reason"].as_str().unwrap().to_string();
+            (action, reason)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            ("crate_unhide".to_string(), "false positive".to_string()),
+            ("crate_hide".to_string(), "malware".to_string()),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn freeze_crate() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let admin = new_admin(&app).await;
+
+    let crate_to_publish = PublishBuilder::new("foo_frozen", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+
+    let body = json!({ "frozen": true, "reason": "DMCA takedown" });
+    let response = admin
+        .patch::<()>("/api/private/admin/crates/foo_frozen", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.json()["crate"]["frozen_at"].is_string());
+
+    let crate_to_publish = PublishBuilder::new("foo_frozen", "1.1.0");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo_frozen` has been frozen by the crates.io team and does not accept new versions"}]}"#);
+
+    let body = json!({ "frozen": false, "reason": "resolved" });
+    let response = admin
+        .patch::<()>("/api/private/admin/crates/foo_frozen", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let crate_to_publish = PublishBuilder::new("foo_frozen", "1.1.0");
+    token.publish_crate(crate_to_publish).await.good();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn quarantine_and_approve_crate() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    CrateBuilder::new("foo_quarantined", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "quarantined": true, "reason": "suspicious build script" });
+    let response = admin
+        .patch::<()>(
+            "/api/private/admin/crates/foo_quarantined",
+            body.to_string(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.json()["crate"]["quarantined_at"].is_string());
+
+    let json = anon.search("q=foo_quarantined").await;
+    assert_eq!(json.meta.total, 0);
+
+    let url = "/api/v1/crates/foo_quarantined/1.0.0/download";
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = json!({ "quarantined": false, "reason": "reviewed, false positive", "notify_owners": true });
+    let response = admin
+        .patch::<()>(
+            "/api/private/admin/crates/foo_quarantined",
+            body.to_string(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["crate"]["quarantined_at"], json!(null));
+
+    let json = anon.search("q=foo_quarantined").await;
+    assert_eq!(json.meta.total, 1);
+
+    anon.get::<()>(url)
+        .await
+        .assert_redirect_ends_with("/crates/foo_quarantined/foo_quarantined-1.0.0.crate");
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn quarantined_crate_files_are_not_public() {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let admin = new_admin(&app).await;
+
+    let crate_to_publish = PublishBuilder::new("foo_quarantined", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    let body = json!({ "quarantined": true, "reason": "suspicious build script" });
+    let url = "/api/private/admin/crates/foo_quarantined";
+    let response = admin.patch::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    app.run_pending_background_jobs().await;
+
+    // The file is moved out of the public bucket that is served by the CDN.
+    let public_path = "crates/foo_quarantined/foo_quarantined-1.0.0.crate".to_string();
+    let quarantined_path = format!("quarantined-crates/{public_path}");
+    assert!(!app.stored_files().await.contains(&public_path));
+    assert!(app.stored_private_files().await.contains(&quarantined_path));
+
+    // New versions are quarantined too.
+    let crate_to_publish = PublishBuilder::new("foo_quarantined", "1.1.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    let files = app.stored_files().await;
+    assert!(!files.contains(&"crates/foo_quarantined/foo_quarantined-1.1.0.crate".into()));
+
+    // Owners can still download the file through the API.
+    let download_url = "/api/v1/crates/foo_quarantined/1.0.0/download";
+    let response = anon.get::<()>(download_url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = user.get::<()>(download_url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/gzip");
+
+    let body = json!({ "quarantined": false, "reason": "reviewed, false positive" });
+    let response = admin.patch::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    app.run_pending_background_jobs().await;
+
+    let files = app.stored_files().await;
+    assert!(files.contains(&public_path));
+    assert!(files.contains(&"crates/foo_quarantined/foo_quarantined-1.1.0.crate".into()));
+    assert_eq!(app.stored_private_files().await, Vec::<String>::new());
+
+    anon.get::<()>(download_url)
+        .await
+        .assert_redirect_ends_with("/crates/foo_quarantined/foo_quarantined-1.0.0.crate");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn force_yank_version() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    CrateBuilder::new("foo_yank", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "reason": "contains malware", "notify_owners": true });
+    let response = admin
+        .put::<()>(
+            "/api/private/admin/crates/foo_yank/1.0.0/yank",
+            body.to_string(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = anon.show_version("foo_yank", "1.0.0").await;
+    assert!(json.version.yanked);
+    assert_eq!(
+        json.version.yank_message.as_deref(),
+        Some("contains malware")
+    );
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delete_and_restore_version() {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let admin = new_admin(&app).await;
+
+    let crate_to_publish = PublishBuilder::new("foo_deleted", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    let crate_to_publish = PublishBuilder::new("foo_deleted", "1.1.0");
+    token.publish_crate(crate_to_publish).await.good();
+
+    let url = "/api/private/admin/crates/foo_deleted/1.1.0/delete";
+    let body = json!({ "reason": "contains malware", "notify_owners": true });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["crate"]["deleted_versions"][0]["num"], "1.1.0");
+    assert!(json["crate"]["deleted_versions"][0]["deleted_at"].is_string());
+
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo_deleted/1.1.0").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = anon.get::<()>("/api/v1/crates/foo_deleted/versions").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(version_nums(&response.json()), ["1.0.0"]);
+
+    let index = app.crates_from_index_head("foo_deleted");
+    assert_eq!(index.len(), 1);
+    assert_eq!(index[0].vers, "1.0.0");
+
+    // The version is also hidden from the other endpoints that list versions.
+    let url = "/api/v1/crates/foo_deleted/follow";
+    user.put::<OkBool>(url, b"" as &[u8]).await.good();
+    let response = user.get::<()>("/api/v1/me/updates").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(version_nums(&response.json()), ["1.0.0"]);
+
+    let response = anon.get::<()>("/api/v1/users/foo/versions").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(version_nums(&response.json()), ["1.0.0"]);
+
+    let url = "/api/v1/crates/foo_deleted/semver_check?from=1.0.0&to=1.1.0";
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = anon.get::<()>("/crates/foo_deleted").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = response.text();
+    assert!(html.contains(">1.0.0</a>"));
+    assert!(!html.contains(">1.1.0</a>"));
+
+    // The file is moved out of the public bucket that is served by the CDN,
+    // so the public path returns a 404 once the CDN caches are invalidated.
+    let quarantined_path = "quarantined-crates/foo_deleted/foo_deleted-1.1.0.crate".to_string();
+    let files = app.stored_files().await;
+    assert!(!files.contains(&"crates/foo_deleted/foo_deleted-1.1.0.crate".into()));
+    assert!(!files
+        .iter()
+        .any(|file| file.starts_with("quarantined-crates/")));
+    assert!(app.stored_private_files().await.contains(&quarantined_path));
+
+    let storage = &app.as_inner().storage;
+    let result = storage.download_crate_file("foo_deleted", "1.1.0").await;
+    assert!(matches!(result, Err(object_store::Error::NotFound { .. })));
+
+    let emails = app.emails().await;
+    assert!(emails
+        .iter()
+        .any(|email| email.contains("Moderation of the \"foo_deleted\" crate")));
+
+    // The last remaining version can't be deleted.
+    let url = "/api/private/admin/crates/foo_deleted/1.0.0/delete";
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the last version of a crate can not be deleted"}]}"#);
+
+    let url = "/api/private/admin/crates/foo_deleted/1.1.0/restore";
+    let body = json!({ "reason": "false positive" });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["crate"]["deleted_versions"], json!([]));
+
+    app.run_pending_background_jobs().await;
+
+    let json = anon.show_version("foo_deleted", "1.1.0").await;
+    assert_eq!(json.version.num, "1.1.0");
+
+    let index = app.crates_from_index_head("foo_deleted");
+    assert_eq!(index.len(), 2);
+
+    let files = app.stored_files().await;
+    assert!(files.contains(&"crates/foo_deleted/foo_deleted-1.1.0.crate".into()));
+    assert!(!app.stored_private_files().await.contains(&quarantined_path));
+
+    // Owners can't delete their own versions.
+    let url = "/api/private/admin/crates/foo_deleted/1.1.0/delete";
+    let response = user.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+fn version_nums(json: &serde_json::Value) -> Vec<&str> {
+    json["versions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|version| version["num"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deleted_versions_are_not_resolved() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let bar = CrateBuilder::new("bar", user.id)
+        .version("1.0.0")
+        .version("1.1.0")
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("foo", user.id)
+        .version(VersionBuilder::new("1.0.0").dependency(&bar, None))
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo/1.0.0/dependency_graph";
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["nodes"][1]["version"], "1.1.0");
+
+    let body = json!({ "reason": "contains malware", "notify_owners": false });
+    let response = admin
+        .put::<()>(
+            "/api/private/admin/crates/bar/1.1.0/delete",
+            body.to_string(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The cached graph is invalidated by the deletion.
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["nodes"][1]["version"], "1.0.0");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn moderation_validation() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "hidden": true, "reason": "" });
+    let response = admin
+        .patch::<()>("/api/private/admin/crates/foo", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a reason is required for moderation actions"}]}"#);
+
+    let body = json!({ "reason": "spam" });
+    let response = admin
+        .patch::<()>("/api/private/admin/crates/foo", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"one of `hidden`, `frozen` or `quarantined` must be set"}]}"#);
+
+    let body = json!({ "hidden": true, "reason": "spam" });
+    let response = user
+        .patch::<()>("/api/private/admin/crates/foo", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires admin privileges"}]}"#);
+
+    let response = admin
+        .patch::<()>("/api/private/admin/crates/unknown", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `unknown` does not exist"}]}"#);
+}
@@ -0,0 +1,50 @@
+use super::new_admin;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn resize_database_pool() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+    let url = "/api/private/admin/database_pools/primary";
+    let body = json!({ "max_size": 8 }).to_string();
+
+    let response = anon.put::<()>(url, body.clone()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.put::<()>(url, body.clone()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = admin.get::<()>("/api/private/admin/database_pools").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let pools = response.json();
+    let pools = pools["database_pools"].as_array().unwrap();
+    assert_eq!(pools.len(), 1);
+    assert_eq!(pools[0]["name"], "primary");
+    assert_eq!(pools[0]["max_size"], 5);
+    assert_eq!(pools[0]["max_allowed_size"], 10);
+
+    let response = admin.put::<()>(url, body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["database_pool"]["max_size"], 8);
+    assert_eq!(response.json()["database_pool"]["configured_size"], 5);
+    assert_eq!(app.as_inner().primary_database.status().max_size, 8);
+
+    let body = json!({ "max_size": 11 }).to_string();
+    let response = admin.put::<()>(url, body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the size of the primary pool must be between 1 and 10"}]}"#);
+
+    let body = json!({ "max_size": 0 }).to_string();
+    let response = admin.put::<()>(url, body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = json!({ "max_size": 2 }).to_string();
+    let response = admin
+        .put::<()>("/api/private/admin/database_pools/replica", body)
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(app.as_inner().primary_database.status().max_size, 8);
+}
@@ -0,0 +1,86 @@
+use super::new_admin;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+const URL: &str = "/api/private/admin/featured_crates";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manage_featured_crates() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+    let user_id = user.as_model().id;
+
+    CrateBuilder::new("foo", user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "crate": "missing" });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `missing` does not exist"}]}"#);
+
+    let now = Utc::now();
+    let body = json!({ "crate": "foo", "starts_at": now, "ends_at": now });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the end date must be after the start date"}]}"#);
+
+    let body = json!({ "crate": "foo", "note": "Great crate" });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["featured_crate"]["crate_name"], "foo");
+    let id = response.json()["featured_crate"]["id"].as_i64().unwrap();
+
+    // Scheduled entries are not listed in the summary yet
+    let body = json!({ "crate": "bar", "starts_at": now + Duration::days(1) });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".featured_crates[].id" => "[id]",
+        ".featured_crates[].starts_at" => "[datetime]",
+        ".featured_crates[].created_at" => "[datetime]",
+    });
+
+    let response = anon.get::<()>("/api/v1/summary").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let featured = response.json()["featured_crates"].clone();
+    assert_eq!(featured.as_array().unwrap().len(), 1);
+    assert_eq!(featured[0]["name"], "foo");
+
+    let url = format!("{URL}/{id}");
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = anon.get::<()>("/api/v1/summary").await;
+    assert_eq!(response.json()["featured_crates"], json!([]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn featured_crates_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "crate": "foo" });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.delete::<()>(&format!("{URL}/1")).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
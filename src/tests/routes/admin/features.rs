@@ -0,0 +1,110 @@
+use super::new_admin;
+use crate::models::FeatureFlag;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manage_features() {
+    let (app, _) = TestApp::init().empty().await;
+    let admin = new_admin(&app).await;
+    let user = app.db_new_user("foo").await;
+    let user_id = user.as_model().id;
+    let feature_flags = &app.as_inner().feature_flags;
+
+    let body = json!({ "enabled": false, "rollout_percentage": 25 });
+    let response = admin
+        .put::<()>("/api/private/admin/features/New-Search", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid feature name, only lowercase letters, digits and underscores are allowed, and the name must not be longer than 64 characters"}]}"#);
+
+    let body = json!({ "enabled": false, "rollout_percentage": 101 });
+    let response = admin
+        .put::<()>("/api/private/admin/features/new_search", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the rollout percentage must be between 0 and 100"}]}"#);
+
+    let url = "/api/private/admin/features/new_search/overrides/foo";
+    let body = json!({ "enabled": true });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = json!({ "enabled": false, "description": "New search backend" });
+    let response = admin
+        .put::<()>("/api/private/admin/features/new_search", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["feature"]["updated_by"], "admin");
+    assert!(
+        !feature_flags
+            .is_enabled_for(app.as_inner(), "new_search", Some(user_id))
+            .await
+    );
+
+    let body = json!({ "enabled": true });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["override"]["user"], "foo");
+    assert!(
+        feature_flags
+            .is_enabled_for(app.as_inner(), "new_search", Some(user_id))
+            .await
+    );
+    assert!(!feature_flags.is_enabled(app.as_inner(), "new_search").await);
+
+    let response = admin.get::<()>("/api/private/admin/features").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".features[].updated_at" => "[datetime]",
+        ".features[].overrides[].created_at" => "[datetime]",
+    });
+
+    let response = admin.delete::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(
+        !feature_flags
+            .is_enabled_for(app.as_inner(), "new_search", Some(user_id))
+            .await
+    );
+
+    let response = admin
+        .delete::<()>("/api/private/admin/features/new_search")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut conn = app.db_conn().await;
+    let feature = FeatureFlag::find(&mut conn, "new_search").await.unwrap();
+    assert!(feature.is_none());
+
+    let response = admin.get::<()>("/api/private/admin/features").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"features":[]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn features_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>("/api/private/admin/features").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "enabled": true });
+    let response = user
+        .put::<()>("/api/private/admin/features/new_search", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user
+        .put::<()>(
+            "/api/private/admin/features/new_search/overrides/foo",
+            body.to_string(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,107 @@
+use super::new_admin;
+use crate::models::IpBan;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_and_lift_ip_bans() {
+    let (app, _) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    let duration = Duration::from_secs(60);
+    let ban = IpBan::record_strike(
+        &mut conn,
+        "192.0.2.0/24",
+        "too many requests",
+        duration,
+        duration,
+    )
+    .await
+    .unwrap();
+
+    let response = admin.get::<()>("/api/private/admin/ip_bans").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".ip_bans[].id" => "[id]",
+        ".ip_bans[].banned_until" => "[datetime]",
+        ".ip_bans[].created_at" => "[datetime]",
+        ".ip_bans[].updated_at" => "[datetime]",
+    });
+
+    let url = format!("/api/private/admin/ip_bans/{}", ban.id);
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = admin.get::<()>("/api/private/admin/ip_bans").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ip_bans":[]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manage_ip_rules() {
+    let (app, _) = TestApp::init().empty().await;
+    let admin = new_admin(&app).await;
+
+    let body = json!({ "network": "foo", "kind": "deny" });
+    let response = admin
+        .post::<()>("/api/private/admin/ip_rules", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid IP network"}]}"#);
+
+    let body = json!({ "network": "192.0.2.42/24", "kind": "deny", "reason": "Scraping" });
+    let response = admin
+        .post::<()>("/api/private/admin/ip_rules", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["ip_rule"]["network"], "192.0.2.0/24");
+    let id = response.json()["ip_rule"]["id"].as_i64().unwrap();
+
+    let response = admin
+        .post::<()>("/api/private/admin/ip_rules", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a rule for `192.0.2.0/24` already exists"}]}"#);
+
+    let response = admin.get::<()>("/api/private/admin/ip_rules").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".ip_rules[].id" => "[id]",
+        ".ip_rules[].created_at" => "[datetime]",
+    });
+
+    let url = format!("/api/private/admin/ip_rules/{id}");
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = admin.get::<()>("/api/private/admin/ip_rules").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ip_rules":[]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ip_access_requires_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>("/api/private/admin/ip_bans").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.get::<()>("/api/private/admin/ip_rules").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "network": "192.0.2.0/24", "kind": "allow" });
+    let response = user
+        .post::<()>("/api/private/admin/ip_rules", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
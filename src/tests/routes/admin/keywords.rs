@@ -0,0 +1,119 @@
+use super::new_admin;
+use crate::models::Keyword;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::{header, StatusCode};
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn merge_keywords() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+    let user_id = user.as_model().id;
+
+    CrateBuilder::new("foo", user_id)
+        .keyword("async")
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", user_id)
+        .keyword("async")
+        .keyword("asynchronous")
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("baz", user_id)
+        .keyword("asynchronous")
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/private/admin/keywords/asynchronous/merge";
+    let body = json!({ "into": "asynchronous" });
+    let response = admin.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a keyword cannot be merged into itself"}]}"#);
+
+    let body = json!({ "into": "async" });
+    let response = admin.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["keyword"]["keyword"], "async");
+    assert_eq!(response.json()["keyword"]["crates_cnt"], 3);
+
+    let response = admin.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"keyword `asynchronous` does not exist"}]}"#);
+
+    // The old keyword URLs redirect to the merged keyword
+    let response = anon.get::<()>("/api/v1/keywords/asynchronous").await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "/api/v1/keywords/async"
+    );
+
+    let json = anon.search("keyword=asynchronous").await;
+    assert_eq!(json.meta.total, 3);
+
+    // Publishing with the old keyword uses the merged keyword
+    let keywords = Keyword::find_or_create_all(&mut conn, &["asynchronous"])
+        .await
+        .unwrap();
+    assert_eq!(keywords.len(), 1);
+    assert_eq!(keywords[0].keyword, "async");
+
+    let response = admin.get::<()>("/api/private/admin/keyword_aliases").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".keyword_aliases[].created_at" => "[datetime]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manage_keyword_aliases() {
+    let (app, anon) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    Keyword::find_or_create_all(&mut conn, &["cli", "terminal"])
+        .await
+        .unwrap();
+
+    let url = "/api/private/admin/keyword_aliases/terminal";
+    let body = json!({ "keyword": "cli" });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`terminal` is an existing keyword, merge it into the other keyword instead"}]}"#);
+
+    let url = "/api/private/admin/keyword_aliases/Command-Line";
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["keyword_alias"]["alias"], "command-line");
+
+    let response = anon.get::<()>("/api/v1/keywords/command-line").await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+
+    let url = "/api/private/admin/keyword_aliases/command-line";
+    let response = admin.delete::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = anon.get::<()>("/api/v1/keywords/command-line").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keyword_moderation_requires_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let body = json!({ "into": "async" });
+    let url = "/api/private/admin/keywords/asynchronous/merge";
+    let response = user.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.get::<()>("/api/private/admin/keyword_aliases").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,33 @@
+use crate::tests::util::{MockCookieUser, TestApp};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+mod announcements;
+mod broadcast_warnings;
+mod crates;
+mod database_pools;
+mod featured_crates;
+mod features;
+mod ip_access;
+mod keywords;
+mod publish_policy;
+mod reclaim_requests;
+mod redirect_requests;
+mod reports;
+mod reserved_names;
+mod reserved_prefixes;
+mod storage_discrepancies;
+mod users;
+
+async fn new_admin(app: &TestApp) -> MockCookieUser {
+    let mut conn = app.db_conn().await;
+
+    let admin = app.db_new_user("admin").await;
+    diesel::update(admin.as_model())
+        .set(crate::schema::users::is_admin.eq(true))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    admin
+}
@@ -0,0 +1,166 @@
+use super::new_admin;
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{MockCookieUser, RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+const URL: &str = "/api/private/admin/publish_policy_rules";
+
+async fn create_rule(admin: &MockCookieUser, kind: &str, value: &str) -> i64 {
+    let body = json!({ "rule": { "kind": kind, "value": value } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json()["rule"]["id"].as_i64().unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_policy_violations() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let admin = new_admin(&app).await;
+
+    create_rule(&admin, "max_features", "1").await;
+    create_rule(&admin, "banned_license", "GPL-3.0-only").await;
+
+    let body = json!({ "rule": {
+        "kind": "required_field",
+        "value": "documentation",
+        "message": "Please link to the documentation of the crate.",
+    } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["rule"]["kind"], "required_field");
+    assert_eq!(json["rule"]["created_by"], "admin");
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .license("MIT AND GPL-3.0-only")
+        .feature("a", &[])
+        .feature("b", &[]);
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let json = response.json();
+    let errors = json["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0]["kind"], "max_features");
+    assert_eq!(
+        errors[0]["detail"],
+        "the crate declares 2 features, but the publish policy of crates.io only allows 1"
+    );
+    assert_eq!(errors[1]["kind"], "banned_license");
+    assert_eq!(errors[2]["kind"], "required_field");
+    assert_eq!(
+        errors[2]["detail"],
+        "the publish policy of crates.io requires the `documentation` field in the `[package]` section of `Cargo.toml`\n\nPlease link to the documentation of the crate."
+    );
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .license("MIT OR GPL-3.0-only")
+        .documentation("https://docs.rs/foo")
+        .feature("a", &[]);
+    token.publish_crate(crate_to_publish).await.good();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn upcoming_publish_policy_rules() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let admin = new_admin(&app).await;
+
+    let body = json!({ "rule": {
+        "kind": "max_features",
+        "value": "1",
+        "enforced_at": "2099-01-01T00:00:00Z",
+    } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json()["rule"]["enforced_at"],
+        "2099-01-01T00:00:00Z"
+    );
+
+    // Violations of rules that are not enforced yet are only warnings.
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .repository("https://github.com/rust-lang/crates.io")
+        .feature("a", &[])
+        .feature("b", &[]);
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["warnings"], @r#"
+    {
+      "invalid_badges": [],
+      "invalid_categories": [],
+      "other": [
+        "starting 2099-01-01, versions like this one will be rejected: the crate declares 2 features, but the publish policy of crates.io only allows 1"
+      ],
+      "structured": [
+        {
+          "category": "policy",
+          "code": "upcoming-policy-rule",
+          "docs_url": null,
+          "message": "starting 2099-01-01, versions like this one will be rejected: the crate declares 2 features, but the publish policy of crates.io only allows 1"
+        }
+      ]
+    }
+    "#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manage_publish_policy_rules() {
+    let (app, _, _) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+
+    let id = create_rule(&admin, "max_dependencies", "100").await;
+
+    let body = json!({ "rule": { "kind": "max_dependencies", "value": "100" } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the publish policy already has this rule"}]}"#);
+
+    let body = json!({ "rule": { "kind": "banned_license", "value": "not-a-license" } });
+    let response = admin.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`not-a-license` is not an SPDX license identifier"}]}"#);
+
+    let url = format!("{URL}/{id}");
+    let body = json!({ "rule": { "value": "many" } });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`many` is not a valid non-negative number"}]}"#);
+
+    let body = json!({ "rule": { "value": "50", "message": "Please split up the crate." } });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["rule"]["value"], "50");
+    assert_eq!(
+        response.json()["rule"]["message"],
+        "Please split up the crate."
+    );
+
+    let response = admin.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["rules"].as_array().unwrap().len(), 1);
+
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = admin.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"rules":[]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_policy_rules_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "rule": { "kind": "max_features", "value": "1" } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.delete::<()>(&format!("{URL}/1")).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
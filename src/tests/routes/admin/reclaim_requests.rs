@@ -0,0 +1,124 @@
+use super::new_admin;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{MockCookieUser, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use crates_io_database::schema::crate_reclaim_requests;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+async fn request_reclaim(user: &MockCookieUser, krate: &str) -> i64 {
+    let url = format!("/api/v1/crates/{krate}/reclaim_requests");
+    let body = json!({ "reclaim_request": { "reason": "The crate is empty." } });
+    let response = user.post::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json()["reclaim_request"]["id"].as_i64().unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn approve_reclaim_request() {
+    let (app, _, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    let requester = app.db_new_user("requester").await;
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let id = request_reclaim(&requester, "foo").await;
+
+    let response = admin.get::<()>("/api/private/admin/reclaim_requests").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total"], 1);
+    assert_eq!(response.json()["reclaim_requests"][0]["id"], id);
+
+    let url = format!("/api/private/admin/reclaim_requests/{id}");
+    let body = json!({ "status": "approved", "decision": "The crate is unused." });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("can not be approved before"));
+
+    diesel::update(crate_reclaim_requests::table)
+        .set(crate_reclaim_requests::created_at.eq(Utc::now() - TimeDelta::days(30)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["reclaim_request"]["status"], "approved");
+    assert!(response.json()["reclaim_request"]["decided_at"].is_string());
+
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the reclaim request has already been decided"}]}"#);
+
+    let owners = requester.get::<()>("/api/v1/crates/foo/owner_user").await;
+    assert_eq!(owners.status(), StatusCode::OK);
+    let owners = owners.json();
+    let owners = owners["users"].as_array().unwrap();
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners[0]["login"], "requester");
+
+    let response = admin
+        .get::<()>("/api/private/admin/reclaim_requests?status=approved")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total"], 1);
+
+    let emails = app.emails().await;
+    assert_eq!(emails.len(), 3);
+    assert!(emails[1].contains("Your request to reclaim the \"foo\" crate"));
+    assert!(emails[2].contains("The \"foo\" crate has been transferred"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reject_reclaim_request() {
+    let (app, _, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    let requester = app.db_new_user("requester").await;
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let id = request_reclaim(&requester, "foo").await;
+
+    let url = format!("/api/private/admin/reclaim_requests/{id}");
+    let body = json!({ "status": "rejected" });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a decision is required"}]}"#);
+
+    let body = json!({ "status": "rejected", "decision": "The crate is still in use." });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["reclaim_request"]["status"], "rejected");
+
+    let owners = owner.get::<()>("/api/v1/crates/foo/owner_user").await;
+    assert_eq!(
+        owners.json()["users"][0]["login"],
+        owner.as_model().gh_login
+    );
+
+    // A rejected request doesn't prevent the user from asking again.
+    request_reclaim(&requester, "foo").await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reclaim_requests_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>("/api/private/admin/reclaim_requests").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "status": "rejected", "decision": "No." });
+    let response = user
+        .patch::<()>("/api/private/admin/reclaim_requests/1", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
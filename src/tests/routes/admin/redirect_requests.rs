@@ -0,0 +1,110 @@
+use super::new_admin;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{MockCookieUser, RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+async fn request_redirect(user: &MockCookieUser, krate: &str, target: &str) -> i64 {
+    let url = format!("/api/v1/crates/{krate}/redirect_requests");
+    let body = json!({ "redirect_request": { "target": target, "reason": "The crate has been renamed." } });
+    let response = user.post::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json()["redirect_request"]["id"].as_i64().unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn approve_redirect_request() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let id = request_redirect(&user, "foo", "bar").await;
+
+    let response = admin
+        .get::<()>("/api/private/admin/redirect_requests")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total"], 1);
+    assert_eq!(response.json()["redirect_requests"][0]["id"], id);
+    assert_eq!(response.json()["redirect_requests"][0]["target"], "bar");
+
+    let url = format!("/api/private/admin/redirect_requests/{id}");
+    let body = json!({ "status": "approved", "decision": "The crate has been renamed." });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["redirect_request"]["status"], "approved");
+    assert!(response.json()["redirect_request"]["decided_at"].is_string());
+
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the redirect request has already been decided"}]}"#);
+
+    let response = anon.get::<()>("/api/v1/crates/foo").await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    response.assert_redirect_ends_with("/api/v1/crates/bar");
+
+    let response = admin
+        .get::<()>("/api/private/admin/redirect_requests?status=approved")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total"], 1);
+
+    let emails = app.emails().await;
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].contains("Your request to redirect the \"foo\" crate"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reject_redirect_request() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let id = request_redirect(&user, "foo", "bar").await;
+
+    let url = format!("/api/private/admin/redirect_requests/{id}");
+    let body = json!({ "status": "rejected" });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a decision is required"}]}"#);
+
+    let body = json!({ "status": "rejected", "decision": "The crate is still maintained." });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["redirect_request"]["status"], "rejected");
+
+    let response = anon.get::<()>("/api/v1/crates/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // A rejected request doesn't prevent the owners from asking again.
+    request_redirect(&user, "foo", "bar").await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn redirect_requests_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>("/api/private/admin/redirect_requests").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "status": "rejected", "decision": "No." });
+    let response = user
+        .patch::<()>("/api/private/admin/redirect_requests/1", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,85 @@
+use super::new_admin;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{MockCookieUser, RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+async fn report(user: &MockCookieUser, krate: &str, category: &str) -> i64 {
+    let url = format!("/api/v1/crates/{krate}/report");
+    let body = json!({ "report": { "category": category, "description": "Please take a look." } });
+    let response = user.post::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json()["report"]["id"].as_i64().unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn triage_reports() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    let reporter = app.db_new_user("reporter").await;
+    CrateBuilder::new("foo_report", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let first = report(&reporter, "foo_report", "malware").await;
+    let second = report(&reporter, "foo_report", "typosquat").await;
+
+    let response = admin.get::<()>("/api/private/admin/reports").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".reports[].id" => "[id]",
+        ".reports[].created_at" => "[datetime]",
+    });
+
+    let url = format!("/api/private/admin/reports/{first}");
+    let body = json!({ "status": "triaged" });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["report"]["status"], "triaged");
+
+    let body = json!({ "status": "resolved", "resolution": "The crate has been removed." });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["report"]["status"], "resolved");
+    assert!(response.json()["report"]["resolved_at"].is_string());
+
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the report has already been closed"}]}"#);
+
+    let url = format!("/api/private/admin/reports/{second}");
+    let body = json!({ "status": "dismissed" });
+    let response = admin.patch::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a resolution is required to resolve or dismiss a report"}]}"#);
+
+    let response = admin.get::<()>("/api/private/admin/reports").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total"], 1);
+
+    let response = admin
+        .get::<()>("/api/private/admin/reports?status=resolved")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total"], 1);
+    assert_eq!(response.json()["reports"][0]["id"], first);
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reports_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>("/api/private/admin/reports").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "status": "triaged" });
+    let response = user
+        .patch::<()>("/api/private/admin/reports/1", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
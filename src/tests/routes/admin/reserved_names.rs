@@ -0,0 +1,100 @@
+use super::new_admin;
+use crate::tests::builders::{CrateBuilder, PublishBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reserve_and_release_crate_name() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let admin = new_admin(&app).await;
+
+    let url = "/api/private/admin/reserved_crate_names/future-std";
+    let body = json!({ "reason": "Planned standard library crate" });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["reserved_crate_name"]["name"], "future-std");
+    assert_eq!(json["reserved_crate_name"]["created_by"], "admin");
+
+    let response = admin
+        .put::<()>(
+            "/api/private/admin/reserved_crate_names/Future_Std",
+            body.to_string(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the crate name `future-std` is already reserved"}]}"#);
+
+    let response = admin
+        .get::<()>("/api/private/admin/reserved_crate_names")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    let names = json["reserved_crate_names"].as_array().unwrap();
+    assert!(names
+        .iter()
+        .any(|reserved| reserved["name"] == "future-std"));
+    assert!(names.iter().any(|reserved| reserved["name"] == "std"));
+
+    let crate_to_publish = PublishBuilder::new("future_std", "1.0.0");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"cannot upload a crate with a reserved name"}]}"#);
+
+    let response = admin.delete::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.delete::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let crate_to_publish = PublishBuilder::new("future_std", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn existing_crate_names_can_not_be_reserved() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({});
+    let response = admin
+        .put::<()>(
+            "/api/private/admin/reserved_crate_names/FOO",
+            body.to_string(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a crate with the name `FOO` already exists"}]}"#);
+
+    let response = admin
+        .put::<()>(
+            "/api/private/admin/reserved_crate_names/foo%20bar",
+            body.to_string(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reserved_crate_names_require_admin() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user
+        .get::<()>("/api/private/admin/reserved_crate_names")
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let url = "/api/private/admin/reserved_crate_names/foo";
+    let response = user.put::<()>(url, json!({}).to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.delete::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,90 @@
+use super::new_admin;
+use crate::models::{NewOrganization, NewReservedPrefix};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_snapshot;
+
+const URL: &str = "/api/private/admin/reserved_prefixes";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn approve_and_delete_reserved_prefixes() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+
+    let new_organization = NewOrganization {
+        login: "acme",
+        name: None,
+    };
+    let organization = new_organization
+        .create(&mut conn, user.as_model())
+        .await
+        .unwrap();
+
+    let mut ids = Vec::new();
+    for prefix in ["acme-", "widgets-"] {
+        let new_reserved = NewReservedPrefix {
+            organization_id: organization.id,
+            prefix,
+            requested_by: user.as_model().id,
+        };
+        ids.push(new_reserved.insert(&mut conn).await.unwrap().id);
+    }
+
+    let response = admin
+        .put::<()>(&format!("{URL}/{}/approve", ids[1]), "")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let approved_at = response.json()["reserved_prefix"]["approved_at"].clone();
+    assert!(approved_at.is_string());
+
+    // Approving a reservation again keeps the original approval date
+    let response = admin
+        .put::<()>(&format!("{URL}/{}/approve", ids[1]), "")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json()["reserved_prefix"]["approved_at"],
+        approved_at
+    );
+
+    // Pending reservations are listed first
+    let response = admin.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["reserved_prefixes"][0]["prefix"], "acme-");
+    assert_eq!(json["reserved_prefixes"][1]["prefix"], "widgets-");
+
+    let response = anon.get::<()>("/api/v1/reserved_prefixes").await;
+    assert_eq!(
+        response.json()["reserved_prefixes"][0]["prefix"],
+        "widgets-"
+    );
+
+    let response = admin.delete::<()>(&format!("{URL}/{}", ids[0])).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ok":true}"#);
+
+    let response = admin.delete::<()>(&format!("{URL}/{}", ids[0])).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = admin
+        .put::<()>(&format!("{URL}/{}/approve", ids[0]), "")
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reserved_prefixes_require_admin() {
+    let (_, anon, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires admin privileges"}]}"#);
+
+    let response = user.put::<()>(&format!("{URL}/1/approve"), "").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = anon.delete::<()>(&format!("{URL}/1")).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,70 @@
+use super::new_admin;
+use crate::models::{StorageDiscrepancy, StorageDiscrepancyKind};
+use crate::tests::util::{RequestHelper, TestApp};
+use chrono::{DateTime, Utc};
+use http::StatusCode;
+use insta::assert_json_snapshot;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_storage_discrepancies() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| {
+            config.storage_orphan_quarantine = Some(Duration::from_secs(7 * 24 * 60 * 60));
+        })
+        .with_user()
+        .await;
+    let mut conn = app.db_conn().await;
+    let admin = new_admin(&app).await;
+    let url = "/api/private/admin/storage_discrepancies";
+
+    let seen_at = DateTime::parse_from_rfc3339("2024-06-20T10:00:00Z")?.with_timezone(&Utc);
+    let paths = ["crates/bar/bar-0.1.0.crate".to_string()];
+    let kind = StorageDiscrepancyKind::OrphanedCrateFile;
+    StorageDiscrepancy::record(&mut conn, kind, &paths, seen_at).await?;
+    let paths = ["3/f/foo".to_string()];
+    let kind = StorageDiscrepancyKind::MissingIndexFile;
+    StorageDiscrepancy::record(&mut conn, kind, &paths, seen_at).await?;
+
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = user.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = admin.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "meta": {
+        "total": 2
+      },
+      "storage_discrepancies": [
+        {
+          "deletable_at": "2024-06-27T10:00:00Z",
+          "first_seen_at": "2024-06-20T10:00:00Z",
+          "kind": "orphaned_crate_file",
+          "last_seen_at": "2024-06-20T10:00:00Z",
+          "path": "crates/bar/bar-0.1.0.crate"
+        },
+        {
+          "deletable_at": null,
+          "first_seen_at": "2024-06-20T10:00:00Z",
+          "kind": "missing_index_file",
+          "last_seen_at": "2024-06-20T10:00:00Z",
+          "path": "3/f/foo"
+        }
+      ]
+    }
+    "#);
+
+    let response = admin
+        .get::<()>(&format!("{url}?kind=missing_index_file"))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["meta"]["total"], 1);
+    assert_eq!(json["storage_discrepancies"][0]["path"], "3/f/foo");
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use super::new_admin;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_users() {
+    let (app, _, _) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+    app.db_new_user("other").await;
+
+    let response = admin.get::<()>("/api/private/admin/users?q=FOO").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json());
+
+    // `other@example.com` is matched by its email address
+    let response = admin
+        .get::<()>("/api/private/admin/users?q=r@example")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let logins = response.json()["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["login"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(logins, ["other"]);
+
+    let response = admin.get::<()>("/api/private/admin/users?q=%20").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"missing search query"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn admin_endpoints_require_admin() {
+    let (_, anon, user, token) = TestApp::init().with_token().await;
+
+    let response = anon.get::<()>("/api/private/admin/users?q=foo").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+
+    let response = user.get::<()>("/api/private/admin/users?q=foo").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires admin privileges"}]}"#);
+
+    let response = token.get::<()>("/api/private/admin/users/foo").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action can only be performed on the crates.io website"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn find_unknown_user() {
+    let (app, _) = TestApp::init().empty().await;
+    let admin = new_admin(&app).await;
+
+    let response = admin.get::<()>("/api/private/admin/users/unknown").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn lock_and_unlock_user() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+
+    let body = json!({ "reason": "spamming", "until": "2099-12-12T12:12:12Z" });
+    let response = admin
+        .put::<()>("/api/private/admin/users/foo/lock", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["user"]["lock"], @r#"
+    {
+      "reason": "spamming",
+      "until": "2099-12-12T12:12:12+00:00"
+    }
+    "#);
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"This account is locked until 2099-12-12 at 12:12:12 UTC. Reason: spamming"}]}"#);
+
+    let response = admin
+        .delete::<()>("/api/private/admin/users/foo/lock")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["user"]["lock"], json!(null));
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin.get::<()>("/api/v1/me/audit").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let actions = response.json()["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(actions, ["account_unlock", "account_lock"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn lock_user_requires_reason() {
+    let (app, _, _) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+
+    let body = json!({ "reason": " " });
+    let response = admin
+        .put::<()>("/api/private/admin/users/foo/lock", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a reason is required to lock an account"}]}"#);
+
+    let body = json!({ "reason": "oops" });
+    let response = admin
+        .put::<()>("/api/private/admin/users/admin/lock", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"you can not lock your own account"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rate_limit_overrides() {
+    let (app, _, _) = TestApp::init().with_user().await;
+    let admin = new_admin(&app).await;
+
+    let url = "/api/private/admin/users/foo/rate_limits";
+    let body = json!({ "action": "publish_new", "burst": 50 });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body =
+        json!({ "action": "publish_new", "burst": 100, "expires_at": "2099-12-12T12:12:12Z" });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["user"]["rate_limit_overrides"], @r#"
+    [
+      {
+        "action": "publish_new",
+        "burst": 100,
+        "expires_at": "2099-12-12T12:12:12+00:00"
+      }
+    ]
+    "#);
+
+    let body = json!({ "action": "publish_new", "burst": 0 });
+    let response = admin.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the burst must be a positive number"}]}"#);
+
+    let response = admin
+        .delete::<()>("/api/private/admin/users/foo/rate_limits/publish_new")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["user"]["rate_limit_overrides"], @"[]");
+}
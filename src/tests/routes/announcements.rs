@@ -0,0 +1,121 @@
+use crate::models::{AnnouncementAudience, AnnouncementSeverity, NewAnnouncement};
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel_async::AsyncPgConnection;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/announcements";
+
+async fn announce(
+    conn: &mut AsyncPgConnection,
+    message: &str,
+    severity: AnnouncementSeverity,
+    audience: AnnouncementAudience,
+    starts_at: DateTime<Utc>,
+    ends_at: Option<DateTime<Utc>>,
+    created_by: i32,
+) {
+    NewAnnouncement {
+        message,
+        docs_url: None,
+        severity,
+        audience,
+        starts_at,
+        ends_at,
+        created_by,
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+}
+
+fn messages(json: Value) -> Vec<String> {
+    json["announcements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|announcement| announcement["message"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_announcements() {
+    let (app, anon, user, token) = TestApp::init().with_token().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let owner = app.db_new_user("owner").await;
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let now = Utc::now();
+    let hour = TimeDelta::hours(1);
+
+    use AnnouncementAudience::*;
+    use AnnouncementSeverity::*;
+    announce(&mut conn, "everyone", Info, All, now - hour, None, user_id).await;
+    announce(
+        &mut conn,
+        "urgent",
+        Critical,
+        All,
+        now - hour,
+        None,
+        user_id,
+    )
+    .await;
+    announce(
+        &mut conn,
+        "owners",
+        Warning,
+        CrateOwners,
+        now - hour,
+        None,
+        user_id,
+    )
+    .await;
+    announce(
+        &mut conn,
+        "tokens",
+        Info,
+        TokenUsers,
+        now - hour,
+        None,
+        user_id,
+    )
+    .await;
+    announce(&mut conn, "scheduled", Info, All, now + hour, None, user_id).await;
+    announce(
+        &mut conn,
+        "ended",
+        Info,
+        All,
+        now - hour * 2,
+        Some(now - hour),
+        user_id,
+    )
+    .await;
+
+    let response = anon.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(messages(response.json()), ["urgent", "everyone"]);
+
+    let response = owner.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(messages(response.json()), ["urgent", "owners", "everyone"]);
+
+    let response = user.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(messages(response.json()), ["urgent", "tokens", "everyone"]);
+
+    let response = token.get::<()>(URL).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(messages(response.json()), ["urgent", "tokens", "everyone"]);
+
+    let json = anon.get::<()>(URL).await.json();
+    assert_eq!(json["announcements"][0]["severity"], "critical");
+    assert!(json["announcements"][0].get("audience").is_none());
+}
@@ -0,0 +1,74 @@
+use crate::tests::builders::CrateBuilder;
+use crate::tests::new_category;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::EncodableAggregateDownload;
+use crate::worker::jobs::UpdateAggregateDownloads;
+use chrono::Utc;
+use crates_io_database::schema::categories;
+use crates_io_worker::BackgroundJob;
+use diesel::insert_into;
+use diesel_async::RunQueryDsl;
+
+#[derive(Deserialize)]
+struct DownloadsResponse {
+    downloads: Vec<EncodableAggregateDownload>,
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn downloads_are_aggregated_by_background_job() -> anyhow::Result<()> {
+    let url = "/api/v1/categories/foo/downloads";
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    anon.get(url).await.assert_not_found();
+
+    let cats = vec![
+        new_category("Foo", "foo", "Foo crates"),
+        new_category("Foo::Bar", "foo::bar", "Bar crates"),
+    ];
+
+    insert_into(categories::table)
+        .values(cats)
+        .execute(&mut conn)
+        .await?;
+
+    CrateBuilder::new("foo", user_id)
+        .category("foo")
+        .version("1.0.0")
+        .recent_downloads(10)
+        .expect_build(&mut conn)
+        .await;
+
+    // Crates in subcategories are not included in the parent category.
+    CrateBuilder::new("bar", user_id)
+        .category("foo::bar")
+        .version("1.0.0")
+        .recent_downloads(5)
+        .expect_build(&mut conn)
+        .await;
+
+    let today = Utc::now().date_naive();
+    UpdateAggregateDownloads::for_date(today)
+        .enqueue(&mut conn)
+        .await?;
+
+    app.run_pending_background_jobs().await;
+
+    let json: DownloadsResponse = anon.get(url).await.good();
+    assert_eq!(
+        json.downloads,
+        vec![EncodableAggregateDownload {
+            date: today.to_string(),
+            downloads: 10,
+        }]
+    );
+
+    let json: DownloadsResponse = anon
+        .get("/api/v1/categories/foo::bar/downloads")
+        .await
+        .good();
+    assert_eq!(json.downloads[0].downloads, 5);
+
+    Ok(())
+}
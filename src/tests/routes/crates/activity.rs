@@ -0,0 +1,86 @@
+use crate::schema::crates;
+use crate::tests::builders::PublishBuilder;
+use crate::tests::routes::crates::versions::yank_unyank::YankRequestHelper;
+use crate::tests::util::{RequestHelper, TestApp};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_activity() {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let new_owner = app.db_new_user("new-owner").await;
+
+    let crate_to_publish = PublishBuilder::new("foo_activity", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    token.yank("foo_activity", "1.0.0").await.good();
+    token.unyank("foo_activity", "1.0.0").await.good();
+
+    let response = user.add_named_owner("foo_activity", "new-owner").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Pending invitations are not part of the public activity
+    let response = anon.get::<()>("/api/v1/crates/foo_activity/activity").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total"], 3);
+
+    let mut conn = app.db_conn().await;
+    let krate_id: i32 = crates::table
+        .filter(crates::name.eq("foo_activity"))
+        .select(crates::id)
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+
+    let body = json!({
+        "crate_owner_invite": {
+            "invited_by_username": "",
+            "crate_name": "foo_activity",
+            "crate_id": krate_id,
+            "created_at": "",
+            "accepted": true
+        }
+    });
+    let url = format!("/api/v1/me/crate_owner_invitations/{krate_id}");
+    let response = new_owner.put::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = new_owner.remove_named_owner("foo_activity", "foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = anon.get::<()>("/api/v1/crates/foo_activity/activity").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".activity[].id" => "[id]",
+        ".activity[].created_at" => "[datetime]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_activity_pagination() {
+    let (_, anon, _, token) = TestApp::full().with_token().await;
+
+    for version in ["1.0.0", "1.1.0", "1.2.0"] {
+        let crate_to_publish = PublishBuilder::new("foo_activity", version);
+        token.publish_crate(crate_to_publish).await.good();
+    }
+
+    let url = "/api/v1/crates/foo_activity/activity";
+    let response = anon.get_with_query::<()>(url, "per_page=2&page=2").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["meta"]["total"], 3);
+    assert_eq!(json["activity"].as_array().unwrap().len(), 1);
+    assert_eq!(json["activity"][0]["version"], "1.0.0");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_activity_unknown_crate() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/crates/unknown/activity").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `unknown` does not exist"}]}"#);
+}
@@ -0,0 +1,56 @@
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_api_usage() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_usage", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    anon.get::<()>("/api/v1/crates/foo_usage").await.good();
+    anon.get::<()>("/api/v1/crates/foo_usage/1.0.0")
+        .await
+        .good();
+    anon.get::<()>("/api/v1/crates/foo_usage/1.0.0/download")
+        .await
+        .assert_redirect_ends_with("/crates/foo_usage/foo_usage-1.0.0.crate");
+
+    // Failed requests and requests about unknown crates are not counted
+    let response = anon.get::<()>("/api/v1/crates/foo_usage/2.0.0").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = anon.get::<()>("/api/v1/crates/unknown").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    app.as_inner().api_usage.persist(&mut conn).await.unwrap();
+
+    let response = user.get::<()>("/api/v1/crates/foo_usage/api_usage").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".usage[].date" => "[date]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_api_usage_requires_ownership() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo_usage", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = other.get::<()>("/api/v1/crates/foo_usage/api_usage").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of this crate can see its API usage"}]}"#);
+
+    let response = anon.get::<()>("/api/v1/crates/foo_usage/api_usage").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+}
@@ -0,0 +1,54 @@
+use crate::tests::builders::{CrateBuilder, PublishBuilder};
+use crate::tests::routes::crates::versions::yank_unyank::YankRequestHelper;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_audit_log() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    app.db_new_user("new-owner").await;
+
+    let crate_to_publish = PublishBuilder::new("foo_audit", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    token.yank("foo_audit", "1.0.0").await.good();
+    token.unyank("foo_audit", "1.0.0").await.good();
+
+    let response = user.add_named_owner("foo_audit", "new-owner").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user.get::<()>("/api/v1/crates/foo_audit/audit").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".entries[].id" => "[id]",
+        ".entries[].created_at" => "[datetime]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_audit_log_requires_ownership() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo_audit", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = other.get::<()>("/api/v1/crates/foo_audit/audit").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of this crate can see its audit log"}]}"#);
+
+    let response = anon.get::<()>("/api/v1/crates/foo_audit/audit").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_audit_log_unknown_crate() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.get::<()>("/api/v1/crates/unknown/audit").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `unknown` does not exist"}]}"#);
+}
@@ -0,0 +1,163 @@
+use crate::rate_limiter::LimitedAction;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{MockCookieUser, RequestHelper, TestApp};
+use googletest::prelude::*;
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+use std::time::Duration;
+
+const URL: &str = "/api/v1/crates/foo_contact/contact_owners";
+
+async fn accept_contact_requests(user: &MockCookieUser) {
+    let url = format!("/api/v1/users/{}", user.as_model().id);
+    let body = json!({ "user": { "contact_requests": true } });
+    let response = user.put::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+fn message() -> String {
+    json!({ "message": {
+        "subject": "Security issue in the parser",
+        "body": "The parser panics on malformed input.",
+    }})
+    .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn contact_owners() {
+    let (app, _, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_contact", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    accept_contact_requests(&owner).await;
+
+    let sender = app.db_new_user("sender").await;
+    let response = sender.post::<()>(URL, message()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ok":true}"#);
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn contact_owners_not_accepted() {
+    let (app, _, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_contact", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let sender = app.db_new_user("sender").await;
+    let response = sender.post::<()>(URL, message()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"none of the owners of this crate accept contact requests"}]}"#);
+
+    assert_that!(app.emails().await, empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn contact_owners_as_owner() {
+    let (app, _, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_contact", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    accept_contact_requests(&owner).await;
+
+    let response = owner.post::<()>(URL, message()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"you are an owner of this crate"}]}"#);
+
+    assert_that!(app.emails().await, empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn contact_owners_invalid_message() {
+    let (app, _, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_contact", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    accept_contact_requests(&owner).await;
+
+    let sender = app.db_new_user("sender").await;
+
+    let body = json!({ "message": { "subject": " ", "body": "Hello" } });
+    let response = sender.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the message subject must not be empty"}]}"#);
+
+    let body = json!({ "message": { "subject": "Hello\nBcc: spam@example.com", "body": "Hello" } });
+    let response = sender.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the message subject must be a single line"}]}"#);
+
+    let body = json!({ "message": { "subject": "Hello", "body": "" } });
+    let response = sender.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the message body must not be empty"}]}"#);
+
+    let body = json!({ "message": { "subject": "Hello", "body": "a".repeat(5_001) } });
+    let response = sender.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the message body must not exceed 5000 characters"}]}"#);
+
+    assert_that!(app.emails().await, empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn contact_owners_errors() {
+    let (app, anon, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_contact", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.post::<()>(URL, message()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+
+    let sender = app.db_new_user("sender").await;
+    let url = "/api/v1/crates/unknown/contact_owners";
+    let response = sender.post::<()>(url, message()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `unknown` does not exist"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn contact_owners_rate_limited() {
+    let (app, _, owner) = TestApp::init()
+        .with_rate_limit(
+            LimitedAction::ContactOwners,
+            Duration::from_secs(60 * 60),
+            1,
+        )
+        .with_user()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_contact", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    accept_contact_requests(&owner).await;
+
+    let sender = app.db_new_user("sender").await;
+    let response = sender.post::<()>(URL, message()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = sender.post::<()>(URL, message()).await;
+    response.assert_rate_limited(LimitedAction::ContactOwners);
+
+    assert_eq!(app.emails().await.len(), 1);
+}
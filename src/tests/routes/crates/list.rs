@@ -1,16 +1,19 @@
+use crate::controllers::helpers::crate_list::{CrateListInclude, CrateListIncludes};
 use crate::models::Category;
 use crate::schema::{crates, users};
 use crate::tests::builders::{CrateBuilder, VersionBuilder};
 use crate::tests::util::{RequestHelper, TestApp};
-use crate::tests::{new_category, new_user};
+use crate::tests::{add_team_to_crate, new_category, new_team, new_user};
 use crates_io_database::schema::categories;
+use diesel::connection::InstrumentationEvent;
 use diesel::{dsl::*, prelude::*, update};
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use googletest::prelude::*;
 use http::StatusCode;
 use insta::{assert_json_snapshot, assert_snapshot};
 use regex::Regex;
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn index() -> anyhow::Result<()> {
@@ -847,6 +850,40 @@ async fn max_stable_version() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn msrv_sorting() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_msrv", user.id)
+        .version(VersionBuilder::new("1.0.0").rust_version("1.70"))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar_msrv", user.id)
+        .version(VersionBuilder::new("1.0.0").rust_version("1.9"))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("baz_msrv", user.id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    // `1.9` is older than `1.70`, and crates without an MSRV come last
+    let json = anon.search("sort=msrv").await;
+    assert_eq!(json.meta.total, 3);
+    assert_eq!(json.crates[0].name, "bar_msrv");
+    assert_eq!(json.crates[0].msrv.as_deref(), Some("1.9"));
+    assert_eq!(json.crates[1].name, "foo_msrv");
+    assert_eq!(json.crates[1].msrv.as_deref(), Some("1.70"));
+    assert_eq!(json.crates[2].name, "baz_msrv");
+    assert_eq!(json.crates[2].msrv, None);
+
+    Ok(())
+}
+
 /// Given two crates, one with downloads less than 90 days ago, the
 /// other with all downloads greater than 90 days ago, check that
 /// the order returned is by recent downloads, descending. Check
@@ -1209,6 +1246,107 @@ async fn crates_by_user_id_not_including_deleted_owners() -> anyhow::Result<()>
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn index_include() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    insert_into(categories::table)
+        .values(new_category("Category 1", "cat1", "Category 1 crates"))
+        .execute(&mut conn)
+        .await?;
+
+    let krate = CrateBuilder::new("foo_include", user.id)
+        .keyword("kw1")
+        .keyword("kw2")
+        .category("cat1")
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar_include", user.id)
+        .expect_build(&mut conn)
+        .await;
+
+    let team = new_team("github:test_org:core")
+        .create_or_update(&mut conn)
+        .await?;
+    add_team_to_crate(&team, &krate, user, &mut conn).await?;
+
+    for json in search_both(&anon, "include=owners,keywords,categories").await {
+        assert_eq!(json.crates.len(), 2);
+
+        let bar = &json.crates[0];
+        assert_eq!(bar.name, "bar_include");
+        assert_eq!(bar.keywords, Some(vec![]));
+        assert_eq!(bar.categories, Some(vec![]));
+        let owners = bar.owners.as_ref().unwrap();
+        assert_eq!(owners.len(), 1);
+        assert_eq!(owners[0].login, "foo");
+
+        let foo = &json.crates[1];
+        assert_eq!(foo.name, "foo_include");
+        assert_eq!(foo.keywords, Some(vec!["kw1".into(), "kw2".into()]));
+        assert_eq!(foo.categories, Some(vec!["cat1".into()]));
+        let owners = foo.owners.as_ref().unwrap();
+        let logins = owners.iter().map(|o| o.login.as_str()).collect::<Vec<_>>();
+        assert_eq!(logins, ["foo", "github:test_org:core"]);
+    }
+
+    for json in search_both(&anon, "").await {
+        assert_none!(json.crates[0].keywords);
+        assert_none!(json.crates[0].categories);
+        assert_none!(json.crates[0].owners);
+    }
+
+    let response = anon.get::<()>("/api/v1/crates?include=badges").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid component for ?include= (expected 'owners', 'keywords', or 'categories')"}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn index_include_queries_are_batched() -> anyhow::Result<()> {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let mut crate_ids = Vec::new();
+    for name in ["foo_batched", "bar_batched", "baz_batched"] {
+        let krate = CrateBuilder::new(name, user.id)
+            .keyword(name)
+            .expect_build(&mut conn)
+            .await;
+        crate_ids.push(krate.id);
+    }
+
+    let num_queries = Arc::new(AtomicUsize::new(0));
+    let counter = num_queries.clone();
+    conn.set_instrumentation(move |event: InstrumentationEvent<'_>| {
+        if matches!(event, InstrumentationEvent::StartQuery { .. }) {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    let include = CrateListInclude {
+        owners: true,
+        keywords: true,
+        categories: true,
+    };
+    let mut includes = CrateListIncludes::load(&mut conn, &crate_ids, include).await?;
+
+    // One query per association, independent of the number of crates
+    assert_eq!(num_queries.load(Ordering::Relaxed), 3);
+    for crate_id in crate_ids {
+        assert_eq!(includes.take_owners(crate_id).unwrap().len(), 1);
+        assert_eq!(includes.take_keywords(crate_id).unwrap().len(), 1);
+        assert_eq!(includes.take_categories(crate_id).unwrap().len(), 0);
+    }
+
+    Ok(())
+}
+
 static PAGE_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"((?:^page|&page|\?page)=\d+)").unwrap());
 
@@ -1,8 +1,24 @@
+mod activity;
+mod api_usage;
+mod audit;
+mod contact_owners;
 pub mod downloads;
 mod following;
 mod list;
+mod most_depended;
 mod new;
 pub mod owners;
+mod page;
 mod read;
+mod reclaim;
+mod redirect;
+mod report;
+mod repository_verification;
 mod reverse_dependencies;
+mod score;
+mod semver_check;
+mod settings;
+mod suggested_categories;
+mod transfer;
+mod trending;
 pub mod versions;
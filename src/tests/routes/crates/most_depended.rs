@@ -0,0 +1,114 @@
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::EncodableMostDependedCrate;
+use crate::worker::jobs::UpdateCrateDependentStats;
+use crates_io_worker::BackgroundJob;
+use insta::assert_snapshot;
+
+#[derive(Deserialize)]
+struct MostDependedResponse {
+    crates: Vec<EncodableMostDependedCrate>,
+    meta: Meta,
+}
+
+#[derive(Deserialize)]
+struct Meta {
+    total: i64,
+}
+
+fn names(response: &MostDependedResponse) -> Vec<&str> {
+    response.crates.iter().map(|c| c.name.as_str()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn most_depended_crates_are_counted_by_background_job() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let foo = CrateBuilder::new("foo", user_id)
+        .description("The foo crate")
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let bar = CrateBuilder::new("bar", user_id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .dependency(&foo, None)
+                .dependency(&foo, Some("cfg(windows)")),
+        )
+        .recent_downloads(10)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("baz", user_id)
+        .version(VersionBuilder::new("1.0.0").dependency(&bar, None))
+        .recent_downloads(5000)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("quux", user_id)
+        .version(VersionBuilder::new("1.0.0").dependency(&foo, None))
+        .expect_build(&mut conn)
+        .await;
+
+    // Yanked crates are not counted as dependents.
+    CrateBuilder::new("qux", user_id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .yanked(true)
+                .dependency(&bar, None),
+        )
+        .recent_downloads(100_000)
+        .expect_build(&mut conn)
+        .await;
+
+    let json: MostDependedResponse = anon.get("/api/v1/crates/most_depended").await.good();
+    assert_eq!(json.crates, vec![]);
+
+    UpdateCrateDependentStats.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    let json: MostDependedResponse = anon.get("/api/v1/crates/most_depended").await.good();
+    assert_eq!(json.meta.total, 2);
+    assert_eq!(
+        json.crates,
+        vec![
+            EncodableMostDependedCrate {
+                name: "foo".into(),
+                description: Some("The foo crate".into()),
+                default_version: Some("1.0.0".into()),
+                dependents: 2,
+                weighted_dependents: 10,
+            },
+            EncodableMostDependedCrate {
+                name: "bar".into(),
+                description: None,
+                default_version: Some("1.0.0".into()),
+                dependents: 1,
+                weighted_dependents: 5000,
+            },
+        ]
+    );
+
+    let url = "/api/v1/crates/most_depended?sort=weighted";
+    let json: MostDependedResponse = anon.get(url).await.good();
+    assert_eq!(names(&json), ["bar", "foo"]);
+
+    let url = "/api/v1/crates/most_depended?per_page=1&page=2";
+    let json: MostDependedResponse = anon.get(url).await.good();
+    assert_eq!(json.meta.total, 2);
+    assert_eq!(names(&json), ["bar"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_sort() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon
+        .get::<()>("/api/v1/crates/most_depended?sort=foo")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid sort order: foo"}]}"#);
+}
@@ -1,9 +1,10 @@
 use crate::models::token::{CrateScope, EndpointScope};
-use crate::tests::builders::CrateBuilder;
+use crate::tests::builders::{CrateBuilder, PublishBuilder};
 use crate::tests::owners::expire_invitation;
 use crate::tests::util::{RequestHelper, TestApp};
 use http::StatusCode;
-use insta::assert_snapshot;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
 
 // This is testing Cargo functionality! ! !
 // specifically functions modify_owners and add_owners
@@ -384,3 +385,66 @@ async fn no_invite_emails_for_txn_rollback() {
     // 9 emails to the good invitees should have been sent.
     assert_eq!(app.emails().await.len(), 9);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invite_with_publish_role() {
+    let (app, _, owner, owner_token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let publisher = app.db_new_user("publisher").await;
+    let krate = CrateBuilder::new("foo_roles", owner.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "owners": ["publisher"], "role": "publish" });
+    let response = owner_token
+        .put::<()>("/api/v1/crates/foo_roles/owners", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let invitation = json!({ "crate_owner_invite": { "crate_id": krate.id, "accepted": true } });
+    let url = format!("/api/v1/me/crate_owner_invitations/{}", krate.id);
+    let response = publisher.put::<()>(&url, invitation.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = owner.get::<()>("/api/v1/crates/foo_roles/owners").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".users[].id" => "[id]",
+    });
+
+    // Owners with the `publish` role can publish new versions…
+    let publisher_token = publisher.db_new_token("publish").await;
+    let crate_to_publish = PublishBuilder::new("foo_roles", "1.1.0");
+    let response = publisher_token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // …but they can not manage the owners of the crate
+    app.db_new_user("other").await;
+    let response = publisher_token.add_named_owner("foo_roles", "other").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"owners with the `publish` role don't have permission to modify owners"}]}"#);
+
+    let response = publisher_token
+        .remove_named_owner("foo_roles", &owner.as_model().gh_login)
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invite_with_invalid_role() {
+    let (app, _, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    app.db_new_user("publisher").await;
+    CrateBuilder::new("foo_roles", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "owners": ["publisher"], "role": "owner" });
+    let response = owner
+        .put::<()>("/api/v1/crates/foo_roles/owners", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
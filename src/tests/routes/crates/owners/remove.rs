@@ -1,4 +1,4 @@
-use crate::models::{CrateOwner, OwnerKind};
+use crate::models::{CrateOwner, OwnerKind, OwnerRole};
 use crate::tests::builders::CrateBuilder;
 use crate::tests::util::{RequestHelper, TestApp};
 use crates_io_database::schema::crate_owners;
@@ -100,6 +100,7 @@ async fn test_remove_uppercase_user() {
             created_by: cookie.as_model().id,
             owner_kind: OwnerKind::User,
             email_notifications: true,
+            role: OwnerRole::Admin,
         })
         .execute(&mut conn)
         .await
@@ -165,3 +166,38 @@ async fn test_remove_uppercase_team() {
     assert_eq!(response.status(), StatusCode::OK);
     assert_snapshot!(response.text(), @r#"{"msg":"owners successfully removed","ok":true}"#);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_remove_last_admin() {
+    use diesel_async::RunQueryDsl;
+
+    let (app, _, cookie) = TestApp::full().with_user().await;
+    let user2 = app.db_new_user("user2").await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", cookie.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    diesel::insert_into(crate_owners::table)
+        .values(CrateOwner {
+            crate_id: krate.id,
+            owner_id: user2.as_model().id,
+            created_by: cookie.as_model().id,
+            owner_kind: OwnerKind::User,
+            email_notifications: true,
+            role: OwnerRole::Publish,
+        })
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let response = cookie
+        .remove_named_owner("foo", &cookie.as_model().gh_login)
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"cannot remove all owners with the `admin` role of a crate. At least one owner with the `admin` role is required to manage the owners of the crate."}]}"#);
+
+    let response = cookie.remove_named_owner("foo", "user2").await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,47 @@
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::{header, StatusCode};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn render_crate_page() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_page", user.as_model().id)
+        .description("A crate with <b>markup</b> in its description")
+        .repository("https://github.com/rust-lang/foo_page")
+        .version(VersionBuilder::new("0.1.0"))
+        .version(VersionBuilder::new("0.2.0"))
+        .version(VersionBuilder::new("0.3.0").yanked(true))
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>("/crates/foo_page").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(response.headers()["surrogate-key"], "crate/foo_page");
+    // Browsers get the frontend instead, so cached pages must not be shared
+    // between different clients.
+    assert_eq!(
+        response.headers()[header::VARY],
+        "Accept, Cookie, User-Agent"
+    );
+
+    let html = response.text();
+    assert!(html.contains("<h1>foo_page "));
+    assert!(html.contains("A crate with &lt;b&gt;markup"));
+    assert!(!html.contains("<b>markup</b>"));
+    assert!(html.contains(">0.1.0</a>"));
+    assert!(!html.contains(">0.3.0</a>"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn render_unknown_crate_page() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/crates/unknown").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
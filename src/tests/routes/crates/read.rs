@@ -105,6 +105,47 @@ async fn show_all_yanked() {
     });
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn show_msrv() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_msrv", user.id)
+        .version(VersionBuilder::new("1.0.0").rust_version("1.60"))
+        .version(VersionBuilder::new("1.1.0").rust_version("1.70"))
+        .version(VersionBuilder::new("2.0.0-beta.1").rust_version("1.80"))
+        .expect_build(&mut conn)
+        .await;
+
+    // The MSRV of the default version is used, not the one of the newest
+    let json = anon.show_crate("foo_msrv").await;
+    assert_eq!(json.krate.default_version.as_deref(), Some("1.1.0"));
+    assert_eq!(json.krate.msrv.as_deref(), Some("1.70"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn show_latest_versions() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_latest", user.id)
+        .version("1.0.0")
+        .version(VersionBuilder::new("1.1.0").yanked(true))
+        .version("2.0.0-beta.1")
+        .version(VersionBuilder::new("2.0.0-beta.2").yanked(true))
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo_latest?include=").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["crate"]["latest_stable"], "1.0.0");
+    assert_eq!(json["crate"]["latest_prerelease"], "2.0.0-beta.1");
+    assert_eq!(json["crate"]["max_stable_version"], "1.0.0");
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_missing() {
     let (_, anon) = TestApp::init().empty().await;
@@ -114,6 +155,36 @@ async fn test_missing() {
     assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `missing` does not exist"}]}"#);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn surrogate_keys() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("Foo-Bar", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    for url in [
+        "/api/v1/crates/foo_bar",
+        "/api/v2/crates/Foo-Bar",
+        "/api/v1/crates/foo-bar/versions",
+        "/api/v1/crates/foo_bar/1.0.0",
+    ] {
+        let response = anon.get::<()>(url).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let headers = response.headers();
+        assert_eq!(headers["surrogate-key"], "crate/foo_bar", "{url}");
+        assert_eq!(headers["surrogate-control"], "max-age=300", "{url}");
+    }
+
+    // Error responses are not cached.
+    let response = anon.get::<()>("/api/v1/crates/missing").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_none!(response.headers().get("surrogate-key"));
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn version_size() {
     let (_, _, user) = TestApp::full().with_user().await;
@@ -219,3 +290,43 @@ async fn test_include_default_version() {
     assert_eq!(resp_both.status(), StatusCode::OK);
     assert_eq!(resp_versions.json(), resp_both.json());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn show_v2() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_show_v2", user.id)
+        .version(VersionBuilder::new("1.0.0"))
+        .version(VersionBuilder::new("0.5.0"))
+        .keyword("kw1")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>("/api/v2/crates/foo_show_v2").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["data"]["name"], "foo_show_v2");
+    assert_eq!(json["data"]["default_version"], "1.0.0");
+    assert_eq!(
+        json["data"]["links"]["versions"],
+        "/api/v2/crates/foo_show_v2/versions"
+    );
+    assert!(json["data"]["created_at"].as_str().unwrap().ends_with('Z'));
+    assert_eq!(json["included"], serde_json::json!({}));
+
+    let response = anon
+        .get::<()>("/api/v2/crates/foo_show_v2?include=versions,keywords")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["included"]["versions"].as_array().unwrap().len(), 2);
+    assert_eq!(json["included"]["versions"][0]["crate_name"], "foo_show_v2");
+    assert_eq!(json["included"]["keywords"][0]["keyword"], "kw1");
+    assert!(json["included"].get("categories").is_none());
+
+    let response = anon.get::<()>("/api/v2/crates/missing").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `missing` does not exist"}]}"#);
+}
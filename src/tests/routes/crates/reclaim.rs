@@ -0,0 +1,77 @@
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn request_to_reclaim_crate() {
+    let (app, _, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let requester = app.db_new_user("requester").await;
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo/reclaim_requests";
+    let body = json!({ "reclaim_request": { "reason": "The crate has been empty for years." } });
+    let response = requester.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["reclaim_request"]["crate_name"], "foo");
+    assert_eq!(json["reclaim_request"]["requester"], "requester");
+    assert_eq!(json["reclaim_request"]["status"], "pending");
+    assert!(json["reclaim_request"]["eligible_at"].is_string());
+
+    let response = requester.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"you already requested this crate"}]}"#);
+
+    let response = owner.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json()["reclaim_requests"][0]["requester"],
+        "requester"
+    );
+
+    let response = other.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"reclaim_requests":[]}"#);
+
+    let emails = app.emails().await;
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].contains("crates.io: Request to reclaim the \"foo\" crate"));
+    assert!(emails[0].contains("The crate has been empty for years."));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_reclaim_requests() {
+    let (app, anon, owner) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let requester = app.db_new_user("requester").await;
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo/reclaim_requests";
+    let body = json!({ "reclaim_request": { "reason": "  " } });
+    let response = requester.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the reason must not be empty"}]}"#);
+
+    let body = json!({ "reclaim_request": { "reason": "I want it." } });
+    let response = owner.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"owners of a crate can not reclaim it"}]}"#);
+
+    let response = anon.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = requester
+        .post::<()>("/api/v1/crates/missing/reclaim_requests", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,190 @@
+use crate::models::RedirectStatus;
+use crate::schema::{crate_redirects, crates};
+use crate::tests::builders::{CrateBuilder, PublishBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+const URL: &str = "/api/v1/crates/foo/redirect_requests";
+
+async fn approve_all(conn: &mut AsyncPgConnection) {
+    diesel::update(crate_redirects::table)
+        .set(crate_redirects::status.eq(RedirectStatus::Approved))
+        .execute(conn)
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn request_redirect() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body =
+        json!({ "redirect_request": { "target": "bar", "reason": "foo has been renamed." } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["redirect_request"]["name"], "foo");
+    assert_eq!(json["redirect_request"]["target"], "bar");
+    assert_eq!(json["redirect_request"]["requested_by"], "foo");
+    assert_eq!(json["redirect_request"]["status"], "pending");
+
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a redirect of this crate has already been requested"}]}"#);
+
+    // Pending redirects don't affect the metadata endpoints.
+    let response = user.get::<()>("/api/v1/crates/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_redirect_requests() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", other.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "redirect_request": { "target": "bar", "reason": "  " } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the reason must not be empty"}]}"#);
+
+    let body = json!({ "redirect_request": { "target": "foo", "reason": "Renamed." } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a crate can not be redirected to itself"}]}"#);
+
+    let body = json!({ "redirect_request": { "target": "bar", "reason": "Renamed." } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of the `bar` crate can do this"}]}"#);
+
+    let response = other.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of the `foo` crate can do this"}]}"#);
+
+    let body = json!({ "redirect_request": { "target": "missing", "reason": "Renamed." } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = anon.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn approved_redirects_redirect_metadata_endpoints() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    let body =
+        json!({ "redirect_request": { "target": "bar", "reason": "foo has been renamed." } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    approve_all(&mut conn).await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo").await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    response.assert_redirect_ends_with("/api/v1/crates/bar");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the crate `foo` has been renamed to `bar`"}]}"#);
+
+    let response = anon.get::<()>("/api/v2/crates/foo").await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    response.assert_redirect_ends_with("/api/v2/crates/bar");
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/foo/versions", "sort=date&per_page=5")
+        .await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    response.assert_redirect_ends_with("/api/v1/crates/bar/versions?sort=date&per_page=5");
+
+    let response = anon.get::<()>("/api/v2/crates/foo/versions").await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    response.assert_redirect_ends_with("/api/v2/crates/bar/versions");
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0").await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    response.assert_redirect_ends_with("/api/v1/crates/bar/1.0.0");
+
+    let response = anon.get::<()>("/api/v2/crates/foo/1.0.0").await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    response.assert_redirect_ends_with("/api/v2/crates/bar/1.0.0");
+
+    // Crate names are compared like crate names themselves.
+    let response = anon.get::<()>("/api/v1/crates/FOO").await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    response.assert_redirect_ends_with("/api/v1/crates/bar");
+
+    let response = anon.get::<()>("/api/v1/crates/bar").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The successor itself can't be redirected any further.
+    CrateBuilder::new("baz", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+    let url = "/api/v1/crates/baz/redirect_requests";
+    let body =
+        json!({ "redirect_request": { "target": "foo", "reason": "baz has been renamed." } });
+    let response = user.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the `foo` crate is already redirected to `bar`"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn approved_redirects_block_re_registration() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body =
+        json!({ "redirect_request": { "target": "bar", "reason": "foo has been renamed." } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    approve_all(&mut conn).await;
+
+    diesel::delete(crates::table.filter(crates::name.eq("foo")))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the crate name `foo` has been redirected to `bar` and can not be registered again"}]}"#);
+
+    let crate_to_publish = PublishBuilder::new("bar", "2.0.0");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,87 @@
+use crate::rate_limiter::LimitedAction;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+use std::time::Duration;
+
+const URL: &str = "/api/v1/crates/foo_report/report";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn report_crate() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let reporter = app.db_new_user("reporter").await;
+    CrateBuilder::new("foo_report", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "report": {
+        "category": "malware",
+        "description": "The build script downloads a binary.",
+        "contact": "security@example.com",
+    }});
+    let response = reporter.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".report.id" => "[id]",
+        ".report.created_at" => "[datetime]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn report_crate_validation() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_report", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "report": { "category": "spam", "description": " " } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the report description must not be empty"}]}"#);
+
+    let body =
+        json!({ "report": { "category": "spam", "description": "spam", "contact": "nope" } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid contact email address"}]}"#);
+
+    let body = json!({ "report": { "category": "unknown", "description": "spam" } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body = json!({ "report": { "category": "spam", "description": "spam" } });
+    let response = anon.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+
+    let url = "/api/v1/crates/unknown/report";
+    let response = user.post::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `unknown` does not exist"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn report_crate_rate_limited() {
+    let (app, _, user) = TestApp::init()
+        .with_rate_limit(LimitedAction::ReportCrate, Duration::from_secs(60 * 60), 1)
+        .with_user()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_report", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "report": { "category": "spam", "description": "spam" } });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user.post::<()>(URL, body.to_string()).await;
+    response.assert_rate_limited(LimitedAction::ReportCrate);
+}
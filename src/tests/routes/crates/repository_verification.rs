@@ -0,0 +1,121 @@
+use crate::schema::crates;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::assert_snapshot;
+
+const URL: &str = "/api/v1/crates/foo_repo/repository_verification";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_repository() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_repo", user.as_model().id)
+        .repository("https://github.com/test-org/writable")
+        .expect_build(&mut conn)
+        .await;
+
+    let krate = anon.show_crate("foo_repo").await.krate;
+    assert_eq!(krate.repository_verified_at, None);
+
+    let response = user.put::<()>(URL, "").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let verified_at = response.json()["repository_verified_at"].clone();
+    assert!(verified_at.is_string());
+
+    let krate = anon.show_crate("foo_repo").await.krate;
+    assert_some!(krate.repository_verified_at);
+
+    let response = user.get::<()>("/api/v1/crates/foo_repo/audit").await;
+    let entries = response.json()["entries"].as_array().unwrap().clone();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "repository_verify");
+
+    // Changing the repository removes the verification
+    diesel::update(crates::table)
+        .filter(crates::name.eq("foo_repo"))
+        .set(crates::repository.eq("https://github.com/test-org/other"))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let krate = anon.show_crate("foo_repo").await.krate;
+    assert_eq!(krate.repository_verified_at, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_repository_without_push_access() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_repo", user.as_model().id)
+        .repository("https://github.com/test-org/read-only")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.put::<()>(URL, "").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"you need push access to the GitHub repository test-org/read-only"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_unknown_repository() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_repo", user.as_model().id)
+        .repository("https://github.com/test-org/unknown")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.put::<()>(URL, "").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"could not find the GitHub repository test-org/unknown"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_unsupported_repository() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_repo", user.as_model().id)
+        .repository("https://gitlab.com/test-org/writable")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.put::<()>(URL, "").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only repositories on GitHub can be verified"}]}"#);
+
+    CrateBuilder::new("foo_no_repo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo_no_repo/repository_verification";
+    let response = user.put::<()>(url, "").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the crate has no repository"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_repository_requires_ownership() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo_repo", user.as_model().id)
+        .repository("https://github.com/test-org/writable")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = other.put::<()>(URL, "").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only user owners of this crate can verify its repository"}]}"#);
+
+    let response = anon.put::<()>(URL, "").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+}
@@ -0,0 +1,78 @@
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::{EncodableCrateScore, EncodableCrateScoreSignals};
+use crate::worker::jobs::UpdateCrateScores;
+use chrono::{Duration, Utc};
+use crates_io_worker::BackgroundJob;
+use insta::assert_snapshot;
+
+#[derive(Deserialize)]
+struct ScoreResponse {
+    score: EncodableCrateScore,
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_scores_are_calculated_by_background_job() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    CrateBuilder::new("foo", user_id)
+        .documentation("https://docs.rs/foo")
+        .repository("https://github.com/rust-lang/foo")
+        .version(VersionBuilder::new("1.0.0").yanked(true))
+        .version("1.1.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let one_year_ago = (Utc::now() - Duration::days(365)).naive_utc();
+    CrateBuilder::new("bar", user_id)
+        .readme("# bar")
+        .version(VersionBuilder::new("1.0.0").created_at(one_year_ago))
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo/score").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the score of crate `foo` has not been calculated yet"}]}"#);
+
+    UpdateCrateScores.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    let json: ScoreResponse = anon.get("/api/v1/crates/foo/score").await.good();
+    assert_eq!(json.score.score, 0.75);
+    assert_eq!(
+        json.score.signals,
+        EncodableCrateScoreSignals {
+            has_documentation: true,
+            has_repository: true,
+            has_readme: false,
+            recent_release: true,
+            maintained: true,
+            yanked_ratio: 0.5,
+        }
+    );
+
+    let json: ScoreResponse = anon.get("/api/v1/crates/bar/score").await.good();
+    assert_eq!(json.score.score, 0.5);
+    assert_eq!(
+        json.score.signals,
+        EncodableCrateScoreSignals {
+            has_documentation: false,
+            has_repository: false,
+            has_readme: true,
+            recent_release: false,
+            maintained: true,
+            yanked_ratio: 0.0,
+        }
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_crate() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo/score").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` does not exist"}]}"#);
+}
@@ -0,0 +1,99 @@
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn semver_check() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_semver", user.as_model().id)
+        .version("1.2.0")
+        .version(VersionBuilder::new("1.2.1").yanked(true))
+        .version("1.3.0")
+        .version("2.0.0-rc.1")
+        .version("2.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo_semver/semver_check?from=1.2.0&to=2.0.0";
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".from.created_at" => "[datetime]",
+        ".to.created_at" => "[datetime]",
+        ".releases[].created_at" => "[datetime]",
+    }, @r#"
+    {
+      "breaking": true,
+      "downgrade": false,
+      "from": {
+        "created_at": "[datetime]",
+        "num": "1.2.0",
+        "yanked": false
+      },
+      "releases": [
+        {
+          "created_at": "[datetime]",
+          "num": "1.2.1",
+          "yanked": true
+        },
+        {
+          "created_at": "[datetime]",
+          "num": "1.3.0",
+          "yanked": false
+        },
+        {
+          "created_at": "[datetime]",
+          "num": "2.0.0-rc.1",
+          "yanked": false
+        }
+      ],
+      "to": {
+        "created_at": "[datetime]",
+        "num": "2.0.0",
+        "yanked": false
+      },
+      "warnings": []
+    }
+    "#);
+
+    let url = "/api/v1/crates/foo_semver/semver_check?from=1.3.0&to=2.0.0-rc.1";
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["warnings"], @r#"
+    [
+      {
+        "detail": "`2.0.0-rc.1` is a pre-release, which cargo only selects if the version requirement also contains a pre-release, e.g. `\"2.0.0-rc.1\"`",
+        "kind": "to_is_prerelease"
+      }
+    ]
+    "#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn semver_check_errors() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_semver", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo_semver/semver_check?from=latest&to=1.0.0";
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`from` is not a valid semver version: unexpected character 'l' while parsing major version number"}]}"#);
+
+    let url = "/api/v1/crates/foo_semver/semver_check?from=1.0.0&to=2.0.0";
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo_semver` does not have a version `2.0.0`"}]}"#);
+
+    let url = "/api/v1/crates/unknown/semver_check?from=1.0.0&to=2.0.0";
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `unknown` does not exist"}]}"#);
+}
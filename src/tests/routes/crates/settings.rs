@@ -0,0 +1,102 @@
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_settings() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo_settings", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo_settings/settings";
+    let response = user.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"settings":{"digest_emails":true,"version_diffs":true,"internal":false}}"#);
+
+    let body = json!({ "digest_emails": false, "internal": true });
+    let response = user.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"settings":{"digest_emails":false,"version_diffs":true,"internal":true}}"#);
+
+    // Settings that are missing from the body are not changed
+    let body = json!({ "version_diffs": false });
+    let response = user.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"settings":{"digest_emails":false,"version_diffs":false,"internal":true}}"#);
+
+    let response = user.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"settings":{"digest_emails":false,"version_diffs":false,"internal":true}}"#);
+
+    let response = user.get::<()>("/api/v1/crates/foo_settings/audit").await;
+    let actions = response.json()["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(actions, ["crate_settings_update", "crate_settings_update"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crate_settings_requires_ownership() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo_settings", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo_settings/settings";
+    let response = other.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of this crate can see its settings"}]}"#);
+
+    let body = json!({ "internal": true });
+    let response = other.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only user owners of this crate can change its settings"}]}"#);
+
+    let response = anon.put::<()>(url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn internal_crates_are_excluded_from_search() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    CrateBuilder::new("foo_public", user_id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("foo_internal", user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "internal": true });
+    let url = "/api/v1/crates/foo_internal/settings";
+    user.put::<()>(url, body.to_string()).await.good();
+
+    let names = |json: crate::tests::CrateList| {
+        let mut names = json.crates.into_iter().map(|c| c.name).collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    let json = anon.search("q=foo").await;
+    assert_eq!(names(json), ["foo_public"]);
+
+    let json = anon.search("q=foo&include_internal=yes").await;
+    assert_eq!(names(json), ["foo_internal", "foo_public"]);
+
+    let json = anon.search(&format!("user_id={user_id}")).await;
+    assert_eq!(names(json), ["foo_internal", "foo_public"]);
+}
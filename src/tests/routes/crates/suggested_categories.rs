@@ -0,0 +1,131 @@
+use crate::tests::builders::{CrateBuilder, PublishBuilder};
+use crate::tests::new_category;
+use crate::tests::util::{RequestHelper, TestApp};
+use crates_io_database::schema::{categories, category_suggestions};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::assert_snapshot;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suggest_and_accept_categories() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    diesel::insert_into(categories::table)
+        .values(&vec![
+            new_category(
+                "Parser implementations",
+                "parser-implementations",
+                "Parsers implemented for particular formats or languages.",
+            ),
+            new_category(
+                "Encoding",
+                "encoding",
+                "Encoding and/or decoding data from one data format to another.",
+            ),
+            new_category("Games", "games", "Applications for fun."),
+        ])
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .description("A TOML parser and encoder")
+        .keyword("parser")
+        .keyword("encoding");
+    token.publish_crate(crate_to_publish).await.good();
+
+    let url = "/api/v1/crates/foo/suggested_categories";
+    let response = user.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    let suggestions = json["suggested_categories"].as_array().unwrap();
+    assert_eq!(suggestions.len(), 2);
+    assert_eq!(suggestions[0]["slug"], "encoding");
+    assert_eq!(suggestions[0]["score"], 6.0);
+    assert_eq!(suggestions[1]["slug"], "parser-implementations");
+
+    let body = json!({ "categories": ["games"] });
+    let response = user
+        .post::<()>(&format!("{url}/accept"), body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`games` is not a suggested category of this crate"}]}"#);
+
+    let body = json!({ "categories": ["encoding"] });
+    let response = user
+        .post::<()>(&format!("{url}/accept"), body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ok":true}"#);
+
+    let json = user.show_crate("foo").await;
+    assert_eq!(json.krate.categories, Some(vec!["encoding".to_string()]));
+
+    let response = user.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(
+        json["suggested_categories"][0]["slug"],
+        "parser-implementations"
+    );
+    assert_eq!(json["suggested_categories"][1], serde_json::Value::Null);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_suggestions_for_crates_with_categories() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    diesel::insert_into(categories::table)
+        .values(new_category("Encoding", "encoding", "Encoding crates"))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .keyword("encoding")
+        .category("encoding");
+    token.publish_crate(crate_to_publish).await.good();
+
+    let count: i64 = category_suggestions::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    let response = user
+        .get::<()>("/api/v1/crates/foo/suggested_categories")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"suggested_categories":[]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suggested_categories_require_ownership() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo/suggested_categories";
+    let response = other.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of this crate can manage its categories"}]}"#);
+
+    let body = json!({ "categories": ["encoding"] });
+    let response = other
+        .post::<()>(&format!("{url}/accept"), body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+}
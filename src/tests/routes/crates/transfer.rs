@@ -0,0 +1,222 @@
+use crate::schema::crate_transfers;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{MockCookieUser, RequestHelper, Response, TestApp};
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+async fn request_transfer(user: &impl RequestHelper, krate: &str, owner: &str) -> Response<()> {
+    let url = format!("/api/v1/crates/{krate}/transfer");
+    let body = json!({ "transfer": { "owner": owner } });
+    user.put(&url, body.to_string()).await
+}
+
+async fn accept_transfer(user: &MockCookieUser, krate: &str) -> Response<()> {
+    let url = format!("/api/v1/crates/{krate}/transfer/accept");
+    user.put(&url, "").await
+}
+
+async fn owner_logins(user: &impl RequestHelper, krate: &str) -> Vec<String> {
+    let url = format!("/api/v1/crates/{krate}/owners");
+    let response = user.get::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response.json()["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|owner| owner["login"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfer_crate() {
+    let (app, anon, owner, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let co_owner = app.db_new_user("co-owner").await;
+    let recipient = app.db_new_user("recipient").await;
+
+    let krate = CrateBuilder::new("foo_transfer", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = token.add_named_owner("foo_transfer", "co-owner").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let url = format!("/api/v1/me/crate_owner_invitations/{}", krate.id);
+    let body = json!({ "crate_owner_invite": { "crate_id": krate.id, "accepted": true } });
+    let response = co_owner.put::<()>(&url, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request_transfer(&token, "foo_transfer", "recipient").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".transfer.id" => "[id]",
+        ".transfer.*.id" => "[id]",
+        ".transfer.created_at" => "[datetime]",
+        ".transfer.expires_at" => "[datetime]",
+    });
+
+    // The transfer is visible to the recipient, but not to other users
+    let response = recipient
+        .get::<()>("/api/v1/crates/foo_transfer/transfer")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = anon.get::<()>("/api/v1/crates/foo_transfer/transfer").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let other = app.db_new_user("other").await;
+    let response = other
+        .get::<()>("/api/v1/crates/foo_transfer/transfer")
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Only the recipient can accept the transfer
+    let response = accept_transfer(&other, "foo_transfer").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = accept_transfer(&recipient, "foo_transfer").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json()["transfer"]["recipient"]["login"],
+        "recipient"
+    );
+
+    assert_eq!(owner_logins(&anon, "foo_transfer").await, vec!["recipient"]);
+
+    // There is no pending transfer anymore
+    let response = recipient
+        .get::<()>("/api/v1/crates/foo_transfer/transfer")
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // The previous owners no longer have permission to modify owners
+    let response = token.add_named_owner("foo_transfer", "other").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn request_transfer_permissions() {
+    let (app, anon, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo_transfer", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = request_transfer(&anon, "foo_transfer", "other").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = request_transfer(&other, "foo_transfer", "other").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners with the `admin` role can transfer crates"}]}"#);
+
+    let response = request_transfer(&owner, "foo_transfer", "unknown").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"could not find user with login `unknown`"}]}"#);
+
+    let response = request_transfer(&owner, "foo_transfer", "foo").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`foo` is already the only owner of this crate"}]}"#);
+
+    let response = request_transfer(&owner, "unknown", "other").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancel_and_decline_transfer() {
+    let (app, _, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let recipient = app.db_new_user("recipient").await;
+    let other = app.db_new_user("other").await;
+    CrateBuilder::new("foo_transfer", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = request_transfer(&owner, "foo_transfer", "recipient").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Unrelated users can not cancel the transfer
+    let response = other
+        .delete::<()>("/api/v1/crates/foo_transfer/transfer")
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Owners can cancel the transfer
+    let response = owner
+        .delete::<()>("/api/v1/crates/foo_transfer/transfer")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = accept_transfer(&recipient, "foo_transfer").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Recipients can decline the transfer
+    let response = request_transfer(&owner, "foo_transfer", "recipient").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = recipient
+        .delete::<()>("/api/v1/crates/foo_transfer/transfer")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = accept_transfer(&recipient, "foo_transfer").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert_eq!(owner_logins(&owner, "foo_transfer").await, vec!["foo"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn new_request_replaces_pending_transfer() {
+    let (app, _, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let first = app.db_new_user("first").await;
+    let second = app.db_new_user("second").await;
+    CrateBuilder::new("foo_transfer", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = request_transfer(&owner, "foo_transfer", "first").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = request_transfer(&owner, "foo_transfer", "second").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = accept_transfer(&first, "foo_transfer").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = accept_transfer(&second, "foo_transfer").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(owner_logins(&owner, "foo_transfer").await, vec!["second"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn accept_expired_transfer() {
+    let (app, _, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let recipient = app.db_new_user("recipient").await;
+    CrateBuilder::new("foo_transfer", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = request_transfer(&owner, "foo_transfer", "recipient").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let expiration = app.as_inner().config.ownership_invitations_expiration_days as i64;
+    diesel::update(crate_transfers::table)
+        .set(crate_transfers::created_at.eq(Utc::now() - TimeDelta::days(expiration)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let response = accept_transfer(&recipient, "foo_transfer").await;
+    assert_eq!(response.status(), StatusCode::GONE);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The transfer of the foo_transfer crate expired. Please reach out to an owner of the crate to request a new transfer."}]}"#);
+
+    assert_eq!(owner_logins(&owner, "foo_transfer").await, vec!["foo"]);
+}
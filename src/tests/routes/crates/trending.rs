@@ -0,0 +1,106 @@
+use crate::schema::{version_downloads, versions};
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::EncodableTrendingCrate;
+use crate::worker::jobs::UpdateTrendingCrates;
+use chrono::{Days, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use insta::assert_snapshot;
+
+#[derive(Deserialize)]
+struct TrendingResponse {
+    crates: Vec<EncodableTrendingCrate>,
+}
+
+async fn add_downloads(conn: &mut AsyncPgConnection, crate_id: i32, days_ago: u64, downloads: i32) {
+    let version_id: i32 = versions::table
+        .filter(versions::crate_id.eq(crate_id))
+        .select(versions::id)
+        .first(conn)
+        .await
+        .unwrap();
+
+    let date = Utc::now().date_naive() - Days::new(days_ago);
+    diesel::insert_into(version_downloads::table)
+        .values((
+            version_downloads::version_id.eq(version_id),
+            version_downloads::date.eq(date),
+            version_downloads::downloads.eq(downloads),
+        ))
+        .execute(conn)
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn trending_crates_are_ranked_by_background_job() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let foo = CrateBuilder::new("foo", user_id)
+        .description("The foo crate")
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+    add_downloads(&mut conn, foo.id, 8, 1000).await;
+    add_downloads(&mut conn, foo.id, 1, 2000).await;
+
+    let bar = CrateBuilder::new("bar", user_id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+    add_downloads(&mut conn, bar.id, 8, 1000).await;
+    add_downloads(&mut conn, bar.id, 1, 3000).await;
+
+    let baz = CrateBuilder::new("baz", user_id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+    add_downloads(&mut conn, baz.id, 8, 10).await;
+    add_downloads(&mut conn, baz.id, 1, 5000).await;
+
+    let json: TrendingResponse = anon.get("/api/v1/crates/trending").await.good();
+    assert_eq!(json.crates, vec![]);
+
+    UpdateTrendingCrates.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    let json: TrendingResponse = anon.get("/api/v1/crates/trending?window=7d").await.good();
+    assert_eq!(
+        json.crates,
+        vec![
+            EncodableTrendingCrate {
+                name: "bar".into(),
+                description: None,
+                default_version: Some("1.0.0".into()),
+                downloads: 3000,
+                previous_downloads: 1000,
+                growth: 2.0,
+            },
+            EncodableTrendingCrate {
+                name: "foo".into(),
+                description: Some("The foo crate".into()),
+                default_version: Some("1.0.0".into()),
+                downloads: 2000,
+                previous_downloads: 1000,
+                growth: 1.0,
+            },
+        ]
+    );
+
+    // The previous downloads are below the baseline of the 30 day window.
+    let json: TrendingResponse = anon.get("/api/v1/crates/trending?window=30d").await.good();
+    assert_eq!(json.crates, vec![]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_window() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/crates/trending?window=1d").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid window `1d`, expected one of `7d`, `30d`"}]}"#);
+}
@@ -0,0 +1,82 @@
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dependency_graph() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let baz = CrateBuilder::new("baz", user.id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let bar = CrateBuilder::new("bar", user.id)
+        .version("1.0.0")
+        .version(VersionBuilder::new("2.0.0").dependency(&baz, None))
+        .version(VersionBuilder::new("3.0.0").yanked(true))
+        .expect_build(&mut conn)
+        .await;
+
+    let yanked = CrateBuilder::new("yanked", user.id)
+        .version(VersionBuilder::new("1.0.0").yanked(true))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("foo", user.id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .dependency(&bar, None)
+                .dependency(&baz, Some("cfg(unix)"))
+                .dependency(&yanked, None),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon
+        .get::<()>("/api/v1/crates/foo/1.0.0/dependency_graph")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json());
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/foo/1.0.0/dependency_graph", "depth=1")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 3);
+    assert_eq!(json["edges"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dependency_graph_errors() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo", user.id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/foo/1.0.0/dependency_graph", "depth=0")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`depth` must be between 1 and 10"}]}"#);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/foo/1.0.0/dependency_graph", "depth=11")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`depth` must be between 1 and 10"}]}"#);
+
+    let response = anon
+        .get::<()>("/api/v1/crates/foo/2.0.0/dependency_graph")
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` does not have a version `2.0.0`"}]}"#);
+}
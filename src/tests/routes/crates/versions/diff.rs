@@ -0,0 +1,53 @@
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn diff() {
+    let (_app, anon, _cookie, token) = TestApp::full().with_token().await;
+
+    let pb = PublishBuilder::new("foo", "1.0.0")
+        .add_file("foo-1.0.0/README.md", "# foo")
+        .add_file("foo-1.0.0/src/lib.rs", "pub fn foo() {}");
+    token.publish_crate(pb).await.good();
+
+    let pb = PublishBuilder::new("foo", "1.1.0")
+        .add_file("foo-1.1.0/build.rs", "fn main() {}")
+        .add_file("foo-1.1.0/src/lib.rs", "pub fn foo() {}\npub fn bar() {}");
+    token.publish_crate(pb).await.good();
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.1.0/diff").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json());
+
+    // The first version of a crate has nothing to compare to
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/diff").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Not Found"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn diff_to_lower_version() {
+    let (_app, anon, _cookie, token) = TestApp::full().with_token().await;
+
+    for version in ["1.0.0", "2.0.0", "1.1.0"] {
+        let pb = PublishBuilder::new("foo", version);
+        token.publish_crate(pb).await.good();
+    }
+
+    // Backported releases are compared to the highest lower version, not to
+    // the most recently published one
+    let response = anon.get::<()>("/api/v1/crates/foo/1.1.0/diff").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["diff"]["previous_version"], "1.0.0");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn diff_not_found() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/diff").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` does not exist"}]}"#);
+}
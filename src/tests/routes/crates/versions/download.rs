@@ -1,5 +1,12 @@
+use crate::schema::crates;
+use crate::storage::StorageConfig;
 use crate::tests::builders::{CrateBuilder, VersionBuilder};
 use crate::tests::util::{RequestHelper, TestApp};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::{header, StatusCode};
+use insta::assert_snapshot;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_redirects() {
@@ -51,3 +58,61 @@ async fn download_with_build_metadata() {
         .await
         .assert_redirect_ends_with("/readmes/foo/foo-1.0.0%2Bbar.html");
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn download_quarantined_crate() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let another_user = app.db_new_user("bar").await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    diesel::update(crates::table)
+        .set(crates::quarantined_at.eq(Utc::now()))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let url = "/api/v1/crates/foo/1.0.0/download";
+
+    // Quarantined crates can only be downloaded by their owners.
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` does not exist"}]}"#);
+
+    let response = another_user.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    user.get::<()>(url)
+        .await
+        .assert_redirect_ends_with("/crates/foo/foo-1.0.0.crate");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn download_failover_to_standby() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| {
+            let mut standby = StorageConfig::in_memory();
+            standby.cdn_prefix = Some("standby.crates.io".to_string());
+            config.standby_storage = Some(standby);
+            config.downloads_failover = true;
+        })
+        .with_user()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/download").await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "https://standby.crates.io/crates/foo/foo-1.0.0.crate"
+    );
+}
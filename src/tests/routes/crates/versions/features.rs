@@ -0,0 +1,43 @@
+use crate::tests::builders::{CrateBuilder, DependencyBuilder, PublishBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn features() {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    for name in ["serde", "serde_json", "log", "tokio", "rand"] {
+        CrateBuilder::new(name, user.id)
+            .version("1.0.0")
+            .expect_build(&mut conn)
+            .await;
+    }
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .dependency(DependencyBuilder::new("serde").optional())
+        .dependency(DependencyBuilder::new("serde_json").optional())
+        .dependency(DependencyBuilder::new("log").rename("logging").optional())
+        .dependency(DependencyBuilder::new("tokio").optional())
+        .dependency(DependencyBuilder::new("rand"))
+        .feature("default", &["std", "tokio?/rt"])
+        .feature("std", &["serde", "rand/std"])
+        .feature("json", &["dep:serde_json", "serde/derive"])
+        .feature("full", &["json", "tokio/full"]);
+    token.publish_crate(crate_to_publish).await.good();
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/features").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn features_not_found() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/features").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` does not exist"}]}"#);
+}
@@ -0,0 +1,88 @@
+use crate::schema::version_files;
+use crate::tests::builders::{CrateBuilder, PublishBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_files() {
+    let (app, anon, _cookie, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let pb = PublishBuilder::new("foo", "1.0.0")
+        .add_file("foo-1.0.0/README.md", "# foo")
+        .add_file("foo-1.0.0/src/lib.rs", "pub fn foo() {}")
+        .add_file("foo-1.0.0/assets/logo.png", &[0x89, 0x50, 0x4e, 0x47][..]);
+    token.publish_crate(pb).await.good();
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/files").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json());
+
+    // Versions without a saved listing fall back to reading the `.crate` file
+    diesel::delete(version_files::table)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let fallback = anon.get::<()>("/api/v1/crates/foo/1.0.0/files").await;
+    assert_eq!(fallback.status(), StatusCode::OK);
+    assert_eq!(fallback.json(), response.json());
+
+    let count: i64 = version_files::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_files_without_crate_file() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/files").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Not Found"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_file() {
+    let (_app, anon, _cookie, token) = TestApp::full().with_token().await;
+
+    let pb = PublishBuilder::new("foo", "1.0.0")
+        .add_file("foo-1.0.0/src/lib.rs", "pub fn foo() {}")
+        .add_file("foo-1.0.0/assets/logo.png", &[0x89, 0x50, 0x4e, 0xff][..]);
+    token.publish_crate(pb).await.good();
+
+    let response = anon
+        .get::<()>("/api/v1/crates/foo/1.0.0/files/src/lib.rs")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/plain; charset=utf-8"
+    );
+    assert_eq!(response.headers()["x-syntax"], "rust");
+    assert_snapshot!(response.text(), @"pub fn foo() {}");
+
+    let response = anon
+        .get::<()>("/api/v1/crates/foo/1.0.0/files/assets/logo.png")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"file is not a text file"}]}"#);
+
+    let response = anon
+        .get::<()>("/api/v1/crates/foo/1.0.0/files/src/main.rs")
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Not Found"}]}"#);
+}
@@ -393,6 +393,139 @@ async fn test_seek_based_pagination_date_sorting() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_seek_based_pagination_downloads_sorting() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_versions", user.id)
+        .version("0.5.0")
+        .version("0.5.1")
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    for (num, downloads) in [("0.5.0", 20), ("0.5.1", 10), ("1.0.0", 30)] {
+        update(versions::table)
+            .filter(versions::num.eq(num))
+            .set(versions::downloads.eq(downloads))
+            .execute(&mut conn)
+            .await?;
+    }
+
+    let url = "/api/v1/crates/foo_versions/versions";
+    let expects = ["1.0.0", "0.5.0", "0.5.1"];
+
+    let json: VersionList = anon
+        .get_with_query(url, "per_page=10&sort=downloads")
+        .await
+        .good();
+    assert_eq!(nums(&json.versions), expects);
+    assert_eq!(json.meta.total as usize, expects.len());
+
+    let json: VersionList = anon
+        .get_with_query(url, "per_page=1&sort=downloads")
+        .await
+        .good();
+    assert_eq!(nums(&json.versions), expects[0..1]);
+
+    let seek = json
+        .meta
+        .next_page
+        .map(|s| s.split_once("seek=").unwrap().1.to_owned())
+        .map(|p| p.split_once('&').map(|t| t.0.to_owned()).unwrap_or(p))
+        .unwrap();
+
+    let json: VersionList = anon
+        .get_with_query(url, &format!("per_page=5&sort=downloads&seek={seek}"))
+        .await
+        .good();
+    assert_eq!(nums(&json.versions), expects[1..]);
+    assert!(json.meta.next_page.is_none());
+
+    // Without pagination, all versions are returned in the same order
+    let json: VersionList = anon.get_with_query(url, "sort=downloads").await.good();
+    assert_eq!(nums(&json.versions), expects);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_include_yanked() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_versions", user.id)
+        .version("0.5.0")
+        .version(VersionBuilder::new("0.5.1").yanked(true))
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo_versions/versions";
+    for sort in ["semver", "date", "downloads"] {
+        let json: VersionList = anon
+            .get_with_query(url, &format!("sort={sort}&include_yanked=false"))
+            .await
+            .good();
+        let mut nums = nums(&json.versions);
+        nums.sort();
+        assert_eq!(nums, ["0.5.0", "1.0.0"]);
+        assert_eq!(json.meta.total, 2);
+
+        let (resp, calls) =
+            page_with_seek(&anon, &format!("{url}?sort={sort}&include_yanked=false")).await;
+        assert_eq!(calls, 3);
+        assert!(resp[..2].iter().all(|json| json.meta.total == 2));
+
+        let json: VersionList = anon
+            .get_with_query(url, &format!("sort={sort}&include_yanked=true"))
+            .await
+            .good();
+        assert_eq!(json.versions.len(), 3);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_semver_sorting_without_sort_key() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_versions", user.id)
+        .version("1.0.0")
+        .version("0.9.0")
+        .version("2.0.0")
+        .version("1.1.0")
+        .expect_build(&mut conn)
+        .await;
+
+    // Make versions 0.9.0 and 2.0.0 mimic versions that were published before we started
+    // saving the sort key, and were not backfilled yet
+    update(versions::table)
+        .filter(versions::num.eq_any(["0.9.0", "2.0.0"]))
+        .set(versions::semver_ord.eq(None::<String>))
+        .execute(&mut conn)
+        .await?;
+
+    let url = "/api/v1/crates/foo_versions/versions";
+    let expects = ["1.1.0", "1.0.0", "2.0.0", "0.9.0"];
+    let json: VersionList = anon.get_with_query(url, "sort=semver").await.good();
+    assert_eq!(nums(&json.versions), expects);
+
+    let (resp, calls) = page_with_seek(&anon, &format!("{url}?sort=semver")).await;
+    for (json, expect) in resp.iter().zip(expects) {
+        assert_eq!(json.versions[0].num, expect);
+    }
+    assert_eq!(calls as usize, expects.len() + 1);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn invalid_seek_parameter() {
     let (app, anon, user) = TestApp::init().with_user().await;
@@ -418,6 +551,13 @@ async fn invalid_seek_parameter() {
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid seek parameter"}]}"#);
 
+    // Sort by downloads
+    let response = anon
+        .get_with_query::<()>(url, "per_page=1&sort=downloads&seek=broken")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid seek parameter"}]}"#);
+
     // broken seek but without per_page parameter should be ok
     // since it's not consider as seek-based pagination
     let response = anon.get_with_query::<()>(url, "seek=broken").await;
@@ -472,3 +612,45 @@ async fn page_with_seek<U: RequestHelper>(anon: &U, url: &str) -> (Vec<VersionLi
     }
     (results, calls)
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_v2() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_versions", user.id)
+        .version("0.5.1")
+        .version("1.0.0")
+        .version("0.5.0")
+        .expect_build(&mut conn)
+        .await;
+
+    // The list is paginated even without `per_page`
+    let url = "/api/v2/crates/foo_versions/versions";
+    let json = anon.get::<()>(url).await.json();
+    assert_eq!(json["meta"], json!({ "total": 3, "next_page": null }));
+    assert!(json.get("included").is_none());
+    let nums = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["num"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(nums, ["1.0.0", "0.5.1", "0.5.0"]);
+
+    let url = "/api/v2/crates/foo_versions/versions?per_page=2&include=release_tracks";
+    let json = anon.get::<()>(url).await.json();
+    assert_eq!(json["data"].as_array().unwrap().len(), 2);
+    assert_eq!(json["meta"]["total"], 3);
+    assert_some!(json["meta"]["next_page"].as_str());
+    assert_eq!(
+        json["included"]["release_tracks"],
+        json!({ "1": { "highest": "1.0.0" }, "0.5": { "highest": "0.5.1" } })
+    );
+
+    let response = anon.get::<()>("/api/v2/crates/unknown/versions").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
@@ -1,6 +1,10 @@
 mod authors;
 pub mod dependencies;
+mod dependency_graph;
+mod diff;
 pub mod download;
+mod features;
+mod files;
 mod list;
 mod read;
 pub mod yank_unyank;
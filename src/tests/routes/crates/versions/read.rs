@@ -63,3 +63,25 @@ async fn show_by_crate_name_and_semver_no_published_by() {
         ".version.updated_at" => "[datetime]",
     });
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn show_v2() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("foo_vers_show_v2", user.id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let json: Value = anon
+        .get("/api/v2/crates/foo_vers_show_v2/1.0.0")
+        .await
+        .good();
+    assert_eq!(json["data"]["crate_name"], "foo_vers_show_v2");
+    assert_eq!(json["data"]["num"], "1.0.0");
+    assert!(json["data"].get("crate").is_none());
+    assert!(json["data"]["created_at"].as_str().unwrap().ends_with('Z'));
+    assert!(json.get("included").is_none());
+}
@@ -0,0 +1,114 @@
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+const LOCKFILE: &str = r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["foo", "bar", "baz"]
+
+[[package]]
+name = "bar"
+version = "0.1.0"
+source = "sparse+https://index.crates.io/"
+checksum = "0000000000000000000000000000000000000000000000000000000000000000"
+
+[[package]]
+name = "baz"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "0000000000000000000000000000000000000000000000000000000000000000"
+
+[[package]]
+name = "foo"
+version = "1.0.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "0000000000000000000000000000000000000000000000000000000000000000"
+
+[[package]]
+name = "git-dep"
+version = "0.1.0"
+source = "git+https://github.com/rust-lang/git-dep#0000000000000000000000000000000000000000"
+"#;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dependency_report() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    CrateBuilder::new("foo", user_id)
+        .version("1.0.0")
+        .version(VersionBuilder::new("1.0.1").yanked(true))
+        .version("1.1.0")
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", user_id)
+        .version("0.1.0")
+        .version("0.2.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.post::<()>("/api/v1/dependency_report", LOCKFILE).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "meta": {
+        "skipped": 2,
+        "total": 3,
+        "with_issues": 3
+      },
+      "packages": [
+        {
+          "issues": [
+            "newer_major_version"
+          ],
+          "latest_version": "0.2.0",
+          "name": "bar",
+          "version": "0.1.0",
+          "yank_message": null
+        },
+        {
+          "issues": [
+            "not_found"
+          ],
+          "latest_version": null,
+          "name": "baz",
+          "version": "1.0.0",
+          "yank_message": null
+        },
+        {
+          "issues": [
+            "yanked"
+          ],
+          "latest_version": "1.1.0",
+          "name": "foo",
+          "version": "1.0.1",
+          "yank_message": null
+        }
+      ]
+    }
+    "#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_lockfile() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon
+        .post::<()>("/api/v1/dependency_report", "[[package]]\nname = 1")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(response
+        .text()
+        .starts_with(r#"{"errors":[{"detail":"invalid lockfile: "#));
+
+    let response = anon.post::<()>("/api/v1/dependency_report", "").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"packages":[],"meta":{"total":0,"skipped":0,"with_issues":0}}"#);
+}
@@ -0,0 +1,35 @@
+use crate::tests::util::{RequestHelper, TestApp};
+use http::{header, Method, StatusCode};
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn liveness() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/healthz").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"status":"ok"}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn readiness() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/readyz").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".checks.*.duration_ms" => "[duration]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn health_checks_do_not_require_user_agent() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    for path in ["/healthz", "/readyz"] {
+        let mut request = anon.request_builder(Method::GET, path);
+        request.headers_mut().remove(header::USER_AGENT);
+        let response = anon.run::<()>(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::EncodableAggregateDownload;
+use crate::worker::jobs::UpdateAggregateDownloads;
+use chrono::Utc;
+use crates_io_worker::BackgroundJob;
+
+#[derive(Deserialize)]
+struct DownloadsResponse {
+    downloads: Vec<EncodableAggregateDownload>,
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn downloads_are_aggregated_by_background_job() {
+    let url = "/api/v1/keywords/foo/downloads";
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    anon.get(url).await.assert_not_found();
+
+    CrateBuilder::new("foo", user_id)
+        .keyword("foo")
+        .version("1.0.0")
+        .recent_downloads(10)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", user_id)
+        .keyword("foo")
+        .keyword("bar")
+        .version("1.0.0")
+        .recent_downloads(5)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("baz", user_id)
+        .keyword("baz")
+        .version("1.0.0")
+        .recent_downloads(100)
+        .expect_build(&mut conn)
+        .await;
+
+    // The downloads are only available once they have been aggregated.
+    let json: DownloadsResponse = anon.get(url).await.good();
+    assert_eq!(json.downloads, vec![]);
+
+    let today = Utc::now().date_naive();
+    UpdateAggregateDownloads::for_date(today)
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+
+    app.run_pending_background_jobs().await;
+
+    let json: DownloadsResponse = anon.get(url).await.good();
+    assert_eq!(
+        json.downloads,
+        vec![EncodableAggregateDownload {
+            date: today.to_string(),
+            downloads: 15,
+        }]
+    );
+}
@@ -1,6 +1,7 @@
 use crate::models::Keyword;
 use crate::tests::util::{RequestHelper, TestApp};
 use crate::views::EncodableKeyword;
+use http::header;
 
 #[derive(Deserialize)]
 struct KeywordList {
@@ -19,7 +20,14 @@ async fn index() {
     let (app, anon) = TestApp::init().empty().await;
     let mut conn = app.db_conn().await;
 
-    let json: KeywordList = anon.get(url).await.good();
+    let response = anon.get::<KeywordList>(url).await;
+    let cache_control = &response.headers()[header::CACHE_CONTROL];
+    assert_eq!(
+        cache_control,
+        "public, max-age=3600, stale-while-revalidate=86400"
+    );
+
+    let json = response.good();
     assert_eq!(json.keywords.len(), 0);
     assert_eq!(json.meta.total, 0);
 
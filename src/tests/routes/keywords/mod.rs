@@ -1,2 +1,3 @@
+mod downloads;
 mod list;
 mod read;
@@ -0,0 +1,48 @@
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn user_audit_log() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let body = json!({ "email": "second@example.com" });
+    let response = user.post::<()>("/api/v1/me/emails", body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let email_id = response.json()["email"]["id"].as_i64().unwrap();
+
+    let url = format!("/api/v1/me/emails/{email_id}");
+    let response = user.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json!({ "api_token": { "name": "audited" } });
+    let response = user.put::<()>("/api/v1/me/tokens", body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let token_id = response.json()["api_token"]["id"].as_i64().unwrap();
+
+    let url = format!("/api/v1/me/tokens/{token_id}");
+    let response = user.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user.get::<()>("/api/v1/me/audit").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".entries[].id" => "[id]",
+        ".entries[].created_at" => "[datetime]",
+        ".entries[].details.token_id" => "[id]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn user_audit_log_requires_cookie() {
+    let (_, anon, _, token) = TestApp::init().with_token().await;
+
+    let response = anon.get::<()>("/api/v1/me/audit").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+
+    let response = token.get::<()>("/api/v1/me/audit").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action can only be performed on the crates.io website"}]}"#);
+}
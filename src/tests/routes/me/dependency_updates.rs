@@ -0,0 +1,78 @@
+use crate::schema::dependencies;
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn anonymous_user_cannot_list_dependency_updates() {
+    let (_app, anon) = TestApp::init().empty().await;
+    anon.get::<()>("/api/v1/me/dependency_updates")
+        .await
+        .assert_forbidden();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dependency_updates() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let other = app.db_new_user("other").await;
+    let other_id = other.as_model().id;
+
+    let days_ago = |days| (Utc::now() - Duration::days(days)).naive_utc();
+
+    let serde = CrateBuilder::new("serde", other_id)
+        .version(VersionBuilder::new("1.0.0").created_at(days_ago(30)))
+        .version("1.1.0")
+        .version("2.0.0-beta.1")
+        .version("2.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("foo", user_id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .created_at(days_ago(10))
+                .dependency(&serde, None),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    diesel::update(dependencies::table)
+        .set(dependencies::req.eq("^1.0"))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    // Crates without dependency updates are not listed.
+    CrateBuilder::new("bar", user_id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.get::<()>("/api/v1/me/dependency_updates").await;
+    assert_eq!(
+        response.json(),
+        json!({
+            "crates": [{
+                "name": "foo",
+                "version": "1.0.0",
+                "dependencies": [{
+                    "name": "serde",
+                    "req": "^1.0",
+                    "kind": "normal",
+                    "compatible": "1.1.0",
+                    "incompatible": "2.0.0",
+                }],
+            }],
+        })
+    );
+
+    // The owner of `serde` doesn't own any crates with dependencies.
+    let response = other.get::<()>("/api/v1/me/dependency_updates").await;
+    assert_eq!(response.json(), json!({ "crates": [] }));
+}
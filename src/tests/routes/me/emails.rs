@@ -0,0 +1,207 @@
+use crate::models::NewEmail;
+use crate::schema::emails;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::EncodableEmail;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+#[derive(Deserialize)]
+struct EmailResponse {
+    email: EncodableEmail,
+}
+
+#[derive(Deserialize)]
+struct EmailsResponse {
+    emails: Vec<EncodableEmail>,
+}
+
+fn add_body(email: &str) -> String {
+    json!({ "email": email }).to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn emails_require_cookie_auth() {
+    let (_, anon, _, token) = TestApp::init().with_token().await;
+
+    let response = anon.get::<()>("/api/v1/me/emails").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = token.get::<()>("/api/v1/me/emails").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = add_body("foo@example.com");
+    let response = token.post::<()>("/api/v1/me/emails", body).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn add_email() {
+    let (app, _, user) = TestApp::init().with_user().await;
+
+    let body = add_body("second@example.com");
+    let response = user.post::<EmailResponse>("/api/v1/me/emails", body).await;
+    let email = response.good().email;
+    assert_eq!(email.email, "second@example.com");
+    assert!(!email.verified);
+    assert!(email.verification_sent);
+    assert!(!email.primary);
+
+    assert_snapshot!(app.emails_snapshot().await);
+
+    let response = user.get::<EmailsResponse>("/api/v1/me/emails").await;
+    let emails = response.good().emails;
+    assert_json_snapshot!(emails, {
+        "[].id" => "[id]",
+    });
+
+    // The primary email address is unaffected
+    let json = user.show_me().await;
+    assert_eq!(json.user.email.unwrap(), "foo@example.com");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn add_email_validation() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user.post::<()>("/api/v1/me/emails", add_body("  ")).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"empty email rejected"}]}"#);
+
+    let body = add_body("not-an-email");
+    let response = user.post::<()>("/api/v1/me/emails", body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid email address"}]}"#);
+
+    let body = add_body("FOO@example.com");
+    let response = user.post::<()>("/api/v1/me/emails", body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"email address has already been added"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn first_email_becomes_primary() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    diesel::delete(emails::table)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let body = add_body("new@example.com");
+    let response = user.post::<EmailResponse>("/api/v1/me/emails", body).await;
+    let email = response.good().email;
+    assert!(email.primary);
+    assert!(!email.verified);
+
+    let json = user.show_me().await;
+    assert_eq!(json.user.email.unwrap(), "new@example.com");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delete_email() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let primary_id: i32 = emails::table
+        .filter(emails::user_id.eq(user_id))
+        .select(emails::id)
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+
+    let url = format!("/api/v1/me/emails/{primary_id}");
+    let response = user.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the primary email address can not be removed"}]}"#);
+
+    let body = add_body("second@example.com");
+    let response = user.post::<EmailResponse>("/api/v1/me/emails", body).await;
+    let second = response.good().email;
+
+    let url = format!("/api/v1/me/emails/{}", second.id);
+    let response = user.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = user.get::<EmailsResponse>("/api/v1/me/emails").await;
+    assert_eq!(response.good().emails.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cannot_delete_email_of_other_user() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let other = app.db_new_user("bar").await;
+
+    let new_email = NewEmail {
+        user_id: other.as_model().id,
+        email: "bar-secondary@example.com",
+        is_primary: false,
+    };
+
+    let id: i32 = diesel::insert_into(emails::table)
+        .values(&new_email)
+        .returning(emails::id)
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+
+    let url = format!("/api/v1/me/emails/{id}");
+    let response = user.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let url = format!("/api/v1/me/emails/{id}/primary");
+    let response = user.put::<()>(&url, "").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn set_primary_email() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let body = add_body("second@example.com");
+    let response = user.post::<EmailResponse>("/api/v1/me/emails", body).await;
+    let second = response.good().email;
+
+    let url = format!("/api/v1/me/emails/{}/primary", second.id);
+    let response = user.put::<()>(&url, "").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only verified email addresses can be used as the primary email address"}]}"#);
+
+    let token: String = emails::table
+        .find(second.id)
+        .select(emails::token)
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+
+    let response = user
+        .put::<()>(&format!("/api/v1/confirm/{token}"), "")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user.put::<()>(&url, "").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ok":true}"#);
+
+    let json = user.show_me().await;
+    assert_eq!(json.user.email.unwrap(), "second@example.com");
+    assert!(json.user.email_verified);
+
+    let response = user.get::<EmailsResponse>("/api/v1/me/emails").await;
+    let emails = response.good().emails;
+    let primary = emails
+        .iter()
+        .filter(|email| email.primary)
+        .collect::<Vec<_>>();
+    assert_eq!(primary.len(), 1);
+    assert_eq!(primary[0].id, second.id);
+}
@@ -0,0 +1,96 @@
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::EncodableAccountExport;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::Value;
+
+#[derive(Deserialize)]
+struct ExportResponse {
+    export: EncodableAccountExport,
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_requires_cookie_auth() {
+    let (_, anon, _, token) = TestApp::full().with_token().await;
+
+    let response = anon.post::<()>("/api/v1/me/export", "").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = token.post::<()>("/api/v1/me/export", "").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = anon.get::<()>("/api/v1/me/export").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = token.get::<()>("/api/v1/me/export").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_happy_path() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    // Looking up the export does not create one
+    let response = user.get::<()>("/api/v1/me/export").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = user.post::<ExportResponse>("/api/v1/me/export", "").await;
+    let export = response.good().export;
+    assert_eq!(export.state, "pending");
+    assert_eq!(export.url, None);
+
+    // Requesting the export again does not enqueue a second one
+    let response = user.post::<ExportResponse>("/api/v1/me/export", "").await;
+    assert_eq!(response.good().export.id, export.id);
+
+    let response = user.get::<ExportResponse>("/api/v1/me/export").await;
+    assert_eq!(response.good().export.state, "pending");
+
+    app.run_pending_background_jobs().await;
+
+    let response = user.get::<ExportResponse>("/api/v1/me/export").await;
+    let ready = response.good().export;
+    assert_eq!(ready.id, export.id);
+    assert_eq!(ready.state, "ready");
+    assert!(ready.expires_at.is_some());
+
+    assert_snapshot!(app.emails_snapshot().await);
+
+    // The export is not stored in the public bucket that is served by the CDN
+    let path = format!("account-exports/{}/{}.json", user.as_model().id, export.id);
+    assert!(!app.stored_files().await.contains(&path));
+    assert!(app.stored_private_files().await.contains(&path));
+
+    // The download URL does not require authentication
+    let url = ready.url.unwrap();
+    let response = anon.get::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-disposition"],
+        "attachment; filename=\"crates-io-export.json\""
+    );
+
+    let json: Value = response.json();
+    assert_json_snapshot!(json, {
+        ".generated_at" => "[datetime]",
+        ".user.id" => "[id]",
+        ".user.github_id" => "[id]",
+        ".**.created_at" => "[datetime]",
+        ".**.owner_since" => "[datetime]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn download_with_invalid_token() {
+    let (_, anon) = TestApp::full().empty().await;
+
+    let response = anon.get::<()>("/api/v1/me/export/invalid").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,96 @@
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+const BULK_URL: &str = "/api/v1/me/following/bulk";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bulk_follow_and_list() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    for name in ["foo", "bar", "baz"] {
+        CrateBuilder::new(name, user_id)
+            .expect_build(&mut conn)
+            .await;
+    }
+
+    let body = json!({ "follow": ["foo", "bar", "baz", "missing"] });
+    let response = user.post::<()>(BULK_URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"followed":3,"not_found":["missing"],"unfollowed":0}"#);
+
+    // Following crates again is a no-op
+    let body = json!({ "follow": ["foo"], "unfollow": ["baz"] });
+    let response = user.post::<()>(BULK_URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"followed":0,"not_found":[],"unfollowed":1}"#);
+
+    let response = user.get::<()>("/api/v1/me/following").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".crates[].id" => "[id]",
+        ".crates[].updated_at" => "[datetime]",
+    });
+
+    let response = user
+        .get_with_query::<()>("/api/v1/me/following", "per_page=1&page=2")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["crates"][0]["name"], "foo");
+    assert_eq!(json["meta"]["total"], 2);
+    assert_eq!(json["meta"]["more"], false);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_sort_orders() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let response = user
+        .get_with_query::<()>("/api/v1/me/following", "sort=recent-updates")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user
+        .get_with_query::<()>("/api/v1/me/following", "sort=downloads")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid sort order: downloads"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bulk_rejects_conflicting_names() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let body = json!({ "follow": ["foo"], "unfollow": ["foo"] });
+    let response = user.post::<()>(BULK_URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"cannot follow and unfollow `foo` at the same time"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bulk_rejects_too_many_names() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let names = (0..=1000).map(|i| format!("foo{i}")).collect::<Vec<_>>();
+    let body = json!({ "follow": names });
+    let response = user.post::<()>(BULK_URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"cannot follow or unfollow more than 1000 crates at once"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requires_authentication() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/me/following").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "follow": ["foo"] });
+    let response = anon.post::<()>(BULK_URL, body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
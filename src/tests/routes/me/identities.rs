@@ -0,0 +1,74 @@
+use crate::models::NewLinkedIdentity;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::EncodableLinkedIdentity;
+use http::StatusCode;
+use insta::assert_json_snapshot;
+
+#[derive(Deserialize)]
+struct IdentitiesResponse {
+    identities: Vec<EncodableLinkedIdentity>,
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn identities_require_cookie_auth() {
+    let (_, anon, _, token) = TestApp::init().with_token().await;
+
+    let response = anon.get::<()>("/api/v1/me/identities").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = token.get::<()>("/api/v1/me/identities").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_and_unlink_identities() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let other = app.db_new_user("bar").await;
+
+    let identity = NewLinkedIdentity {
+        user_id: user.as_model().id,
+        provider: "gitlab",
+        account_id: "1234",
+        login: "foo-on-gitlab",
+        email: Some("foo@example.com"),
+    };
+    let identity = identity.insert(&mut conn).await.unwrap();
+
+    let other_identity = NewLinkedIdentity {
+        user_id: other.as_model().id,
+        provider: "gitlab",
+        account_id: "5678",
+        login: "bar-on-gitlab",
+        email: None,
+    };
+    let other_identity = other_identity.insert(&mut conn).await.unwrap();
+
+    let response = user
+        .get::<IdentitiesResponse>("/api/v1/me/identities")
+        .await;
+    let identities = response.good().identities;
+    assert_json_snapshot!(identities, {
+        "[].id" => "[id]",
+        "[].created_at" => "[datetime]",
+    });
+
+    // Identities of other users can not be unlinked
+    let url = format!("/api/v1/me/identities/{}", other_identity.id);
+    let response = user.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let url = format!("/api/v1/me/identities/{}", identity.id);
+    let response = user.delete::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user
+        .get::<IdentitiesResponse>("/api/v1/me/identities")
+        .await;
+    assert_eq!(response.good().identities.len(), 0);
+
+    let response = other
+        .get::<IdentitiesResponse>("/api/v1/me/identities")
+        .await;
+    assert_eq!(response.good().identities.len(), 1);
+}
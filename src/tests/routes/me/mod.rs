@@ -1,4 +1,11 @@
+mod audit;
+mod dependency_updates;
 mod email_notifications;
+mod emails;
+mod export;
+mod following;
 pub mod get;
+mod identities;
 pub mod tokens;
 mod updates;
+mod usage;
@@ -0,0 +1,34 @@
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn me_usage() {
+    let (app, _, user, token) = TestApp::init().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    user.get::<()>("/api/v1/me").await.good();
+    user.get::<()>("/api/v1/me/updates").await.good();
+    token.get::<()>("/api/v1/me/following").await.good();
+
+    // Requests that are rejected by the authentication are not counted
+    let response = token.get::<()>("/api/v1/me/tokens").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    app.as_inner().api_usage.persist(&mut conn).await.unwrap();
+
+    let response = user.get::<()>("/api/v1/me/usage").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".usage[].date" => "[date]",
+    });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn me_usage_requires_authentication() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/me/usage").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+}
@@ -11,13 +11,21 @@
 //! - testing output serialization of a route
 //! - testing query parameter combinations of a route
 
+pub mod admin;
+pub mod announcements;
 pub mod categories;
 pub mod category_slugs;
 pub mod crates;
+pub mod dependency_report;
+pub mod health;
 pub mod keywords;
 pub mod me;
 pub mod metrics;
+pub mod organizations;
 mod private;
+pub mod robots;
 pub mod session;
+pub mod snapshot;
+pub mod stats;
 pub mod summary;
 pub mod users;
@@ -0,0 +1,384 @@
+use crate::models::{Organization, OrganizationRole};
+use crate::schema::reserved_prefixes;
+use crate::tests::builders::{CrateBuilder, PublishBuilder};
+use crate::tests::util::{MockCookieUser, RequestHelper, TestApp};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+async fn create_organization(user: &MockCookieUser, login: &str) -> Organization {
+    let body = json!({ "organization": { "login": login, "name": "ACME Corp" } });
+    let response = user
+        .post::<()>("/api/v1/organizations", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut conn = user.app().db_conn().await;
+    Organization::find_by_login(&mut conn, login).await.unwrap()
+}
+
+async fn set_member_role(
+    user: &MockCookieUser,
+    organization: &str,
+    member: &str,
+    role: OrganizationRole,
+) -> crate::tests::util::Response<()> {
+    let url = format!("/api/v1/organizations/{organization}/members");
+    let body = json!({ "member": { "login": member, "role": role } });
+    user.put(&url, body.to_string()).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_organization_requires_cookie_auth() {
+    let (_, anon, _, token) = TestApp::init().with_token().await;
+    let body = json!({ "organization": { "login": "acme" } }).to_string();
+
+    let response = anon.post::<()>("/api/v1/organizations", body.clone()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = token.post::<()>("/api/v1/organizations", body).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_and_show_organization() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+
+    let body = json!({ "organization": { "login": "acme", "name": "ACME Corp" } });
+    let response = user
+        .post::<()>("/api/v1/organizations", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".organization.id" => "[id]",
+        ".organization.created_at" => "[datetime]",
+    });
+
+    let response = anon.get::<()>("/api/v1/organizations/ACME").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json()["organization"]["login"], "acme");
+
+    let response = anon.get::<()>("/api/v1/organizations/unknown").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // The creator becomes the first admin of the organization
+    let mut conn = app.db_conn().await;
+    let organization = Organization::find_by_login(&mut conn, "acme")
+        .await
+        .unwrap();
+    let role = organization
+        .role_of(&mut conn, user.as_model().id)
+        .await
+        .unwrap();
+    assert_eq!(role, Some(OrganizationRole::Admin));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_organization_validation() {
+    let (_, _, user) = TestApp::init().with_user().await;
+    create_organization(&user, "acme").await;
+
+    let body = json!({ "organization": { "login": "ACME" } }).to_string();
+    let response = user.post::<()>("/api/v1/organizations", body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"organization `ACME` already exists"}]}"#);
+
+    let body = json!({ "organization": { "login": "-acme" } }).to_string();
+    let response = user.post::<()>("/api/v1/organizations", body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"organization names must start with a letter or digit and may only contain letters, digits, `-` and `_`"}]}"#);
+
+    let body = json!({ "organization": { "login": "" } }).to_string();
+    let response = user.post::<()>("/api/v1/organizations", body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"organization names must be between 1 and 39 characters long"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manage_members() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let other = app.db_new_user("bar").await;
+    create_organization(&user, "acme").await;
+
+    // Only members can see the members of an organization
+    let response = anon.get::<()>("/api/v1/organizations/acme/members").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = other.get::<()>("/api/v1/organizations/acme/members").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = set_member_role(&user, "acme", "bar", OrganizationRole::Publisher).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = other.get::<()>("/api/v1/organizations/acme/members").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".members[].user.id" => "[id]",
+        ".members[].created_at" => "[datetime]",
+    });
+
+    // Only admins can manage members
+    let response = set_member_role(&other, "acme", "bar", OrganizationRole::Admin).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only admins can manage the members of an organization"}]}"#);
+
+    let response = set_member_role(&user, "acme", "unknown", OrganizationRole::Viewer).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // The last admin can not be demoted or removed
+    let response = set_member_role(&user, "acme", "foo", OrganizationRole::Viewer).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"an organization must have at least one admin"}]}"#);
+
+    let response = user
+        .delete::<()>("/api/v1/organizations/acme/members/foo")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Members can remove themselves
+    let response = other
+        .delete::<()>("/api/v1/organizations/acme/members/bar")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = other.get::<()>("/api/v1/organizations/acme/members").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn organization_as_crate_owner() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+    let publisher = app.db_new_user("bar").await;
+    let viewer = app.db_new_user("baz").await;
+
+    CrateBuilder::new("foo_org", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    create_organization(&publisher, "acme").await;
+
+    // Only admins of the organization can add it as a crate owner
+    let response = token.add_named_owner("foo_org", "org:acme").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = set_member_role(&publisher, "acme", "foo", OrganizationRole::Admin).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = set_member_role(&user, "acme", "bar", OrganizationRole::Publisher).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = set_member_role(&user, "acme", "baz", OrganizationRole::Viewer).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = token.add_named_owner("foo_org", "org:acme").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"msg":"organization org:acme has been added as an owner of crate foo_org","ok":true}"#);
+
+    let response = user.get::<()>("/api/v1/crates/foo_org/owners").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".users[].id" => "[id]",
+    });
+
+    // Publishers of the organization can publish new versions
+    let crate_to_publish = PublishBuilder::new("foo_org", "1.1.0");
+    let response = publisher
+        .db_new_token("publish")
+        .await
+        .publish_crate(crate_to_publish)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Viewers of the organization can not
+    let crate_to_publish = PublishBuilder::new("foo_org", "1.2.0");
+    let response = viewer
+        .db_new_token("publish")
+        .await
+        .publish_crate(crate_to_publish)
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn organization_tokens() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let organization = create_organization(&user, "acme").await;
+
+    CrateBuilder::new("foo_org", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("foo_personal", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let token = user.db_new_token("admin").await;
+    token.add_named_owner("foo_org", "org:acme").await.good();
+
+    let token = user
+        .db_new_organization_token("acme", organization.id)
+        .await;
+
+    let crate_to_publish = PublishBuilder::new("foo_org", "1.1.0");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Organization tokens can not be used for crates outside of the organization
+    let crate_to_publish = PublishBuilder::new("foo_personal", "1.1.0");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this token can only be used for crates owned by its organization"}]}"#);
+
+    let crate_to_publish = PublishBuilder::new("foo_new", "1.0.0");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn organization_tokens_require_membership() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let other = app.db_new_user("bar").await;
+    create_organization(&user, "acme").await;
+
+    let body = json!({ "api_token": { "name": "acme", "organization": "acme" } });
+    let response = other.put::<()>("/api/v1/me/tokens", body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only admins and publishers of organization `acme` can create tokens for it"}]}"#);
+
+    let response = user.put::<()>("/api/v1/me/tokens", body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn request_reserved_prefix(
+    user: &MockCookieUser,
+    organization: &str,
+    prefix: &str,
+) -> crate::tests::util::Response<()> {
+    let url = format!("/api/v1/organizations/{organization}/reserved_prefixes");
+    let body = json!({ "reserved_prefix": { "prefix": prefix } });
+    user.post(&url, body.to_string()).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn request_reserved_prefixes() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let other = app.db_new_user("bar").await;
+    create_organization(&user, "acme").await;
+    create_organization(&other, "other").await;
+
+    // Only admins of the organization can reserve prefixes
+    let response = request_reserved_prefix(&other, "acme", "acme-").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only admins can reserve prefixes for an organization"}]}"#);
+
+    let response = request_reserved_prefix(&user, "acme", "acme").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"reserved prefixes must be at least 3 characters long and end with `-` or `_`"}]}"#);
+
+    let response = request_reserved_prefix(&user, "acme", "ACME-SDK-*").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["reserved_prefix"]["prefix"], "acme-sdk-");
+    assert_eq!(json["reserved_prefix"]["organization"], "acme");
+    assert_eq!(
+        json["reserved_prefix"]["approved_at"],
+        serde_json::Value::Null
+    );
+
+    // Prefixes can't overlap with prefixes of other reservations
+    let response = request_reserved_prefix(&other, "other", "acme_").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"prefix `acme_` overlaps with the reserved prefix `acme-sdk-`"}]}"#);
+
+    // Pending reservations are only visible to members of the organization
+    let url = "/api/v1/organizations/acme/reserved_prefixes";
+    let response = user.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json()["reserved_prefixes"][0]["prefix"],
+        "acme-sdk-"
+    );
+
+    let response = other.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = anon.get::<()>("/api/v1/reserved_prefixes").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"reserved_prefixes":[]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reserved_prefixes_restrict_new_crates() {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+    let publisher = app.db_new_user("bar").await;
+    let outsider = app.db_new_user("baz").await;
+    let outsider_token = outsider.db_new_token("publish").await;
+
+    CrateBuilder::new("acme-existing", outsider.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    create_organization(&user, "acme").await;
+    let response = set_member_role(&user, "acme", "bar", OrganizationRole::Publisher).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request_reserved_prefix(&user, "acme", "acme-").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Pending reservations are not enforced
+    let crate_to_publish = PublishBuilder::new("acme-pending", "1.0.0");
+    outsider_token.publish_crate(crate_to_publish).await.good();
+
+    diesel::update(reserved_prefixes::table)
+        .set(reserved_prefixes::approved_at.eq(Utc::now()))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let crate_to_publish = PublishBuilder::new("acme_new", "1.0.0");
+    let response = outsider_token.publish_crate(crate_to_publish).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the crate name prefix `acme-` is reserved by the organization `acme`"}]}"#);
+
+    // Existing crates with the prefix are not affected
+    let crate_to_publish = PublishBuilder::new("acme-existing", "1.1.0");
+    outsider_token.publish_crate(crate_to_publish).await.good();
+
+    // Admins and publishers of the organization can publish new crates
+    let crate_to_publish = PublishBuilder::new("acme-foo", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+
+    let crate_to_publish = PublishBuilder::new("acme-bar", "1.0.0");
+    let response = publisher
+        .db_new_token("publish")
+        .await
+        .publish_crate(crate_to_publish)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = anon.get::<()>("/api/v1/reserved_prefixes").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".reserved_prefixes[].id" => "[id]",
+        ".reserved_prefixes[].approved_at" => "[datetime]",
+        ".reserved_prefixes[].created_at" => "[datetime]",
+    }, @r#"
+    {
+      "reserved_prefixes": [
+        {
+          "approved_at": "[datetime]",
+          "created_at": "[datetime]",
+          "id": "[id]",
+          "organization": "acme",
+          "prefix": "acme-"
+        }
+      ]
+    }
+    "#);
+}
@@ -0,0 +1,144 @@
+use crate::schema::versions;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{MockAnonymousUser, MockRequestExt, RequestHelper, Response, TestApp};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::{Method, StatusCode};
+use insta::assert_snapshot;
+use serde_json::Value;
+
+async fn list_changes(
+    anon: &MockAnonymousUser,
+    token: Option<&str>,
+    since_seq: i64,
+) -> Response<()> {
+    let url = format!("/api/private/crate_changes?since_seq={since_seq}");
+    let mut req = anon.request_builder(Method::GET, &url);
+    if let Some(token) = token {
+        req.header("Authorization", &format!("Bearer {token}"));
+    }
+    anon.run(req).await
+}
+
+/// Returns the kind, crate and version of each change, and the
+/// `next_since_seq` value of the response.
+fn summarize(json: Value) -> (Vec<(String, String, Option<String>)>, i64) {
+    let changes = json["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| {
+            let kind = change["kind"].as_str().unwrap().to_string();
+            let krate = change["crate"].as_str().unwrap().to_string();
+            let version = change["version"].as_str().map(ToString::to_string);
+            (kind, krate, version)
+        })
+        .collect();
+
+    (changes, json["meta"]["next_since_seq"].as_i64().unwrap())
+}
+
+fn change(kind: &str, krate: &str, version: Option<&str>) -> (String, String, Option<String>) {
+    (kind.into(), krate.into(), version.map(Into::into))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn changes_are_recorded() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.crate_changes_tokens = vec!["docs-rs".into(), "crater".into()])
+        .with_user()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    let response = list_changes(&anon, Some("crater"), 0).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(summarize(response.json()), (vec![], 0));
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = list_changes(&anon, Some("docs-rs"), 0).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let (changes, since_seq) = summarize(response.json());
+    assert_eq!(
+        changes,
+        vec![
+            change("crate_created", "foo", None),
+            change("version_published", "foo", Some("1.0.0")),
+        ]
+    );
+
+    diesel::update(versions::table)
+        .set(versions::yanked.eq(true))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    diesel::update(versions::table)
+        .set(versions::yanked.eq(false))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    // Only the changes after the given sequence number are returned.
+    let response = list_changes(&anon, Some("docs-rs"), since_seq).await;
+    let (changes, next_since_seq) = summarize(response.json());
+    assert_eq!(
+        changes,
+        vec![
+            change("version_yanked", "foo", Some("1.0.0")),
+            change("version_unyanked", "foo", Some("1.0.0")),
+        ]
+    );
+    assert!(next_since_seq > since_seq);
+
+    let response = list_changes(&anon, Some("docs-rs"), next_since_seq).await;
+    assert_eq!(summarize(response.json()), (vec![], next_since_seq));
+
+    diesel::update(versions::table)
+        .set(versions::deleted_at.eq(Utc::now()))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    diesel::update(versions::table)
+        .set(versions::deleted_at.eq(None::<DateTime<Utc>>))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let response = list_changes(&anon, Some("docs-rs"), next_since_seq).await;
+    let (changes, _) = summarize(response.json());
+    assert_eq!(
+        changes,
+        vec![
+            change("version_soft_deleted", "foo", Some("1.0.0")),
+            change("version_restored", "foo", Some("1.0.0")),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_token() {
+    let (_app, anon) = TestApp::init()
+        .with_config(|config| config.crate_changes_tokens = vec!["secret".into()])
+        .empty()
+        .await;
+
+    let response = list_changes(&anon, None, 0).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid or missing authorization token"}]}"#);
+
+    let response = list_changes(&anon, Some("wrong"), 0).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn feed_disabled() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = list_changes(&anon, Some("secret"), 0).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the crate change feed is disabled on this crates.io instance"}]}"#);
+}
@@ -0,0 +1,100 @@
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::{MockAnonymousUser, MockRequestExt, RequestHelper, Response, TestApp};
+use http::{Method, StatusCode};
+use insta::assert_snapshot;
+use serde_json::{json, Value};
+
+const URL: &str = "/api/private/docs_rs/builds";
+
+async fn record_build(anon: &MockAnonymousUser, token: Option<&str>, body: Value) -> Response<()> {
+    let body = serde_json::to_vec(&body).unwrap();
+    let mut req = anon
+        .request_builder(Method::PUT, URL)
+        .with_body(body.into());
+    if let Some(token) = token {
+        req.header("Authorization", &format!("Bearer {token}"));
+    }
+    anon.run(req).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn record_build_result() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.docs_rs_callback_token = Some("secret".into()))
+        .with_user()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let version = anon.get::<()>("/api/v1/crates/foo/1.0.0").await.json();
+    assert_eq!(version["version"]["docs_rs"], Value::Null);
+
+    let body =
+        json!({ "name": "foo", "version": "1.0.0", "status": "success", "doc_coverage": 87.5 });
+    let response = record_build(&anon, Some("secret"), body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ok":true}"#);
+
+    let version = anon.get::<()>("/api/v1/crates/foo/1.0.0").await.json();
+    let docs_rs = &version["version"]["docs_rs"];
+    assert_eq!(docs_rs["status"], "success");
+    assert_eq!(docs_rs["doc_coverage"], 87.5);
+    assert!(docs_rs["built_at"].is_string());
+
+    // A later build replaces the previous result
+    let body = json!({ "name": "foo", "version": "1.0.0", "status": "failure" });
+    let response = record_build(&anon, Some("secret"), body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let version = anon.get::<()>("/api/v1/crates/foo/1.0.0").await.json();
+    let docs_rs = &version["version"]["docs_rs"];
+    assert_eq!(docs_rs["status"], "failure");
+    assert_eq!(docs_rs["doc_coverage"], Value::Null);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn record_build_errors() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.docs_rs_callback_token = Some("secret".into()))
+        .with_user()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "name": "foo", "version": "1.0.0", "status": "success" });
+
+    let response = record_build(&anon, None, body.clone()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid or missing authorization token"}]}"#);
+
+    let response = record_build(&anon, Some("wrong"), body).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = json!({ "name": "foo", "version": "2.0.0", "status": "success" });
+    let response = record_build(&anon, Some("secret"), body).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` does not have a version `2.0.0`"}]}"#);
+
+    let body =
+        json!({ "name": "foo", "version": "1.0.0", "status": "success", "doc_coverage": 120.0 });
+    let response = record_build(&anon, Some("secret"), body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`doc_coverage` must be between 0 and 100"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn record_build_disabled() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let body = json!({ "name": "foo", "version": "1.0.0", "status": "success" });
+    let response = record_build(&anon, Some("secret"), body).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -1 +1,3 @@
+mod crate_changes;
 mod crate_owner_invitations;
+mod docs_rs;
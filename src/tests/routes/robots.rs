@@ -0,0 +1,52 @@
+use crate::tests::util::{RequestHelper, TestApp};
+use http::{header, StatusCode};
+use insta::assert_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn robots_txt() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/robots.txt").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r"
+    User-agent: *
+    Disallow: /api/
+    Disallow: /me
+    Disallow: /settings
+    Disallow: /dashboard
+    Disallow: /search
+    Disallow: /confirm/
+    Disallow: /accept-invite/
+    Disallow: /crates/*/settings
+    Disallow: /crates/*/delete
+
+    Sitemap: https://crates.io/sitemaps/sitemap.xml
+    ");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn robots_txt_without_crawlers() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| config.allow_crawlers = false)
+        .empty()
+        .await;
+
+    let response = anon.get::<()>("/robots.txt").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r"
+    User-agent: *
+    Disallow: /
+    ");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sitemap_redirect() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/sitemap.xml").await;
+    assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "https://crates.io/sitemaps/sitemap.xml"
+    );
+}
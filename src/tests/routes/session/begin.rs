@@ -1,4 +1,6 @@
 use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_snapshot;
 
 #[derive(Deserialize)]
 struct AuthResponse {
@@ -12,3 +14,54 @@ async fn auth_gives_a_token() {
     let json: AuthResponse = anon.get("/api/private/session/begin").await.good();
     assert!(json.url.contains(&json.state));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn begin_with_gitlab() {
+    let (_, anon) = TestApp::init().empty().await;
+    let url = "/api/private/session/begin?provider=gitlab";
+    let json: AuthResponse = anon.get(url).await.good();
+    assert!(json.url.starts_with("https://gitlab.com/oauth/authorize?"));
+    assert!(json.url.contains("scope=read_user"));
+    assert!(json.url.contains(&json.state));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn begin_with_unconfigured_provider() {
+    let (_, anon) = TestApp::init().empty().await;
+    let response = anon
+        .get::<()>("/api/private/session/begin?provider=google")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Google login is not available"}]}"#);
+
+    let response = anon
+        .get::<()>("/api/private/session/begin?provider=bitbucket")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn begin_link_requires_cookie_auth() {
+    let (_, anon, _, token) = TestApp::init().with_token().await;
+    let url = "/api/private/session/begin?provider=gitlab&link=true";
+
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = token.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn begin_link() {
+    let (_, _, user) = TestApp::init().with_user().await;
+
+    let url = "/api/private/session/begin?provider=gitlab&link=true";
+    let json: AuthResponse = user.get(url).await.good();
+    assert!(json.url.starts_with("https://gitlab.com/oauth/authorize?"));
+
+    let url = "/api/private/session/begin?link=true";
+    let response = user.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"GitHub accounts can not be linked"}]}"#);
+}
@@ -0,0 +1,15 @@
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn download_snapshot_redirects() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/snapshot/crates.jsonl.gz").await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    response.assert_redirect_ends_with("/snapshot/crates.jsonl.gz");
+
+    let response = anon.get::<()>("/api/v1/snapshot/crates.index.json").await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    response.assert_redirect_ends_with("/snapshot/crates.index.json");
+}
@@ -0,0 +1,87 @@
+use crate::models::RegistryStats;
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::views::EncodableRegistryStats;
+use crate::worker::jobs::UpdateRegistryStats;
+use chrono::{NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use insta::assert_snapshot;
+
+#[derive(Deserialize)]
+struct StatsResponse {
+    stats: Vec<EncodableRegistryStats>,
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stats_are_calculated_by_background_job() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let user_id = user.as_model().id;
+    let yesterday = Utc::now().naive_utc() - chrono::Duration::days(1);
+
+    CrateBuilder::new("foo", user_id)
+        .version(VersionBuilder::new("1.0.0").created_at(yesterday))
+        .version("1.1.0")
+        .recent_downloads(10)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", user_id)
+        .version("0.1.0")
+        .recent_downloads(5)
+        .expect_build(&mut conn)
+        .await;
+
+    let today = Utc::now().date_naive();
+    UpdateRegistryStats::for_date(today)
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+
+    app.run_pending_background_jobs().await;
+
+    let json: StatsResponse = anon.get("/api/v1/stats").await.good();
+    assert_eq!(
+        json.stats,
+        vec![EncodableRegistryStats {
+            date: today,
+            total_crates: 2,
+            total_versions: 3,
+            publishes: 2,
+            downloads: 15,
+        }]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stats_are_ordered_and_limited() {
+    let (app, anon) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+
+    for day in 1..=5 {
+        RegistryStats {
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            total_crates: day as i64 * 10,
+            total_versions: day as i64 * 20,
+            publishes: 20,
+            downloads: day as i64 * 100,
+        }
+        .upsert(&mut conn)
+        .await
+        .unwrap();
+    }
+
+    let json: StatsResponse = anon.get("/api/v1/stats?days=3").await.good();
+    let dates = json
+        .stats
+        .iter()
+        .map(|s| s.date.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(dates, ["2024-01-03", "2024-01-04", "2024-01-05"]);
+    assert_eq!(json.stats[2].total_crates, 50);
+
+    let response = anon.get::<()>("/api/v1/stats?days=0").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`days` must be between 1 and 3650"}]}"#);
+}
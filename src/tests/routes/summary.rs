@@ -1,4 +1,5 @@
-use crate::schema::metadata;
+use crate::models::DocsRsStatus;
+use crate::schema::{metadata, versions};
 use crate::tests::builders::{CrateBuilder, VersionBuilder};
 use crate::tests::new_category;
 use crate::tests::util::{RequestHelper, TestApp};
@@ -8,6 +9,9 @@ use crates_io_database::schema::categories;
 use diesel::{insert_into, update, ExpressionMethods};
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, RunQueryDsl};
+use http::{header, StatusCode};
+use insta::assert_snapshot;
+use serde_json::json;
 
 #[derive(Deserialize)]
 struct SummaryResponse {
@@ -264,3 +268,74 @@ async fn all_yanked() {
     assert!(json.most_recently_downloaded[0].yanked);
     assert_eq!(json.most_recently_downloaded[0].recent_downloads, Some(10));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn personal_summary() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let broken = CrateBuilder::new("broken_docs", user_id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("working_docs", user_id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    update(versions::table)
+        .filter(versions::crate_id.eq(broken.id))
+        .set(versions::docs_rs_status.eq(DocsRsStatus::Failure))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let response = user
+        .put::<()>("/api/v1/crates/working_docs/follow", b"" as &[u8])
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The public summary doesn't contain any personal data
+    let response = user.get::<()>("/api/v1/summary").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.json().get("personal").is_none());
+    assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+    let response = user
+        .get_with_query::<()>("/api/v1/summary", "include=personal")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CACHE_CONTROL],
+        "private, no-store"
+    );
+
+    let json = response.json();
+    assert_eq!(json["num_crates"], 2);
+    assert_eq!(
+        json["personal"]["your_crates"],
+        json!([{ "name": "broken_docs", "version": "1.0.0", "issues": ["docs_build_failed"] }])
+    );
+    assert_eq!(
+        json["personal"]["followed_crates"][0]["name"],
+        "working_docs"
+    );
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/summary", "include=personal")
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_include() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/summary", "include=foo")
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid component for ?include= (expected 'personal')"}]}"#);
+}
@@ -1,3 +1,4 @@
 mod read;
 mod stats;
 pub mod update;
+mod versions;
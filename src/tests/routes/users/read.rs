@@ -1,8 +1,15 @@
-use crate::models::NewUser;
-use crate::schema::users;
+use crate::models::{CrateOwner, NewUser, OwnerKind, OwnerRole};
+use crate::schema::{crate_downloads, crate_owners, users};
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
 use crate::tests::util::{RequestHelper, TestApp};
 use crate::views::EncodablePublicUser;
+use crate::worker::jobs::UpdateUserStats;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::assert_json_snapshot;
+use serde_json::json;
 
 #[derive(Deserialize)]
 pub struct UserShowPublicResponse {
@@ -22,6 +29,19 @@ async fn show() {
     assert_eq!(json.user.url, "https://github.com/Bar");
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn show_surrogate_keys() {
+    let (_, anon, user) = TestApp::init().with_user().await;
+    let user_id = user.as_model().id;
+
+    let response = anon.get::<()>("/api/v1/users/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["surrogate-key"],
+        format!("user/{user_id}")
+    );
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn show_latest_user_case_insensitively() {
     let (app, anon) = TestApp::init().empty().await;
@@ -64,3 +84,78 @@ async fn show_latest_user_case_insensitively() {
         json.user.name.unwrap()
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn show_public_stats() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let model = user.as_model();
+    let co_owner = app.db_new_user("bar").await;
+
+    let krate = CrateBuilder::new("foo_stats", model.id)
+        .version(VersionBuilder::new("1.0.0"))
+        .version(VersionBuilder::new("1.1.0"))
+        .expect_build(&mut conn)
+        .await;
+    diesel::update(crate_downloads::table.filter(crate_downloads::crate_id.eq(krate.id)))
+        .set(crate_downloads::downloads.eq(42))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let crate_owner = CrateOwner {
+        crate_id: krate.id,
+        owner_id: co_owner.as_model().id,
+        created_by: model.id,
+        owner_kind: OwnerKind::User,
+        email_notifications: true,
+        role: OwnerRole::Admin,
+    };
+    diesel::insert_into(crate_owners::table)
+        .values(&crate_owner)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    // The stats are only computed for users that opted in.
+    UpdateUserStats.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/api/v1/users/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.json()["stats"].is_null());
+
+    let url = format!("/api/v1/users/{}", model.id);
+    let payload = json!({ "user": { "public_stats": true } });
+    let response = user.put::<()>(&url, payload.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    UpdateUserStats.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/api/v1/users/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["stats"], {
+        ".publishes_per_year[].year" => "[year]",
+        ".updated_at" => "[datetime]",
+    });
+
+    // The stats are hidden immediately and deleted by the next run when the
+    // user opts out again.
+    let payload = json!({ "user": { "public_stats": false } });
+    let response = user.put::<()>(&url, payload.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = anon.get::<()>("/api/v1/users/foo").await;
+    assert!(response.json()["stats"].is_null());
+
+    UpdateUserStats.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    let count: i64 = crate::schema::user_stats::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
@@ -111,6 +111,24 @@ async fn test_invalid_email_address() {
     assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid email address"}]}"#);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_secondary_email_address() {
+    let (_app, _, user) = TestApp::init().with_user().await;
+    let model = user.as_model();
+
+    let body = json!({ "email": "second@example.com" });
+    let response = user.post::<()>("/api/v1/me/emails", body.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let email = Some("Second@example.com");
+    let response = user.update_email_more_control(model.id, email).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"email address has already been added as a secondary address"}]}"#);
+
+    let json = user.show_me().await;
+    assert_eq!(json.user.email.unwrap(), "foo@example.com");
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_invalid_json() {
     let (_app, _anon, user) = TestApp::init().with_user().await;
@@ -121,3 +139,71 @@ async fn test_invalid_json() {
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Failed to parse the request body as JSON: user: expected ident at line 1 column 12"}]}"#);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_update_locale() {
+    let (app, _anon, user) = TestApp::init().with_user().await;
+    let model = user.as_model();
+
+    let url = format!("/api/v1/users/{}", model.id);
+    let payload = json!({"user": { "locale": "de", "email": "neu@example.com" }});
+    let response = user.put::<()>(&url, payload.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ok":true}"#);
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_eq!(response.json()["user"]["locale"], "de");
+
+    // The confirmation email is sent in the new locale
+    let emails = app.emails().await;
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].contains("Hallo foo! Willkommen bei crates.io."));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unsupported_locale() {
+    let (_app, _anon, user) = TestApp::init().with_user().await;
+    let model = user.as_model();
+
+    let url = format!("/api/v1/users/{}", model.id);
+    let payload = json!({"user": { "locale": "xx" }});
+    let response = user.put::<()>(&url, payload.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"unsupported locale, expected one of: de, en, fr"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_subscribe_to_weekly_digest() {
+    let (_app, _anon, user) = TestApp::init().with_user().await;
+    let model = user.as_model();
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_eq!(response.json()["user"]["weekly_digest"], false);
+
+    let url = format!("/api/v1/users/{}", model.id);
+    let payload = json!({"user": { "weekly_digest": true }});
+    let response = user.put::<()>(&url, payload.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ok":true}"#);
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_eq!(response.json()["user"]["weekly_digest"], true);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_accept_contact_requests() {
+    let (_app, _anon, user) = TestApp::init().with_user().await;
+    let model = user.as_model();
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_eq!(response.json()["user"]["contact_requests"], false);
+
+    let url = format!("/api/v1/users/{}", model.id);
+    let payload = json!({"user": { "contact_requests": true }});
+    let response = user.put::<()>(&url, payload.to_string()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_snapshot!(response.text(), @r#"{"ok":true}"#);
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_eq!(response.json()["user"]["contact_requests"], true);
+}
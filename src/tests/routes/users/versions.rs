@@ -0,0 +1,73 @@
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::{RequestHelper, TestApp};
+use http::StatusCode;
+
+fn version_names(json: &serde_json::Value) -> Vec<String> {
+    json["versions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| {
+            format!(
+                "{}@{}",
+                v["crate"].as_str().unwrap(),
+                v["num"].as_str().unwrap()
+            )
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_user_versions() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let other = app.db_new_user("bar").await;
+
+    CrateBuilder::new("foo-a", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .version(VersionBuilder::new("1.1.0").yanked(true))
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("foo_b", user.as_model().id)
+        .version("0.1.0")
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar_c", other.as_model().id)
+        .version("2.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>("/api/v1/users/foo/versions").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["meta"]["total"], 3);
+    assert_eq!(
+        version_names(&json),
+        ["foo_b@0.1.0", "foo-a@1.1.0", "foo-a@1.0.0"]
+    );
+    assert_eq!(json["versions"][0]["published_by"]["login"], "foo");
+    assert_eq!(json["versions"][1]["yanked"], true);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/users/foo/versions", "crate[]=FOO_A")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["meta"]["total"], 2);
+    assert_eq!(version_names(&json), ["foo-a@1.1.0", "foo-a@1.0.0"]);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/users/foo/versions", "per_page=1")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    assert_eq!(version_names(&json), ["foo_b@0.1.0"]);
+    assert_eq!(json["meta"]["next_page"], "?per_page=1&page=2");
+
+    let response = anon.get::<()>("/api/v1/users/bar/versions").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(version_names(&response.json()), ["bar_c@2.0.0"]);
+
+    let response = anon.get::<()>("/api/v1/users/missing/versions").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,40 @@
+//! The `statement_timeout` of pooled connections is limited by the deadline
+//! of the current request.
+
+use crate::deadline::Deadline;
+use crate::tests::util::TestApp;
+use diesel::dsl::sql;
+use diesel::sql_types::Text;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::time::Duration;
+
+async fn statement_timeout(conn: &mut AsyncPgConnection) -> String {
+    diesel::select(sql::<Text>("current_setting('statement_timeout')"))
+        .get_result(conn)
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn statement_timeout_follows_deadline() {
+    let (app, _) = TestApp::init()
+        .with_config(|config| config.db.statement_timeout = Duration::from_secs(30))
+        .empty()
+        .await;
+    let app = app.as_inner();
+
+    // Deadlines that are further away than the configured timeout keep it.
+    let deadline = Deadline::after(Duration::from_secs(60));
+    let mut conn = deadline.scope(app.db_write()).await.unwrap();
+    assert_eq!(statement_timeout(&mut conn).await, "30s");
+    drop(conn);
+
+    let deadline = Deadline::after(Duration::from_secs(10));
+    let mut conn = deadline.scope(app.db_write()).await.unwrap();
+    assert_ne!(statement_timeout(&mut conn).await, "30s");
+    drop(conn);
+
+    // The shorter timeout is not kept for the next request.
+    let mut conn = app.db_write().await.unwrap();
+    assert_eq!(statement_timeout(&mut conn).await, "30s");
+}
@@ -52,6 +52,33 @@ async fn http_error_with_unhealthy_database() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn readiness_check_with_unhealthy_database() -> anyhow::Result<()> {
+    let (app, anon) = TestApp::init().with_chaos_proxy().empty().await;
+
+    let response = anon.get::<()>("/readyz").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    app.primary_db_chaosproxy().break_networking()?;
+
+    let response = anon.get::<()>("/readyz").await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.json()["status"], "unavailable");
+    assert_eq!(response.json()["checks"]["database"]["status"], "error");
+    assert_eq!(response.json()["checks"]["storage"]["status"], "ok");
+
+    let response = anon.get::<()>("/healthz").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    app.primary_db_chaosproxy().restore_networking()?;
+    wait_until_healthy(&app.as_inner().primary_database).await;
+
+    let response = anon.get::<()>("/readyz").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn download_requests_with_unhealthy_database_succeed() -> anyhow::Result<()> {
     let (app, anon, _, token) = TestApp::init().with_chaos_proxy().with_token().await;
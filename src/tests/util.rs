@@ -43,18 +43,24 @@ use std::net::SocketAddr;
 use tower::ServiceExt;
 
 mod chaosproxy;
+pub mod faults;
 pub mod github;
 pub mod insta;
 pub mod matchers;
 mod mock_request;
+mod queries;
 mod response;
 mod test_app;
 
 use mock_request::MockRequest;
 pub use mock_request::MockRequestExt;
+pub use queries::RecordedQueries;
 pub use response::Response;
 pub use test_app::TestApp;
 
+/// The CSRF token that is stored in the sessions of [`encode_session_header()`].
+pub const CSRF_TOKEN: &str = "test-csrf-token";
+
 /// This function can be used to create a `Cookie` header for mock requests that
 /// include cookie-based authentication.
 ///
@@ -63,17 +69,24 @@ pub use test_app::TestApp;
 /// request.header(header::COOKIE, &cookie);
 /// ```
 ///
-/// The implementation matches roughly what is happening inside of our
-/// session middleware.
+/// The session contains [`CSRF_TOKEN`] as its CSRF token.
 pub fn encode_session_header(session_key: &cookie::Key, user_id: i32) -> String {
-    let cookie_name = "cargo_session";
-
-    // build session data map
     let mut map = HashMap::new();
     map.insert("user_id".into(), user_id.to_string());
+    map.insert("csrf_token".into(), CSRF_TOKEN.into());
+
+    encode_session(session_key, &map)
+}
+
+/// Creates a `Cookie` header for a session with the given data.
+///
+/// The implementation matches roughly what is happening inside of our
+/// session middleware.
+pub fn encode_session(session_key: &cookie::Key, map: &HashMap<String, String>) -> String {
+    let cookie_name = "cargo_session";
 
     // encode the map into a cookie value string
-    let encoded = crates_io_session::encode(&map);
+    let encoded = crates_io_session::encode(map);
 
     // put the cookie into a signed cookie jar
     let cookie = Cookie::build((cookie_name, encoded));
@@ -139,6 +152,12 @@ pub trait RequestHelper {
         self.run(request).await
     }
 
+    /// Issue a POST request
+    async fn post<T>(&self, path: &str, body: impl Into<Bytes>) -> Response<T> {
+        let request = self.post_request(path).with_body(body.into());
+        self.run(request).await
+    }
+
     /// Issue a PUT request
     async fn put<T>(&self, path: &str, body: impl Into<Bytes>) -> Response<T> {
         let request = self
@@ -279,6 +298,7 @@ impl RequestHelper for MockCookieUser {
 
         let mut request = req(method, path);
         request.header(header::COOKIE, &cookie);
+        request.header("x-csrf-token", CSRF_TOKEN);
         request
     }
 
@@ -336,6 +356,34 @@ impl MockCookieUser {
             token,
         }
     }
+
+    /// Creates a token owned by an organization and wraps it in a helper struct
+    ///
+    /// This method updates the database directly
+    pub async fn db_new_organization_token(
+        &self,
+        name: &str,
+        organization_id: i32,
+    ) -> MockTokenUser {
+        let mut conn = self.app().db_conn().await;
+
+        let token = ApiToken::insert_for_organization(
+            &mut conn,
+            self.user.id,
+            Some(organization_id),
+            name,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        MockTokenUser {
+            app: self.app.clone(),
+            token,
+        }
+    }
 }
 
 /// A type that can generate token authenticated requests
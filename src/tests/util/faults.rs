@@ -0,0 +1,204 @@
+//! Fault injection for the storage, email and database backends of the test
+//! suite, which is used to verify that failures at specific points don't
+//! leave inconsistent state behind.
+//!
+//! Storage and email faults are enabled with
+//! [`TestAppBuilder::with_fault_injection()`](super::test_app::TestAppBuilder::with_fault_injection)
+//! and then injected and cleared at runtime via [`TestApp::faults()`](super::TestApp::faults).
+
+use crate::email::EmailBackend;
+use async_trait::async_trait;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use futures_util::stream::BoxStream;
+use lettre::Message;
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore, PutMultipartOpts,
+    PutOptions, PutPayload, PutResult, Result,
+};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Fault {
+    /// Uploads of files with the given path prefix fail, e.g. `crates/` for
+    /// crate files or `index/` for sparse index files.
+    StorageUpload(&'static str),
+    /// Sending emails fails.
+    SendEmail,
+}
+
+/// The set of currently injected faults, which is shared by all backends of
+/// a [`TestApp`](super::TestApp).
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    faults: Arc<Mutex<HashSet<Fault>>>,
+}
+
+impl FaultInjector {
+    pub fn inject(&self, fault: Fault) {
+        self.faults.lock().insert(fault);
+    }
+
+    pub fn clear(&self, fault: &Fault) {
+        self.faults.lock().remove(fault);
+    }
+
+    pub fn clear_all(&self) {
+        self.faults.lock().clear();
+    }
+
+    fn is_injected(&self, fault: &Fault) -> bool {
+        self.faults.lock().contains(fault)
+    }
+
+    fn check_upload(&self, location: &Path) -> Result<()> {
+        let location = location.as_ref();
+        let faults = self.faults.lock();
+        let injected = faults.iter().any(|fault| match fault {
+            Fault::StorageUpload(prefix) => location.starts_with(prefix),
+            Fault::SendEmail => false,
+        });
+
+        if injected {
+            let message = format!("injected upload fault for `{location}`");
+            return Err(object_store::Error::Generic {
+                store: "FaultyStore",
+                source: message.into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// An [`ObjectStore`] that fails uploads while a matching
+/// [`Fault::StorageUpload`] is injected.
+#[derive(Debug)]
+pub struct FaultyStore {
+    inner: Arc<dyn ObjectStore>,
+    faults: FaultInjector,
+}
+
+impl FaultyStore {
+    pub fn new(inner: Arc<dyn ObjectStore>, faults: FaultInjector) -> Self {
+        Self { inner, faults }
+    }
+}
+
+impl fmt::Display for FaultyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FaultyStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for FaultyStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> Result<PutResult> {
+        self.faults.check_upload(location)?;
+        self.inner.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> Result<Box<dyn MultipartUpload>> {
+        self.faults.check_upload(location)?;
+        self.inner.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, Result<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.faults.check_upload(to)?;
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.faults.check_upload(to)?;
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+/// An [`EmailBackend`] that fails to send emails while [`Fault::SendEmail`]
+/// is injected.
+#[derive(Debug)]
+pub struct FaultyEmailBackend {
+    inner: Arc<dyn EmailBackend>,
+    faults: FaultInjector,
+}
+
+impl FaultyEmailBackend {
+    pub fn new(inner: Arc<dyn EmailBackend>, faults: FaultInjector) -> Self {
+        Self { inner, faults }
+    }
+}
+
+#[async_trait]
+impl EmailBackend for FaultyEmailBackend {
+    async fn send(&self, message: Message) -> anyhow::Result<Option<String>> {
+        if self.faults.is_injected(&Fault::SendEmail) {
+            anyhow::bail!("injected email fault");
+        }
+
+        self.inner.send(message).await
+    }
+}
+
+/// Makes all transactions that insert into the given table fail when they
+/// are committed, after all of their statements have succeeded.
+///
+/// This is implemented with a deferred constraint trigger, so it only
+/// affects the database of the current test.
+pub async fn fail_commits_inserting_into(conn: &mut AsyncPgConnection, table: &str) {
+    diesel::sql_query(
+        "create or replace function inject_commit_fault() returns trigger as $$
+        begin
+            raise exception 'injected commit fault';
+        end;
+        $$ language plpgsql",
+    )
+    .execute(conn)
+    .await
+    .unwrap();
+
+    diesel::sql_query(format!(
+        "create constraint trigger inject_commit_fault
+        after insert on {table}
+        deferrable initially deferred
+        for each row execute function inject_commit_fault()"
+    ))
+    .execute(conn)
+    .await
+    .unwrap();
+}
+
+/// Removes the trigger of [`fail_commits_inserting_into()`] again.
+pub async fn restore_commits_inserting_into(conn: &mut AsyncPgConnection, table: &str) {
+    diesel::sql_query(format!("drop trigger inject_commit_fault on {table}"))
+        .execute(conn)
+        .await
+        .unwrap();
+}
@@ -1,7 +1,7 @@
 use anyhow::anyhow;
 use crates_io_github::{
-    GitHubError, GitHubOrgMembership, GitHubOrganization, GitHubTeam, GitHubTeamMembership,
-    GithubUser, MockGitHubClient,
+    GitHubError, GitHubOrgMembership, GitHubOrganization, GitHubRepository,
+    GitHubRepositoryPermissions, GitHubTeam, GitHubTeamMembership, GithubUser, MockGitHubClient,
 };
 use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -49,6 +49,20 @@ pub(crate) const MOCK_GITHUB_DATA: MockData = MockData {
             email: "owner@example.com",
         },
     ],
+    repos: &[
+        MockRepo {
+            id: 3000,
+            owner: "test-org",
+            name: "writable",
+            push: true,
+        },
+        MockRepo {
+            id: 3001,
+            owner: "test-org",
+            name: "read-only",
+            push: false,
+        },
+    ],
 };
 
 impl MockData {
@@ -72,6 +86,9 @@ impl MockData {
         mock.expect_org_membership()
             .returning(|org_id, username, _auth| self.org_membership(org_id, username));
 
+        mock.expect_repository()
+            .returning(|owner, repo, _auth| self.repository(owner, repo));
+
         mock
     }
 
@@ -167,6 +184,22 @@ impl MockData {
             Err(not_found())
         }
     }
+
+    fn repository(&self, owner: &str, repo: &str) -> Result<GitHubRepository, GitHubError> {
+        let repo = self
+            .repos
+            .iter()
+            .find(|r| r.owner == owner.to_lowercase() && r.name == repo.to_lowercase())
+            .ok_or_else(not_found)?;
+        Ok(GitHubRepository {
+            id: repo.id,
+            full_name: format!("{}/{}", repo.owner, repo.name),
+            permissions: Some(GitHubRepositoryPermissions {
+                admin: false,
+                push: repo.push,
+            }),
+        })
+    }
 }
 
 fn not_found() -> GitHubError {
@@ -176,6 +209,7 @@ fn not_found() -> GitHubError {
 pub(crate) struct MockData {
     orgs: &'static [MockOrg],
     users: &'static [MockUser],
+    repos: &'static [MockRepo],
 }
 
 struct MockUser {
@@ -197,3 +231,11 @@ struct MockTeam {
     name: &'static str,
     members: &'static [&'static str],
 }
+
+/// A repository on which the users of the tests have the given permissions.
+struct MockRepo {
+    id: i64,
+    owner: &'static str,
+    name: &'static str,
+    push: bool,
+}
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+/// The SQL queries that the application executed while a future was
+/// running, as returned by [`TestApp::record_queries()`].
+///
+/// [`TestApp::record_queries()`]: crate::tests::util::TestApp::record_queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedQueries {
+    pub count: u64,
+    pub duration: Duration,
+}
+
+impl RecordedQueries {
+    /// Asserts that at most `max` queries were executed.
+    #[track_caller]
+    pub fn assert_count_at_most(&self, max: u64) {
+        assert!(
+            self.count <= max,
+            "expected at most {max} queries, but {} were executed",
+            self.count
+        );
+    }
+
+    /// Asserts that the queries took at most `max` in total.
+    #[track_caller]
+    pub fn assert_duration_at_most(&self, max: Duration) {
+        assert!(
+            self.duration <= max,
+            "expected the queries to take at most {max:?}, but they took {:?}",
+            self.duration
+        );
+    }
+}
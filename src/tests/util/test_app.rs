@@ -1,6 +1,7 @@
 use super::{MockAnonymousUser, MockCookieUser, MockTokenUser};
 use crate::config::{
     self, Base, CdnLogQueueConfig, CdnLogStorageConfig, DatabasePools, DbPoolConfig,
+    HttpServerConfig, OAuthClientConfig, SecurityHeadersConfig,
 };
 use crate::middleware::cargo_compat::StatusCodeConfig;
 use crate::models::token::{CrateScope, EndpointScope};
@@ -9,7 +10,9 @@ use crate::rate_limiter::{LimitedAction, RateLimiterConfig};
 use crate::schema::users;
 use crate::storage::StorageConfig;
 use crate::tests::util::chaosproxy::ChaosProxy;
+use crate::tests::util::faults::FaultInjector;
 use crate::tests::util::github::MOCK_GITHUB_DATA;
+use crate::tests::util::RecordedQueries;
 use crate::worker::{Environment, RunnerExt};
 use crate::{App, Emails, Env};
 use crates_io_github::MockGitHubClient;
@@ -23,6 +26,7 @@ use futures_util::TryStreamExt;
 use oauth2::{ClientId, ClientSecret};
 use regex::Regex;
 use std::collections::HashSet;
+use std::future::Future;
 use std::sync::LazyLock;
 use std::{rc::Rc, sync::Arc, time::Duration};
 use tokio::runtime::Handle;
@@ -36,6 +40,7 @@ struct TestAppInner {
 
     primary_db_chaosproxy: Option<Arc<ChaosProxy>>,
     replica_db_chaosproxy: Option<Arc<ChaosProxy>>,
+    faults: Option<FaultInjector>,
 
     // Must be the last field of the struct!
     test_database: TestDatabase,
@@ -100,6 +105,7 @@ impl TestApp {
             index: None,
             build_job_runner: false,
             use_chaos_proxy: false,
+            faults: None,
             team_repo: MockTeamRepo::new(),
             github: None,
         }
@@ -139,6 +145,7 @@ impl TestApp {
                 emails::user_id.eq(user.id),
                 emails::email.eq(email),
                 emails::verified.eq(true),
+                emails::is_primary.eq(true),
             ))
             .execute(&mut conn)
             .await
@@ -173,6 +180,19 @@ impl TestApp {
             .collect()
     }
 
+    /// Obtain a list of the files in the private store, which is not served
+    /// by the CDN
+    pub async fn stored_private_files(&self) -> Vec<String> {
+        let store = self.as_inner().storage.as_private_inner();
+
+        let stream = store.list(None);
+        let list = stream.try_collect::<Vec<_>>().await.unwrap();
+
+        list.into_iter()
+            .map(|meta| meta.location.to_string())
+            .collect()
+    }
+
     pub async fn emails(&self) -> Vec<String> {
         let emails = self.as_inner().emails.mails_in_memory().await.unwrap();
         emails.into_iter().map(|(_, email)| email).collect()
@@ -191,6 +211,9 @@ impl TestApp {
         static INVITE_TOKEN_REGEX: LazyLock<Regex> =
             LazyLock::new(|| Regex::new(r"/accept-invite/\w+").unwrap());
 
+        static EXPORT_TOKEN_REGEX: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"/me/export/\w+").unwrap());
+
         static SEPARATOR: &str = "\n----------------------------------------\n\n";
 
         self.emails()
@@ -201,6 +224,7 @@ impl TestApp {
                 let email = DATE_TIME_REGEX.replace_all(&email, "[0000-00-00T00:00:00Z]");
                 let email = EMAIL_CONFIRM_REGEX.replace_all(&email, "/confirm/[confirm-token]");
                 let email = INVITE_TOKEN_REGEX.replace_all(&email, "/accept-invite/[invite-token]");
+                let email = EXPORT_TOKEN_REGEX.replace_all(&email, "/me/export/[export-token]");
                 email.to_string()
             })
             .collect::<Vec<_>>()
@@ -218,11 +242,34 @@ impl TestApp {
         result.expect("Could not determine if jobs failed");
     }
 
+    /// Runs the future, e.g. a request, and returns its output together with
+    /// the SQL queries that the application executed in the meantime.
+    ///
+    /// Queries on connections that are not managed by the application, like
+    /// the ones returned by [`TestApp::db_conn()`], are not recorded.
+    pub async fn record_queries<F: Future>(&self, future: F) -> (F::Output, RecordedQueries) {
+        let stats = &self.as_inner().query_stats;
+        let (count, duration) = (stats.count(), stats.duration());
+
+        let output = future.await;
+
+        let queries = RecordedQueries {
+            count: stats.count() - count,
+            duration: stats.duration() - duration,
+        };
+        (output, queries)
+    }
+
     /// Obtain a reference to the inner `App` value
     pub fn as_inner(&self) -> &App {
         &self.0.app
     }
 
+    /// Obtain a new shared handle of the inner `App` value
+    pub fn app_arc(&self) -> Arc<App> {
+        self.0.app.clone()
+    }
+
     /// Obtain a reference to the axum Router
     pub fn router(&self) -> &axum::Router {
         &self.0.router
@@ -241,6 +288,14 @@ impl TestApp {
             .clone()
             .expect("ChaosProxy is not enabled on this test, call with_database during app init")
     }
+
+    /// Obtain the fault injector of the storage and email backends
+    pub fn faults(&self) -> &FaultInjector {
+        self.0
+            .faults
+            .as_ref()
+            .expect("Fault injection is not enabled on this test, call with_fault_injection during app init")
+    }
 }
 
 pub struct TestAppBuilder {
@@ -248,6 +303,7 @@ pub struct TestAppBuilder {
     index: Option<UpstreamIndex>,
     build_job_runner: bool,
     use_chaos_proxy: bool,
+    faults: Option<FaultInjector>,
     team_repo: MockTeamRepo,
     github: Option<MockGitHubClient>,
 }
@@ -288,7 +344,7 @@ impl TestAppBuilder {
             (primary_proxy, replica_proxy)
         };
 
-        let (app, router) = build_app(self.config, self.github);
+        let (app, router) = build_app(self.config, self.github, self.faults.clone());
 
         let runner = if self.build_job_runner {
             let index = self
@@ -305,9 +361,11 @@ impl TestAppBuilder {
                 .config(app.config.clone())
                 .repository_config(repository_config)
                 .storage(app.storage.clone())
+                .maybe_standby_storage(app.standby_storage.clone())
                 .deadpool(app.primary_database.clone())
                 .emails(app.emails.clone())
                 .team_repo(Box::new(self.team_repo))
+                .github(app.github.clone())
                 .build();
 
             let runner = Runner::new(app.primary_database.clone(), Arc::new(environment))
@@ -327,6 +385,7 @@ impl TestAppBuilder {
             runner,
             primary_db_chaosproxy,
             replica_db_chaosproxy,
+            faults: self.faults,
         };
         let test_app = TestApp(Rc::new(test_app_inner));
         let anon = MockAnonymousUser {
@@ -391,6 +450,19 @@ impl TestAppBuilder {
         self
     }
 
+    /// Use storage and email backends that fail while the faults of
+    /// [`TestApp::faults()`] are injected.
+    pub fn with_fault_injection(mut self) -> Self {
+        let faults = FaultInjector::default();
+
+        let mut storage = StorageConfig::in_memory_with_faults(faults.clone());
+        storage.cdn_prefix = self.config.storage.cdn_prefix.take();
+        self.config.storage = storage;
+
+        self.faults = Some(faults);
+        self
+    }
+
     pub fn with_github(mut self, github: MockGitHubClient) -> Self {
         self.github = Some(github);
         self
@@ -408,6 +480,7 @@ impl TestAppBuilder {
             url: primary.url.clone(),
             read_only_mode: true,
             pool_size: primary.pool_size,
+            max_pool_size: primary.max_pool_size,
             min_idle: primary.min_idle,
         });
 
@@ -426,6 +499,7 @@ fn simple_config() -> config::Server {
             url: String::from("invalid default url").into(),
             read_only_mode: false,
             pool_size: 5,
+            max_pool_size: 10,
             min_idle: None,
         },
         replica: None,
@@ -444,18 +518,37 @@ fn simple_config() -> config::Server {
         ip: [127, 0, 0, 1].into(),
         port: 8888,
         max_blocking_threads: None,
+        shutdown_timeout: Duration::from_secs(30),
+        http: HttpServerConfig::default(),
+        request_log_sample_rate: 1.0,
+        blocking_pool_size: 4,
+        blocking_pool_queue_size: 16,
+        request_body_limit: 2 * 1024 * 1024,
+        request_timeout: Duration::from_secs(30),
+        publish_request_timeout: Duration::from_secs(60),
         db,
         storage,
+        standby_storage: None,
+        downloads_failover: false,
         cdn_log_queue: CdnLogQueueConfig::Mock,
         cdn_log_storage: CdnLogStorageConfig::memory(),
         session_key: cookie::Key::derive_from("test this has to be over 32 bytes long".as_bytes()),
         gh_client_id: ClientId::new(dotenvy::var("GH_CLIENT_ID").unwrap_or_default()),
         gh_client_secret: ClientSecret::new(dotenvy::var("GH_CLIENT_SECRET").unwrap_or_default()),
+        gitlab_oauth: Some(OAuthClientConfig {
+            client_id: ClientId::new("gitlab-client-id".into()),
+            client_secret: ClientSecret::new("gitlab-client-secret".into()),
+            redirect_url: "https://crates.io/github-redirect.html".into(),
+        }),
+        google_oauth: None,
+        email: None,
         max_upload_size: 128 * 1024, // 128 kB should be enough for most testing purposes
         max_unpack_size: 128 * 1024, // 128 kB should be enough for most testing purposes
         max_features: 10,
         max_dependencies: 10,
         rate_limiter: Default::default(),
+        request_rate_limiter: Default::default(),
+        abuse_throttle: Default::default(),
         new_version_rate_limit: Some(10),
         blocked_traffic: Default::default(),
         blocked_ips: Default::default(),
@@ -464,32 +557,47 @@ fn simple_config() -> config::Server {
         page_offset_cidr_blocklist: vec![],
         excluded_crate_names: vec![],
         domain_name: "crates.io".into(),
+        allow_crawlers: true,
         allowed_origins: Default::default(),
         downloads_persist_interval: Duration::from_secs(1),
+        api_usage_persist_interval: Duration::from_secs(60),
         ownership_invitations_expiration_days: 30,
         metrics_authorization_token: None,
+        docs_rs_callback_token: None,
+        crate_changes_tokens: vec![],
         instance_metrics_log_every_seconds: None,
         blocked_routes: HashSet::new(),
         version_id_cache_size: 10000,
         version_id_cache_ttl: Duration::from_secs(5 * 60),
         cdn_user_agent: "Amazon CloudFront".to_string(),
+        malware_quarantine_score: 50,
+        typosquat_quarantine_matches: 1,
+        storage_orphan_quarantine: None,
 
         // The middleware has its own unit tests to verify its functionality.
         // Here, we can test what would happen if we toggled the status code
         // enforcement off eventually.
         cargo_compat_status_code_config: StatusCodeConfig::Disabled,
+        deployed_sha: None,
 
         // The frontend code is not needed for the backend tests.
         serve_dist: false,
         serve_html: false,
-        content_security_policy: None,
+        security_headers: SecurityHeadersConfig::default(),
     }
 }
 
-fn build_app(config: config::Server, github: Option<MockGitHubClient>) -> (Arc<App>, axum::Router) {
+fn build_app(
+    config: config::Server,
+    github: Option<MockGitHubClient>,
+    faults: Option<FaultInjector>,
+) -> (Arc<App>, axum::Router) {
     // Use the in-memory email backend for all tests, allowing tests to analyze the emails sent by
     // the application. This will also prevent cluttering the filesystem.
-    let emails = Emails::new_in_memory();
+    let emails = match faults {
+        Some(faults) => Emails::new_in_memory_with_faults(faults),
+        None => Emails::new_in_memory(),
+    };
 
     let github = github.unwrap_or_else(|| MOCK_GITHUB_DATA.as_mock_client());
     let github = Box::new(github);
@@ -16,8 +16,8 @@ async fn record_rerendered_readme_time() -> anyhow::Result<()> {
         .await;
 
     let mut conn = app.db_conn().await;
-    Version::record_readme_rendering(version.id, &mut conn).await?;
-    Version::record_readme_rendering(version.id, &mut conn).await?;
+    Version::record_readme_rendering(version.id, None, &mut conn).await?;
+    Version::record_readme_rendering(version.id, Some("b94d27b9"), &mut conn).await?;
 
     Ok(())
 }
@@ -0,0 +1,55 @@
+use crate::models::PublishMethod;
+use crate::schema::versions;
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::worker::jobs::BackfillPublishMethods;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn backfill_publish_methods() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let pb = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(pb).await.good();
+
+    let pb = PublishBuilder::new("bar", "1.0.0");
+    user.publish_crate(pb).await.good();
+
+    let publish_methods: Vec<Option<PublishMethod>> = versions::table
+        .order(versions::id)
+        .select(versions::publish_method)
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        publish_methods,
+        [Some(PublishMethod::ApiToken), Some(PublishMethod::Web)]
+    );
+
+    diesel::update(versions::table)
+        .set(versions::publish_method.eq(None::<PublishMethod>))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    BackfillPublishMethods::after(0)
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+    app.run_pending_background_jobs().await;
+
+    let publish_methods: Vec<Option<PublishMethod>> = versions::table
+        .order(versions::id)
+        .select(versions::publish_method)
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+    // Publishes with a session cookie can't be told apart from publishes
+    // with a legacy token, so their method stays unknown.
+    assert_eq!(publish_methods, [Some(PublishMethod::ApiToken), None]);
+}
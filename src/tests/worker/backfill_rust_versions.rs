@@ -0,0 +1,46 @@
+use crate::schema::versions;
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::worker::jobs::BackfillRustVersions;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+fn manifest(name: &str, rust_version: &str) -> String {
+    format!(
+        "[package]\nname = \"{name}\"\nversion = \"1.0.0\"\ndescription = \"description\"\nlicense = \"MIT\"\nrust-version = \"{rust_version}\"\n"
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn backfill_rust_versions() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let pb = PublishBuilder::new("foo", "1.0.0").custom_manifest(manifest("foo", "1.70"));
+    token.publish_crate(pb).await.good();
+
+    let pb = PublishBuilder::new("bar", "1.0.0");
+    token.publish_crate(pb).await.good();
+
+    diesel::update(versions::table)
+        .set(versions::rust_version.eq(None::<String>))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    BackfillRustVersions::after(0)
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+    app.run_pending_background_jobs().await;
+
+    let rust_versions: Vec<Option<String>> = versions::table
+        .order(versions::id)
+        .select(versions::rust_version)
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+    assert_eq!(rust_versions, vec![Some("1.70".to_string()), None]);
+}
@@ -0,0 +1,49 @@
+use crate::schema::versions;
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::TestApp;
+use crate::worker::jobs::BackfillSemverOrd;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn backfill_semver_ord() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version("1.0.0-beta.2")
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    diesel::update(versions::table)
+        .set(versions::semver_ord.eq(None::<String>))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    BackfillSemverOrd::after(0)
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+    app.run_pending_background_jobs().await;
+
+    let nums: Vec<String> = versions::table
+        .order(versions::semver_ord.desc())
+        .select(versions::num)
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+    assert_eq!(nums, ["1.0.0", "1.0.0-beta.2"]);
+
+    let num_missing: i64 = versions::table
+        .filter(versions::semver_ord.is_null())
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+
+    assert_eq!(num_missing, 0);
+}
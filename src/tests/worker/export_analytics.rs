@@ -0,0 +1,138 @@
+use crate::schema::{version_downloads, versions};
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::TestApp;
+use crate::worker::jobs::ExportAnalytics;
+use bytes::Bytes;
+use chrono::NaiveDate;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::{assert_debug_snapshot, assert_snapshot};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+
+async fn read_file(app: &TestApp, path: &str) -> Bytes {
+    let path = object_store::path::Path::parse(path).unwrap();
+    let result = app.as_inner().storage.as_inner().get(&path).await.unwrap();
+    result.bytes().await.unwrap()
+}
+
+/// Reads a Parquet file and returns the given columns of all rows.
+fn read_rows(bytes: Bytes, columns: &[&str]) -> Vec<String> {
+    let reader = SerializedFileReader::new(bytes).unwrap();
+    reader
+        .get_row_iter(None)
+        .unwrap()
+        .map(|row| {
+            let row = row.unwrap();
+            row.get_column_iter()
+                .filter(|(name, _)| columns.contains(&name.as_str()))
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_analytics() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let user_id = user.as_model().id;
+    CrateBuilder::new("foo", user_id)
+        .description("The foo crate")
+        .version(VersionBuilder::new("1.0.0").license("MIT"))
+        .version(VersionBuilder::new("1.1.0").yanked(true))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", user_id)
+        .version("0.1.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+    let other_date = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+    let version_ids: Vec<i32> = versions::table
+        .select(versions::id)
+        .order(versions::id)
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+    let downloads = version_ids
+        .iter()
+        .enumerate()
+        .flat_map(|(i, version_id)| {
+            [(date, 10 * (i as i32 + 1)), (other_date, 1)].map(|(date, downloads)| {
+                (
+                    version_downloads::version_id.eq(*version_id),
+                    version_downloads::date.eq(date),
+                    version_downloads::downloads.eq(downloads),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    diesel::insert_into(version_downloads::table)
+        .values(&downloads)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    ExportAnalytics::for_date(date)
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    analytics/v1/crates/date=2024-01-02/crates.parquet
+    analytics/v1/schema.json
+    analytics/v1/version_downloads/date=2024-01-02/version_downloads.parquet
+    analytics/v1/versions/date=2024-01-02/versions.parquet
+    ");
+
+    let schema = read_file(&app, "analytics/v1/schema.json").await;
+    let schema: serde_json::Value = serde_json::from_slice(&schema).unwrap();
+    assert_eq!(schema["version"], 1);
+    let tables = schema["tables"].as_object().unwrap();
+    let tables = tables.keys().collect::<Vec<_>>();
+    assert_eq!(tables, ["crates", "version_downloads", "versions"]);
+    assert_eq!(schema["tables"]["crates"][1]["name"], "name");
+    assert_eq!(schema["tables"]["crates"][1]["type"], "string");
+
+    let path = "analytics/v1/crates/date=2024-01-02/crates.parquet";
+    let bytes = read_file(&app, path).await;
+    let rows = read_rows(bytes, &["name", "description", "homepage"]);
+    assert_debug_snapshot!(rows, @r#"
+    [
+        "name=\"foo\" description=\"The foo crate\" homepage=null",
+        "name=\"bar\" description=null homepage=null",
+    ]
+    "#);
+
+    let path = "analytics/v1/versions/date=2024-01-02/versions.parquet";
+    let bytes = read_file(&app, path).await;
+    let rows = read_rows(bytes, &["num", "yanked", "license"]);
+    assert_debug_snapshot!(rows, @r#"
+    [
+        "num=\"1.0.0\" yanked=false license=\"MIT\"",
+        "num=\"1.1.0\" yanked=true license=null",
+        "num=\"0.1.0\" yanked=false license=null",
+    ]
+    "#);
+
+    let path = "analytics/v1/version_downloads/date=2024-01-02/version_downloads.parquet";
+    let bytes = read_file(&app, path).await;
+    let rows = read_rows(bytes, &["date", "downloads"]);
+    assert_debug_snapshot!(rows, @r#"
+    [
+        "date=2024-01-02 downloads=10",
+        "date=2024-01-02 downloads=20",
+        "date=2024-01-02 downloads=30",
+    ]
+    "#);
+}
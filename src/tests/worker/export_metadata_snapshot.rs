@@ -0,0 +1,85 @@
+use crate::schema::versions;
+use crate::tests::builders::{CrateBuilder, VersionBuilder};
+use crate::tests::util::TestApp;
+use crate::worker::jobs::ExportMetadataSnapshot;
+use bytes::Bytes;
+use chrono::Utc;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use flate2::read::{GzDecoder, MultiGzDecoder};
+use insta::assert_snapshot;
+use std::io::Read;
+
+async fn read_file(app: &TestApp, path: &str) -> Bytes {
+    let path = object_store::path::Path::parse(path).unwrap();
+    let result = app.as_inner().storage.as_inner().get(&path).await.unwrap();
+    result.bytes().await.unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_metadata_snapshot() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let user_id = user.as_model().id;
+    CrateBuilder::new("foo", user_id)
+        .description("The foo crate")
+        .version(VersionBuilder::new("1.0.0").license("MIT"))
+        .version(VersionBuilder::new("1.1.0").yanked(true))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", user_id)
+        .version("0.1.0")
+        .version("0.2.0")
+        .expect_build(&mut conn)
+        .await;
+
+    // Soft-deleted versions are not included.
+    diesel::update(versions::table.filter(versions::num.eq("0.2.0")))
+        .set(versions::deleted_at.eq(Utc::now()))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    ExportMetadataSnapshot.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    snapshot/crates.index.json
+    snapshot/crates.jsonl.gz
+    ");
+
+    let snapshot = read_file(&app, "snapshot/crates.jsonl.gz").await;
+    let index = read_file(&app, "snapshot/crates.index.json").await;
+    let index: serde_json::Value = serde_json::from_slice(&index).unwrap();
+    assert_eq!(index["size"], snapshot.len());
+
+    let mut content = String::new();
+    MultiGzDecoder::new(snapshot.as_ref())
+        .read_to_string(&mut content)
+        .unwrap();
+    let crates = content
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(crates.len(), 2);
+    assert_eq!(crates[0]["name"], "bar");
+    assert_eq!(crates[0]["versions"].as_array().unwrap().len(), 1);
+    assert_eq!(crates[1]["name"], "foo");
+    assert_eq!(crates[1]["description"], "The foo crate");
+    assert_eq!(crates[1]["versions"][0]["num"], "1.0.0");
+    assert_eq!(crates[1]["versions"][0]["license"], "MIT");
+    assert_eq!(crates[1]["versions"][1]["yanked"], true);
+
+    // Single crates can be extracted by their byte range.
+    let offset = index["crates"]["foo"]["offset"].as_u64().unwrap() as usize;
+    let length = index["crates"]["foo"]["length"].as_u64().unwrap() as usize;
+    let mut line = String::new();
+    GzDecoder::new(&snapshot[offset..offset + length])
+        .read_to_string(&mut line)
+        .unwrap();
+    let krate: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(krate, crates[1]);
+}
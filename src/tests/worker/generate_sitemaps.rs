@@ -0,0 +1,77 @@
+use crate::schema::crates;
+use crate::tests::util::TestApp;
+use crate::worker::jobs::GenerateSitemaps;
+use chrono::{DateTime, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use insta::assert_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_generate_sitemaps() -> anyhow::Result<()> {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    create_crate(&mut conn, "serde", "2024-06-20T10:13:54Z", false).await?;
+    create_crate(&mut conn, "anyhow", "2024-06-21T17:01:33Z", false).await?;
+    create_crate(&mut conn, "syn", "2024-06-22T12:45:12Z", false).await?;
+    create_crate(&mut conn, "sus", "2024-06-23T08:00:00Z", true).await?;
+
+    GenerateSitemaps.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    sitemaps/crates-a-1.xml
+    sitemaps/crates-s-1.xml
+    sitemaps/sitemap.xml
+    ");
+
+    assert_snapshot!(read_file(&app, "sitemaps/sitemap.xml").await?, @r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+    <sitemap><loc>https://crates.io/sitemaps/crates-a-1.xml</loc></sitemap>
+    <sitemap><loc>https://crates.io/sitemaps/crates-s-1.xml</loc></sitemap>
+    </sitemapindex>
+    "#);
+
+    // The quarantined crate is not included.
+    assert_snapshot!(read_file(&app, "sitemaps/crates-s-1.xml").await?, @r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+    <url><loc>https://crates.io/crates/serde</loc><lastmod>2024-06-20</lastmod></url>
+    <url><loc>https://crates.io/crates/syn</loc><lastmod>2024-06-22</lastmod></url>
+    </urlset>
+    "#);
+
+    Ok(())
+}
+
+async fn read_file(app: &TestApp, path: &str) -> anyhow::Result<String> {
+    let store = app.as_inner().storage.as_inner();
+    let result = store.get(&path.into()).await?;
+    let bytes = result.bytes().await?;
+    Ok(String::from_utf8(bytes.to_vec())?)
+}
+
+async fn create_crate(
+    conn: &mut AsyncPgConnection,
+    name: &str,
+    updated_at: &str,
+    quarantined: bool,
+) -> anyhow::Result<()> {
+    let updated_at = DateTime::parse_from_rfc3339(updated_at)?;
+    let quarantined_at = quarantined.then(|| updated_at.with_timezone(&Utc));
+    let updated_at = updated_at.naive_utc();
+
+    diesel::insert_into(crates::table)
+        .values((
+            crates::name.eq(name),
+            crates::created_at.eq(updated_at),
+            crates::updated_at.eq(updated_at),
+            crates::quarantined_at.eq(quarantined_at),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
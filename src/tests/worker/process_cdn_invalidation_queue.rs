@@ -0,0 +1,106 @@
+use crate::models::{Cdn, CdnInvalidation};
+use crate::schema::cdn_invalidations;
+use crate::tests::util::TestApp;
+use crate::worker::jobs::ProcessCdnInvalidationQueue;
+use chrono::{TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn queued_paths_are_deduplicated() {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    CdnInvalidation::queue(
+        &mut conn,
+        Cdn::CloudFront,
+        &["/index/fo/o/foo", "/rss/crates.xml"],
+    )
+    .await
+    .unwrap();
+    CdnInvalidation::queue(&mut conn, Cdn::CloudFront, &["/index/fo/o/foo"])
+        .await
+        .unwrap();
+    CdnInvalidation::queue(&mut conn, Cdn::Fastly, &["/rss/crates.xml"])
+        .await
+        .unwrap();
+
+    let queued: Vec<(Cdn, String)> = cdn_invalidations::table
+        .select((cdn_invalidations::cdn, cdn_invalidations::path))
+        .order((cdn_invalidations::cdn, cdn_invalidations::path))
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        queued,
+        [
+            (Cdn::CloudFront, "/index/fo/o/foo".to_string()),
+            (Cdn::CloudFront, "/rss/crates.xml".to_string()),
+            (Cdn::Fastly, "/rss/crates.xml".to_string()),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn paths_queued_again_stay_in_the_queue() {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    CdnInvalidation::queue(
+        &mut conn,
+        Cdn::CloudFront,
+        &["/index/fo/o/foo", "/index/ba/r/bar"],
+    )
+    .await
+    .unwrap();
+
+    let started_at = Utc::now();
+    CdnInvalidation::queue(&mut conn, Cdn::CloudFront, &["/index/fo/o/foo"])
+        .await
+        .unwrap();
+
+    let paths = vec!["/index/fo/o/foo".to_string(), "/index/ba/r/bar".to_string()];
+    let num_dequeued = CdnInvalidation::dequeue(&mut conn, Cdn::CloudFront, &paths, started_at)
+        .await
+        .unwrap();
+    assert_eq!(num_dequeued, 1);
+
+    let batch = CdnInvalidation::oldest(&mut conn, Cdn::CloudFront, 10)
+        .await
+        .unwrap();
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].path, "/index/fo/o/foo");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn process_queue_without_configured_cdns() {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    CdnInvalidation::queue(&mut conn, Cdn::CloudFront, &["/index/fo/o/foo"])
+        .await
+        .unwrap();
+
+    // Skip the batch window.
+    diesel::update(cdn_invalidations::table)
+        .set(cdn_invalidations::queued_at.eq(Utc::now() - TimeDelta::minutes(1)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    ProcessCdnInvalidationQueue
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+    app.run_pending_background_jobs().await;
+
+    // The test app has no CDNs configured, so the queued paths are dropped.
+    let count: i64 = cdn_invalidations::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,62 @@
+use crate::schema::{crates, versions};
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::assert_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn staged_crate_file_is_published_after_commit() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let upstream = app.upstream_index();
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = token.put::<()>("/api/v1/crates/new", body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Until the background jobs have run, the tarball is only staged and the
+    // index is not updated.
+    let mut conn = app.db_conn().await;
+    let version_id: i32 = versions::table
+        .select(versions::id)
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+
+    let staged_path = format!("staged-crates/{version_id}.crate");
+    assert_eq!(app.stored_files().await, vec![staged_path]);
+    assert_ok_eq!(upstream.crate_exists("foo"), false);
+
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    crates/foo/foo-1.0.0.crate
+    index/3/f/foo
+    rss/crates.xml
+    rss/crates/foo.xml
+    rss/updates.xml
+    ");
+    assert_ok_eq!(upstream.crate_exists("foo"), true);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn staged_crate_file_of_deleted_crate_is_removed() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = token.put::<()>("/api/v1/crates/new", body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut conn = app.db_conn().await;
+    diesel::delete(crates::table)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    app.run_pending_background_jobs().await;
+
+    let files = app.stored_files().await;
+    assert!(!files.iter().any(|path| path.starts_with("staged-crates/")));
+    assert!(!files.iter().any(|path| path.starts_with("crates/")));
+}
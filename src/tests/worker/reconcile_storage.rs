@@ -0,0 +1,142 @@
+use crate::models::StorageDiscrepancy;
+use crate::schema::{storage_discrepancies, versions};
+use crate::tests::builders::CrateBuilder;
+use crate::tests::util::TestApp;
+use crate::worker::jobs::ReconcileStorage;
+use bytes::Bytes;
+use chrono::{TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use insta::assert_snapshot;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reconcile_storage() -> anyhow::Result<()> {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let storage = &app.as_inner().storage;
+
+    let user_id = user.as_model().id;
+    let foo = CrateBuilder::new("foo", user_id)
+        .version("1.0.0")
+        .version("1.1.0")
+        .expect_build(&mut conn)
+        .await;
+
+    // The files of new versions might not have been uploaded yet, so they
+    // are not reported as missing.
+    CrateBuilder::new("baz", user_id)
+        .version("0.1.0")
+        .expect_build(&mut conn)
+        .await;
+
+    diesel::update(versions::table.filter(versions::crate_id.eq(foo.id)))
+        .set(versions::created_at.eq(Utc::now().naive_utc() - TimeDelta::hours(2)))
+        .execute(&mut conn)
+        .await?;
+
+    storage
+        .upload_crate_file("foo", "1.0.0", Bytes::new())
+        .await?;
+    storage.sync_index("foo", Some("{}".into())).await?;
+
+    // The files of a crate that does not exist in the database anymore.
+    storage
+        .upload_crate_file("bar", "0.1.0", Bytes::new())
+        .await?;
+    storage.sync_index("bar", Some("{}".into())).await?;
+
+    ReconcileStorage.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(discrepancies(&mut conn).await?, @r"
+    orphaned_crate_file crates/bar/bar-0.1.0.crate
+    missing_crate_file crates/foo/foo-1.1.0.crate
+    orphaned_index_file 3/b/bar
+    ");
+
+    // Orphaned files are only deleted if a quarantine period is configured.
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    crates/bar/bar-0.1.0.crate
+    crates/foo/foo-1.0.0.crate
+    index/3/b/bar
+    index/3/f/foo
+    ");
+
+    // Discrepancies that have been fixed in the meantime are removed.
+    storage
+        .upload_crate_file("foo", "1.1.0", Bytes::new())
+        .await?;
+
+    ReconcileStorage.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(discrepancies(&mut conn).await?, @r"
+    orphaned_crate_file crates/bar/bar-0.1.0.crate
+    orphaned_index_file 3/b/bar
+    ");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reconcile_storage_deletes_orphans() -> anyhow::Result<()> {
+    let (app, _, user) = TestApp::full()
+        .with_config(|config| config.storage_orphan_quarantine = Some(Duration::ZERO))
+        .with_user()
+        .await;
+    let mut conn = app.db_conn().await;
+    let storage = &app.as_inner().storage;
+
+    let user_id = user.as_model().id;
+    CrateBuilder::new("foo", user_id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    storage
+        .upload_crate_file("foo", "1.0.0", Bytes::new())
+        .await?;
+    storage.sync_index("foo", Some("{}".into())).await?;
+    storage
+        .upload_crate_file("bar", "0.1.0", Bytes::new())
+        .await?;
+    storage.sync_index("bar", Some("{}".into())).await?;
+
+    // The first run only records the orphaned files…
+    ReconcileStorage.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(discrepancies(&mut conn).await?, @r"
+    orphaned_crate_file crates/bar/bar-0.1.0.crate
+    orphaned_index_file 3/b/bar
+    ");
+    assert_eq!(app.stored_files().await.len(), 4);
+
+    // … and the next run deletes them, once they have been quarantined.
+    ReconcileStorage.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(discrepancies(&mut conn).await?, @"");
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    crates/foo/foo-1.0.0.crate
+    index/3/f/foo
+    ");
+
+    Ok(())
+}
+
+async fn discrepancies(conn: &mut AsyncPgConnection) -> QueryResult<String> {
+    let discrepancies: Vec<StorageDiscrepancy> = storage_discrepancies::table
+        .select(StorageDiscrepancy::as_select())
+        .order((storage_discrepancies::kind, storage_discrepancies::path))
+        .load(conn)
+        .await?;
+
+    Ok(discrepancies
+        .into_iter()
+        .map(|discrepancy| format!("{} {}", discrepancy.kind.name(), discrepancy.path))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
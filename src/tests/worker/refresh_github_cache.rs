@@ -0,0 +1,59 @@
+use crate::github_cache::{self, GitHubRequest};
+use crate::schema::github_cache as cache;
+use crate::tests::util::TestApp;
+use crate::worker::jobs::RefreshGitHubCache;
+use chrono::{TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde_json::{json, Value};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refresh_github_cache() {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    let user = app.db_new_user("user-one-team").await;
+    let user_id = user.as_model().id;
+
+    let used = GitHubRequest::TeamMembership {
+        org_id: 1000,
+        team_id: 2000,
+        login: "user-one-team".to_string(),
+    };
+    let unused = GitHubRequest::TeamMembership {
+        org_id: 1000,
+        team_id: 2001,
+        login: "user-one-team".to_string(),
+    };
+
+    let outdated = json!({ "state": "pending" });
+    github_cache::store(&mut conn, &used, &outdated, user_id)
+        .await
+        .unwrap();
+    github_cache::store(&mut conn, &unused, &outdated, user_id)
+        .await
+        .unwrap();
+
+    diesel::update(cache::table)
+        .set(cache::expires_at.eq(Utc::now()))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    diesel::update(cache::table.find(unused.key()))
+        .set(cache::last_used_at.eq(Utc::now() - TimeDelta::days(8)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    RefreshGitHubCache.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    let entries: Vec<(String, Value)> = cache::table
+        .select((cache::key, cache::value))
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+    assert_eq!(entries, [(used.key(), json!({ "state": "active" }))]);
+}
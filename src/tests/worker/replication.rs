@@ -0,0 +1,68 @@
+use crate::storage::StorageConfig;
+use crate::tests::builders::PublishBuilder;
+use crate::tests::util::{RequestHelper, TestApp};
+use crate::worker::jobs::VerifyReplication;
+use crates_io_worker::BackgroundJob;
+use futures_util::TryStreamExt;
+use insta::assert_snapshot;
+use object_store::path::Path;
+
+async fn standby_files(app: &TestApp) -> Vec<String> {
+    let standby = app.as_inner().standby_storage.as_ref().unwrap();
+    let store = standby.as_inner();
+
+    let list = store.list(None).try_collect::<Vec<_>>().await.unwrap();
+    list.into_iter()
+        .map(|meta| meta.location.to_string())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replicate_published_crates() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| {
+            let mut standby = StorageConfig::in_memory();
+            standby.cdn_prefix = Some("standby.crates.io".to_string());
+            config.standby_storage = Some(standby);
+        })
+        .with_token()
+        .await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    assert_snapshot!(standby_files(&app).await.join("\n"), @r"
+    crates/foo/foo-1.0.0.crate
+    index/3/f/foo
+    ");
+
+    // Files that are missing in the standby region or have been deleted from
+    // the primary storage are repaired by the verification.
+    let standby = app.as_inner().standby_storage.clone().unwrap();
+    let store = standby.as_inner();
+    store
+        .delete(&Path::from("crates/foo/foo-1.0.0.crate"))
+        .await
+        .unwrap();
+    store
+        .put(&Path::from("crates/bar/bar-1.0.0.crate"), "bar".into())
+        .await
+        .unwrap();
+
+    let mut conn = app.db_conn().await;
+    VerifyReplication.enqueue(&mut conn).await.unwrap();
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(standby_files(&app).await.join("\n"), @r"
+    crates/foo/foo-1.0.0.crate
+    index/3/f/foo
+    ");
+
+    let primary = app.as_inner().storage.as_inner();
+    let path = Path::from("crates/foo/foo-1.0.0.crate");
+    let expected = primary.get(&path).await.unwrap().bytes().await.unwrap();
+    let actual = store.get(&path).await.unwrap().bytes().await.unwrap();
+    assert_eq!(actual, expected);
+}
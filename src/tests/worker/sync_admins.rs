@@ -90,6 +90,7 @@ async fn create_user(
             emails::user_id.eq(user_id),
             emails::email.eq(format!("{}@crates.io", name)),
             emails::verified.eq(true),
+            emails::is_primary.eq(true),
         ))
         .execute(conn)
         .await?;
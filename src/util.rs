@@ -1,9 +1,11 @@
 pub use self::io_util::{read_fill, read_le_u32};
 pub use self::request_helpers::*;
 
+pub mod crawlers;
 pub mod diesel;
 pub mod errors;
 mod io_util;
+pub mod redact;
 mod request_helpers;
 pub mod rfc3339;
 pub mod string_excl_null;
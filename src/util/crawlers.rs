@@ -0,0 +1,44 @@
+//! Detection of crawlers and other bots by their `User-Agent` header.
+//!
+//! Bots are served the server-rendered crate pages, since they usually don't
+//! run the frontend, and get a separate rate limit for the list endpoints.
+
+/// Substrings of the lowercase `User-Agent` headers of crawlers and of other
+/// bots, like link preview bots.
+const CRAWLER_USER_AGENTS: &[&str] = &[
+    "bot",
+    "crawler",
+    "spider",
+    "slurp",
+    "facebookexternalhit",
+    "embedly",
+    "skypeuripreview",
+    "whatsapp",
+];
+
+/// Returns `true` if the `User-Agent` header identifies a crawler or another
+/// kind of bot.
+pub fn is_crawler(user_agent: &str) -> bool {
+    let user_agent = user_agent.to_lowercase();
+    CRAWLER_USER_AGENTS
+        .iter()
+        .any(|crawler| user_agent.contains(crawler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_crawler() {
+        assert!(is_crawler("Mozilla/5.0 (compatible; Googlebot/2.1)"));
+        assert!(is_crawler("Mozilla/5.0 (compatible; Yahoo! Slurp)"));
+        assert!(is_crawler("facebookexternalhit/1.1"));
+        assert!(is_crawler("WhatsApp/2.23.20.0"));
+        assert!(!is_crawler(
+            "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Firefox/121.0"
+        ));
+        assert!(!is_crawler("cargo/1.84.0"));
+        assert!(!is_crawler(""));
+    }
+}
@@ -32,8 +32,9 @@ mod json;
 use crate::email::EmailError;
 use crate::util::diesel::is_read_only_error;
 use crates_io_github::GitHubError;
+pub use json::ErrorCode;
 pub use json::TOKEN_FORMAT_ERROR;
-pub(crate) use json::{custom, InsecurelyGeneratedTokenRevoked, TooManyRequests};
+pub(crate) use json::{custom, custom_with_code, InsecurelyGeneratedTokenRevoked, TooManyRequests};
 
 pub type BoxedAppError = Box<dyn AppError>;
 
@@ -53,7 +54,7 @@ pub fn account_locked(reason: &str, until: Option<NaiveDateTime>) -> BoxedAppErr
         .map(|until| format!("This account is locked until {until}. Reason: {reason}"))
         .unwrap_or_else(|| format!("This account is indefinitely locked. Reason: {reason}"));
 
-    custom(StatusCode::FORBIDDEN, detail)
+    custom_with_code(StatusCode::FORBIDDEN, "account-locked", detail)
 }
 
 pub fn forbidden(detail: impl Into<Cow<'static, str>>) -> BoxedAppError {
@@ -74,14 +75,33 @@ pub fn service_unavailable() -> BoxedAppError {
     custom(StatusCode::SERVICE_UNAVAILABLE, "Service unavailable")
 }
 
+/// Returns an error with status 503 for requests that would modify data while
+/// crates.io is in read-only mode
+pub fn read_only_mode() -> BoxedAppError {
+    let detail =
+        "crates.io is currently in read-only mode for maintenance. Please try again later.";
+    custom_with_code(StatusCode::SERVICE_UNAVAILABLE, "read-only-mode", detail)
+}
+
+/// Returns an error with status 413 for request bodies larger than `limit` bytes
+pub fn payload_too_large(limit: usize) -> BoxedAppError {
+    let detail = format!("request body is too large, the maximum is {limit} bytes");
+    custom(StatusCode::PAYLOAD_TOO_LARGE, detail)
+}
+
+/// Returns an error with status 408 for requests that took too long to process
+pub fn request_timeout() -> BoxedAppError {
+    custom(StatusCode::REQUEST_TIMEOUT, "Request timed out")
+}
+
 pub fn crate_not_found(krate: &str) -> BoxedAppError {
     let detail = format!("crate `{krate}` does not exist");
-    custom(StatusCode::NOT_FOUND, detail)
+    custom_with_code(StatusCode::NOT_FOUND, "crate-not-found", detail)
 }
 
 pub fn version_not_found(krate: &str, version: &str) -> BoxedAppError {
     let detail = format!("crate `{krate}` does not have a version `{version}`");
-    custom(StatusCode::NOT_FOUND, detail)
+    custom_with_code(StatusCode::NOT_FOUND, "version-not-found", detail)
 }
 
 // =============================================================================
@@ -146,10 +166,7 @@ impl From<DieselError> for BoxedAppError {
     fn from(err: DieselError) -> BoxedAppError {
         match err {
             DieselError::NotFound => not_found(),
-            e if is_read_only_error(&e) => {
-                let detail = "crates.io is currently in read-only mode for maintenance. Please try again later.";
-                custom(StatusCode::SERVICE_UNAVAILABLE, detail)
-            }
+            e if is_read_only_error(&e) => read_only_mode(),
             DieselError::DatabaseError(DatabaseErrorKind::ClosedConnection, _) => {
                 service_unavailable()
             }
@@ -167,6 +184,7 @@ impl From<EmailError> for BoxedAppError {
                 error!(?error, "Failed to send email");
                 server_error("Failed to send the email")
             }
+            EmailError::DatabaseError(error) => error.into(),
         }
     }
 }
@@ -11,6 +11,13 @@ use crate::rate_limiter::LimitedAction;
 use chrono::NaiveDateTime;
 use http::{header, StatusCode};
 
+/// A stable, machine-readable identifier of an error case.
+///
+/// It is used as the `type` of `application/problem+json` error responses.
+/// Errors without an explicit code are identified by their status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode(pub &'static str);
+
 /// Generates a response with the provided status and description as JSON
 fn json_error(detail: &str, status: StatusCode) -> Response {
     let json = json!({ "errors": [{ "detail": detail }] });
@@ -22,6 +29,20 @@ fn json_error(detail: &str, status: StatusCode) -> Response {
 pub fn custom(status: StatusCode, detail: impl Into<Cow<'static, str>>) -> BoxedAppError {
     Box::new(CustomApiError {
         status,
+        code: None,
+        detail: detail.into(),
+    })
+}
+
+/// Same as [custom], but with an explicit [ErrorCode].
+pub fn custom_with_code(
+    status: StatusCode,
+    code: &'static str,
+    detail: impl Into<Cow<'static, str>>,
+) -> BoxedAppError {
+    Box::new(CustomApiError {
+        status,
+        code: Some(ErrorCode(code)),
         detail: detail.into(),
     })
 }
@@ -29,6 +50,7 @@ pub fn custom(status: StatusCode, detail: impl Into<Cow<'static, str>>) -> Boxed
 #[derive(Debug, Clone)]
 pub struct CustomApiError {
     status: StatusCode,
+    code: Option<ErrorCode>,
     detail: Cow<'static, str>,
 }
 
@@ -40,7 +62,11 @@ impl fmt::Display for CustomApiError {
 
 impl AppError for CustomApiError {
     fn response(&self) -> Response {
-        json_error(&self.detail, self.status)
+        let response = json_error(&self.detail, self.status);
+        match self.code {
+            Some(code) => (Extension(code), response).into_response(),
+            None => response,
+        }
     }
 }
 
@@ -61,6 +87,7 @@ impl AppError for TooManyRequests {
             self.action.error_message()
         );
         let mut response = json_error(&detail, StatusCode::TOO_MANY_REQUESTS);
+        response.extensions_mut().insert(ErrorCode("rate-limited"));
         response.headers_mut().insert(
             header::RETRY_AFTER,
             retry_after
@@ -90,8 +117,9 @@ impl InsecurelyGeneratedTokenRevoked {
 impl AppError for InsecurelyGeneratedTokenRevoked {
     fn response(&self) -> Response {
         let cause = CauseField("insecurely generated, revoked 2020-07".to_string());
+        let code = ErrorCode("token-revoked");
         let response = json_error(&self.to_string(), StatusCode::UNAUTHORIZED);
-        (Extension(cause), response).into_response()
+        (Extension(cause), Extension(code), response).into_response()
     }
 }
 
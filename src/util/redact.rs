@@ -0,0 +1,71 @@
+//! Redaction of sensitive values before they are written to the logs or sent
+//! to Sentry.
+//!
+//! All values that end up in the request logs go through [`redact()`], so that
+//! API tokens, email addresses and secrets in query strings never show up in
+//! them, even if they are part of an error message.
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Query parameters whose values are secret, e.g. the OAuth `code` and
+/// `state` of the login callback.
+static SECRET_QUERY_PARAMS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)([?&](?:code|state|token|api_token|email|secret|password)=)[^&#\s]*").unwrap()
+});
+
+/// API tokens of crates.io, and the GitHub tokens of users.
+static TOKENS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:cio[a-zA-Z0-9]{32}|gh[oprsu]_[a-zA-Z0-9]{36,})\b").unwrap());
+
+static EMAILS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}").unwrap());
+
+/// Replaces all sensitive values in the string with `[REDACTED]`.
+pub fn redact(value: &str) -> Cow<'_, str> {
+    let replacements = [
+        (&*SECRET_QUERY_PARAMS, "${1}[REDACTED]"),
+        (&*TOKENS, REDACTED),
+        (&*EMAILS, REDACTED),
+    ];
+
+    let mut value = Cow::Borrowed(value);
+    for (regex, replacement) in replacements {
+        if regex.is_match(&value) {
+            value = Cow::Owned(regex.replace_all(&value, replacement).into_owned());
+        }
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact("/api/v1/crates/serde"), "/api/v1/crates/serde");
+        assert!(matches!(redact("nothing to see"), Cow::Borrowed(_)));
+
+        assert_eq!(
+            redact("/api/private/session/authorize?code=1234&state=abcd"),
+            "/api/private/session/authorize?code=[REDACTED]&state=[REDACTED]"
+        );
+        assert_eq!(
+            redact("/api/v1/crates?page=2&TOKEN=secret"),
+            "/api/v1/crates?page=2&TOKEN=[REDACTED]"
+        );
+        assert_eq!(
+            redact("invalid token cioAbCdEfGhIjKlMnOpQrStUvWxYz012345"),
+            "invalid token [REDACTED]"
+        );
+        assert_eq!(
+            redact("failed to send an email to foo.bar+baz@example.com"),
+            "failed to send an email to [REDACTED]"
+        );
+    }
+}
@@ -83,7 +83,7 @@ impl ExposeSecret<str> for PlainToken {
     }
 }
 
-fn generate_secure_alphanumeric_string(len: usize) -> String {
+pub(crate) fn generate_secure_alphanumeric_string(len: usize) -> String {
     const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
 
     OsRng
@@ -93,6 +93,12 @@ fn generate_secure_alphanumeric_string(len: usize) -> String {
         .collect()
 }
 
+/// Compares two secrets without leaking the length of their common prefix
+/// through the time the comparison takes.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,4 +122,12 @@ mod tests {
     fn test_parse_no_kind() {
         assert_err!(HashedToken::parse("nokind"));
     }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+        assert!(!constant_time_eq(b"", b"abc"));
+    }
 }
@@ -1,5 +1,6 @@
 use crates_io_env_vars::var;
 use sentry::integrations::tracing::EventFilter;
+use std::io::IsTerminal;
 use tracing::Level;
 use tracing::Metadata;
 use tracing_subscriber::filter::LevelFilter;
@@ -9,7 +10,9 @@ use tracing_subscriber::{prelude::*, EnvFilter, Layer};
 ///
 /// Regular CLI output is influenced by the optional
 /// [`RUST_LOG`](tracing_subscriber::filter::EnvFilter) environment variable
-/// and is showing all `INFO` level events by default.
+/// and is showing all `INFO` level events by default. The output format can be
+/// set to `json` or `compact` by the `RUST_LOG_FORMAT` environment variable,
+/// and defaults to `json` if the output is not a terminal.
 ///
 /// This function also sets up the Sentry error reporting integration for the
 /// `tracing` framework, which is hardcoded to include all `INFO` level events.
@@ -28,8 +31,19 @@ fn init_with_default_level(level: LevelFilter) {
         })
         .unwrap_or_default();
 
-    let log_layer = match log_format.as_deref() {
-        Some("json") => json_subscriber::fmt::layer()
+    // The logs are written as JSON by default if they are not read by a
+    // human, e.g. when they are collected by the log drain in production.
+    let log_format = log_format.unwrap_or_else(|| {
+        let format = if std::io::stdout().is_terminal() {
+            "compact"
+        } else {
+            "json"
+        };
+        format.to_string()
+    });
+
+    let log_layer = match log_format.as_str() {
+        "json" => json_subscriber::fmt::layer()
             .flatten_event(true)
             .with_flat_span_list(true)
             .with_filter(env_filter)
@@ -1,18 +1,32 @@
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
 use secrecy::ExposeSecret;
+use std::fmt;
+use std::time::Duration;
 
 use crate::external_urls::remove_blocked_urls;
 use crate::models::{
-    ApiToken, Category, Crate, CrateOwnerInvitation, CreatedApiToken, Dependency, DependencyKind,
-    Keyword, Owner, ReverseDependency, Team, TopVersions, User, Version, VersionDownload,
-    VersionOwnerAction,
+    AccountExport, AggregateDownload, Announcement, AnnouncementAudience, AnnouncementSeverity,
+    ApiToken, ApiUsageCount, AuditLogAction, AuditLogEntry, BroadcastWarning, Category, Crate,
+    CrateChange, CrateChangeKind, CrateOwnerInvitation, CrateReclaimRequest, CrateRedirect,
+    CrateReport, CrateScore, CrateSettings, CrateTransfer, CreatedApiToken, DbDump, DbDumpKind,
+    Dependency, DependencyKind, DocsRsStatus, Email, FeatureFlag, FeatureOverride, FeaturedCrate,
+    FundingLink, FundingPlatform, IpAccessRule, IpAccessRuleKind, IpBan, Keyword, LinkedIdentity,
+    Organization, OrganizationMember, OrganizationRole, Owner, OwnerRole, PolicyRuleKind,
+    PublishMethod, PublishPolicyRule, PublishRateOverride, ReclaimStatus, RedirectStatus,
+    RegistryStats, ReportCategory, ReportStatus, ReservedCrateName, ReservedPrefix,
+    ReverseDependency, StorageDiscrepancy, StorageDiscrepancyKind, Team, TopVersions, User,
+    UserStats, Version, VersionDownload, VersionOwnerAction,
 };
+use crate::rate_limiter::LimitedAction;
+use crate::storage::Storage;
 use crate::util::rfc3339;
 use crates_io_github as github;
 
 pub mod krate_publish;
 pub use self::krate_publish::{EncodableCrateDependency, PublishMetadata};
 
+pub mod v2;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableCategory {
     pub id: String,
@@ -167,6 +181,49 @@ impl From<VersionDownload> for EncodableVersionDownload {
     }
 }
 
+/// The serialization format for the `AggregateDownload` model.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct EncodableAggregateDownload {
+    pub date: String,
+    pub downloads: i64,
+}
+
+impl From<AggregateDownload> for EncodableAggregateDownload {
+    fn from(download: AggregateDownload) -> Self {
+        Self {
+            date: download.date.to_string(),
+            downloads: download.downloads,
+        }
+    }
+}
+
+/// A crate in the ranking of the `/api/v1/crates/trending` endpoint.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EncodableTrendingCrate {
+    pub name: String,
+    pub description: Option<String>,
+    pub default_version: Option<String>,
+    /// The number of downloads in the current window.
+    pub downloads: i64,
+    /// The number of downloads in the previous window.
+    pub previous_downloads: i64,
+    /// The relative growth of the downloads, e.g. `0.5` for 50% more
+    /// downloads than in the previous window.
+    pub growth: f64,
+}
+
+/// A crate in the ranking of the `/api/v1/crates/most_depended` endpoint.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct EncodableMostDependedCrate {
+    pub name: String,
+    pub description: Option<String>,
+    pub default_version: Option<String>,
+    /// The number of distinct crates that depend on this crate.
+    pub dependents: i32,
+    /// The sum of the recent downloads of all dependent crates.
+    pub weighted_dependents: i64,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableKeyword {
     pub id: String,
@@ -193,6 +250,15 @@ impl From<Keyword> for EncodableKeyword {
     }
 }
 
+/// The serialization format for the rows of the `keyword_aliases` table.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncodableKeywordAlias {
+    pub alias: String,
+    /// The keyword that the alias redirects to.
+    pub keyword: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableCrate {
     pub id: String,
@@ -202,6 +268,9 @@ pub struct EncodableCrate {
     pub versions: Option<Vec<i32>>,
     pub keywords: Option<Vec<String>>,
     pub categories: Option<Vec<String>>,
+    /// The owners of the crate, only included in crate lists if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owners: Option<Vec<EncodableOwner>>,
     pub badges: [(); 0],
     #[serde(with = "rfc3339")]
     pub created_at: NaiveDateTime,
@@ -210,14 +279,29 @@ pub struct EncodableCrate {
     pub recent_downloads: Option<i64>,
     pub default_version: Option<String>,
     pub yanked: bool,
+    /// The minimum supported Rust version of the default version, as
+    /// declared by the `rust-version` field of its manifest.
+    pub msrv: Option<String>,
     // NOTE: Used by shields.io, altering `max_version` requires a PR with shields.io
     pub max_version: String,
     pub newest_version: String, // Most recently updated version, which may not be max
     pub max_stable_version: Option<String>,
+    /// The highest version that is neither yanked nor a pre-release.
+    pub latest_stable: Option<String>,
+    /// The highest pre-release version that is not yanked.
+    pub latest_prerelease: Option<String>,
     pub description: Option<String>,
     pub homepage: Option<String>,
     pub documentation: Option<String>,
     pub repository: Option<String>,
+    /// The time at which an owner of the crate proved that they have push
+    /// access to the `repository`, if it has been verified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository_verified_at: Option<DateTime<Utc>>,
+    /// Links to the funding pages of the crate, only included when a single
+    /// crate is requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub funding_links: Vec<EncodableFundingLink>,
     pub links: EncodableCrateLinks,
     pub exact_match: bool,
 }
@@ -228,6 +312,7 @@ impl EncodableCrate {
         krate: Crate,
         default_version: Option<&str>,
         yanked: Option<bool>,
+        msrv: Option<&str>,
         top_versions: Option<&TopVersions>,
         versions: Option<Vec<i32>>,
         keywords: Option<&[Keyword]>,
@@ -244,6 +329,7 @@ impl EncodableCrate {
             homepage,
             documentation,
             repository,
+            repository_verified_at,
             ..
         } = krate;
         let versions_link = match versions {
@@ -255,6 +341,7 @@ impl EncodableCrate {
         let homepage = remove_blocked_urls(homepage);
         let documentation = remove_blocked_urls(documentation);
         let repository = remove_blocked_urls(repository);
+        let repository_verified_at = repository_verified_at.filter(|_| repository.is_some());
 
         let default_version = default_version.map(ToString::to_string);
         if default_version.is_none() {
@@ -262,6 +349,7 @@ impl EncodableCrate {
             sentry::capture_message(&message, sentry::Level::Info);
         }
         let yanked = yanked.unwrap_or_default();
+        let msrv = msrv.map(ToString::to_string);
 
         let max_version = top_versions
             .and_then(|v| v.highest.as_ref())
@@ -277,6 +365,10 @@ impl EncodableCrate {
             .and_then(|v| v.highest_stable.as_ref())
             .map(|v| v.to_string());
 
+        let latest_prerelease = top_versions
+            .and_then(|v| v.highest_prerelease.as_ref())
+            .map(|v| v.to_string());
+
         // the total number of downloads is eventually consistent, but can lag
         // behind the number of "recent downloads". to hide this inconsistency
         // we will use the "recent downloads" as "total downloads" in case it is
@@ -297,17 +389,23 @@ impl EncodableCrate {
             versions,
             keywords: keyword_ids,
             categories: category_ids,
+            owners: None,
             badges: [],
             default_version,
             yanked,
+            msrv,
             max_version,
             newest_version,
+            latest_stable: max_stable_version.clone(),
             max_stable_version,
+            latest_prerelease,
             documentation,
             homepage,
             exact_match,
             description,
             repository,
+            repository_verified_at,
+            funding_links: Vec::new(),
             links: EncodableCrateLinks {
                 version_downloads: format!("/api/v1/crates/{name}/downloads"),
                 versions: versions_link,
@@ -319,10 +417,12 @@ impl EncodableCrate {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn from_minimal(
         krate: Crate,
         default_version: Option<&str>,
         yanked: Option<bool>,
+        msrv: Option<&str>,
         top_versions: Option<&TopVersions>,
         exact_match: bool,
         downloads: i64,
@@ -332,6 +432,7 @@ impl EncodableCrate {
             krate,
             default_version,
             yanked,
+            msrv,
             top_versions,
             None,
             None,
@@ -361,18 +462,25 @@ pub struct EncodableOwner {
     pub url: Option<String>,
     pub name: Option<String>,
     pub avatar: Option<String>,
+    /// The role of the owner. Team members can only publish, while the rights
+    /// of organization members depend on their role in the organization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<OwnerRole>,
 }
 
 impl From<Owner> for EncodableOwner {
     fn from(owner: Owner) -> Self {
         match owner {
-            Owner::User(User {
-                id,
-                name,
-                gh_login,
-                gh_avatar,
-                ..
-            }) => {
+            Owner::User(
+                User {
+                    id,
+                    name,
+                    gh_login,
+                    gh_avatar,
+                    ..
+                },
+                role,
+            ) => {
                 let url = format!("https://github.com/{gh_login}");
                 Self {
                     id,
@@ -381,6 +489,7 @@ impl From<Owner> for EncodableOwner {
                     url: Some(url),
                     name,
                     kind: String::from("user"),
+                    role: Some(role),
                 }
             }
             Owner::Team(Team {
@@ -398,8 +507,490 @@ impl From<Owner> for EncodableOwner {
                     avatar,
                     name,
                     kind: String::from("team"),
+                    role: Some(OwnerRole::Publish),
                 }
             }
+            Owner::Organization(organization) => {
+                let login = organization.owner_login();
+                Self {
+                    id: organization.id,
+                    login,
+                    url: None,
+                    avatar: None,
+                    name: organization.name,
+                    kind: String::from("organization"),
+                    role: None,
+                }
+            }
+        }
+    }
+}
+
+/// The serialization format for the `AuditLogEntry` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableAuditLogEntry {
+    pub id: i64,
+    pub action: AuditLogAction,
+    /// Login of the user that performed the action, if the user still exists.
+    pub user: Option<String>,
+    /// Name of the affected crate, if any.
+    pub crate_name: Option<String>,
+    /// Whether the action was performed with an API token.
+    pub via_api_token: bool,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableAuditLogEntry {
+    pub fn new(entry: AuditLogEntry, user: Option<String>, crate_name: Option<String>) -> Self {
+        Self {
+            id: entry.id,
+            action: entry.action,
+            user,
+            crate_name,
+            via_api_token: entry.api_token_id.is_some(),
+            details: entry.details,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+/// An entry of the public activity timeline of a crate.
+#[derive(Serialize, Debug)]
+pub struct EncodableCrateActivity {
+    pub id: i64,
+    /// One of `publish`, `yank`, `unyank`, `owner_add`, `owner_remove` or
+    /// `transfer_accept`.
+    pub action: AuditLogAction,
+    /// Login of the user that performed the action, if the user still exists.
+    pub user: Option<String>,
+    /// The affected version, for publishes and yanks.
+    pub version: Option<String>,
+    /// The added or removed owner, for ownership changes.
+    pub owner: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableCrateActivity {
+    pub fn new(entry: AuditLogEntry, user: Option<String>) -> Self {
+        let detail = |key: &str| entry.details.get(key).and_then(|v| v.as_str());
+
+        let version = detail("version").map(ToString::to_string);
+        let owner = match entry.action {
+            // Accepted invitations and transfers don't record the owner,
+            // since it is the user that performed the action.
+            AuditLogAction::OwnerAdd | AuditLogAction::TransferAccept => {
+                detail("owner").map(ToString::to_string).or(user.clone())
+            }
+            _ => detail("owner").map(ToString::to_string),
+        };
+
+        Self {
+            id: entry.id,
+            action: entry.action,
+            user,
+            version,
+            owner,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `CrateReport` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableCrateReport {
+    pub id: i32,
+    pub crate_name: String,
+    /// Login of the user that submitted the report, if the user still exists.
+    pub reporter: Option<String>,
+    pub category: ReportCategory,
+    pub description: String,
+    pub contact: Option<String>,
+    pub status: ReportStatus,
+    pub resolution: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Severity score of automated reports, used to prioritize the triage queue.
+    pub score: Option<i32>,
+}
+
+impl EncodableCrateReport {
+    pub fn new(report: CrateReport, crate_name: &str, reporter: Option<&str>) -> Self {
+        Self {
+            id: report.id,
+            crate_name: crate_name.to_string(),
+            reporter: reporter.map(ToString::to_string),
+            category: report.category,
+            description: report.description,
+            contact: report.contact,
+            status: report.status,
+            resolution: report.resolution,
+            created_at: report.created_at,
+            resolved_at: report.resolved_at,
+            score: report.score,
+        }
+    }
+}
+
+/// The serialization format for the `CrateReclaimRequest` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableCrateReclaimRequest {
+    pub id: i32,
+    pub crate_name: String,
+    /// Login of the user that wants to become the owner of the crate.
+    pub requester: String,
+    pub reason: String,
+    pub status: ReclaimStatus,
+    pub decision: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// The date and time from which on the request can be approved.
+    pub eligible_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+impl EncodableCrateReclaimRequest {
+    pub fn new(request: CrateReclaimRequest, crate_name: &str, requester: &str) -> Self {
+        Self {
+            id: request.id,
+            crate_name: crate_name.to_string(),
+            requester: requester.to_string(),
+            eligible_at: request.eligible_at(),
+            reason: request.reason,
+            status: request.status,
+            decision: request.decision,
+            created_at: request.created_at,
+            decided_at: request.decided_at,
+        }
+    }
+}
+
+/// The serialization format for the `CrateRedirect` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableCrateRedirect {
+    pub id: i32,
+    /// Name of the renamed crate.
+    pub name: String,
+    /// Name of the crate that the requests are redirected to.
+    pub target: String,
+    /// Login of the owner that requested the redirect.
+    pub requested_by: Option<String>,
+    pub reason: String,
+    pub status: RedirectStatus,
+    pub decision: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+impl EncodableCrateRedirect {
+    pub fn new(redirect: CrateRedirect, target: &str, requested_by: Option<String>) -> Self {
+        Self {
+            id: redirect.id,
+            name: redirect.name,
+            target: target.to_string(),
+            requested_by,
+            reason: redirect.reason,
+            status: redirect.status,
+            decision: redirect.decision,
+            created_at: redirect.created_at,
+            decided_at: redirect.decided_at,
+        }
+    }
+}
+
+/// The serialization format for the `FeatureFlag` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableFeatureFlag {
+    pub name: String,
+    pub description: Option<String>,
+    /// Whether the feature is switched on for all users.
+    pub enabled: bool,
+    /// Percentage of users for which the feature is switched on, if it is
+    /// not enabled for all users.
+    pub rollout_percentage: i16,
+    pub overrides: Vec<EncodableFeatureOverride>,
+    /// Login of the admin that changed the feature most recently.
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EncodableFeatureFlag {
+    pub fn new(
+        flag: FeatureFlag,
+        updated_by: Option<String>,
+        overrides: Vec<EncodableFeatureOverride>,
+    ) -> Self {
+        Self {
+            name: flag.name,
+            description: flag.description,
+            enabled: flag.enabled,
+            rollout_percentage: flag.rollout_percentage,
+            overrides,
+            updated_by,
+            updated_at: flag.updated_at,
+        }
+    }
+}
+
+/// The serialization format for the `FeatureOverride` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableFeatureOverride {
+    /// Login of the user for which the feature is overridden.
+    pub user: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableFeatureOverride {
+    pub fn new(feature_override: FeatureOverride, user: String) -> Self {
+        Self {
+            user,
+            enabled: feature_override.enabled,
+            created_at: feature_override.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `IpBan` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableIpBan {
+    pub id: i32,
+    pub network: String,
+    /// Number of consecutive bans of the network, which determines the
+    /// duration of the current ban.
+    pub strikes: i32,
+    pub reason: String,
+    pub banned_until: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<IpBan> for EncodableIpBan {
+    fn from(ban: IpBan) -> Self {
+        Self {
+            id: ban.id,
+            network: ban.network,
+            strikes: ban.strikes,
+            reason: ban.reason,
+            banned_until: ban.banned_until,
+            created_at: ban.created_at,
+            updated_at: ban.updated_at,
+        }
+    }
+}
+
+/// The serialization format for the `IpAccessRule` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableIpAccessRule {
+    pub id: i32,
+    pub network: String,
+    pub kind: IpAccessRuleKind,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<IpAccessRule> for EncodableIpAccessRule {
+    fn from(rule: IpAccessRule) -> Self {
+        Self {
+            id: rule.id,
+            network: rule.network,
+            kind: rule.kind,
+            reason: rule.reason,
+            created_at: rule.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `StorageDiscrepancy` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableStorageDiscrepancy {
+    pub kind: StorageDiscrepancyKind,
+    pub path: String,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// The date and time from which on an orphaned file is deleted by the
+    /// next run of the background job, if a quarantine period is configured.
+    pub deletable_at: Option<DateTime<Utc>>,
+}
+
+impl EncodableStorageDiscrepancy {
+    pub fn new(discrepancy: StorageDiscrepancy, quarantine: Option<Duration>) -> Self {
+        let deletable_at = match discrepancy.kind {
+            StorageDiscrepancyKind::OrphanedCrateFile
+            | StorageDiscrepancyKind::OrphanedIndexFile => quarantine
+                .and_then(|quarantine| TimeDelta::from_std(quarantine).ok())
+                .map(|quarantine| discrepancy.first_seen_at + quarantine),
+            _ => None,
+        };
+
+        Self {
+            kind: discrepancy.kind,
+            path: discrepancy.path,
+            first_seen_at: discrepancy.first_seen_at,
+            last_seen_at: discrepancy.last_seen_at,
+            deletable_at,
+        }
+    }
+}
+
+/// The serialization format for the `FeaturedCrate` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableFeaturedCrate {
+    pub id: i32,
+    pub crate_name: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableFeaturedCrate {
+    pub fn new(featured: FeaturedCrate, crate_name: String) -> Self {
+        Self {
+            id: featured.id,
+            crate_name,
+            starts_at: featured.starts_at,
+            ends_at: featured.ends_at,
+            note: featured.note,
+            created_at: featured.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `FundingLink` model.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct EncodableFundingLink {
+    pub platform: FundingPlatform,
+    pub url: String,
+}
+
+impl From<FundingLink> for EncodableFundingLink {
+    fn from(link: FundingLink) -> Self {
+        Self {
+            platform: link.platform,
+            url: link.url,
+        }
+    }
+}
+
+/// The serialization format for the `CrateChange` model.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct EncodableCrateChange {
+    pub seq: i64,
+    pub kind: CrateChangeKind,
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub version: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<CrateChange> for EncodableCrateChange {
+    fn from(change: CrateChange) -> Self {
+        Self {
+            seq: change.seq,
+            kind: change.kind,
+            krate: change.crate_name,
+            version: change.version,
+            created_at: change.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `CrateScore` model.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct EncodableCrateScore {
+    /// The composite score between 0 and 1.
+    pub score: f64,
+    pub signals: EncodableCrateScoreSignals,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The individual signals that make up the composite score of a crate.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct EncodableCrateScoreSignals {
+    /// Whether the default version was built successfully on docs.rs, or
+    /// the crate has a documentation URL.
+    pub has_documentation: bool,
+    pub has_repository: bool,
+    pub has_readme: bool,
+    /// Whether a version that is not yanked was published in the last 6
+    /// months.
+    pub recent_release: bool,
+    /// Whether a version that is not yanked was published in the last 2
+    /// years.
+    pub maintained: bool,
+    /// The share of the versions that are yanked, between 0 and 1.
+    pub yanked_ratio: f64,
+}
+
+impl From<CrateScore> for EncodableCrateScore {
+    fn from(score: CrateScore) -> Self {
+        Self {
+            score: score.score,
+            signals: EncodableCrateScoreSignals {
+                has_documentation: score.has_documentation,
+                has_repository: score.has_repository,
+                has_readme: score.has_readme,
+                recent_release: score.recent_release,
+                maintained: score.maintained,
+                yanked_ratio: score.yanked_ratio,
+            },
+            updated_at: score.updated_at,
+        }
+    }
+}
+
+/// The serialization format for the `CrateSettings` model.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct EncodableCrateSettings {
+    /// Whether the crate is included in the weekly digest emails of its
+    /// owners.
+    pub digest_emails: bool,
+    /// Whether the files of new versions are compared to the previous
+    /// release.
+    pub version_diffs: bool,
+    /// Whether the crate is an internal or test crate, which is excluded
+    /// from the default crate search.
+    pub internal: bool,
+}
+
+impl From<CrateSettings> for EncodableCrateSettings {
+    fn from(settings: CrateSettings) -> Self {
+        Self {
+            digest_emails: settings.digest_emails,
+            version_diffs: settings.version_diffs,
+            internal: settings.internal,
+        }
+    }
+}
+
+/// The serialization format for the `CrateTransfer` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableCrateTransfer {
+    pub id: i32,
+    pub crate_name: String,
+    pub requested_by: EncodablePublicUser,
+    pub recipient: EncodablePublicUser,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+}
+
+impl EncodableCrateTransfer {
+    pub fn new(
+        transfer: CrateTransfer,
+        crate_name: &str,
+        requested_by: &User,
+        recipient: &User,
+        expires_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: transfer.id,
+            crate_name: crate_name.to_string(),
+            requested_by: requested_by.clone().into(),
+            recipient: recipient.clone().into(),
+            created_at: transfer.created_at,
+            expires_at,
+            accepted_at: transfer.accepted_at,
         }
     }
 }
@@ -454,6 +1045,165 @@ impl From<CreatedApiToken> for EncodableApiTokenWithToken {
     }
 }
 
+/// The serialization format for the `AccountExport` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableAccountExport {
+    pub id: i32,
+    /// Either `pending` or `ready`.
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The secret download URL, once the export is ready.
+    pub url: Option<String>,
+}
+
+impl From<AccountExport> for EncodableAccountExport {
+    fn from(export: AccountExport) -> Self {
+        let url = export
+            .is_completed()
+            .then(|| format!("/api/v1/me/export/{}", export.token.expose_secret()));
+
+        let state = if export.is_completed() {
+            "ready"
+        } else {
+            "pending"
+        };
+
+        EncodableAccountExport {
+            id: export.id,
+            state: state.into(),
+            created_at: export.created_at,
+            completed_at: export.completed_at,
+            expires_at: export.expires_at,
+            url,
+        }
+    }
+}
+
+/// The serialization format for the `DbDump` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableDbDump {
+    pub kind: DbDumpKind,
+    pub url: String,
+    /// The size of the archive in bytes.
+    pub size: i64,
+    /// The hex-encoded SHA256 checksum of the archive.
+    pub checksum: String,
+    /// For incremental dumps, the time of the previous dump.
+    pub since: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableDbDump {
+    pub fn from(dump: DbDump, storage: &Storage) -> Self {
+        Self {
+            kind: dump.kind,
+            url: storage.db_dump_location(&dump.path),
+            size: dump.size,
+            checksum: dump.checksum,
+            since: dump.since,
+            created_at: dump.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `RegistryStats` model.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct EncodableRegistryStats {
+    pub date: NaiveDate,
+    /// The number of crates that existed at the end of the day.
+    pub total_crates: i64,
+    /// The number of versions that existed at the end of the day.
+    pub total_versions: i64,
+    /// The number of versions that were published on this day.
+    pub publishes: i64,
+    /// The number of downloads of all versions on this day.
+    pub downloads: i64,
+}
+
+impl From<RegistryStats> for EncodableRegistryStats {
+    fn from(stats: RegistryStats) -> Self {
+        let RegistryStats {
+            date,
+            total_crates,
+            total_versions,
+            publishes,
+            downloads,
+        } = stats;
+
+        Self {
+            date,
+            total_crates,
+            total_versions,
+            publishes,
+            downloads,
+        }
+    }
+}
+
+/// The serialization format for the `ApiUsageCount` model.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct EncodableApiUsage {
+    pub date: NaiveDate,
+    /// `cookie` or `token` for the requests of a user, and `download` or
+    /// `metadata` for the requests about a crate.
+    pub kind: String,
+    /// The number of requests on this day.
+    pub count: i64,
+}
+
+impl From<ApiUsageCount> for EncodableApiUsage {
+    fn from(usage: ApiUsageCount) -> Self {
+        let ApiUsageCount { date, kind, count } = usage;
+        Self { date, kind, count }
+    }
+}
+
+/// The serialization format for the `Email` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableEmail {
+    pub id: i32,
+    pub email: String,
+    pub verified: bool,
+    pub verification_sent: bool,
+    pub primary: bool,
+}
+
+impl From<Email> for EncodableEmail {
+    fn from(email: Email) -> Self {
+        EncodableEmail {
+            id: email.id,
+            email: email.email,
+            verified: email.verified,
+            verification_sent: email.token_generated_at.is_some(),
+            primary: email.is_primary,
+        }
+    }
+}
+
+/// The serialization format for the `LinkedIdentity` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableLinkedIdentity {
+    pub id: i32,
+    pub provider: String,
+    pub login: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<LinkedIdentity> for EncodableLinkedIdentity {
+    fn from(identity: LinkedIdentity) -> Self {
+        EncodableLinkedIdentity {
+            id: identity.id,
+            provider: identity.provider,
+            login: identity.login,
+            email: identity.email,
+            created_at: identity.created_at,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct OwnedCrate {
     pub id: i32,
@@ -482,6 +1232,10 @@ pub struct EncodablePrivateUser {
     pub url: Option<String>,
     pub is_admin: bool,
     pub publish_notifications: bool,
+    pub locale: Option<String>,
+    pub weekly_digest: bool,
+    pub contact_requests: bool,
+    pub public_stats: bool,
 }
 
 impl EncodablePrivateUser {
@@ -499,6 +1253,10 @@ impl EncodablePrivateUser {
             gh_avatar,
             is_admin,
             publish_notifications,
+            locale,
+            weekly_digest,
+            contact_requests,
+            public_stats,
             ..
         } = user;
         let url = format!("https://github.com/{gh_login}");
@@ -514,6 +1272,10 @@ impl EncodablePrivateUser {
             url: Some(url),
             is_admin,
             publish_notifications,
+            locale,
+            weekly_digest,
+            contact_requests,
+            public_stats,
         }
     }
 }
@@ -550,6 +1312,311 @@ impl From<User> for EncodablePublicUser {
     }
 }
 
+/// The serialization format for the `UserStats` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableUserStats {
+    /// Total number of downloads of all crates that the user owns.
+    pub total_downloads: i64,
+    /// Number of versions that the user published per year.
+    pub publishes_per_year: serde_json::Value,
+    /// Users that most frequently own crates together with the user.
+    pub co_owners: serde_json::Value,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<UserStats> for EncodableUserStats {
+    fn from(stats: UserStats) -> Self {
+        Self {
+            total_downloads: stats.total_downloads,
+            publishes_per_year: stats.publishes_per_year,
+            co_owners: stats.co_owners,
+            updated_at: stats.updated_at,
+        }
+    }
+}
+
+/// The serialization format for the `User` model in the admin console.
+///
+/// Contains everything administrators need to look into an account, including
+/// all email addresses, the account lock and the rate limit overrides.
+#[derive(Serialize, Debug)]
+pub struct EncodableAdminUser {
+    pub id: i32,
+    pub login: String,
+    pub name: Option<String>,
+    pub avatar: Option<String>,
+    pub url: String,
+    pub is_admin: bool,
+    pub emails: Vec<EncodableEmail>,
+    pub lock: Option<EncodableAccountLock>,
+    pub rate_limit_overrides: Vec<EncodableRateLimitOverride>,
+}
+
+impl EncodableAdminUser {
+    pub fn from(user: User, emails: Vec<Email>, overrides: Vec<PublishRateOverride>) -> Self {
+        let lock = user.account_lock_reason.map(|reason| EncodableAccountLock {
+            reason,
+            until: user.account_lock_until,
+        });
+
+        EncodableAdminUser {
+            id: user.id,
+            url: format!("https://github.com/{}", user.gh_login),
+            login: user.gh_login,
+            name: user.name,
+            avatar: user.gh_avatar,
+            is_admin: user.is_admin,
+            emails: emails.into_iter().map(EncodableEmail::from).collect(),
+            lock,
+            rate_limit_overrides: overrides
+                .into_iter()
+                .map(EncodableRateLimitOverride::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableAccountLock {
+    pub reason: String,
+    #[serde(with = "rfc3339::option")]
+    pub until: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableRateLimitOverride {
+    pub action: LimitedAction,
+    pub burst: i32,
+    #[serde(with = "rfc3339::option")]
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl From<PublishRateOverride> for EncodableRateLimitOverride {
+    fn from(rate_override: PublishRateOverride) -> Self {
+        EncodableRateLimitOverride {
+            action: rate_override.action,
+            burst: rate_override.burst,
+            expires_at: rate_override.expires_at,
+        }
+    }
+}
+
+/// The moderation state of a crate in the admin console.
+#[derive(Serialize, Debug)]
+pub struct EncodableCrateModeration {
+    pub name: String,
+    pub hidden_at: Option<DateTime<Utc>>,
+    pub frozen_at: Option<DateTime<Utc>>,
+    pub quarantined_at: Option<DateTime<Utc>>,
+    /// Versions that have been soft-deleted by the crates.io team, most
+    /// recently deleted first.
+    pub deleted_versions: Vec<EncodableDeletedVersion>,
+}
+
+/// A soft-deleted version in the moderation state of a crate.
+#[derive(Serialize, Debug)]
+pub struct EncodableDeletedVersion {
+    pub num: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// The serialization format for the `Organization` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableOrganization {
+    pub id: i32,
+    pub login: String,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Organization> for EncodableOrganization {
+    fn from(organization: Organization) -> Self {
+        EncodableOrganization {
+            id: organization.id,
+            login: organization.login,
+            name: organization.name,
+            created_at: organization.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `OrganizationMember` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableOrganizationMember {
+    pub user: EncodablePublicUser,
+    pub role: OrganizationRole,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<(OrganizationMember, User)> for EncodableOrganizationMember {
+    fn from((member, user): (OrganizationMember, User)) -> Self {
+        EncodableOrganizationMember {
+            user: user.into(),
+            role: member.role,
+            created_at: member.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `PublishPolicyRule` model.
+#[derive(Serialize, Debug)]
+pub struct EncodablePublishPolicyRule {
+    pub id: i32,
+    pub kind: PolicyRuleKind,
+    pub value: String,
+    /// Additional explanation that is shown to users when their crate
+    /// violates the rule.
+    pub message: Option<String>,
+    /// Login of the admin that created the rule.
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Date and time from which on the rule is enforced, or `null` if it is
+    /// enforced already.
+    pub enforced_at: Option<DateTime<Utc>>,
+}
+
+impl EncodablePublishPolicyRule {
+    pub fn new(rule: PublishPolicyRule, created_by: Option<String>) -> Self {
+        Self {
+            id: rule.id,
+            kind: rule.kind,
+            value: rule.value,
+            message: rule.message,
+            created_by,
+            created_at: rule.created_at,
+            updated_at: rule.updated_at,
+            enforced_at: rule.enforced_at,
+        }
+    }
+}
+
+/// The serialization format for the `BroadcastWarning` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableBroadcastWarning {
+    pub id: i32,
+    pub message: String,
+    pub docs_url: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Login of the admin that created the warning.
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableBroadcastWarning {
+    pub fn new(warning: BroadcastWarning, created_by: Option<String>) -> Self {
+        Self {
+            id: warning.id,
+            message: warning.message,
+            docs_url: warning.docs_url,
+            expires_at: warning.expires_at,
+            created_by,
+            created_at: warning.created_at,
+        }
+    }
+}
+
+/// The public serialization format for the `Announcement` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableAnnouncement {
+    pub id: i32,
+    pub message: String,
+    pub docs_url: Option<String>,
+    pub severity: AnnouncementSeverity,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl From<Announcement> for EncodableAnnouncement {
+    fn from(announcement: Announcement) -> Self {
+        Self {
+            id: announcement.id,
+            message: announcement.message,
+            docs_url: announcement.docs_url,
+            severity: announcement.severity,
+            starts_at: announcement.starts_at,
+            ends_at: announcement.ends_at,
+        }
+    }
+}
+
+/// The serialization format for the `Announcement` model in the admin
+/// endpoints, which includes the audience and the creator.
+#[derive(Serialize, Debug)]
+pub struct EncodableAdminAnnouncement {
+    pub id: i32,
+    pub message: String,
+    pub docs_url: Option<String>,
+    pub severity: AnnouncementSeverity,
+    pub audience: AnnouncementAudience,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Login of the admin that created the announcement.
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EncodableAdminAnnouncement {
+    pub fn new(announcement: Announcement, created_by: Option<String>) -> Self {
+        Self {
+            id: announcement.id,
+            message: announcement.message,
+            docs_url: announcement.docs_url,
+            severity: announcement.severity,
+            audience: announcement.audience,
+            starts_at: announcement.starts_at,
+            ends_at: announcement.ends_at,
+            created_by,
+            created_at: announcement.created_at,
+            updated_at: announcement.updated_at,
+        }
+    }
+}
+
+/// The serialization format for the `ReservedCrateName` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableReservedCrateName {
+    pub name: String,
+    pub reason: Option<String>,
+    /// Login of the admin that reserved the name.
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableReservedCrateName {
+    pub fn new(reserved: ReservedCrateName, created_by: Option<String>) -> Self {
+        Self {
+            name: reserved.name,
+            reason: reserved.reason,
+            created_by,
+            created_at: reserved.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `ReservedPrefix` model.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EncodableReservedPrefix {
+    pub id: i32,
+    pub prefix: String,
+    pub organization: String,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableReservedPrefix {
+    pub fn new(reserved: ReservedPrefix, organization: String) -> Self {
+        Self {
+            id: reserved.id,
+            prefix: reserved.prefix,
+            organization,
+            approved_at: reserved.approved_at,
+            created_at: reserved.created_at,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct EncodableAuditAction {
     pub action: String,
@@ -581,6 +1648,9 @@ pub struct EncodableVersion {
     pub links: EncodableVersionLinks,
     pub crate_size: i32,
     pub published_by: Option<EncodablePublicUser>,
+    /// How the version was published, or `None` if the version was published
+    /// before crates.io started recording it.
+    pub publish_method: Option<PublishMethod>,
     pub audit_actions: Vec<EncodableAuditAction>,
     pub checksum: String,
     pub rust_version: Option<String>,
@@ -591,6 +1661,9 @@ pub struct EncodableVersion {
     pub homepage: Option<String>,
     pub documentation: Option<String>,
     pub repository: Option<String>,
+    /// The result of the most recent docs.rs build, or `None` if no build
+    /// result has been received yet.
+    pub docs_rs: Option<EncodableDocsRsBuild>,
 }
 
 impl EncodableVersion {
@@ -621,9 +1694,21 @@ impl EncodableVersion {
             homepage,
             documentation,
             repository,
+            docs_rs_status,
+            docs_rs_doc_coverage,
+            docs_rs_built_at,
+            publish_method,
             ..
         } = version;
 
+        let docs_rs = docs_rs_status
+            .zip(docs_rs_built_at)
+            .map(|(status, built_at)| EncodableDocsRsBuild {
+                status,
+                doc_coverage: docs_rs_doc_coverage,
+                built_at,
+            });
+
         let links = EncodableVersionLinks {
             dependencies: format!("/api/v1/crates/{crate_name}/{num}/dependencies"),
             version_downloads: format!("/api/v1/crates/{crate_name}/{num}/downloads"),
@@ -655,7 +1740,9 @@ impl EncodableVersion {
             homepage,
             documentation,
             repository,
+            docs_rs,
             published_by: published_by.map(User::into),
+            publish_method,
             audit_actions: audit_actions
                 .into_iter()
                 .map(|(audit_action, user)| EncodableAuditAction {
@@ -668,6 +1755,14 @@ impl EncodableVersion {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncodableDocsRsBuild {
+    pub status: DocsRsStatus,
+    /// The percentage of documented items, if reported by docs.rs.
+    pub doc_coverage: Option<f32>,
+    pub built_at: DateTime<Utc>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableVersionLinks {
     pub dependencies: String,
@@ -686,7 +1781,41 @@ pub struct GoodCrate {
 pub struct PublishWarnings {
     pub invalid_categories: Vec<String>,
     pub invalid_badges: Vec<String>,
+    /// The messages of the `structured` warnings, which are shown by cargo.
     pub other: Vec<String>,
+    /// The warnings with their code and category, for clients that want to
+    /// handle them individually.
+    #[serde(default)]
+    pub structured: Vec<PublishWarning>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PublishWarning {
+    /// Identifier of the kind of warning, e.g. `missing-repository`.
+    pub code: String,
+    pub category: PublishWarningCategory,
+    pub message: String,
+    /// URL of a page with more information about the warning.
+    pub docs_url: Option<String>,
+}
+
+impl fmt::Display for PublishWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.docs_url {
+            Some(docs_url) => write!(f, "{} (see {docs_url})", self.message),
+            None => self.message.fmt(f),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishWarningCategory {
+    License,
+    Metadata,
+    Size,
+    Policy,
+    Announcement,
 }
 
 #[cfg(test)]
@@ -784,11 +1913,13 @@ mod tests {
             has_lib: None,
             bin_names: None,
             published_by: None,
+            publish_method: None,
             edition: None,
             description: None,
             homepage: None,
             documentation: None,
             repository: None,
+            docs_rs: None,
             audit_actions: vec![EncodableAuditAction {
                 action: "publish".to_string(),
                 user: EncodablePublicUser {
@@ -826,6 +1957,7 @@ mod tests {
             versions: None,
             keywords: None,
             categories: None,
+            owners: None,
             badges: [],
             created_at: NaiveDate::from_ymd_opt(2017, 1, 6)
                 .unwrap()
@@ -835,13 +1967,18 @@ mod tests {
             recent_downloads: None,
             default_version: None,
             yanked: false,
+            msrv: None,
             max_version: "".to_string(),
             newest_version: "".to_string(),
             max_stable_version: None,
+            latest_stable: None,
+            latest_prerelease: None,
             description: None,
             homepage: None,
             documentation: None,
             repository: None,
+            repository_verified_at: None,
+            funding_links: Vec::new(),
             links: EncodableCrateLinks {
                 version_downloads: "".to_string(),
                 versions: None,
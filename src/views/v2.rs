@@ -0,0 +1,279 @@
+//! Views for the `/api/v2/` endpoints.
+//!
+//! These are converted from the corresponding `/api/v1/` views, so that both
+//! API versions share the same controllers and database queries. Compared to
+//! version 1, the responses use a consistent envelope:
+//!
+//! - the requested resource is always returned in the `data` field, which
+//!   avoids the `crate` keyword collision of the version 1 responses,
+//! - related resources are returned in the `included` field,
+//! - paginated lists always contain a `meta` field with the `total` count
+//!   and the `next_page` query string,
+//! - all timestamps are serialized as RFC 3339 timestamps in UTC.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::views::{
+    EncodableAuditAction, EncodableCategory, EncodableCrate, EncodableCrateLinks,
+    EncodableDocsRsBuild, EncodableFundingLink, EncodableKeyword, EncodablePublicUser,
+    EncodableVersion, EncodableVersionLinks,
+};
+
+/// A single resource, with optional related resources.
+#[derive(Serialize, Debug)]
+pub struct Document<T, I = ()> {
+    pub data: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub included: Option<I>,
+}
+
+impl<T> Document<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            included: None,
+        }
+    }
+}
+
+/// A paginated list of resources, with optional related resources.
+#[derive(Serialize, Debug)]
+pub struct ListDocument<T, I = ()> {
+    pub data: Vec<T>,
+    pub meta: PaginationMeta,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub included: Option<I>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PaginationMeta {
+    pub total: i64,
+    pub next_page: Option<String>,
+}
+
+fn utc(dt: NaiveDateTime) -> DateTime<Utc> {
+    dt.and_utc()
+}
+
+#[derive(Serialize, Debug)]
+pub struct Crate {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub downloads: i64,
+    pub recent_downloads: Option<i64>,
+    pub default_version: Option<String>,
+    pub yanked: bool,
+    pub msrv: Option<String>,
+    pub max_version: String,
+    pub newest_version: String,
+    pub max_stable_version: Option<String>,
+    pub latest_stable: Option<String>,
+    pub latest_prerelease: Option<String>,
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub documentation: Option<String>,
+    pub repository: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub categories: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub funding_links: Vec<EncodableFundingLink>,
+    pub links: EncodableCrateLinks,
+}
+
+impl From<EncodableCrate> for Crate {
+    fn from(krate: EncodableCrate) -> Self {
+        let links = EncodableCrateLinks {
+            versions: Some(format!("/api/v2/crates/{}/versions", krate.name)),
+            ..krate.links
+        };
+
+        Self {
+            id: krate.id,
+            name: krate.name,
+            created_at: utc(krate.created_at),
+            updated_at: utc(krate.updated_at),
+            downloads: krate.downloads,
+            recent_downloads: krate.recent_downloads,
+            default_version: krate.default_version,
+            yanked: krate.yanked,
+            msrv: krate.msrv,
+            max_version: krate.max_version,
+            newest_version: krate.newest_version,
+            max_stable_version: krate.max_stable_version,
+            latest_stable: krate.latest_stable,
+            latest_prerelease: krate.latest_prerelease,
+            description: krate.description,
+            homepage: krate.homepage,
+            documentation: krate.documentation,
+            repository: krate.repository,
+            keywords: krate.keywords,
+            categories: krate.categories,
+            funding_links: krate.funding_links,
+            links,
+        }
+    }
+}
+
+/// The related resources of a [Crate], depending on the `include` query
+/// parameter of the request.
+#[derive(Serialize, Debug)]
+pub struct CrateIncluded {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub versions: Option<Vec<Version>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<Keyword>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<Category>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Version {
+    pub id: i32,
+    pub crate_name: String,
+    pub num: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub downloads: i32,
+    pub features: serde_json::Value,
+    pub yanked: bool,
+    pub yank_message: Option<String>,
+    pub lib_links: Option<String>,
+    pub license: Option<String>,
+    pub crate_size: i32,
+    pub checksum: String,
+    pub rust_version: Option<String>,
+    pub has_lib: Option<bool>,
+    pub bin_names: Option<Vec<Option<String>>>,
+    pub edition: Option<String>,
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub documentation: Option<String>,
+    pub repository: Option<String>,
+    pub published_by: Option<EncodablePublicUser>,
+    pub audit_actions: Vec<AuditAction>,
+    pub docs_rs: Option<EncodableDocsRsBuild>,
+    pub dl_path: String,
+    pub readme_path: String,
+    pub links: EncodableVersionLinks,
+}
+
+impl From<EncodableVersion> for Version {
+    fn from(version: EncodableVersion) -> Self {
+        Self {
+            id: version.id,
+            crate_name: version.krate,
+            num: version.num,
+            created_at: utc(version.created_at),
+            updated_at: utc(version.updated_at),
+            downloads: version.downloads,
+            features: version.features,
+            yanked: version.yanked,
+            yank_message: version.yank_message,
+            lib_links: version.lib_links,
+            license: version.license,
+            crate_size: version.crate_size,
+            checksum: version.checksum,
+            rust_version: version.rust_version,
+            has_lib: version.has_lib,
+            bin_names: version.bin_names,
+            edition: version.edition,
+            description: version.description,
+            homepage: version.homepage,
+            documentation: version.documentation,
+            repository: version.repository,
+            published_by: version.published_by,
+            audit_actions: version.audit_actions.into_iter().map(Into::into).collect(),
+            docs_rs: version.docs_rs,
+            dl_path: version.dl_path,
+            readme_path: version.readme_path,
+            links: version.links,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuditAction {
+    pub action: String,
+    pub user: EncodablePublicUser,
+    pub time: DateTime<Utc>,
+}
+
+impl From<EncodableAuditAction> for AuditAction {
+    fn from(action: EncodableAuditAction) -> Self {
+        Self {
+            action: action.action,
+            user: action.user,
+            time: utc(action.time),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Keyword {
+    pub id: String,
+    pub keyword: String,
+    pub created_at: DateTime<Utc>,
+    pub crates_cnt: i32,
+}
+
+impl From<EncodableKeyword> for Keyword {
+    fn from(keyword: EncodableKeyword) -> Self {
+        Self {
+            id: keyword.id,
+            keyword: keyword.keyword,
+            created_at: utc(keyword.created_at),
+            crates_cnt: keyword.crates_cnt,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Category {
+    pub id: String,
+    pub category: String,
+    pub slug: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub crates_cnt: i32,
+}
+
+impl From<EncodableCategory> for Category {
+    fn from(category: EncodableCategory) -> Self {
+        Self {
+            id: category.id,
+            category: category.category,
+            slug: category.slug,
+            description: category.description,
+            created_at: utc(category.created_at),
+            crates_cnt: category.crates_cnt,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn timestamps_are_serialized_in_utc() {
+        let created_at = NaiveDate::from_ymd_opt(2017, 1, 6)
+            .unwrap()
+            .and_hms_milli_opt(14, 23, 11, 123)
+            .unwrap();
+
+        let keyword = Keyword::from(EncodableKeyword {
+            id: "foo".to_string(),
+            keyword: "foo".to_string(),
+            created_at,
+            crates_cnt: 1,
+        });
+        let json = serde_json::to_string(&Document::new(keyword)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"data":{"id":"foo","keyword":"foo","created_at":"2017-01-06T14:23:11.123Z","crates_cnt":1}}"#
+        );
+    }
+}
@@ -1,12 +1,16 @@
 use crate::cloudfront::CloudFront;
 use crate::fastly::Fastly;
+use crate::models::{Cdn, CdnInvalidation};
 use crate::storage::Storage;
 use crate::typosquat;
+use crate::worker::jobs::ProcessCdnInvalidationQueue;
 use crate::Emails;
 use anyhow::Context;
 use bon::Builder;
+use crates_io_github::GitHubClient;
 use crates_io_index::{Repository, RepositoryConfig};
 use crates_io_team_repo::TeamRepo;
+use crates_io_worker::BackgroundJob;
 use diesel_async::pooled_connection::deadpool::Pool;
 use diesel_async::AsyncPgConnection;
 use object_store::ObjectStore;
@@ -26,10 +30,18 @@ pub struct Environment {
     cloudfront: Option<CloudFront>,
     fastly: Option<Fastly>,
     pub storage: Arc<Storage>,
+    /// The storage of the standby region that crate files and index files
+    /// are replicated to, if configured.
+    pub standby_storage: Option<Arc<Storage>>,
     pub downloads_archive_store: Option<Box<dyn ObjectStore>>,
     pub deadpool: Pool<AsyncPgConnection>,
     pub emails: Emails,
     pub team_repo: Box<dyn TeamRepo + Send + Sync>,
+    /// The GitHub client used to refresh the cached GitHub responses.
+    pub github: Arc<dyn GitHubClient>,
+    /// The HTTP client used to deliver the follow notification webhooks.
+    #[builder(default)]
+    pub http_client: reqwest::Client,
 
     /// A lazily initialised cache of the most popular crates ready to use in typosquatting checks.
     #[builder(skip)]
@@ -77,6 +89,54 @@ impl Environment {
         Ok(())
     }
 
+    /// Queue a file for invalidation in all registered CDNs.
+    ///
+    /// The queued paths are invalidated in batches by the
+    /// [`ProcessCdnInvalidationQueue`] job, which avoids the rate limits of
+    /// the CDN APIs when many files change at the same time.
+    pub(crate) async fn queue_cdn_invalidation(
+        &self,
+        conn: &mut AsyncPgConnection,
+        path: &str,
+    ) -> anyhow::Result<()> {
+        self.queue_invalidation(conn, &[Cdn::CloudFront, Cdn::Fastly], path)
+            .await
+    }
+
+    /// Queue a file for invalidation on CloudFront only.
+    pub(crate) async fn queue_cloudfront_invalidation(
+        &self,
+        conn: &mut AsyncPgConnection,
+        path: &str,
+    ) -> anyhow::Result<()> {
+        self.queue_invalidation(conn, &[Cdn::CloudFront], path)
+            .await
+    }
+
+    async fn queue_invalidation(
+        &self,
+        conn: &mut AsyncPgConnection,
+        cdns: &[Cdn],
+        path: &str,
+    ) -> anyhow::Result<()> {
+        let cdns = cdns.iter().filter(|cdn| match cdn {
+            Cdn::CloudFront => self.cloudfront.is_some(),
+            Cdn::Fastly => self.fastly.is_some(),
+        });
+
+        let mut queued = false;
+        for cdn in cdns {
+            CdnInvalidation::queue(conn, *cdn, &[path]).await?;
+            queued = true;
+        }
+
+        if queued {
+            ProcessCdnInvalidationQueue.enqueue(conn).await?;
+        }
+
+        Ok(())
+    }
+
     /// Returns the typosquatting cache, initialising it if required.
     pub(crate) async fn typosquat_cache(
         &self,
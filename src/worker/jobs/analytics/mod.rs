@@ -0,0 +1,157 @@
+//! Export of crate metadata and download counts as Parquet files, which can
+//! be loaded into BigQuery or other analytics tools without having to query
+//! the crates.io API.
+//!
+//! The files are partitioned by date, using the Hive partitioning scheme:
+//!
+//! ```text
+//! analytics/v1/schema.json
+//! analytics/v1/crates/date=2024-01-01/crates.parquet
+//! analytics/v1/versions/date=2024-01-01/versions.parquet
+//! analytics/v1/version_downloads/date=2024-01-01/version_downloads.parquet
+//! ```
+
+mod parquet;
+mod tables;
+
+use self::parquet::{Column, ParquetWriter};
+use self::tables::{CrateRow, ExportTable, VersionDownloadsRow, VersionRow};
+use crate::tasks::spawn_blocking;
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::{NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel_async::AsyncPgConnection;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// The version of the export file layout and table schemas.
+///
+/// This is part of the file paths, so that consumers don't break when
+/// columns are removed or changed. Adding new columns should also bump the
+/// version, since the schema of the existing partitions is not updated.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The number of rows that are loaded from the database and written to a
+/// single Parquet row group at a time.
+const BATCH_SIZE: i64 = 50_000;
+
+/// Export snapshots of the `crates` and `versions` tables, and the download
+/// counts of the given day as Parquet files to the public bucket.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportAnalytics {
+    date: NaiveDate,
+}
+
+impl ExportAnalytics {
+    pub fn for_date(date: NaiveDate) -> Self {
+        Self { date }
+    }
+}
+
+impl Default for ExportAnalytics {
+    /// Exports the data of yesterday, which is the most recent day with
+    /// complete download counts.
+    fn default() -> Self {
+        Self::for_date(Utc::now().date_naive() - chrono::Duration::days(1))
+    }
+}
+
+impl BackgroundJob for ExportAnalytics {
+    const JOB_NAME: &'static str = "export_analytics";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(date = %self.date), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Exporting analytics data…");
+        export_table::<CrateRow>(&env, &mut conn, self.date).await?;
+        export_table::<VersionRow>(&env, &mut conn, self.date).await?;
+        export_table::<VersionDownloadsRow>(&env, &mut conn, self.date).await?;
+
+        let schema = serde_json::to_vec_pretty(&Schema::current())?;
+        let path = format!("analytics/v{SCHEMA_VERSION}/schema.json");
+        env.storage
+            .upload_analytics_schema(&path, schema.into())
+            .await?;
+
+        info!("Finished exporting analytics data");
+        Ok(())
+    }
+}
+
+/// Returns the path of the Parquet file of a table for the given date.
+fn table_path(table: &str, date: NaiveDate) -> String {
+    format!("analytics/v{SCHEMA_VERSION}/{table}/date={date}/{table}.parquet")
+}
+
+async fn export_table<T: ExportTable>(
+    env: &Environment,
+    conn: &mut AsyncPgConnection,
+    date: NaiveDate,
+) -> anyhow::Result<()> {
+    info!(table = T::NAME, "Exporting table…");
+
+    let tempfile = tempfile::NamedTempFile::new().context("Failed to create temporary file")?;
+    let file = tempfile.reopen()?;
+    let mut writer = ParquetWriter::new(file, T::COLUMNS, SCHEMA_VERSION)?;
+
+    let mut cursor = 0;
+    let mut num_rows = 0;
+    loop {
+        let rows = T::load(conn, date, cursor, BATCH_SIZE).await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+
+        cursor = last.cursor();
+        num_rows += rows.len();
+        let is_last_batch = rows.len() < BATCH_SIZE as usize;
+
+        writer = spawn_blocking(move || {
+            writer.write_row_group(T::into_values(rows))?;
+            Ok::<_, anyhow::Error>(writer)
+        })
+        .await??;
+
+        if is_last_batch {
+            break;
+        }
+    }
+
+    spawn_blocking(move || writer.close()).await??;
+
+    let path = table_path(T::NAME, date);
+    info!(table = T::NAME, num_rows, %path, "Uploading Parquet file…");
+    env.storage
+        .upload_analytics_file(&path, tempfile.path())
+        .await?;
+
+    Ok(())
+}
+
+/// The contents of the `schema.json` file, which describes the columns of
+/// all exported tables.
+#[derive(Debug, Serialize)]
+struct Schema {
+    version: u32,
+    tables: BTreeMap<&'static str, &'static [Column]>,
+}
+
+impl Schema {
+    fn current() -> Self {
+        let tables = [
+            (CrateRow::NAME, CrateRow::COLUMNS),
+            (VersionRow::NAME, VersionRow::COLUMNS),
+            (VersionDownloadsRow::NAME, VersionDownloadsRow::COLUMNS),
+        ];
+
+        Self {
+            version: SCHEMA_VERSION,
+            tables: tables.into_iter().collect(),
+        }
+    }
+}
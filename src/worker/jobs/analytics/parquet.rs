@@ -0,0 +1,234 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use parquet::basic::{Compression, LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DataType, Int32Type, Int64Type};
+use parquet::errors::Result;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedColumnWriter, SerializedFileWriter};
+use parquet::format::{KeyValue, MicroSeconds};
+use parquet::schema::types::Type;
+use std::fs::File;
+use std::sync::Arc;
+
+/// The key of the Parquet file metadata entry that contains the
+/// [`SCHEMA_VERSION`](super::SCHEMA_VERSION) of the export.
+const SCHEMA_VERSION_KEY: &str = "crates_io.schema_version";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    Int32,
+    Int64,
+    Boolean,
+    String,
+    /// Microseconds since the Unix epoch, in UTC.
+    Timestamp,
+    /// Days since the Unix epoch.
+    Date,
+}
+
+/// The definition of a column in an exported table.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Column {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub ty: ColumnType,
+    pub nullable: bool,
+}
+
+impl Column {
+    pub const fn required(name: &'static str, ty: ColumnType) -> Self {
+        Self {
+            name,
+            ty,
+            nullable: false,
+        }
+    }
+
+    pub const fn nullable(name: &'static str, ty: ColumnType) -> Self {
+        Self {
+            name,
+            ty,
+            nullable: true,
+        }
+    }
+
+    fn parquet_type(&self) -> Result<Type> {
+        let repetition = match self.nullable {
+            true => Repetition::OPTIONAL,
+            false => Repetition::REQUIRED,
+        };
+
+        let (physical_type, logical_type) = match self.ty {
+            ColumnType::Int32 => (PhysicalType::INT32, None),
+            ColumnType::Int64 => (PhysicalType::INT64, None),
+            ColumnType::Boolean => (PhysicalType::BOOLEAN, None),
+            ColumnType::String => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+            ColumnType::Timestamp => {
+                let logical_type = LogicalType::Timestamp {
+                    is_adjusted_to_u_t_c: true,
+                    unit: TimeUnit::MICROS(MicroSeconds {}),
+                };
+                (PhysicalType::INT64, Some(logical_type))
+            }
+            ColumnType::Date => (PhysicalType::INT32, Some(LogicalType::Date)),
+        };
+
+        Type::primitive_type_builder(self.name, physical_type)
+            .with_repetition(repetition)
+            .with_logical_type(logical_type)
+            .build()
+    }
+}
+
+/// The values of a single column in a row group.
+///
+/// Required columns must not contain any `None` values.
+pub enum Values {
+    Int32(Vec<Option<i32>>),
+    Int64(Vec<Option<i64>>),
+    Boolean(Vec<Option<bool>>),
+    String(Vec<Option<String>>),
+    Timestamp(Vec<Option<NaiveDateTime>>),
+    Date(Vec<Option<NaiveDate>>),
+}
+
+impl Values {
+    fn write(self, column: &mut SerializedColumnWriter<'_>) -> Result<()> {
+        match self {
+            Values::Int32(values) => write_values::<Int32Type, _>(column, values, |v| v),
+            Values::Int64(values) => write_values::<Int64Type, _>(column, values, |v| v),
+            Values::Boolean(values) => write_values::<BoolType, _>(column, values, |v| v),
+            Values::String(values) => write_values::<ByteArrayType, _>(column, values, |v| {
+                ByteArray::from(v.into_bytes())
+            }),
+            Values::Timestamp(values) => {
+                write_values::<Int64Type, _>(column, values, |v| v.and_utc().timestamp_micros())
+            }
+            Values::Date(values) => write_values::<Int32Type, _>(column, values, |v| {
+                (v - DateTime::UNIX_EPOCH.date_naive()).num_days() as i32
+            }),
+        }
+    }
+}
+
+fn write_values<T: DataType, V>(
+    column: &mut SerializedColumnWriter<'_>,
+    values: Vec<Option<V>>,
+    f: impl Fn(V) -> T::T,
+) -> Result<()> {
+    let writer = column.typed::<T>();
+
+    // Nullable columns need a definition level for every row, which tells
+    // readers whether the row has a value or not.
+    let def_levels = (writer.get_descriptor().max_def_level() > 0).then(|| {
+        values
+            .iter()
+            .map(|v| v.is_some() as i16)
+            .collect::<Vec<_>>()
+    });
+
+    let values = values.into_iter().flatten().map(f).collect::<Vec<_>>();
+    writer.write_batch(&values, def_levels.as_deref(), None)?;
+    Ok(())
+}
+
+/// Writes a Parquet file one row group at a time, so that large tables don't
+/// have to be loaded into memory at once.
+pub struct ParquetWriter {
+    writer: SerializedFileWriter<File>,
+}
+
+impl ParquetWriter {
+    pub fn new(file: File, columns: &[Column], schema_version: u32) -> Result<Self> {
+        let fields = columns
+            .iter()
+            .map(|column| column.parquet_type().map(Arc::new))
+            .collect::<Result<Vec<_>>>()?;
+
+        let schema = Type::group_type_builder("schema")
+            .with_fields(fields)
+            .build()?;
+
+        let metadata = KeyValue::new(SCHEMA_VERSION_KEY.into(), schema_version.to_string());
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(Default::default()))
+            .set_key_value_metadata(Some(vec![metadata]))
+            .build();
+
+        let writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))?;
+        Ok(Self { writer })
+    }
+
+    /// Writes a row group with the given column values, which have to be in
+    /// the same order as the columns passed to [`ParquetWriter::new`].
+    pub fn write_row_group(&mut self, columns: Vec<Values>) -> Result<()> {
+        let mut row_group = self.writer.next_row_group()?;
+        for values in columns {
+            let Some(mut column) = row_group.next_column()? else {
+                return Err(parquet::errors::ParquetError::General(
+                    "Too many columns".into(),
+                ));
+            };
+
+            values.write(&mut column)?;
+            column.close()?;
+        }
+
+        row_group.close()?;
+        Ok(())
+    }
+
+    pub fn close(self) -> Result<()> {
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_write_and_read() {
+        let columns = [
+            Column::required("id", ColumnType::Int32),
+            Column::nullable("name", ColumnType::String),
+            Column::required("date", ColumnType::Date),
+        ];
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = ParquetWriter::new(file.reopen().unwrap(), &columns, 1).unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        writer
+            .write_row_group(vec![
+                Values::Int32(vec![Some(1), Some(2)]),
+                Values::String(vec![Some("foo".into()), None]),
+                Values::Date(vec![Some(date), Some(date)]),
+            ])
+            .unwrap();
+        writer.close().unwrap();
+
+        let reader = SerializedFileReader::new(file.reopen().unwrap()).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 2);
+
+        let key_value = &metadata.key_value_metadata().unwrap()[0];
+        assert_eq!(key_value.key, SCHEMA_VERSION_KEY);
+        assert_eq!(key_value.value.as_deref(), Some("1"));
+
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                "{id: 1, name: \"foo\", date: 2024-01-02}",
+                "{id: 2, name: null, date: 2024-01-02}",
+            ]
+        );
+    }
+}
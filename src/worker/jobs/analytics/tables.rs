@@ -0,0 +1,283 @@
+use super::parquet::{Column, ColumnType, Values};
+use crate::schema::{crate_downloads, crates, version_downloads, versions};
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::future::Future;
+
+/// A database table that is exported as a series of Parquet files, one for
+/// each day.
+pub trait ExportTable: Sized + Send + 'static {
+    /// The name of the table in the export, which is also used as part of
+    /// the file path.
+    const NAME: &'static str;
+
+    /// The columns of the exported table. Changing these requires a bump of
+    /// the [`SCHEMA_VERSION`](super::SCHEMA_VERSION).
+    const COLUMNS: &'static [Column];
+
+    /// Loads the next batch of rows for the given `date`, starting after the
+    /// row with the given `cursor` value.
+    fn load(
+        conn: &mut AsyncPgConnection,
+        date: NaiveDate,
+        cursor: i32,
+        limit: i64,
+    ) -> impl Future<Output = QueryResult<Vec<Self>>> + Send;
+
+    /// Returns the value that is used to continue loading rows after this
+    /// row.
+    fn cursor(&self) -> i32;
+
+    /// Converts a batch of rows into column values, in the order of
+    /// [`ExportTable::COLUMNS`].
+    fn into_values(rows: Vec<Self>) -> Vec<Values>;
+}
+
+/// A snapshot of the `crates` table, combined with the total download count.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = crates, check_for_backend(diesel::pg::Pg))]
+pub struct CrateRow {
+    id: i32,
+    name: String,
+    description: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    repository: Option<String>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    #[diesel(select_expression = crate_downloads::downloads.nullable())]
+    downloads: Option<i64>,
+}
+
+impl ExportTable for CrateRow {
+    const NAME: &'static str = "crates";
+
+    const COLUMNS: &'static [Column] = &[
+        Column::required("id", ColumnType::Int32),
+        Column::required("name", ColumnType::String),
+        Column::nullable("description", ColumnType::String),
+        Column::nullable("homepage", ColumnType::String),
+        Column::nullable("documentation", ColumnType::String),
+        Column::nullable("repository", ColumnType::String),
+        Column::required("created_at", ColumnType::Timestamp),
+        Column::required("updated_at", ColumnType::Timestamp),
+        Column::required("downloads", ColumnType::Int64),
+    ];
+
+    async fn load(
+        conn: &mut AsyncPgConnection,
+        _date: NaiveDate,
+        cursor: i32,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        crates::table
+            .left_join(crate_downloads::table)
+            .filter(crates::id.gt(cursor))
+            .order(crates::id)
+            .limit(limit)
+            .select(CrateRow::as_select())
+            .load(conn)
+            .await
+    }
+
+    fn cursor(&self) -> i32 {
+        self.id
+    }
+
+    fn into_values(rows: Vec<Self>) -> Vec<Values> {
+        let mut id = Vec::with_capacity(rows.len());
+        let mut name = Vec::with_capacity(rows.len());
+        let mut description = Vec::with_capacity(rows.len());
+        let mut homepage = Vec::with_capacity(rows.len());
+        let mut documentation = Vec::with_capacity(rows.len());
+        let mut repository = Vec::with_capacity(rows.len());
+        let mut created_at = Vec::with_capacity(rows.len());
+        let mut updated_at = Vec::with_capacity(rows.len());
+        let mut downloads = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            id.push(Some(row.id));
+            name.push(Some(row.name));
+            description.push(row.description);
+            homepage.push(row.homepage);
+            documentation.push(row.documentation);
+            repository.push(row.repository);
+            created_at.push(Some(row.created_at));
+            updated_at.push(Some(row.updated_at));
+            downloads.push(Some(row.downloads.unwrap_or_default()));
+        }
+
+        vec![
+            Values::Int32(id),
+            Values::String(name),
+            Values::String(description),
+            Values::String(homepage),
+            Values::String(documentation),
+            Values::String(repository),
+            Values::Timestamp(created_at),
+            Values::Timestamp(updated_at),
+            Values::Int64(downloads),
+        ]
+    }
+}
+
+/// A snapshot of the `versions` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = versions, check_for_backend(diesel::pg::Pg))]
+pub struct VersionRow {
+    id: i32,
+    crate_id: i32,
+    num: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    yanked: bool,
+    license: Option<String>,
+    crate_size: i32,
+    rust_version: Option<String>,
+    edition: Option<String>,
+    downloads: i32,
+}
+
+impl ExportTable for VersionRow {
+    const NAME: &'static str = "versions";
+
+    const COLUMNS: &'static [Column] = &[
+        Column::required("id", ColumnType::Int32),
+        Column::required("crate_id", ColumnType::Int32),
+        Column::required("num", ColumnType::String),
+        Column::required("created_at", ColumnType::Timestamp),
+        Column::required("updated_at", ColumnType::Timestamp),
+        Column::required("yanked", ColumnType::Boolean),
+        Column::nullable("license", ColumnType::String),
+        Column::required("crate_size", ColumnType::Int32),
+        Column::nullable("rust_version", ColumnType::String),
+        Column::nullable("edition", ColumnType::String),
+        Column::required("downloads", ColumnType::Int64),
+    ];
+
+    async fn load(
+        conn: &mut AsyncPgConnection,
+        _date: NaiveDate,
+        cursor: i32,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        versions::table
+            .filter(versions::id.gt(cursor))
+            .order(versions::id)
+            .limit(limit)
+            .select(VersionRow::as_select())
+            .load(conn)
+            .await
+    }
+
+    fn cursor(&self) -> i32 {
+        self.id
+    }
+
+    fn into_values(rows: Vec<Self>) -> Vec<Values> {
+        let mut id = Vec::with_capacity(rows.len());
+        let mut crate_id = Vec::with_capacity(rows.len());
+        let mut num = Vec::with_capacity(rows.len());
+        let mut created_at = Vec::with_capacity(rows.len());
+        let mut updated_at = Vec::with_capacity(rows.len());
+        let mut yanked = Vec::with_capacity(rows.len());
+        let mut license = Vec::with_capacity(rows.len());
+        let mut crate_size = Vec::with_capacity(rows.len());
+        let mut rust_version = Vec::with_capacity(rows.len());
+        let mut edition = Vec::with_capacity(rows.len());
+        let mut downloads = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            id.push(Some(row.id));
+            crate_id.push(Some(row.crate_id));
+            num.push(Some(row.num));
+            created_at.push(Some(row.created_at));
+            updated_at.push(Some(row.updated_at));
+            yanked.push(Some(row.yanked));
+            license.push(row.license);
+            crate_size.push(Some(row.crate_size));
+            rust_version.push(row.rust_version);
+            edition.push(row.edition);
+            downloads.push(Some(row.downloads as i64));
+        }
+
+        vec![
+            Values::Int32(id),
+            Values::Int32(crate_id),
+            Values::String(num),
+            Values::Timestamp(created_at),
+            Values::Timestamp(updated_at),
+            Values::Boolean(yanked),
+            Values::String(license),
+            Values::Int32(crate_size),
+            Values::String(rust_version),
+            Values::String(edition),
+            Values::Int64(downloads),
+        ]
+    }
+}
+
+/// The download counts of all versions on a single day.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = version_downloads, check_for_backend(diesel::pg::Pg))]
+pub struct VersionDownloadsRow {
+    date: NaiveDate,
+    version_id: i32,
+    #[diesel(select_expression = versions::crate_id)]
+    crate_id: i32,
+    downloads: i32,
+}
+
+impl ExportTable for VersionDownloadsRow {
+    const NAME: &'static str = "version_downloads";
+
+    const COLUMNS: &'static [Column] = &[
+        Column::required("date", ColumnType::Date),
+        Column::required("version_id", ColumnType::Int32),
+        Column::required("crate_id", ColumnType::Int32),
+        Column::required("downloads", ColumnType::Int32),
+    ];
+
+    async fn load(
+        conn: &mut AsyncPgConnection,
+        date: NaiveDate,
+        cursor: i32,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        version_downloads::table
+            .inner_join(versions::table)
+            .filter(version_downloads::date.eq(date))
+            .filter(version_downloads::version_id.gt(cursor))
+            .order(version_downloads::version_id)
+            .limit(limit)
+            .select(VersionDownloadsRow::as_select())
+            .load(conn)
+            .await
+    }
+
+    fn cursor(&self) -> i32 {
+        self.version_id
+    }
+
+    fn into_values(rows: Vec<Self>) -> Vec<Values> {
+        let mut date = Vec::with_capacity(rows.len());
+        let mut version_id = Vec::with_capacity(rows.len());
+        let mut crate_id = Vec::with_capacity(rows.len());
+        let mut downloads = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            date.push(Some(row.date));
+            version_id.push(Some(row.version_id));
+            crate_id.push(Some(row.crate_id));
+            downloads.push(Some(row.downloads));
+        }
+
+        vec![
+            Values::Date(date),
+            Values::Int32(version_id),
+            Values::Int32(crate_id),
+            Values::Int32(downloads),
+        ]
+    }
+}
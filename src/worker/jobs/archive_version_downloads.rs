@@ -1,11 +1,12 @@
+use super::partition_version_downloads::{list_partitions, partition_name};
 use super::IndexVersionDownloadsArchive;
-use crate::schema::version_downloads;
 use crate::tasks::spawn_blocking;
 use crate::worker::Environment;
 use anyhow::{anyhow, Context};
-use chrono::{NaiveDate, Utc};
+use chrono::{Months, NaiveDate, Utc};
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
+use diesel::sql_types::Date;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use futures_util::StreamExt;
 use object_store::ObjectStore;
@@ -19,13 +20,17 @@ use tempfile::tempdir;
 
 const FILE_NAME: &str = "version_downloads.csv";
 
-/// Archive data from the `version_downloads` table older than the given
-/// date to S3.
+define_sql_function!(fn archive_version_downloads_partition(month: Date));
+
+/// Archive the monthly partitions of the `version_downloads` table that end
+/// before the given date to S3.
 ///
-/// This job first exports the data from the database to a CSV file using `psql`
-/// and a `COPY` command. The CSV file is then split into multiple files based
-/// on the date column and those are uploaded to the object store. Finally, the
-/// successfully uploaded dates are deleted from the database.
+/// For every partition, this job first exports the data from the database to
+/// a CSV file using `psql` and a `COPY` command. The CSV file is then split
+/// into multiple files based on the date column and those are uploaded to the
+/// object store. Finally, if all dates were uploaded successfully, the
+/// partition is moved to the `version_downloads_archive` table. Partitions
+/// with failed uploads are retried on the next run.
 #[derive(Serialize, Deserialize)]
 pub struct ArchiveVersionDownloads {
     before: NaiveDate,
@@ -57,34 +62,54 @@ impl BackgroundJob for ArchiveVersionDownloads {
             return Ok(());
         };
 
-        let tempdir = tempdir().context("Failed to create temporary directory")?;
-        let csv_path = tempdir.path().join(FILE_NAME);
+        let mut conn = env.deadpool.get().await?;
 
-        export(&env.config.db.primary.url, &csv_path, &self.before).await?;
-        let dates = spawn_blocking(move || split(csv_path)).await??;
-        let uploaded_dates = upload(downloads_archive_store, tempdir.path(), dates).await?;
+        let months = list_partitions(&mut conn).await?;
+        let months = months
+            .into_iter()
+            .filter(|month| *month + Months::new(1) <= self.before);
 
-        let mut conn = env.deadpool.get().await?;
-        delete(&mut conn, uploaded_dates).await?;
+        let mut num_archived = 0;
+        for month in months {
+            info!("Archiving version downloads of {month}…");
 
-        // Queue up the job to regenerate the archive index.
-        IndexVersionDownloadsArchive
-            .enqueue(&mut conn)
-            .await
-            .context("Failed to enqueue IndexVersionDownloadsArchive job")?;
+            let tempdir = tempdir().context("Failed to create temporary directory")?;
+            let csv_path = tempdir.path().join(FILE_NAME);
+
+            export(&env.config.db.primary.url, &csv_path, month).await?;
+            let dates = spawn_blocking(move || split(csv_path)).await??;
+            let num_dates = dates.len();
+            let uploaded_dates = upload(downloads_archive_store, tempdir.path(), dates).await?;
+
+            // Keep the partition in the `version_downloads` table, so that the
+            // failed uploads are retried on the next run.
+            if uploaded_dates.len() < num_dates {
+                warn!("Failed to upload all version downloads of {month}");
+                continue;
+            }
 
-        info!("Finished archiving old version downloads");
+            archive_partition(&mut conn, month).await?;
+            num_archived += 1;
+        }
+
+        if num_archived > 0 {
+            // Queue up the job to regenerate the archive index.
+            IndexVersionDownloadsArchive
+                .enqueue(&mut conn)
+                .await
+                .context("Failed to enqueue IndexVersionDownloadsArchive job")?;
+        }
+
+        info!("Finished archiving {num_archived} months of old version downloads");
         Ok(())
     }
 }
 
-/// Export a subset of the `version_downloads` table to a CSV file.
-///
-/// The subset includes all rows with a date before the given `before` date.
+/// Export the `version_downloads` partition of the given month to a CSV file.
 async fn export(
     database_url: &SecretString,
     filename: impl AsRef<Path>,
-    before: &NaiveDate,
+    month: NaiveDate,
 ) -> anyhow::Result<()> {
     let filename = filename.as_ref().as_os_str();
     let filename = filename
@@ -93,7 +118,8 @@ async fn export(
 
     info!("Exporting version downloads to CSV file…");
     let instant = Instant::now();
-    let command = format!("\\copy (SELECT date, version_id, downloads FROM version_downloads WHERE date < '{before}') TO '{filename}' WITH CSV HEADER");
+    let partition = partition_name(month);
+    let command = format!("\\copy (SELECT date, version_id, downloads FROM partitions.{partition} ORDER BY date, version_id) TO '{filename}' WITH CSV HEADER");
     psql(database_url, &command).await?;
 
     let elapsed = instant.elapsed();
@@ -222,34 +248,26 @@ async fn upload_file(store: &impl ObjectStore, path: impl AsRef<Path>) -> anyhow
     Ok(())
 }
 
-/// Delete version downloads for the given dates from the database.
-async fn delete(conn: &mut AsyncPgConnection, dates: Vec<NaiveDate>) -> anyhow::Result<()> {
-    // Delete version downloads for the given dates in chunks to avoid running
-    // into the maximum query parameter limit.
-    const CHUNK_SIZE: usize = 5000;
-
-    info!("Deleting old version downloads for {} dates…", dates.len());
-    for chunk in dates.chunks(CHUNK_SIZE) {
-        let subset = version_downloads::table.filter(version_downloads::date.eq_any(chunk));
-        match diesel::delete(subset).execute(conn).await {
-            Ok(num_deleted_rows) => {
-                info!("Deleted {num_deleted_rows} rows from `version_downloads`");
-            }
-            Err(err) => {
-                error!("Failed to delete rows from `version_downloads`: {err}");
-            }
-        }
-    }
+/// Move the `version_downloads` partition of the given month to the
+/// `version_downloads_archive` table.
+async fn archive_partition(conn: &mut AsyncPgConnection, month: NaiveDate) -> anyhow::Result<()> {
+    diesel::select(archive_version_downloads_partition(month))
+        .execute(conn)
+        .await
+        .with_context(|| format!("Failed to archive version downloads partition for {month}"))?;
 
+    info!("Moved version downloads of {month} to the archive");
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schema::{crates, version_downloads, versions};
+    use crate::models::VersionDownload;
+    use crate::schema::{crates, version_downloads, version_downloads_archive, versions};
+    use crate::worker::jobs::partition_version_downloads::create_partition;
     use crates_io_test_db::TestDatabase;
-    use insta::assert_snapshot;
+    use insta::{assert_debug_snapshot, assert_snapshot};
 
     #[tokio::test]
     async fn test_export() {
@@ -261,16 +279,18 @@ mod tests {
         let csv_path = tempdir.path().join(FILE_NAME);
 
         let database_url = SecretString::from(test_db.url().to_string());
-        let before = NaiveDate::from_ymd_opt(2021, 1, 3).unwrap();
-        export(&database_url, &csv_path, &before).await.unwrap();
+        let month = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap();
+        export(&database_url, &csv_path, month).await.unwrap();
 
         let content = tokio::fs::read_to_string(&csv_path).await.unwrap();
         assert_snapshot!(content, @r"
         date,version_id,downloads
         2021-01-01,1,100
-        2021-01-02,1,200
         2021-01-01,2,400
+        2021-01-02,1,200
         2021-01-02,2,500
+        2021-01-03,1,300
+        2021-01-03,2,600
         ");
     }
 
@@ -354,23 +374,67 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_delete() {
+    async fn test_archive_partition() {
         let test_db = TestDatabase::new();
         let mut conn = test_db.async_connect().await;
-        prepare_database(&mut conn).await;
+        let version_ids = prepare_database(&mut conn).await;
+
+        let month = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap();
+        archive_partition(&mut conn, month).await.unwrap();
 
-        let dates = vec![NaiveDate::from_ymd_opt(2021, 1, 1).unwrap()];
-        delete(&mut conn, dates).await.unwrap();
+        let months = list_partitions(&mut conn).await.unwrap();
+        assert_eq!(months, vec![NaiveDate::from_ymd_opt(2021, 2, 1).unwrap()]);
 
         let row_count: i64 = version_downloads::table
             .count()
             .get_result(&mut conn)
             .await
             .unwrap();
-        assert_eq!(row_count, 4);
+        assert_eq!(row_count, 1);
+
+        let row_count: i64 = version_downloads_archive::table
+            .count()
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(row_count, 6);
+
+        // The archived downloads can still be read.
+        let start = NaiveDate::from_ymd_opt(2021, 1, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2021, 2, 1).unwrap();
+        let downloads = VersionDownload::load_between(&mut conn, &version_ids, start, end)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|download| (download.date.to_string(), download.downloads))
+            .collect::<Vec<_>>();
+        assert_debug_snapshot!(downloads, @r#"
+        [
+            (
+                "2021-01-02",
+                200,
+            ),
+            (
+                "2021-01-02",
+                500,
+            ),
+            (
+                "2021-01-03",
+                300,
+            ),
+            (
+                "2021-01-03",
+                600,
+            ),
+            (
+                "2021-02-01",
+                700,
+            ),
+        ]
+        "#);
     }
 
-    async fn prepare_database(conn: &mut AsyncPgConnection) {
+    async fn prepare_database(conn: &mut AsyncPgConnection) -> Vec<i32> {
         let c1 = create_crate(conn, "foo").await;
         let v1 = create_version(conn, c1, "1.0.0").await;
         let v2 = create_version(conn, c1, "2.0.0").await;
@@ -380,6 +444,14 @@ mod tests {
         insert_downloads(conn, v2, "2021-01-01", 400).await;
         insert_downloads(conn, v2, "2021-01-02", 500).await;
         insert_downloads(conn, v2, "2021-01-03", 600).await;
+        insert_downloads(conn, v1, "2021-02-01", 700).await;
+
+        for month in [(2021, 1), (2021, 2)] {
+            let month = NaiveDate::from_ymd_opt(month.0, month.1, 1).unwrap();
+            create_partition(conn, month).await.unwrap();
+        }
+
+        vec![v1, v2]
     }
 
     async fn create_crate(conn: &mut AsyncPgConnection, name: &str) -> i32 {
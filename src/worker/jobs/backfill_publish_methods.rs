@@ -0,0 +1,80 @@
+use crate::models::{PublishMethod, VersionAction};
+use crate::schema::{version_owner_actions, versions};
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use std::sync::Arc;
+
+/// The number of versions that are processed by a single job run.
+const BATCH_SIZE: i64 = 1000;
+
+/// Backfills the `publish_method` column of versions that were published
+/// before crates.io started recording how a version was published.
+///
+/// Only versions whose publish action references an API token can be
+/// backfilled. Publishes without a recorded token may have used a legacy
+/// token or a session cookie, so their method stays unknown.
+///
+/// Each run processes a batch of versions with an `id` greater than `after`,
+/// and enqueues another job for the next batch until all versions have been
+/// processed.
+#[derive(Serialize, Deserialize)]
+pub struct BackfillPublishMethods {
+    after: i32,
+}
+
+impl BackfillPublishMethods {
+    pub fn after(after: i32) -> Self {
+        Self { after }
+    }
+}
+
+impl BackgroundJob for BackfillPublishMethods {
+    const JOB_NAME: &'static str = "backfill_publish_methods";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(after = self.after), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let batch: Vec<i32> = versions::table
+            .filter(versions::id.gt(self.after))
+            .filter(versions::publish_method.is_null())
+            .order(versions::id)
+            .limit(BATCH_SIZE)
+            .select(versions::id)
+            .load(&mut conn)
+            .await?;
+
+        let published_with_token = version_owner_actions::table
+            .filter(version_owner_actions::action.eq(VersionAction::Publish))
+            .filter(version_owner_actions::api_token_id.is_not_null())
+            .select(version_owner_actions::version_id);
+
+        let num_updated = diesel::update(versions::table)
+            .filter(versions::id.eq_any(&batch))
+            .filter(versions::id.eq_any(published_with_token))
+            .set(versions::publish_method.eq(PublishMethod::ApiToken))
+            .execute(&mut conn)
+            .await?;
+
+        info!(
+            num_updated,
+            "Backfilled `publish_method` of {} versions",
+            batch.len()
+        );
+
+        if batch.len() == BATCH_SIZE as usize {
+            if let Some(last_id) = batch.last() {
+                BackfillPublishMethods::after(*last_id)
+                    .enqueue(&mut conn)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use crate::models::Version;
+use crate::schema::{crates, versions};
+use crate::worker::Environment;
+use crates_io_tarball::process_tarball;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use std::sync::Arc;
+
+/// The number of versions that are processed by a single job run.
+const BATCH_SIZE: i64 = 100;
+
+/// Backfills the `rust_version` column of versions that were published
+/// before crates.io started saving the `rust-version` manifest field.
+///
+/// The `.crate` files of the versions are downloaded from storage and their
+/// manifests are parsed. Each run processes a batch of versions with an `id`
+/// greater than `after`, and enqueues another job for the next batch until
+/// all versions have been processed.
+#[derive(Serialize, Deserialize)]
+pub struct BackfillRustVersions {
+    after: i32,
+}
+
+impl BackfillRustVersions {
+    pub fn after(after: i32) -> Self {
+        Self { after }
+    }
+}
+
+impl BackgroundJob for BackfillRustVersions {
+    const JOB_NAME: &'static str = "backfill_rust_versions";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(after = self.after), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let batch: Vec<(i32, String, String)> = versions::table
+            .inner_join(crates::table)
+            .filter(versions::id.gt(self.after))
+            .filter(versions::rust_version.is_null())
+            .order(versions::id)
+            .limit(BATCH_SIZE)
+            .select((versions::id, crates::name, versions::num))
+            .load(&mut conn)
+            .await?;
+
+        let mut num_updated = 0;
+        for (id, name, num) in &batch {
+            let rust_version = match read_rust_version(&env, name, num).await {
+                Ok(Some(rust_version)) => rust_version,
+                Ok(None) => continue,
+                Err(error) => {
+                    warn!("Failed to read manifest of {name}@{num}: {error}");
+                    continue;
+                }
+            };
+
+            if !Version::is_valid_rust_version(&rust_version) {
+                warn!("Skipping invalid `rust-version` of {name}@{num}: {rust_version}");
+                continue;
+            }
+
+            diesel::update(versions::table.find(id))
+                .set(versions::rust_version.eq(rust_version))
+                .execute(&mut conn)
+                .await?;
+
+            num_updated += 1;
+        }
+
+        info!(
+            num_updated,
+            "Backfilled `rust_version` of {} versions",
+            batch.len()
+        );
+
+        if batch.len() == BATCH_SIZE as usize {
+            if let Some((last_id, _, _)) = batch.last() {
+                BackfillRustVersions::after(*last_id)
+                    .enqueue(&mut conn)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the `rust-version` field from the manifest in the `.crate` file of
+/// the given version.
+async fn read_rust_version(
+    env: &Environment,
+    name: &str,
+    version: &str,
+) -> anyhow::Result<Option<String>> {
+    let tarball = env.storage.download_crate_file(name, version).await?;
+
+    let pkg_name = format!("{name}-{version}");
+    let info = process_tarball(&pkg_name, &*tarball, env.config.max_unpack_size).await?;
+
+    let rust_version = info
+        .manifest
+        .package
+        .and_then(|package| package.rust_version)
+        .and_then(|rust_version| rust_version.as_local());
+
+    Ok(rust_version)
+}
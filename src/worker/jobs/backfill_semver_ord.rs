@@ -0,0 +1,79 @@
+use crate::models::semver_ord;
+use crate::schema::versions;
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use std::sync::Arc;
+
+/// The number of versions that are processed by a single job run.
+const BATCH_SIZE: i64 = 100;
+
+/// Backfills the `semver_ord` column of versions that were published before
+/// crates.io started saving the sort key of the version number.
+///
+/// Each run processes a batch of versions with an `id` greater than `after`,
+/// and enqueues another job for the next batch until all versions have been
+/// processed. Versions with an invalid version number keep a `NULL` key.
+#[derive(Serialize, Deserialize)]
+pub struct BackfillSemverOrd {
+    after: i32,
+}
+
+impl BackfillSemverOrd {
+    pub fn after(after: i32) -> Self {
+        Self { after }
+    }
+}
+
+impl BackgroundJob for BackfillSemverOrd {
+    const JOB_NAME: &'static str = "backfill_semver_ord";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(after = self.after), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let batch: Vec<(i32, String)> = versions::table
+            .filter(versions::id.gt(self.after))
+            .filter(versions::semver_ord.is_null())
+            .order(versions::id)
+            .limit(BATCH_SIZE)
+            .select((versions::id, versions::num))
+            .load(&mut conn)
+            .await?;
+
+        let mut num_updated = 0;
+        for (id, num) in &batch {
+            let Some(semver_ord) = semver_ord(num) else {
+                warn!("Skipping invalid version number of version {id}: {num}");
+                continue;
+            };
+
+            diesel::update(versions::table.find(id))
+                .set(versions::semver_ord.eq(semver_ord))
+                .execute(&mut conn)
+                .await?;
+
+            num_updated += 1;
+        }
+
+        info!(
+            num_updated,
+            "Backfilled `semver_ord` of {} versions",
+            batch.len()
+        );
+
+        if batch.len() == BATCH_SIZE as usize {
+            if let Some((last_id, _)) = batch.last() {
+                BackfillSemverOrd::after(*last_id)
+                    .enqueue(&mut conn)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use crate::models::{CrateSettings, VersionDiff};
+use crate::schema::{crates, versions};
+use crate::tarball_diff;
+use crate::tasks::spawn_blocking;
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use std::sync::Arc;
+
+/// Background job that compares the `.crate` file of a newly published
+/// version to the one of the previous release, and saves a summary of the
+/// added, removed and modified files.
+///
+/// The previous release is the highest version of the crate that is lower
+/// than the new version, regardless of whether it has been yanked. Nothing
+/// is saved for the first version of a crate, or if the owners have disabled
+/// version diffs in the settings of the crate.
+#[derive(Serialize, Deserialize)]
+pub struct ComputeVersionDiff {
+    version_id: i32,
+}
+
+impl ComputeVersionDiff {
+    pub fn new(version_id: i32) -> Self {
+        Self { version_id }
+    }
+}
+
+impl BackgroundJob for ComputeVersionDiff {
+    const JOB_NAME: &'static str = "compute_version_diff";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(version_id = self.version_id), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let version = versions::table
+            .inner_join(crates::table)
+            .filter(versions::id.eq(self.version_id))
+            .select((crates::id, crates::name, versions::num))
+            .first::<(i32, String, String)>(&mut conn)
+            .await
+            .optional()?;
+
+        let Some((crate_id, name, num)) = version else {
+            info!("Skipping version diff: version has been deleted");
+            return Ok(());
+        };
+
+        let settings = CrateSettings::for_crate(&mut conn, crate_id).await?;
+        if !settings.version_diffs {
+            info!("Skipping version diff: version diffs are disabled for {name}");
+            return Ok(());
+        }
+
+        let semver = semver::Version::parse(&num)?;
+
+        let versions: Vec<(i32, String)> = versions::table
+            .filter(versions::crate_id.eq(crate_id))
+            .filter(versions::id.ne(self.version_id))
+            .select((versions::id, versions::num))
+            .load(&mut conn)
+            .await?;
+
+        let previous = versions
+            .into_iter()
+            .filter_map(|(id, num)| Some((id, num.clone(), semver::Version::parse(&num).ok()?)))
+            .filter(|(_, _, other)| *other < semver)
+            .max_by(|(_, _, a), (_, _, b)| a.cmp(b));
+
+        let Some((previous_id, previous_num, _)) = previous else {
+            info!("Skipping version diff: {name}@{num} has no previous release");
+            return Ok(());
+        };
+
+        info!("Comparing {name}@{num} to {name}@{previous_num}…");
+
+        let old = match env.storage.download_crate_file(&name, &previous_num).await {
+            Ok(old) => old,
+            Err(object_store::Error::NotFound { .. }) => {
+                warn!("Skipping version diff: {name}@{previous_num} has no `.crate` file");
+                return Ok(());
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let new = env.storage.download_crate_file(&name, &num).await?;
+
+        let diff = spawn_blocking(move || {
+            let old = tarball_diff::list_files(&old)?;
+            let new = tarball_diff::list_files(&new)?;
+            Ok::<_, anyhow::Error>(tarball_diff::diff(&old, &new))
+        })
+        .await??;
+
+        VersionDiff {
+            version_id: self.version_id,
+            previous_version_id: previous_id,
+            files_added: diff.files_added,
+            files_removed: diff.files_removed,
+            files_modified: diff.files_modified,
+            size_delta: diff.size_delta,
+            files: serde_json::to_value(&diff.files)?,
+        }
+        .upsert(&mut conn)
+        .await?;
+
+        Ok(())
+    }
+}
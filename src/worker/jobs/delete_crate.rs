@@ -42,6 +42,15 @@ impl BackgroundJob for DeleteCrateFromStorage {
                 let feed_id = FeedId::Crate { name };
                 let result = ctx.storage.delete_feed(&feed_id).await;
                 result.context("Failed to delete RSS feed from S3")
+            },
+            async {
+                let Some(standby) = &ctx.standby_storage else {
+                    return Ok(());
+                };
+
+                info!("{name}: Deleting crate files from the standby region…");
+                let result = standby.delete_all_crate_files(name).await;
+                result.context("Failed to delete crate files from the standby region")
             }
         )?;
 
@@ -0,0 +1,26 @@
+use crate::models::PublishIdempotencyKey;
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use std::sync::Arc;
+
+/// Deletes the saved results of publish requests with an `Idempotency-Key`
+/// header, once they are no longer returned for retries of the request.
+#[derive(Serialize, Deserialize)]
+pub struct DeleteExpiredIdempotencyKeys;
+
+impl BackgroundJob for DeleteExpiredIdempotencyKeys {
+    const JOB_NAME: &'static str = "delete_expired_idempotency_keys";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Deleting expired idempotency keys…");
+        let deleted = PublishIdempotencyKey::delete_expired(&mut conn).await?;
+        info!("Deleted {deleted} expired idempotency keys");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+use crate::schema::recent_crate_downloads;
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// Compares the incrementally maintained `recent_crate_downloads` table with
+/// the downloads recalculated from the `version_downloads` table, and repairs
+/// the crates that don't match.
+///
+/// Mismatches are not expected during normal operation, but can be caused by
+/// e.g. deleted versions, whose downloads are not subtracted from the table.
+/// Since the recalculation is as expensive as the previous full refresh of
+/// the materialized view, this job is meant to run much less often than the
+/// [`UpdateRecentCrateDownloads`](super::UpdateRecentCrateDownloads) job.
+#[derive(Serialize, Deserialize)]
+pub struct CheckRecentCrateDownloads;
+
+impl BackgroundJob for CheckRecentCrateDownloads {
+    const JOB_NAME: &'static str = "check_recent_crate_downloads";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Checking recent crate downloads…");
+        let mismatches = check(&mut conn).await?;
+        if mismatches.is_empty() {
+            info!("Recent crate downloads are consistent");
+        } else {
+            warn!(
+                "Repaired recent crate downloads of {} crates",
+                mismatches.len()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, QueryableByName)]
+struct Mismatch {
+    #[diesel(sql_type = Integer)]
+    crate_id: i32,
+    #[diesel(sql_type = BigInt)]
+    expected: i64,
+    #[diesel(sql_type = BigInt)]
+    actual: i64,
+}
+
+async fn check(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Mismatch>> {
+    conn.transaction(|conn| {
+        async move {
+            // Block the `UpdateDownloads` and `UpdateRecentCrateDownloads`
+            // jobs until the repairs are committed, so that the downloads
+            // they add or remove in the meantime are not overwritten.
+            diesel::sql_query("LOCK TABLE recent_crate_downloads IN SHARE ROW EXCLUSIVE MODE")
+                .execute(conn)
+                .await?;
+
+            let mismatches: Vec<Mismatch> = diesel::sql_query(include_str!("check_recent.sql"))
+                .load(conn)
+                .await?;
+
+            for mismatch in &mismatches {
+                let Mismatch {
+                    crate_id,
+                    expected,
+                    actual,
+                } = *mismatch;
+
+                debug!("Repairing recent downloads of crate {crate_id}: {actual} -> {expected}");
+
+                if expected == 0 {
+                    diesel::delete(recent_crate_downloads::table.find(crate_id))
+                        .execute(conn)
+                        .await?;
+                } else {
+                    diesel::insert_into(recent_crate_downloads::table)
+                        .values((
+                            recent_crate_downloads::crate_id.eq(crate_id),
+                            recent_crate_downloads::downloads.eq(expected),
+                        ))
+                        .on_conflict(recent_crate_downloads::crate_id)
+                        .do_update()
+                        .set(recent_crate_downloads::downloads.eq(expected))
+                        .execute(conn)
+                        .await?;
+                }
+            }
+
+            Ok(mismatches)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{crates, version_downloads, versions};
+    use crates_io_test_db::TestDatabase;
+
+    async fn insert_crate(conn: &mut AsyncPgConnection, name: &str, downloads: i32) -> i32 {
+        let crate_id = diesel::insert_into(crates::table)
+            .values(crates::name.eq(name))
+            .returning(crates::id)
+            .get_result::<i32>(conn)
+            .await
+            .unwrap();
+
+        let version_id = diesel::insert_into(versions::table)
+            .values((
+                versions::crate_id.eq(crate_id),
+                versions::num.eq("1.0.0"),
+                versions::num_no_build.eq("1.0.0"),
+                versions::checksum.eq(""),
+                versions::crate_size.eq(0),
+            ))
+            .returning(versions::id)
+            .get_result::<i32>(conn)
+            .await
+            .unwrap();
+
+        diesel::insert_into(version_downloads::table)
+            .values((
+                version_downloads::version_id.eq(version_id),
+                version_downloads::downloads.eq(downloads),
+                version_downloads::counted.eq(downloads),
+            ))
+            .execute(conn)
+            .await
+            .unwrap();
+
+        crate_id
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let foo = insert_crate(&mut conn, "foo", 10).await;
+        let bar = insert_crate(&mut conn, "bar", 20).await;
+        let baz = insert_crate(&mut conn, "baz", 30).await;
+        let qux = diesel::insert_into(crates::table)
+            .values(crates::name.eq("qux"))
+            .returning(crates::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        // `foo` is correct, `bar` is wrong, `baz` is missing and `qux` has
+        // no downloads at all.
+        diesel::insert_into(recent_crate_downloads::table)
+            .values(&vec![
+                (
+                    recent_crate_downloads::crate_id.eq(foo),
+                    recent_crate_downloads::downloads.eq(10),
+                ),
+                (
+                    recent_crate_downloads::crate_id.eq(bar),
+                    recent_crate_downloads::downloads.eq(25),
+                ),
+                (
+                    recent_crate_downloads::crate_id.eq(qux),
+                    recent_crate_downloads::downloads.eq(5),
+                ),
+            ])
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let mismatches = check(&mut conn).await.unwrap();
+        let expected = vec![
+            Mismatch {
+                crate_id: bar,
+                expected: 20,
+                actual: 25,
+            },
+            Mismatch {
+                crate_id: baz,
+                expected: 30,
+                actual: 0,
+            },
+            Mismatch {
+                crate_id: qux,
+                expected: 0,
+                actual: 5,
+            },
+        ];
+        assert_eq!(mismatches, expected);
+
+        let downloads: Vec<(i32, i64)> = recent_crate_downloads::table
+            .order(recent_crate_downloads::crate_id)
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(downloads, vec![(foo, 10), (bar, 20), (baz, 30)]);
+
+        // The table is consistent after the repairs.
+        assert_eq!(check(&mut conn).await.unwrap(), vec![]);
+    }
+}
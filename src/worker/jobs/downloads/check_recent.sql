@@ -0,0 +1,28 @@
+WITH expected_downloads AS (
+    -- Recalculate the downloads since `metadata.recent_downloads_start`,
+    -- including the months that have been archived already in case the
+    -- `UpdateRecentCrateDownloads` job has not run for a while.
+    SELECT versions.crate_id, SUM(recent.counted) AS downloads
+    FROM (
+        SELECT version_id, counted
+        FROM version_downloads
+        WHERE date >= (SELECT recent_downloads_start FROM metadata)
+        UNION ALL
+        SELECT version_id, counted
+        FROM version_downloads_archive
+        WHERE date >= (SELECT recent_downloads_start FROM metadata)
+    ) recent
+    INNER JOIN versions ON versions.id = recent.version_id
+    GROUP BY versions.crate_id
+    HAVING SUM(recent.counted) > 0
+)
+-- Return all crates whose downloads in the `recent_crate_downloads` table
+-- don't match the recalculated downloads.
+SELECT
+    crate_id,
+    COALESCE(expected_downloads.downloads, 0) AS expected,
+    COALESCE(recent_crate_downloads.downloads, 0) AS actual
+FROM expected_downloads
+FULL OUTER JOIN recent_crate_downloads USING (crate_id)
+WHERE expected_downloads.downloads IS DISTINCT FROM recent_crate_downloads.downloads
+ORDER BY crate_id
@@ -1,9 +1,13 @@
+mod check_recent;
 mod clean_processed_log_files;
 mod process_log;
 mod queue;
 mod update_metadata;
+mod update_recent;
 
+pub use check_recent::CheckRecentCrateDownloads;
 pub use clean_processed_log_files::CleanProcessedLogFiles;
 pub use process_log::ProcessCdnLog;
 pub use queue::ProcessCdnLogQueue;
 pub use update_metadata::UpdateDownloads;
+pub use update_recent::UpdateRecentCrateDownloads;
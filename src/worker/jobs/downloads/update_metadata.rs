@@ -24,7 +24,6 @@ impl BackgroundJob for UpdateDownloads {
 
 async fn update(conn: &mut AsyncPgConnection) -> QueryResult<()> {
     use diesel::dsl::now;
-    use diesel::select;
 
     info!("Updating versions…");
 
@@ -65,14 +64,6 @@ async fn update(conn: &mut AsyncPgConnection) -> QueryResult<()> {
         .await?;
     info!("Finished freezing old version_downloads");
 
-    define_sql_function!(fn refresh_recent_crate_downloads());
-
-    select(refresh_recent_crate_downloads())
-        .execute(conn)
-        .await?;
-
-    info!("Finished running refresh_recent_crate_downloads");
-
     Ok(())
 }
 
@@ -106,7 +97,7 @@ async fn batch_update(batch_size: i64, conn: &mut AsyncPgConnection) -> QueryRes
 mod tests {
     use super::*;
     use crate::models::{Crate, NewCrate, NewUser, NewVersion, User, Version};
-    use crate::schema::{crate_downloads, crates, users, versions};
+    use crate::schema::{crate_downloads, crates, recent_crate_downloads, users, versions};
     use crates_io_test_db::TestDatabase;
     use diesel_async::AsyncConnection;
 
@@ -177,6 +168,13 @@ mod tests {
             .await;
         assert_eq!(crate_downloads, Ok(1));
 
+        let recent_downloads = recent_crate_downloads::table
+            .find(krate.id)
+            .select(recent_crate_downloads::downloads)
+            .first(&mut conn)
+            .await;
+        assert_eq!(recent_downloads, Ok(1));
+
         super::update(&mut conn).await.unwrap();
 
         let version_downloads = versions::table
@@ -30,6 +30,17 @@ WITH downloads_batch AS (
     SET downloads = crate_downloads.downloads + crate_downloads_batch.downloads
     FROM crate_downloads_batch
     WHERE crate_downloads.crate_id = crate_downloads_batch.crate_id
+), updated_recent_crate_downloads AS (
+    -- Add the downloads that are inside of the 90 day window to the
+    -- `recent_crate_downloads` table. The downloads of days that leave the
+    -- window are removed again by the `UpdateRecentCrateDownloads` job.
+    INSERT INTO recent_crate_downloads (crate_id, downloads)
+    SELECT crate_id, SUM(downloads_batch.downloads)
+    FROM downloads_batch
+    WHERE date >= (SELECT recent_downloads_start FROM metadata)
+    GROUP BY crate_id
+    ON CONFLICT (crate_id) DO UPDATE
+    SET downloads = recent_crate_downloads.downloads + excluded.downloads
 ), updated_metadata AS (
     -- Update the `total_downloads` count in the `metadata` table.
     UPDATE metadata
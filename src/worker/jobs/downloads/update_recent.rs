@@ -0,0 +1,172 @@
+use crate::schema::metadata;
+use crate::worker::Environment;
+use chrono::{Days, NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel::sql_types::Date;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// The number of days that are included in the `recent_crate_downloads`
+/// table, including the current day.
+const RECENT_DAYS: u64 = 90;
+
+define_sql_function!(fn expire_recent_crate_downloads(day: Date));
+
+/// Removes the downloads of the days that left the 90 day window from the
+/// `recent_crate_downloads` table.
+///
+/// The downloads inside of the window are added by the [`UpdateDownloads`]
+/// job while it counts them, so instead of recalculating the whole table this
+/// job only has to subtract the downloads of one day, once a day.
+///
+/// [`UpdateDownloads`]: super::UpdateDownloads
+#[derive(Serialize, Deserialize)]
+pub struct UpdateRecentCrateDownloads;
+
+impl BackgroundJob for UpdateRecentCrateDownloads {
+    const JOB_NAME: &'static str = "update_recent_crate_downloads";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Updating recent crate downloads…");
+        update(&mut conn, Utc::now().date_naive()).await?;
+        info!("Finished updating recent crate downloads");
+
+        Ok(())
+    }
+}
+
+async fn update(conn: &mut AsyncPgConnection, today: NaiveDate) -> QueryResult<()> {
+    let window_start = window_start(today);
+
+    // Each day is expired in a separate transaction, so that the
+    // `UpdateDownloads` job is not blocked for long if the job didn't run
+    // for a couple of days.
+    loop {
+        let start = recent_downloads_start(conn).await?;
+        if start >= window_start {
+            return Ok(());
+        }
+
+        info!("Removing downloads of {start} from the recent crate downloads…");
+        diesel::select(expire_recent_crate_downloads(start))
+            .execute(conn)
+            .await?;
+    }
+}
+
+/// Returns the oldest date whose downloads are currently included in the
+/// `recent_crate_downloads` table.
+pub(super) async fn recent_downloads_start(conn: &mut AsyncPgConnection) -> QueryResult<NaiveDate> {
+    metadata::table
+        .select(metadata::recent_downloads_start)
+        .get_result(conn)
+        .await
+}
+
+/// Returns the oldest date of the 90 day window ending on `today`.
+fn window_start(today: NaiveDate) -> NaiveDate {
+    today - Days::new(RECENT_DAYS - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{crates, recent_crate_downloads, version_downloads, versions};
+    use crates_io_test_db::TestDatabase;
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%F").unwrap()
+    }
+
+    async fn recent_downloads(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<i64> {
+        recent_crate_downloads::table
+            .find(crate_id)
+            .select(recent_crate_downloads::downloads)
+            .get_result(conn)
+            .await
+    }
+
+    #[test]
+    fn test_window_start() {
+        assert_eq!(window_start(date("2024-04-30")), date("2024-02-01"));
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let crate_id = diesel::insert_into(crates::table)
+            .values(crates::name.eq("foo"))
+            .returning(crates::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let version_id = diesel::insert_into(versions::table)
+            .values((
+                versions::crate_id.eq(crate_id),
+                versions::num.eq("1.0.0"),
+                versions::num_no_build.eq("1.0.0"),
+                versions::checksum.eq(""),
+                versions::crate_size.eq(0),
+            ))
+            .returning(versions::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        for (day, downloads) in [("2024-01-31", 100), ("2024-02-01", 20), ("2024-02-02", 3)] {
+            diesel::insert_into(version_downloads::table)
+                .values((
+                    version_downloads::version_id.eq(version_id),
+                    version_downloads::date.eq(date(day)),
+                    version_downloads::downloads.eq(downloads),
+                    version_downloads::counted.eq(downloads),
+                    version_downloads::processed.eq(true),
+                ))
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+
+        diesel::update(metadata::table)
+            .set(metadata::recent_downloads_start.eq(date("2024-01-31")))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        diesel::insert_into(recent_crate_downloads::table)
+            .values((
+                recent_crate_downloads::crate_id.eq(crate_id),
+                recent_crate_downloads::downloads.eq(123),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        update(&mut conn, date("2024-04-30")).await.unwrap();
+        assert_eq!(recent_downloads(&mut conn, crate_id).await, Ok(23));
+        let start = recent_downloads_start(&mut conn).await.unwrap();
+        assert_eq!(start, date("2024-02-01"));
+
+        // Running the job again on the same day doesn't change anything.
+        update(&mut conn, date("2024-04-30")).await.unwrap();
+        assert_eq!(recent_downloads(&mut conn, crate_id).await, Ok(23));
+
+        // Crates without recent downloads are removed from the table.
+        update(&mut conn, date("2024-05-02")).await.unwrap();
+        assert_eq!(
+            recent_downloads(&mut conn, crate_id).await,
+            Err(diesel::NotFound)
+        );
+        let start = recent_downloads_start(&mut conn).await.unwrap();
+        assert_eq!(start, date("2024-02-03"));
+    }
+}
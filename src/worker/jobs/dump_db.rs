@@ -1,11 +1,20 @@
+use crate::models::{DbDump, DbDumpKind, NewDbDump};
 use crate::tasks::spawn_blocking;
 use crate::worker::Environment;
-use crates_io_database_dump::{create_archives, DumpDirectory};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use crates_io_database_dump::{create_archives, Archives, DumpDirectory};
 use crates_io_worker::BackgroundJob;
+use diesel_async::AsyncPgConnection;
+use hex::ToHex;
 use secrecy::ExposeSecret;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Incremental dumps older than this are deleted from the storage.
+const INCREMENTAL_RETENTION_DAYS: i64 = 30;
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct DumpDb;
 
@@ -17,6 +26,9 @@ impl BackgroundJob for DumpDb {
 
     /// Create CSV dumps of the public information in the database, wrap them in a
     /// tarball and upload to S3.
+    ///
+    /// If a previous dump exists, an additional incremental dump is created,
+    /// which only contains the rows that have changed since then.
     async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
         const TAR_PATH: &str = "db-dump.tar.gz";
         const ZIP_PATH: &str = "db-dump.zip";
@@ -25,16 +37,21 @@ impl BackgroundJob for DumpDb {
         let db_pool_config = db_config.replica.as_ref().unwrap_or(&db_config.primary);
         let database_url = db_pool_config.url.clone();
 
-        let archives = spawn_blocking(move || {
+        let mut conn = env.deadpool.get().await?;
+        let since = DbDump::latest_timestamp(&mut conn).await?;
+
+        let url = database_url.clone();
+        let (timestamp, archives) = spawn_blocking(move || {
             let directory = DumpDirectory::create()?;
 
             info!("Exporting database…");
-            directory.populate(database_url.expose_secret())?;
+            directory.populate(url.expose_secret())?;
 
             let export_dir = directory.path();
             info!(path = ?export_dir, "Creating tarball…");
             let tarball_prefix = PathBuf::from(directory.timestamp.format("%F-%H%M%S").to_string());
-            create_archives(export_dir, &tarball_prefix)
+            let archives = create_archives(export_dir, &tarball_prefix)?;
+            Ok::<_, anyhow::Error>((directory.timestamp, archives))
         })
         .await??;
 
@@ -60,6 +77,96 @@ impl BackgroundJob for DumpDb {
             warn!("Failed to invalidate CDN caches: {error}");
         }
 
+        let paths = (TAR_PATH, ZIP_PATH);
+        let kind = DbDumpKind::Full;
+        record_archives(&mut conn, kind, paths, archives, None, timestamp).await?;
+
+        if let Some(since) = since {
+            let (timestamp, archives) = spawn_blocking(move || {
+                let directory = DumpDirectory::create_incremental(since)?;
+
+                info!(%since, "Exporting incremental database dump…");
+                directory.populate(database_url.expose_secret())?;
+
+                let export_dir = directory.path();
+                info!(path = ?export_dir, "Creating incremental tarball…");
+                let timestamp = directory.timestamp.format("%F-%H%M%S");
+                let tarball_prefix = PathBuf::from(format!("{timestamp}-incremental"));
+                let archives = create_archives(export_dir, &tarball_prefix)?;
+                Ok::<_, anyhow::Error>((directory.timestamp, archives))
+            })
+            .await??;
+
+            let name = timestamp.format("%F-%H%M%S");
+            let tar_path = format!("db-dump-incremental/{name}.tar.gz");
+            let zip_path = format!("db-dump-incremental/{name}.zip");
+
+            info!("Uploading incremental tarball…");
+            env.storage
+                .upload_db_dump(&tar_path, archives.tar.path())
+                .await?;
+
+            info!("Uploading incremental zip file…");
+            env.storage
+                .upload_db_dump(&zip_path, archives.zip.path())
+                .await?;
+            info!("Incremental database dump uploaded");
+
+            let paths = (tar_path.as_str(), zip_path.as_str());
+            let kind = DbDumpKind::Incremental;
+            record_archives(&mut conn, kind, paths, archives, Some(since), timestamp).await?;
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::days(INCREMENTAL_RETENTION_DAYS);
+        let expired = DbDump::delete_incremental_before(&mut conn, cutoff).await?;
+        for path in expired {
+            info!(%path, "Deleting expired incremental database dump…");
+            if let Err(error) = env.storage.delete_db_dump(&path).await {
+                warn!(%path, "Failed to delete expired incremental database dump: {error}");
+            }
+        }
+
         Ok(())
     }
 }
+
+/// Adds the uploaded tarball and zip file to the `db_dumps` table, which is
+/// used to serve the dump manifest API.
+async fn record_archives(
+    conn: &mut AsyncPgConnection,
+    kind: DbDumpKind,
+    (tar_path, zip_path): (&str, &str),
+    archives: Archives,
+    since: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let (tar, zip) = spawn_blocking(move || {
+        let tar = file_summary(archives.tar.path())?;
+        let zip = file_summary(archives.zip.path())?;
+        Ok::<_, anyhow::Error>((tar, zip))
+    })
+    .await??;
+
+    for (path, (size, checksum)) in [(tar_path, tar), (zip_path, zip)] {
+        NewDbDump::builder()
+            .kind(kind)
+            .path(path)
+            .size(size)
+            .checksum(&checksum)
+            .maybe_since(since)
+            .created_at(created_at)
+            .build()
+            .insert(conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Returns the size and the hex-encoded SHA256 checksum of a file.
+fn file_summary(path: &Path) -> anyhow::Result<(i64, String)> {
+    let mut file = std::fs::File::open(path).context("Failed to open archive")?;
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut file, &mut hasher).context("Failed to hash archive")?;
+    Ok((size as i64, hasher.finalize().encode_hex()))
+}
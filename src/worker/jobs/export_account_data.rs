@@ -0,0 +1,385 @@
+use crate::email::Email;
+use crate::models::{AccountExport, OwnerKind, User};
+use crate::schema::{
+    account_exports, api_tokens, crate_owner_invitations, crate_owners, crates, emails, follows,
+    versions,
+};
+use crate::util::rfc3339;
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use secrecy::ExposeSecret;
+use std::sync::Arc;
+
+/// How long a generated export can be downloaded before it expires.
+const EXPORT_LIFETIME: TimeDelta = TimeDelta::days(7);
+
+/// A background job that assembles all data associated with a user account
+/// into a JSON document and uploads it to the storage backend, where the user
+/// can download it via a secret URL until it expires.
+#[derive(Serialize, Deserialize)]
+pub struct ExportAccountData {
+    export_id: i32,
+}
+
+impl ExportAccountData {
+    pub fn new(export_id: i32) -> Self {
+        Self { export_id }
+    }
+}
+
+impl BackgroundJob for ExportAccountData {
+    const JOB_NAME: &'static str = "export_account_data";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(export_id = self.export_id))]
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        let export: Option<AccountExport> = account_exports::table
+            .find(self.export_id)
+            .select(AccountExport::as_select())
+            .first(&mut conn)
+            .await
+            .optional()?;
+
+        let Some(export) = export else {
+            warn!("Account export not found. Skipping.");
+            return Ok(());
+        };
+
+        if export.is_completed() {
+            info!("Account export was already generated. Skipping.");
+            return Ok(());
+        }
+
+        let user = User::find(&mut conn, export.user_id).await?;
+
+        info!(user.id, "Collecting account data…");
+        let data = AccountData::load(&mut conn, &user).await?;
+        let bytes = serde_json::to_vec_pretty(&data)?;
+
+        info!(user.id, "Uploading account export…");
+        ctx.storage
+            .upload_account_export(user.id, export.id, bytes.into())
+            .await
+            .context("Failed to upload account export")?;
+
+        let completed_at = Utc::now();
+        let expires_at = completed_at + EXPORT_LIFETIME;
+        diesel::update(&export)
+            .set((
+                account_exports::completed_at.eq(completed_at),
+                account_exports::expires_at.eq(expires_at),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        delete_previous_exports(&ctx, &mut conn, &export).await?;
+
+        if let Some(recipient) = user.verified_email(&mut conn).await? {
+            let email = AccountExportReadyEmail {
+                user_name: &user.gh_login,
+                domain: &ctx.emails.domain,
+                token: export.token.expose_secret(),
+                expires_at,
+            };
+
+            if let Err(error) = ctx.emails.send(&recipient, email).await {
+                warn!(%error, "Failed to send account export notification");
+            }
+        }
+
+        info!(user.id, "Account export is ready");
+        Ok(())
+    }
+}
+
+/// Removes all older exports of the user from the database and the storage
+/// backend, so that only the most recent export is retained.
+async fn delete_previous_exports(
+    ctx: &Environment,
+    conn: &mut AsyncPgConnection,
+    export: &AccountExport,
+) -> anyhow::Result<()> {
+    let previous: Vec<(i32, Option<DateTime<Utc>>)> = account_exports::table
+        .filter(account_exports::user_id.eq(export.user_id))
+        .filter(account_exports::id.ne(export.id))
+        .select((account_exports::id, account_exports::completed_at))
+        .load(conn)
+        .await?;
+
+    for (id, completed_at) in previous {
+        if completed_at.is_some() {
+            ctx.storage
+                .delete_account_export(export.user_id, id)
+                .await
+                .context("Failed to delete previous account export")?;
+        }
+
+        diesel::delete(account_exports::table.find(id))
+            .execute(conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct AccountData {
+    generated_at: DateTime<Utc>,
+    user: UserData,
+    emails: Vec<EmailData>,
+    api_tokens: Vec<ApiTokenData>,
+    owned_crates: Vec<OwnedCrateData>,
+    invitations: Vec<InvitationData>,
+    published_versions: Vec<PublishedVersionData>,
+    followed_crates: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct UserData {
+    id: i32,
+    login: String,
+    name: Option<String>,
+    avatar: Option<String>,
+    github_id: i32,
+    is_admin: bool,
+    publish_notifications: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct EmailData {
+    email: String,
+    verified: bool,
+    primary: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiTokenData {
+    name: String,
+    #[serde(with = "rfc3339")]
+    created_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    last_used_at: Option<NaiveDateTime>,
+    #[serde(with = "rfc3339::option")]
+    expired_at: Option<NaiveDateTime>,
+    revoked: bool,
+    crate_scopes: Option<Vec<String>>,
+    endpoint_scopes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct OwnedCrateData {
+    name: String,
+    #[serde(with = "rfc3339")]
+    owner_since: NaiveDateTime,
+    email_notifications: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct InvitationData {
+    crate_name: String,
+    invited_user_id: i32,
+    invited_by_user_id: i32,
+    #[serde(with = "rfc3339")]
+    created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize)]
+struct PublishedVersionData {
+    crate_name: String,
+    version: String,
+    #[serde(with = "rfc3339")]
+    created_at: NaiveDateTime,
+    yanked: bool,
+}
+
+impl AccountData {
+    async fn load(conn: &mut AsyncPgConnection, user: &User) -> QueryResult<Self> {
+        let emails = emails::table
+            .filter(emails::user_id.eq(user.id))
+            .select((emails::email, emails::verified, emails::is_primary))
+            .order(emails::id)
+            .load::<(String, bool, bool)>(conn)
+            .await?
+            .into_iter()
+            .map(|(email, verified, primary)| EmailData {
+                email,
+                verified,
+                primary,
+            })
+            .collect();
+
+        let api_tokens = api_tokens::table
+            .filter(api_tokens::user_id.eq(user.id))
+            .select((
+                api_tokens::name,
+                api_tokens::created_at,
+                api_tokens::last_used_at,
+                api_tokens::expired_at,
+                api_tokens::revoked,
+                api_tokens::crate_scopes,
+                api_tokens::endpoint_scopes,
+            ))
+            .order(api_tokens::id)
+            .load(conn)
+            .await?
+            .into_iter()
+            .map(
+                |(
+                    name,
+                    created_at,
+                    last_used_at,
+                    expired_at,
+                    revoked,
+                    crate_scopes,
+                    endpoint_scopes,
+                )| {
+                    ApiTokenData {
+                        name,
+                        created_at,
+                        last_used_at,
+                        expired_at,
+                        revoked,
+                        crate_scopes,
+                        endpoint_scopes,
+                    }
+                },
+            )
+            .collect();
+
+        let owned_crates = crate_owners::table
+            .inner_join(crates::table)
+            .filter(crate_owners::owner_id.eq(user.id))
+            .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+            .filter(crate_owners::deleted.eq(false))
+            .select((
+                crates::name,
+                crate_owners::created_at,
+                crate_owners::email_notifications,
+            ))
+            .order(crates::name)
+            .load(conn)
+            .await?
+            .into_iter()
+            .map(|(name, owner_since, email_notifications)| OwnedCrateData {
+                name,
+                owner_since,
+                email_notifications,
+            })
+            .collect();
+
+        let invitations = crate_owner_invitations::table
+            .inner_join(crates::table)
+            .filter(
+                crate_owner_invitations::invited_user_id
+                    .eq(user.id)
+                    .or(crate_owner_invitations::invited_by_user_id.eq(user.id)),
+            )
+            .select((
+                crates::name,
+                crate_owner_invitations::invited_user_id,
+                crate_owner_invitations::invited_by_user_id,
+                crate_owner_invitations::created_at,
+            ))
+            .order(crate_owner_invitations::created_at)
+            .load(conn)
+            .await?
+            .into_iter()
+            .map(
+                |(crate_name, invited_user_id, invited_by_user_id, created_at)| InvitationData {
+                    crate_name,
+                    invited_user_id,
+                    invited_by_user_id,
+                    created_at,
+                },
+            )
+            .collect();
+
+        let published_versions = versions::table
+            .inner_join(crates::table)
+            .filter(versions::published_by.eq(user.id))
+            .select((
+                crates::name,
+                versions::num,
+                versions::created_at,
+                versions::yanked,
+            ))
+            .order(versions::created_at)
+            .load(conn)
+            .await?
+            .into_iter()
+            .map(
+                |(crate_name, version, created_at, yanked)| PublishedVersionData {
+                    crate_name,
+                    version,
+                    created_at,
+                    yanked,
+                },
+            )
+            .collect();
+
+        let followed_crates = follows::table
+            .inner_join(crates::table)
+            .filter(follows::user_id.eq(user.id))
+            .select(crates::name)
+            .order(crates::name)
+            .load(conn)
+            .await?;
+
+        Ok(AccountData {
+            generated_at: Utc::now(),
+            user: UserData {
+                id: user.id,
+                login: user.gh_login.clone(),
+                name: user.name.clone(),
+                avatar: user.gh_avatar.clone(),
+                github_id: user.gh_id,
+                is_admin: user.is_admin,
+                publish_notifications: user.publish_notifications,
+            },
+            emails,
+            api_tokens,
+            owned_crates,
+            invitations,
+            published_versions,
+            followed_crates,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AccountExportReadyEmail<'a> {
+    user_name: &'a str,
+    domain: &'a str,
+    token: &'a str,
+    expires_at: DateTime<Utc>,
+}
+
+impl Email for AccountExportReadyEmail<'_> {
+    fn subject(&self) -> String {
+        "crates.io: Your account data export is ready".into()
+    }
+
+    fn body(&self) -> String {
+        format!(
+            "Hello {user_name}!
+
+The export of your crates.io account data that you requested is ready. You can download it at:
+
+https://{domain}/api/v1/me/export/{token}
+
+This link will expire on {expires_at}. If you did not request this export, please contact help@crates.io immediately.",
+            user_name = self.user_name,
+            domain = self.domain,
+            token = self.token,
+            expires_at = self.expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )
+    }
+}
@@ -0,0 +1,310 @@
+use crate::email::Email;
+use crate::models::VersionAction;
+use crate::schema::{crates, emails, follows, users, version_owner_actions, versions};
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Background job that notifies the followers of crates about new versions
+/// and yanks, through the notification channels that they chose.
+///
+/// The job is meant to be run periodically. All releases since the previous
+/// run are batched into a single notification per follower, so that rapid
+/// releases don't flood the followers with emails.
+#[derive(Default, Serialize, Deserialize, Debug)]
+pub struct SendFollowNotifications;
+
+impl BackgroundJob for SendFollowNotifications {
+    const JOB_NAME: &'static str = "send_follow_notifications";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip(env), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+        let until = Utc::now();
+
+        let follows = follows::table
+            .inner_join(crates::table)
+            .filter(follows::notify_email.or(follows::webhook_url.is_not_null()))
+            .filter(follows::notified_at.lt(until))
+            .select(FollowRow::as_select())
+            .load(&mut conn)
+            .await?;
+
+        let Some(since) = follows.iter().map(|follow| follow.notified_at).min() else {
+            info!("Skipping follow notifications: no crates with notifications found");
+            return Ok(());
+        };
+
+        let events = load_events(&mut conn, &follows, since, until).await?;
+
+        let mut emails_by_user: HashMap<i32, Vec<(&str, Vec<&Event>)>> = HashMap::new();
+        for follow in &follows {
+            let Some(events) = events.get(&follow.crate_id) else {
+                continue;
+            };
+
+            let events = events
+                .iter()
+                .filter(|event| event.time > follow.notified_at)
+                .collect::<Vec<_>>();
+
+            if events.is_empty() {
+                continue;
+            }
+
+            if let Some(webhook_url) = &follow.webhook_url {
+                let payload = WebhookPayload {
+                    krate: &follow.crate_name,
+                    events: &events,
+                };
+
+                if let Err(error) = send_webhook(&env, webhook_url, &payload).await {
+                    warn!(%webhook_url, "Failed to send follow notification webhook: {error:#}");
+                }
+            }
+
+            if follow.notify_email {
+                let crates = emails_by_user.entry(follow.user_id).or_default();
+                crates.push((follow.crate_name.as_str(), events));
+            }
+        }
+
+        let user_ids = emails_by_user.keys().copied().collect::<Vec<_>>();
+        let recipients = users::table
+            .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+            .filter(users::id.eq_any(user_ids))
+            .filter(emails::is_primary)
+            .filter(emails::verified.eq(true))
+            .select((users::id, users::gh_login, emails::email))
+            .load::<(i32, String, String)>(&mut conn)
+            .await?;
+
+        let mut failed_user_ids = vec![];
+        for (user_id, user_name, email_address) in recipients {
+            let Some(crates) = emails_by_user.remove(&user_id) else {
+                continue;
+            };
+
+            let email = FollowNotificationEmail {
+                user_name: &user_name,
+                domain: &env.config.domain_name,
+                crates,
+            };
+
+            if let Err(error) = env.emails.send(&email_address, email).await {
+                warn!("Failed to send follow notifications to {email_address}: {error}");
+                failed_user_ids.push(user_id);
+            }
+        }
+
+        // Users with failed emails are notified again on the next run.
+        diesel::update(follows::table)
+            .filter(follows::notify_email.or(follows::webhook_url.is_not_null()))
+            .filter(follows::notified_at.lt(until))
+            .filter(follows::user_id.ne_all(failed_user_ids))
+            .set(follows::notified_at.eq(until))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Queryable, Selectable)]
+struct FollowRow {
+    #[diesel(select_expression = follows::user_id)]
+    user_id: i32,
+    #[diesel(select_expression = follows::crate_id)]
+    crate_id: i32,
+    #[diesel(select_expression = crates::name)]
+    crate_name: String,
+    #[diesel(select_expression = follows::notify_email)]
+    notify_email: bool,
+    #[diesel(select_expression = follows::webhook_url)]
+    webhook_url: Option<String>,
+    #[diesel(select_expression = follows::notified_at)]
+    notified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct Event {
+    action: &'static str,
+    version: String,
+    time: DateTime<Utc>,
+}
+
+/// Loads the published and yanked versions of the followed crates, grouped
+/// by crate ID.
+async fn load_events(
+    conn: &mut AsyncPgConnection,
+    follows: &[FollowRow],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> QueryResult<HashMap<i32, Vec<Event>>> {
+    let crate_ids = follows
+        .iter()
+        .map(|follow| follow.crate_id)
+        .collect::<Vec<_>>();
+
+    let rows = version_owner_actions::table
+        .inner_join(versions::table)
+        .filter(versions::crate_id.eq_any(crate_ids))
+        .filter(version_owner_actions::action.ne(VersionAction::Unyank))
+        .filter(version_owner_actions::time.gt(since.naive_utc()))
+        .filter(version_owner_actions::time.le(until.naive_utc()))
+        .select((
+            versions::crate_id,
+            versions::num,
+            version_owner_actions::action,
+            version_owner_actions::time,
+        ))
+        .order(version_owner_actions::time)
+        .load::<(i32, String, VersionAction, NaiveDateTime)>(conn)
+        .await?;
+
+    let mut events: HashMap<i32, Vec<Event>> = HashMap::new();
+    for (crate_id, version, action, time) in rows {
+        events.entry(crate_id).or_default().push(Event {
+            action: action.into(),
+            version,
+            time: time.and_utc(),
+        });
+    }
+
+    Ok(events)
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    #[serde(rename = "crate")]
+    krate: &'a str,
+    events: &'a [&'a Event],
+}
+
+async fn send_webhook(
+    env: &Environment,
+    url: &str,
+    payload: &WebhookPayload<'_>,
+) -> anyhow::Result<()> {
+    env.http_client
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .context("Failed to send the webhook request")?
+        .error_for_status()
+        .context("Received an error response from the webhook")?;
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct FollowNotificationEmail<'a> {
+    user_name: &'a str,
+    domain: &'a str,
+    crates: Vec<(&'a str, Vec<&'a Event>)>,
+}
+
+impl Email for FollowNotificationEmail<'_> {
+    fn subject(&self) -> String {
+        match &self.crates[..] {
+            [(krate, _)] => format!("crates.io: New releases of {krate}"),
+            _ => "crates.io: New releases of crates you follow".into(),
+        }
+    }
+
+    fn body(&self) -> String {
+        let mut body = format!(
+            "Hello {}!\n\nThere are new releases of crates that you follow on crates.io:\n",
+            self.user_name
+        );
+
+        let crates = self.crates.iter().cloned().collect::<BTreeMap<_, _>>();
+        for (krate, events) in crates {
+            let _ = write!(body, "\n{krate} (https://{}/crates/{krate})\n", self.domain);
+            for event in events {
+                let time = event.time.to_rfc3339_opts(SecondsFormat::Secs, true);
+                let action = match event.action {
+                    "yank" => "yanked",
+                    _ => "published",
+                };
+
+                let _ = writeln!(body, "- {} {action} at {time}", event.version);
+            }
+        }
+
+        body.push_str(
+            "\nYou can switch off these notifications in the `following` settings of the crates.",
+        );
+
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    fn event(action: &'static str, version: &str) -> Event {
+        let time = "2024-01-01T12:00:00Z".parse().unwrap();
+        let version = version.to_string();
+        Event {
+            action,
+            version,
+            time,
+        }
+    }
+
+    #[test]
+    fn test_email() {
+        let publish = event("publish", "1.1.0");
+        let yank = event("yank", "1.0.0");
+        let other = event("publish", "0.1.0");
+
+        let email = FollowNotificationEmail {
+            user_name: "foo",
+            domain: "crates.io",
+            crates: vec![("foo", vec![&publish, &yank]), ("bar", vec![&other])],
+        };
+
+        assert_eq!(
+            email.subject(),
+            "crates.io: New releases of crates you follow"
+        );
+        assert_snapshot!(email.body(), @r"
+        Hello foo!
+
+        There are new releases of crates that you follow on crates.io:
+
+        bar (https://crates.io/crates/bar)
+        - 0.1.0 published at 2024-01-01T12:00:00Z
+
+        foo (https://crates.io/crates/foo)
+        - 1.1.0 published at 2024-01-01T12:00:00Z
+        - 1.0.0 yanked at 2024-01-01T12:00:00Z
+
+        You can switch off these notifications in the `following` settings of the crates.
+        ");
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let publish = event("publish", "1.1.0");
+        let payload = WebhookPayload {
+            krate: "foo",
+            events: &[&publish],
+        };
+
+        assert_snapshot!(serde_json::to_string(&payload).unwrap(), @r#"{"crate":"foo","events":[{"action":"publish","version":"1.1.0","time":"2024-01-01T12:00:00Z"}]}"#);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::schema::crates;
+use crate::storage::Storage;
+use crate::worker::Environment;
+use chrono::NaiveDateTime;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// The name of the sitemap index file, which lists all other sitemap files.
+pub const SITEMAP_INDEX: &str = "sitemap.xml";
+
+/// The maximum number of URLs in a single sitemap file, as defined by the
+/// sitemap protocol.
+const MAX_URLS_PER_SITEMAP: usize = 50_000;
+
+/// Generates the sitemaps of all crate pages and uploads them to the storage.
+///
+/// The crates are sharded by the first character of their name into files
+/// like `sitemaps/crates-s-1.xml`, which are listed in the sitemap index at
+/// `sitemaps/sitemap.xml`. Shards with more than [`MAX_URLS_PER_SITEMAP`]
+/// crates are split into multiple numbered files.
+///
+/// Hidden and quarantined crates are not included, since their pages are not
+/// meant to be found by search engines.
+#[derive(Serialize, Deserialize)]
+pub struct GenerateSitemaps;
+
+impl BackgroundJob for GenerateSitemaps {
+    const JOB_NAME: &'static str = "generate_sitemaps";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Loading crates for the sitemaps");
+        let crates: Vec<(String, NaiveDateTime)> = crates::table
+            .filter(crates::hidden_at.is_null())
+            .filter(crates::quarantined_at.is_null())
+            .select((crates::name, crates::updated_at))
+            .order(crates::name)
+            .load(&mut conn)
+            .await?;
+
+        drop(conn);
+
+        let domain = &env.config.domain_name;
+
+        let mut files = Vec::new();
+        for (shard, crates) in shard_crates(&crates) {
+            for (index, chunk) in crates.chunks(MAX_URLS_PER_SITEMAP).enumerate() {
+                let file = format!("crates-{shard}-{}.xml", index + 1);
+                let content = crates_sitemap(domain, chunk);
+                env.storage.upload_sitemap(&file, content).await?;
+                files.push(file);
+            }
+        }
+
+        let content = sitemap_index(&env.storage, domain, &files);
+        env.storage.upload_sitemap(SITEMAP_INDEX, content).await?;
+
+        info!(
+            "Uploaded {} sitemaps with {} crates",
+            files.len(),
+            crates.len()
+        );
+
+        Ok(())
+    }
+}
+
+/// Groups the crates by the lowercase first character of their name.
+fn shard_crates(
+    crates: &[(String, NaiveDateTime)],
+) -> BTreeMap<char, Vec<&(String, NaiveDateTime)>> {
+    let mut shards: BTreeMap<char, Vec<_>> = BTreeMap::new();
+    for krate in crates {
+        let first = krate.0.chars().next().unwrap_or('_');
+        shards
+            .entry(first.to_ascii_lowercase())
+            .or_default()
+            .push(krate);
+    }
+
+    shards
+}
+
+/// Renders the sitemap of the crate pages.
+///
+/// Crate names and domain names only contain characters that don't need to
+/// be escaped in XML.
+fn crates_sitemap(domain: &str, crates: &[&(String, NaiveDateTime)]) -> String {
+    let mut content = String::new();
+    content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    content.push('\n');
+    content.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    content.push('\n');
+
+    for (name, updated_at) in crates {
+        let lastmod = updated_at.format("%Y-%m-%d");
+        let _ = writeln!(
+            content,
+            "<url><loc>https://{domain}/crates/{name}</loc><lastmod>{lastmod}</lastmod></url>"
+        );
+    }
+
+    content.push_str("</urlset>\n");
+    content
+}
+
+/// Renders the sitemap index that lists the sitemap files.
+fn sitemap_index(storage: &Storage, domain: &str, files: &[String]) -> String {
+    let mut content = String::new();
+    content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    content.push('\n');
+    content.push_str(r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    content.push('\n');
+
+    for file in files {
+        let location = absolute_url(domain, storage.sitemap_location(file));
+        let _ = writeln!(content, "<sitemap><loc>{location}</loc></sitemap>");
+    }
+
+    content.push_str("</sitemapindex>\n");
+    content
+}
+
+/// Sitemaps have to use absolute URLs, but the storage returns relative
+/// locations if no CDN is configured.
+pub fn absolute_url(domain: &str, location: String) -> String {
+    if location.starts_with('/') {
+        format!("https://{domain}{location}")
+    } else {
+        location
+    }
+}
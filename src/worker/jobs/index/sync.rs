@@ -1,5 +1,6 @@
 use crate::index::get_index_data;
 use crate::tasks::spawn_blocking;
+use crate::worker::jobs::{PurgeSurrogateKeys, ReplicateIndexFile};
 use crate::worker::Environment;
 use anyhow::Context;
 use crates_io_index::Repository;
@@ -111,13 +112,25 @@ impl BackgroundJob for SyncToSparseIndex {
         let future = env.storage.sync_index(&self.krate, content);
         future.await.context("Failed to sync index data")?;
 
-        if let Some(cloudfront) = env.cloudfront() {
-            let path = Repository::relative_index_file_for_url(&self.krate);
-
-            info!(%path, "Invalidating index file on CloudFront");
-            let future = cloudfront.invalidate(&path);
-            future.await.context("Failed to invalidate CloudFront")?;
+        if env.standby_storage.is_some() {
+            ReplicateIndexFile::new(&self.krate)
+                .enqueue(&mut conn)
+                .await
+                .context("Failed to enqueue index replication")?;
         }
+
+        let path = Repository::relative_index_file_for_url(&self.krate);
+
+        info!(%path, "Queueing index file for invalidation on CloudFront");
+        env.queue_cloudfront_invalidation(&mut conn, &path)
+            .await
+            .context("Failed to queue CloudFront invalidation")?;
+
+        PurgeSurrogateKeys::krate(&self.krate)
+            .enqueue(&mut conn)
+            .await
+            .context("Failed to enqueue surrogate key purge")?;
+
         Ok(())
     }
 }
@@ -0,0 +1,332 @@
+//! Export of the essential metadata of all crates and their versions, which
+//! mirrors and offline tooling can use to bootstrap without querying the API
+//! for every single crate.
+//!
+//! The export consists of two files:
+//!
+//! ```text
+//! snapshot/crates.jsonl.gz
+//! snapshot/crates.index.json
+//! ```
+//!
+//! Each line of `crates.jsonl.gz` contains one crate with all of its
+//! versions, ordered by crate name. Every line is compressed as a separate
+//! gzip member. gzip decoders transparently concatenate the members, but it
+//! also allows `crates.index.json` to list the byte range of each crate in
+//! the compressed file, so that single crates can be fetched from the CDN
+//! with HTTP range requests.
+
+use crate::models::Version;
+use crate::schema::{crates, versions};
+use crate::tasks::spawn_blocking;
+use crate::util::rfc3339;
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hex::ToHex;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
+
+pub const SNAPSHOT_FILE: &str = "crates.jsonl.gz";
+pub const SNAPSHOT_INDEX_FILE: &str = "crates.index.json";
+
+/// The number of crates that are loaded from the database at a time.
+const BATCH_SIZE: i64 = 1000;
+
+/// Export the metadata of all crates and versions to the `snapshot/`
+/// directory of the public bucket.
+///
+/// Hidden and quarantined crates, and soft-deleted versions are not included.
+/// The job is enqueued again whenever a version is deleted or restored, so
+/// that deleted versions are removed from the snapshot right away.
+#[derive(Serialize, Deserialize)]
+pub struct ExportMetadataSnapshot;
+
+impl BackgroundJob for ExportMetadataSnapshot {
+    const JOB_NAME: &'static str = "export_metadata_snapshot";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Exporting metadata snapshot…");
+        let tempfile = tempfile::NamedTempFile::new().context("Failed to create temporary file")?;
+        let writer = SnapshotWriter::new(tempfile.reopen()?);
+        let index = export(&mut conn, writer).await?;
+        let num_crates = index.crates.len();
+
+        // The snapshot is uploaded before the index, so that the byte ranges
+        // of a new index never point into an old snapshot. Consumers can
+        // compare the `size` and `sha256` fields of the index with the
+        // snapshot they downloaded.
+        info!(num_crates, "Uploading metadata snapshot…");
+        env.storage
+            .upload_metadata_snapshot(SNAPSHOT_FILE, tempfile.path())
+            .await?;
+
+        let index_file =
+            tempfile::NamedTempFile::new().context("Failed to create temporary file")?;
+        let mut writer = BufWriter::new(index_file.reopen()?);
+        serde_json::to_writer(&mut writer, &index)?;
+        writer.flush()?;
+        env.storage
+            .upload_metadata_snapshot(SNAPSHOT_INDEX_FILE, index_file.path())
+            .await?;
+
+        info!("Invalidating CDN caches…");
+        for file in [SNAPSHOT_FILE, SNAPSHOT_INDEX_FILE] {
+            let path = format!("snapshot/{file}");
+            if let Err(error) = env.invalidate_cdns(&path).await {
+                warn!("Failed to invalidate CDN caches: {error}");
+            }
+        }
+
+        info!("Finished exporting metadata snapshot");
+        Ok(())
+    }
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = crates, check_for_backend(diesel::pg::Pg))]
+struct CrateRow {
+    id: i32,
+    name: String,
+    description: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    repository: Option<String>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotCrate {
+    name: String,
+    description: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    repository: Option<String>,
+    #[serde(with = "rfc3339")]
+    created_at: NaiveDateTime,
+    #[serde(with = "rfc3339")]
+    updated_at: NaiveDateTime,
+    versions: Vec<SnapshotVersion>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = versions, check_for_backend(diesel::pg::Pg))]
+struct SnapshotVersion {
+    #[serde(skip)]
+    crate_id: i32,
+    num: String,
+    checksum: String,
+    yanked: bool,
+    license: Option<String>,
+    rust_version: Option<String>,
+    edition: Option<String>,
+    crate_size: i32,
+    #[serde(with = "rfc3339")]
+    created_at: NaiveDateTime,
+}
+
+/// The contents of the `crates.index.json` file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotIndex {
+    pub generated_at: DateTime<Utc>,
+    /// The size of the snapshot file in bytes.
+    pub size: u64,
+    /// The hex encoded SHA256 checksum of the snapshot file.
+    pub sha256: String,
+    pub crates: BTreeMap<String, ByteRange>,
+}
+
+/// The position of a crate in the compressed snapshot file.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
+async fn export(
+    conn: &mut AsyncPgConnection,
+    mut writer: SnapshotWriter,
+) -> anyhow::Result<SnapshotIndex> {
+    let mut cursor = String::new();
+    loop {
+        let crates: Vec<CrateRow> = crates::table
+            .filter(crates::name.gt(&cursor))
+            .filter(crates::hidden_at.is_null())
+            .filter(crates::quarantined_at.is_null())
+            .order(crates::name)
+            .limit(BATCH_SIZE)
+            .select(CrateRow::as_select())
+            .load(conn)
+            .await?;
+
+        let Some(last) = crates.last() else {
+            break;
+        };
+        cursor = last.name.clone();
+        let is_last_batch = crates.len() < BATCH_SIZE as usize;
+
+        let crate_ids = crates.iter().map(|krate| krate.id).collect::<Vec<_>>();
+        let versions: Vec<SnapshotVersion> = versions::table
+            .filter(versions::crate_id.eq_any(&crate_ids))
+            .filter(Version::not_deleted())
+            .order(versions::id)
+            .select(SnapshotVersion::as_select())
+            .load(conn)
+            .await?;
+
+        let mut versions_by_crate: HashMap<i32, Vec<SnapshotVersion>> = HashMap::new();
+        for version in versions {
+            versions_by_crate
+                .entry(version.crate_id)
+                .or_default()
+                .push(version);
+        }
+
+        let crates = crates
+            .into_iter()
+            .map(|krate| SnapshotCrate {
+                versions: versions_by_crate.remove(&krate.id).unwrap_or_default(),
+                name: krate.name,
+                description: krate.description,
+                homepage: krate.homepage,
+                documentation: krate.documentation,
+                repository: krate.repository,
+                created_at: krate.created_at,
+                updated_at: krate.updated_at,
+            })
+            .collect::<Vec<_>>();
+
+        writer = spawn_blocking(move || {
+            for krate in &crates {
+                writer.write_crate(krate)?;
+            }
+            Ok::<_, anyhow::Error>(writer)
+        })
+        .await??;
+
+        if is_last_batch {
+            break;
+        }
+    }
+
+    spawn_blocking(move || writer.finish()).await?
+}
+
+/// Writes the crates to the snapshot file and keeps track of their byte
+/// ranges.
+struct SnapshotWriter {
+    file: BufWriter<File>,
+    hasher: Sha256,
+    size: u64,
+    crates: BTreeMap<String, ByteRange>,
+}
+
+impl SnapshotWriter {
+    fn new(file: File) -> Self {
+        Self {
+            file: BufWriter::new(file),
+            hasher: Sha256::new(),
+            size: 0,
+            crates: BTreeMap::new(),
+        }
+    }
+
+    fn write_crate(&mut self, krate: &SnapshotCrate) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(krate)?;
+        line.push(b'\n');
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&line)?;
+        let member = encoder.finish()?;
+
+        self.file.write_all(&member)?;
+        self.hasher.update(&member);
+
+        let length = member.len() as u64;
+        let range = ByteRange {
+            offset: self.size,
+            length,
+        };
+        self.crates.insert(krate.name.clone(), range);
+        self.size += length;
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> anyhow::Result<SnapshotIndex> {
+        self.file.flush()?;
+
+        Ok(SnapshotIndex {
+            generated_at: Utc::now(),
+            size: self.size,
+            sha256: self.hasher.finalize().encode_hex(),
+            crates: self.crates,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::{GzDecoder, MultiGzDecoder};
+    use std::io::Read;
+
+    fn snapshot_crate(name: &str) -> SnapshotCrate {
+        SnapshotCrate {
+            name: name.to_string(),
+            description: None,
+            homepage: None,
+            documentation: None,
+            repository: None,
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+            versions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_byte_ranges() {
+        let tempfile = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = SnapshotWriter::new(tempfile.reopen().unwrap());
+        writer.write_crate(&snapshot_crate("bar")).unwrap();
+        writer.write_crate(&snapshot_crate("foo")).unwrap();
+        let index = writer.finish().unwrap();
+
+        let bytes = std::fs::read(tempfile.path()).unwrap();
+        assert_eq!(index.size, bytes.len() as u64);
+
+        // The whole file can be decompressed at once...
+        let mut content = String::new();
+        MultiGzDecoder::new(bytes.as_slice())
+            .read_to_string(&mut content)
+            .unwrap();
+        let names = content
+            .lines()
+            .map(|line| serde_json::from_str::<SnapshotCrate>(line).unwrap().name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["bar", "foo"]);
+
+        // ... or single crates by their byte range.
+        let range = &index.crates["foo"];
+        let start = range.offset as usize;
+        let member = &bytes[start..start + range.length as usize];
+        let mut line = String::new();
+        GzDecoder::new(member).read_to_string(&mut line).unwrap();
+        let krate: SnapshotCrate = serde_json::from_str(&line).unwrap();
+        assert_eq!(krate.name, "foo");
+    }
+}
@@ -1,30 +1,85 @@
+mod analytics;
 mod archive_version_downloads;
+mod backfill_publish_methods;
+mod backfill_rust_versions;
+mod backfill_semver_ord;
+mod compute_version_diff;
 mod daily_db_maintenance;
 mod delete_crate;
+mod delete_expired_idempotency_keys;
 mod downloads;
 pub mod dump_db;
 mod expiry_notification;
+mod export_account_data;
+mod follow_notifications;
+pub mod generate_sitemaps;
 mod index;
 mod index_version_downloads_archive;
+pub mod metadata_snapshot;
+mod partition_version_downloads;
+mod process_cdn_invalidation_queue;
+mod publish_crate_file;
+mod purge_surrogate_keys;
 mod readmes;
+mod reconcile_storage;
+mod refresh_github_cache;
+mod replication;
 pub mod rss;
+mod scan_for_malware;
 mod send_publish_notifications;
+mod suggest_categories;
 mod sync_admins;
+mod sync_crate_file_quarantine;
 mod typosquat;
+mod update_aggregate_downloads;
+mod update_crate_dependent_stats;
+mod update_crate_scores;
 mod update_default_version;
+mod update_registry_stats;
+mod update_trending_crates;
+mod update_user_stats;
+mod weekly_digest;
 
+pub use self::analytics::ExportAnalytics;
 pub use self::archive_version_downloads::ArchiveVersionDownloads;
+pub use self::backfill_publish_methods::BackfillPublishMethods;
+pub use self::backfill_rust_versions::BackfillRustVersions;
+pub use self::backfill_semver_ord::BackfillSemverOrd;
+pub use self::compute_version_diff::ComputeVersionDiff;
 pub use self::daily_db_maintenance::DailyDbMaintenance;
 pub use self::delete_crate::DeleteCrateFromStorage;
+pub use self::delete_expired_idempotency_keys::DeleteExpiredIdempotencyKeys;
 pub use self::downloads::{
-    CleanProcessedLogFiles, ProcessCdnLog, ProcessCdnLogQueue, UpdateDownloads,
+    CheckRecentCrateDownloads, CleanProcessedLogFiles, ProcessCdnLog, ProcessCdnLogQueue,
+    UpdateDownloads, UpdateRecentCrateDownloads,
 };
 pub use self::dump_db::DumpDb;
 pub use self::expiry_notification::SendTokenExpiryNotifications;
+pub use self::export_account_data::ExportAccountData;
+pub use self::follow_notifications::SendFollowNotifications;
+pub use self::generate_sitemaps::GenerateSitemaps;
 pub use self::index::{NormalizeIndex, SquashIndex, SyncToGitIndex, SyncToSparseIndex};
 pub use self::index_version_downloads_archive::IndexVersionDownloadsArchive;
+pub use self::metadata_snapshot::ExportMetadataSnapshot;
+pub use self::partition_version_downloads::PartitionVersionDownloads;
+pub use self::process_cdn_invalidation_queue::ProcessCdnInvalidationQueue;
+pub use self::publish_crate_file::{PublishCrateFile, ReconcileStagedCrateFiles};
+pub use self::purge_surrogate_keys::PurgeSurrogateKeys;
 pub use self::readmes::RenderAndUploadReadme;
+pub use self::reconcile_storage::ReconcileStorage;
+pub use self::refresh_github_cache::RefreshGitHubCache;
+pub use self::replication::{ReplicateCrateFile, ReplicateIndexFile, VerifyReplication};
+pub use self::scan_for_malware::ScanVersionForMalware;
 pub use self::send_publish_notifications::SendPublishNotificationsJob;
+pub use self::suggest_categories::SuggestCategories;
 pub use self::sync_admins::SyncAdmins;
+pub use self::sync_crate_file_quarantine::SyncCrateFileQuarantine;
 pub use self::typosquat::CheckTyposquat;
+pub use self::update_aggregate_downloads::UpdateAggregateDownloads;
+pub use self::update_crate_dependent_stats::UpdateCrateDependentStats;
+pub use self::update_crate_scores::UpdateCrateScores;
 pub use self::update_default_version::UpdateDefaultVersion;
+pub use self::update_registry_stats::UpdateRegistryStats;
+pub use self::update_trending_crates::UpdateTrendingCrates;
+pub use self::update_user_stats::UpdateUserStats;
+pub use self::weekly_digest::SendWeeklyDigests;
@@ -0,0 +1,242 @@
+use crate::worker::Environment;
+use anyhow::{anyhow, Context};
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::{Date, Nullable, Text};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// The number of months after the current month that partitions are created
+/// for in advance.
+const UPCOMING_MONTHS: u32 = 2;
+
+/// The prefix of the names of the monthly `version_downloads` partitions,
+/// which are followed by the year and month, e.g. `version_downloads_2024_06`.
+const PARTITION_PREFIX: &str = "version_downloads_";
+
+define_sql_function!(fn create_version_downloads_partition(month: Date));
+
+/// Moves the rows of the default partition of the `version_downloads` table
+/// into monthly partitions, and creates the partitions for the current and
+/// the upcoming months.
+///
+/// Rows end up in the default partition if there is no partition for their
+/// month, e.g. all rows from before the table was partitioned. Each month is
+/// moved in a separate transaction to avoid holding the locks on the default
+/// partition for too long.
+#[derive(Serialize, Deserialize)]
+pub struct PartitionVersionDownloads;
+
+impl BackgroundJob for PartitionVersionDownloads {
+    const JOB_NAME: &'static str = "partition_version_downloads";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Partitioning version downloads…");
+        partition(&mut conn, Utc::now().date_naive()).await?;
+        info!("Finished partitioning version downloads");
+
+        Ok(())
+    }
+}
+
+async fn partition(conn: &mut AsyncPgConnection, today: NaiveDate) -> anyhow::Result<()> {
+    let mut previous_month = None;
+    while let Some(date) = oldest_default_partition_date(conn).await? {
+        let month = first_day_of_month(date);
+
+        // The partition of the month exists already, but isn't attached to
+        // the `version_downloads` table anymore, e.g. because it has been
+        // archived.
+        if previous_month == Some(month) {
+            return Err(anyhow!(
+                "Failed to move version downloads of {month} out of the default partition"
+            ));
+        }
+
+        info!("Moving version downloads of {month} to a new partition…");
+        create_partition(conn, month).await?;
+        previous_month = Some(month);
+    }
+
+    let current_month = first_day_of_month(today);
+    for offset in 0..=UPCOMING_MONTHS {
+        let month = current_month + Months::new(offset);
+        create_partition(conn, month).await?;
+    }
+
+    Ok(())
+}
+
+async fn oldest_default_partition_date(
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Option<NaiveDate>> {
+    diesel::select(sql::<Nullable<Date>>(
+        "(SELECT min(date) FROM partitions.version_downloads_default)",
+    ))
+    .get_result(conn)
+    .await
+}
+
+pub(super) async fn create_partition(
+    conn: &mut AsyncPgConnection,
+    month: NaiveDate,
+) -> anyhow::Result<()> {
+    diesel::select(create_version_downloads_partition(month))
+        .execute(conn)
+        .await
+        .with_context(|| format!("Failed to create version downloads partition for {month}"))?;
+
+    Ok(())
+}
+
+/// Returns the months of the monthly partitions that are currently attached
+/// to the `version_downloads` table, in ascending order.
+pub(super) async fn list_partitions(
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<Vec<NaiveDate>> {
+    #[derive(QueryableByName)]
+    struct Partition {
+        #[diesel(sql_type = Text)]
+        name: String,
+    }
+
+    let partitions: Vec<Partition> = diesel::sql_query(
+        "SELECT pg_class.relname AS name FROM pg_inherits \
+        INNER JOIN pg_class ON pg_class.oid = pg_inherits.inhrelid \
+        WHERE pg_inherits.inhparent = 'version_downloads'::regclass",
+    )
+    .load(conn)
+    .await?;
+
+    let mut months = partitions
+        .iter()
+        .filter_map(|partition| parse_partition_name(&partition.name))
+        .collect::<Vec<_>>();
+
+    months.sort();
+
+    Ok(months)
+}
+
+/// Returns the name of the `version_downloads` partition for the given month.
+pub(super) fn partition_name(month: NaiveDate) -> String {
+    format!("{PARTITION_PREFIX}{}", month.format("%Y_%m"))
+}
+
+/// Returns the month of a `version_downloads_YYYY_MM` partition name, or
+/// `None` for other names, e.g. of the default partition.
+fn parse_partition_name(name: &str) -> Option<NaiveDate> {
+    let suffix = name.strip_prefix(PARTITION_PREFIX)?;
+    NaiveDate::parse_from_str(&format!("{suffix}_01"), "%Y_%m_%d").ok()
+}
+
+fn first_day_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{crates, version_downloads, versions};
+    use crates_io_test_db::TestDatabase;
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%F").unwrap()
+    }
+
+    #[test]
+    fn test_partition_name() {
+        let name = partition_name(date("2024-06-01"));
+        assert_eq!(name, "version_downloads_2024_06");
+        assert_eq!(parse_partition_name(&name), Some(date("2024-06-01")));
+        assert_eq!(parse_partition_name("version_downloads_default"), None);
+        assert_eq!(parse_partition_name("version_downloads_2024_13"), None);
+    }
+
+    #[tokio::test]
+    async fn test_partition() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let crate_id = diesel::insert_into(crates::table)
+            .values(crates::name.eq("foo"))
+            .returning(crates::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let version_id = diesel::insert_into(versions::table)
+            .values((
+                versions::crate_id.eq(crate_id),
+                versions::num.eq("1.0.0"),
+                versions::num_no_build.eq("1.0.0"),
+                versions::checksum.eq(""),
+                versions::crate_size.eq(0),
+            ))
+            .returning(versions::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        for (day, downloads) in [
+            ("2021-01-01", 100),
+            ("2021-01-31", 200),
+            ("2021-03-15", 300),
+        ] {
+            diesel::insert_into(version_downloads::table)
+                .values((
+                    version_downloads::version_id.eq(version_id),
+                    version_downloads::date.eq(date(day)),
+                    version_downloads::downloads.eq(downloads),
+                ))
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+
+        partition(&mut conn, date("2021-03-20")).await.unwrap();
+
+        let months = list_partitions(&mut conn).await.unwrap();
+        let expected = ["2021-01-01", "2021-03-01", "2021-04-01", "2021-05-01"];
+        assert_eq!(months, expected.map(date));
+
+        assert_eq!(
+            oldest_default_partition_date(&mut conn).await.unwrap(),
+            None
+        );
+
+        let total: Option<i64> = version_downloads::table
+            .select(diesel::dsl::sum(version_downloads::downloads))
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(total, Some(600));
+
+        // New rows end up in the partitions of their month.
+        diesel::insert_into(version_downloads::table)
+            .values((
+                version_downloads::version_id.eq(version_id),
+                version_downloads::date.eq(date("2021-04-02")),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            oldest_default_partition_date(&mut conn).await.unwrap(),
+            None
+        );
+
+        // Running the job again doesn't change anything.
+        partition(&mut conn, date("2021-03-20")).await.unwrap();
+        let months = list_partitions(&mut conn).await.unwrap();
+        assert_eq!(months, expected.map(date));
+    }
+}
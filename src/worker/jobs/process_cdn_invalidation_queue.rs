@@ -0,0 +1,188 @@
+use crate::models::{Cdn, CdnInvalidation};
+use crate::schema::cdn_invalidations;
+use crate::worker::Environment;
+use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::{exists, min};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use futures_util::StreamExt;
+use std::sync::Arc;
+
+/// How long paths are collected before they are invalidated, so that the
+/// paths of a burst of index updates end up in the same batch.
+const BATCH_WINDOW: TimeDelta = TimeDelta::seconds(10);
+
+/// The maximum number of paths in a single CloudFront invalidation. This is
+/// well below the CloudFront limit of 3000 paths per request, since
+/// CloudFront also limits the number of paths that are being invalidated at
+/// the same time.
+const CLOUDFRONT_BATCH_SIZE: i64 = 1000;
+
+/// The maximum number of paths that are purged on Fastly by a single job
+/// run. Fastly purges paths one by one.
+const FASTLY_BATCH_SIZE: i64 = 500;
+
+/// The maximum number of concurrent Fastly purge requests.
+const FASTLY_CONCURRENCY: usize = 10;
+
+/// Invalidates the paths in the `cdn_invalidations` queue in batches.
+///
+/// Paths are added to the queue with [`Environment::queue_cdn_invalidation`]
+/// instead of being invalidated right away, which would run into the rate
+/// limits of the CDN APIs when many crates are published at the same time.
+/// Paths that fail to be invalidated stay in the queue, and the job is
+/// retried.
+#[derive(Serialize, Deserialize)]
+pub struct ProcessCdnInvalidationQueue;
+
+impl BackgroundJob for ProcessCdnInvalidationQueue {
+    const JOB_NAME: &'static str = "process_cdn_invalidation_queue";
+    const PRIORITY: i16 = 100;
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        wait_for_batch_window(&mut conn).await?;
+
+        let cloudfront_result = invalidate_on_cloudfront(&env, &mut conn).await;
+        let fastly_result = invalidate_on_fastly(&env, &mut conn).await;
+
+        let is_empty = !diesel::select(exists(cdn_invalidations::table))
+            .get_result::<bool>(&mut conn)
+            .await?;
+
+        if !is_empty && cloudfront_result.is_ok() && fastly_result.is_ok() {
+            ProcessCdnInvalidationQueue.enqueue(&mut conn).await?;
+        }
+
+        cloudfront_result?;
+        fastly_result
+    }
+}
+
+/// Waits until the oldest queued path is at least [`BATCH_WINDOW`] old.
+async fn wait_for_batch_window(conn: &mut AsyncPgConnection) -> QueryResult<()> {
+    let oldest: Option<DateTime<Utc>> = cdn_invalidations::table
+        .select(min(cdn_invalidations::queued_at))
+        .get_result(conn)
+        .await?;
+
+    let remaining = oldest
+        .map(|oldest| oldest + BATCH_WINDOW - Utc::now())
+        .and_then(|remaining| remaining.to_std().ok());
+
+    if let Some(remaining) = remaining {
+        debug!(?remaining, "Waiting for more paths to invalidate");
+        tokio::time::sleep(remaining).await;
+    }
+
+    Ok(())
+}
+
+async fn invalidate_on_cloudfront(
+    env: &Environment,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    let batch = CdnInvalidation::oldest(conn, Cdn::CloudFront, CLOUDFRONT_BATCH_SIZE).await?;
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    let paths = batch
+        .iter()
+        .map(|invalidation| invalidation.path.clone())
+        .collect::<Vec<_>>();
+
+    let Some(cloudfront) = env.cloudfront() else {
+        warn!(
+            "Dropping {} queued paths, since CloudFront is not configured",
+            paths.len()
+        );
+        CdnInvalidation::dequeue(conn, Cdn::CloudFront, &paths, Utc::now()).await?;
+        return Ok(());
+    };
+
+    let started_at = Utc::now();
+
+    let path_refs = paths.iter().map(String::as_str).collect::<Vec<_>>();
+    info!("Invalidating {} paths on CloudFront", paths.len());
+    cloudfront.invalidate_many(&path_refs).await?;
+
+    CdnInvalidation::dequeue(conn, Cdn::CloudFront, &paths, started_at).await?;
+    log_latency(Cdn::CloudFront, &batch);
+
+    Ok(())
+}
+
+async fn invalidate_on_fastly(
+    env: &Environment,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    let batch = CdnInvalidation::oldest(conn, Cdn::Fastly, FASTLY_BATCH_SIZE).await?;
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    let Some(fastly) = env.fastly() else {
+        let paths = batch
+            .into_iter()
+            .map(|invalidation| invalidation.path)
+            .collect::<Vec<_>>();
+
+        warn!(
+            "Dropping {} queued paths, since Fastly is not configured",
+            paths.len()
+        );
+        CdnInvalidation::dequeue(conn, Cdn::Fastly, &paths, Utc::now()).await?;
+        return Ok(());
+    };
+
+    let started_at = Utc::now();
+
+    info!("Purging {} paths on Fastly", batch.len());
+    let purged_paths = futures_util::stream::iter(&batch)
+        .map(|invalidation| async move {
+            let path = &invalidation.path;
+            let result = fastly.invalidate(path).await;
+            result.map(|_| path.clone()).inspect_err(|error| {
+                warn!(%path, "Failed to purge path on Fastly: {error}");
+            })
+        })
+        .buffer_unordered(FASTLY_CONCURRENCY)
+        .filter_map(|result| async { result.ok() })
+        .collect::<Vec<_>>()
+        .await;
+
+    CdnInvalidation::dequeue(conn, Cdn::Fastly, &purged_paths, started_at).await?;
+    log_latency(Cdn::Fastly, &batch);
+
+    let num_failed = batch.len() - purged_paths.len();
+    if num_failed > 0 {
+        return Err(anyhow!("Failed to purge {num_failed} paths on Fastly"));
+    }
+
+    Ok(())
+}
+
+fn log_latency(cdn: Cdn, batch: &[CdnInvalidation]) {
+    let now = Utc::now();
+    if let Some(oldest) = batch
+        .iter()
+        .map(|invalidation| invalidation.queued_at)
+        .min()
+    {
+        let latency = (now - oldest).num_milliseconds();
+        info!(
+            cdn = cdn.name(),
+            latency_ms = latency,
+            "Invalidated {} paths",
+            batch.len()
+        );
+    }
+}
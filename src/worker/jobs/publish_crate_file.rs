@@ -0,0 +1,191 @@
+//! The publish endpoint doesn't upload `.crate` files to their public
+//! location directly. Instead it uploads them to a staging location and
+//! enqueues a [`PublishCrateFile`] job in the same transaction that inserts
+//! the version, so that the `background_jobs` table acts as an outbox: the
+//! file is only published, and the index only updated, if the transaction
+//! has been committed.
+//!
+//! Staged files of failed transactions, and of jobs that got lost, are
+//! cleaned up or published by the [`ReconcileStagedCrateFiles`] job.
+
+use crate::schema::{crates, versions};
+use crate::worker::jobs::{
+    ComputeVersionDiff, ReplicateCrateFile, ScanVersionForMalware, SendPublishNotificationsJob,
+    SyncCrateFileQuarantine, SyncToGitIndex, SyncToSparseIndex,
+};
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::{TimeDelta, Utc};
+use crates_io_worker::{BackgroundJob, EnqueueError};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use futures_util::TryFutureExt;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Staged files are only reconciled after this time, so that the
+/// transactions of ongoing publishes are not interfered with.
+const STAGED_FILE_MIN_AGE: TimeDelta = TimeDelta::hours(1);
+
+/// Moves the staged `.crate` file of a newly published version to its public
+/// location, and then enqueues the jobs that depend on it, like the index
+/// updates and the publish notifications.
+#[derive(Serialize, Deserialize)]
+pub struct PublishCrateFile {
+    version_id: i32,
+}
+
+impl PublishCrateFile {
+    pub fn new(version_id: i32) -> Self {
+        Self { version_id }
+    }
+}
+
+impl BackgroundJob for PublishCrateFile {
+    const JOB_NAME: &'static str = "publish_crate_file";
+    const PRIORITY: i16 = 100;
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(version_id = self.version_id), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let version_id = self.version_id;
+        let mut conn = env.deadpool.get().await?;
+
+        let version = versions::table
+            .inner_join(crates::table)
+            .filter(versions::id.eq(version_id))
+            .select((
+                crates::name,
+                versions::num,
+                crates::quarantined_at.is_not_null(),
+            ))
+            .first::<(String, String, bool)>(&mut conn)
+            .await
+            .optional()?;
+
+        let Some((name, num, quarantined)) = version else {
+            info!("Deleting staged crate file: version has been deleted");
+            env.storage.delete_staged_crate_file(version_id).await?;
+            return Ok(());
+        };
+
+        info!("Publishing crate file of {name}@{num}…");
+        env.storage
+            .publish_staged_crate_file(version_id, &name, &num)
+            .await
+            .context("Failed to publish staged crate file")?;
+
+        let replicate = env.standby_storage.is_some();
+        enqueue_follow_up_jobs(&mut conn, version_id, &name, &num, replicate, quarantined).await?;
+
+        Ok(())
+    }
+}
+
+/// Enqueues the jobs that need the public `.crate` file in a single
+/// transaction, so that a retry of the [`PublishCrateFile`] job doesn't
+/// enqueue them twice.
+async fn enqueue_follow_up_jobs(
+    conn: &mut AsyncPgConnection,
+    version_id: i32,
+    name: &str,
+    num: &str,
+    replicate: bool,
+    quarantined: bool,
+) -> Result<(), EnqueueError> {
+    conn.transaction(|conn| {
+        async move {
+            let git_index_job = SyncToGitIndex::new(name);
+            let sparse_index_job = SyncToSparseIndex::new(name);
+            let publish_notifications_job = SendPublishNotificationsJob::new(version_id);
+            let malware_scan_job = ScanVersionForMalware::new(version_id);
+            let version_diff_job = ComputeVersionDiff::new(version_id);
+
+            tokio::try_join!(
+                git_index_job.enqueue(conn),
+                sparse_index_job.enqueue(conn),
+                publish_notifications_job.enqueue(conn),
+                malware_scan_job.enqueue(conn).or_else(|error| async move {
+                    error!("Failed to enqueue `ScanVersionForMalware` job: {error}");
+                    Ok::<_, EnqueueError>(None)
+                }),
+                version_diff_job.enqueue(conn).or_else(|error| async move {
+                    error!("Failed to enqueue `ComputeVersionDiff` job: {error}");
+                    Ok::<_, EnqueueError>(None)
+                }),
+            )?;
+
+            if replicate {
+                let replication_job = ReplicateCrateFile::new(name, num);
+                if let Err(error) = replication_job.enqueue(conn).await {
+                    error!("Failed to enqueue `ReplicateCrateFile` job: {error}");
+                }
+            }
+
+            // New versions of quarantined crates must not stay in the public
+            // bucket until the crate has been approved.
+            if quarantined {
+                SyncCrateFileQuarantine::new(version_id)
+                    .enqueue(conn)
+                    .await?;
+            }
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Deletes the staged `.crate` files of versions that don't exist, because
+/// their publish transaction failed, and re-enqueues the [`PublishCrateFile`]
+/// jobs of the remaining ones.
+#[derive(Serialize, Deserialize)]
+pub struct ReconcileStagedCrateFiles;
+
+impl BackgroundJob for ReconcileStagedCrateFiles {
+    const JOB_NAME: &'static str = "reconcile_staged_crate_files";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let cutoff = Utc::now() - STAGED_FILE_MIN_AGE;
+        let staged = env.storage.list_staged_crate_files().await?;
+        let version_ids = staged
+            .into_iter()
+            .filter(|(_, last_modified)| *last_modified < cutoff)
+            .map(|(version_id, _)| version_id)
+            .collect::<Vec<_>>();
+
+        if version_ids.is_empty() {
+            info!("No staged crate files to reconcile");
+            return Ok(());
+        }
+
+        let existing: HashSet<i32> = versions::table
+            .filter(versions::id.eq_any(&version_ids))
+            .select(versions::id)
+            .load::<i32>(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        for version_id in version_ids {
+            if existing.contains(&version_id) {
+                warn!(version_id, "Re-enqueueing publish of staged crate file");
+                PublishCrateFile::new(version_id).enqueue(&mut conn).await?;
+            } else {
+                info!(version_id, "Deleting staged crate file of failed publish");
+                env.storage.delete_staged_crate_file(version_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
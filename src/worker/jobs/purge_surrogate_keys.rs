@@ -0,0 +1,48 @@
+use crate::surrogate_keys::SurrogateKey;
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use std::sync::Arc;
+
+/// Purges all cached API responses that are tagged with one of the keys on
+/// Fastly.
+///
+/// This is enqueued whenever data changes that is part of cacheable API
+/// responses, see [`crate::surrogate_keys`].
+#[derive(Serialize, Deserialize)]
+pub struct PurgeSurrogateKeys {
+    keys: Vec<String>,
+}
+
+impl PurgeSurrogateKeys {
+    pub fn new(keys: impl IntoIterator<Item = SurrogateKey>) -> Self {
+        let keys = keys.into_iter().map(|key| key.to_string()).collect();
+        Self { keys }
+    }
+
+    pub fn krate(name: &str) -> Self {
+        Self::new([SurrogateKey::krate(name)])
+    }
+
+    pub fn user(user_id: i32) -> Self {
+        Self::new([SurrogateKey::user(user_id)])
+    }
+}
+
+impl BackgroundJob for PurgeSurrogateKeys {
+    const JOB_NAME: &'static str = "purge_surrogate_keys";
+    const PRIORITY: i16 = 100;
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(keys = ?self.keys))]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let Some(fastly) = env.fastly() else {
+            debug!("Skipping surrogate key purge, since Fastly is not configured");
+            return Ok(());
+        };
+
+        info!("Purging surrogate keys on Fastly");
+        fastly.purge_surrogate_keys(&self.keys).await
+    }
+}
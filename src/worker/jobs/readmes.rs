@@ -7,6 +7,8 @@ use crates_io_markdown::text_to_html;
 use crates_io_worker::BackgroundJob;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::AsyncConnection;
+use hex::ToHex;
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -65,21 +67,27 @@ impl BackgroundJob for RenderAndUploadReadme {
             return Ok(());
         }
 
+        // Readmes are stored by their checksum, so that identical readmes of
+        // different versions are only stored once.
+        let checksum: String = Sha256::digest(&rendered).encode_hex();
+
         let mut conn = env.deadpool.get().await?;
         conn.transaction(|conn| {
             async move {
-                Version::record_readme_rendering(job.version_id, conn).await?;
-                let (crate_name, vers): (String, String) = versions::table
+                Version::record_readme_rendering(job.version_id, Some(&checksum), conn).await?;
+                let crate_name: String = versions::table
                     .find(job.version_id)
                     .inner_join(crates::table)
-                    .select((crates::name, versions::num))
+                    .select(crates::name)
                     .first(conn)
                     .await?;
 
                 tracing::Span::current().record("krate.name", tracing::field::display(&crate_name));
 
                 let bytes = rendered.into();
-                env.storage.upload_readme(&crate_name, &vers, bytes).await?;
+                env.storage
+                    .upload_readme(&crate_name, &checksum, bytes)
+                    .await?;
 
                 Ok(())
             }
@@ -0,0 +1,252 @@
+use crate::models::StorageDiscrepancyKind::{
+    MissingCrateFile, MissingIndexFile, OrphanedCrateFile, OrphanedIndexFile,
+};
+use crate::models::{StorageDiscrepancy, StorageDiscrepancyKind, Version};
+use crate::schema::{crates, versions};
+use crate::storage::crate_file_path;
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use crates_io_diesel_helpers::lower;
+use crates_io_index::Repository;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// Versions are only expected to have files in the storage after this time,
+/// since the files of new versions are uploaded after the database
+/// transaction of the publish has been committed.
+const MISSING_FILE_MIN_AGE: TimeDelta = TimeDelta::hours(1);
+
+/// Compares the `.crate` files and sparse index files in the storage with
+/// the database.
+///
+/// Orphaned files, which don't belong to any version or crate anymore, and
+/// missing files of existing versions and crates are recorded in the
+/// `storage_discrepancies` table. They are exported as service metrics, and
+/// listed by the admin console.
+///
+/// Orphaned files are deleted once they have been found for the quarantine
+/// period that is configured by `STORAGE_ORPHAN_QUARANTINE_DAYS`. Missing
+/// files are only reported, since they have to be restored from a backup or
+/// the standby region.
+#[derive(Serialize, Deserialize)]
+pub struct ReconcileStorage;
+
+impl BackgroundJob for ReconcileStorage {
+    const JOB_NAME: &'static str = "reconcile_storage";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let started_at = Utc::now();
+
+        let mut conn = env.deadpool.get().await?;
+
+        // The files of deleted versions are kept in the quarantine instead.
+        info!("Loading versions…");
+        let versions: Vec<(String, String, NaiveDateTime)> = versions::table
+            .inner_join(crates::table)
+            .filter(Version::not_deleted())
+            .select((crates::name, versions::num, versions::created_at))
+            .load(&mut conn)
+            .await?;
+
+        // Listing the storage takes a while, so the connection is returned
+        // to the pool in the meantime.
+        drop(conn);
+
+        info!("Listing crate files…");
+        let crate_files = env.storage.list_crate_files().await?;
+        let expected_crate_files = expected_crate_files(&versions);
+        let (orphaned_crate_files, missing_crate_files) =
+            compare(expected_crate_files, crate_files, started_at);
+
+        info!("Listing index files…");
+        let index_files = env.storage.list_index_files().await?;
+        let expected_index_files = expected_index_files(&versions);
+        let (orphaned_index_files, missing_index_files) =
+            compare(expected_index_files, index_files, started_at);
+
+        let discrepancies = [
+            (OrphanedCrateFile, orphaned_crate_files),
+            (MissingCrateFile, missing_crate_files),
+            (OrphanedIndexFile, orphaned_index_files),
+            (MissingIndexFile, missing_index_files),
+        ];
+
+        let mut conn = env.deadpool.get().await?;
+        for (kind, paths) in &discrepancies {
+            StorageDiscrepancy::record(&mut conn, *kind, paths, started_at).await?;
+        }
+        StorageDiscrepancy::remove_resolved(&mut conn, started_at).await?;
+
+        if discrepancies.iter().all(|(_, paths)| paths.is_empty()) {
+            info!("The storage is consistent with the database");
+        } else {
+            for (kind, paths) in &discrepancies {
+                warn!(
+                    kind = kind.name(),
+                    count = paths.len(),
+                    "Found storage discrepancies"
+                );
+            }
+        }
+
+        let Some(quarantine) = env.config.storage_orphan_quarantine else {
+            return Ok(());
+        };
+
+        let cutoff = started_at - TimeDelta::from_std(quarantine)?;
+        delete_orphans(&env, &mut conn, OrphanedCrateFile, cutoff).await?;
+        delete_orphans(&env, &mut conn, OrphanedIndexFile, cutoff).await?;
+
+        Ok(())
+    }
+}
+
+/// Returns the path of the `.crate` file of every version, together with the
+/// time after which the file is expected to exist.
+fn expected_crate_files(
+    versions: &[(String, String, NaiveDateTime)],
+) -> BTreeMap<String, DateTime<Utc>> {
+    versions
+        .iter()
+        .map(|(name, num, created_at)| {
+            let path = crate_file_path(name, num).to_string();
+            (path, created_at.and_utc() + MISSING_FILE_MIN_AGE)
+        })
+        .collect()
+}
+
+/// Returns the path of the index file of every crate with at least one
+/// version, together with the time after which the file is expected to
+/// exist.
+fn expected_index_files(
+    versions: &[(String, String, NaiveDateTime)],
+) -> BTreeMap<String, DateTime<Utc>> {
+    let mut files = BTreeMap::new();
+    for (name, _, created_at) in versions {
+        let path = Repository::relative_index_file_for_url(name);
+        let expected_at = created_at.and_utc() + MISSING_FILE_MIN_AGE;
+        files
+            .entry(path)
+            .and_modify(|existing: &mut DateTime<Utc>| *existing = expected_at.min(*existing))
+            .or_insert(expected_at);
+    }
+
+    files
+}
+
+/// Compares the expected files with the files in the storage, and returns
+/// the orphaned and the missing files.
+fn compare(
+    mut expected: BTreeMap<String, DateTime<Utc>>,
+    stored: Vec<(String, DateTime<Utc>)>,
+    now: DateTime<Utc>,
+) -> (Vec<String>, Vec<String>) {
+    let mut orphaned = Vec::new();
+    for (path, _) in stored {
+        if expected.remove(&path).is_none() {
+            orphaned.push(path);
+        }
+    }
+    orphaned.sort();
+
+    let missing = expected
+        .into_iter()
+        .filter(|(_, expected_at)| *expected_at < now)
+        .map(|(path, _)| path)
+        .collect();
+
+    (orphaned, missing)
+}
+
+/// Deletes the orphaned files of the given kind that have been found before
+/// `cutoff`.
+async fn delete_orphans(
+    env: &Environment,
+    conn: &mut AsyncPgConnection,
+    kind: StorageDiscrepancyKind,
+    cutoff: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let paths = StorageDiscrepancy::quarantined_orphans(conn, kind, cutoff).await?;
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    info!("Deleting {} files of kind {}…", paths.len(), kind.name());
+
+    // The paths that are not orphaned anymore, either because they have
+    // been deleted, or because they belong to a new version.
+    let mut resolved = Vec::with_capacity(paths.len());
+    for path in paths {
+        if !is_still_orphaned(conn, kind, &path).await? {
+            info!(%path, "Skipping file that belongs to a new version");
+            resolved.push(path);
+            continue;
+        }
+
+        if kind == OrphanedIndexFile {
+            env.storage.delete_index_file_at(&path).await?;
+            env.queue_cloudfront_invalidation(conn, &path)
+                .await
+                .context("Failed to queue CloudFront invalidation")?;
+        } else {
+            env.storage.delete_crate_file_at(&path).await?;
+        }
+
+        resolved.push(path);
+    }
+
+    StorageDiscrepancy::remove(conn, kind, &resolved).await?;
+
+    Ok(())
+}
+
+/// Checks that an orphaned file still doesn't belong to any version, since
+/// a crate with the same name might have been published after the storage
+/// was compared with the database.
+///
+/// Public files of deleted versions are reported as orphaned until they have
+/// been moved to the quarantine, but they are never deleted here, since they
+/// belong to a version that might be restored.
+async fn is_still_orphaned(
+    conn: &mut AsyncPgConnection,
+    kind: StorageDiscrepancyKind,
+    path: &str,
+) -> QueryResult<bool> {
+    // Crate files are stored as `crates/{name}/{name}-{version}.crate`,
+    // while index files are stored as `.../{name}` with a lowercase name.
+    let name = match kind {
+        OrphanedCrateFile => path.split('/').nth(1),
+        _ => path.rsplit('/').next(),
+    };
+    let Some(name) = name else {
+        return Ok(true);
+    };
+
+    let versions: Vec<(String, String)> = versions::table
+        .inner_join(crates::table)
+        .filter(lower(crates::name).eq(name.to_lowercase()))
+        .select((crates::name, versions::num))
+        .load(conn)
+        .await?;
+
+    let paths = match kind {
+        OrphanedCrateFile => versions
+            .iter()
+            .map(|(name, num)| crate_file_path(name, num).to_string())
+            .collect::<HashSet<_>>(),
+        _ => versions
+            .iter()
+            .map(|(name, _)| Repository::relative_index_file_for_url(name))
+            .collect::<HashSet<_>>(),
+    };
+
+    Ok(!paths.contains(path))
+}
@@ -0,0 +1,95 @@
+use crate::github_cache::{self, GitHubRequest};
+use crate::schema::{github_cache as cache, users};
+use crate::worker::Environment;
+use chrono::{TimeDelta, Utc};
+use crates_io_github::GitHubError;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use oauth2::AccessToken;
+use std::sync::Arc;
+
+/// Entries that expire within this duration are refreshed.
+const REFRESH_WINDOW: TimeDelta = TimeDelta::minutes(15);
+
+/// Only entries that were used within this duration are refreshed. The
+/// other ones are fetched again on their next use.
+const REFRESH_IF_USED_WITHIN: TimeDelta = TimeDelta::days(1);
+
+/// Entries that were not used within this duration are deleted.
+const DELETE_IF_UNUSED_FOR: TimeDelta = TimeDelta::days(7);
+
+/// The maximum number of entries that are refreshed by a single job run, to
+/// stay well below the GitHub rate limits.
+const MAX_REFRESHES: i64 = 500;
+
+/// Refreshes the cached GitHub responses that are still in use before they
+/// expire, and deletes the ones that are no longer used.
+#[derive(Serialize, Deserialize)]
+pub struct RefreshGitHubCache;
+
+impl BackgroundJob for RefreshGitHubCache {
+    const JOB_NAME: &'static str = "refresh_github_cache";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let now = Utc::now();
+
+        let num_deleted =
+            diesel::delete(cache::table.filter(cache::last_used_at.lt(now - DELETE_IF_UNUSED_FOR)))
+                .execute(&mut conn)
+                .await?;
+
+        info!("Deleted {num_deleted} unused GitHub cache entries");
+
+        let entries: Vec<(String, i32, String)> = cache::table
+            .inner_join(users::table)
+            .filter(cache::expires_at.lt(now + REFRESH_WINDOW))
+            .filter(cache::last_used_at.gt(now - REFRESH_IF_USED_WITHIN))
+            .order(cache::expires_at)
+            .limit(MAX_REFRESHES)
+            .select((cache::key, users::id, users::gh_access_token))
+            .load(&mut conn)
+            .await?;
+
+        let mut num_refreshed = 0;
+        for (key, user_id, gh_access_token) in entries {
+            let Ok(request) = GitHubRequest::from_key(&key) else {
+                warn!(%key, "Deleting GitHub cache entry with an invalid key");
+                diesel::delete(cache::table.find(&key))
+                    .execute(&mut conn)
+                    .await?;
+                continue;
+            };
+
+            let token = AccessToken::new(gh_access_token);
+            match request.fetch(&*env.github, &token).await {
+                Ok(value) => {
+                    github_cache::store(&mut conn, &request, &value, user_id).await?;
+                    num_refreshed += 1;
+                }
+                Err(GitHubError::Permission(_)) => {
+                    // The token of the user can't be used for this request
+                    // anymore, so the entry will be fetched again on its
+                    // next use.
+                    diesel::delete(cache::table.find(&key))
+                        .execute(&mut conn)
+                        .await?;
+                }
+                Err(error) => {
+                    warn!("Stopping refresh of the GitHub cache after failed request: {error}");
+                    break;
+                }
+            }
+        }
+
+        info!("Refreshed {num_refreshed} GitHub cache entries");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! Replication of crate files and index files to the storage of a standby
+//! region, which can take over the downloads if the primary region is
+//! unavailable.
+//!
+//! New files are replicated by the [`ReplicateCrateFile`] and
+//! [`ReplicateIndexFile`] jobs right after they have been uploaded, while
+//! the [`VerifyReplication`] job periodically compares both storages and
+//! repairs the files that were missed, e.g. because a job failed or files
+//! were deleted.
+
+use crate::worker::Environment;
+use anyhow::Context;
+use crates_io_worker::BackgroundJob;
+use std::sync::Arc;
+
+/// Copies the `.crate` file of a version to the standby region.
+#[derive(Serialize, Deserialize)]
+pub struct ReplicateCrateFile {
+    name: String,
+    version: String,
+}
+
+impl ReplicateCrateFile {
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        let name = name.into();
+        let version = version.into();
+        Self { name, version }
+    }
+}
+
+impl BackgroundJob for ReplicateCrateFile {
+    const JOB_NAME: &'static str = "replicate_crate_file";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(krate.name = self.name, krate.version = self.version))]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let Some(standby) = &env.standby_storage else {
+            warn!("Skipping replication because no standby storage is configured");
+            return Ok(());
+        };
+
+        info!("Replicating crate file to the standby region…");
+        let future = env
+            .storage
+            .replicate_crate_file(standby, &self.name, &self.version);
+        future.await.context("Failed to replicate crate file")?;
+
+        Ok(())
+    }
+}
+
+/// Copies the sparse index file of a crate to the standby region, or deletes
+/// it there if the crate has been deleted.
+#[derive(Serialize, Deserialize)]
+pub struct ReplicateIndexFile {
+    krate: String,
+}
+
+impl ReplicateIndexFile {
+    pub fn new(krate: impl Into<String>) -> Self {
+        let krate = krate.into();
+        Self { krate }
+    }
+}
+
+impl BackgroundJob for ReplicateIndexFile {
+    const JOB_NAME: &'static str = "replicate_index_file";
+    const PRIORITY: i16 = 100;
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(krate.name = self.krate))]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let Some(standby) = &env.standby_storage else {
+            warn!("Skipping replication because no standby storage is configured");
+            return Ok(());
+        };
+
+        info!("Replicating index file to the standby region…");
+        let future = env.storage.replicate_index_file(standby, &self.krate);
+        future.await.context("Failed to replicate index file")?;
+
+        Ok(())
+    }
+}
+
+/// Compares the crate files and index files of the primary storage with the
+/// standby region, and repairs all differences.
+#[derive(Serialize, Deserialize)]
+pub struct VerifyReplication;
+
+impl BackgroundJob for VerifyReplication {
+    const JOB_NAME: &'static str = "verify_replication";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let Some(standby) = &env.standby_storage else {
+            warn!("Skipping verification because no standby storage is configured");
+            return Ok(());
+        };
+
+        info!("Comparing the storage with the standby region…");
+        let diff = env.storage.compare_replica(standby).await?;
+        if diff.is_empty() {
+            info!("The standby region is consistent");
+            return Ok(());
+        }
+
+        warn!(
+            outdated_crate_files = diff.crate_files.outdated.len(),
+            extra_crate_files = diff.crate_files.extra.len(),
+            outdated_index_files = diff.index_files.outdated.len(),
+            extra_index_files = diff.index_files.extra.len(),
+            "Repairing the standby region…"
+        );
+        env.storage.repair_replica(standby, &diff).await?;
+
+        info!("Finished repairing the standby region");
+        Ok(())
+    }
+}
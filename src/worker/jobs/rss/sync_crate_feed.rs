@@ -1,3 +1,4 @@
+use crate::models::Version;
 use crate::schema::{crates, versions};
 use crate::storage::FeedId;
 use crate::worker::Environment;
@@ -78,8 +79,8 @@ impl BackgroundJob for SyncCrateFeed {
         ctx.storage.upload_feed(&feed_id, &channel).await?;
 
         let path = object_store::path::Path::from(&feed_id);
-        if let Err(error) = ctx.invalidate_cdns(path.as_ref()).await {
-            warn!("Failed to invalidate CDN caches: {error}");
+        if let Err(error) = ctx.queue_cdn_invalidation(&mut conn, path.as_ref()).await {
+            warn!("Failed to queue CDN invalidation: {error}");
         }
 
         info!("Finished syncing updates feed");
@@ -101,6 +102,7 @@ async fn load_version_updates(
 
     let updates = versions::table
         .inner_join(crates::table)
+        .filter(Version::not_deleted())
         .filter(crates::name.eq(name))
         .filter(versions::created_at.gt(threshold_dt))
         .order(versions::created_at.desc())
@@ -115,6 +117,7 @@ async fn load_version_updates(
 
     versions::table
         .inner_join(crates::table)
+        .filter(Version::not_deleted())
         .filter(crates::name.eq(name))
         .order(versions::created_at.desc())
         .select(VersionUpdate::as_select())
@@ -236,6 +239,18 @@ mod tests {
         let updates = assert_ok!(load_version_updates("foo", &mut conn).await);
         assert_eq!(updates.len() as i64, NUM_ITEMS + 10);
         assert_debug_snapshot!(updates.iter().map(|u| &u.version).collect::<Vec<_>>());
+
+        // Soft-deleted versions are not included
+        let deleted = create_version(&mut conn, foo, "1.4.0", now).await;
+        diesel::update(versions::table.find(deleted))
+            .set(versions::deleted_at.eq(chrono::Utc::now()))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let updates = assert_ok!(load_version_updates("foo", &mut conn).await);
+        assert_eq!(updates.len() as i64, NUM_ITEMS + 10);
+        assert!(!updates.iter().any(|u| u.version == "1.4.0"));
     }
 
     async fn create_crate(conn: &mut AsyncPgConnection, name: &str) -> i32 {
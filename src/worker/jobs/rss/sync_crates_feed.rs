@@ -65,8 +65,8 @@ impl BackgroundJob for SyncCratesFeed {
         ctx.storage.upload_feed(&feed_id, &channel).await?;
 
         let path = object_store::path::Path::from(&feed_id);
-        if let Err(error) = ctx.invalidate_cdns(path.as_ref()).await {
-            warn!("Failed to invalidate CDN caches: {error}");
+        if let Err(error) = ctx.queue_cdn_invalidation(&mut conn, path.as_ref()).await {
+            warn!("Failed to queue CDN invalidation: {error}");
         }
 
         info!("Finished syncing crates feed");
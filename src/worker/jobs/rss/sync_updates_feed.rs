@@ -1,3 +1,4 @@
+use crate::models::Version;
 use crate::schema::{crates, versions};
 use crate::storage::FeedId;
 use crate::worker::Environment;
@@ -65,8 +66,8 @@ impl BackgroundJob for SyncUpdatesFeed {
         ctx.storage.upload_feed(&feed_id, &channel).await?;
 
         let path = object_store::path::Path::from(&feed_id);
-        if let Err(error) = ctx.invalidate_cdns(path.as_ref()).await {
-            warn!("Failed to invalidate CDN caches: {error}");
+        if let Err(error) = ctx.queue_cdn_invalidation(&mut conn, path.as_ref()).await {
+            warn!("Failed to queue CDN invalidation: {error}");
         }
 
         info!("Finished syncing updates feed");
@@ -85,6 +86,7 @@ async fn load_version_updates(conn: &mut AsyncPgConnection) -> QueryResult<Vec<V
 
     let updates = versions::table
         .inner_join(crates::table)
+        .filter(Version::not_deleted())
         .filter(versions::created_at.gt(threshold_dt))
         .order(versions::created_at.desc())
         .select(VersionUpdate::as_select())
@@ -98,6 +100,7 @@ async fn load_version_updates(conn: &mut AsyncPgConnection) -> QueryResult<Vec<V
 
     versions::table
         .inner_join(crates::table)
+        .filter(Version::not_deleted())
         .order(versions::created_at.desc())
         .select(VersionUpdate::as_select())
         .limit(NUM_ITEMS)
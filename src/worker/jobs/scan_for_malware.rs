@@ -0,0 +1,131 @@
+use crate::malware::{ScanResult, Scanner};
+use crate::models::{Crate, NewCrateReport, ReportCategory};
+use crate::schema::{crates, versions};
+use crate::tasks::spawn_blocking;
+use crate::worker::jobs::SyncCrateFileQuarantine;
+use crate::worker::Environment;
+use crates_io_worker::{BackgroundJob, EnqueueError};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// Background job that scans the `.crate` file of a newly published version
+/// for potentially malicious code.
+///
+/// Suspicious versions are not blocked, instead an automated report is added
+/// to the admin triage queue. New crates with a high enough score are also
+/// quarantined until the crates.io team has reviewed them.
+#[derive(Serialize, Deserialize)]
+pub struct ScanVersionForMalware {
+    version_id: i32,
+}
+
+impl ScanVersionForMalware {
+    pub fn new(version_id: i32) -> Self {
+        Self { version_id }
+    }
+}
+
+impl BackgroundJob for ScanVersionForMalware {
+    const JOB_NAME: &'static str = "scan_version_for_malware";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(version_id = self.version_id), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let version = versions::table
+            .inner_join(crates::table)
+            .filter(versions::id.eq(self.version_id))
+            .select((crates::id, crates::name, versions::num))
+            .first::<(i32, String, String)>(&mut conn)
+            .await
+            .optional()?;
+
+        let Some((crate_id, name, version)) = version else {
+            info!("Skipping malware scan: version has been deleted");
+            return Ok(());
+        };
+
+        info!("Scanning {name}@{version} for malware…");
+
+        // The crate might have been quarantined by another check already.
+        let tarball = match env.storage.download_crate_file(&name, &version).await {
+            Err(object_store::Error::NotFound { .. }) => {
+                env.storage
+                    .download_quarantined_crate_file(&name, &version)
+                    .await?
+            }
+            result => result?,
+        };
+        let result = spawn_blocking(move || Scanner::default().scan_tarball(&tarball)).await??;
+        if result.findings.is_empty() {
+            return Ok(());
+        }
+
+        let score = result.score();
+        info!(score, findings = ?result.findings, "Found suspicious code in {name}@{version}");
+
+        let description = describe(&version, &result);
+        NewCrateReport::builder()
+            .crate_id(crate_id)
+            .category(ReportCategory::Malware)
+            .description(&description)
+            .score(score)
+            .build()
+            .insert(&mut conn)
+            .await?;
+
+        if score >= env.config.malware_quarantine_score {
+            let num_versions: i64 = versions::table
+                .filter(versions::crate_id.eq(crate_id))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+
+            if num_versions == 1 {
+                let reason = format!("automated malware scan of version {version} (score {score})");
+                if quarantine(&mut conn, crate_id, &reason).await? {
+                    info!("Quarantined {name} after malware scan");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Quarantines the crate and moves its `.crate` files out of the public
+/// bucket.
+async fn quarantine(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    reason: &str,
+) -> Result<bool, EnqueueError> {
+    conn.transaction(|conn| {
+        async move {
+            let quarantined = Crate::quarantine(conn, crate_id, reason).await?;
+            if quarantined {
+                SyncCrateFileQuarantine::enqueue_for_crate(conn, crate_id).await?;
+            }
+
+            Ok(quarantined)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+fn describe(version: &str, result: &ScanResult) -> String {
+    let findings = result
+        .findings
+        .iter()
+        .map(|finding| format!("- {finding}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("The automated malware scan of version {version} found suspicious code:\n\n{findings}")
+}
@@ -51,6 +51,7 @@ impl BackgroundJob for SendPublishNotificationsJob {
             .inner_join(users::table)
             .filter(users::publish_notifications.eq(true))
             .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+            .filter(emails::is_primary)
             .filter(emails::verified.eq(true))
             .select((users::gh_login, emails::email))
             .load::<(String, String)>(&mut conn)
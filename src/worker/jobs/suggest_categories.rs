@@ -0,0 +1,251 @@
+use crate::models::Category;
+use crate::schema::{
+    categories, category_suggestions, crates, crates_categories, crates_keywords, keywords,
+};
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The maximum number of categories that are suggested for a crate.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The minimum score of a category to be suggested for a crate.
+const MIN_SCORE: f32 = 3.0;
+
+/// Words that are too common to say anything about the category of a crate.
+const STOP_WORDS: &[&str] = &[
+    "and", "are", "based", "crate", "crates", "for", "from", "into", "library", "other", "rust",
+    "support", "that", "the", "this", "tool", "tools", "use", "using", "with", "your",
+];
+
+/// Background job that suggests categories for a crate without categories,
+/// based on the description and keywords of the crate.
+///
+/// The suggestions replace any previous suggestions for the crate, and are
+/// deleted if the crate has categories by the time the job runs.
+#[derive(Serialize, Deserialize)]
+pub struct SuggestCategories {
+    crate_id: i32,
+}
+
+impl SuggestCategories {
+    pub fn new(crate_id: i32) -> Self {
+        Self { crate_id }
+    }
+}
+
+impl BackgroundJob for SuggestCategories {
+    const JOB_NAME: &'static str = "suggest_categories";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(crate_id = self.crate_id), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let krate = crates::table
+            .find(self.crate_id)
+            .select((crates::name, crates::description))
+            .first::<(String, Option<String>)>(&mut conn)
+            .await
+            .optional()?;
+
+        let Some((name, description)) = krate else {
+            info!("Skipping category suggestions: crate has been deleted");
+            return Ok(());
+        };
+
+        let has_categories = diesel::select(diesel::dsl::exists(
+            crates_categories::table.filter(crates_categories::crate_id.eq(self.crate_id)),
+        ))
+        .get_result::<bool>(&mut conn)
+        .await?;
+
+        let suggestions = if has_categories {
+            info!("Skipping category suggestions: {name} already has categories");
+            Vec::new()
+        } else {
+            let keywords: Vec<String> = crates_keywords::table
+                .inner_join(keywords::table)
+                .filter(crates_keywords::crate_id.eq(self.crate_id))
+                .select(keywords::keyword)
+                .load(&mut conn)
+                .await?;
+
+            let categories: Vec<Category> = categories::table
+                .select(Category::as_select())
+                .load(&mut conn)
+                .await?;
+
+            suggest(
+                &categories,
+                description.as_deref().unwrap_or_default(),
+                &keywords,
+            )
+        };
+
+        info!("Suggesting {} categories for {name}", suggestions.len());
+
+        let rows = suggestions
+            .iter()
+            .map(|(category_id, score)| {
+                (
+                    category_suggestions::crate_id.eq(self.crate_id),
+                    category_suggestions::category_id.eq(category_id),
+                    category_suggestions::score.eq(score),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        conn.transaction(|conn| {
+            async move {
+                diesel::delete(category_suggestions::table)
+                    .filter(category_suggestions::crate_id.eq(self.crate_id))
+                    .execute(conn)
+                    .await?;
+
+                diesel::insert_into(category_suggestions::table)
+                    .values(&rows)
+                    .execute(conn)
+                    .await?;
+
+                Ok::<_, anyhow::Error>(())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
+
+/// Scores the categories by how many of their words appear in the
+/// description and keywords of a crate, and returns the IDs and scores of
+/// the best matching categories, best match first.
+///
+/// Words in the name of a category count three times as much as words in
+/// its description, and keywords of the crate count twice as much as words
+/// in its description.
+fn suggest(categories: &[Category], description: &str, keywords: &[String]) -> Vec<(i32, f32)> {
+    let mut crate_words = HashMap::<String, f32>::new();
+    for word in words(description) {
+        crate_words.entry(word).or_insert(1.0);
+    }
+    for word in keywords.iter().flat_map(|keyword| words(keyword)) {
+        crate_words.insert(word, 2.0);
+    }
+
+    let mut scores = categories
+        .iter()
+        .map(|category| {
+            let mut category_words = HashMap::<String, f32>::new();
+            for word in words(&category.description) {
+                category_words.entry(word).or_insert(1.0);
+            }
+            for word in words(&category.category).chain(words(&category.slug)) {
+                category_words.insert(word, 3.0);
+            }
+
+            let score = category_words
+                .iter()
+                .filter_map(|(word, weight)| Some(weight * crate_words.get(word)?))
+                .sum::<f32>();
+
+            (category.id, score)
+        })
+        .filter(|(_, score)| *score >= MIN_SCORE)
+        .collect::<Vec<_>>();
+
+    scores.sort_by(|(a_id, a), (b_id, b)| b.total_cmp(a).then(a_id.cmp(b_id)));
+    scores.truncate(MAX_SUGGESTIONS);
+    scores
+}
+
+/// Splits the text into lowercase words, ignoring short words and
+/// [`STOP_WORDS`], and strips the plural "s" so that e.g. "parser" and
+/// "parsers" match, but not the "s" of e.g. "process" or "status".
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| word.len() >= 3 && !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| {
+            let is_plural =
+                word.ends_with('s') && !["ss", "us", "is"].iter().any(|s| word.ends_with(s));
+            match word.strip_suffix('s') {
+                Some(stem) if is_plural && stem.len() >= 3 => stem.to_string(),
+                _ => word,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn category(id: i32, category: &str, slug: &str, description: &str) -> Category {
+        Category {
+            id,
+            category: category.into(),
+            slug: slug.into(),
+            description: description.into(),
+            crates_cnt: 0,
+            created_at: NaiveDateTime::default(),
+        }
+    }
+
+    fn categories() -> Vec<Category> {
+        vec![
+            category(
+                1,
+                "Parser implementations",
+                "parser-implementations",
+                "Parsers implemented for particular formats or languages.",
+            ),
+            category(
+                2,
+                "Encoding",
+                "encoding",
+                "Encoding and/or decoding data from one data format to another.",
+            ),
+            category(
+                3,
+                "Command-line utilities",
+                "command-line-utilities",
+                "Applications to run at the command line.",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_words() {
+        let words = words("A parser for TOML files, with serde support").collect::<Vec<_>>();
+        assert_eq!(words, ["parser", "toml", "file", "serde"]);
+
+        let words = words("Access the process status").collect::<Vec<_>>();
+        assert_eq!(words, ["access", "process", "status"]);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let categories = categories();
+
+        let keywords = vec!["parser".to_string(), "toml".to_string()];
+        let suggestions = suggest(&categories, "A TOML parser and encoder", &keywords);
+        assert_eq!(suggestions, [(1, 6.0)]);
+
+        let keywords = vec!["cli".to_string(), "encoding".to_string()];
+        let description = "Command line tool to convert data between formats";
+        let suggestions = suggest(&categories, description, &keywords);
+        assert_eq!(suggestions, [(2, 8.0), (3, 6.0)]);
+    }
+
+    #[test]
+    fn test_suggest_without_matches() {
+        let suggestions = suggest(&categories(), "An unrelated crate", &[]);
+        assert!(suggestions.is_empty());
+    }
+}
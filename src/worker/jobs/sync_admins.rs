@@ -42,7 +42,7 @@ impl BackgroundJob for SyncAdmins {
         // Existing admins from the database.
 
         let database_admins = users::table
-            .left_join(emails::table)
+            .left_join(emails::table.on(emails::user_id.eq(users::id).and(emails::is_primary)))
             .select((users::gh_id, users::gh_login, emails::email.nullable()))
             .filter(users::is_admin.eq(true))
             .get_results::<(i32, String, Option<String>)>(&mut conn)
@@ -0,0 +1,122 @@
+use crate::schema::{crates, versions};
+use crate::storage::{crate_file_path, Storage};
+use crate::worker::Environment;
+use anyhow::Context;
+use crates_io_worker::{BackgroundJob, EnqueueError};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// Moves the `.crate` file of a version to the quarantine if the version has
+/// been soft-deleted or its crate has been quarantined, or back to its public
+/// location if it has been restored or approved.
+///
+/// The job looks up the current state of the version instead of being told
+/// which way to move the file, so that a restore that quickly follows a
+/// deletion can't be overtaken by the older job.
+#[derive(Serialize, Deserialize)]
+pub struct SyncCrateFileQuarantine {
+    version_id: i32,
+}
+
+impl SyncCrateFileQuarantine {
+    pub fn new(version_id: i32) -> Self {
+        Self { version_id }
+    }
+
+    /// Enqueues a job for every version of a crate, after the crate has been
+    /// quarantined or approved.
+    pub async fn enqueue_for_crate(
+        conn: &mut AsyncPgConnection,
+        crate_id: i32,
+    ) -> Result<(), EnqueueError> {
+        let version_ids: Vec<i32> = versions::table
+            .filter(versions::crate_id.eq(crate_id))
+            .select(versions::id)
+            .load(conn)
+            .await?;
+
+        for version_id in version_ids {
+            Self::new(version_id).enqueue(conn).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl BackgroundJob for SyncCrateFileQuarantine {
+    const JOB_NAME: &'static str = "sync_crate_file_quarantine";
+    const PRIORITY: i16 = 100;
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(version_id = self.version_id), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let version = versions::table
+            .inner_join(crates::table)
+            .filter(versions::id.eq(self.version_id))
+            .select((
+                crates::name,
+                versions::num,
+                versions::deleted_at.is_not_null(),
+                crates::quarantined_at.is_not_null(),
+            ))
+            .first::<(String, String, bool, bool)>(&mut conn)
+            .await
+            .optional()?;
+
+        drop(conn);
+
+        let Some((name, num, deleted, quarantined)) = version else {
+            warn!("Skipping crate file: version has been deleted from the database");
+            return Ok(());
+        };
+
+        let hidden = deleted || quarantined;
+        if hidden {
+            info!("Moving crate file of {name}@{num} to the quarantine…");
+        } else {
+            info!("Restoring crate file of {name}@{num} from the quarantine…");
+        }
+
+        sync_quarantine(&env.storage, &name, &num, hidden)
+            .await
+            .context("Failed to move crate file")?;
+
+        if let Some(standby) = &env.standby_storage {
+            // Files that haven't been replicated yet are repaired by the
+            // `VerifyReplication` job instead.
+            match sync_quarantine(standby, &name, &num, hidden).await {
+                Err(object_store::Error::NotFound { .. }) => {
+                    warn!("Crate file of {name}@{num} not found in the standby region");
+                }
+                result => result.context("Failed to move crate file in the standby region")?,
+            }
+        }
+
+        // The public file is cached by the CDNs for a long time, so hidden
+        // versions have to be invalidated right away.
+        let path = crate_file_path(&name, &num).to_string();
+        env.invalidate_cdns(&path)
+            .await
+            .context("Failed to invalidate CDN caches")?;
+
+        Ok(())
+    }
+}
+
+async fn sync_quarantine(
+    storage: &Storage,
+    name: &str,
+    num: &str,
+    hidden: bool,
+) -> object_store::Result<()> {
+    if hidden {
+        storage.quarantine_crate_file(name, num).await
+    } else {
+        storage.restore_crate_file(name, num).await
+    }
+}
@@ -1,11 +1,16 @@
 use std::sync::Arc;
 
-use crates_io_worker::BackgroundJob;
-use diesel_async::AsyncPgConnection;
+use crates_io_worker::{BackgroundJob, EnqueueError};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use typomania::Package;
 
 use crate::email::Email;
+use crate::models::{self, NewCrateReport, ReportCategory};
+use crate::schema::crates;
 use crate::typosquat::{Cache, Crate};
+use crate::worker::jobs::SyncCrateFileQuarantine;
 use crate::worker::Environment;
 use crate::Emails;
 
@@ -35,7 +40,15 @@ impl BackgroundJob for CheckTyposquat {
         let mut conn = env.deadpool.get().await?;
 
         let cache = env.typosquat_cache(&mut conn).await?;
-        check(&env.emails, cache, &mut conn, &crate_name).await
+        let quarantine_matches = env.config.typosquat_quarantine_matches;
+        check(
+            &env.emails,
+            cache,
+            &mut conn,
+            &crate_name,
+            quarantine_matches,
+        )
+        .await
     }
 }
 
@@ -44,6 +57,7 @@ async fn check(
     cache: &Cache,
     conn: &mut AsyncPgConnection,
     name: &str,
+    quarantine_matches: usize,
 ) -> anyhow::Result<()> {
     if let Some(harness) = cache.get_harness() {
         info!(name, "Checking new crate for potential typosquatting");
@@ -70,12 +84,70 @@ async fn check(
                     );
                 }
             }
+
+            if squats.len() >= quarantine_matches {
+                quarantine(conn, name, &squats).await?;
+            }
         }
     }
 
     Ok(())
 }
 
+/// Quarantines the new crate and adds a report to the admin triage queue, so
+/// that the crates.io team can review it.
+async fn quarantine(
+    conn: &mut AsyncPgConnection,
+    name: &str,
+    squats: &[typomania::checks::Squat],
+) -> anyhow::Result<()> {
+    let crate_id: i32 = crates::table
+        .filter(crates::name.eq(name))
+        .select(crates::id)
+        .get_result(conn)
+        .await?;
+
+    let reason = format!("name matches {} typosquatting checks", squats.len());
+    let quarantined = conn
+        .transaction(|conn| {
+            async move {
+                let quarantined = models::Crate::quarantine(conn, crate_id, &reason).await?;
+                if quarantined {
+                    SyncCrateFileQuarantine::enqueue_for_crate(conn, crate_id).await?;
+                }
+
+                Ok::<_, EnqueueError>(quarantined)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    if !quarantined {
+        return Ok(());
+    }
+
+    info!(name, "Quarantined possible typosquatting crate");
+
+    let squats = squats
+        .iter()
+        .map(|squat| format!("- {squat}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let description =
+        format!("The crate was quarantined because it may be typosquatting:\n\n{squats}");
+
+    NewCrateReport::builder()
+        .crate_id(crate_id)
+        .category(ReportCategory::Typosquat)
+        .description(&description)
+        .build()
+        .insert(conn)
+        .await?;
+
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 struct PossibleTyposquatEmail<'a> {
     domain: &'a str,
@@ -120,6 +192,7 @@ Specific squat checks that triggered:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::crate_reports;
     use crate::typosquat::test_util::faker;
     use crates_io_test_db::TestDatabase;
     use lettre::Address;
@@ -159,16 +232,31 @@ mod tests {
         .await?;
 
         // Run the check with a crate that shouldn't cause problems.
-        check(&emails, &cache, &mut async_conn, &angel.name).await?;
+        check(&emails, &cache, &mut async_conn, &angel.name, 1).await?;
         assert!(emails.mails_in_memory().await.unwrap().is_empty());
 
         // Now run the check with a less innocent crate.
-        check(&emails, &cache, &mut async_conn, &demon.name).await?;
+        check(&emails, &cache, &mut async_conn, &demon.name, 1).await?;
         let sent_mail = emails.mails_in_memory().await.unwrap();
         assert!(!sent_mail.is_empty());
         let sent = sent_mail.into_iter().next().unwrap();
         assert_eq!(&sent.0.to(), &["admin@example.com".parse::<Address>()?]);
 
+        // Only the problematic crate has been quarantined.
+        let quarantined: Vec<String> = crates::table
+            .filter(crates::quarantined_at.is_not_null())
+            .select(crates::name)
+            .load(&mut async_conn)
+            .await?;
+        assert_eq!(quarantined, vec!["mycrate"]);
+
+        let reports: i64 = crate_reports::table
+            .filter(crate_reports::category.eq(ReportCategory::Typosquat))
+            .count()
+            .get_result(&mut async_conn)
+            .await?;
+        assert_eq!(reports, 1);
+
         Ok(())
     }
 }
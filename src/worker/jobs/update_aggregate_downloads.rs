@@ -0,0 +1,215 @@
+use crate::schema::{category_downloads, keyword_downloads};
+use crate::worker::Environment;
+use chrono::{NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel::sql_types::Date;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// Aggregates the downloads of a single day per keyword and per category and
+/// saves them in the `keyword_downloads` and `category_downloads` tables,
+/// which are used by the `/api/v1/keywords/{keyword}/downloads` and
+/// `/api/v1/categories/{category}/downloads` endpoints.
+///
+/// This job is supposed to run once per night, but it can also be used to
+/// backfill the downloads of previous days.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateAggregateDownloads {
+    date: NaiveDate,
+}
+
+impl UpdateAggregateDownloads {
+    pub fn for_date(date: NaiveDate) -> Self {
+        Self { date }
+    }
+}
+
+impl Default for UpdateAggregateDownloads {
+    /// Aggregates the downloads of yesterday, which is the most recent day
+    /// with complete download counts.
+    fn default() -> Self {
+        Self::for_date(Utc::now().date_naive() - chrono::Duration::days(1))
+    }
+}
+
+impl BackgroundJob for UpdateAggregateDownloads {
+    const JOB_NAME: &'static str = "update_aggregate_downloads";
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(date = %self.date), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Aggregating downloads per keyword and category…");
+        update(&mut conn, self.date).await?;
+        info!("Finished aggregating downloads per keyword and category");
+
+        Ok(())
+    }
+}
+
+async fn update(conn: &mut AsyncPgConnection, date: NaiveDate) -> QueryResult<()> {
+    // The existing aggregates of the day are replaced, so that keywords and
+    // categories that have been removed from all crates in the meantime don't
+    // keep their previous downloads.
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(keyword_downloads::table)
+                .filter(keyword_downloads::date.eq(date))
+                .execute(conn)
+                .await?;
+
+            diesel::sql_query(include_str!("update_keyword_downloads.sql"))
+                .bind::<Date, _>(date)
+                .execute(conn)
+                .await?;
+
+            diesel::delete(category_downloads::table)
+                .filter(category_downloads::date.eq(date))
+                .execute(conn)
+                .await?;
+
+            diesel::sql_query(include_str!("update_category_downloads.sql"))
+                .bind::<Date, _>(date)
+                .execute(conn)
+                .await?;
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AggregateDownload, Category, Keyword};
+    use crate::schema::{categories, crates, version_downloads, versions};
+    use crates_io_test_db::TestDatabase;
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%F").unwrap()
+    }
+
+    async fn insert_crate(conn: &mut AsyncPgConnection, name: &str) -> (i32, i32) {
+        let crate_id = diesel::insert_into(crates::table)
+            .values(crates::name.eq(name))
+            .returning(crates::id)
+            .get_result::<i32>(conn)
+            .await
+            .unwrap();
+
+        let version_id = diesel::insert_into(versions::table)
+            .values((
+                versions::crate_id.eq(crate_id),
+                versions::num.eq("1.0.0"),
+                versions::num_no_build.eq("1.0.0"),
+                versions::checksum.eq(""),
+                versions::crate_size.eq(0),
+            ))
+            .returning(versions::id)
+            .get_result::<i32>(conn)
+            .await
+            .unwrap();
+
+        (crate_id, version_id)
+    }
+
+    async fn insert_downloads(
+        conn: &mut AsyncPgConnection,
+        version_id: i32,
+        day: &str,
+        downloads: i32,
+    ) {
+        diesel::insert_into(version_downloads::table)
+            .values((
+                version_downloads::version_id.eq(version_id),
+                version_downloads::date.eq(date(day)),
+                version_downloads::downloads.eq(downloads),
+            ))
+            .execute(conn)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let (foo, foo_version) = insert_crate(&mut conn, "foo").await;
+        let (bar, bar_version) = insert_crate(&mut conn, "bar").await;
+
+        insert_downloads(&mut conn, foo_version, "2024-01-01", 10).await;
+        insert_downloads(&mut conn, foo_version, "2024-01-02", 20).await;
+        insert_downloads(&mut conn, bar_version, "2024-01-02", 5).await;
+
+        Keyword::update_crate(&mut conn, foo, &["cli", "async"])
+            .await
+            .unwrap();
+        Keyword::update_crate(&mut conn, bar, &["async"])
+            .await
+            .unwrap();
+
+        diesel::insert_into(categories::table)
+            .values((
+                categories::category.eq("Parsing"),
+                categories::slug.eq("parsing"),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        Category::update_crate(&mut conn, bar, &["parsing"])
+            .await
+            .unwrap();
+
+        update(&mut conn, date("2024-01-01")).await.unwrap();
+        update(&mut conn, date("2024-01-02")).await.unwrap();
+
+        let cli = Keyword::find_by_keyword(&mut conn, "cli").await.unwrap();
+        let downloads = AggregateDownload::for_keyword(&mut conn, cli.id, date("2024-01-01"))
+            .await
+            .unwrap();
+        assert_eq!(
+            downloads,
+            vec![
+                AggregateDownload {
+                    date: date("2024-01-01"),
+                    downloads: 10
+                },
+                AggregateDownload {
+                    date: date("2024-01-02"),
+                    downloads: 20
+                },
+            ]
+        );
+
+        let parsing: Category = Category::by_slug("parsing").first(&mut conn).await.unwrap();
+        let downloads = AggregateDownload::for_category(&mut conn, parsing.id, date("2024-01-01"))
+            .await
+            .unwrap();
+        assert_eq!(
+            downloads,
+            vec![AggregateDownload {
+                date: date("2024-01-02"),
+                downloads: 5
+            }]
+        );
+
+        // Keywords that have been removed from all crates lose their
+        // downloads when the day is aggregated again.
+        Keyword::update_crate(&mut conn, foo, &["async"])
+            .await
+            .unwrap();
+        update(&mut conn, date("2024-01-02")).await.unwrap();
+
+        let downloads = AggregateDownload::for_keyword(&mut conn, cli.id, date("2024-01-02"))
+            .await
+            .unwrap();
+        assert_eq!(downloads, vec![]);
+    }
+}
@@ -0,0 +1,9 @@
+-- Sum up the downloads of the day of all crates per category. Crates are only
+-- counted for the categories they are directly in, not for their parents.
+INSERT INTO category_downloads (category_id, date, downloads)
+SELECT crates_categories.category_id, version_downloads.date, SUM(version_downloads.downloads)
+FROM version_downloads
+INNER JOIN versions ON versions.id = version_downloads.version_id
+INNER JOIN crates_categories ON crates_categories.crate_id = versions.crate_id
+WHERE version_downloads.date = $1
+GROUP BY crates_categories.category_id, version_downloads.date
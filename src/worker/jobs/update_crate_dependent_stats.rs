@@ -0,0 +1,53 @@
+use crate::schema::crate_dependent_stats;
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// Counts the dependent crates of every crate and saves them in the
+/// `crate_dependent_stats` table, which is used by the
+/// `/api/v1/crates/most_depended` endpoint.
+///
+/// The weighted count sums up the recent downloads of the dependent crates,
+/// so this job should run after the recent downloads have been updated.
+#[derive(Serialize, Deserialize)]
+pub struct UpdateCrateDependentStats;
+
+impl BackgroundJob for UpdateCrateDependentStats {
+    const JOB_NAME: &'static str = "update_crate_dependent_stats";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Updating crate dependent stats…");
+        update(&mut conn).await?;
+        info!("Finished updating crate dependent stats");
+
+        Ok(())
+    }
+}
+
+async fn update(conn: &mut AsyncPgConnection) -> QueryResult<()> {
+    // The whole table is replaced, so that crates which lost all of their
+    // dependents in the meantime don't keep their previous counts.
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(crate_dependent_stats::table)
+                .execute(conn)
+                .await?;
+
+            diesel::sql_query(include_str!("update_crate_dependent_stats.sql"))
+                .execute(conn)
+                .await?;
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+}
@@ -0,0 +1,20 @@
+WITH dependents AS (
+    -- Get the distinct dependent crates of each crate. Like on the reverse
+    -- dependencies page, only the default versions of the dependent crates
+    -- are considered, and yanked crates are filtered out (if the default
+    -- version is yanked, then the whole crate is yanked).
+    SELECT DISTINCT dependencies.crate_id, default_versions.crate_id AS dependent_id
+    FROM dependencies
+    INNER JOIN default_versions ON default_versions.version_id = dependencies.version_id
+    INNER JOIN versions ON versions.id = default_versions.version_id
+    WHERE NOT versions.yanked
+        AND default_versions.crate_id != dependencies.crate_id
+)
+INSERT INTO crate_dependent_stats (crate_id, dependents, weighted_dependents)
+SELECT
+    dependents.crate_id,
+    COUNT(*),
+    COALESCE(SUM(recent_crate_downloads.downloads), 0)
+FROM dependents
+LEFT JOIN recent_crate_downloads ON recent_crate_downloads.crate_id = dependents.dependent_id
+GROUP BY dependents.crate_id
@@ -0,0 +1,40 @@
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::sql_types::Integer;
+use diesel_async::RunQueryDsl;
+use std::sync::Arc;
+
+/// The number of days in which a crate needs a release to count as having
+/// a recent release.
+const RECENT_RELEASE_DAYS: i32 = 180;
+
+/// The number of days in which a crate needs a release to count as
+/// maintained.
+const MAINTAINED_DAYS: i32 = 2 * 365;
+
+/// Calculates the quality signals and the composite score of every crate
+/// and saves them in the `crate_scores` table, which is used by the
+/// `/api/v1/crates/{name}/score` endpoint.
+#[derive(Serialize, Deserialize)]
+pub struct UpdateCrateScores;
+
+impl BackgroundJob for UpdateCrateScores {
+    const JOB_NAME: &'static str = "update_crate_scores";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Updating crate scores…");
+        let count = diesel::sql_query(include_str!("update_crate_scores.sql"))
+            .bind::<Integer, _>(RECENT_RELEASE_DAYS)
+            .bind::<Integer, _>(MAINTAINED_DAYS)
+            .execute(&mut conn)
+            .await?;
+        info!("Updated the scores of {count} crates");
+
+        Ok(())
+    }
+}
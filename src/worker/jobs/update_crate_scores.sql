@@ -0,0 +1,60 @@
+WITH version_stats AS (
+    SELECT
+        crate_id,
+        COUNT(*) AS total,
+        COUNT(*) FILTER (WHERE yanked) AS yanked,
+        MAX(created_at) FILTER (WHERE NOT yanked) AS last_release
+    FROM versions
+    GROUP BY crate_id
+), signals AS (
+    SELECT
+        crates.id AS crate_id,
+        COALESCE(default_version.docs_rs_status = 0, FALSE)
+            OR crates.documentation IS NOT NULL AS has_documentation,
+        crates.repository IS NOT NULL AS has_repository,
+        COALESCE(crates.readme, '') != '' AS has_readme,
+        COALESCE(version_stats.last_release >= NOW() - make_interval(days => $1), FALSE) AS recent_release,
+        COALESCE(version_stats.last_release >= NOW() - make_interval(days => $2), FALSE) AS maintained,
+        version_stats.yanked::float8 / version_stats.total AS yanked_ratio
+    FROM crates
+    INNER JOIN default_versions ON default_versions.crate_id = crates.id
+    INNER JOIN versions AS default_version ON default_version.id = default_versions.version_id
+    INNER JOIN version_stats ON version_stats.crate_id = crates.id
+)
+INSERT INTO crate_scores (
+    crate_id,
+    has_documentation,
+    has_repository,
+    has_readme,
+    recent_release,
+    maintained,
+    yanked_ratio,
+    score
+)
+SELECT
+    crate_id,
+    has_documentation,
+    has_repository,
+    has_readme,
+    recent_release,
+    maintained,
+    yanked_ratio,
+    -- The average of all signals, where a lower yanked ratio is better.
+    (
+        has_documentation::int
+        + has_repository::int
+        + has_readme::int
+        + recent_release::int
+        + maintained::int
+        + (1 - yanked_ratio)
+    ) / 6
+FROM signals
+ON CONFLICT (crate_id) DO UPDATE SET
+    has_documentation = EXCLUDED.has_documentation,
+    has_repository = EXCLUDED.has_repository,
+    has_readme = EXCLUDED.has_readme,
+    recent_release = EXCLUDED.recent_release,
+    maintained = EXCLUDED.maintained,
+    yanked_ratio = EXCLUDED.yanked_ratio,
+    score = EXCLUDED.score,
+    updated_at = NOW()
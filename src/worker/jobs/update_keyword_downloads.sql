@@ -0,0 +1,8 @@
+-- Sum up the downloads of the day of all crates per keyword.
+INSERT INTO keyword_downloads (keyword_id, date, downloads)
+SELECT crates_keywords.keyword_id, version_downloads.date, SUM(version_downloads.downloads)
+FROM version_downloads
+INNER JOIN versions ON versions.id = version_downloads.version_id
+INNER JOIN crates_keywords ON crates_keywords.crate_id = versions.crate_id
+WHERE version_downloads.date = $1
+GROUP BY crates_keywords.keyword_id, version_downloads.date
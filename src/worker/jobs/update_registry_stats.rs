@@ -0,0 +1,91 @@
+use crate::models::RegistryStats;
+use crate::schema::{crates, version_downloads, versions};
+use crate::worker::Environment;
+use chrono::{NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::sum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// Calculates the statistics of the whole registry for a single day and
+/// saves them in the `registry_stats` table, which is used by the
+/// `/api/v1/stats` endpoint.
+///
+/// This job is supposed to run once per night, but it can also be used to
+/// backfill the statistics of previous days.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateRegistryStats {
+    date: NaiveDate,
+}
+
+impl UpdateRegistryStats {
+    pub fn for_date(date: NaiveDate) -> Self {
+        Self { date }
+    }
+}
+
+impl Default for UpdateRegistryStats {
+    /// Updates the statistics of yesterday, which is the most recent day
+    /// with complete download counts.
+    fn default() -> Self {
+        Self::for_date(Utc::now().date_naive() - chrono::Duration::days(1))
+    }
+}
+
+impl BackgroundJob for UpdateRegistryStats {
+    const JOB_NAME: &'static str = "update_registry_stats";
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(date = %self.date), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Updating registry statistics…");
+        let stats = calculate(&mut conn, self.date).await?;
+        stats.upsert(&mut conn).await?;
+        info!(?stats, "Registry statistics updated");
+
+        Ok(())
+    }
+}
+
+async fn calculate(conn: &mut AsyncPgConnection, date: NaiveDate) -> QueryResult<RegistryStats> {
+    let start = date.and_time(Default::default());
+    let end = start + chrono::Duration::days(1);
+
+    let total_crates = crates::table
+        .filter(crates::created_at.lt(end))
+        .count()
+        .get_result(conn)
+        .await?;
+
+    let total_versions = versions::table
+        .filter(versions::created_at.lt(end))
+        .count()
+        .get_result(conn)
+        .await?;
+
+    let publishes = versions::table
+        .filter(versions::created_at.ge(start))
+        .filter(versions::created_at.lt(end))
+        .count()
+        .get_result(conn)
+        .await?;
+
+    let downloads = version_downloads::table
+        .filter(version_downloads::date.eq(date))
+        .select(sum(version_downloads::downloads))
+        .get_result::<Option<i64>>(conn)
+        .await?
+        .unwrap_or_default();
+
+    Ok(RegistryStats {
+        date,
+        total_crates,
+        total_versions,
+        publishes,
+        downloads,
+    })
+}
@@ -0,0 +1,178 @@
+use crate::models::TrendingWindow;
+use crate::schema::trending_crates;
+use crate::worker::Environment;
+use chrono::{NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Date, Integer};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// The number of crates that are ranked per window.
+const MAX_TRENDING_CRATES: i64 = 100;
+
+/// The minimum number of downloads per day that a crate needs in the previous
+/// window to be ranked, to exclude crates whose growth is just noise.
+const MIN_BASELINE_DOWNLOADS_PER_DAY: i64 = 100;
+
+/// Ranks the crates by the growth of their downloads for each
+/// [`TrendingWindow`] and saves the rankings in the `trending_crates` table,
+/// which is used by the `/api/v1/crates/trending` endpoint.
+///
+/// Only complete days are compared, so this job is supposed to run once per
+/// night.
+#[derive(Serialize, Deserialize)]
+pub struct UpdateTrendingCrates;
+
+impl BackgroundJob for UpdateTrendingCrates {
+    const JOB_NAME: &'static str = "update_trending_crates";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let today = Utc::now().date_naive();
+        for window in TrendingWindow::ALL {
+            info!("Updating trending crates of the {window} window…");
+            update(&mut conn, window, today).await?;
+        }
+
+        info!("Finished updating trending crates");
+
+        Ok(())
+    }
+}
+
+/// Replaces the ranking of the given window with the growth of the windows
+/// that end before `end`.
+async fn update(
+    conn: &mut AsyncPgConnection,
+    window: TrendingWindow,
+    end: NaiveDate,
+) -> QueryResult<()> {
+    let days = window.days();
+    let min_baseline = MIN_BASELINE_DOWNLOADS_PER_DAY * i64::from(days);
+
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(trending_crates::table)
+                .filter(trending_crates::window_days.eq(days))
+                .execute(conn)
+                .await?;
+
+            diesel::sql_query(include_str!("update_trending_crates.sql"))
+                .bind::<Integer, _>(days)
+                .bind::<Date, _>(end)
+                .bind::<BigInt, _>(min_baseline)
+                .bind::<BigInt, _>(MAX_TRENDING_CRATES)
+                .execute(conn)
+                .await?;
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{crates, version_downloads, versions};
+    use chrono::Days;
+    use crates_io_test_db::TestDatabase;
+
+    async fn insert_crate(
+        conn: &mut AsyncPgConnection,
+        name: &str,
+        downloads: &[(NaiveDate, i32)],
+    ) -> i32 {
+        let crate_id = diesel::insert_into(crates::table)
+            .values(crates::name.eq(name))
+            .returning(crates::id)
+            .get_result::<i32>(conn)
+            .await
+            .unwrap();
+
+        let version_id = diesel::insert_into(versions::table)
+            .values((
+                versions::crate_id.eq(crate_id),
+                versions::num.eq("1.0.0"),
+                versions::num_no_build.eq("1.0.0"),
+                versions::checksum.eq(""),
+                versions::crate_size.eq(0),
+            ))
+            .returning(versions::id)
+            .get_result::<i32>(conn)
+            .await
+            .unwrap();
+
+        for (date, downloads) in downloads {
+            diesel::insert_into(version_downloads::table)
+                .values((
+                    version_downloads::version_id.eq(version_id),
+                    version_downloads::date.eq(date),
+                    version_downloads::downloads.eq(downloads),
+                ))
+                .execute(conn)
+                .await
+                .unwrap();
+        }
+
+        crate_id
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let end = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let current = end - Days::new(1);
+        let previous = end - Days::new(8);
+
+        // Doubled its downloads.
+        let foo = insert_crate(&mut conn, "foo", &[(previous, 1000), (current, 2000)]).await;
+        // Tripled its downloads.
+        let bar = insert_crate(&mut conn, "bar", &[(previous, 1000), (current, 3000)]).await;
+        // Grew a lot, but from a baseline that is too small.
+        insert_crate(&mut conn, "baz", &[(previous, 10), (current, 5000)]).await;
+        // Lost downloads.
+        insert_crate(&mut conn, "qux", &[(previous, 5000), (current, 1000)]).await;
+        // Grew, but only today, which is not complete yet.
+        insert_crate(&mut conn, "quux", &[(previous, 1000), (end, 5000)]).await;
+
+        update(&mut conn, TrendingWindow::Week, end).await.unwrap();
+
+        let ranking: Vec<(i32, i32, i64, i64, f64)> = trending_crates::table
+            .filter(trending_crates::window_days.eq(7))
+            .select((
+                trending_crates::rank,
+                trending_crates::crate_id,
+                trending_crates::downloads,
+                trending_crates::previous_downloads,
+                trending_crates::growth,
+            ))
+            .order(trending_crates::rank)
+            .load(&mut conn)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ranking,
+            vec![(1, bar, 3000, 1000, 2.0), (2, foo, 2000, 1000, 1.0)]
+        );
+
+        // Running the job again replaces the previous ranking.
+        update(&mut conn, TrendingWindow::Week, end).await.unwrap();
+        let count: i64 = trending_crates::table
+            .count()
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,36 @@
+WITH window_downloads AS (
+    -- Sum up the downloads of each crate in the current window, which ends
+    -- before `$2`, and in the previous window of the same length.
+    SELECT
+        versions.crate_id,
+        COALESCE(SUM(version_downloads.downloads) FILTER (WHERE version_downloads.date >= $2 - $1), 0) AS downloads,
+        COALESCE(SUM(version_downloads.downloads) FILTER (WHERE version_downloads.date < $2 - $1), 0) AS previous_downloads
+    FROM version_downloads
+    INNER JOIN versions ON versions.id = version_downloads.version_id
+    WHERE version_downloads.date >= $2 - 2 * $1
+        AND version_downloads.date < $2
+    GROUP BY versions.crate_id
+), growing_crates AS (
+    -- Only consider crates with enough downloads in the previous window, so
+    -- that e.g. a crate going from 1 to 10 downloads isn't ranked first.
+    SELECT
+        window_downloads.*,
+        (downloads - previous_downloads)::double precision / previous_downloads AS growth
+    FROM window_downloads
+    INNER JOIN crates ON crates.id = window_downloads.crate_id
+    WHERE previous_downloads >= $3
+        AND downloads > previous_downloads
+        AND crates.hidden_at IS NULL
+        AND crates.quarantined_at IS NULL
+)
+INSERT INTO trending_crates (window_days, crate_id, rank, downloads, previous_downloads, growth)
+SELECT
+    $1,
+    crate_id,
+    ROW_NUMBER() OVER (ORDER BY growth DESC, downloads DESC, crate_id),
+    downloads,
+    previous_downloads,
+    growth
+FROM growing_crates
+ORDER BY growth DESC, downloads DESC, crate_id
+LIMIT $4
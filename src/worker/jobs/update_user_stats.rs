@@ -0,0 +1,60 @@
+use crate::schema::{user_stats, users};
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+
+/// The number of co-owners that are listed per user.
+const MAX_CO_OWNERS: i64 = 10;
+
+/// Computes the contribution stats of the users that opted in to public
+/// stats and saves them in the `user_stats` table, which is used by the
+/// `/api/v1/users/{user}` endpoint.
+///
+/// The stats of users that opted out again are deleted. The stats only
+/// change slowly, so this job is supposed to run once per night.
+#[derive(Serialize, Deserialize)]
+pub struct UpdateUserStats;
+
+impl BackgroundJob for UpdateUserStats {
+    const JOB_NAME: &'static str = "update_user_stats";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Updating user stats…");
+        let updated = update(&mut conn).await?;
+        info!("Finished updating the stats of {updated} users");
+
+        Ok(())
+    }
+}
+
+/// Replaces the stats of all opted-in users, and returns their number.
+async fn update(conn: &mut AsyncPgConnection) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        async move {
+            let opted_out = users::table
+                .filter(users::public_stats.eq(false))
+                .select(users::id);
+
+            diesel::delete(user_stats::table)
+                .filter(user_stats::user_id.eq_any(opted_out))
+                .execute(conn)
+                .await?;
+
+            diesel::sql_query(include_str!("update_user_stats.sql"))
+                .bind::<BigInt, _>(MAX_CO_OWNERS)
+                .execute(conn)
+                .await
+        }
+        .scope_boxed()
+    })
+    .await
+}
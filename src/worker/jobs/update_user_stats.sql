@@ -0,0 +1,74 @@
+WITH opted_in AS (
+    SELECT id FROM users WHERE public_stats
+), owned_crates AS (
+    SELECT crate_owners.owner_id AS user_id, crate_owners.crate_id
+    FROM crate_owners
+    INNER JOIN opted_in ON opted_in.id = crate_owners.owner_id
+    WHERE crate_owners.owner_kind = 0
+        AND NOT crate_owners.deleted
+), downloads AS (
+    SELECT owned_crates.user_id, SUM(crate_downloads.downloads)::bigint AS total_downloads
+    FROM owned_crates
+    INNER JOIN crate_downloads ON crate_downloads.crate_id = owned_crates.crate_id
+    GROUP BY owned_crates.user_id
+), yearly_publishes AS (
+    SELECT
+        versions.published_by AS user_id,
+        EXTRACT(YEAR FROM versions.created_at)::integer AS year,
+        COUNT(*) AS count
+    FROM versions
+    INNER JOIN opted_in ON opted_in.id = versions.published_by
+    GROUP BY versions.published_by, year
+), publishes AS (
+    SELECT
+        user_id,
+        jsonb_agg(jsonb_build_object('year', year, 'count', count) ORDER BY year) AS publishes_per_year
+    FROM yearly_publishes
+    GROUP BY user_id
+), shared_crates AS (
+    -- Count the crates that each opted-in user shares with each other user
+    -- owner, and rank the co-owners of each user by that number.
+    SELECT
+        owned_crates.user_id,
+        crate_owners.owner_id AS co_owner_id,
+        COUNT(*) AS shared_crates,
+        ROW_NUMBER() OVER (
+            PARTITION BY owned_crates.user_id
+            ORDER BY COUNT(*) DESC, crate_owners.owner_id
+        ) AS rank
+    FROM owned_crates
+    INNER JOIN crate_owners ON crate_owners.crate_id = owned_crates.crate_id
+    WHERE crate_owners.owner_kind = 0
+        AND NOT crate_owners.deleted
+        AND crate_owners.owner_id <> owned_crates.user_id
+    GROUP BY owned_crates.user_id, crate_owners.owner_id
+), co_owners AS (
+    SELECT
+        shared_crates.user_id,
+        jsonb_agg(jsonb_build_object(
+            'id', users.id,
+            'login', users.gh_login,
+            'avatar', users.gh_avatar,
+            'shared_crates', shared_crates.shared_crates
+        ) ORDER BY shared_crates.rank) AS co_owners
+    FROM shared_crates
+    INNER JOIN users ON users.id = shared_crates.co_owner_id
+    WHERE shared_crates.rank <= $1
+    GROUP BY shared_crates.user_id
+)
+INSERT INTO user_stats (user_id, total_downloads, publishes_per_year, co_owners, updated_at)
+SELECT
+    opted_in.id,
+    COALESCE(downloads.total_downloads, 0),
+    COALESCE(publishes.publishes_per_year, '[]'::jsonb),
+    COALESCE(co_owners.co_owners, '[]'::jsonb),
+    now()
+FROM opted_in
+LEFT JOIN downloads ON downloads.user_id = opted_in.id
+LEFT JOIN publishes ON publishes.user_id = opted_in.id
+LEFT JOIN co_owners ON co_owners.user_id = opted_in.id
+ON CONFLICT (user_id) DO UPDATE SET
+    total_downloads = excluded.total_downloads,
+    publishes_per_year = excluded.publishes_per_year,
+    co_owners = excluded.co_owners,
+    updated_at = excluded.updated_at
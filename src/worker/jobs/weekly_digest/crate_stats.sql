@@ -0,0 +1,41 @@
+WITH owned_crates AS (
+    SELECT crate_id
+    FROM crate_owners
+    WHERE owner_id = $1
+      AND owner_kind = 0
+      AND NOT deleted
+      -- Skip the crates that the owners excluded from the digest
+      AND crate_id NOT IN (SELECT crate_id FROM crate_settings WHERE NOT digest_emails)
+), downloads AS (
+    SELECT versions.crate_id,
+           SUM(version_downloads.downloads) FILTER (WHERE version_downloads.date >= $2 - 7) AS this_week,
+           SUM(version_downloads.downloads) FILTER (WHERE version_downloads.date < $2 - 7) AS last_week
+    FROM version_downloads
+    INNER JOIN versions ON versions.id = version_downloads.version_id
+    WHERE versions.crate_id IN (SELECT crate_id FROM owned_crates)
+      AND version_downloads.date >= $2 - 14
+      AND version_downloads.date < $2
+    GROUP BY versions.crate_id
+), dependents AS (
+    -- The first time that each crate published a version depending on one of the owned crates
+    SELECT dependencies.crate_id, MIN(versions.created_at) AS first_published_at
+    FROM dependencies
+    INNER JOIN versions ON versions.id = dependencies.version_id
+    WHERE dependencies.crate_id IN (SELECT crate_id FROM owned_crates)
+    GROUP BY dependencies.crate_id, versions.crate_id
+), new_reverse_dependencies AS (
+    SELECT crate_id, COUNT(*) AS count
+    FROM dependents
+    WHERE first_published_at >= $2 - 7
+      AND first_published_at < $2
+    GROUP BY crate_id
+)
+SELECT crates.name,
+       COALESCE(downloads.this_week, 0) AS downloads_this_week,
+       COALESCE(downloads.last_week, 0) AS downloads_last_week,
+       COALESCE(new_reverse_dependencies.count, 0) AS new_reverse_dependencies
+FROM owned_crates
+INNER JOIN crates ON crates.id = owned_crates.crate_id
+LEFT JOIN downloads ON downloads.crate_id = owned_crates.crate_id
+LEFT JOIN new_reverse_dependencies ON new_reverse_dependencies.crate_id = owned_crates.crate_id
+ORDER BY downloads_this_week DESC, crates.name;
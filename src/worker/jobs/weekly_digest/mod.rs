@@ -0,0 +1,204 @@
+use crate::email::Email;
+use crate::schema::{crate_owner_invitations, crates, emails, users};
+use crate::worker::Environment;
+use chrono::{Datelike, NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel::sql_types::{Date, Integer};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use minijinja::{context, Environment as Templates};
+use std::sync::Arc;
+
+/// The maximum number of crates that are listed in a single digest.
+const MAX_CRATES: usize = 25;
+
+/// Background job that sends a weekly email to all users that opted in to
+/// the digest, summarizing the activity of the crates they own.
+///
+/// The digest covers the seven days before the day the job runs, and each
+/// user receives at most one digest per calendar week, even if the job is
+/// retried or enqueued more than once.
+#[derive(Default, Serialize, Deserialize, Debug)]
+pub struct SendWeeklyDigests;
+
+impl BackgroundJob for SendWeeklyDigests {
+    const JOB_NAME: &'static str = "send_weekly_digests";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip(env), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+        let today = Utc::now().date_naive();
+
+        let recipients = users::table
+            .filter(users::weekly_digest.eq(true))
+            .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+            .filter(emails::is_primary)
+            .filter(emails::verified.eq(true))
+            .select((users::id, users::gh_login, emails::email))
+            .load::<(i32, String, String)>(&mut conn)
+            .await?;
+
+        info!("Sending weekly digests to {} users…", recipients.len());
+
+        let mut num_sent = 0;
+        for (user_id, user_name, email_address) in recipients {
+            let digest = Digest::load(&mut conn, &env, user_id, today).await?;
+            if digest.is_empty() {
+                debug!("Skipping weekly digest for user {user_id}: nothing to report");
+                continue;
+            }
+
+            let body = digest.render(&user_name, &env.config.domain_name, today)?;
+            let email = WeeklyDigestEmail { body };
+
+            let week = today.iso_week();
+            let key = format!("weekly-digest:{user_id}:{}-{}", week.year(), week.week());
+
+            let result = env
+                .emails
+                .send_idempotent(&mut conn, &key, &email_address, email)
+                .await;
+
+            match result {
+                Ok(()) => num_sent += 1,
+                Err(error) => warn!("Failed to send weekly digest to {email_address}: {error}"),
+            }
+        }
+
+        info!("Sent {num_sent} weekly digests");
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, QueryableByName, Serialize)]
+struct CrateStats {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    downloads_this_week: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    downloads_last_week: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    new_reverse_dependencies: i64,
+}
+
+#[derive(Debug)]
+struct Digest {
+    crates: Vec<CrateStats>,
+    /// Names of the crates that the user has been invited to become an
+    /// owner of, but hasn't accepted yet.
+    invitations: Vec<String>,
+}
+
+impl Digest {
+    async fn load(
+        conn: &mut AsyncPgConnection,
+        env: &Environment,
+        user_id: i32,
+        today: NaiveDate,
+    ) -> QueryResult<Self> {
+        let crates = diesel::sql_query(include_str!("crate_stats.sql"))
+            .bind::<Integer, _>(user_id)
+            .bind::<Date, _>(today)
+            .load(conn)
+            .await?;
+
+        let expiration_days = env.config.ownership_invitations_expiration_days as i64;
+        let expired_before = Utc::now().naive_utc() - chrono::Duration::days(expiration_days);
+
+        let invitations = crate_owner_invitations::table
+            .inner_join(crates::table)
+            .filter(crate_owner_invitations::invited_user_id.eq(user_id))
+            .filter(crate_owner_invitations::created_at.gt(expired_before))
+            .select(crates::name)
+            .order(crates::name)
+            .load(conn)
+            .await?;
+
+        Ok(Self {
+            crates,
+            invitations,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.crates.is_empty() && self.invitations.is_empty()
+    }
+
+    fn render(&self, user_name: &str, domain: &str, today: NaiveDate) -> anyhow::Result<String> {
+        let crates = &self.crates[..self.crates.len().min(MAX_CRATES)];
+        let more_crates = self.crates.len() - crates.len();
+
+        let context = context! {
+            user_name => user_name,
+            domain => domain,
+            date => today.to_string(),
+            crates => crates,
+            more_crates => more_crates,
+            invitations => &self.invitations,
+        };
+
+        let template = include_str!("digest.txt.j2");
+        Ok(Templates::new().render_str(template, context)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WeeklyDigestEmail {
+    body: String,
+}
+
+impl Email for WeeklyDigestEmail {
+    fn subject(&self) -> String {
+        "crates.io: Your weekly crate digest".into()
+    }
+
+    fn body(&self) -> String {
+        self.body.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    fn stats(name: &str, this_week: i64, last_week: i64, new_rev_deps: i64) -> CrateStats {
+        CrateStats {
+            name: name.to_string(),
+            downloads_this_week: this_week,
+            downloads_last_week: last_week,
+            new_reverse_dependencies: new_rev_deps,
+        }
+    }
+
+    #[test]
+    fn render_digest() {
+        let digest = Digest {
+            crates: vec![stats("foo", 120, 100, 1), stats("bar", 0, 3, 2)],
+            invitations: vec!["baz".to_string()],
+        };
+
+        let today = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let body = digest.render("foo", "crates.io", today).unwrap();
+        assert_snapshot!(body);
+    }
+
+    #[test]
+    fn render_digest_with_many_crates() {
+        let crates = (0..MAX_CRATES + 2).map(|i| stats(&format!("foo{i}"), 1, 1, 0));
+        let digest = Digest {
+            crates: crates.collect(),
+            invitations: vec![],
+        };
+
+        let today = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let body = digest.render("foo", "crates.io", today).unwrap();
+        assert!(body.contains("- foo24: 1 downloads (+0)\n- and 2 more crates\n"));
+        assert!(!body.contains("pending invitations"));
+    }
+}
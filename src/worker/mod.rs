@@ -20,24 +20,56 @@ pub trait RunnerExt {
 impl RunnerExt for Runner<Arc<Environment>> {
     fn register_crates_io_job_types(self) -> Self {
         self.register_job_type::<jobs::ArchiveVersionDownloads>()
+            .register_job_type::<jobs::CheckRecentCrateDownloads>()
             .register_job_type::<jobs::CheckTyposquat>()
             .register_job_type::<jobs::CleanProcessedLogFiles>()
             .register_job_type::<jobs::DailyDbMaintenance>()
             .register_job_type::<jobs::DeleteCrateFromStorage>()
+            .register_job_type::<jobs::DeleteExpiredIdempotencyKeys>()
             .register_job_type::<jobs::DumpDb>()
+            .register_job_type::<jobs::ExportAccountData>()
+            .register_job_type::<jobs::ExportAnalytics>()
+            .register_job_type::<jobs::ExportMetadataSnapshot>()
+            .register_job_type::<jobs::BackfillPublishMethods>()
+            .register_job_type::<jobs::BackfillRustVersions>()
+            .register_job_type::<jobs::BackfillSemverOrd>()
+            .register_job_type::<jobs::ComputeVersionDiff>()
+            .register_job_type::<jobs::GenerateSitemaps>()
             .register_job_type::<jobs::IndexVersionDownloadsArchive>()
             .register_job_type::<jobs::NormalizeIndex>()
+            .register_job_type::<jobs::PartitionVersionDownloads>()
+            .register_job_type::<jobs::ProcessCdnInvalidationQueue>()
             .register_job_type::<jobs::ProcessCdnLog>()
             .register_job_type::<jobs::ProcessCdnLogQueue>()
+            .register_job_type::<jobs::PublishCrateFile>()
+            .register_job_type::<jobs::PurgeSurrogateKeys>()
+            .register_job_type::<jobs::ReconcileStagedCrateFiles>()
+            .register_job_type::<jobs::ReconcileStorage>()
+            .register_job_type::<jobs::RefreshGitHubCache>()
             .register_job_type::<jobs::RenderAndUploadReadme>()
+            .register_job_type::<jobs::ReplicateCrateFile>()
+            .register_job_type::<jobs::ReplicateIndexFile>()
+            .register_job_type::<jobs::ScanVersionForMalware>()
             .register_job_type::<jobs::SquashIndex>()
+            .register_job_type::<jobs::SuggestCategories>()
             .register_job_type::<jobs::SyncAdmins>()
+            .register_job_type::<jobs::SyncCrateFileQuarantine>()
             .register_job_type::<jobs::SyncToGitIndex>()
             .register_job_type::<jobs::SyncToSparseIndex>()
+            .register_job_type::<jobs::UpdateAggregateDownloads>()
+            .register_job_type::<jobs::UpdateCrateDependentStats>()
+            .register_job_type::<jobs::UpdateCrateScores>()
             .register_job_type::<jobs::UpdateDownloads>()
+            .register_job_type::<jobs::UpdateRecentCrateDownloads>()
             .register_job_type::<jobs::UpdateDefaultVersion>()
+            .register_job_type::<jobs::UpdateRegistryStats>()
+            .register_job_type::<jobs::UpdateTrendingCrates>()
+            .register_job_type::<jobs::UpdateUserStats>()
+            .register_job_type::<jobs::VerifyReplication>()
             .register_job_type::<jobs::SendTokenExpiryNotifications>()
             .register_job_type::<jobs::SendPublishNotificationsJob>()
+            .register_job_type::<jobs::SendFollowNotifications>()
+            .register_job_type::<jobs::SendWeeklyDigests>()
             .register_job_type::<jobs::rss::SyncCrateFeed>()
             .register_job_type::<jobs::rss::SyncCratesFeed>()
             .register_job_type::<jobs::rss::SyncUpdatesFeed>()